@@ -24,6 +24,7 @@ use egui_tiles::{Container, Tile};
 use rfd::AsyncFileDialog;
 
 use gemi_core::cartridge::Cartridge;
+use gemi_core::gameboy::ResetKind;
 use gemi_core::ppu::graphic_data::TileMap;
 
 use crate::behaviour::TreeBehaviour;
@@ -226,6 +227,9 @@ impl eframe::App for EmulatorApplication {
 
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // keep the debugging session of the current ROM
+        self.get_state_mut().store_session();
+
         eframe::set_value(storage, eframe::APP_KEY, &self);
     }
 }
@@ -269,6 +273,20 @@ impl EmulatorApplication {
     }
 
 
+    /// Resets the current emulator instance and notifies the views.
+    pub fn reset(&mut self, kind: ResetKind) {
+        let state = self.behaviour.get_state_mut();
+        state.reset(kind);
+
+        visit_tiles(
+            &mut self.tree,
+            |tile| {
+                tile.on_emulator_reset(state);
+            }
+        );
+    }
+
+
     /// Handle the menu bar at the top of the window.
     fn update_menu_bar(&mut self, ctx: &Context, frame: &mut Frame) {
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
@@ -296,6 +314,13 @@ impl EmulatorApplication {
             self.open_file_dialog();
         }
 
+        // "Clear Session" button to remove all debugging artifacts of the current ROM
+        let is_emulator_loaded = self.get_state().emu.is_emulator_loaded();
+        if ui.add_enabled(is_emulator_loaded, egui::Button::new("Clear Session")).clicked() {
+            ui.close_menu();
+            self.get_state_mut().clear_session();
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             // "Quit" button to close the application
@@ -378,6 +403,7 @@ impl EmulatorApplication {
         let state = self.get_state_mut();
         let mut is_running = state.is_running();
         let mut is_paused  = state.ui.is_paused();
+        let mut reset      = false;
 
         // "Reload" button
         ui.add_enabled_ui(state.emu.is_emulator_loaded(), |ui| {
@@ -385,6 +411,10 @@ impl EmulatorApplication {
                 _ = state.reload();
             }
 
+            if ui.button(BUTTON_LABEL_RESET).clicked() {
+                reset = true;
+            }
+
             ui.separator();
         });
 
@@ -460,6 +490,11 @@ impl EmulatorApplication {
                 state.ui.set_device_type(all_types[selected_index]);
             }
         }
+
+        // reset after the toolbar is complete, so the views can be notified
+        if reset {
+            self.reset(ResetKind::Soft);
+        }
     }
 
 
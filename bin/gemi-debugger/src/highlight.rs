@@ -172,6 +172,41 @@ impl HighlightTesting {
                 }
             }
 
+            // oam entries highlighted by a selected object palette
+            (Selected::OamEntry(oam_index), Selected::Palette(true, palette_index)) => {
+                let oam   = gb.get_peripherals().ppu.get_oam();
+                let entry = &oam[*oam_index];
+
+                let entry_palette = if gb.get_config().is_gbc_enabled() {
+                    entry.get_color_palette()
+                }
+                else {
+                    entry.get_dmg_palette()
+                };
+
+                if entry_palette == *palette_index {
+                    return true;
+                }
+            }
+
+            // tiles highlighted by a selected background palette, only on GBC,
+            // since on DMG each tile uses the same palette
+            (Selected::Tile(tilemap_bit, tile_index), Selected::Palette(false, palette_index)) => {
+                if gb.get_config().is_gbc_enabled() {
+                    let ppu     = &gb.get_peripherals().ppu;
+                    let vram1   = ppu.get_vram(1);
+                    let tilemap = TileMap::by_select_bit(*tilemap_bit);
+
+                    let tilemap_field_address     = tilemap.base_address() as usize + tile_index;
+                    let tilemap_field_vram_offset = tilemap_field_address - MEMORY_LOCATION_VRAM_BEGIN as usize;
+                    let tile_attributes           = vram1[tilemap_field_vram_offset];
+
+                    if (tile_attributes & 0x07) == *palette_index {
+                        return true;
+                    }
+                }
+            }
+
             _ => { }
         }
 
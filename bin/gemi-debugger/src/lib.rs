@@ -20,6 +20,7 @@ pub mod behaviour;
 pub mod event;
 pub mod highlight;
 pub mod selection;
+pub mod session;
 pub mod state;
 pub mod strings;
 pub mod ui;
@@ -28,6 +28,7 @@ mod app;
 mod behaviour;
 mod event;
 mod selection;
+mod session;
 mod state;
 mod strings;
 mod ui;
@@ -39,6 +39,12 @@ pub enum Selected {
     /// The selection is on a disassembled instruction,
     /// referred by its address range.
     Instruction(Range<u16>),
+
+    /// The selection is on a color palette.
+    /// The parameters contain a flag whether the palette is an object palette
+    /// and the index of the palette, which is 0-1 for OBP0/OBP1 on DMG
+    /// or 0-7 for color palettes on GBC.
+    Palette(bool, u8),
 }
 
 
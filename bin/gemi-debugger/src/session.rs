@@ -0,0 +1,130 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use gemi_core::cartridge::Cartridge;
+
+use crate::state::FrozenMemoryValue;
+
+
+/// The current version of the session format.
+/// Sessions stored with a different version will be discarded when being restored.
+pub const SESSION_VERSION: u32 = 1;
+
+
+/// Stores all debugging artifacts created for a single ROM, so they can be restored
+/// when the same ROM is opened again.
+/// Any field may be missing when deserialized from an older session.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DebugSession {
+    /// The version of the format this session was stored with.
+    #[serde(default)]
+    pub version: u32,
+
+    /// The path of the ROM file this session belongs to, if known.
+    #[serde(default)]
+    pub rom_file: Option<PathBuf>,
+
+    /// Memory values frozen by the user.
+    #[serde(default)]
+    pub frozen_memory: Vec<FrozenMemoryValue>,
+
+    /// Breakpoints set by the user.
+    #[serde(default)]
+    pub breakpoints: Vec<SessionBreakpoint>,
+
+    /// The path of the symbol file loaded for this ROM, if any.
+    #[serde(default)]
+    pub symbol_file: Option<PathBuf>,
+}
+
+
+/// A breakpoint stored within a [DebugSession].
+/// One-shot breakpoints only live for a single run and will not be stored.
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone)]
+pub struct SessionBreakpoint {
+    /// The address of the instruction to stop at.
+    pub address: u16,
+
+    /// Whether the breakpoint is enabled.
+    pub enabled: bool,
+}
+
+
+/// A collection of debugging sessions, each of them identified by the ROM they belong to.
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct DebugSessions {
+    /// All sessions stored by their session key.
+    sessions: BTreeMap<String, DebugSession>,
+}
+
+
+impl DebugSession {
+    /// Checks whether this session is empty and does not need to be stored.
+    pub fn is_empty(&self) -> bool {
+            self.frozen_memory.is_empty()
+        &&  self.breakpoints.is_empty()
+        &&  self.symbol_file.is_none()
+    }
+}
+
+
+impl DebugSessions {
+    /// Get the key to identify the session of a particular cartridge.
+    /// The key is based on the cartridge's title and checksums of its header.
+    pub fn get_session_key(cartridge: &Cartridge) -> String {
+        format!(
+            "{}-{:02x}-{:04x}",
+            cartridge.get_title(),
+            cartridge.get_header_checksum(),
+            cartridge.get_global_checksum()
+        )
+    }
+
+
+    /// Stores a session for a given cartridge, replacing any previous session.
+    /// Empty sessions will not be stored, but remove any existing session instead.
+    pub fn store(&mut self, cartridge: &Cartridge, session: DebugSession) {
+        let key = Self::get_session_key(cartridge);
+
+        if session.is_empty() {
+            self.sessions.remove(&key);
+        }
+        else {
+            self.sessions.insert(key, session);
+        }
+    }
+
+
+    /// Takes the session of a given cartridge, if any.
+    /// Sessions stored with an incompatible version will be discarded.
+    pub fn take(&mut self, cartridge: &Cartridge) -> Option<DebugSession> {
+        let key = Self::get_session_key(cartridge);
+
+        self.sessions
+            .remove(&key)
+            .filter(|session| session.version == SESSION_VERSION)
+    }
+
+
+    /// Removes the session of a given cartridge, if any.
+    pub fn remove(&mut self, cartridge: &Cartridge) {
+        let key = Self::get_session_key(cartridge);
+        self.sessions.remove(&key);
+    }
+}
@@ -16,8 +16,8 @@
  */
 
 use gemi_core::cartridge::Cartridge;
-use gemi_core::debug::DebugEvent;
-use gemi_core::gameboy::{Clock, DeviceType, EmulatorUpdateResults, GameBoy};
+use gemi_core::debug::{DebugEvent, DebugEvents};
+use gemi_core::gameboy::{Clock, DeviceType, EmulatorUpdateResults, GameBoy, ResetKind};
 use gemi_core::input::InputButton;
 use gemi_core::mmu::memory_data::MemoryData;
 use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
@@ -27,6 +27,7 @@ use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 
 use crate::selection::{Kind, Selection};
+use crate::session::{DebugSession, DebugSessions, SessionBreakpoint, SESSION_VERSION};
 
 /// An enum to store the device type to be emulated
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -78,6 +79,33 @@ pub enum UpdateStepMode {
 
 
 
+/// The size of a value stored in memory.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MemoryValueSize {
+    /// A single byte value.
+    U8,
+
+    /// A 16 bit value stored as little endian.
+    U16,
+}
+
+
+/// A value in memory, which will be rewritten after each frame.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone)]
+pub struct FrozenMemoryValue {
+    /// The address of the value.
+    pub address: u16,
+
+    /// The value to be written.
+    pub value: u16,
+
+    /// The size of the value.
+    pub size: MemoryValueSize,
+}
+
+
 /// An object handling the current state of the emulator.
 /// This provides functionality to load ROMs and serialize the emulator state.
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -85,6 +113,10 @@ pub struct EmulatorState {
     /// Stores the last file path opened.
     pub last_rom_file: Option<PathBuf>,
 
+    /// The path of the symbol file loaded for the current ROM, if any.
+    #[serde(default)]
+    pub symbol_file: Option<PathBuf>,
+
     /// The actual state of the emulator, which also
     /// contains the emulator instance itself.
     pub emu: EmulatorInstance,
@@ -92,6 +124,10 @@ pub struct EmulatorState {
     /// Various states of the UI, which contains the state of buttons
     /// or any active selection.
     pub ui: UiStates,
+
+    /// Debugging sessions of each ROM opened before.
+    #[serde(default)]
+    pub sessions: DebugSessions,
 }
 
 
@@ -103,6 +139,10 @@ pub struct EmulatorInstance {
     /// Will be [None] if no ROM is loaded.
     #[serde(deserialize_with = "deserialize_emulator_instance")]
     gb: Option<GameBoy>,
+
+    /// A list of memory values to be rewritten after each frame.
+    #[serde(default)]
+    frozen_memory: Vec<FrozenMemoryValue>,
 }
 
 
@@ -241,6 +281,9 @@ impl EmulatorState {
     /// Takes an existing Cartridge object and load it into the emulator.
     /// If there's already a running instance of the emulator, this will be closed.
     pub fn load_cartridge(&mut self, cartridge: Cartridge) -> Result<(), String> {
+        // keep the session of the previous program
+        self.store_session();
+
         // no path known
         self.last_rom_file = None;
 
@@ -260,10 +303,24 @@ impl EmulatorState {
                 .map_err(|e| e.to_string())?
         ;
 
+        // keep the session to be restored for the new instance
+        self.store_session();
+
         self.instantiate_emulator_with_cartridge(new_cartridge)
     }
 
 
+    /// Resets the current emulator instance, if any.
+    /// Pending one-shot breakpoints like 'run to address' belong to the previous run
+    /// and will be removed.
+    pub fn reset(&mut self, kind: ResetKind) {
+        if let Some(emu) = self.emu.get_emulator_mut() {
+            emu.reset(kind);
+            emu.get_breakpoints_mut().clear_one_shots();
+        }
+    }
+
+
     /// Internal function to create a new emulator instance with an existing cartridge
     /// without changing any other configuration.
     fn instantiate_emulator_with_cartridge(&mut self, cartridge: Cartridge) -> Result<(), String> {
@@ -282,11 +339,83 @@ impl EmulatorState {
         // store the new emulator instance
         self.emu.gb = Some(gb);
 
+        // frozen memory values and symbols are bound to the previous program
+        self.emu.frozen_memory.clear();
+        self.symbol_file = None;
+
+        // restore the session of the new program, if it was opened before
+        self.restore_session();
+
         // success!
         Ok(())
     }
 
 
+    /// Stores the debugging session of the currently loaded ROM,
+    /// so it can be restored when the same ROM is opened again.
+    pub fn store_session(&mut self) {
+        if let Some(emu) = self.emu.get_emulator() {
+            // one-shot breakpoints only belong to the current run
+            let breakpoints = emu.get_breakpoints()
+                    .iter()
+                    .filter(|breakpoint| !breakpoint.one_shot)
+                    .map(|breakpoint| SessionBreakpoint {
+                        address: breakpoint.address,
+                        enabled: breakpoint.enabled,
+                    })
+                    .collect()
+            ;
+
+            let session = DebugSession {
+                version:        SESSION_VERSION,
+                rom_file:       self.last_rom_file.clone(),
+                frozen_memory:  self.emu.frozen_memory.clone(),
+                breakpoints,
+                symbol_file:    self.symbol_file.clone(),
+            };
+
+            if let Some(cartridge) = emu.get_peripherals().mem.get_cartridge() {
+                self.sessions.store(cartridge, session);
+            }
+        }
+    }
+
+
+    /// Restores the debugging session of the currently loaded ROM, if any.
+    fn restore_session(&mut self) {
+        if let Some(cartridge) = self.emu.get_cartridge() {
+            if let Some(session) = self.sessions.take(cartridge) {
+                self.emu.frozen_memory = session.frozen_memory;
+                self.symbol_file       = session.symbol_file;
+
+                if let Some(emu) = self.emu.get_emulator_mut() {
+                    let breakpoints = emu.get_breakpoints_mut();
+
+                    for breakpoint in session.breakpoints {
+                        breakpoints.add(breakpoint.address);
+                        breakpoints.set_enabled(breakpoint.address, breakpoint.enabled);
+                    }
+                }
+            }
+        }
+    }
+
+
+    /// Removes all debugging artifacts of the currently loaded ROM.
+    pub fn clear_session(&mut self) {
+        if let Some(cartridge) = self.emu.get_cartridge() {
+            self.sessions.remove(cartridge);
+        }
+
+        if let Some(emu) = self.emu.get_emulator_mut() {
+            emu.get_breakpoints_mut().clear();
+        }
+
+        self.emu.frozen_memory.clear();
+        self.symbol_file = None;
+    }
+
+
     /// Checks whether the emulator is currently running or not.
     /// This will be the case if the emulator is loaded and not paused.
     pub fn is_running(&self) -> bool {
@@ -302,10 +431,17 @@ impl EmulatorState {
                 UpdateMode::Paused
             }
 
-            // process the next frame and stay in continuous mode
+            // process the next frame and stay in continuous mode,
+            // unless a breakpoint was reached
             UpdateMode::Continuous => {
-                self.emu.run_frame();
-                UpdateMode::Continuous
+                let events = self.emu.run_frame();
+
+                if events.contains(DebugEvent::BreakpointReached) {
+                    UpdateMode::Paused
+                }
+                else {
+                    UpdateMode::Continuous
+                }
             }
 
             // process the next step and switch into pause mode
@@ -314,10 +450,40 @@ impl EmulatorState {
                     UpdateStepMode::Frame       => self.emu.run_frame(),
                     UpdateStepMode::Line        => self.emu.run_line(),
                     UpdateStepMode::Instruction => self.emu.run_single_step(),
-                }
+                };
 
                 UpdateMode::Paused
             }
+        };
+
+        // apply memory values which are frozen by the user
+        self.emu.apply_frozen_memory();
+    }
+
+
+    /// Continues running the emulator until the CPU reaches the given address.
+    /// This sets a one-shot breakpoint on the address, which will pause
+    /// the emulator when being reached.
+    pub fn run_to_address(&mut self, address: u16) {
+        if let Some(emu) = self.emu.get_emulator_mut() {
+            emu.get_breakpoints_mut().add_one_shot(address);
+            self.ui.set_update_mode(UpdateMode::Continuous);
+        }
+    }
+
+
+    /// Adds a breakpoint on the given address or removes an existing one.
+    pub fn toggle_breakpoint(&mut self, address: u16) {
+        if let Some(emu) = self.emu.get_emulator_mut() {
+            let breakpoints = emu.get_breakpoints_mut();
+            let exists      = breakpoints.get(address).is_some_and(|breakpoint| !breakpoint.one_shot);
+
+            if exists {
+                breakpoints.remove(address);
+            }
+            else {
+                breakpoints.add(address);
+            }
         }
     }
 
@@ -358,47 +524,96 @@ impl EmulatorInstance {
 
 
     /// Process a single frame of the emulator, if any.
-    pub fn run_frame(&mut self) {
+    /// Returns all events occurred while running the emulator.
+    pub fn run_frame(&mut self) -> DebugEvents {
         self.run_until(|_emu, cycles, result|
                 result.events.contains(DebugEvent::PpuFrameCompleted)
             ||  cycles >= CPU_CYCLES_PER_FRAME
-        );
+        )
     }
 
 
     /// Run the emulator until the next scanline was completed drawing.
-    pub fn run_line(&mut self) {
+    /// Returns all events occurred while running the emulator.
+    pub fn run_line(&mut self) -> DebugEvents {
         self.run_until(|_emu, cycles, result|
                 result.events.contains(DebugEvent::PpuLineCompleted)
             ||  cycles >= CPU_CYCLES_PER_FRAME
-        );
+        )
     }
 
 
     /// Run the emulator for a single instruction.
-    pub fn run_single_step(&mut self) {
-        if let Some(emu) = self.get_emulator_mut() {
-            emu.run_single_step();
+    /// Returns all events occurred while running the emulator.
+    pub fn run_single_step(&mut self) -> DebugEvents {
+        match self.get_emulator_mut() {
+            Some(emu) => emu.run_single_step().events,
+            None      => DebugEvents::default(),
+        }
+    }
+
+
+    /// Freezes a value in memory, which will be rewritten after each frame.
+    /// Any value frozen on the same address before will be replaced.
+    pub fn freeze_memory(&mut self, frozen: FrozenMemoryValue) {
+        self.unfreeze_memory(frozen.address);
+        self.frozen_memory.push(frozen);
+    }
+
+
+    /// Removes a frozen memory value from the given address.
+    pub fn unfreeze_memory(&mut self, address: u16) {
+        self.frozen_memory.retain(|frozen| frozen.address != address);
+    }
+
+
+    /// Get the frozen memory value on a given address, if any.
+    pub fn get_frozen_memory(&self, address: u16) -> Option<&FrozenMemoryValue> {
+        self.frozen_memory.iter().find(|frozen| frozen.address == address)
+    }
+
+
+    /// Rewrites all frozen memory values into the emulator's memory.
+    pub fn apply_frozen_memory(&mut self) {
+        if let Some(gb) = &mut self.gb {
+            let mmu = gb.get_mmu_mut();
+
+            for frozen in &self.frozen_memory {
+                match frozen.size {
+                    MemoryValueSize::U8  => mmu.write_u8(frozen.address, frozen.value as u8),
+                    MemoryValueSize::U16 => mmu.write_u16(frozen.address, frozen.value),
+                }
+            }
         }
     }
 
 
-    /// Run the emulator until a certain condition is met. 
-    pub fn run_until<F>(&mut self, condition: F)
+    /// Run the emulator until a certain condition is met or a breakpoint was reached.
+    /// Returns all events occurred while running the emulator.
+    pub fn run_until<F>(&mut self, condition: F) -> DebugEvents
         where F: Fn(&GameBoy, Clock, EmulatorUpdateResults) -> bool
     {
+        let mut events = DebugEvents::default();
+
         if let Some(emu) = self.get_emulator_mut() {
             let mut cycles = 0;
 
             loop {
                 let result = emu.run_single_step();
                 cycles += result.cycles;
+                events |= result.events;
+
+                if events.contains(DebugEvent::BreakpointReached) {
+                    break;
+                }
 
                 if condition(emu, cycles, result) {
                     break;
                 }
             }
         }
+
+        events
     }
 }
 
@@ -473,11 +688,15 @@ impl Default for EmulatorState {
     fn default() -> Self {
         Self {
             last_rom_file: None,
+            symbol_file: None,
 
             emu: EmulatorInstance {
                 gb: None,
+                frozen_memory: Vec::new(),
             },
 
+            sessions: DebugSessions::default(),
+
             ui: UiStates {
                 key_bindings:       make_default_key_bindings(),
                 device_type:        EmulatorDevice::GameBoyColor,
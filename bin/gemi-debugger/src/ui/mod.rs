@@ -19,5 +19,6 @@ pub mod data_list;
 pub mod memory_editor;
 pub mod sprite_cache;
 pub mod style;
+pub mod tile_images;
 pub mod draw_tile;
 pub mod utils;
@@ -125,6 +125,14 @@ impl GemiStyle {
         italic: false,
     };
 
+    /// Text formatting to display the names of symbols, like labels in the disassembly.
+    pub const SYMBOL: TextFormatting = TextFormatting {
+        style: TextStyle::Monospace,
+        color: Some(Color32::from_rgb(0xff, 0xc6, 0x6d)),
+        bold: true,
+        italic: false,
+    };
+
     /// Text formatting to display keywords.
     pub const KEYWORD: TextFormatting = TextFormatting {
         style: TextStyle::Monospace,
@@ -0,0 +1,108 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{Clock, GameBoy};
+use gemi_core::ppu::ppu::Ppu;
+use gemi_core::ppu::sprite_image::SpriteImage;
+use gemi_core::ppu::video_memory::VRAM_TILES_PER_BANK;
+
+/// The number of VRAM banks available on any device.
+const VRAM_BANKS : usize = 2;
+
+
+/// Caches the images of tiles stored in VRAM, so they don't need to be
+/// read from VRAM each frame. Each image is stored together with the
+/// generation of the tile when it was read, so only tiles modified
+/// since then will be read again.
+#[derive(Default)]
+pub struct TileImageCache {
+    banks: [Vec<Option<TileImageCacheEntry>>; VRAM_BANKS],
+
+    /// The total number of cycles processed by the emulator when the cache
+    /// was validated last time.
+    last_total_cycles: Clock,
+}
+
+
+/// A single image stored in the [TileImageCache].
+struct TileImageCacheEntry {
+    /// The generation of the tile in VRAM when the image was read.
+    generation: u32,
+
+    /// The image read from VRAM.
+    image: SpriteImage,
+}
+
+
+impl TileImageCache {
+    /// Creates a new, empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+
+    /// Checks whether the cached images still belong to the given emulator instance.
+    /// Generations of a new emulator instance start again from zero, so they cannot
+    /// be compared with the previous instance. Therefore, the cache will be cleared
+    /// if the emulator's time did go backwards, like after loading a new ROM.
+    pub fn validate(&mut self, emu: &GameBoy) {
+        let total_cycles = emu.get_total_cycles_processed();
+
+        if total_cycles < self.last_total_cycles {
+            self.clear();
+        }
+
+        self.last_total_cycles = total_cycles;
+    }
+
+
+    /// Get the image of a tile in VRAM. The image will only be read from VRAM,
+    /// if it is not cached yet or if the tile was modified since the image was
+    /// read last time.
+    pub fn get_image(&mut self, ppu: &Ppu, tile_index: usize, bank: u8) -> &SpriteImage {
+        let generation = ppu.get_sprite_image_generation(tile_index, bank);
+        let entries    = &mut self.banks[(bank & 0x01) as usize];
+
+        if entries.is_empty() {
+            entries.resize_with(VRAM_TILES_PER_BANK, || None);
+        }
+
+        let entry = &mut entries[tile_index];
+
+        // read the image again, when the generation did change
+        match entry {
+            Some(cached) if cached.generation == generation => { }
+
+            _ => {
+                *entry = Some(TileImageCacheEntry {
+                    generation,
+                    image: ppu.get_sprite_image(tile_index, bank),
+                });
+            }
+        }
+
+        &entry.as_ref().unwrap().image
+    }
+
+
+    /// Removes all images from the cache.
+    pub fn clear(&mut self) {
+        for entries in &mut self.banks {
+            entries.clear();
+        }
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use egui::{Grid, Ui};
+
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::mmu::locations::{MEMORY_LOCATION_APU_NR50, MEMORY_LOCATION_APU_NR51, MEMORY_LOCATION_APU_NR52};
+
+use crate::state::EmulatorState;
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// The names of each audio channel to be displayed.
+const CHANNEL_NAMES: [&str; 4] = [
+    "CH1 Pulse",
+    "CH2 Pulse",
+    "CH3 Wave",
+    "CH4 Noise",
+];
+
+
+/// A view to display the state of the audio channels and to mute
+/// or solo single channels in the audio output.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ApuView {}
+
+
+impl ApuView {
+    /// Creates a new [`ApuView`] object.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+
+impl View for ApuView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "APU"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        let Some(emu) = state.emu.get_emulator_mut() else {
+            return;
+        };
+
+        let nr50 = emu.get_mmu().read_u8(MEMORY_LOCATION_APU_NR50);
+        let nr51 = emu.get_mmu().read_u8(MEMORY_LOCATION_APU_NR51);
+        let nr52 = emu.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52);
+
+        ui.horizontal(|ui| {
+            ui.label("NR50");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:02x}", nr50)));
+            ui.separator();
+            ui.label("NR51");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:02x}", nr51)));
+            ui.separator();
+            ui.label("NR52");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:02x}", nr52)));
+        });
+
+        ui.separator();
+
+        let apu      = &mut emu.get_peripherals_mut().apu;
+        let mut solo = apu.get_mixer().get_channel_solo();
+
+        Grid::new("apu_channels")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Channel");
+                ui.label("Enabled");
+                ui.label("Muted");
+                ui.label("Solo");
+                ui.end_row();
+
+                for (channel, name) in ChannelType::ALL.into_iter().zip(CHANNEL_NAMES) {
+                    let mut enabled = apu.is_channel_enabled(channel);
+                    let mut muted   = apu.get_mixer().is_channel_muted(channel);
+
+                    ui.label(name);
+
+                    // the enabled state is read only
+                    ui.checkbox(&mut enabled, "");
+
+                    if ui.checkbox(&mut muted, "").changed() {
+                        apu.set_channel_muted(channel, muted);
+                    }
+
+                    ui.radio_value(&mut solo, Some(channel), "");
+                    ui.end_row();
+                }
+
+                ui.label("All");
+                ui.label("");
+                ui.label("");
+                ui.radio_value(&mut solo, None, "");
+                ui.end_row();
+            })
+        ;
+
+        if solo != apu.get_mixer().get_channel_solo() {
+            apu.set_channel_solo(solo);
+        }
+    }
+}
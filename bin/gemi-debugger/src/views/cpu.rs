@@ -159,16 +159,19 @@ impl CpuView {
 
         ui.separator();
 
-        // Interrupts and HALT flags (readonly)
+        // Interrupts, HALT and STOP flags (readonly)
         if let Some(emu) = state.emu.get_emulator_mut() {
             let mut is_ime  = emu.cpu.is_interrupts_enabled();
-            let mut is_halt = emu.cpu.is_running() == false;
+            let mut is_stop = emu.cpu.is_stopped();
+            let mut is_halt = emu.cpu.is_running() == false && !is_stop;
             ui.checkbox(&mut is_ime,  "Interrupts Enabled");
             ui.checkbox(&mut is_halt, "HALT");
+            ui.checkbox(&mut is_stop, "STOP");
         }
         else {
             ui.checkbox(&mut false, "Interrupts Enabled");
             ui.checkbox(&mut false, "HALT");
+            ui.checkbox(&mut false, "STOP");
         }
     }
 
@@ -17,8 +17,9 @@
 
 use std::ops::Range;
 
-use egui::{vec2, Grid, RichText, ScrollArea, Sense, TextStyle, Ui};
+use egui::{vec2, Grid, Key, Label, RichText, ScrollArea, Sense, TextStyle, Ui};
 
+use gemi_core::cpu::disassembler::{Disassembler, SymbolTable};
 use gemi_core::cpu::opcode::{Instruction, Token};
 use gemi_core::gameboy::GameBoy;
 
@@ -31,6 +32,9 @@ use crate::views::View;
 
 const ADDITIONAL_LINES_BEYOND_VIEW : usize = 10;
 
+/// Keyboard shortcut to run the emulator until reaching the selected line.
+const KEY_RUN_TO_SELECTED_LINE : Key = Key::F4;
+
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct DisassemblyView {
@@ -58,6 +62,38 @@ struct RuntimeData {
     /// When set, cause the [ScrollArea] to bring the requested line into
     /// the center of its viewport.
     scroll_to_line: Option<usize>,
+
+    /// The address the disassembly was started from, when the user navigated
+    /// to a different address. When `None`, the disassembly follows the
+    /// current instruction pointer.
+    origin: Option<u16>,
+
+    /// Previous addresses the user navigated away from.
+    history_back: Vec<Option<u16>>,
+
+    /// Addresses the user navigated back from.
+    history_forward: Vec<Option<u16>>,
+
+    /// Actions requested by the user while rendering the disassembly,
+    /// which will be applied afterwards.
+    pending_action: Option<DisassemblyAction>,
+
+    /// Names of known addresses to be displayed as labels above their instructions.
+    symbols: SymbolTable,
+}
+
+
+/// Actions the user may invoke on the disassembly.
+#[derive(Copy, Clone)]
+enum DisassemblyAction {
+    /// Run the emulator until reaching the given address.
+    RunTo(u16),
+
+    /// Show the disassembly starting at the given address.
+    GoTo(u16),
+
+    /// Adds or removes a breakpoint on the given address.
+    ToggleBreakpoint(u16),
 }
 
 
@@ -86,9 +122,12 @@ struct InstructionDisplayEntry {
     /// The actual bytes forming the instruction.
     instruction_bytes: Vec<u8>,
 
+    /// The absolute address this instruction may jump to, if any.
+    jump_target: Option<u16>,
+
     label_address: RichText,
     label_opcode_bytes: RichText,
-    label_opcode_desc: Vec<RichText>,
+    label_opcode_desc: Vec<(RichText, bool)>,
 }
 
 
@@ -101,9 +140,27 @@ impl View for DisassemblyView {
 
     fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
         if let Some(emu) = state.emu.get_emulator() {
+            self.render_toolbar(ui, emu);
             self.update_disassembly(ui, &mut state.ui, emu);
             self.render_disassembly_list(ui, &mut state.ui, emu);
         }
+
+        // apply actions invoked by the user
+        match self.rt.pending_action.take() {
+            Some(DisassemblyAction::RunTo(address)) => {
+                state.run_to_address(address);
+            }
+
+            Some(DisassemblyAction::GoTo(address)) => {
+                self.navigate_to(Some(address));
+            }
+
+            Some(DisassemblyAction::ToggleBreakpoint(address)) => {
+                state.toggle_breakpoint(address);
+            }
+
+            None => { }
+        }
     }
 
 
@@ -123,6 +180,34 @@ impl View for DisassemblyView {
             _ => { }
         }
     }
+
+
+    fn on_emulator_loaded(&mut self, state: &mut EmulatorState) {
+        // prefer the symbol file restored from the debug session,
+        // otherwise look for a symbol file next to the ROM file
+        let symbol_file = state.symbol_file
+                .clone()
+                .or_else(|| state.last_rom_file.as_ref().map(|path| path.with_extension("sym")))
+        ;
+
+        let symbols = symbol_file
+                .as_ref()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .map(|data| SymbolTable::from_no_gba_sym(&data))
+        ;
+
+        // remember the file loaded, so it will be stored with the debug session
+        state.symbol_file = symbols.as_ref().and(symbol_file);
+
+        self.set_symbol_table(symbols);
+    }
+
+
+    fn on_emulator_reset(&mut self, _state: &mut EmulatorState) {
+        // follow the instruction pointer back to the start of the program,
+        // even if the user navigated to a different address before
+        self.show_origin(None);
+    }
 }
 
 
@@ -135,6 +220,13 @@ impl DisassemblyView {
     }
 
 
+    /// Set the table of symbols to label addresses in the disassembly.
+    /// Without a symbol table, only hardware defined locations will be labeled.
+    pub fn set_symbol_table(&mut self, symbols: Option<SymbolTable>) {
+        self.rt.symbols = symbols.unwrap_or_default();
+    }
+
+
     /// Updates the currently cached disassembly as needed.
     fn update_disassembly(&mut self, ui: &mut Ui, ui_states: &mut UiStates, emu: &GameBoy) {
         let current_pc = emu.cpu.get_instruction_pointer();
@@ -151,16 +243,20 @@ impl DisassemblyView {
                 // address on. After the CPU did jump out of the current disassembly,
                 // we cant be sure whether it's still valid or not
                 self.rt.disassembly_cache.reset();
+                self.rt.origin = None;
             }
         }
 
         // when no disassembly is ready yet (or was cleared before)
-        // disassemble entries from the current instruction pointer onwards.
+        // disassemble entries from the requested address or the
+        // current instruction pointer onwards.
         if self.rt.disassembly_cache.is_empty() {
             let visible_lines = Self::compute_visible_lines(ui);
+            let origin        = self.rt.origin.unwrap_or(current_pc);
 
-            self.rt.disassembly_cache = DisassemblyCache::disassemble_entries_from_pc(
+            self.rt.disassembly_cache = DisassemblyCache::disassemble_entries_from(
                 emu,
+                origin,
                 visible_lines + ADDITIONAL_LINES_BEYOND_VIEW
             );
 
@@ -180,6 +276,45 @@ impl DisassemblyView {
     }
 
 
+    /// Renders the navigation buttons on top of the disassembly list.
+    fn render_toolbar(&mut self, ui: &mut Ui, emu: &GameBoy) {
+        ui.horizontal(|ui| {
+            let back_button = ui.add_enabled(
+                !self.rt.history_back.is_empty(),
+                egui::Button::new("\u{23f4}")
+            );
+
+            if back_button.on_hover_text("Back").clicked() {
+                self.navigate_back();
+            }
+
+            let forward_button = ui.add_enabled(
+                !self.rt.history_forward.is_empty(),
+                egui::Button::new("\u{23f5}")
+            );
+
+            if forward_button.on_hover_text("Forward").clicked() {
+                self.navigate_forward();
+            }
+
+            let pc_button = ui.add_enabled(
+                self.rt.origin.is_some(),
+                egui::Button::new("PC")
+            );
+
+            if pc_button.on_hover_text("Go to the current instruction pointer").clicked() {
+                self.navigate_to(None);
+            }
+
+            ui.label(
+                GemiStyle::ADDRESS.rich_text(
+                    format!("{:04x}", self.rt.origin.unwrap_or(emu.cpu.get_instruction_pointer()))
+                )
+            );
+        });
+    }
+
+
     /// Renders the actual UI using the currently stored disassembly cache.
     fn render_disassembly_list(&mut self, ui: &mut Ui, ui_states: &mut UiStates, emu: &GameBoy) {
         let (line_content_height, line_height_padded) = Self::compute_line_height(ui);
@@ -258,6 +393,15 @@ impl DisassemblyView {
                                 // get an entry, if still valid, otherwise will leave the rendering
                                 let entry = self.rt.disassembly_cache.get_instruction(row)?;
 
+                                // render the symbol name of this address as a label above the instruction
+                                if let Some(symbol) = self.rt.symbols.get_symbol(entry.instruction.opcode_address) {
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.label(GemiStyle::SYMBOL.rich_text(format!("{symbol}:")));
+                                    ui.end_row();
+                                }
+
                                 // bounding box of the whole line
                                 let line_bounds = egui::Rect::from_min_size(
                                     ui.cursor().left_top(),
@@ -280,12 +424,16 @@ impl DisassemblyView {
                                     );
                                 }
 
-                                // render the actual element
-                                entry.render_as_row(ui, emu);
-
-                                // mouse interaction with the current row
+                                // mouse interaction with the current row; this needs to be
+                                // registered before the row's content, so the jump target
+                                // label will receive its own clicks
                                 let line_response = ui.interact(line_bounds, ui.id().with(row), Sense::click());
 
+                                // render the actual element
+                                if let Some(target) = entry.render_as_row(ui, emu) {
+                                    self.rt.pending_action = Some(DisassemblyAction::GoTo(target));
+                                }
+
                                 // handle hover state
                                 ui_states.hover.set(selection_key.clone(), line_response.hovered());
 
@@ -293,6 +441,31 @@ impl DisassemblyView {
                                 if line_response.clicked() {
                                     ui_states.focus.toggle(selection_key.clone());
                                 }
+
+                                // context menu with actions on the current line
+                                let address        = entry.instruction.opcode_address;
+                                let jump_target    = entry.jump_target;
+                                let has_breakpoint = emu.get_breakpoints().get(address).is_some_and(|breakpoint| !breakpoint.one_shot);
+
+                                line_response.context_menu(|ui| {
+                                    if ui.button("Run to here").clicked() {
+                                        self.rt.pending_action = Some(DisassemblyAction::RunTo(address));
+                                        ui.close_menu();
+                                    }
+
+                                    let breakpoint_label = if has_breakpoint { "Remove breakpoint" } else { "Add breakpoint" };
+                                    if ui.button(breakpoint_label).clicked() {
+                                        self.rt.pending_action = Some(DisassemblyAction::ToggleBreakpoint(address));
+                                        ui.close_menu();
+                                    }
+
+                                    if let Some(target) = jump_target {
+                                        if ui.button(format!("Go to {target:04x}")).clicked() {
+                                            self.rt.pending_action = Some(DisassemblyAction::GoTo(target));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
                             }
 
                             Some(())
@@ -306,6 +479,55 @@ impl DisassemblyView {
                 }
             }
         );
+
+        // run to the selected line via keyboard shortcut
+        if ui.input(|input| input.key_pressed(KEY_RUN_TO_SELECTED_LINE)) {
+            let selected_address = self.line_selected
+                    .and_then(|line| self.rt.disassembly_cache.get_instruction(line))
+                    .map(|entry| entry.instruction.opcode_address)
+            ;
+
+            if let Some(address) = selected_address {
+                self.rt.pending_action = Some(DisassemblyAction::RunTo(address));
+            }
+        }
+    }
+
+
+    /// Shows the disassembly starting at the given address, or the current
+    /// instruction pointer, if `None`. The previous address will be stored
+    /// in the navigation history.
+    fn navigate_to(&mut self, origin: Option<u16>) {
+        let previous = self.rt.origin;
+
+        self.rt.history_back.push(previous);
+        self.rt.history_forward.clear();
+        self.show_origin(origin);
+    }
+
+
+    /// Navigates to the previous address in the navigation history.
+    fn navigate_back(&mut self) {
+        if let Some(origin) = self.rt.history_back.pop() {
+            self.rt.history_forward.push(self.rt.origin);
+            self.show_origin(origin);
+        }
+    }
+
+
+    /// Navigates to the next address in the navigation history.
+    fn navigate_forward(&mut self) {
+        if let Some(origin) = self.rt.history_forward.pop() {
+            self.rt.history_back.push(self.rt.origin);
+            self.show_origin(origin);
+        }
+    }
+
+
+    /// Clears the disassembly, so it will be rebuilt starting at the given address.
+    fn show_origin(&mut self, origin: Option<u16>) {
+        self.rt.origin = origin;
+        self.rt.disassembly_cache.reset();
     }
 
 
@@ -338,14 +560,12 @@ impl DisassemblyView {
 
 
 impl DisassemblyCache {
-    /// Creates a new disassembly, starting at the current address of the instruction
-    /// pointer, creating a specific number of lines.
-    fn disassemble_entries_from_pc(emu: &GameBoy, max_entries: usize) -> Self {
-        let current_pc = emu.cpu.get_instruction_pointer();
-
-        // creates an empty disassembly on the address of the current instruction pointer
+    /// Creates a new disassembly, starting at the given address,
+    /// creating a specific number of lines.
+    fn disassemble_entries_from(emu: &GameBoy, address: u16, max_entries: usize) -> Self {
+        // creates an empty disassembly on the requested address
         let mut disassembly = Self {
-            address_range: current_pc .. current_pc,
+            address_range: address .. address,
             .. Default::default()
         };
 
@@ -359,33 +579,24 @@ impl DisassemblyCache {
     /// Continues to disassemble from the last address until [max_entries] lines
     /// are stored in the disassembly cache.
     fn fill_up(&mut self, emu: &GameBoy, max_entries: usize) -> usize {
-        let mut pc          = self.address_range.end;
-        let mut added_lines = 0;
-
-        // accessor to read from emulator memory
-        let read_emu = |address| emu.get_mmu().read_u8(address);
+        let missing_lines = max_entries.saturating_sub(self.instruction_entries.len());
 
-        // keep adding entries until reaching the maximum number
+        // disassemble until reaching the maximum number of entries
         // or the instruction pointer reaches the end of address range
-        while
-                self.instruction_entries.len() < max_entries
-            &&  pc < 0xffff
-        {
-            let instruction = Instruction::read_instruction(pc, read_emu);
-            let entry       = InstructionDisplayEntry::prepare_instruction_display(instruction, emu);
+        let instructions = Disassembler::new(|address| emu.get_mmu().read_u8(address))
+            .disassemble_count(self.address_range.end, missing_lines)
+        ;
 
-            let instruction_length = entry.instruction.get_instruction_length();
-            self.instruction_entries.push(entry);
+        let added_lines = instructions.len();
 
-            // compute the address of the next instruction
-            pc = pc.saturating_add(instruction_length);
+        for instruction in instructions {
+            let entry = InstructionDisplayEntry::prepare_instruction_display(instruction, emu);
 
-            added_lines += 1;
+            // update the range to end behind the instruction just added
+            self.address_range.end = entry.get_address_range().end;
+            self.instruction_entries.push(entry);
         }
 
-        // update the range
-        self.address_range.end = pc;
-
         added_lines
     }
 
@@ -473,10 +684,9 @@ impl DisassemblyCache {
             let original_instruction_length = self.instruction_entries[line].get_length();
 
             // read the instruction again from memory
-            let new_instruction = Instruction::read_instruction(
-                original_instruction_address,
-                |address| emu.get_mmu().read_u8(address)
-            );
+            let new_instruction = Disassembler::new(|address| emu.get_mmu().read_u8(address))
+                .disassemble_at(original_instruction_address)
+            ;
 
             // only if the length is matching, we can replace the old one with the new one
             // otherwise this means all the following instructions are invalid as well
@@ -515,16 +725,9 @@ impl InstructionDisplayEntry {
         };
 
         // instruction bytes
-        let instruction_bytes = {
-            // number of bytes for this instruction (+1 for 0xcb opcodes)
-            let num_instruction_bytes = instruction.get_instruction_length();
-
-            (0..num_instruction_bytes)
-                    .into_iter()
-                    .map(|offset| instruction.opcode_address.wrapping_add(offset))
-                    .map(|address| emu.get_mmu().read_u8(address))
-                    .collect::<Vec<_>>()
-        };
+        let instruction_bytes = Disassembler::new(|address| emu.get_mmu().read_u8(address))
+            .read_instruction_bytes(&instruction)
+        ;
 
         // instruction bytes label
         let label_opcode_bytes = {
@@ -545,30 +748,36 @@ impl InstructionDisplayEntry {
 
         // format the opcode label
         let label_opcode_desc = {
-            instruction.opcode.tokenize()
+            instruction.tokenize()
                     .into_iter()
                     .map(|token| {
                         match token {
                             Token::Command(cmd) => {
-                                GemiStyle::KEYWORD.rich_text(cmd)
+                                (GemiStyle::KEYWORD.rich_text(cmd), false)
                             }
 
                             Token::Text(t) => {
-                                GemiStyle::KEYWORD_LOW.rich_text(t)
+                                (GemiStyle::KEYWORD_LOW.rich_text(t), false)
                             }
 
+                            // arguments of jump instructions are the jump target,
+                            // which the user may click on to navigate
                             Token::Argument(arg) => {
-                                let str = instruction.resolve_argument(&arg);
-                                GemiStyle::KEYWORD_LOW.rich_text(str)
+                                let str       = instruction.resolve_argument(&arg);
+                                let is_target = instruction.get_jump_target().is_some();
+                                (GemiStyle::KEYWORD_LOW.rich_text(str), is_target)
                             }
                         }
                     })
                     .collect()
         };
 
+        let jump_target = instruction.get_jump_target();
+
         Self {
             instruction,
             instruction_bytes,
+            jump_target,
             label_address,
             label_opcode_bytes,
             label_opcode_desc,
@@ -607,8 +816,11 @@ impl InstructionDisplayEntry {
     }
 
 
-    /// Renders a single instruction into a row
-    fn render_as_row(&self, ui: &mut Ui, emu: &GameBoy) {
+    /// Renders a single instruction into a row.
+    /// Returns the jump target of the instruction, if the user clicked on it.
+    fn render_as_row(&self, ui: &mut Ui, emu: &GameBoy) -> Option<u16> {
+        let mut clicked_target = None;
+
         // is current
         {
             let current_pc = emu.cpu.get_instruction_pointer();
@@ -635,8 +847,22 @@ impl InstructionDisplayEntry {
         // format the opcode label
         {
             ui.horizontal(|ui|{
-                for token in &self.label_opcode_desc {
-                    ui.label(token.clone());
+                for (token, is_target) in &self.label_opcode_desc {
+                    match (is_target, self.jump_target) {
+                        (true, Some(target)) => {
+                            let response = ui.add(Label::new(token.clone().underline()).sense(Sense::click()))
+                                    .on_hover_text(format!("Go to {target:04x}"))
+                            ;
+
+                            if response.clicked() {
+                                clicked_target = Some(target);
+                            }
+                        }
+
+                        _ => {
+                            ui.label(token.clone());
+                        }
+                    }
 
                     // reduce item spacing for each following item
                     ui.style_mut().spacing.item_spacing.x = 0.0;
@@ -645,5 +871,7 @@ impl InstructionDisplayEntry {
         }
 
         ui.end_row();
+
+        clicked_target
     }
 }
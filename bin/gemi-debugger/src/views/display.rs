@@ -26,7 +26,7 @@ use egui::{Color32, Context, Grid, Image, Pos2, Sense, TextureHandle, Ui, Vec2,
 use gemi_core::gameboy::{Clock, GameBoy};
 use gemi_core::ppu::flags::LcdControlFlag;
 use gemi_core::ppu::graphic_data::Sprite;
-use gemi_core::ppu::ppu::{SCREEN_H, SCREEN_W};
+use gemi_core::ppu::ppu::{Layer, SCREEN_H, SCREEN_W};
 
 use crate::highlight::test_selection;
 use crate::selection::Selected;
@@ -34,6 +34,14 @@ use crate::state::{EmulatorState, UiStates, UpdateMode};
 use crate::views::View;
 
 
+/// Name entries for each layer of the display.
+const LAYER_NAMES : [&str; 3] = [
+    "Background",
+    "Window",
+    "Objects",
+];
+
+
 /// The main view to show the emulator's display.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct EmulatorDisplayView {
@@ -66,10 +74,11 @@ impl View for EmulatorDisplayView {
 
 
     fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
-        match state.emu.get_emulator() {
+        match state.emu.get_emulator_mut() {
             None => {}
 
             Some(emu) => {
+                Self::render_layer_toggles(ui, emu);
                 self.update_display_image(ui.ctx(), emu, &mut state.ui);
                 self.render_display_image(ui, emu, &mut state.ui);
             }
@@ -118,14 +127,45 @@ impl EmulatorDisplayView {
                 self.apply_highlight(&mut image);
             }
 
-            let texture = ctx.load_texture("display", image, TextureOptions::NEAREST);
-
-            self.rt.display_image           = Some(texture);
+            self.upload_display_image(ctx, image);
             self.rt.display_image_timestamp = emu.get_total_cycles_processed();
         }
     }
 
 
+    /// Uploads the display image into the cached texture. The texture will be created once
+    /// and only be re-created when the size of the display changes; otherwise the existing
+    /// texture will just receive the new pixel data.
+    fn upload_display_image(&mut self, ctx: &Context, image: ColorImage) {
+        match &mut self.rt.display_image {
+            Some(texture) if texture.size() == image.size => {
+                texture.set(image, TextureOptions::NEAREST);
+            }
+
+            _ => {
+                let texture = ctx.load_texture("display", image, TextureOptions::NEAREST);
+                self.rt.display_image = Some(texture);
+            }
+        }
+    }
+
+
+    /// Render checkboxes to show or hide each layer of the emulator's display.
+    fn render_layer_toggles(ui: &mut Ui, emu: &mut GameBoy) {
+        let ppu = &mut emu.get_peripherals_mut().ppu;
+
+        ui.horizontal(|ui| {
+            for (layer, name) in Layer::ALL.into_iter().zip(LAYER_NAMES) {
+                let mut visible = ppu.is_layer_visible(layer);
+
+                if ui.checkbox(&mut visible, name).changed() {
+                    ppu.set_layer_visible(layer, visible);
+                }
+            }
+        });
+    }
+
+
     /// Render the display image of the currently running emulator.
     fn render_display_image(&mut self, ui: &mut Ui, emu: &GameBoy, ui_states: &mut UiStates) {
         if let Some(texture) = &self.rt.display_image {
@@ -0,0 +1,138 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use egui::{Grid, TextEdit, Ui};
+
+use gemi_core::mmu::locations::MEMORY_LOCATION_DMA_ADDRESS;
+use gemi_core::mmu::memory::DmaTransferState;
+
+use crate::state::EmulatorState;
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// A view displaying the state of the OAM DMA transfer
+/// and a history of the most recent transfers completed.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DmaView {
+    /// The source page entered by the user to start a transfer manually.
+    source_page: String,
+}
+
+
+impl DmaView {
+    /// Creates a new [`DmaView`] object.
+    pub fn new() -> Self {
+        Self {
+            source_page: "C0".to_string(),
+        }
+    }
+}
+
+
+impl View for DmaView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "DMA"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        let Some(emu) = state.emu.get_emulator_mut() else {
+            return;
+        };
+
+        // enable recording DMA transfers as long as this view exists
+        if !emu.get_dma_history().is_enabled() {
+            emu.enable_dma_history(true);
+        }
+
+        ui.label(GemiStyle::CAPTION.rich_text("OAM DMA"));
+
+        Grid::new("dma_state")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    match emu.get_mmu().get_dma_transfer_state() {
+                        DmaTransferState::Disabled => {
+                            ui.label("Status:");
+                            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text("Idle"));
+                            ui.end_row();
+                        }
+
+                        DmaTransferState::Transferring(transfer) => {
+                            ui.label("Status:");
+                            ui.label(GemiStyle::VALUE_HIGHLIGHTED.rich_text("Transferring"));
+                            ui.end_row();
+
+                            ui.label("Source:");
+                            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:04X}", transfer.start_address)));
+                            ui.end_row();
+
+                            ui.label("Remaining:");
+                            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{} bytes", transfer.get_remaining_bytes())));
+                            ui.end_row();
+                        }
+                    }
+                })
+        ;
+
+        // start a transfer from any source page for testing
+        ui.horizontal(|ui| {
+            let source_page = u8::from_str_radix(self.source_page.trim(), 16).ok();
+
+            ui.label("Source page:");
+
+            ui.add(
+                TextEdit::singleline(&mut self.source_page)
+                        .hint_text("XX")
+                        .desired_width(30.0)
+            );
+
+            if ui.add_enabled(source_page.is_some(), egui::Button::new("Start Transfer")).clicked() {
+                if let Some(source_page) = source_page {
+                    emu.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, source_page);
+                }
+            }
+        });
+
+        ui.separator();
+
+        ui.label(GemiStyle::CAPTION.rich_text("HDMA / GDMA"));
+        ui.label("HDMA and GDMA transfers are not emulated yet.");
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label(GemiStyle::CAPTION.rich_text("Completed Transfers"));
+
+            if ui.button("Clear").clicked() {
+                emu.clear_dma_history();
+            }
+        });
+
+        Grid::new("dma_history")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for event in emu.get_dma_history().iter().rev() {
+                        ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("@{}", event.cycle)));
+                        ui.label(GemiStyle::ADDRESS.rich_text(format!("{:04X}", event.source_address)));
+                        ui.end_row();
+                    }
+                })
+        ;
+    }
+}
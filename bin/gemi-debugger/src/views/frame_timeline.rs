@@ -0,0 +1,217 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use egui::{Color32, Grid, Rect, ScrollArea, Sense, Ui, Vec2};
+
+use gemi_core::gameboy::Clock;
+use gemi_core::ppu::frame_log::{FrameLog, FrameLogEventKind, ScanlineLog};
+use gemi_core::ppu::ppu::{Mode, CPU_CYCLES_PER_LINE};
+
+use crate::state::EmulatorState;
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// The height of a single scanline bar.
+const LINE_HEIGHT: f32 = 4.0;
+
+/// The number of horizontal pixels per CPU cycle.
+const PIXELS_PER_CYCLE: f32 = 1.0;
+
+/// The size of markers for events within a scanline.
+const MARKER_WIDTH: f32 = 2.0;
+
+
+/// A view displaying a timeline of the last completed frame.
+/// Each scanline is displayed as a bar, segmented by the duration
+/// of each PPU mode, with markers of events occurred within this line.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FrameTimelineView {
+    #[serde(skip)]
+    rt: FrameTimelineRuntimeData,
+}
+
+
+/// Internal data of the [FrameTimelineView], which does not get serialized.
+#[derive(Default)]
+struct FrameTimelineRuntimeData {
+    /// The log of the frame being displayed.
+    frame_log: Option<FrameLog>,
+}
+
+
+impl FrameTimelineView {
+    /// Creates a new [`FrameTimelineView`] object.
+    pub fn new() -> Self {
+        Self {
+            rt: Default::default(),
+        }
+    }
+}
+
+
+impl View for FrameTimelineView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "Frame Timeline"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        if let Some(emu) = state.emu.get_emulator_mut() {
+            let ppu = &mut emu.get_peripherals_mut().ppu;
+
+            // enable recording the frame log as long as this view exists
+            if !ppu.is_frame_log_enabled() {
+                ppu.set_frame_log_enabled(true);
+            }
+
+            // take the most recent completed frame, if any
+            if let Some(frame_log) = ppu.take_frame_log() {
+                self.rt.frame_log = Some(frame_log);
+            }
+        }
+
+        match &self.rt.frame_log {
+            Some(frame_log) => {
+                ScrollArea::vertical()
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            for (line, scanline) in frame_log.lines.iter().enumerate() {
+                                Self::display_scanline(ui, line, scanline);
+                            }
+                        })
+                ;
+            }
+
+            None => {
+                ui.label("No frame recorded yet.");
+            }
+        }
+    }
+
+
+    fn on_emulator_loaded(&mut self, _state: &mut EmulatorState) {
+        self.rt.frame_log = None;
+    }
+}
+
+
+impl FrameTimelineView {
+    /// Display a single scanline as a bar.
+    fn display_scanline(ui: &mut Ui, line: usize, scanline: &ScanlineLog) {
+        let size             = Vec2::new(CPU_CYCLES_PER_LINE as f32 * PIXELS_PER_CYCLE, LINE_HEIGHT);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter          = ui.painter();
+
+        // draw a segment for each mode
+        for (index, change) in scanline.mode_changes.iter().enumerate() {
+            let end = match scanline.mode_changes.get(index + 1) {
+                Some(next) => next.cycle,
+                None       => CPU_CYCLES_PER_LINE,
+            };
+
+            painter.rect_filled(
+                Self::cycle_range_to_rect(&rect, change.cycle, end),
+                0.0,
+                Self::get_mode_color(change.mode)
+            );
+        }
+
+        // draw markers for each event
+        for event in &scanline.events {
+            painter.rect_filled(
+                Self::cycle_range_to_rect(&rect, event.cycle, event.cycle).expand2(Vec2::new(MARKER_WIDTH / 2.0, 0.0)),
+                0.0,
+                Self::get_event_color(&event.kind)
+            );
+        }
+
+        // tooltip
+        response.on_hover_ui(|ui| {
+            Grid::new("tooltip")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("line");
+                        ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(line.to_string()));
+                        ui.end_row();
+
+                        for (name, mode) in [
+                            ("mode 2", Mode::OamScan),
+                            ("mode 3", Mode::DrawLine),
+                            ("mode 0", Mode::HBlank),
+                            ("mode 1", Mode::VBlank),
+                        ] {
+                            let duration = scanline.get_mode_duration(mode, CPU_CYCLES_PER_LINE);
+
+                            if duration != 0 {
+                                ui.label(name);
+                                ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{duration} cycles")));
+                                ui.end_row();
+                            }
+                        }
+
+                        for event in &scanline.events {
+                            ui.label(format!("@{}", event.cycle));
+                            ui.label(Self::get_event_name(&event.kind));
+                            ui.end_row();
+                        }
+                    })
+            ;
+        });
+    }
+
+
+    /// Get the rectangle within a scanline bar covering a range of cycles.
+    fn cycle_range_to_rect(line_rect: &Rect, begin: Clock, end: Clock) -> Rect {
+        let x0 = line_rect.left() + (begin as f32 * PIXELS_PER_CYCLE);
+        let x1 = line_rect.left() + (end   as f32 * PIXELS_PER_CYCLE);
+
+        Rect::from_x_y_ranges(x0 ..= x1, line_rect.top() ..= line_rect.bottom())
+    }
+
+
+    /// Get the color to display a PPU mode.
+    fn get_mode_color(mode: Mode) -> Color32 {
+        match mode {
+            Mode::OamScan  => Color32::from_rgb(0x3c, 0x8d, 0x2f),
+            Mode::DrawLine => Color32::from_rgb(0xd2, 0x69, 0x1e),
+            Mode::HBlank   => Color32::from_rgb(0x46, 0x5a, 0x82),
+            Mode::VBlank   => Color32::from_rgb(0x50, 0x50, 0x50),
+        }
+    }
+
+
+    /// Get the color of the marker of any event.
+    fn get_event_color(kind: &FrameLogEventKind) -> Color32 {
+        match kind {
+            FrameLogEventKind::Interrupt(_)    => Color32::from_rgb(0xff, 0x30, 0x30),
+            FrameLogEventKind::LycMatch        => Color32::from_rgb(0xff, 0xff, 0x40),
+            FrameLogEventKind::OamDmaStarted   => Color32::from_rgb(0xe0, 0x40, 0xe0),
+            FrameLogEventKind::OamDmaCompleted => Color32::from_rgb(0xa0, 0x30, 0xa0),
+        }
+    }
+
+
+    /// Get a readable name of any event.
+    fn get_event_name(kind: &FrameLogEventKind) -> String {
+        match kind {
+            FrameLogEventKind::Interrupt(interrupt) => format!("Interrupt {interrupt:?}"),
+            FrameLogEventKind::LycMatch             => "LY = LYC".to_string(),
+            FrameLogEventKind::OamDmaStarted        => "OAM DMA started".to_string(),
+            FrameLogEventKind::OamDmaCompleted      => "OAM DMA completed".to_string(),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use egui::{Color32, Grid, RichText, ScrollArea, Ui};
+
+use gemi_core::cpu::interrupts::Interrupt;
+
+use crate::state::EmulatorState;
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// All interrupts in the order of their priority.
+const INTERRUPTS: [Interrupt; 5] = [
+    Interrupt::VBlank,
+    Interrupt::LcdStat,
+    Interrupt::Timer,
+    Interrupt::Serial,
+    Interrupt::Input,
+];
+
+
+/// A view displaying the most recent interrupts requested by any component
+/// with the time and scanline they were requested at.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct InterruptHistoryView {
+    /// Whether each interrupt is displayed, in the order of [INTERRUPTS].
+    show_interrupts: [bool; 5],
+}
+
+
+impl InterruptHistoryView {
+    /// Creates a new [`InterruptHistoryView`] object.
+    pub fn new() -> Self {
+        Self {
+            show_interrupts: [true; 5],
+        }
+    }
+}
+
+
+impl View for InterruptHistoryView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "Interrupt History"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        let Some(emu) = state.emu.get_emulator_mut() else {
+            return;
+        };
+
+        // enable recording interrupts as long as this view exists
+        if !emu.get_interrupt_history().is_enabled() {
+            emu.enable_interrupt_history(true);
+        }
+
+        // filter by interrupt type
+        ui.horizontal(|ui| {
+            for (interrupt, show) in INTERRUPTS.iter().zip(self.show_interrupts.iter_mut()) {
+                ui.checkbox(show, Self::get_interrupt_label(*interrupt));
+            }
+
+            ui.separator();
+
+            if ui.button("Clear").clicked() {
+                emu.clear_interrupt_history();
+            }
+        });
+
+        ui.separator();
+
+        let history = emu.get_interrupt_history();
+
+        ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    Grid::new("interrupt_history")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for event in history.iter().filter(|event| self.is_visible(event.interrupt)) {
+                                    ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("@{}", event.cycle)));
+
+                                    ui.label(Self::get_interrupt_text(event.interrupt))
+                                            .on_hover_text(format!("LY = {}", event.ly))
+                                    ;
+
+                                    ui.end_row();
+                                }
+                            })
+                    ;
+                })
+        ;
+    }
+}
+
+
+impl InterruptHistoryView {
+    /// Checks whether the given interrupt passes the current filter.
+    fn is_visible(&self, interrupt: Interrupt) -> bool {
+        INTERRUPTS.iter()
+                .position(|i| *i == interrupt)
+                .map(|index| self.show_interrupts[index])
+                .unwrap_or(true)
+    }
+
+
+    /// Get a readable name of any interrupt.
+    fn get_interrupt_label(interrupt: Interrupt) -> &'static str {
+        match interrupt {
+            Interrupt::VBlank  => "VBlank",
+            Interrupt::LcdStat => "LCD STAT",
+            Interrupt::Timer   => "Timer",
+            Interrupt::Serial  => "Serial",
+            Interrupt::Input   => "Joypad",
+        }
+    }
+
+
+    /// Get the color to display any interrupt.
+    fn get_interrupt_color(interrupt: Interrupt) -> Color32 {
+        match interrupt {
+            Interrupt::VBlank  => Color32::from_rgb(0x50, 0x9c, 0xe0),
+            Interrupt::LcdStat => Color32::from_rgb(0x3c, 0xb0, 0x50),
+            Interrupt::Timer   => Color32::from_rgb(0xe0, 0xa0, 0x30),
+            Interrupt::Serial  => Color32::from_rgb(0xc0, 0x60, 0xd0),
+            Interrupt::Input   => Color32::from_rgb(0xe0, 0x50, 0x50),
+        }
+    }
+
+
+    /// Get the text to display an interrupt in the history list.
+    fn get_interrupt_text(interrupt: Interrupt) -> RichText {
+        RichText::new(Self::get_interrupt_label(interrupt))
+                .monospace()
+                .color(Self::get_interrupt_color(interrupt))
+    }
+}
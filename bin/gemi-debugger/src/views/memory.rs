@@ -139,6 +139,13 @@ impl MemoryView {
                 );
             }
 
+            // palette RAM is not mapped into the memory bus
+            SelectionChanged(kind, Some(Palette(_, _))) => {
+                self.memory_editor.clear_highlight(
+                        get_highlight_index(kind)
+                );
+            }
+
             SelectionChanged(kind, None) => {
                 self.memory_editor.clear_highlight(
                         get_highlight_index(kind)
@@ -0,0 +1,349 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use egui::{ComboBox, Grid, ScrollArea, TextEdit, Ui};
+
+use gemi_core::gameboy::GameBoy;
+
+use crate::state::{EmulatorState, EmulatorInstance, FrozenMemoryValue, MemoryValueSize};
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// The maximum number of results displayed in the result list.
+const MAX_RESULTS_DISPLAYED: usize = 256;
+
+/// The range of WRAM addresses to be searched.
+const SEARCH_RANGE_BEGIN: u16 = 0xc000;
+const SEARCH_RANGE_END:   u16 = 0xdfff;
+
+
+/// A filter to be applied on the current search results.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SearchFilter {
+    /// The current value equals the operand.
+    Equals,
+
+    /// The current value does not equal the operand.
+    NotEquals,
+
+    /// The current value is lower than the previous value.
+    Decreased,
+
+    /// The current value is greater than the previous value.
+    Increased,
+
+    /// The current value differs from the previous value by the operand.
+    ChangedBy,
+}
+
+
+/// A single address which matched all filters applied so far.
+#[derive(Copy, Clone)]
+struct SearchResult {
+    /// The address of the value.
+    address: u16,
+
+    /// The value when the last filter was applied.
+    previous_value: u16,
+}
+
+
+/// A view to search for memory values in WRAM, which may be used to find
+/// addresses of game variables and to freeze them on a specific value.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MemorySearchView {
+    /// The size of values to search for.
+    value_size: MemoryValueSize,
+
+    /// The filter to be applied next.
+    filter: SearchFilter,
+
+    /// The operand of the filter as entered by the user.
+    operand: String,
+
+    #[serde(skip)]
+    rt: MemorySearchRuntimeData,
+}
+
+
+/// Internal data of the [MemorySearchView], which does not get serialized.
+#[derive(Default)]
+struct MemorySearchRuntimeData {
+    /// The list of addresses matching all filters applied since capturing the baseline.
+    /// Will be [None] if no baseline was captured yet.
+    results: Option<Vec<SearchResult>>,
+}
+
+
+impl SearchFilter {
+    /// A list of all filters available.
+    pub const ALL: [SearchFilter; 5] = [
+        SearchFilter::Equals,
+        SearchFilter::NotEquals,
+        SearchFilter::Decreased,
+        SearchFilter::Increased,
+        SearchFilter::ChangedBy,
+    ];
+
+
+    /// Get a readable name of this filter.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SearchFilter::Equals    => "== X",
+            SearchFilter::NotEquals => "!= X",
+            SearchFilter::Decreased => "< previous",
+            SearchFilter::Increased => "> previous",
+            SearchFilter::ChangedBy => "changed by X",
+        }
+    }
+
+
+    /// Checks whether this filter requires an operand.
+    pub fn needs_operand(&self) -> bool {
+        match self {
+            SearchFilter::Equals    => true,
+            SearchFilter::NotEquals => true,
+            SearchFilter::Decreased => false,
+            SearchFilter::Increased => false,
+            SearchFilter::ChangedBy => true,
+        }
+    }
+
+
+    /// Checks whether a value matches this filter.
+    pub fn matches(&self, current: u16, previous: u16, operand: u16, size: MemoryValueSize) -> bool {
+        match self {
+            SearchFilter::Equals    => current == operand,
+            SearchFilter::NotEquals => current != operand,
+            SearchFilter::Decreased => current < previous,
+            SearchFilter::Increased => current > previous,
+            SearchFilter::ChangedBy => {
+                let diff = current.wrapping_sub(previous);
+
+                match size {
+                    MemoryValueSize::U8  => (diff & 0xff) == (operand & 0xff),
+                    MemoryValueSize::U16 => diff == operand,
+                }
+            }
+        }
+    }
+}
+
+
+impl MemorySearchView {
+    /// Creates a new [`MemorySearchView`] object.
+    pub fn new() -> Self {
+        Self {
+            value_size: MemoryValueSize::U8,
+            filter:     SearchFilter::Equals,
+            operand:    String::new(),
+            rt:         Default::default(),
+        }
+    }
+}
+
+
+impl View for MemorySearchView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "Memory Search"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        if !state.emu.is_emulator_loaded() {
+            return;
+        }
+
+        self.display_controls(ui, &state.emu);
+        ui.separator();
+        self.display_results(ui, &mut state.emu);
+    }
+
+
+    fn on_emulator_loaded(&mut self, _state: &mut EmulatorState) {
+        self.rt.results = None;
+    }
+}
+
+
+impl MemorySearchView {
+    /// Display the controls to configure and run the search.
+    fn display_controls(&mut self, ui: &mut Ui, emu: &EmulatorInstance) {
+        ui.horizontal(|ui| {
+            // changing the value size invalidates the current results
+            let size_before = self.value_size;
+            ui.radio_value(&mut self.value_size, MemoryValueSize::U8,  "8 bit");
+            ui.radio_value(&mut self.value_size, MemoryValueSize::U16, "16 bit");
+
+            if size_before != self.value_size {
+                self.rt.results = None;
+            }
+
+            if ui.button("Capture Baseline").clicked() {
+                if let Some(gb) = emu.get_emulator() {
+                    self.capture_baseline(gb);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt("memory_search_filter")
+                    .selected_text(self.filter.get_name())
+                    .show_ui(ui, |ui| {
+                        for filter in SearchFilter::ALL {
+                            ui.selectable_value(&mut self.filter, filter, filter.get_name());
+                        }
+                    })
+            ;
+
+            let operand = parse_value(&self.operand);
+
+            if self.filter.needs_operand() {
+                ui.add(
+                    TextEdit::singleline(&mut self.operand)
+                            .hint_text("X")
+                            .desired_width(60.0)
+                );
+            }
+
+            let can_filter =
+                    self.rt.results.is_some()
+                &&  (operand.is_some() || !self.filter.needs_operand())
+            ;
+
+            if ui.add_enabled(can_filter, egui::Button::new("Filter")).clicked() {
+                if let Some(gb) = emu.get_emulator() {
+                    self.apply_filter(gb, operand.unwrap_or(0));
+                }
+            }
+        });
+    }
+
+
+    /// Display the list of addresses matching all filters so far.
+    fn display_results(&mut self, ui: &mut Ui, emu: &mut EmulatorInstance) {
+        let Some(results) = &self.rt.results else {
+            ui.label("Capture a baseline to start searching.");
+            return;
+        };
+
+        ui.label(format!("{} addresses found", results.len()));
+
+        if results.len() > MAX_RESULTS_DISPLAYED {
+            ui.label("Apply more filters to display the results.");
+            return;
+        }
+
+        ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    Grid::new("memory_search_results")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for result in results {
+                                    let current_value = match emu.get_emulator() {
+                                        Some(gb) => read_value(gb, result.address, self.value_size),
+                                        None     => continue,
+                                    };
+
+                                    let mut is_frozen = emu.get_frozen_memory(result.address).is_some();
+
+                                    ui.label(GemiStyle::ADDRESS.rich_text(format!("{:04x}", result.address)));
+                                    ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{}", current_value)));
+                                    ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("0x{:x}", current_value)));
+
+                                    if ui.checkbox(&mut is_frozen, "freeze").changed() {
+                                        if is_frozen {
+                                            emu.freeze_memory(FrozenMemoryValue {
+                                                address: result.address,
+                                                value:   current_value,
+                                                size:    self.value_size,
+                                            });
+                                        }
+                                        else {
+                                            emu.unfreeze_memory(result.address);
+                                        }
+                                    }
+
+                                    ui.end_row();
+                                }
+                            })
+                    ;
+                })
+        ;
+    }
+
+
+    /// Captures the current values of all addresses in WRAM as the new baseline.
+    fn capture_baseline(&mut self, gb: &GameBoy) {
+        let last_address = match self.value_size {
+            MemoryValueSize::U8  => SEARCH_RANGE_END,
+            MemoryValueSize::U16 => SEARCH_RANGE_END - 1,
+        };
+
+        let results = (SEARCH_RANGE_BEGIN ..= last_address)
+                .map(|address| SearchResult {
+                    address,
+                    previous_value: read_value(gb, address, self.value_size),
+                })
+                .collect()
+        ;
+
+        self.rt.results = Some(results);
+    }
+
+
+    /// Apply the currently selected filter on the list of results.
+    fn apply_filter(&mut self, gb: &GameBoy, operand: u16) {
+        let filter = self.filter;
+        let size   = self.value_size;
+
+        if let Some(results) = &mut self.rt.results {
+            results.retain_mut(|result| {
+                let current_value = read_value(gb, result.address, size);
+                let matches       = filter.matches(current_value, result.previous_value, operand, size);
+
+                result.previous_value = current_value;
+
+                matches
+            });
+        }
+    }
+}
+
+
+/// Reads a value of the given size from the emulator's memory.
+fn read_value(gb: &GameBoy, address: u16, size: MemoryValueSize) -> u16 {
+    match size {
+        MemoryValueSize::U8  => gb.get_mmu().read_u8(address) as u16,
+        MemoryValueSize::U16 => gb.get_mmu().read_u16(address),
+    }
+}
+
+
+/// Parses a value entered by the user, either as decimal or,
+/// prefixed with '0x', as hexadecimal value.
+fn parse_value(s: &str) -> Option<u16> {
+    let s = s.trim();
+
+    match s.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None      => s.parse::<u16>().ok(),
+    }
+}
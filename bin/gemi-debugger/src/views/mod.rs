@@ -21,26 +21,38 @@ use gemi_core::ppu::graphic_data::TileMap;
 use crate::event::UiEvent;
 use crate::selection::Selected;
 use crate::state::EmulatorState;
+use crate::views::apu::ApuView;
 use crate::views::cartridge_info::CartridgeInfoView;
 use crate::views::cpu::CpuView;
 use crate::views::disassembly::DisassemblyView;
 use crate::views::display::EmulatorDisplayView;
+use crate::views::dma::DmaView;
 use crate::views::file_browser::FileBrowserView;
+use crate::views::frame_timeline::FrameTimelineView;
+use crate::views::interrupt_history::InterruptHistoryView;
 use crate::views::memory::MemoryView;
+use crate::views::memory_search::MemorySearchView;
 use crate::views::oam::OamView;
 use crate::views::palettes::PaletteView;
+use crate::views::serial_console::SerialConsoleView;
 use crate::views::snapshots::SnapshotsView;
 use crate::views::sprites::SpritesView;
 use crate::views::tilemap::TileMapView;
 
+mod apu;
 mod cartridge_info;
 mod cpu;
 mod disassembly;
 mod display;
+mod dma;
 mod file_browser;
+mod frame_timeline;
+mod interrupt_history;
 mod memory;
+mod memory_search;
 mod oam;
 mod palettes;
+mod serial_console;
 mod snapshots;
 mod sprites;
 mod tilemap;
@@ -69,6 +81,11 @@ pub trait View: serde::Serialize + serde::de::DeserializeOwned {
     fn on_emulator_loaded(&mut self, state: &mut EmulatorState) {
         _ = state;
     }
+
+    /// Invoked after the current emulator instance was reset.
+    fn on_emulator_reset(&mut self, state: &mut EmulatorState) {
+        _ = state;
+    }
 }
 
 
@@ -86,6 +103,12 @@ pub enum ViewClass {
     Palettes(PaletteView),
     Oam(OamView),
     Snapshots(SnapshotsView),
+    FrameTimeline(FrameTimelineView),
+    MemorySearch(MemorySearchView),
+    SerialConsole(SerialConsoleView),
+    Apu(ApuView),
+    InterruptHistory(InterruptHistoryView),
+    Dma(DmaView),
 }
 
 
@@ -93,7 +116,7 @@ pub type Instantiate = fn() -> ViewClass;
 
 
 impl ViewClass {
-    pub const ALL : [(&'static str, Instantiate); 13] = [
+    pub const ALL : [(&'static str, Instantiate); 19] = [
         ("Display",         ViewClass::new_display_view),
         ("CartridgeInfo",   ViewClass::new_cartridge_info),
         ("Cpu",             ViewClass::new_cpu),
@@ -107,6 +130,12 @@ impl ViewClass {
         ("TileMap #9C00",   || ViewClass::new_tile_map(TileMap::H9C00)),
         ("FileBrowser",     ViewClass::new_file_browser),
         ("Snapshots",       ViewClass::new_snapshots),
+        ("FrameTimeline",   ViewClass::new_frame_timeline),
+        ("MemorySearch",    ViewClass::new_memory_search),
+        ("SerialConsole",   ViewClass::new_serial_console),
+        ("Apu",             ViewClass::new_apu),
+        ("InterruptHistory", ViewClass::new_interrupt_history),
+        ("Dma",             ViewClass::new_dma),
     ];
     
     
@@ -174,6 +203,42 @@ impl ViewClass {
     pub fn new_snapshots() -> ViewClass {
         ViewClass::Snapshots(SnapshotsView::new())
     }
+
+
+    /// Creates a new [`FrameTimelineView`] object.
+    pub fn new_frame_timeline() -> ViewClass {
+        ViewClass::FrameTimeline(FrameTimelineView::new())
+    }
+
+
+    /// Creates a new [`MemorySearchView`] object.
+    pub fn new_memory_search() -> ViewClass {
+        ViewClass::MemorySearch(MemorySearchView::new())
+    }
+
+
+    /// Creates a new [`SerialConsoleView`] object.
+    pub fn new_serial_console() -> ViewClass {
+        ViewClass::SerialConsole(SerialConsoleView::new())
+    }
+
+
+    /// Creates a new [`ApuView`] object.
+    pub fn new_apu() -> ViewClass {
+        ViewClass::Apu(ApuView::new())
+    }
+
+
+    /// Creates a new [`InterruptHistoryView`] object.
+    pub fn new_interrupt_history() -> ViewClass {
+        ViewClass::InterruptHistory(InterruptHistoryView::new())
+    }
+
+
+    /// Creates a new [`DmaView`] object.
+    pub fn new_dma() -> ViewClass {
+        ViewClass::Dma(DmaView::new())
+    }
 }
 
 
@@ -191,6 +256,12 @@ impl View for ViewClass {
             ViewClass::Palettes(v)      => v.title(state),
             ViewClass::Oam(v)           => v.title(state),
             ViewClass::Snapshots(v)     => v.title(state),
+            ViewClass::FrameTimeline(v) => v.title(state),
+            ViewClass::MemorySearch(v)  => v.title(state),
+            ViewClass::SerialConsole(v) => v.title(state),
+            ViewClass::Apu(v)           => v.title(state),
+            ViewClass::InterruptHistory(v) => v.title(state),
+            ViewClass::Dma(v)              => v.title(state),
         }
     }
 
@@ -208,6 +279,12 @@ impl View for ViewClass {
             ViewClass::Palettes(v)      => v.ui(state, ui),
             ViewClass::Oam(v)           => v.ui(state, ui),
             ViewClass::Snapshots(v)     => v.ui(state, ui),
+            ViewClass::FrameTimeline(v) => v.ui(state, ui),
+            ViewClass::MemorySearch(v)  => v.ui(state, ui),
+            ViewClass::SerialConsole(v) => v.ui(state, ui),
+            ViewClass::Apu(v)           => v.ui(state, ui),
+            ViewClass::InterruptHistory(v) => v.ui(state, ui),
+            ViewClass::Dma(v)              => v.ui(state, ui),
         }
     }
 
@@ -225,6 +302,12 @@ impl View for ViewClass {
             ViewClass::Palettes(v)      => v.get_current_selection(),
             ViewClass::Oam(v)           => v.get_current_selection(),
             ViewClass::Snapshots(v)     => v.get_current_selection(),
+            ViewClass::FrameTimeline(v) => v.get_current_selection(),
+            ViewClass::MemorySearch(v)  => v.get_current_selection(),
+            ViewClass::SerialConsole(v) => v.get_current_selection(),
+            ViewClass::Apu(v)           => v.get_current_selection(),
+            ViewClass::InterruptHistory(v) => v.get_current_selection(),
+            ViewClass::Dma(v)              => v.get_current_selection(),
         }
     }
 
@@ -242,6 +325,12 @@ impl View for ViewClass {
             ViewClass::Palettes(v)      => v.handle_ui_event(event),
             ViewClass::Oam(v)           => v.handle_ui_event(event),
             ViewClass::Snapshots(v)     => v.handle_ui_event(event),
+            ViewClass::FrameTimeline(v) => v.handle_ui_event(event),
+            ViewClass::MemorySearch(v)  => v.handle_ui_event(event),
+            ViewClass::SerialConsole(v) => v.handle_ui_event(event),
+            ViewClass::Apu(v)           => v.handle_ui_event(event),
+            ViewClass::InterruptHistory(v) => v.handle_ui_event(event),
+            ViewClass::Dma(v)              => v.handle_ui_event(event),
         }
     }
 
@@ -259,6 +348,35 @@ impl View for ViewClass {
             ViewClass::Palettes(v)      => v.on_emulator_loaded(state),
             ViewClass::Oam(v)           => v.on_emulator_loaded(state),
             ViewClass::Snapshots(v)     => v.on_emulator_loaded(state),
+            ViewClass::FrameTimeline(v) => v.on_emulator_loaded(state),
+            ViewClass::MemorySearch(v)  => v.on_emulator_loaded(state),
+            ViewClass::SerialConsole(v) => v.on_emulator_loaded(state),
+            ViewClass::Apu(v)           => v.on_emulator_loaded(state),
+            ViewClass::InterruptHistory(v) => v.on_emulator_loaded(state),
+            ViewClass::Dma(v)              => v.on_emulator_loaded(state),
+        }
+    }
+
+
+    fn on_emulator_reset(&mut self, state: &mut EmulatorState) {
+        match self {
+            ViewClass::FileBrowser(v)   => v.on_emulator_reset(state),
+            ViewClass::Display(v)       => v.on_emulator_reset(state),
+            ViewClass::CartridgeInfo(v) => v.on_emulator_reset(state),
+            ViewClass::Cpu(v)           => v.on_emulator_reset(state),
+            ViewClass::Memory(v)        => v.on_emulator_reset(state),
+            ViewClass::Disassembly(v)   => v.on_emulator_reset(state),
+            ViewClass::TileMap(v)       => v.on_emulator_reset(state),
+            ViewClass::Sprites(v)       => v.on_emulator_reset(state),
+            ViewClass::Palettes(v)      => v.on_emulator_reset(state),
+            ViewClass::Oam(v)           => v.on_emulator_reset(state),
+            ViewClass::Snapshots(v)     => v.on_emulator_reset(state),
+            ViewClass::FrameTimeline(v) => v.on_emulator_reset(state),
+            ViewClass::MemorySearch(v)  => v.on_emulator_reset(state),
+            ViewClass::SerialConsole(v) => v.on_emulator_reset(state),
+            ViewClass::Apu(v)           => v.on_emulator_reset(state),
+            ViewClass::InterruptHistory(v) => v.on_emulator_reset(state),
+            ViewClass::Dma(v)              => v.on_emulator_reset(state),
         }
     }
 }
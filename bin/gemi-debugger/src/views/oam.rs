@@ -22,6 +22,7 @@ use egui_extras::{Column, TableBuilder, TableRow};
 use gemi_core::gameboy::GameBoy;
 use gemi_core::ppu::flags::LcdControlFlag;
 use gemi_core::ppu::graphic_data::Sprite;
+use gemi_core::ppu::ppu::SPRITES_PER_LINE;
 use gemi_core::ppu::sprite_image::SpriteImage;
 
 use crate::event::UiEvent;
@@ -102,6 +103,16 @@ impl View for OamView {
                 SPRITE_DISPLAY_SIZE
             };
 
+            // allow to disable the hardware limit of sprites per scanline for debugging
+            let ppu             = &mut emu.get_peripherals_mut().ppu;
+            let mut limit_lines = ppu.get_sprite_limit().is_some();
+
+            if ui.checkbox(&mut limit_lines, format!("Limit to {SPRITES_PER_LINE} sprites per line")).changed() {
+                ppu.set_sprite_limit(limit_lines.then_some(SPRITES_PER_LINE));
+            }
+
+            ui.separator();
+
             TableBuilder::new(ui)
                     .column(Column::exact(SPRITE_DISPLAY_SIZE)) // image
                     .column(Column::auto().resizable(true))     // tile index
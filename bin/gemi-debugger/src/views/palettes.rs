@@ -14,18 +14,23 @@
  * You should have received a copy of the GNU General Public License
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
-
 use egui::{Color32, Grid, Response, Sense, TextStyle, Ui, Vec2};
 
 use gemi_core::gameboy::GameBoy;
 use gemi_core::ppu::graphic_data::{Color, DmgDisplayPalette, DmgPalette, GbcPaletteData, SpritePixelValue};
 
-use crate::state::EmulatorState;
+use crate::event::UiEvent;
+use crate::highlight::test_selection;
+use crate::selection::{Kind, Selected};
+use crate::state::{EmulatorState, UiStates};
 use crate::ui::style::GemiStyle;
 use crate::views::View;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct PaletteView {
+    /// The currently selected palette, described by the object palette flag and its index.
+    palette_selected: Option<(bool, u8)>,
+
     #[serde(skip)]
     rt: PalettesRuntimeData,
 }
@@ -43,6 +48,7 @@ impl PaletteView {
     /// Creates a new [`PaletteView`] object.
     pub fn new() -> Self {
         Self {
+            palette_selected: None,
             rt: Default::default(),
         }
     }
@@ -58,7 +64,9 @@ impl View for PaletteView {
     fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
         self.rt.is_paused = state.ui.is_paused();
 
-        if let Some(emu) = state.emu.get_emulator_mut() {
+        let ui_states = &mut state.ui;
+
+        if let Some(emu) = state.emu.get_emulator() {
             let is_gbc = emu.get_config().is_gbc_enabled();
 
             // display all palettes within a grid
@@ -67,86 +75,188 @@ impl View for PaletteView {
                     .min_col_width(1.0)
                     .striped(true)
                     .show(ui, |ui| {
-                        self.display_dmg_palettes(ui, emu);
-
                         if is_gbc {
-                            self.display_gbc_palettes(ui, emu);
+                            self.display_gbc_palettes(ui, ui_states, emu);
+                        }
+                        else {
+                            self.display_dmg_palettes(ui, ui_states, emu);
                         }
                     }
             );
         }
     }
+
+
+    fn get_current_selection(&self) -> Option<Selected> {
+        self.palette_selected.map(|(is_object, index)| Selected::Palette(is_object, index))
+    }
+
+
+    fn handle_ui_event(&mut self, event: &UiEvent) {
+        match event {
+            UiEvent::SelectionChanged(Kind::Focus, Some(Selected::Palette(is_object, index))) => {
+                self.palette_selected = Some((*is_object, *index));
+            },
+
+            _ => { }
+        }
+    }
 }
 
 
 impl PaletteView {
     /// Display a list of all non-color palettes within the current grid.
-    fn display_dmg_palettes(&self, ui: &mut Ui, emu: &GameBoy) {
+    fn display_dmg_palettes(&mut self, ui: &mut Ui, ui_states: &mut UiStates, emu: &GameBoy) {
         let ppu      = &emu.get_peripherals().ppu;
         let palettes = ppu.get_palettes();
 
-        self.display_dmg_palette_entry(ui, "BGP",  &palettes.bgp);
-        self.display_dmg_palette_entry(ui, "OBP0", &palettes.obp[0]);
-        self.display_dmg_palette_entry(ui, "OBP1", &palettes.obp[1]);
+        self.display_dmg_palette_entry(ui, ui_states, emu, "BGP",  Selected::Palette(false, 0), &palettes.bgp);
+        self.display_dmg_palette_entry(ui, ui_states, emu, "OBP0", Selected::Palette(true,  0), &palettes.obp[0]);
+        self.display_dmg_palette_entry(ui, ui_states, emu, "OBP1", Selected::Palette(true,  1), &palettes.obp[1]);
     }
 
 
     /// Display a single non-color palette together with its name.
-    fn display_dmg_palette_entry(&self, ui: &mut Ui, name: &str, palette: &DmgPalette) {
+    fn display_dmg_palette_entry(
+        &mut self,
+        ui: &mut Ui,
+        ui_states: &mut UiStates,
+        emu: &GameBoy,
+        name: &str,
+        selection: Selected,
+        palette: &DmgPalette
+    ) {
         // The palette name
-        ui.label(GemiStyle::ADDRESS.rich_text(name));
+        self.display_palette_name(ui, ui_states, emu, name, &selection);
+
+        let mut hovered = false;
+        let mut clicked = false;
+
+        let palette_value: u8 = (*palette).into();
 
         // display the color for each possible pixel value
-        for pixel in &self.rt.pixels {
+        for (index, pixel) in self.rt.pixels.iter().enumerate() {
             let palette_color = palette.get_color(pixel);
             let color         = self.rt.dmg_display_palette.get_color(&palette_color);
+            let shade         = (palette_value >> (index * 2)) & 0x03;
+
+            let response = self.display_color_box(ui, color);
+            hovered |= response.hovered();
+            clicked |= response.clicked();
 
-            self.display_color_box(ui, color);
+            response.on_hover_ui(|ui| {
+                Self::display_color_tooltip(ui, index, &format!("shade {shade}"));
+            });
         }
 
+        self.handle_palette_response(ui_states, selection, hovered, clicked);
+
         ui.end_row();
     }
 
 
 
     /// Display all palettes of the GameBoy Color.
-    fn display_gbc_palettes(&self, ui: &mut Ui, emu: &GameBoy) {
+    fn display_gbc_palettes(&mut self, ui: &mut Ui, ui_states: &mut UiStates, emu: &GameBoy) {
         let ppu      = &emu.get_peripherals().ppu;
         let palettes = ppu.get_palettes();
         let bg       = palettes.gbc_background_palette.get();
         let obj      = palettes.gbc_object_palette.get();
 
-        ui.end_row();
-
         // background palettes 0-7
         for i in 0..8 {
-            self.display_gbc_palette_entry(ui, &format!("BCP #{i}"), &bg[i]);
+            self.display_gbc_palette_entry(ui, ui_states, emu, &format!("BCP #{i}"), Selected::Palette(false, i as u8), &bg[i]);
         }
 
         ui.end_row();
 
         // object palettes 0-7
         for i in 0..8 {
-            self.display_gbc_palette_entry(ui, &format!("OCP #{i}"), &obj[i]);
+            self.display_gbc_palette_entry(ui, ui_states, emu, &format!("OCP #{i}"), Selected::Palette(true, i as u8), &obj[i]);
         }
     }
 
 
     /// Display a single color palette together with its name.
-    fn display_gbc_palette_entry(&self, ui: &mut Ui, name: &str, palette: &GbcPaletteData) {
+    fn display_gbc_palette_entry(
+        &mut self,
+        ui: &mut Ui,
+        ui_states: &mut UiStates,
+        emu: &GameBoy,
+        name: &str,
+        selection: Selected,
+        palette: &GbcPaletteData
+    ) {
         // The palette name
-        ui.label(GemiStyle::ADDRESS.rich_text(name));
+        self.display_palette_name(ui, ui_states, emu, name, &selection);
+
+        let mut hovered = false;
+        let mut clicked = false;
 
         // display the color for each possible pixel value
-        for pixel in &self.rt.pixels {
-            let color = palette.get_color(&pixel);
-            self.display_color_box(ui, &color);
+        for (index, pixel) in self.rt.pixels.iter().enumerate() {
+            let color     = palette.get_color(&pixel);
+            let raw_color = palette.get_raw_color(&pixel);
+
+            let response = self.display_color_box(ui, &color);
+            hovered |= response.hovered();
+            clicked |= response.clicked();
+
+            response.on_hover_ui(|ui| {
+                Self::display_color_tooltip(ui, index, &format!("0x{raw_color:04x}"));
+            });
         }
 
+        self.handle_palette_response(ui_states, selection, hovered, clicked);
+
         ui.end_row();
     }
 
 
+    /// Display the name of a palette, highlighted if the palette is selected
+    /// or related to any other selected item.
+    fn display_palette_name(&self, ui: &mut Ui, ui_states: &UiStates, emu: &GameBoy, name: &str, selection: &Selected) {
+        let highlight_state = test_selection(selection.clone())
+                .of_view(self)
+                .compare_with_ui_states(ui_states, emu)
+        ;
+
+        let text = match highlight_state {
+            Some(_) => GemiStyle::ADDRESS_SELECTED.rich_text(name),
+            None    => GemiStyle::ADDRESS.rich_text(name),
+        };
+
+        ui.label(text);
+    }
+
+
+    /// Update hover and focus selection for a palette, depending on the
+    /// combined response of all of its color boxes.
+    fn handle_palette_response(&mut self, ui_states: &mut UiStates, selection: Selected, hovered: bool, clicked: bool) {
+        ui_states.hover.set(selection.clone(), hovered);
+
+        if clicked {
+            ui_states.focus.toggle(selection);
+        }
+    }
+
+
+    /// Display the tooltip of a single color within a palette.
+    fn display_color_tooltip(ui: &mut Ui, index: usize, value: &str) {
+        Grid::new("tooltip")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("index");
+                    ui.label(index.to_string());
+                    ui.end_row();
+
+                    ui.label("value");
+                    ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(value));
+                    ui.end_row();
+                })
+        ;
+    }
+
 
     /// Display a single color within a small box.
     fn display_color_box(&self, ui: &mut Ui, color: &Color) -> Response {
@@ -159,7 +269,7 @@ impl PaletteView {
             color.a
         );
 
-        let (rect, response) = ui.allocate_exact_size(Vec2::splat(text_height), Sense::hover());
+        let (rect, response) = ui.allocate_exact_size(Vec2::splat(text_height), Sense::click());
         ui.painter().rect(rect, 0.0, color32, ui.visuals().window_stroke);
 
         response
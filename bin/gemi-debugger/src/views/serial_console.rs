@@ -0,0 +1,172 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::fmt::Write;
+
+use egui::{ScrollArea, Ui};
+
+use gemi_core::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
+
+use crate::state::EmulatorState;
+use crate::ui::style::GemiStyle;
+use crate::views::View;
+
+
+/// The number of bytes displayed in a single line in hex mode.
+const HEX_BYTES_PER_LINE: usize = 16;
+
+
+/// Determines how data received via serial port will be displayed.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SerialConsoleMode {
+    /// Each byte is interpreted as an ASCII character.
+    Ascii,
+
+    /// Each byte is displayed as hexadecimal value.
+    Hex,
+}
+
+
+/// A view to display all data sent by the running program via serial port,
+/// for example the results of test ROMs.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SerialConsoleView {
+    /// How to display the received data.
+    display_mode: SerialConsoleMode,
+
+    #[serde(skip)]
+    rt: SerialConsoleRuntimeData,
+}
+
+
+/// Internal data of the [SerialConsoleView], which does not get serialized.
+#[derive(Default)]
+struct SerialConsoleRuntimeData {
+    /// All data received since the console was cleared the last time.
+    data: Vec<u8>,
+}
+
+
+impl SerialConsoleView {
+    /// Creates a new [`SerialConsoleView`] object.
+    pub fn new() -> Self {
+        Self {
+            display_mode: SerialConsoleMode::Ascii,
+            rt:           Default::default(),
+        }
+    }
+}
+
+
+impl View for SerialConsoleView {
+    fn title(&self, _state: &mut EmulatorState) -> &str {
+        "Serial Console"
+    }
+
+
+    fn ui(&mut self, state: &mut EmulatorState, ui: &mut Ui) {
+        let Some(emu) = state.emu.get_emulator_mut() else {
+            return;
+        };
+
+        let serial = &mut emu.get_peripherals_mut().serial;
+
+        // enable the output queue as long as this view exists
+        if !serial.is_output_queue_enabled() {
+            serial.enable_output_queue(true);
+        }
+
+        // drain the output received since the last frame
+        self.rt.data.append(&mut serial.take_output());
+
+        let transferred_bytes = serial.get_transferred_bytes();
+        let sb                = emu.get_mmu().read_u8(MEMORY_LOCATION_SB);
+        let sc                = emu.get_mmu().read_u8(MEMORY_LOCATION_SC);
+
+        ui.horizontal(|ui| {
+            ui.label("SB");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:02x}", sb)));
+            ui.separator();
+            ui.label("SC");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{:02x}", sc)));
+            ui.separator();
+            ui.label("Transferred");
+            ui.label(GemiStyle::VALUE_READ_ONLY.rich_text(format!("{} bytes", transferred_bytes)));
+        });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.display_mode, SerialConsoleMode::Ascii, "ASCII");
+            ui.radio_value(&mut self.display_mode, SerialConsoleMode::Hex,   "Hex");
+
+            ui.separator();
+
+            if ui.button("Clear").clicked() {
+                self.rt.data.clear();
+            }
+
+            if ui.button("Copy").clicked() {
+                ui.ctx().copy_text(self.get_text());
+            }
+        });
+
+        ui.separator();
+
+        let text = self.get_text();
+
+        ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    ui.label(GemiStyle::MONOSPACE.rich_text(text));
+                })
+        ;
+    }
+
+
+    fn on_emulator_loaded(&mut self, _state: &mut EmulatorState) {
+        self.rt.data.clear();
+    }
+}
+
+
+impl SerialConsoleView {
+    /// Get the received data as text according to the current display mode.
+    fn get_text(&self) -> String {
+        match self.display_mode {
+            SerialConsoleMode::Ascii => {
+                self.rt.data
+                    .iter()
+                    .map(|b| *b as char)
+                    .collect()
+            }
+
+            SerialConsoleMode::Hex => {
+                let mut text = String::new();
+
+                for line in self.rt.data.chunks(HEX_BYTES_PER_LINE) {
+                    for b in line {
+                        _ = write!(text, "{:02x} ", b);
+                    }
+
+                    text.push('\n');
+                }
+
+                text
+            }
+        }
+    }
+}
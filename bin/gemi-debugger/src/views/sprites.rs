@@ -29,6 +29,7 @@ use crate::selection::{Kind, Selected};
 use crate::state::{EmulatorState, UiStates};
 use crate::ui::draw_tile::DrawTile;
 use crate::ui::style::GemiStyle;
+use crate::ui::tile_images::TileImageCache;
 use crate::views::View;
 
 const TOTAL_SPRITES : usize     = 384;
@@ -39,6 +40,10 @@ const SPRITE_DISPLAY_SIZE : f32 = 64.0;
 pub struct SpritesView {
     bank_index: u8,
     sprite_selected: Option<usize>,
+
+    /// Images of all sprites, which are only read again when modified.
+    #[serde(skip)]
+    tile_images: TileImageCache,
 }
 
 
@@ -47,6 +52,7 @@ impl SpritesView {
         Self {
             bank_index,
             sprite_selected: None,
+            tile_images: TileImageCache::new(),
         }
     }
 }
@@ -72,6 +78,8 @@ impl View for SpritesView {
                 return;
             }
 
+            self.tile_images.validate(emu);
+
             let scroll_area = ScrollArea::vertical()
                     .id_salt("sprites_scroll_area")
                     .auto_shrink([false, false])
@@ -154,7 +162,7 @@ impl SpritesView {
     /// Display a single sprite within the grid.
     fn display_sprite(&mut self, ui: &mut Ui, ui_states: &mut UiStates, emu: &GameBoy, sprite_index: usize, bank_index: u8) {
         let ppu     = &emu.get_peripherals().ppu;
-        let sprite  = ppu.get_sprite_image(sprite_index, bank_index);
+        let sprite  = self.tile_images.get_image(ppu, sprite_index, bank_index).clone();
 
         let highlight_state = test_selection(Selected::Sprite(self.bank_index, sprite_index))
                 .of_view(self)
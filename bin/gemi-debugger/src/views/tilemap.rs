@@ -32,6 +32,7 @@ use crate::selection::{Kind, Selected};
 use crate::state::{EmulatorState, UiStates};
 use crate::ui::draw_tile::DrawTile;
 use crate::ui::style::GemiStyle;
+use crate::ui::tile_images::TileImageCache;
 use crate::views::View;
 
 const TILE_ROWS: usize      = 32;
@@ -47,6 +48,10 @@ pub struct TileMapView {
     tilemap: TileMap,
 
     tile_selected: Option<(bool, usize)>,
+
+    /// Images of all tiles, which are only read again when modified.
+    #[serde(skip)]
+    tile_images: TileImageCache,
 }
 
 
@@ -55,6 +60,7 @@ impl TileMapView {
         Self {
             tilemap,
             tile_selected: None,
+            tile_images: TileImageCache::new(),
         }
     }
 }
@@ -74,6 +80,7 @@ impl View for TileMapView {
             None => {}
 
             Some(emu) => {
+                self.tile_images.validate(emu);
                 self.render_tilemap(ui, emu, &mut state.ui);
             }
         }
@@ -98,7 +105,7 @@ impl View for TileMapView {
 
 
 impl TileMapView {
-    fn render_tilemap(&self, ui: &mut Ui, emu: &GameBoy, ui_states: &mut UiStates) {
+    fn render_tilemap(&mut self, ui: &mut Ui, emu: &GameBoy, ui_states: &mut UiStates) {
         let scale = DEFAULT_SCALE as f32;
 
         // compute the size of a tile how it will be displayed
@@ -129,7 +136,7 @@ impl TileMapView {
 
 
     /// Renders the whole tilemap as a 32x32 grid.
-    fn render_tilemap_grid(&self, ui: &mut Ui, emu: &GameBoy, ui_states: &mut UiStates) {
+    fn render_tilemap_grid(&mut self, ui: &mut Ui, emu: &GameBoy, ui_states: &mut UiStates) {
         let ppu     = &emu.get_peripherals().ppu;
         let vram0   = ppu.get_vram(0);
         let tileset = TileSet::by_select_bit(ppu.check_lcdc(LcdControlFlag::TileDataSelect));
@@ -146,7 +153,7 @@ impl TileMapView {
                     let vram1            = ppu.get_vram(1);
                     let tile_attributes  = vram1[tilemap_field_vram_offset];
                     let tile_image_bank  = get_bit(tile_attributes, TILE_ATTR_BIT_VRAM_BANK) as u8;
-                    let tile_image       = ppu.get_sprite_image(tile_image_index, tile_image_bank);
+                    let tile_image       = self.tile_images.get_image(ppu, tile_image_index, tile_image_bank).clone();
                     let palette_index    = (tile_attributes & 0b0000_0111) as usize;
                     let palette          = ppu.get_palettes().gbc_background_palette.get()[palette_index];
 
@@ -155,7 +162,7 @@ impl TileMapView {
                             .set_palette_gbc(palette)
                 }
                 else {
-                    let tile_image       = ppu.get_sprite_image(tile_image_index, 0);
+                    let tile_image       = self.tile_images.get_image(ppu, tile_image_index, 0).clone();
                     let palette          = &ppu.get_palettes().bgp;
 
                     DrawTile::from(tile_image)
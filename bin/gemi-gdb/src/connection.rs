@@ -0,0 +1,174 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+
+/// The byte sent by GDB to interrupt the running target.
+const INTERRUPT: u8 = 0x03;
+
+
+/// A packet received from the GDB client.
+pub enum Packet {
+    /// A command packet with its payload.
+    Command(String),
+
+    /// The client requested to interrupt the running target.
+    Interrupt,
+}
+
+
+/// A connection to a GDB client, which handles the framing of
+/// packets according to the remote serial protocol.
+/// Each packet is sent as `$<data>#<checksum>` and acknowledged with `+`,
+/// or `-` if the checksum did not match.
+pub struct Connection {
+    /// The TCP stream connected to the client.
+    stream: TcpStream,
+
+    /// The last packet sent, which will be sent again if the client requests it.
+    last_packet: Vec<u8>,
+}
+
+
+impl Connection {
+    /// Creates a new connection for a TCP stream.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            last_packet: Vec::new(),
+        })
+    }
+
+
+    /// Waits for the next packet to be received.
+    /// Returns `None` when the client closed the connection.
+    pub fn read_packet(&mut self) -> io::Result<Option<Packet>> {
+        loop {
+            let Some(byte) = self.read_byte()? else {
+                return Ok(None);
+            };
+
+            match byte {
+                INTERRUPT => {
+                    return Ok(Some(Packet::Interrupt));
+                }
+
+                b'$' => {
+                    if let Some(data) = self.read_packet_data()? {
+                        return Ok(Some(Packet::Command(data)));
+                    }
+                }
+
+                // the client requested to send the last packet again
+                b'-' => {
+                    self.stream.write_all(&self.last_packet)?;
+                }
+
+                // ignore acknowledgements and any unexpected data
+                _ => { }
+            }
+        }
+    }
+
+
+    /// Sends a packet with the given payload to the client.
+    pub fn send_packet(&mut self, data: &str) -> io::Result<()> {
+        let checksum = compute_checksum(data.as_bytes());
+
+        self.last_packet = format!("${data}#{checksum:02x}").into_bytes();
+        self.stream.write_all(&self.last_packet)?;
+        self.stream.flush()
+    }
+
+
+    /// Checks whether the client sent an interrupt request without blocking.
+    /// Any other data received in the meantime will be discarded.
+    pub fn has_interrupt(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+
+        let mut buffer      = [0u8; 64];
+        let mut interrupted = false;
+
+        let result = loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0)  => break Err(io::Error::from(ErrorKind::UnexpectedEof)),
+                Ok(n)  => interrupted |= buffer[..n].contains(&INTERRUPT),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(interrupted),
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.stream.set_nonblocking(false)?;
+
+        result
+    }
+
+
+    /// Reads the data of a packet after receiving the start marker.
+    /// Returns `None` if the checksum did not match.
+    fn read_packet_data(&mut self) -> io::Result<Option<String>> {
+        let mut data = Vec::new();
+
+        loop {
+            match self.read_byte()? {
+                Some(b'#') => break,
+                Some(byte) => data.push(byte),
+                None       => return Err(io::Error::from(ErrorKind::UnexpectedEof)),
+            }
+        }
+
+        let mut checksum = [0u8; 2];
+        self.stream.read_exact(&mut checksum)?;
+
+        let expected = std::str::from_utf8(&checksum)
+            .ok()
+            .and_then(|s| u8::from_str_radix(s, 16).ok())
+        ;
+
+        if expected == Some(compute_checksum(&data)) {
+            self.stream.write_all(b"+")?;
+            Ok(Some(String::from_utf8_lossy(&data).to_string()))
+        }
+        else {
+            self.stream.write_all(b"-")?;
+            Ok(None)
+        }
+    }
+
+
+    /// Reads a single byte from the stream.
+    /// Returns `None` when the client closed the connection.
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0u8; 1];
+
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
+
+
+/// Computes the checksum of a packet, which is the sum of all bytes modulo 256.
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::env;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use gemi_core::cartridge::Cartridge;
+use gemi_core::gameboy::{DeviceType, GameBoy};
+
+use crate::connection::Connection;
+use crate::stub::GdbStub;
+
+mod connection;
+mod stub;
+
+
+/// The default TCP port to listen for GDB connections.
+const DEFAULT_PORT: u16 = 2345;
+
+
+/// Creates the emulator instance and gets the port to listen on from the commandline arguments.
+fn parse_arguments() -> Result<(GameBoy, u16), String> {
+    let mut args    = env::args();
+    let mut builder = GameBoy::build();
+    let mut port    = DEFAULT_PORT;
+
+    // skip first argument, which is the executable name
+    _ = args.next();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                port = args.next()
+                    .and_then(|port| port.parse().ok())
+                    .ok_or_else(|| String::from("'--port' needs to be followed by a valid port number"))
+                    ?
+                ;
+            }
+
+            "--dmg" => {
+                builder.set_device_type(DeviceType::GameBoyDmg);
+            }
+
+            "--gbc" => {
+                builder.set_device_type(DeviceType::GameBoyColor);
+            }
+
+            "--allow-invalid-header" => {
+                builder.set_allow_invalid_header(true);
+            }
+
+            _ => {
+                let file = PathBuf::from(arg);
+                let cart = Cartridge::load_files_with_default_ram(&file)
+                    .map_err(|e| format!("Failed to load cartridge: {}", e))
+                    ?;
+
+                builder.set_cartridge(cart);
+            }
+        }
+    }
+
+    let mut gb = builder.finish()
+        .map_err(|e| e.to_string())
+        ?
+    ;
+
+    gb.initialize();
+
+    Ok((gb, port))
+}
+
+
+fn main() -> Result<(), String> {
+    let (gb, port) = parse_arguments()?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {port}: {e}"))
+        ?
+    ;
+
+    println!("Waiting for GDB to connect on port {port}");
+
+    let (stream, address) = listener.accept()
+        .map_err(|e| format!("Failed to accept connection: {e}"))
+        ?
+    ;
+
+    println!("Connected to {address}");
+
+    let mut connection = Connection::new(stream)
+        .map_err(|e| e.to_string())
+        ?
+    ;
+
+    let mut stub = GdbStub::new(gb);
+    stub.run(&mut connection)
+        .map_err(|e| format!("Connection failed: {e}"))
+        ?
+    ;
+
+    println!("Connection closed");
+
+    Ok(())
+}
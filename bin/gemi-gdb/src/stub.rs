@@ -0,0 +1,403 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::io;
+
+use gemi_core::cpu::cpu::RegisterR8;
+use gemi_core::debug::DebugEvent;
+use gemi_core::gameboy::GameBoy;
+
+use crate::connection::{Connection, Packet};
+
+
+/// Stop reply sent when the target stopped because of a breakpoint or a single step (SIGTRAP).
+const STOP_REPLY_TRAP: &str = "S05";
+
+/// Stop reply sent when the target was interrupted by the client (SIGINT).
+const STOP_REPLY_INTERRUPTED: &str = "S02";
+
+/// The number of frames to run before checking for an interrupt request of the client.
+const FRAMES_PER_INTERRUPT_CHECK: usize = 8;
+
+/// The 8 bit registers in the order used by the register packets.
+/// These are followed by the 16 bit registers SP and PC.
+const REGISTERS_R8: [RegisterR8; 8] = [
+    RegisterR8::A,
+    RegisterR8::F,
+    RegisterR8::B,
+    RegisterR8::C,
+    RegisterR8::D,
+    RegisterR8::E,
+    RegisterR8::H,
+    RegisterR8::L,
+];
+
+/// Register index of the stack pointer.
+const REGISTER_SP: usize = 8;
+
+/// Register index of the program counter.
+const REGISTER_PC: usize = 9;
+
+
+/// What to do after handling a command.
+enum Response {
+    /// Send a reply to the client.
+    Reply(String),
+
+    /// End the session, optionally sending a final reply.
+    Close(Option<&'static str>),
+}
+
+
+/// A stub implementing the GDB remote serial protocol on top of an emulator instance.
+pub struct GdbStub {
+    gb: GameBoy,
+}
+
+
+impl GdbStub {
+    /// Creates a new stub to debug the given emulator instance.
+    pub fn new(gb: GameBoy) -> Self {
+        Self {
+            gb,
+        }
+    }
+
+
+    /// Handles commands received from the client until the session ends.
+    pub fn run(&mut self, connection: &mut Connection) -> io::Result<()> {
+        while let Some(packet) = connection.read_packet()? {
+            let command = match packet {
+                Packet::Command(command) => command,
+
+                // the target is already halted, so just report the stop
+                Packet::Interrupt => {
+                    connection.send_packet(STOP_REPLY_INTERRUPTED)?;
+                    continue;
+                }
+            };
+
+            match self.handle_command(&command, connection)? {
+                Response::Reply(reply) => {
+                    connection.send_packet(&reply)?;
+                }
+
+                Response::Close(reply) => {
+                    if let Some(reply) = reply {
+                        connection.send_packet(reply)?;
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+
+    /// Handles a single command and creates the response to be sent.
+    fn handle_command(&mut self, command: &str, connection: &mut Connection) -> io::Result<Response> {
+        let reply = match command.split_at(command.len().min(1)) {
+            ("?", _)    => STOP_REPLY_TRAP.to_string(),
+            ("g", _)    => self.read_registers(),
+            ("G", args) => self.write_registers(args),
+            ("p", args) => self.read_register(args),
+            ("P", args) => self.write_register(args),
+            ("m", args) => self.read_memory(args),
+            ("M", args) => self.write_memory(args),
+            ("c", args) => self.resume(args, connection)?,
+            ("s", args) => self.step(args),
+            ("Z", args) => self.set_breakpoint(args, true),
+            ("z", args) => self.set_breakpoint(args, false),
+            ("H", _)    => String::from("OK"),
+            ("q", args) => self.query(args),
+            ("D", _)    => return Ok(Response::Close(Some("OK"))),
+            ("k", _)    => return Ok(Response::Close(None)),
+
+            // an empty reply tells the client the command is not supported
+            _ => String::new(),
+        };
+
+        Ok(Response::Reply(reply))
+    }
+
+
+    /// Handles general query packets.
+    fn query(&self, args: &str) -> String {
+        match args.split(':').next().unwrap_or_default() {
+            "Supported"     => String::from("PacketSize=1000"),
+            "Attached"      => String::from("1"),
+            "C"             => String::from("QC1"),
+            "fThreadInfo"   => String::from("m1"),
+            "sThreadInfo"   => String::from("l"),
+            _               => String::new(),
+        }
+    }
+
+
+    /// Get the value of a register by its index within the register packet.
+    fn get_register(&self, index: usize) -> Option<u16> {
+        match index {
+            REGISTER_SP => Some(self.gb.cpu.get_stack_pointer()),
+            REGISTER_PC => Some(self.gb.cpu.get_instruction_pointer()),
+            _           => REGISTERS_R8.get(index).map(|r| self.gb.cpu.get_r8(*r) as u16),
+        }
+    }
+
+
+    /// Set the value of a register by its index within the register packet.
+    fn set_register(&mut self, index: usize, value: u16) -> bool {
+        match index {
+            REGISTER_SP => self.gb.cpu.set_stack_pointer(value),
+            REGISTER_PC => self.gb.cpu.set_instruction_pointer(value),
+
+            _ => match REGISTERS_R8.get(index) {
+                // the lower 4 bits of the flags register are always zero
+                Some(RegisterR8::F) => self.gb.cpu.set_r8(RegisterR8::F, (value as u8) & 0xf0),
+                Some(register)      => self.gb.cpu.set_r8(*register, value as u8),
+                None                => return false,
+            }
+        }
+
+        true
+    }
+
+
+    /// Get the size of a register in bytes by its index within the register packet.
+    fn get_register_size(index: usize) -> usize {
+        match index {
+            REGISTER_SP | REGISTER_PC => 2,
+            _                         => 1,
+        }
+    }
+
+
+    /// Reads all registers: A, F, B, C, D, E, H, L, SP, PC.
+    /// 16 bit registers are encoded in little endian byte order.
+    fn read_registers(&self) -> String {
+        (0 ..= REGISTER_PC)
+            .map(|index| encode_register(self.get_register(index).unwrap_or_default(), Self::get_register_size(index)))
+            .collect()
+    }
+
+
+    /// Writes all registers from a hex string in the same layout as [Self::read_registers].
+    fn write_registers(&mut self, args: &str) -> String {
+        let Some(bytes) = decode_hex(args) else {
+            return error_reply();
+        };
+
+        let mut offset = 0;
+
+        for index in 0 ..= REGISTER_PC {
+            let size = Self::get_register_size(index);
+
+            let Some(value) = bytes.get(offset .. offset + size).map(decode_register) else {
+                return error_reply();
+            };
+
+            self.set_register(index, value);
+            offset += size;
+        }
+
+        String::from("OK")
+    }
+
+
+    /// Reads a single register: `p<index>`
+    fn read_register(&self, args: &str) -> String {
+        let index = usize::from_str_radix(args, 16).ok();
+
+        match index.and_then(|index| self.get_register(index).map(|value| (index, value))) {
+            Some((index, value)) => encode_register(value, Self::get_register_size(index)),
+            None                 => error_reply(),
+        }
+    }
+
+
+    /// Writes a single register: `P<index>=<value>`
+    fn write_register(&mut self, args: &str) -> String {
+        let parsed = args
+            .split_once('=')
+            .and_then(|(index, value)| Some((usize::from_str_radix(index, 16).ok()?, decode_hex(value)?)))
+        ;
+
+        match parsed {
+            Some((index, bytes)) if bytes.len() == Self::get_register_size(index) => {
+                if self.set_register(index, decode_register(&bytes)) {
+                    String::from("OK")
+                }
+                else {
+                    error_reply()
+                }
+            }
+
+            _ => error_reply(),
+        }
+    }
+
+
+    /// Reads memory: `m<address>,<length>`
+    fn read_memory(&self, args: &str) -> String {
+        let Some((address, length)) = parse_address_and_length(args) else {
+            return error_reply();
+        };
+
+        (0..length)
+            .map(|offset| address.wrapping_add(offset as u16))
+            .map(|address| format!("{:02x}", self.gb.get_mmu().read_u8(address)))
+            .collect()
+    }
+
+
+    /// Writes memory: `M<address>,<length>:<data>`
+    fn write_memory(&mut self, args: &str) -> String {
+        let parsed = args
+            .split_once(':')
+            .and_then(|(location, data)| Some((parse_address_and_length(location)?, decode_hex(data)?)))
+        ;
+
+        match parsed {
+            Some(((address, length), data)) if data.len() == length => {
+                for (offset, value) in data.into_iter().enumerate() {
+                    self.gb.get_mmu_mut().write_u8(address.wrapping_add(offset as u16), value);
+                }
+
+                String::from("OK")
+            }
+
+            _ => error_reply(),
+        }
+    }
+
+
+    /// Sets the program counter, if an address was passed to a `c` or `s` command.
+    fn apply_resume_address(&mut self, args: &str) {
+        if let Ok(address) = u16::from_str_radix(args, 16) {
+            self.gb.cpu.set_instruction_pointer(address);
+        }
+    }
+
+
+    /// Continues running the emulator until a breakpoint is reached
+    /// or the client requests to interrupt: `c[address]`
+    fn resume(&mut self, args: &str, connection: &mut Connection) -> io::Result<String> {
+        self.apply_resume_address(args);
+
+        loop {
+            for _ in 0..FRAMES_PER_INTERRUPT_CHECK {
+                let results = self.gb.run_frame();
+
+                if results.events.contains(DebugEvent::BreakpointReached) {
+                    return Ok(STOP_REPLY_TRAP.to_string());
+                }
+            }
+
+            if connection.has_interrupt()? {
+                return Ok(STOP_REPLY_INTERRUPTED.to_string());
+            }
+        }
+    }
+
+
+    /// Executes a single instruction: `s[address]`
+    fn step(&mut self, args: &str) -> String {
+        self.apply_resume_address(args);
+        self.gb.run_single_step();
+
+        STOP_REPLY_TRAP.to_string()
+    }
+
+
+    /// Adds or removes a breakpoint: `Z<type>,<address>,<kind>` or `z<type>,<address>,<kind>`
+    /// Software and hardware breakpoints are both supported, watchpoints are not.
+    fn set_breakpoint(&mut self, args: &str, enabled: bool) -> String {
+        let mut parts = args.split(',');
+
+        let breakpoint_type = parts.next();
+        let address         = parts.next().and_then(|address| u16::from_str_radix(address, 16).ok());
+
+        match (breakpoint_type, address) {
+            (Some("0") | Some("1"), Some(address)) => {
+                let breakpoints = self.gb.get_breakpoints_mut();
+
+                if enabled {
+                    breakpoints.add(address);
+                }
+                else {
+                    breakpoints.remove(address);
+                }
+
+                String::from("OK")
+            }
+
+            (Some("0") | Some("1"), None) => error_reply(),
+
+            // unsupported breakpoint type
+            _ => String::new(),
+        }
+    }
+}
+
+
+/// The reply sent for any invalid request.
+fn error_reply() -> String {
+    String::from("E01")
+}
+
+
+/// Parses the `<address>,<length>` arguments of memory commands.
+fn parse_address_and_length(args: &str) -> Option<(u16, usize)> {
+    let (address, length) = args.split_once(',')?;
+
+    Some((
+        u16::from_str_radix(address, 16).ok()?,
+        usize::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+
+/// Encodes a register value with the given size in bytes as hex string in little endian order.
+fn encode_register(value: u16, size: usize) -> String {
+    value.to_le_bytes()[..size]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+
+/// Decodes a register value from its bytes in little endian order.
+fn decode_register(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u16, |value, byte| (value << 8) | (*byte as u16))
+}
+
+
+/// Decodes a string of hex digits into bytes.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index .. index + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use gemi_core::cartridge::Cartridge;
+use gemi_core::gameboy::{DeviceType, GameBoy};
+
+
+/// The default number of frames to run, which is roughly one minute on the original hardware.
+const DEFAULT_FRAMES: u64 = 3600;
+
+/// Text sent by test ROMs via serial port when all tests passed.
+const TEXT_PASSED: &str = "Passed";
+
+/// Text sent by test ROMs via serial port when any test failed.
+const TEXT_FAILED: &str = "Failed";
+
+
+/// Options to run the emulator with, taken from the commandline arguments.
+struct HeadlessOptions {
+    /// The ROM file to be executed.
+    rom: Option<PathBuf>,
+
+    /// The maximum number of frames to run.
+    frames: u64,
+
+    /// Whether to print the serial output to stdout.
+    serial_output: bool,
+
+    /// A file to store a screenshot after running all frames.
+    screenshot: Option<PathBuf>,
+
+    /// Optional device type to run the ROM on.
+    device_type: Option<DeviceType>,
+
+    /// Whether to accept cartridges with an invalid header.
+    allow_invalid_header: bool,
+}
+
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            rom:                  None,
+            frames:               DEFAULT_FRAMES,
+            serial_output:        false,
+            screenshot:           None,
+            device_type:          None,
+            allow_invalid_header: false,
+        }
+    }
+}
+
+
+/// Reads the options from the commandline arguments.
+fn parse_arguments() -> Result<HeadlessOptions, String> {
+    let mut args    = env::args();
+    let mut options = HeadlessOptions::default();
+
+    // skip first argument, which is the executable name
+    _ = args.next();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rom" => {
+                let file = args.next()
+                    .ok_or_else(|| String::from("'--rom' needs to be followed by the path to a ROM file"))
+                    ?
+                ;
+
+                options.rom = Some(PathBuf::from(file));
+            }
+
+            "--frames" => {
+                options.frames = args.next()
+                    .and_then(|frames| frames.parse().ok())
+                    .ok_or_else(|| String::from("'--frames' needs to be followed by the number of frames to run"))
+                    ?
+                ;
+            }
+
+            "--serial-output" => {
+                options.serial_output = true;
+            }
+
+            "--screenshot" => {
+                let file = args.next()
+                    .ok_or_else(|| String::from("'--screenshot' needs to be followed by the path of the image file"))
+                    ?
+                ;
+
+                options.screenshot = Some(PathBuf::from(file));
+            }
+
+            "--dmg" => {
+                options.device_type = Some(DeviceType::GameBoyDmg);
+            }
+
+            "--gbc" => {
+                options.device_type = Some(DeviceType::GameBoyColor);
+            }
+
+            "--allow-invalid-header" => {
+                options.allow_invalid_header = true;
+            }
+
+            _ => {
+                return Err(format!("Unknown argument '{arg}'"));
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+
+/// Creates the emulator instance running the ROM selected by the options.
+fn create_gameboy(options: &HeadlessOptions) -> Result<GameBoy, String> {
+    let rom_file = options.rom.as_ref()
+        .ok_or_else(|| String::from("No ROM file specified, use '--rom <path>'"))
+        ?
+    ;
+
+    let cartridge = Cartridge::load_file(rom_file)
+        .map_err(|e| format!("Failed to load cartridge: {e}"))
+        ?
+    ;
+
+    let mut builder = GameBoy::build();
+    builder.set_cartridge(cartridge);
+    builder.set_allow_invalid_header(options.allow_invalid_header);
+
+    if let Some(device_type) = options.device_type {
+        builder.set_device_type(device_type);
+    }
+
+    let mut gb = builder.finish()
+        .map_err(|e| e.to_string())
+        ?
+    ;
+
+    gb.initialize();
+    gb.get_peripherals_mut().serial.enable_output_queue(true);
+
+    Ok(gb)
+}
+
+
+/// Runs the emulator until the frame limit was reached or the ROM reported its result
+/// via serial port. Returns the whole serial output.
+fn run(gb: &mut GameBoy, options: &HeadlessOptions) -> String {
+    let mut output = String::new();
+
+    for _ in 0..options.frames {
+        gb.run_frame();
+
+        let text = gb.get_peripherals_mut().serial.take_output_as_text();
+
+        if !text.is_empty() {
+            if options.serial_output {
+                print!("{text}");
+                _ = std::io::stdout().flush();
+            }
+
+            output.push_str(&text);
+        }
+
+        // stop as soon as the ROM has reported its result
+        if output.contains(TEXT_PASSED) || output.contains(TEXT_FAILED) {
+            break;
+        }
+    }
+
+    if options.serial_output && !output.is_empty() && !output.ends_with('\n') {
+        println!();
+    }
+
+    output
+}
+
+
+fn main() -> ExitCode {
+    let result = parse_arguments()
+        .and_then(|options| {
+            let mut gb = create_gameboy(&options)?;
+            let output = run(&mut gb, &options);
+
+            if let Some(screenshot) = &options.screenshot {
+                gb.save_screenshot(screenshot)
+                    .map_err(|e| format!("Failed to save screenshot to {}: {e}", screenshot.display()))
+                    ?
+                ;
+            }
+
+            if output.is_empty() {
+                return Err(format!("No serial output received within {} frames", options.frames));
+            }
+
+            if output.contains(TEXT_FAILED) {
+                return Err(String::from("Test failed"));
+            }
+
+            Ok(())
+        })
+    ;
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use gemi_core::cartridge::{Cartridge, FILE_EXT_RAM};
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::memory_data::MemoryData;
+
+
+/// The interval to save the cartridge RAM, if it was modified.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+
+/// Keeps the battery powered RAM of a cartridge in sync with its file
+/// located next to the ROM file, named like `<rom>.sav`.
+pub struct BatterySave {
+    /// The file to store the cartridge RAM.
+    ram_file: PathBuf,
+
+    /// The RAM contents which were last loaded or saved, to detect modifications.
+    saved_data: Vec<u8>,
+
+    /// Whether to save the RAM periodically while running.
+    autosave: bool,
+
+    /// The time when to check for modifications next.
+    next_autosave: Instant,
+}
+
+
+impl BatterySave {
+    /// Creates a new battery save handler for the cartridge of the emulator.
+    /// Returns `None`, if there is no cartridge with battery powered RAM,
+    /// which was loaded from a file.
+    pub fn new(gb: &GameBoy, autosave: bool) -> Option<Self> {
+        let cartridge = gb.get_peripherals().mem.get_cartridge()?;
+
+        if !cartridge.has_ram() || !cartridge.has_battery() {
+            return None;
+        }
+
+        let ram_file = cartridge.get_source_file()?.with_extension(FILE_EXT_RAM);
+
+        Some(Self {
+            ram_file,
+            saved_data:     cartridge.get_ram().to_vec(),
+            autosave,
+            next_autosave:  Instant::now() + AUTOSAVE_INTERVAL,
+        })
+    }
+
+
+    /// Checks whether the cartridge RAM was modified since it was last loaded or saved.
+    fn is_dirty(&self, gb: &GameBoy) -> bool {
+        match gb.get_peripherals().mem.get_cartridge() {
+            Some(cartridge) => cartridge.get_ram().as_slice() != self.saved_data.as_slice(),
+            None            => false,
+        }
+    }
+
+
+    /// Saves the cartridge RAM, if autosave is enabled, the autosave interval
+    /// has passed and the RAM was modified since it was saved the last time.
+    pub fn update(&mut self, gb: &GameBoy) {
+        if !self.autosave || Instant::now() < self.next_autosave {
+            return;
+        }
+
+        self.next_autosave = Instant::now() + AUTOSAVE_INTERVAL;
+
+        if self.is_dirty(gb) {
+            if let Err(error) = self.save(gb) {
+                eprintln!("{error}");
+            }
+        }
+    }
+
+
+    /// Saves the cartridge RAM into its file.
+    pub fn save(&mut self, gb: &GameBoy) -> Result<(), String> {
+        if let Some(cartridge) = gb.get_peripherals().mem.get_cartridge() {
+            let ram = cartridge.get_ram();
+
+            ram.save_to_file(&self.ram_file)
+                .map_err(|e| format!("Failed to save cartridge RAM to {}: {e}", self.ram_file.display()))
+                ?
+            ;
+
+            self.saved_data = ram.to_vec();
+
+            println!("Cartridge RAM saved to {}", self.ram_file.display());
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Loads the battery powered RAM of a cartridge from its file located next to the ROM file.
+/// Files with a different size than the cartridge RAM will be loaded as far as possible,
+/// to be able to use files created by other emulators.
+pub fn load_battery_ram(cartridge: &mut Cartridge) -> Result<(), String> {
+    if !cartridge.has_ram() || !cartridge.has_battery() {
+        return Ok(());
+    }
+
+    let ram_file = match cartridge.get_source_file() {
+        Some(rom_file) => rom_file.with_extension(FILE_EXT_RAM),
+        None           => return Ok(()),
+    };
+
+    if !ram_file.exists() {
+        return Ok(());
+    }
+
+    let data = std::fs::read(&ram_file)
+        .map_err(|e| format!("Failed to load cartridge RAM from {}: {e}", ram_file.display()))
+        ?
+    ;
+
+    let ram    = cartridge.get_ram_mut().as_slice_mut();
+    let length = data.len().min(ram.len());
+
+    if data.len() != ram.len() {
+        eprintln!(
+            "Size of {} does not match the cartridge RAM: {} bytes (expected: {} bytes)",
+            ram_file.display(),
+            data.len(),
+            ram.len()
+        );
+    }
+
+    ram[..length].copy_from_slice(&data[..length]);
+
+    println!("Cartridge RAM loaded from {}", ram_file.display());
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use sdl2::controller::{Axis, GameController};
+use sdl2::event::Event;
+use sdl2::{GameControllerSubsystem, Sdl};
+
+use crate::key_config::ControllerInput;
+
+
+/// Manages all game controllers connected and translates their events
+/// into the pressed state of [ControllerInput] values.
+pub struct Controllers {
+    /// The SDL subsystem to open game controllers.
+    subsystem: GameControllerSubsystem,
+
+    /// All game controllers currently opened.
+    controllers: Vec<GameController>,
+
+    /// The value an axis needs to exceed to be recognized as pressed.
+    dead_zone: i16,
+
+    /// All inputs currently pressed.
+    pressed_inputs: Vec<ControllerInput>,
+}
+
+
+impl Controllers {
+    /// Initializes the game controller subsystem.
+    /// Controllers already connected will be reported via events and opened then.
+    pub fn new(sdl: &Sdl, dead_zone: i16) -> Result<Self, String> {
+        Ok(Self {
+            subsystem:      sdl.game_controller()?,
+            controllers:    Vec::new(),
+            dead_zone,
+            pressed_inputs: Vec::new(),
+        })
+    }
+
+
+    /// Handles any controller related event.
+    /// Returns each input which changed its pressed state.
+    pub fn handle_event(&mut self, event: &Event) -> Vec<(ControllerInput, bool)> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                self.open(which);
+                Vec::new()
+            }
+
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.close(which)
+            }
+
+            Event::ControllerButtonDown { button, .. } => {
+                self.set_pressed(ControllerInput::Button(button), true).into_iter().collect()
+            }
+
+            Event::ControllerButtonUp { button, .. } => {
+                self.set_pressed(ControllerInput::Button(button), false).into_iter().collect()
+            }
+
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                self.handle_axis_motion(axis, value)
+            }
+
+            _ => Vec::new(),
+        }
+    }
+
+
+    /// Opens the controller with the given joystick index.
+    fn open(&mut self, joystick_index: u32) {
+        match self.subsystem.open(joystick_index) {
+            Ok(controller) => {
+                println!("Game controller connected: {}", controller.name());
+                self.controllers.push(controller);
+            }
+
+            Err(e) => {
+                println!("Failed to open game controller: {e}");
+            }
+        }
+    }
+
+
+    /// Closes the controller with the given instance ID after it was disconnected.
+    /// Since inputs are not tracked per controller, this releases all inputs currently pressed.
+    fn close(&mut self, instance_id: u32) -> Vec<(ControllerInput, bool)> {
+        self.controllers.retain(|controller| controller.instance_id() != instance_id);
+
+        std::mem::take(&mut self.pressed_inputs)
+            .into_iter()
+            .map(|input| (input, false))
+            .collect()
+    }
+
+
+    /// Converts the value of an axis into the pressed state of both of its directions.
+    fn handle_axis_motion(&mut self, axis: Axis, value: i16) -> Vec<(ControllerInput, bool)> {
+        let positive = value >  self.dead_zone;
+        let negative = value < -self.dead_zone;
+
+        [
+            self.set_pressed(ControllerInput::AxisPositive(axis), positive),
+            self.set_pressed(ControllerInput::AxisNegative(axis), negative),
+        ]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+
+    /// Updates the pressed state of an input.
+    /// Returns the new state, if it has changed.
+    fn set_pressed(&mut self, input: ControllerInput, pressed: bool) -> Option<(ControllerInput, bool)> {
+        let was_pressed = self.pressed_inputs.contains(&input);
+
+        match (was_pressed, pressed) {
+            (false, true) => self.pressed_inputs.push(input),
+            (true, false) => self.pressed_inputs.retain(|i| *i != input),
+            _             => return None,
+        }
+
+        Some((input, pressed))
+    }
+}
@@ -0,0 +1,516 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use gemi_core::input::InputButton;
+use gemi_utils::config_file::{ConfigFile, ConfigParseError};
+use gemi_utils::keybindings::{KeyBindingsError, KeyNames};
+use sdl2::controller::{Axis, Button};
+use sdl2::keyboard::{Keycode, Scancode};
+
+use crate::sound_queue::AudioConfig;
+
+
+/// Alias type for the key bindings of the emulator buttons.
+pub type KeyBindings = gemi_utils::keybindings::KeyBindings<PlayerInput>;
+
+
+/// The name of the config file section containing the player function bindings.
+const SECTION_FUNCTIONS: &str = "functions";
+
+/// The name of the config file section containing the game controller settings.
+const SECTION_CONTROLLER: &str = "controller";
+
+/// The name of the config entry containing the dead zone of controller axes.
+const ENTRY_DEAD_ZONE: &str = "DeadZone";
+
+/// The default dead zone of controller axes.
+const DEFAULT_DEAD_ZONE: i16 = 8000;
+
+/// The name of the config file section containing the display settings.
+const SECTION_DISPLAY: &str = "display";
+
+/// The name of the config entry containing the weight of the previous frame when blending frames.
+const ENTRY_FRAME_BLEND_WEIGHT: &str = "FrameBlendWeight";
+
+/// The default weight of the previous frame when blending frames.
+const DEFAULT_FRAME_BLEND_WEIGHT: f32 = 0.5;
+
+/// The name of the config file section containing the audio settings.
+const SECTION_AUDIO: &str = "audio";
+
+/// The name of the config entry containing the size of the audio device buffer.
+const ENTRY_BUFFER_SIZE: &str = "BufferSize";
+
+/// The name of the config entry containing the number of samples to be queued ahead of the playback.
+const ENTRY_TARGET_QUEUE_DEPTH: &str = "TargetQueueDepth";
+
+/// The name of the config entry containing the maximum number of samples to be queued.
+const ENTRY_MAX_QUEUE_DEPTH: &str = "MaxQueueDepth";
+
+/// The prefix of the names of any game controller input.
+const CONTROLLER_INPUT_PREFIX: &str = "Pad ";
+
+/// The name of the file to store the key bindings.
+const KEY_CONFIG_FILE: &str = "keybindings.ini";
+
+/// The number of scancodes to be checked when collecting all known keys.
+const SCANCODE_COUNT: i32 = 512;
+
+
+/// An input of a game controller, which can be bound like a key.
+/// Each axis is split into two directions, which will be pressed
+/// when the axis value exceeds the dead zone.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ControllerInput {
+    Button(Button),
+    AxisPositive(Axis),
+    AxisNegative(Axis),
+}
+
+
+/// Any input source, which can be bound to emulator buttons or player functions.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum PlayerInput {
+    Key(Keycode),
+    Controller(ControllerInput),
+}
+
+
+/// The names of each [ControllerInput] as used in the config file.
+const CONTROLLER_INPUT_NAMES: [(ControllerInput, &str); 27] = [
+    (ControllerInput::Button(Button::A),                    "A"),
+    (ControllerInput::Button(Button::B),                    "B"),
+    (ControllerInput::Button(Button::X),                    "X"),
+    (ControllerInput::Button(Button::Y),                    "Y"),
+    (ControllerInput::Button(Button::Back),                 "Back"),
+    (ControllerInput::Button(Button::Guide),                "Guide"),
+    (ControllerInput::Button(Button::Start),                "Start"),
+    (ControllerInput::Button(Button::LeftStick),            "LeftStick"),
+    (ControllerInput::Button(Button::RightStick),           "RightStick"),
+    (ControllerInput::Button(Button::LeftShoulder),         "LeftShoulder"),
+    (ControllerInput::Button(Button::RightShoulder),        "RightShoulder"),
+    (ControllerInput::Button(Button::DPadUp),               "DPadUp"),
+    (ControllerInput::Button(Button::DPadDown),             "DPadDown"),
+    (ControllerInput::Button(Button::DPadLeft),             "DPadLeft"),
+    (ControllerInput::Button(Button::DPadRight),            "DPadRight"),
+    (ControllerInput::AxisPositive(Axis::LeftX),            "LeftX+"),
+    (ControllerInput::AxisNegative(Axis::LeftX),            "LeftX-"),
+    (ControllerInput::AxisPositive(Axis::LeftY),            "LeftY+"),
+    (ControllerInput::AxisNegative(Axis::LeftY),            "LeftY-"),
+    (ControllerInput::AxisPositive(Axis::RightX),           "RightX+"),
+    (ControllerInput::AxisNegative(Axis::RightX),           "RightX-"),
+    (ControllerInput::AxisPositive(Axis::RightY),           "RightY+"),
+    (ControllerInput::AxisNegative(Axis::RightY),           "RightY-"),
+    (ControllerInput::AxisPositive(Axis::TriggerLeft),      "TriggerLeft"),
+    (ControllerInput::AxisPositive(Axis::TriggerRight),     "TriggerRight"),
+    (ControllerInput::Button(Button::Misc1),                "Misc1"),
+    (ControllerInput::Button(Button::Touchpad),             "Touchpad"),
+];
+
+
+/// Functions of the player application, which can be bound to keys.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum PlayerFunction {
+    Quit,
+    FastForward,
+    SaveState,
+    LoadState,
+    SelectSaveStateSlot(u8),
+    Screenshot,
+    DisplayGame,
+    DisplayBackground,
+    DisplayObjects,
+    VolumeUp,
+    VolumeDown,
+    CycleColorCorrection,
+    Pause,
+    FrameAdvance,
+    SlowMotion,
+    ToggleFrameBlending,
+    IncreaseSpeed,
+    DecreaseSpeed,
+    ToggleAudioRecording,
+    ToggleChannelMute(u8),
+    CycleChannelSolo,
+}
+
+
+/// The names of each [PlayerFunction] as used in the config file.
+const FUNCTION_NAMES: [(PlayerFunction, &str); 27] = [
+    (PlayerFunction::Quit,                      "Quit"),
+    (PlayerFunction::FastForward,               "FastForward"),
+    (PlayerFunction::SaveState,                 "SaveState"),
+    (PlayerFunction::LoadState,                 "LoadState"),
+    (PlayerFunction::SelectSaveStateSlot(1),    "SelectSlot1"),
+    (PlayerFunction::SelectSaveStateSlot(2),    "SelectSlot2"),
+    (PlayerFunction::SelectSaveStateSlot(3),    "SelectSlot3"),
+    (PlayerFunction::SelectSaveStateSlot(4),    "SelectSlot4"),
+    (PlayerFunction::Screenshot,                "Screenshot"),
+    (PlayerFunction::DisplayGame,               "DisplayGame"),
+    (PlayerFunction::DisplayBackground,         "DisplayBackground"),
+    (PlayerFunction::DisplayObjects,            "DisplayObjects"),
+    (PlayerFunction::VolumeUp,                  "VolumeUp"),
+    (PlayerFunction::VolumeDown,                "VolumeDown"),
+    (PlayerFunction::CycleColorCorrection,      "CycleColorCorrection"),
+    (PlayerFunction::Pause,                     "Pause"),
+    (PlayerFunction::FrameAdvance,              "FrameAdvance"),
+    (PlayerFunction::SlowMotion,                "SlowMotion"),
+    (PlayerFunction::ToggleFrameBlending,       "ToggleFrameBlending"),
+    (PlayerFunction::IncreaseSpeed,             "IncreaseSpeed"),
+    (PlayerFunction::DecreaseSpeed,             "DecreaseSpeed"),
+    (PlayerFunction::ToggleAudioRecording,      "ToggleAudioRecording"),
+    (PlayerFunction::ToggleChannelMute(1),      "MuteChannel1"),
+    (PlayerFunction::ToggleChannelMute(2),      "MuteChannel2"),
+    (PlayerFunction::ToggleChannelMute(3),      "MuteChannel3"),
+    (PlayerFunction::ToggleChannelMute(4),      "MuteChannel4"),
+    (PlayerFunction::CycleChannelSolo,          "CycleChannelSolo"),
+];
+
+
+/// Maps keys to functions of the player application.
+pub struct FunctionBindings {
+    bindings: Vec<(PlayerFunction, Vec<PlayerInput>)>,
+}
+
+
+/// The complete key configuration of the player.
+pub struct KeyConfig {
+    /// Key bindings for the emulator buttons.
+    pub buttons: KeyBindings,
+
+    /// Key bindings for the player functions.
+    pub functions: FunctionBindings,
+
+    /// The value a controller axis needs to exceed to be recognized as pressed.
+    pub controller_dead_zone: i16,
+
+    /// The weight of the previous frame when blending it with the current one.
+    pub frame_blend_weight: f32,
+
+    /// The configuration of the audio playback.
+    pub audio: AudioConfig,
+}
+
+
+impl FunctionBindings {
+    /// Find the function bound to a given input, if any.
+    pub fn find_function(&self, input: PlayerInput) -> Option<PlayerFunction> {
+        self.bindings
+            .iter()
+            .find(|(_, inputs)| inputs.contains(&input))
+            .map(|(function, _)| *function)
+    }
+
+
+    /// Reads the function bindings from the functions section of a config file.
+    fn from_config(config: &ConfigFile, key_names: &KeyNames<PlayerInput>) -> Result<Self, KeyBindingsError> {
+        let mut bindings = Vec::new();
+
+        if let Some(section) = config.get_section(SECTION_FUNCTIONS) {
+            for entry in section.get_entries() {
+                let function = FUNCTION_NAMES
+                    .iter()
+                    .find(|(_, name)| name.eq_ignore_ascii_case(&entry.name))
+                    .map(|(function, _)| *function)
+                ;
+
+                let Some(function) = function else {
+                    return Err(KeyBindingsError::unknown_name(
+                        entry,
+                        FUNCTION_NAMES.iter().map(|(_, name)| *name)
+                    ));
+                };
+
+                bindings.push((function, key_names.parse_entry(entry)?));
+            }
+        }
+
+        Ok(Self { bindings })
+    }
+
+
+    /// Writes the function bindings into the functions section of a config file.
+    fn write_config(&self, config: &mut ConfigFile, key_names: &KeyNames<PlayerInput>) {
+        let section = config.get_or_create_section(SECTION_FUNCTIONS);
+
+        for (function, keys) in &self.bindings {
+            let name = FUNCTION_NAMES
+                .iter()
+                .find(|(f, _)| f == function)
+                .map(|(_, name)| *name)
+                .unwrap_or_default()
+            ;
+
+            section.add_entry(name, key_names.get_names_of(keys.iter()));
+        }
+    }
+}
+
+
+impl Default for FunctionBindings {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (PlayerFunction::Quit,                      vec![key(Keycode::Escape)]),
+                (PlayerFunction::FastForward,               vec![key(Keycode::Tab), axis_positive(Axis::TriggerRight)]),
+                (PlayerFunction::SaveState,                 vec![key(Keycode::F5)]),
+                (PlayerFunction::LoadState,                 vec![key(Keycode::F8)]),
+                (PlayerFunction::SelectSaveStateSlot(1),    vec![key(Keycode::Num1)]),
+                (PlayerFunction::SelectSaveStateSlot(2),    vec![key(Keycode::Num2)]),
+                (PlayerFunction::SelectSaveStateSlot(3),    vec![key(Keycode::Num3)]),
+                (PlayerFunction::SelectSaveStateSlot(4),    vec![key(Keycode::Num4)]),
+                (PlayerFunction::Screenshot,                vec![key(Keycode::F12)]),
+                (PlayerFunction::DisplayGame,               vec![key(Keycode::F1)]),
+                (PlayerFunction::DisplayBackground,         vec![key(Keycode::F2)]),
+                (PlayerFunction::DisplayObjects,            vec![key(Keycode::F3)]),
+                (PlayerFunction::VolumeUp,                  vec![key(Keycode::KpPlus)]),
+                (PlayerFunction::VolumeDown,                vec![key(Keycode::KpMinus)]),
+                (PlayerFunction::CycleColorCorrection,      vec![key(Keycode::F4)]),
+                (PlayerFunction::Pause,                     vec![key(Keycode::Space)]),
+                (PlayerFunction::FrameAdvance,              vec![key(Keycode::Period)]),
+                (PlayerFunction::SlowMotion,                vec![key(Keycode::Backspace)]),
+                (PlayerFunction::ToggleFrameBlending,       vec![key(Keycode::F6)]),
+                (PlayerFunction::IncreaseSpeed,             vec![key(Keycode::Plus), key(Keycode::Equals)]),
+                (PlayerFunction::DecreaseSpeed,             vec![key(Keycode::Minus)]),
+                (PlayerFunction::ToggleAudioRecording,      vec![key(Keycode::F9)]),
+                (PlayerFunction::ToggleChannelMute(1),      vec![key(Keycode::Num5)]),
+                (PlayerFunction::ToggleChannelMute(2),      vec![key(Keycode::Num6)]),
+                (PlayerFunction::ToggleChannelMute(3),      vec![key(Keycode::Num7)]),
+                (PlayerFunction::ToggleChannelMute(4),      vec![key(Keycode::Num8)]),
+                (PlayerFunction::CycleChannelSolo,          vec![key(Keycode::Num9)]),
+            ],
+        }
+    }
+}
+
+
+impl KeyConfig {
+    /// Loads the key configuration from the user's config directory.
+    /// If there is no config file yet, it will be created with the default key bindings.
+    pub fn load_or_create() -> Result<Self, String> {
+        let key_names = make_key_names();
+        let file      = get_key_config_file()?;
+
+        if !file.exists() {
+            let key_config = Self::default();
+            key_config.save(&file, &key_names)?;
+
+            return Ok(key_config);
+        }
+
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {e}", file.display()))
+            ?
+        ;
+
+        Self::parse(&text, &key_names)
+            .map_err(|e| format!("Invalid key bindings in {}: {e}", file.display()))
+    }
+
+
+    /// Parses a key configuration from the content of a config file.
+    fn parse(text: &str, key_names: &KeyNames<PlayerInput>) -> Result<Self, KeyBindingsError> {
+        let config = ConfigFile::parse(text)?;
+
+        Ok(Self {
+            buttons:              KeyBindings::from_config(&config, key_names)?,
+            functions:            FunctionBindings::from_config(&config, key_names)?,
+            controller_dead_zone: read_dead_zone(&config)?,
+            frame_blend_weight:   read_frame_blend_weight(&config)?,
+            audio:                read_audio_config(&config)?,
+        })
+    }
+
+
+    /// Stores the key configuration into a file.
+    fn save(&self, file: &PathBuf, key_names: &KeyNames<PlayerInput>) -> Result<(), String> {
+        let mut config = ConfigFile::new();
+        self.buttons.write_config(&mut config, key_names);
+        self.functions.write_config(&mut config, key_names);
+
+        config.get_or_create_section(SECTION_CONTROLLER)
+            .add_entry(ENTRY_DEAD_ZONE, vec![self.controller_dead_zone.to_string()])
+        ;
+
+        config.get_or_create_section(SECTION_DISPLAY)
+            .add_entry(ENTRY_FRAME_BLEND_WEIGHT, vec![self.frame_blend_weight.to_string()])
+        ;
+
+        let audio_section = config.get_or_create_section(SECTION_AUDIO);
+        audio_section.add_entry(ENTRY_BUFFER_SIZE,        vec![self.audio.buffer_samples.to_string()]);
+        audio_section.add_entry(ENTRY_TARGET_QUEUE_DEPTH, vec![self.audio.target_queue_depth.to_string()]);
+        audio_section.add_entry(ENTRY_MAX_QUEUE_DEPTH,    vec![self.audio.max_queue_depth.to_string()]);
+
+        if let Some(directory) = file.parent() {
+            fs::create_dir_all(directory)
+                .map_err(|e| format!("Failed to create directory {}: {e}", directory.display()))
+                ?
+            ;
+        }
+
+        fs::write(file, config.to_string())
+            .map_err(|e| format!("Failed to write {}: {e}", file.display()))
+    }
+}
+
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        Self {
+            // game controller buttons are named by their position on a XBox controller,
+            // so the buttons on the right and bottom are mapped to the GameBoy's A and B buttons
+            buttons: KeyBindings::with_mapping(
+                vec![
+                    (InputButton::DPadRight,    vec![key(Keycode::D),   key(Keycode::Right),    button(Button::DPadRight),  axis_positive(Axis::LeftX)]),
+                    (InputButton::DPadLeft,     vec![key(Keycode::A),   key(Keycode::Left),     button(Button::DPadLeft),   axis_negative(Axis::LeftX)]),
+                    (InputButton::DPadUp,       vec![key(Keycode::W),   key(Keycode::Up),       button(Button::DPadUp),     axis_negative(Axis::LeftY)]),
+                    (InputButton::DPadDown,     vec![key(Keycode::S),   key(Keycode::Down),     button(Button::DPadDown),   axis_positive(Axis::LeftY)]),
+                    (InputButton::A,            vec![key(Keycode::E),   key(Keycode::X),        button(Button::B)]),
+                    (InputButton::B,            vec![key(Keycode::Q),   key(Keycode::Y),        button(Button::A)]),
+                    (InputButton::Select,       vec![key(Keycode::LShift),                      button(Button::Back)]),
+                    (InputButton::Start,        vec![key(Keycode::Return),                      button(Button::Start)]),
+                ]
+            ),
+
+            functions: FunctionBindings::default(),
+
+            controller_dead_zone: DEFAULT_DEAD_ZONE,
+
+            frame_blend_weight: DEFAULT_FRAME_BLEND_WEIGHT,
+
+            audio: AudioConfig::default(),
+        }
+    }
+}
+
+
+/// Get the path of the file containing the key configuration.
+fn get_key_config_file() -> Result<PathBuf, String> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| String::from("Unable to determine the user's config directory"))
+        ?
+    ;
+
+    Ok(config_dir.join("gemi").join(KEY_CONFIG_FILE))
+}
+
+
+/// Reads a numeric entry from the config file, which needs to be within the given range.
+/// Returns the default value, if the entry does not exist.
+fn read_number<T>(config: &ConfigFile, section: &str, name: &str, range: RangeInclusive<T>, default: T) -> Result<T, KeyBindingsError>
+    where T: FromStr + PartialOrd + Display
+{
+    let entry = config
+        .get_section(section)
+        .and_then(|section| section.get_entries().iter().find(|entry| entry.name == name))
+    ;
+
+    let Some(entry) = entry else {
+        return Ok(default);
+    };
+
+    entry.values
+        .first()
+        .and_then(|value| value.parse::<T>().ok())
+        .filter(|value| range.contains(value))
+        .ok_or_else(|| KeyBindingsError::Parse(ConfigParseError::new(
+            entry.line.unwrap_or_default(),
+            format!("{name} needs to be a number from {} to {}", range.start(), range.end())
+        )))
+}
+
+
+/// Reads the dead zone of controller axes from the config file.
+fn read_dead_zone(config: &ConfigFile) -> Result<i16, KeyBindingsError> {
+    read_number(config, SECTION_CONTROLLER, ENTRY_DEAD_ZONE, 0 ..= i16::MAX, DEFAULT_DEAD_ZONE)
+}
+
+
+/// Reads the weight of the previous frame when blending frames from the config file.
+fn read_frame_blend_weight(config: &ConfigFile) -> Result<f32, KeyBindingsError> {
+    read_number(config, SECTION_DISPLAY, ENTRY_FRAME_BLEND_WEIGHT, 0.0 ..= 1.0, DEFAULT_FRAME_BLEND_WEIGHT)
+}
+
+
+/// Reads the audio buffer and queue sizes from the config file.
+fn read_audio_config(config: &ConfigFile) -> Result<AudioConfig, KeyBindingsError> {
+    let default = AudioConfig::default();
+
+    let buffer_samples     = read_number(config, SECTION_AUDIO, ENTRY_BUFFER_SIZE,        64 ..= 8192,                    default.buffer_samples)?;
+    let target_queue_depth = read_number(config, SECTION_AUDIO, ENTRY_TARGET_QUEUE_DEPTH, 0 ..= 65536,                    default.target_queue_depth)?;
+    let max_queue_depth    = read_number(config, SECTION_AUDIO, ENTRY_MAX_QUEUE_DEPTH,    target_queue_depth ..= 1 << 20, default.max_queue_depth.max(target_queue_depth))?;
+
+    Ok(AudioConfig {
+        buffer_samples,
+        target_queue_depth,
+        max_queue_depth,
+    })
+}
+
+
+/// Shortcut to create a [PlayerInput] for a keyboard key.
+fn key(keycode: Keycode) -> PlayerInput {
+    PlayerInput::Key(keycode)
+}
+
+
+/// Shortcut to create a [PlayerInput] for a game controller button.
+fn button(button: Button) -> PlayerInput {
+    PlayerInput::Controller(ControllerInput::Button(button))
+}
+
+
+/// Shortcut to create a [PlayerInput] for the positive direction of a game controller axis.
+fn axis_positive(axis: Axis) -> PlayerInput {
+    PlayerInput::Controller(ControllerInput::AxisPositive(axis))
+}
+
+
+/// Shortcut to create a [PlayerInput] for the negative direction of a game controller axis.
+fn axis_negative(axis: Axis) -> PlayerInput {
+    PlayerInput::Controller(ControllerInput::AxisNegative(axis))
+}
+
+
+/// Creates a lookup table of all keys known by SDL and all game controller inputs with their names.
+/// Game controller inputs are prefixed to distinguish them from keyboard keys.
+fn make_key_names() -> KeyNames<PlayerInput> {
+    let mut keys: Vec<(String, PlayerInput)> = Vec::new();
+
+    for scancode in (0..SCANCODE_COUNT).filter_map(Scancode::from_i32) {
+        if let Some(keycode) = Keycode::from_scancode(scancode) {
+            let name = keycode.name();
+
+            // skip keys without name or with names which cannot be stored in the config file
+            if !name.is_empty() && !name.contains(',') && !keys.iter().any(|(_, key)| *key == PlayerInput::Key(keycode)) {
+                keys.push((name, PlayerInput::Key(keycode)));
+            }
+        }
+    }
+
+    for (input, name) in CONTROLLER_INPUT_NAMES {
+        keys.push((format!("{CONTROLLER_INPUT_PREFIX}{name}"), PlayerInput::Controller(input)));
+    }
+
+    KeyNames::new(keys)
+}
@@ -17,18 +17,30 @@
 
 extern crate core;
 
-use std::{env, time};
+use std::env;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use gemi_core::apu::channels::channel::ChannelType;
 use gemi_core::boot_rom::BootRom;
 use gemi_core::cartridge::Cartridge;
 use gemi_core::cartridge::GameBoyColorSupport;
 use gemi_core::cpu::cpu::CPU_CLOCK_SPEED;
-use gemi_core::gameboy::{DeviceType, GameBoy};
-
-use crate::window::Window;
-
+use gemi_core::gameboy::{Clock, DeviceType, GameBoy, ResetKind};
+
+use crate::battery::BatterySave;
+use crate::key_config::KeyConfig;
+use crate::pacing::FramePacer;
+use crate::window::{PlayerAction, Window};
+
+mod battery;
+mod controllers;
+mod key_config;
+mod output_files;
+mod pacing;
+mod printouts;
+mod save_states;
+mod screenshots;
 mod sound_queue;
 mod window;
 
@@ -70,50 +82,275 @@ fn print_rom_info(file: &Path, cartridge: &Cartridge) {
 }
 
 
-fn run(window: &mut Window, gb: &mut GameBoy) {
-    let mut interval_begin  = time::Instant::now();
-    let mut interval_cycles = 0;
+/// The maximum wall time to spend on emulating frames in fast forward mode before presenting.
+const FAST_FORWARD_PRESENT_INTERVAL: Duration = Duration::from_millis(16);
+
+/// The time to wait between handling events while the emulation is paused.
+const PAUSED_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// The minimum emulation speed multiplier selectable by the user.
+const MIN_SPEED_MULTIPLIER: f64 = 0.25;
+
+/// The maximum emulation speed multiplier selectable by the user.
+const MAX_SPEED_MULTIPLIER: f64 = 4.0;
+
+/// The name of the directory where to store audio recordings.
+const AUDIO_RECORDINGS_DIR: &str = "recordings";
+
+
+/// Options for the player application, which are not related to the emulator itself.
+struct PlayerOptions {
+    /// Whether to wait for the vertical sync of the display when presenting a frame.
+    vsync: bool,
+
+    /// Whether to display the number of frames per second in the window title.
+    show_fps: bool,
+
+    /// Whether to save the cartridge RAM periodically while running.
+    autosave: bool,
+
+    /// A file to record the audio output into, starting with the emulation.
+    record_audio: Option<PathBuf>,
+
+    /// Whether to connect a GameBoy Printer to the serial port.
+    printer: bool,
+}
+
+
+impl Default for PlayerOptions {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            show_fps: false,
+            autosave: true,
+            record_audio: None,
+            printer: false,
+        }
+    }
+}
+
+
+fn run(window: &mut Window, gb: &mut GameBoy, battery: &mut Option<BatterySave>, options: &PlayerOptions) {
+    let mut pacer = FramePacer::new(gb.get_total_system_cycles_processed(), CPU_CLOCK_SPEED);
 
     while window.is_opened() {
-        let frame_results = gb.run_frame();
-        let frame_cycles  = frame_results.cycles;
-        interval_cycles += frame_cycles;
+        // handle user input
+        window.poll_events();
+        window.apply_button_states(&mut gb.get_peripherals_mut().input);
+
+        // handle actions requested by the user
+        for action in window.take_actions() {
+            let message = handle_action(gb, window, action);
+            window.show_message(message);
+
+            // the emulator's clock may have changed after loading a state
+            pacer.reset(gb.get_total_system_cycles_processed());
+        }
+
+        // save the cartridge RAM from time to time, so it won't get lost
+        if let Some(battery) = battery {
+            battery.update(gb);
+        }
+
+        // while paused, only single frames are processed on request
+        if window.is_paused() {
+            if window.take_frame_advance() {
+                gb.run_frame();
+            }
+
+            present(window, gb);
+            std::thread::sleep(PAUSED_POLL_INTERVAL);
 
-        // update window
-        {
-            let peripherals = gb.get_peripherals_mut();
+            // don't try to catch up with the time passed while paused
+            pacer.reset(gb.get_total_system_cycles_processed());
 
-            window.poll_events();
-            window.apply_button_states(&mut peripherals.input);
-            window.present(peripherals.ppu.get_lcd(), &peripherals.ppu);
+            continue;
         }
 
-        // handle frame times
-        {
-            let frame_end_time = time::Instant::now();
+        // pacing is based on the system clock, which is not affected by double speed mode;
+        // the speed multiplier changes the number of cycles to be emulated per second,
+        // in slow motion, the emulator runs at half of its clock speed
+        let clock_speed = (CPU_CLOCK_SPEED as f64 * gb.get_speed_multiplier()) as Clock;
+        let clock_speed = if window.is_slow_motion() {
+            clock_speed / 2
+        }
+        else {
+            clock_speed
+        };
+
+        pacer.set_clock_speed(gb.get_total_system_cycles_processed(), clock_speed);
+
+        let frames_processed = if window.is_fast_forward() {
+            run_fast_forward(gb, &mut pacer)
+        }
+        else {
+            run_paced(gb, &mut pacer)
+        };
+
+        if frames_processed > 0 {
+            present(window, gb);
+        }
+        else {
+            // the emulator is ahead of the wall time, so wait until it's due to run the next frame
+            let time_remaining = pacer.get_time_until(gb.get_total_system_cycles_processed());
+            std::thread::sleep(time_remaining);
+        }
 
-            let interval_duration_ns = (frame_end_time - interval_begin).as_nanos() as u64;
-            let expected_time_ns     = (1_000_000_000u64 * interval_cycles) / CPU_CLOCK_SPEED;
+        if pacer.measure_speed(gb.get_total_system_cycles_processed()) {
+            window.show_speed(pacer.get_current_speed());
 
-            // when the interval time was shorter than expected,
-            // let the CPU sleep for the time difference
-            if interval_duration_ns < expected_time_ns {
-                let time_remaining = expected_time_ns - interval_duration_ns;
-                std::thread::sleep(Duration::from_nanos(time_remaining));
+            // show the frame rate measured since the last update
+            if options.show_fps {
+                window.show_fps(gb.get_frame_stats().average_fps);
+                gb.reset_frame_stats();
             }
+        }
+    }
+}
+
+
+/// Presents the current emulator output on the window.
+fn present(window: &mut Window, gb: &GameBoy) {
+    let peripherals = gb.get_peripherals();
+    let ppu         = &peripherals.ppu;
+    window.present(ppu.get_lcd(), ppu, peripherals.sgb.as_deref());
+}
+
+
+/// Performs an action requested by the user.
+/// Returns a message describing the result of the action.
+fn handle_action(gb: &mut GameBoy, window: &mut Window, action: PlayerAction) -> String {
+    let result = match action {
+        PlayerAction::SaveState(slot) => {
+            save_states::save_state(gb, slot).map(|_| format!("State {slot} saved"))
+        }
+
+        PlayerAction::LoadState(slot) => {
+            save_states::load_state(gb, slot).map(|_| format!("State {slot} loaded"))
+        }
+
+        PlayerAction::Screenshot => {
+            screenshots::save_screenshot(gb).map(|file| format!("Screenshot saved to {}", file.display()))
+        }
+
+        PlayerAction::ScreenshotDisplayContent => {
+            screenshots::save_display_content(gb, window).map(|file| format!("Screenshot saved to {}", file.display()))
+        }
+
+        PlayerAction::CycleColorCorrection => {
+            let ppu              = &mut gb.get_peripherals_mut().ppu;
+            let color_correction = ppu.get_color_correction().next();
+            ppu.set_color_correction(color_correction);
+
+            Ok(format!("Color correction: {color_correction}"))
+        }
+
+        PlayerAction::ChangeSpeed(step) => {
+            let multiplier = (gb.get_speed_multiplier() + step).clamp(MIN_SPEED_MULTIPLIER, MAX_SPEED_MULTIPLIER);
+            gb.set_speed_multiplier(multiplier);
+
+            Ok(format!("Speed: {:.0}%", multiplier * 100.0))
+        }
 
-            // reset the interval after counting more than the number of cycles per second,
-            // so we get a bit more precision than just counting per frame
-            if interval_cycles >= CPU_CLOCK_SPEED {
-                interval_cycles -= CPU_CLOCK_SPEED;
-                interval_begin = frame_end_time;
+        PlayerAction::ToggleAudioRecording => {
+            toggle_audio_recording(gb, window)
+        }
+
+        PlayerAction::ToggleChannelMute(number) => {
+            let channel = ChannelType::ALL[(number - 1) as usize];
+            let apu     = &mut gb.get_peripherals_mut().apu;
+            let muted   = !apu.get_mixer().is_channel_muted(channel);
+            apu.set_channel_muted(channel, muted);
+
+            Ok(format!("Channel {number} {}", if muted { "muted" } else { "unmuted" }))
+        }
+
+        PlayerAction::CycleChannelSolo => {
+            // cycle through all channels, followed by no channel being selected
+            let apu     = &mut gb.get_peripherals_mut().apu;
+            let current = apu.get_mixer().get_channel_solo();
+            let next    = match current.and_then(|channel| ChannelType::ALL.iter().position(|c| *c == channel)) {
+                None        => Some(0),
+                Some(index) => if index + 1 < ChannelType::ALL.len() { Some(index + 1) } else { None },
+            };
+
+            apu.set_channel_solo(next.map(|index| ChannelType::ALL[index]));
+
+            match next {
+                Some(index) => Ok(format!("Solo: channel {}", index + 1)),
+                None        => Ok(String::from("Solo: off")),
             }
         }
+
+        PlayerAction::Reset => {
+            gb.reset(ResetKind::Soft);
+            Ok(String::from("Reset"))
+        }
+    };
+
+    match result {
+        Ok(message) => message,
+        Err(error)  => {
+            eprintln!("{error}");
+            error
+        }
+    }
+}
+
+
+/// Starts recording the audio output into a new file, or stops the recording currently running.
+/// Returns a message describing the result of the action.
+fn toggle_audio_recording(gb: &GameBoy, window: &mut Window) -> Result<String, String> {
+    if window.is_audio_recording() {
+        let file = window.stop_audio_recording()?;
+
+        return Ok(match file {
+            Some(file) => format!("Audio recording saved to {}", file.display()),
+            None       => String::from("Audio recording stopped"),
+        });
+    }
+
+    let file = output_files::get_output_file(gb, AUDIO_RECORDINGS_DIR, "recording", ".wav")?;
+    window.start_audio_recording(&file)?;
+
+    Ok(format!("Recording audio to {}", file.display()))
+}
+
+
+/// Runs as many frames as needed to catch up with the wall time.
+/// Returns the number of frames processed.
+fn run_paced(gb: &mut GameBoy, pacer: &mut FramePacer) -> usize {
+    let target_cycles    = pacer.get_target_cycles(gb.get_total_system_cycles_processed());
+    let mut frames_count = 0;
+
+    while gb.get_total_system_cycles_processed() < target_cycles {
+        gb.run_frame();
+        frames_count += 1;
+    }
+
+    frames_count
+}
+
+
+/// Runs frames as fast as possible until it's time to present the next frame.
+/// Returns the number of frames processed.
+fn run_fast_forward(gb: &mut GameBoy, pacer: &mut FramePacer) -> usize {
+    let begin            = Instant::now();
+    let mut frames_count = 0;
+
+    while begin.elapsed() < FAST_FORWARD_PRESENT_INTERVAL {
+        gb.run_frame();
+        frames_count += 1;
     }
+
+    // continue pacing from the current state after leaving fast forward mode
+    pacer.reset(gb.get_total_system_cycles_processed());
+
+    frames_count
 }
 
 
-fn make_gameboy_instance() -> Result<GameBoy, String> {
+fn make_gameboy_instance(options: &mut PlayerOptions) -> Result<GameBoy, String> {
     let mut args    = env::args().into_iter();
     let mut builder = GameBoy::build();
 
@@ -164,12 +401,41 @@ fn make_gameboy_instance() -> Result<GameBoy, String> {
                 builder.set_print_opcodes(true);
             }
 
+            "--no-vsync" => {
+                options.vsync = false;
+            }
+
+            "--show-fps" => {
+                options.show_fps = true;
+            }
+
+            "--no-autosave" => {
+                options.autosave = false;
+            }
+
+            "--record-audio" => {
+                let filename = args.next()
+                    .expect("'--record-audio' needs to be followed by the path of the WAV file to be created");
+
+                options.record_audio = Some(PathBuf::from(filename));
+            }
+
+            "--printer" => {
+                options.printer = true;
+            }
+
+            "--allow-invalid-header" => {
+                builder.set_allow_invalid_header(true);
+            }
+
             _ => {
                 let file = PathBuf::from(arg);
-                let cart = Cartridge::load_files_with_default_ram(&file)
+                let mut cart = Cartridge::load_file(&file)
                     .map_err(|e| format!("Failed to load cartridge: {}", e))
                     ?;
 
+                battery::load_battery_ram(&mut cart)?;
+
                 print_rom_info(&file, &cart);
 
                 builder.set_cartridge(cart);
@@ -178,31 +444,53 @@ fn make_gameboy_instance() -> Result<GameBoy, String> {
     }
 
     builder.finish()
+        .map_err(|e| e.to_string())
 }
 
 
 fn main() -> Result<(), String> {
     // create the gb instance using the current commandline arguments
-    let mut gb = make_gameboy_instance()?;
+    let mut options = PlayerOptions::default();
+    let mut gb      = make_gameboy_instance(&mut options)?;
     gb.initialize();
 
+    // connect the printer, if requested
+    if options.printer {
+        printouts::connect_printer(&mut gb)?;
+    }
+
     // determine the title based on the cartridge available
     let title = match gb.get_peripherals().mem.get_cartridge() {
         Some(cartridge) => cartridge.get_title().to_string(),
         None => "GameBoy".to_string(),
     };
 
+    // load the key bindings configured by the user
+    let key_config = KeyConfig::load_or_create()?;
+
     // create window
-    let mut window = Window::create(&title, &mut gb)?;
+    let mut window = Window::create(&title, &mut gb, options.vsync, key_config)?;
+
+    // start recording the audio output, if requested
+    if let Some(file) = &options.record_audio {
+        window.start_audio_recording(file)?;
+    }
+
+    // keep track of the battery powered cartridge RAM, if any
+    let mut battery = BatterySave::new(&gb, options.autosave);
 
     // run the game
-    run(&mut window, &mut gb);
+    run(&mut window, &mut gb, &mut battery, &options);
+
+    // complete the audio recording, if any
+    if let Some(file) = window.stop_audio_recording()? {
+        println!("Audio recording saved to {}", file.display());
+    }
 
     // after running the cartridge, save it's on-chip-RAM, if any
-    gb.get_peripherals().mem.save_cartridge_ram_to_file_if_any()
-        .map_err(|e| format!("Failed to save cartridge RAM: {}", e))
-        ?
-    ;
+    if let Some(battery) = &mut battery {
+        battery.save(&gb)?;
+    }
 
     // everything went ok
     Ok(())
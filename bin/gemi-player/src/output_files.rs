@@ -0,0 +1,59 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gemi_core::gameboy::GameBoy;
+
+
+/// Get a path for a new file created by the player, like screenshots or audio recordings.
+/// Files will be stored in a directory with the given name next to the ROM file of the
+/// current cartridge, named by the ROM and a timestamp, followed by the given suffix.
+/// Without a ROM file, the default name is used and the directory is relative to the
+/// current working directory.
+pub fn get_output_file(gb: &GameBoy, directory_name: &str, default_name: &str, suffix: &str) -> Result<PathBuf, String> {
+    let rom_file = gb.get_peripherals().mem.get_cartridge()
+        .and_then(|cartridge| cartridge.get_source_file())
+    ;
+
+    let (directory, name) = match rom_file {
+        Some(rom_file) => (
+            rom_file.with_file_name(directory_name),
+            rom_file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        ),
+
+        None => (
+            PathBuf::from(directory_name),
+            String::from(default_name),
+        ),
+    };
+
+    fs::create_dir_all(&directory)
+        .map_err(|e| format!("Failed to create directory {}: {e}", directory.display()))
+        ?
+    ;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default()
+    ;
+
+    Ok(directory.join(format!("{name}_{timestamp}{suffix}")))
+}
@@ -0,0 +1,140 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::time::{Duration, Instant};
+
+use gemi_core::gameboy::Clock;
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+
+/// The maximum number of frames the emulator may lag behind the wall time,
+/// before the pacer gives up on catching up and just continues from the current time.
+const MAX_FRAMES_BEHIND: Clock = 4;
+
+/// The interval in which the current emulation speed will be measured.
+const SPEED_MEASURE_INTERVAL: Duration = Duration::from_secs(1);
+
+
+/// Keeps the emulation in sync with the wall time, independent of
+/// the refresh rate of the display the emulator is presented on.
+pub struct FramePacer {
+    /// The point in time at which the pacer started counting.
+    reference_time: Instant,
+
+    /// The number of cycles the emulator had processed at the reference time.
+    reference_cycles: Clock,
+
+    /// The clock speed in cycles per second used to compute the expected cycles.
+    clock_speed: Clock,
+
+    /// The begin of the current interval to measure the emulation speed.
+    measure_begin_time: Instant,
+
+    /// The number of cycles processed when the current measure interval began.
+    measure_begin_cycles: Clock,
+
+    /// The emulation speed measured in the last interval, relative to the original hardware.
+    current_speed: f32,
+}
+
+
+impl FramePacer {
+    /// Creates a new pacer starting at the current point in time
+    /// with the given number of cycles already processed.
+    pub fn new(cycles: Clock, clock_speed: Clock) -> Self {
+        let now = Instant::now();
+
+        Self {
+            reference_time:       now,
+            reference_cycles:     cycles,
+            clock_speed,
+            measure_begin_time:   now,
+            measure_begin_cycles: cycles,
+            current_speed:        1.0,
+        }
+    }
+
+
+    /// Restarts counting from the current point in time. This is necessary
+    /// when the emulation was running unpaced, for example in fast forward mode.
+    pub fn reset(&mut self, cycles: Clock) {
+        self.reference_time   = Instant::now();
+        self.reference_cycles = cycles;
+    }
+
+
+    /// Updates the clock speed, for example when the speed multiplier of the emulator was changed.
+    /// Since the cycles already processed are based on the previous clock speed, this resets
+    /// the reference point.
+    pub fn set_clock_speed(&mut self, cycles: Clock, clock_speed: Clock) {
+        if self.clock_speed != clock_speed {
+            self.clock_speed = clock_speed;
+            self.reset(cycles);
+        }
+    }
+
+
+    /// Get the number of cycles the emulator is expected to have processed at the current time.
+    /// If the emulator lags too far behind, the reference point is moved, so the emulator won't
+    /// try to catch up for a long time after a hiccup.
+    pub fn get_target_cycles(&mut self, cycles: Clock) -> Clock {
+        let elapsed_ns = self.reference_time.elapsed().as_nanos();
+        let target     = self.reference_cycles + ((elapsed_ns * self.clock_speed as u128) / 1_000_000_000) as Clock;
+
+        if target > cycles + MAX_FRAMES_BEHIND * CPU_CYCLES_PER_FRAME {
+            self.reset(cycles);
+            return cycles;
+        }
+
+        target
+    }
+
+
+    /// Get the wall time remaining until the emulator is expected to reach the given number of cycles.
+    pub fn get_time_until(&self, cycles: Clock) -> Duration {
+        let cycles_ahead = cycles.saturating_sub(self.reference_cycles) as u128;
+        let due_time_ns  = (cycles_ahead * 1_000_000_000) / self.clock_speed as u128;
+        let due_time     = Duration::from_nanos(due_time_ns as u64);
+
+        due_time.saturating_sub(self.reference_time.elapsed())
+    }
+
+
+    /// Updates the measured emulation speed. Returns `true` if a new value was measured.
+    pub fn measure_speed(&mut self, cycles: Clock) -> bool {
+        let elapsed = self.measure_begin_time.elapsed();
+
+        if elapsed >= SPEED_MEASURE_INTERVAL {
+            let cycles_processed  = cycles.saturating_sub(self.measure_begin_cycles);
+            let cycles_expected   = self.clock_speed as f64 * elapsed.as_secs_f64();
+
+            self.current_speed        = (cycles_processed as f64 / cycles_expected) as f32;
+            self.measure_begin_time   = Instant::now();
+            self.measure_begin_cycles = cycles;
+
+            return true;
+        }
+
+        false
+    }
+
+
+    /// Get the emulation speed measured in the last interval, where `1.0` is the original speed.
+    pub fn get_current_speed(&self) -> f32 {
+        self.current_speed
+    }
+}
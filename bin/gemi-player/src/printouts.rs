@@ -0,0 +1,50 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::printer::GameBoyPrinter;
+
+use crate::output_files;
+
+
+/// The name of the directory where to store printouts.
+const PRINTOUTS_DIR: &str = "printouts";
+
+
+/// Connects a GameBoy Printer to the serial port, which saves each printout into a PNG file.
+/// Printouts will be stored in a directory next to the ROM file of the current cartridge,
+/// named by the ROM, the time the printer was connected and a running number.
+pub fn connect_printer(gb: &mut GameBoy) -> Result<(), String> {
+    let file_prefix = output_files::get_output_file(gb, PRINTOUTS_DIR, "printout", "")?;
+    let mut count   = 0;
+
+    let printer = GameBoyPrinter::new(move |printout| {
+        count += 1;
+
+        let mut file = file_prefix.clone().into_os_string();
+        file.push(format!("_{count}.png"));
+
+        match printout.save_png(&file) {
+            Ok(_)  => println!("Printout saved to {}", file.to_string_lossy()),
+            Err(e) => eprintln!("Failed to save printout: {e}"),
+        }
+    });
+
+    gb.get_peripherals_mut().serial.connect_device(Box::new(printer));
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::snapshots::{Snapshot, SnapshotError};
+
+
+/// The number of slots available to store save states.
+pub const SAVE_STATE_SLOTS: u8 = 4;
+
+
+/// Get the file to store the save state of a given slot. The file will be located
+/// next to the ROM file of the current cartridge, named like `<rom>.slot<n>.state`.
+fn get_state_file(gb: &GameBoy, slot: u8) -> Result<PathBuf, String> {
+    let rom_file = gb.get_peripherals().mem.get_cartridge()
+        .and_then(|cartridge| cartridge.get_source_file())
+        .ok_or_else(|| String::from("No cartridge loaded from a file"))
+        ?
+    ;
+
+    Ok(rom_file.with_extension(format!("slot{slot}.state")))
+}
+
+
+/// Saves the current state of the emulator into the given slot.
+/// The cartridge RAM will be saved as well, so both files stay consistent.
+pub fn save_state(gb: &GameBoy, slot: u8) -> Result<(), String> {
+    let state_file = get_state_file(gb, slot)?;
+
+    gb.get_peripherals().mem.save_cartridge_ram_to_file_if_any()
+        .map_err(|e| format!("Failed to save cartridge RAM: {e}"))
+        ?
+    ;
+
+    let snapshot = Snapshot::create_from(gb)
+        .map_err(|e| format!("Failed to save state {slot}: {e}"))
+        ?
+    ;
+
+    snapshot.save_to_file(&state_file)
+        .map_err(|e| format!("Failed to save state {slot}: {e}"))
+}
+
+
+/// Loads the state stored in the given slot and replaces the current emulator state with it.
+/// Fails without modifying the emulator if there is no state stored or the state
+/// was created with a different ROM.
+pub fn load_state(gb: &mut GameBoy, slot: u8) -> Result<(), String> {
+    let state_file = get_state_file(gb, slot)?;
+
+    if !state_file.exists() {
+        return Err(format!("State {slot} not found"));
+    }
+
+    let restored = Snapshot::read_from_file(&state_file)
+        .map_err(SnapshotError::from)
+        .and_then(|snapshot| snapshot.restore())
+        .map_err(|e| format!("Failed to load state {slot}: {e}"))
+        ?
+    ;
+
+    let is_same_rom = match (
+        gb.get_peripherals().mem.get_cartridge(),
+        restored.get_peripherals().mem.get_cartridge()
+    ) {
+        (Some(current), Some(restored)) => current.has_same_rom(restored),
+        _ => false,
+    };
+
+    if !is_same_rom {
+        return Err(format!("State {slot} was created with a different ROM"));
+    }
+
+    gb.replace_with(restored);
+
+    Ok(())
+}
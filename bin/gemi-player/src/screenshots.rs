@@ -0,0 +1,63 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+
+use gemi_core::gameboy::GameBoy;
+
+use crate::output_files;
+use crate::window::Window;
+
+
+/// The name of the directory where to store screenshots.
+const SCREENSHOTS_DIR: &str = "screenshots";
+
+
+/// Get the file to store a new screenshot. Screenshots will be stored in a directory
+/// next to the ROM file of the current cartridge, named by the ROM and a timestamp.
+fn get_screenshot_file(gb: &GameBoy, suffix: &str) -> Result<PathBuf, String> {
+    output_files::get_output_file(gb, SCREENSHOTS_DIR, "screenshot", &format!("{suffix}.png"))
+}
+
+
+/// Saves the current content of the LCD into a PNG file.
+/// Returns the path of the file created.
+pub fn save_screenshot(gb: &GameBoy) -> Result<PathBuf, String> {
+    let file = get_screenshot_file(gb, "")?;
+
+    gb.save_screenshot(&file)
+        .map_err(|e| format!("Failed to save screenshot: {e}"))
+        ?
+    ;
+
+    Ok(file)
+}
+
+
+/// Saves the content currently displayed in the window, which may be one
+/// of the debug views like background or objects, into a PNG file.
+/// Returns the path of the file created.
+pub fn save_display_content(gb: &GameBoy, window: &Window) -> Result<PathBuf, String> {
+    let file = get_screenshot_file(gb, window.get_display_mode().get_file_suffix())?;
+
+    window.get_display_image().save(&file)
+        .map_err(|e| format!("Failed to save screenshot: {e}"))
+        ?
+    ;
+
+    Ok(file)
+}
@@ -15,27 +15,79 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use sdl2::audio::*;
 use sdl2::Sdl;
 use gemi_core::apu::apu::Apu;
-use gemi_core::apu::{audio_output, sample};
-use gemi_core::apu::audio_output::{AudioOutputSpec, SamplesReceiver};
+use gemi_core::apu::sample;
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SamplesReceiver};
+use gemi_core::apu::audio_recorder::AudioRecorder;
+use gemi_core::apu::sample::StereoSample;
 
 
 const SAMPLE_FREQ    : u32   = 48_000;
 const CHANNEL_COUNT  : u8    = 2;
-const BUFFER_SAMPLES : usize = audio_output::SAMPLE_BUFFER_SIZE;
 const DEFAULT_VOLUME : f32   = 0.10;
 
+/// The maximum deviation of the playback speed used to keep the queue depth at its target,
+/// which is small enough to not be noticed as a change of pitch.
+const MAX_SKEW       : f32   = 0.005;
+
+
+/// Configuration of the audio playback.
+#[derive(Copy, Clone)]
+pub struct AudioConfig {
+    /// The size of the audio device buffer in samples per channel.
+    /// Smaller values reduce the latency, but may cause underruns on some systems.
+    pub buffer_samples: u16,
+
+    /// The number of samples per channel to be kept queued ahead of the playback.
+    pub target_queue_depth: usize,
+
+    /// The maximum number of samples per channel to be queued. When exceeded,
+    /// the oldest samples will be dropped to prevent the playback from lagging behind.
+    pub max_queue_depth: usize,
+}
+
 
 /// SoundQueue to feed sound data into the audio device.
 pub struct SoundQueue {
     /// The device for audio output
     audio_device:  AudioDevice<SoundQueueCallback>,
 
+    /// The sample rate of the audio device, which may differ from the rate requested.
+    sample_rate: u32,
+
     /// Mirror value of the configured volume in the queue callback.
     /// Used to avoid unnecessary locking of the callback object.
     volume: f32,
+
+    /// Mirror value of the fast forward flag in the queue callback.
+    fast_forward: bool,
+
+    /// Whether the playback is currently paused.
+    paused: bool,
+
+    /// Counters of underruns and overruns, shared with the queue callback.
+    statistics: Arc<SoundQueueStatistics>,
+
+    /// The file the audio output is currently recorded into, if any.
+    recording_file: Option<PathBuf>,
+}
+
+
+/// Counters to validate the queue configuration.
+#[derive(Default)]
+struct SoundQueueStatistics {
+    /// The number of times the device requested more samples than available.
+    underruns: AtomicU64,
+
+    /// The number of times queued samples were dropped, because the maximum queue depth was exceeded.
+    overruns: AtomicU64,
 }
 
 
@@ -44,52 +96,105 @@ struct SoundQueueCallback {
     /// Receiver object of the channel to receive audio samples from the backend.
     receiver: SamplesReceiver,
 
+    /// Samples received from the backend, but not played yet, as left and right values.
+    pending: VecDeque<(f32, f32)>,
+
+    /// The fractional position of the next sample to be played within the pending samples.
+    position: f32,
+
+    /// Whether enough samples were queued to start playing.
+    playing: bool,
+
+    /// The configured queue depths.
+    config: AudioConfig,
+
     /// The current volume.
     volume: f32,
+
+    /// While fast forwarding, the emulator produces more samples than can be played.
+    /// In this case, surplus samples will be discarded to keep the original pitch.
+    fast_forward: bool,
+
+    /// Counters of underruns and overruns.
+    statistics: Arc<SoundQueueStatistics>,
+
+    /// Records all samples received from the backend, while a recording is running.
+    recorder: Option<AudioRecorder>,
+}
+
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            buffer_samples:     1024,
+            target_queue_depth: 2048,
+            max_queue_depth:    8192,
+        }
+    }
 }
 
 
 impl SoundQueue {
     /// Creates a new SoundQueue
-    pub fn create(sdl: &Sdl, apu: &mut Apu) -> Result<Self, String> {
+    pub fn create(sdl: &Sdl, apu: &mut Apu, config: AudioConfig) -> Result<Self, String> {
         let sdl_audio = sdl.audio()?;
 
         let audio_spec = AudioSpecDesired {
             freq:     Some(SAMPLE_FREQ as i32),
             channels: Some(CHANNEL_COUNT),
-            samples:  Some(BUFFER_SAMPLES as u16),
+            samples:  Some(config.buffer_samples),
         };
 
         // open a channel to the APU backend to receive audio data
         let receiver = apu.get_audio_output().open_channel(
             AudioOutputSpec {
                 sample_rate: SAMPLE_FREQ,
+                format:      SampleFormat::F32,
             }
         ).ok_or_else(
             || String::from("Cannot connect to emulator")
         )
         ?;
 
+        let statistics          = Arc::new(SoundQueueStatistics::default());
+        let callback_statistics = statistics.clone();
+
         let audio_device = sdl_audio.open_playback(
             None,
             &audio_spec,
             move |_| {
                 SoundQueueCallback {
                     receiver,
+                    pending: VecDeque::with_capacity(config.max_queue_depth),
+                    position: 0.0,
+                    playing: false,
+                    config,
                     volume: DEFAULT_VOLUME,
+                    fast_forward: false,
+                    statistics: callback_statistics,
+                    recorder: None,
                 }
             }
         )?;
 
+        // the device may not support the requested sample rate,
+        // so the emulator needs to generate samples with the rate actually obtained
+        let sample_rate = audio_device.spec().freq as u32;
+        apu.get_audio_output().set_sample_rate(sample_rate);
+
         audio_device.resume();
 
         Ok (Self {
             audio_device,
-            volume: DEFAULT_VOLUME
+            sample_rate,
+            volume: DEFAULT_VOLUME,
+            fast_forward: false,
+            paused: false,
+            statistics,
+            recording_file: None,
         })
     }
 
-
     /// Set the playback volume.
     pub fn set_volume(&mut self, volume: f32) {
         let volume_clamped = volume.clamp(0.0, 1.0);
@@ -106,12 +211,169 @@ impl SoundQueue {
     pub fn get_volume(&self) -> f32 {
         self.volume
     }
+
+
+    /// Enables or disables fast forward mode. While enabled, the sample buffers
+    /// produced in excess will be dropped instead of being queued.
+    pub fn set_fast_forward(&mut self, fast_forward: bool) {
+        if self.fast_forward != fast_forward {
+            self.fast_forward = fast_forward;
+
+            self.audio_device.lock().fast_forward = fast_forward;
+        }
+    }
+
+
+    /// Pauses or resumes the playback.
+    /// When resuming, all samples queued in the meantime will be discarded,
+    /// so the playback does not lag behind the emulation.
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused != paused {
+            self.paused = paused;
+
+            if paused {
+                self.audio_device.pause();
+            }
+            else {
+                self.audio_device.lock().discard_pending();
+                self.audio_device.resume();
+            }
+        }
+    }
+
+
+    /// Starts recording the audio output into a WAV file.
+    /// A recording already running will be stopped.
+    pub fn start_recording(&mut self, file: &Path) -> Result<(), String> {
+        self.stop_recording()?;
+
+        let recorder = AudioRecorder::create(file, self.sample_rate)
+            .map_err(|e| format!("Failed to create {}: {e}", file.display()))
+            ?
+        ;
+
+        self.audio_device.lock().recorder = Some(recorder);
+        self.recording_file = Some(file.to_path_buf());
+
+        Ok(())
+    }
+
+
+    /// Stops the current recording, if any, and completes the recorded file.
+    /// Returns the file the audio output was recorded into.
+    pub fn stop_recording(&mut self) -> Result<Option<PathBuf>, String> {
+        let recorder = self.audio_device.lock().recorder.take();
+        let file     = self.recording_file.take();
+
+        if let Some(recorder) = recorder {
+            recorder.finish()
+                .map_err(|e| format!("Failed to save audio recording: {e}"))
+                ?
+            ;
+        }
+
+        Ok(file)
+    }
+
+
+    /// Checks whether the audio output is currently being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording_file.is_some()
+    }
+}
+
+
+impl SoundQueueCallback {
+    /// Discards all samples received but not yet played.
+    fn discard_pending(&mut self) {
+        while self.receiver.try_recv().is_ok() {
+        }
+
+        self.pending.clear();
+        self.position = 0.0;
+        self.playing  = false;
+    }
+
+
+    /// Moves all sample buffers received from the backend into the queue of pending samples.
+    /// If the queue exceeds its maximum depth, or is above its target depth while
+    /// fast forwarding, the oldest samples will be dropped.
+    fn receive_samples(&mut self) {
+        while let Ok(samples) = self.receiver.try_recv() {
+            self.record_samples(&samples[..]);
+
+            self.pending.extend(
+                samples.iter().map(|sample| (sample.left.get_value(), sample.right.get_value()))
+            );
+        }
+
+        let max_depth = if self.fast_forward {
+            self.config.target_queue_depth
+        }
+        else {
+            self.config.max_queue_depth
+        };
+
+        if self.pending.len() > max_depth {
+            let surplus = self.pending.len() - self.config.target_queue_depth;
+            self.pending.drain(..surplus);
+
+            if !self.fast_forward {
+                self.statistics.overruns.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+
+    /// Writes the samples received into the current recording, if any.
+    /// On failure, the recording will be cancelled.
+    fn record_samples(&mut self, samples: &[StereoSample]) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.write_samples(samples) {
+                eprintln!("Failed to record audio: {e}");
+                self.recorder = None;
+            }
+        }
+    }
+
+
+    /// Computes the number of samples to be advanced for each sample played.
+    /// This slightly adjusts the playback speed, so the queue depth stays around its target,
+    /// to compensate for the clocks of the emulator and the audio device drifting apart.
+    fn get_playback_step(&self) -> f32 {
+        let target = self.config.target_queue_depth.max(1) as f32;
+        let depth  = self.pending.len() as f32;
+        let skew   = ((depth - target) / target * MAX_SKEW).clamp(-MAX_SKEW, MAX_SKEW);
+
+        1.0 + skew
+    }
+}
+
+
+impl SoundQueueStatistics {
+    /// Prints the number of underruns and overruns, if any, and the number
+    /// of sample buffers dropped by the emulator before being received.
+    fn print(&self, dropped_buffers: u64) {
+        let underruns = self.underruns.load(Ordering::Relaxed);
+        let overruns  = self.overruns.load(Ordering::Relaxed);
+
+        if underruns != 0 || overruns != 0 || dropped_buffers != 0 {
+            println!("Audio playback: {underruns} underruns, {overruns} overruns, {dropped_buffers} buffers dropped");
+        }
+    }
 }
 
 
 impl Drop for SoundQueue {
     fn drop(&mut self) {
         self.audio_device.pause();
+
+        let dropped_buffers = self.audio_device.lock().receiver.get_dropped_buffers();
+        self.statistics.print(dropped_buffers);
+
+        if let Err(e) = self.stop_recording() {
+            eprintln!("{e}");
+        }
     }
 }
 
@@ -120,19 +382,48 @@ impl AudioCallback for SoundQueueCallback {
     type Channel = sample::SampleType;
 
     fn callback(&mut self, out: &mut [Self::Channel]) {
-        let result = self.receiver.try_recv();
+        self.receive_samples();
 
-        match result {
-            Ok(samples) => {
-                for i in 0..BUFFER_SAMPLES {
-                    out[i * 2 + 0] = self.volume * samples[i].left.get_value();
-                    out[i * 2 + 1] = self.volume * samples[i].right.get_value();
-                }
+        // after starting or an underrun, wait until the queue was filled again
+        if !self.playing {
+            if self.pending.len() < self.config.target_queue_depth {
+                out.fill(0.0);
+                return;
             }
 
-            Err(_) => {
-                out.iter_mut().for_each(|x| *x = 0.0);
+            self.playing = true;
+        }
+
+        let step     = self.get_playback_step();
+        let channels = CHANNEL_COUNT as usize;
+
+        for frame in 0 .. out.len() / channels {
+            let index  = self.position as usize;
+            let offset = frame * channels;
+
+            // not enough samples left to interpolate the next one
+            if index + 1 >= self.pending.len() {
+                self.statistics.underruns.fetch_add(1, Ordering::Relaxed);
+                self.playing = false;
+
+                out[offset..].fill(0.0);
+                break;
             }
+
+            // linear interpolation between the two samples around the current position
+            let fraction = self.position - (index as f32);
+            let (l0, r0) = self.pending[index];
+            let (l1, r1) = self.pending[index + 1];
+
+            out[offset + 0] = self.volume * (l0 + (l1 - l0) * fraction);
+            out[offset + 1] = self.volume * (r0 + (r1 - r0) * fraction);
+
+            self.position += step;
         }
+
+        // remove all samples already played
+        let consumed = (self.position as usize).min(self.pending.len());
+        self.pending.drain(..consumed);
+        self.position -= consumed as f32;
     }
 }
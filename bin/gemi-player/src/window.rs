@@ -17,16 +17,25 @@
 
 extern crate sdl2;
 
-use gemi_core::input::{Input, InputButton};
+use gemi_core::input::Input;
 use gemi_core::gameboy::GameBoy;
 use gemi_core::mmu::locations::MEMORY_LOCATION_SPRITES_BEGIN;
 use gemi_core::ppu::flags::LcdControlFlag;
 use gemi_core::ppu::graphic_data::{Color, DmgPalette, TileMap, TileSet};
 use gemi_core::ppu::ppu::{LcdBuffer, Ppu, SCREEN_H, SCREEN_W};
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use gemi_core::sgb::{SgbState, SGB_BORDER_H, SGB_BORDER_W, SGB_SCREEN_OFFSET_X, SGB_SCREEN_OFFSET_Y};
+use image::RgbaImage;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::rect::Rect;
+use sdl2::pixels;
 use sdl2::render::{Texture, TextureCreator, UpdateTextureError, WindowCanvas};
+use sdl2::video::FullscreenType;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::controllers::Controllers;
+use crate::key_config::{FunctionBindings, KeyBindings, KeyConfig, PlayerFunction, PlayerInput};
+use crate::save_states::SAVE_STATE_SLOTS;
 use crate::sound_queue::SoundQueue;
 
 
@@ -44,49 +53,116 @@ pub enum State {
 }
 
 
+/// Actions requested by the user, which need to be handled by the application.
+pub enum PlayerAction {
+    /// Save the current emulator state into the given slot.
+    SaveState(u8),
+
+    /// Load the emulator state stored in the given slot.
+    LoadState(u8),
+
+    /// Save the current LCD content into a file.
+    Screenshot,
+
+    /// Save the content currently displayed, which may be a debug view, into a file.
+    ScreenshotDisplayContent,
+
+    /// Switch to the next color correction mode.
+    CycleColorCorrection,
+
+    /// Change the emulation speed multiplier by the given amount.
+    ChangeSpeed(f64),
+
+    /// Start or stop recording the audio output into a file.
+    ToggleAudioRecording,
+
+    /// Mute or unmute the audio channel with the given number.
+    ToggleChannelMute(u8),
+
+    /// Select the next audio channel to be played exclusively.
+    CycleChannelSolo,
+
+    /// Perform a soft reset of the emulator.
+    Reset,
+}
+
+
+/// A message to be displayed for a short time.
+struct Message {
+    text:       String,
+    expires_at: Instant,
+}
+
+
+impl DisplayMode {
+    /// Get a suffix for files created from the content displayed in this mode.
+    pub fn get_file_suffix(&self) -> &'static str {
+        match self {
+            DisplayMode::Game       => "",
+            DisplayMode::Background => "_background",
+            DisplayMode::Objects    => "_objects",
+        }
+    }
+}
+
+
 /// A texture with a separate color buffer to store pixel data
 /// until it will be transferred into the texture GPU memory.
 pub struct BufferedTexture {
     width:      u32,
     height:     u32,
     buffer:     Vec<u8>,
+    previous:   Option<Vec<u8>>,
     texture:    Texture,
 }
 
 
+/// Keeps track of the SuperGameBoy border drawn around the game content.
+struct SgbBorderState {
+    /// The generation of the border data, which was drawn into the texture.
+    generation: u32,
+}
+
+
 /// A window to present the Gameboy's output.
 pub struct Window {
+    title:              String,
     display_scale:      u32,
+    viewport:           Rect,
     event_pump:         sdl2::EventPump,
+    controllers:        Controllers,
     canvas:             WindowCanvas,
     texture_game:       BufferedTexture,
     texture_background: BufferedTexture,
     texture_objects:    BufferedTexture,
+    sgb_border:         Option<SgbBorderState>,
     state:              State,
     display_mode:       DisplayMode,
     key_bindings:       KeyBindings,
+    function_bindings:  FunctionBindings,
     audio:              SoundQueue,
+    fast_forward:       bool,
+    paused:             bool,
+    slow_motion:        bool,
+    frame_advance:      bool,
+    frame_blending:     bool,
+    frame_blend_weight: f32,
+    speed:              f32,
+    fps:                Option<f64>,
+    message:            Option<Message>,
+    save_state_slot:    u8,
+    pending_actions:    Vec<PlayerAction>,
 }
 
 
-/// Alias type for the key bindings used by this window.
-type KeyBindings = gemi_utils::keybindings::KeyBindings<Keycode>;
-
-/// Create the keybindings used by this window.
-fn make_keybindings() -> KeyBindings {
-    KeyBindings::with_mapping(
-        vec![
-            (InputButton::DPadRight,    vec![Keycode::D,    Keycode::Right  ]),
-            (InputButton::DPadLeft,     vec![Keycode::A,    Keycode::Left   ]),
-            (InputButton::DPadUp,       vec![Keycode::W,    Keycode::Up     ]),
-            (InputButton::DPadDown,     vec![Keycode::S,    Keycode::Down   ]),
-            (InputButton::A,            vec![Keycode::E,    Keycode::X      ]),
-            (InputButton::B,            vec![Keycode::Q,    Keycode::Y      ]),
-            (InputButton::Select,       vec![Keycode::Num1, Keycode::LShift ]),
-            (InputButton::Start,        vec![Keycode::Num2, Keycode::Return ]),
-        ]
-    )
-}
+/// The time a message will be displayed.
+const MESSAGE_DURATION: Duration = Duration::from_secs(2);
+
+/// The scale of the display content when the window gets created.
+const DEFAULT_DISPLAY_SCALE: u32 = 4;
+
+/// The amount to change the emulation speed multiplier with each key press.
+const SPEED_MULTIPLIER_STEP: f64 = 0.25;
 
 
 impl BufferedTexture {
@@ -105,6 +181,7 @@ impl BufferedTexture {
                 width,
                 height,
                 buffer: vec![0xff; size],
+                previous: None,
                 texture,
             }
         )
@@ -130,6 +207,50 @@ impl BufferedTexture {
         self.buffer[offset + 3] = color.a;
     }
 
+    /// Fills the whole buffer with a single color.
+    pub fn fill(&mut self, color: &Color) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set_color(x, y, color);
+            }
+        }
+    }
+
+    /// Blends the current buffer with the content of the previous frame,
+    /// which emulates the slow pixel response of the original LCD.
+    /// `weight` is the share of the previous frame in the result.
+    /// The current buffer, before blending, is kept as the previous frame for the next call.
+    pub fn blend_with_previous(&mut self, weight: f32) {
+        let Some(previous) = &mut self.previous else {
+            self.previous = Some(self.buffer.clone());
+            return;
+        };
+
+        for (current, previous) in self.buffer.iter_mut().zip(previous.iter_mut()) {
+            let value = *current;
+            *current  = ((value as f32) * (1.0 - weight) + (*previous as f32) * weight).round() as u8;
+            *previous = value;
+        }
+    }
+
+    /// Discards the previous frame, so the next frame won't be blended with it.
+    pub fn discard_previous(&mut self) {
+        self.previous = None;
+    }
+
+    /// Creates an RGBA image from the pixel data in the current buffer.
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        // the buffer stores pixels in BGRA order, so swap red and blue channels
+        let pixels = self.buffer
+            .chunks_exact(4)
+            .flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]])
+            .collect()
+        ;
+
+        RgbaImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size does not match the texture size")
+    }
+
     /// Updates the texture with the pixel data in the current buffer.
     pub fn update_texture(&mut self) -> Result<(), UpdateTextureError> {
         self.texture.update(
@@ -139,12 +260,16 @@ impl BufferedTexture {
         )
     }
 
-    /// Copy the texture content into the given canvas.
-    pub fn copy_to_canvas(&self, canvas: &mut WindowCanvas, display_scale: u32) -> Result<(), String> {
+    /// Copy the texture content into the given viewport of a canvas.
+    /// The area outside of the viewport will be filled with black.
+    pub fn copy_to_canvas(&self, canvas: &mut WindowCanvas, viewport: Rect) -> Result<(), String> {
+        canvas.set_draw_color(pixels::Color::BLACK);
+        canvas.clear();
+
         canvas.copy(
             &self.texture,
             Rect::new(0, 0, self.width, self.height),
-            Rect::new(0, 0, self.width * display_scale, self.height * display_scale)
+            viewport
         )
     }
 }
@@ -152,48 +277,86 @@ impl BufferedTexture {
 
 impl Window {
     /// Creates a new window with a given size and title.
-    pub fn create(title: &str, gb: &mut GameBoy) -> Result<Window, String> {
-        let display_scale = 4;
+    /// When `vsync` is enabled, presenting a frame will wait for the display's vertical sync.
+    pub fn create(title: &str, gb: &mut GameBoy, vsync: bool, key_config: KeyConfig) -> Result<Window, String> {
+        let display_scale = DEFAULT_DISPLAY_SCALE;
+
+        // on SuperGameBoy devices, the game content is surrounded by a border
+        let (sgb_border, game_width, game_height) = if gb.get_config().device.is_super_gameboy() {
+            (Some(SgbBorderState { generation: 0 }), SGB_BORDER_W, SGB_BORDER_H)
+        }
+        else {
+            (None, SCREEN_W, SCREEN_H)
+        };
 
         let sdl = sdl2::init()?;
         let video = sdl.video()?;
         let event_pump = sdl.event_pump()?;
+        let controllers = Controllers::new(&sdl, key_config.controller_dead_zone)?;
 
         let window = video
-            .window(title, SCREEN_W * display_scale, SCREEN_H * display_scale)
+            .window(title, game_width * display_scale, game_height * display_scale)
             .position_centered()
+            .resizable()
             .build()
             .map_err(|e| e.to_string())
             ?
         ;
 
-        let canvas = window
+        let mut canvas_builder = window
             .into_canvas()
             .accelerated()
-            .present_vsync()
+        ;
+
+        if vsync {
+            canvas_builder = canvas_builder.present_vsync();
+        }
+
+        let canvas = canvas_builder
             .build()
             .map_err(|e| e.to_string())
             ?
         ;
 
         let texture_creator    = canvas.texture_creator();
-        let texture_game       = BufferedTexture::new(&texture_creator, SCREEN_W, SCREEN_H)?;
+        let mut texture_game   = BufferedTexture::new(&texture_creator, game_width, game_height)?;
         let texture_background = BufferedTexture::new(&texture_creator, 256, 256)?;
         let texture_objects    = BufferedTexture::new(&texture_creator, 16*8, 24*8)?;
 
-        let audio = SoundQueue::create(&sdl, &mut gb.get_peripherals_mut().apu)?;
+        // the border area stays black until the game sends its border
+        if sgb_border.is_some() {
+            texture_game.fill(&Color::black());
+        }
+
+        let audio = SoundQueue::create(&sdl, &mut gb.get_peripherals_mut().apu, key_config.audio)?;
 
         Ok(Window {
+            title: title.to_string(),
             display_scale,
+            viewport: Rect::new(0, 0, game_width * display_scale, game_height * display_scale),
             event_pump,
+            controllers,
             canvas,
             texture_game,
             texture_background,
             texture_objects,
+            sgb_border,
             state: State::Open,
             display_mode: DisplayMode::Game,
-            key_bindings: make_keybindings(),
+            key_bindings: key_config.buttons,
+            function_bindings: key_config.functions,
             audio,
+            fast_forward: false,
+            paused: false,
+            slow_motion: false,
+            frame_advance: false,
+            frame_blending: false,
+            frame_blend_weight: key_config.frame_blend_weight,
+            speed: 1.0,
+            fps: None,
+            message: None,
+            save_state_slot: 1,
+            pending_actions: Vec::new(),
         })
     }
 
@@ -212,6 +375,173 @@ impl Window {
         self.state = State::Closed;
     }
 
+
+    /// Checks whether the user requested to run the emulator in fast forward mode.
+    pub fn is_fast_forward(&self) -> bool {
+        self.fast_forward
+    }
+
+
+    /// Enables or disables fast forward mode.
+    fn set_fast_forward(&mut self, fast_forward: bool) {
+        if self.fast_forward != fast_forward {
+            self.fast_forward = fast_forward;
+            self.audio.set_fast_forward(fast_forward);
+            self.update_title();
+        }
+    }
+
+
+    /// Checks whether the emulation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+
+    /// Pauses or resumes the emulation. While paused, audio playback is paused as well.
+    fn set_paused(&mut self, paused: bool) {
+        if self.paused != paused {
+            self.paused = paused;
+            self.audio.set_paused(paused);
+            self.update_title();
+        }
+    }
+
+
+    /// Requests to run a single frame while the emulation is paused.
+    /// If the emulation is still running, it will be paused.
+    fn request_frame_advance(&mut self) {
+        self.set_paused(true);
+        self.frame_advance = true;
+    }
+
+
+    /// Checks whether the user requested to advance a single frame and resets the request.
+    pub fn take_frame_advance(&mut self) -> bool {
+        std::mem::take(&mut self.frame_advance)
+    }
+
+
+    /// Checks whether the emulation should run in slow motion with half of the original speed.
+    pub fn is_slow_motion(&self) -> bool {
+        self.slow_motion
+    }
+
+
+    /// Enables or disables slow motion.
+    fn toggle_slow_motion(&mut self) {
+        self.slow_motion = !self.slow_motion;
+        self.update_title();
+    }
+
+
+    /// Enables or disables blending each frame with the previous one.
+    fn toggle_frame_blending(&mut self) {
+        self.frame_blending = !self.frame_blending;
+        self.texture_game.discard_previous();
+
+        let state = if self.frame_blending { "on" } else { "off" };
+        self.show_message(format!("Frame blending {state}"));
+    }
+
+
+    /// Starts recording the audio output into a WAV file.
+    pub fn start_audio_recording(&mut self, file: &Path) -> Result<(), String> {
+        self.audio.start_recording(file)?;
+        self.update_title();
+
+        Ok(())
+    }
+
+
+    /// Stops recording the audio output.
+    /// Returns the file the audio output was recorded into, if a recording was running.
+    pub fn stop_audio_recording(&mut self) -> Result<Option<PathBuf>, String> {
+        let result = self.audio.stop_recording();
+        self.update_title();
+
+        result
+    }
+
+
+    /// Checks whether the audio output is currently being recorded.
+    pub fn is_audio_recording(&self) -> bool {
+        self.audio.is_recording()
+    }
+
+
+    /// Displays the current emulation speed relative to the original hardware in the window title.
+    pub fn show_speed(&mut self, speed: f32) {
+        self.speed = speed;
+        self.update_title();
+    }
+
+
+    /// Displays the number of frames per second in the window title.
+    pub fn show_fps(&mut self, fps: f64) {
+        self.fps = Some(fps);
+        self.update_title();
+    }
+
+
+    /// Displays a message in the window title for a short time.
+    pub fn show_message(&mut self, text: String) {
+        self.message = Some(Message {
+            text,
+            expires_at: Instant::now() + MESSAGE_DURATION,
+        });
+
+        self.update_title();
+    }
+
+
+    /// Updates the window title with the current speed and message, if any.
+    fn update_title(&mut self) {
+        let mut title = format!("{} - {:.0}%", self.title, self.speed * 100.0);
+
+        if let Some(fps) = self.fps {
+            title.push_str(&format!(" - {fps:.1} fps"));
+        }
+
+        if self.paused {
+            title.push_str(" (paused)");
+        }
+        else if self.fast_forward {
+            title.push_str(" (fast forward)");
+        }
+        else if self.slow_motion {
+            title.push_str(" (slow motion)");
+        }
+
+        if self.audio.is_recording() {
+            title.push_str(" (recording)");
+        }
+
+        if let Some(message) = &self.message {
+            title.push_str(" - ");
+            title.push_str(&message.text);
+        }
+
+        _ = self.canvas.window_mut().set_title(&title);
+    }
+
+
+    /// Removes the current message, once its display time has expired.
+    fn update_message(&mut self) {
+        if let Some(message) = &self.message {
+            if Instant::now() >= message.expires_at {
+                self.message = None;
+                self.update_title();
+            }
+        }
+    }
+
+
+    /// Takes all actions requested by the user since the last call.
+    pub fn take_actions(&mut self) -> Vec<PlayerAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+
     /// Polls and handles events of this window.
     pub fn poll_events(&mut self) {
         while let Some(event) = self.event_pump.poll_event() {
@@ -220,54 +550,129 @@ impl Window {
                     self.close();
                 }
 
-                Event::KeyDown { keycode: Some(keycode), .. } => {
-                    self.handle_key_down(keycode);
+                Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                    self.update_viewport();
+                }
+
+                // Alt+Enter toggles fullscreen mode and is not passed to the emulator
+                Event::KeyDown { keycode: Some(Keycode::Return), keymod, repeat, .. }
+                    if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) =>
+                {
+                    if !repeat {
+                        self.toggle_fullscreen();
+                    }
+                }
+
+                // Ctrl+R performs a soft reset and is not passed to the emulator
+                Event::KeyDown { keycode: Some(Keycode::R), keymod, repeat, .. }
+                    if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) =>
+                {
+                    if !repeat {
+                        self.pending_actions.push(PlayerAction::Reset);
+                    }
+                }
+
+                Event::KeyDown { keycode: Some(keycode), keymod, .. } => {
+                    self.handle_input(PlayerInput::Key(keycode), true, keymod);
                 }
 
-                Event::KeyUp { keycode: Some(keycode), .. } => {
-                    self.handle_key_up(keycode);
+                Event::KeyUp { keycode: Some(keycode), keymod, .. } => {
+                    self.handle_input(PlayerInput::Key(keycode), false, keymod);
                 }
 
-                _ => { }
+                _ => {
+                    for (input, pressed) in self.controllers.handle_event(&event) {
+                        self.handle_input(PlayerInput::Controller(input), pressed, Mod::NOMOD);
+                    }
+                }
             }
         }
+
+        self.update_message();
     }
 
 
-    fn handle_key_down(&mut self, keycode: Keycode) {
+    /// Handles a key or game controller input being pressed or released.
+    fn handle_input(&mut self, input: PlayerInput, pressed: bool, keymod: Mod) {
         // set the emulator button states
         self.key_bindings.set_key_pressed(
-            keycode,
-            true
+            input,
+            pressed
         );
 
         // handle key events for the player application itself
-        match keycode {
-            Keycode::Escape => { self.close(); },
-            Keycode::F1     => { self.set_display_mode(DisplayMode::Game); }
-            Keycode::F2     => { self.set_display_mode(DisplayMode::Background); }
-            Keycode::F3     => { self.set_display_mode(DisplayMode::Objects); }
+        if let Some(function) = self.function_bindings.find_function(input) {
+            if pressed {
+                self.handle_function(function, keymod);
+            }
+            else {
+                self.handle_function_released(function);
+            }
+        }
+    }
+
 
-            Keycode::KpMinus => {
+    /// Performs a player function requested by the user.
+    fn handle_function(&mut self, function: PlayerFunction, keymod: Mod) {
+        match function {
+            PlayerFunction::Quit                        => { self.close(); }
+            PlayerFunction::FastForward                 => { self.set_fast_forward(true); }
+            PlayerFunction::SaveState                   => { self.pending_actions.push(PlayerAction::SaveState(self.save_state_slot)); }
+            PlayerFunction::LoadState                   => { self.pending_actions.push(PlayerAction::LoadState(self.save_state_slot)); }
+            PlayerFunction::SelectSaveStateSlot(slot)   => { self.select_save_state_slot(slot); }
+            PlayerFunction::Screenshot                  => { self.pending_actions.push(self.get_screenshot_action(keymod)); }
+            PlayerFunction::DisplayGame                 => { self.set_display_mode(DisplayMode::Game); }
+            PlayerFunction::DisplayBackground           => { self.set_display_mode(DisplayMode::Background); }
+            PlayerFunction::DisplayObjects              => { self.set_display_mode(DisplayMode::Objects); }
+            PlayerFunction::CycleColorCorrection        => { self.pending_actions.push(PlayerAction::CycleColorCorrection); }
+            PlayerFunction::Pause                       => { self.set_paused(!self.paused); }
+            PlayerFunction::FrameAdvance                => { self.request_frame_advance(); }
+            PlayerFunction::SlowMotion                  => { self.toggle_slow_motion(); }
+            PlayerFunction::ToggleFrameBlending         => { self.toggle_frame_blending(); }
+            PlayerFunction::IncreaseSpeed               => { self.pending_actions.push(PlayerAction::ChangeSpeed( SPEED_MULTIPLIER_STEP)); }
+            PlayerFunction::DecreaseSpeed               => { self.pending_actions.push(PlayerAction::ChangeSpeed(-SPEED_MULTIPLIER_STEP)); }
+            PlayerFunction::ToggleAudioRecording        => { self.pending_actions.push(PlayerAction::ToggleAudioRecording); }
+            PlayerFunction::ToggleChannelMute(channel)  => { self.pending_actions.push(PlayerAction::ToggleChannelMute(channel)); }
+            PlayerFunction::CycleChannelSolo            => { self.pending_actions.push(PlayerAction::CycleChannelSolo); }
+
+            PlayerFunction::VolumeUp => {
                 let volume = self.audio.get_volume();
-                self.audio.set_volume(volume - 0.05);
+                self.audio.set_volume(volume + 0.05);
             }
 
-            Keycode::KpPlus => {
+            PlayerFunction::VolumeDown => {
                 let volume = self.audio.get_volume();
-                self.audio.set_volume(volume + 0.05);
+                self.audio.set_volume(volume - 0.05);
             }
+        }
+    }
+
 
-            _ => { }
+    /// Handles the input bound to a player function being released.
+    fn handle_function_released(&mut self, function: PlayerFunction) {
+        if function == PlayerFunction::FastForward {
+            self.set_fast_forward(false);
         }
     }
 
 
-    fn handle_key_up(&mut self, keycode: Keycode) {
-        self.key_bindings.set_key_pressed(
-            keycode,
-            false
-        );
+    /// Get the screenshot action to be performed, which depends on whether shift is pressed or not.
+    fn get_screenshot_action(&self, keymod: Mod) -> PlayerAction {
+        if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+            PlayerAction::ScreenshotDisplayContent
+        }
+        else {
+            PlayerAction::Screenshot
+        }
+    }
+
+
+    /// Selects the slot to be used for saving and loading states.
+    fn select_save_state_slot(&mut self, slot: u8) {
+        if (1..=SAVE_STATE_SLOTS).contains(&slot) {
+            self.save_state_slot = slot;
+            self.show_message(format!("Slot {slot} selected"));
+        }
     }
 
 
@@ -277,28 +682,107 @@ impl Window {
     }
 
 
+    /// Get the current display mode.
+    pub fn get_display_mode(&self) -> &DisplayMode {
+        &self.display_mode
+    }
+
+
+    /// Get an image of the content currently displayed.
+    pub fn get_display_image(&self) -> RgbaImage {
+        match self.display_mode {
+            DisplayMode::Game       => self.texture_game.to_rgba_image(),
+            DisplayMode::Background => self.texture_background.to_rgba_image(),
+            DisplayMode::Objects    => self.texture_objects.to_rgba_image(),
+        }
+    }
+
+
     /// Switches the display mode to display the selected content.
     pub fn set_display_mode(&mut self, mode: DisplayMode) {
         if self.display_mode != mode {
             self.display_mode = mode;
 
-            let target_texture = match self.display_mode {
-                DisplayMode::Game       => &self.texture_game,
-                DisplayMode::Background => &self.texture_background,
-                DisplayMode::Objects    => &self.texture_objects,
-            };
+            // don't blend with outdated content when returning to the game
+            self.texture_game.discard_previous();
+
+            let (width, height) = self.get_display_content_size();
+
+            // keep the current scale, unless the window is in fullscreen mode
+            if self.canvas.window().fullscreen_state() == FullscreenType::Off {
+                self.canvas.window_mut().set_size(
+                    self.display_scale * width,
+                    self.display_scale * height
+                ).unwrap();
+            }
+
+            self.update_viewport();
+        }
+    }
+
+
+    /// Get the size of the content to be displayed in the current display mode.
+    fn get_display_content_size(&self) -> (u32, u32) {
+        let texture = match self.display_mode {
+            DisplayMode::Game       => &self.texture_game,
+            DisplayMode::Background => &self.texture_background,
+            DisplayMode::Objects    => &self.texture_objects,
+        };
 
-            self.canvas.window_mut().set_size(
-                self.display_scale * target_texture.get_width(),
-                self.display_scale * target_texture.get_height()
-            ).unwrap();
+        (texture.get_width(), texture.get_height())
+    }
+
+
+    /// Get the area of the window where the display content is drawn.
+    /// This may be used to translate window coordinates into display coordinates.
+    pub fn get_viewport(&self) -> Rect {
+        self.viewport
+    }
+
+
+    /// Computes the largest integer scale for the display content to fit into the window
+    /// and centers the display content within the window.
+    fn update_viewport(&mut self) {
+        let (content_width, content_height) = self.get_display_content_size();
+        let (output_width,  output_height)  = self.canvas.output_size().unwrap_or((content_width, content_height));
+
+        let scale = (output_width / content_width)
+            .min(output_height / content_height)
+            .max(1)
+        ;
+
+        let viewport_width  = content_width  * scale;
+        let viewport_height = content_height * scale;
+
+        self.display_scale = scale;
+        self.viewport      = Rect::new(
+            (output_width  as i32 - viewport_width  as i32) / 2,
+            (output_height as i32 - viewport_height as i32) / 2,
+            viewport_width,
+            viewport_height
+        );
+    }
+
+
+    /// Switches between windowed and fullscreen mode.
+    fn toggle_fullscreen(&mut self) {
+        let fullscreen_type = match self.canvas.window().fullscreen_state() {
+            FullscreenType::Off => FullscreenType::Desktop,
+            _                   => FullscreenType::Off,
+        };
+
+        if let Err(e) = self.canvas.window_mut().set_fullscreen(fullscreen_type) {
+            self.show_message(format!("Failed to switch fullscreen mode: {e}"));
         }
+
+        self.update_viewport();
     }
 
     /// Presents the content of a LCD buffer on the window.
-    pub fn present(&mut self, lcd: &LcdBuffer, ppu: &Ppu) {
+    /// On SuperGameBoy devices, the border received by the SuperGameBoy will be drawn around the game.
+    pub fn present(&mut self, lcd: &LcdBuffer, ppu: &Ppu, sgb: Option<&SgbState>) {
         match self.display_mode {
-            DisplayMode::Game       => self.present_game(lcd),
+            DisplayMode::Game       => self.present_game(lcd, sgb),
             DisplayMode::Background => self.present_background(ppu),
             DisplayMode::Objects    => self.present_objects(ppu),
         }
@@ -307,15 +791,45 @@ impl Window {
 
     /// Present the current LCD buffer content on the screen.
     /// This will be the content as it would be displayed to the player.
-    fn present_game(&mut self, lcd: &LcdBuffer) {
+    fn present_game(&mut self, lcd: &LcdBuffer, sgb: Option<&SgbState>) {
+        let (offset_x, offset_y) = match &mut self.sgb_border {
+            Some(sgb_border) => {
+                // draw the border only when it was changed
+                if let Some(sgb) = sgb {
+                    if let Some(border) = sgb.get_border() {
+                        if sgb_border.generation != sgb.get_border_generation() {
+                            sgb_border.generation = sgb.get_border_generation();
+
+                            let pixels = border.render(Color::black());
+
+                            for y in 0..SGB_BORDER_H {
+                                for x in 0..SGB_BORDER_W {
+                                    self.texture_game.set_color(x, y, &pixels[(y * SGB_BORDER_W + x) as usize]);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                (SGB_SCREEN_OFFSET_X, SGB_SCREEN_OFFSET_Y)
+            }
+
+            None => (0, 0),
+        };
+
         // convert palette based image data into RGBA
         for y in 0..SCREEN_H {
             for x in 0..SCREEN_W {
                 let color = lcd.get_pixel(x, y);
-                self.texture_game.set_color(x, y, &color);
+                self.texture_game.set_color(offset_x + x, offset_y + y, &color);
             }
         }
 
+        // blend with the previous frame before uploading, if enabled
+        if self.frame_blending {
+            self.texture_game.blend_with_previous(self.frame_blend_weight);
+        }
+
         // update texture
         self.texture_game.update_texture()
             .map_err(|e| e.to_string())
@@ -323,7 +837,7 @@ impl Window {
         ;
 
         // copy texture into framebuffer
-        self.texture_game.copy_to_canvas(&mut self.canvas, self.display_scale).unwrap();
+        self.texture_game.copy_to_canvas(&mut self.canvas, self.viewport).unwrap();
 
         // present the framebuffer
         self.canvas.present();
@@ -361,7 +875,7 @@ impl Window {
         ;
 
         // copy texture into framebuffer
-        self.texture_background.copy_to_canvas(&mut self.canvas, self.display_scale).unwrap();
+        self.texture_background.copy_to_canvas(&mut self.canvas, self.viewport).unwrap();
 
         // present the framebuffer
         self.canvas.present();
@@ -407,7 +921,7 @@ impl Window {
         ;
 
         // copy texture into framebuffer
-        self.texture_objects.copy_to_canvas(&mut self.canvas, self.display_scale).unwrap();
+        self.texture_objects.copy_to_canvas(&mut self.canvas, self.viewport).unwrap();
 
         // present the framebuffer
         self.canvas.present();
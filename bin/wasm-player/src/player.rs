@@ -20,10 +20,14 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::wasm_bindgen;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
 
-use gemi_core::apu::audio_output::{AudioOutputSpec, SamplesReceiver};
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SamplesReceiver};
 use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
 use gemi_core::input::InputButton;
 use gemi_core::mmu::memory_data::MemoryData;
+use gemi_core::ppu::color_correction::ColorCorrection;
+use gemi_core::ppu::graphic_data::Color;
+use gemi_core::ppu::ppu::{SCREEN_H, SCREEN_W};
+use gemi_core::sgb::{SGB_BORDER_H, SGB_BORDER_W, SGB_SCREEN_OFFSET_X, SGB_SCREEN_OFFSET_Y};
 
 use crate::cartridge::Cartridge;
 
@@ -42,6 +46,16 @@ pub struct WasmPlayer {
 
     /// The key bindings to use for mapping JS key events to emulator input.
     key_bindings: KeyBindings,
+
+    /// On SuperGameBoy devices, the RGBA data of the frame including the border
+    /// and the generation of the border data last drawn into it.
+    sgb_frame: Option<(u32, Vec<u8>)>,
+
+    /// The number of frames to be skipped from rendering after each frame being rendered.
+    frame_skip: u32,
+
+    /// The number of frames still to be skipped until the next frame will be rendered.
+    frames_to_skip: u32,
 }
 
 
@@ -112,9 +126,17 @@ impl WasmPlayer {
         ;
 
         // finalize and initialize the emulator
-        let mut gb = builder.finish()?;
+        let mut gb = builder.finish()
+                .map_err(|e| JsValue::from_str(&e.to_string()))?
+        ;
         gb.initialize();
 
+        // on SuperGameBoy devices, the game is surrounded by a border, which stays black until received
+        let sgb_frame = gb.get_peripherals().sgb.as_ref().map(|_| {
+            let black = Color::black();
+            (0, [black.r, black.g, black.b, black.a].repeat((SGB_BORDER_W * SGB_BORDER_H) as usize))
+        });
+
         Ok(
             WasmPlayer {
                 gb,
@@ -123,6 +145,11 @@ impl WasmPlayer {
                 samples_receiver: None,
 
                 key_bindings: default_keymap(),
+
+                sgb_frame,
+
+                frame_skip:     0,
+                frames_to_skip: 0,
             }
         )
     }
@@ -135,6 +162,22 @@ impl WasmPlayer {
     }
 
 
+    /// Get the width of the frames rendered to the canvas.
+    /// On SuperGameBoy devices, this includes the border.
+    #[wasm_bindgen]
+    pub fn get_display_width(&self) -> u32 {
+        if self.sgb_frame.is_some() { SGB_BORDER_W } else { SCREEN_W }
+    }
+
+
+    /// Get the height of the frames rendered to the canvas.
+    /// On SuperGameBoy devices, this includes the border.
+    #[wasm_bindgen]
+    pub fn get_display_height(&self) -> u32 {
+        if self.sgb_frame.is_some() { SGB_BORDER_H } else { SCREEN_H }
+    }
+
+
     /// Checks whether the emulator is currently running in GBC mode.
     #[wasm_bindgen]
     pub fn is_gbc_mode(&self) -> bool {
@@ -145,28 +188,93 @@ impl WasmPlayer {
     }
 
 
+    /// Set the color correction to be applied on GameBoy Color output.
+    /// `name` is the name of the color correction mode, which is one of
+    /// "Off", "CGB LCD" or "GBA LCD".
+    #[wasm_bindgen]
+    pub fn set_color_correction(&mut self, name: String) -> Result<(), JsValue> {
+        let color_correction = ColorCorrection::from_name(&name)
+            .ok_or_else(|| JsValue::from_str(&format!("Invalid color correction '{name}'")))
+            ?
+        ;
+
+        self.gb.get_peripherals_mut().ppu.set_color_correction(color_correction);
+
+        Ok(())
+    }
+
+
+    /// Get the name of the color correction currently applied on GameBoy Color output.
+    #[wasm_bindgen]
+    pub fn get_color_correction(&self) -> String {
+        self.gb.get_peripherals().ppu.get_color_correction().get_name().to_string()
+    }
+
+
     /// Process the next frame and publish it to the canvas.
     #[wasm_bindgen]
     pub fn next_frame(&mut self) -> Result<(), JsValue> {
+        let render = self.frames_to_skip == 0;
+
+        self.gb.get_peripherals_mut().ppu.set_render_enabled(render);
         self.process_frame();
-        self.render_frame()?;
+
+        if render {
+            self.render_frame()?;
+            self.frames_to_skip = self.frame_skip;
+        }
+        else {
+            self.frames_to_skip -= 1;
+        }
 
         Ok(())
     }
 
 
+    /// Sets the number of frames to be skipped from rendering after each rendered frame.
+    /// Skipped frames are still emulated with the full timing, but neither drawn by the PPU
+    /// nor sent to the canvas, which reduces the workload on slower devices.
+    #[wasm_bindgen]
+    pub fn set_frame_skip(&mut self, frame_skip: u32) {
+        self.frame_skip     = frame_skip;
+        self.frames_to_skip = self.frames_to_skip.min(frame_skip);
+    }
+
+
+    /// Get the number of frames skipped from rendering after each rendered frame.
+    #[wasm_bindgen]
+    pub fn get_frame_skip(&self) -> u32 {
+        self.frame_skip
+    }
+
+
     /// Open the audio channel to the emulator.
     /// After doing so, audio samples may be received via [take_audio_samples].
     #[wasm_bindgen]
     pub fn open_audio(&mut self, sample_rate: u32) -> Result<(), JsValue> {
         self.samples_receiver = self.gb.get_peripherals_mut().apu.get_audio_output().open_channel(AudioOutputSpec {
-            sample_rate
+            sample_rate,
+            format: SampleFormat::F32,
         });
 
         Ok(())
     }
 
 
+    /// Changes the sample rate of the audio channel opened via [open_audio].
+    /// This allows to open the channel before the audio context reported its actual sample rate.
+    #[wasm_bindgen]
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) -> Result<(), JsValue> {
+        if sample_rate == 0 {
+            return Err(JsValue::from_str("Invalid sample rate"));
+        }
+
+        self.gb.get_peripherals_mut().apu.get_audio_output().set_sample_rate(sample_rate);
+
+        Ok(())
+    }
+
+
     /// Takes all pending audio samples from the audio channel.
     /// This channel has to be opened via [open_audio] first.
     /// All pending samples will be put together into a continuous array with alternating between
@@ -175,14 +283,10 @@ impl WasmPlayer {
     pub fn take_audio_samples(&mut self) -> Result<Vec<f32>, JsValue> {
         match &self.samples_receiver {
             Some(receiver) => {
-                Ok(
-                    receiver
-                        .try_iter()
-                        .fuse()
-                        .flat_map(|samples| samples.into_iter())
-                        .flat_map(|sample| [sample.left.get_value(), sample.right.get_value()])
-                        .collect::<Vec<_>>()
-                )
+                let mut samples = Vec::new();
+                receiver.take_interleaved(&mut samples);
+
+                Ok(samples)
             }
 
             None => {
@@ -231,12 +335,44 @@ impl WasmPlayer {
 
     /// Render the current frame to the canvas.
     pub fn render_frame(&mut self) -> Result<(), JsValue> {
-        let frame = self.gb.get_peripherals().ppu.get_lcd();
-        let image = ImageData::new_with_u8_clamped_array_and_sh(
-            wasm_bindgen::Clamped(frame.get_pixels_as_slice()),
-            frame.get_width(),
-            frame.get_height()
-        )?;
+        let peripherals = self.gb.get_peripherals();
+        let frame       = peripherals.ppu.get_lcd();
+
+        let image = match (&mut self.sgb_frame, &peripherals.sgb) {
+            (Some((generation, pixels)), Some(sgb)) => {
+                // draw the border only when it was changed
+                if let Some(border) = sgb.get_border() {
+                    if *generation != sgb.get_border_generation() {
+                        *generation = sgb.get_border_generation();
+
+                        for (index, color) in border.render(Color::black()).iter().enumerate() {
+                            pixels[index * 4 .. index * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+                        }
+                    }
+                }
+
+                // copy the game content into the center of the border
+                let row_size = (SCREEN_W * 4) as usize;
+                for (y, row) in frame.get_pixels_as_slice().chunks_exact(row_size).enumerate() {
+                    let offset = (((SGB_SCREEN_OFFSET_Y + y as u32) * SGB_BORDER_W + SGB_SCREEN_OFFSET_X) * 4) as usize;
+                    pixels[offset .. offset + row_size].copy_from_slice(row);
+                }
+
+                ImageData::new_with_u8_clamped_array_and_sh(
+                    wasm_bindgen::Clamped(&pixels[..]),
+                    SGB_BORDER_W,
+                    SGB_BORDER_H
+                )?
+            }
+
+            _ => {
+                ImageData::new_with_u8_clamped_array_and_sh(
+                    wasm_bindgen::Clamped(frame.get_pixels_as_slice()),
+                    frame.get_width(),
+                    frame.get_height()
+                )?
+            }
+        };
 
         self.rc.put_image_data(&image, 0.0, 0.0)
     }
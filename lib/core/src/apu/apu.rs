@@ -22,6 +22,7 @@ use crate::apu::channels::noise::NoiseGenerator;
 use crate::apu::channels::pulse::PulseGenerator;
 use crate::apu::channels::wave::WaveGenerator;
 use crate::apu::mixer::Mixer;
+use crate::apu::sample::{Sample, SampleResult};
 use crate::gameboy::{Clock, DeviceConfig};
 use crate::mmu::locations::*;
 use crate::mmu::memory_bus::MemoryBusConnection;
@@ -292,6 +293,101 @@ impl Apu {
     }
 
 
+    /// Get the current sample of a single channel after being converted by the channel's DAC.
+    /// The sample is scaled to the full range of i16, while a silent channel returns zero.
+    /// This does not advance the state of the channel, so it can be used to display
+    /// the waveform of each channel separately.
+    pub fn channel_sample(&self, channel: ChannelType) -> i16 {
+        let sample = match channel {
+            ChannelType::Ch1Pulse1 => self.ch1.get_sample(&self.state),
+            ChannelType::Ch2Pulse2 => self.ch2.get_sample(&self.state),
+            ChannelType::Ch3Wave   => self.ch3.get_sample(&self.state),
+            ChannelType::Ch4Noise  => self.ch4.get_sample(&self.state),
+        };
+
+        match sample {
+            SampleResult::Audio(sample) => (sample.get_value() * (i16::MAX as f32)) as i16,
+            SampleResult::Silence       => 0,
+        }
+    }
+
+
+    /// Get the current digital output of a single channel before being converted by the
+    /// channel's DAC, in the range of 0x00 to 0x0f. On GBC, this can be read via PCM12 and PCM34.
+    pub fn channel_digital_output(&self, channel: ChannelType) -> u8 {
+        match channel {
+            ChannelType::Ch1Pulse1 => self.ch1.get_digital_output(&self.state),
+            ChannelType::Ch2Pulse2 => self.ch2.get_digital_output(&self.state),
+            ChannelType::Ch3Wave   => self.ch3.get_digital_output(&self.state),
+            ChannelType::Ch4Noise  => self.ch4.get_digital_output(&self.state),
+        }
+    }
+
+
+    /// Reads the digital outputs of two channels, with the first channel
+    /// in the lower and the second channel in the upper nibble.
+    /// These registers are only available on GBC and read 0xff otherwise.
+    fn read_pcm(&self, low: ChannelType, high: ChannelType) -> u8 {
+        if self.state.device_config.is_gbc_enabled() {
+                (self.channel_digital_output(low)  & 0x0f)
+            |   ((self.channel_digital_output(high) & 0x0f) << 4)
+        }
+        else {
+            0xff
+        }
+    }
+
+
+    /// Checks whether a channel is currently enabled, as reported by NR52.
+    pub fn is_channel_enabled(&self, channel: ChannelType) -> bool {
+        match channel {
+            ChannelType::Ch1Pulse1 => self.ch1.is_channel_enabled(),
+            ChannelType::Ch2Pulse2 => self.ch2.is_channel_enabled(),
+            ChannelType::Ch3Wave   => self.ch3.is_channel_enabled(),
+            ChannelType::Ch4Noise  => self.ch4.is_channel_enabled(),
+        }
+    }
+
+
+    /// Get the mixer, which contains the panning and volume settings of all channels.
+    pub fn get_mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+
+    /// Sets the audio signal provided by the cartridge via the VIN pin, see [Mixer::set_vin_sample].
+    /// This defaults to silence, as most cartridges don't provide any sound.
+    pub fn set_vin_sample(&mut self, sample: SampleResult<Sample>) {
+        self.mixer.set_vin_sample(sample);
+    }
+
+
+    /// Mutes or unmutes a single channel in the output, see [Mixer::set_channel_muted].
+    pub fn set_channel_muted(&mut self, channel: ChannelType, muted: bool) {
+        self.mixer.set_channel_muted(channel, muted);
+    }
+
+
+    /// Selects a single channel to be played exclusively, see [Mixer::set_channel_solo].
+    pub fn set_channel_solo(&mut self, channel: Option<ChannelType>) {
+        self.mixer.set_channel_solo(channel);
+    }
+
+
+    /// Takes over all settings of another APU, which are controlled by the frontend
+    /// and not part of the emulated state.
+    pub(crate) fn take_settings_from(&mut self, other: &mut Apu) {
+        self.audio_output.take_channel_from(&mut other.audio_output);
+        self.mixer.take_settings_from(&other.mixer);
+    }
+
+
+    /// Get the sound generator of the noise channel.
+    pub fn get_noise_generator(&self) -> &NoiseGenerator {
+        self.ch4.get_generator()
+    }
+
+
     /// Get the audio output object which allows the frontend to control the sound generation
     /// and receive the generated sample data.
     pub fn get_audio_output(&mut self) -> &mut AudioOutput {
@@ -358,6 +454,16 @@ impl MemoryBusConnection for Apu {
                 self.ch3.on_read_register(address, &self.state)
             }
 
+            // Digital outputs of channels 1 and 2
+            MEMORY_LOCATION_PCM12 => {
+                self.read_pcm(ChannelType::Ch1Pulse1, ChannelType::Ch2Pulse2)
+            }
+
+            // Digital outputs of channels 3 and 4
+            MEMORY_LOCATION_PCM34 => {
+                self.read_pcm(ChannelType::Ch3Wave, ChannelType::Ch4Noise)
+            }
+
             _ => 0xff
         }
     }
@@ -16,10 +16,22 @@
  */
 
 use std::cmp::min;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::Receiver;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::RecvTimeoutError;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
 use crate::apu::hpf::StereoHighPassFilters;
-use crate::apu::sample::{SampleResult, StereoSample};
+use crate::apu::sample::{Sample, SampleResult, StereoSample};
+use crate::apu::sample_queue::{sample_queue, SAMPLE_QUEUE_CAPACITY};
 use crate::cpu::cpu::CPU_CLOCK_SPEED;
 use crate::gameboy::{Clock, DeviceConfig};
 use crate::utils::SerializableArray;
@@ -32,16 +44,47 @@ pub const SAMPLE_BUFFER_SIZE: usize = 1024;
 /// Type alias for an array storing sample data generated by the APU.
 pub type SampleBuffer = SerializableArray<StereoSample, SAMPLE_BUFFER_SIZE>;
 
-/// Sender part of the channel to transfer audio samples from the APU to the consumer.
-pub type SamplesSender = Sender<Box<SampleBuffer>>;
+pub use crate::apu::sample_queue::{SamplesReceiver, SamplesSender};
 
-/// Receiver part of the channel to transfer audio samples from the APU to the consumer.
-pub type SamplesReceiver = Receiver<Box<SampleBuffer>>;
+
+/// The time a callback thread waits for new sample data before checking
+/// whether it was requested to stop.
+#[cfg(not(target_arch = "wasm32"))]
+const CALLBACK_THREAD_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 
 /// Stores the configuration to initialize the audio generation.
 pub struct AudioOutputSpec {
     pub sample_rate: u32,
+    pub format: SampleFormat,
+}
+
+
+/// The format of the samples transmitted to the receiver of generated audio data.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SampleFormat {
+    /// Samples as produced by the mixer. After passing the high pass filter,
+    /// the values may slightly exceed the range of -1.0 to 1.0.
+    #[default]
+    Native,
+
+    /// Samples clamped into the range of -1.0 to 1.0, which can be passed
+    /// directly into audio APIs expecting floating point samples.
+    F32,
+}
+
+
+/// A handle to a background thread created via [AudioOutput::open_callback_channel],
+/// which forwards the generated sample data into a callback function.
+/// The thread will be stopped, when this handle is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct CallbackHandle {
+    /// Flag to request the background thread to stop.
+    stop_requested: Arc<AtomicBool>,
+
+    /// The join handle of the background thread.
+    thread: Option<JoinHandle<()>>,
 }
 
 
@@ -51,9 +94,19 @@ pub struct AudioOutput {
     /// The sample rate currently configured to generate audio data.
     sample_rate: u32,
 
+    /// The format of the samples transmitted to the receiver.
+    format: SampleFormat,
+
+    /// The number of CPU cycles per second, which is used to convert
+    /// the cycles passed into the time of the generated samples.
+    clock_speed: Clock,
+
     /// Records the time passed to check when to record a new sample.
     time_passed: Clock,
 
+    /// Timestamp when the current sample was started to be recorded.
+    last_sample_time: Clock,
+
     /// Timestamp when the next sample has to be recorded.
     next_sample_time: Clock,
 
@@ -75,7 +128,12 @@ pub struct AudioOutput {
 
     /// Sender part of the channel to transfer sample data to the emulator frontend.
     #[cfg_attr(feature = "serde", serde(skip))]
-    sender: Option<SamplesSender>
+    sender: Option<SamplesSender>,
+
+    /// Receives buffers the receiver of the sample data is done with,
+    /// so they can be re-used instead of allocating a new buffer each time.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recycled_buffers: Option<Receiver<Box<SampleBuffer>>>,
 }
 
 
@@ -86,7 +144,10 @@ impl AudioOutput {
     pub fn new(device_config: DeviceConfig) -> Self {
         Self {
             sample_rate:        Self::DEFAULT_SAMPLE_RATE,
+            format:             SampleFormat::Native,
+            clock_speed:        CPU_CLOCK_SPEED,
             time_passed:        0,
+            last_sample_time:   0,
             next_sample_time:   0,
             sample_multiplier:  0.0,
             current_sample:     StereoSample::default(),
@@ -94,6 +155,7 @@ impl AudioOutput {
             buffer_insert_pos:  0,
             high_pass_filter:   StereoHighPassFilters::new(device_config),
             sender:             None,
+            recycled_buffers:   None,
         }
     }
 
@@ -104,11 +166,62 @@ impl AudioOutput {
     }
 
 
+    /// Changes the sample rate of the audio data generated.
+    /// This may be done while a channel is open, for example when the frontend
+    /// learns about the actual rate of the audio device after it was started.
+    /// The sample currently being recorded will be completed with the new rate,
+    /// so there is no step in the signal between the old and new sample rate.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        assert_ne!(sample_rate, 0);
+
+        if sample_rate == self.sample_rate {
+            return;
+        }
+
+        self.sample_rate = sample_rate;
+
+        // until the first sample was completed, there's no partial sample to be adjusted
+        if self.sample_multiplier == 0.0 {
+            return;
+        }
+
+        // Keep the start time of the current sample, but compute its end time based on the
+        // new sample rate. The data already accumulated was weighted by the previous sample
+        // duration and needs to be re-weighted by the new one.
+        let sample_duration    = (self.clock_speed / (sample_rate as Clock)).max(1);
+        let next_sample_time   = (self.last_sample_time + sample_duration).max(self.time_passed);
+        let multiplier         = 1.0 / ((next_sample_time - self.last_sample_time) as f32);
+
+        self.current_sample    = self.current_sample * (multiplier / self.sample_multiplier);
+        self.sample_multiplier = multiplier;
+        self.next_sample_time  = next_sample_time;
+    }
+
+
+    /// Get the format of the samples transmitted to the receiver.
+    pub fn get_sample_format(&self) -> SampleFormat {
+        self.format
+    }
+
+
+    /// Get the number of CPU cycles per second the audio output is based on.
+    pub fn get_clock_speed(&self) -> Clock {
+        self.clock_speed
+    }
+
+
+    /// Set the number of CPU cycles per second the audio output is based on.
+    /// This needs to be updated each time the speed multiplier of the emulator changes.
+    pub(crate) fn set_clock_speed(&mut self, clock_speed: Clock) {
+        self.clock_speed = clock_speed;
+    }
+
+
     /// Push a new sample into the buffer.
     /// Takes a sample as read from the APU channels and the number of cycles
     /// this sample was live.
     /// To produce sample data with the requested sample rate, the audio output will
-    /// compute the average of all values within `CPU_CLOCK / sample rate` cycles.
+    /// compute the average of all values within `clock speed / sample rate` cycles.
     pub(crate) fn push(&mut self, sample: SampleResult<StereoSample>, cycles: Clock) {
         let mut remaining_cycles = cycles;
 
@@ -129,17 +242,21 @@ impl AudioOutput {
                 self.finish_sample();
 
                 // wrap after a second has passed to prevent an overflow
-                if self.next_sample_time > CPU_CLOCK_SPEED {
-                    self.next_sample_time -= CPU_CLOCK_SPEED;
-                    self.time_passed -= CPU_CLOCK_SPEED;
+                let clock_speed = self.clock_speed;
+                if self.next_sample_time > clock_speed {
+                    self.next_sample_time -= clock_speed;
+                    self.time_passed -= clock_speed;
                 }
 
+                // the next sample starts where the current one was completed
+                self.last_sample_time = self.next_sample_time;
+
                 // compute the time when the next sample has to be generated
                 let sample_rate           = self.sample_rate as Clock;
                 let last_sample_time      = self.next_sample_time;
-                let current_sample_number = (self.next_sample_time + 1) * sample_rate / CPU_CLOCK_SPEED;
+                let current_sample_number = (self.next_sample_time + 1) * sample_rate / clock_speed;
                 let next_sample_number    = current_sample_number + 1;
-                let next_sample_time      = next_sample_number * CPU_CLOCK_SPEED / sample_rate;
+                let next_sample_time      = next_sample_number * clock_speed / sample_rate;
                 self.next_sample_time     = next_sample_time;
 
                 // compute a multiplier, the sample has to be multiplied with in order to get
@@ -158,7 +275,13 @@ impl AudioOutput {
     /// and, if reached the end of the buffer, sends it to a receiver object.
     fn finish_sample(&mut self) {
         // store the sample on the current position inside the sample buffer
-        self.buffer[self.buffer_insert_pos] = self.current_sample;
+        self.buffer[self.buffer_insert_pos] = match self.format {
+            SampleFormat::Native => self.current_sample,
+            SampleFormat::F32    => StereoSample {
+                left:  Sample::new(self.current_sample.left.get_value().clamp(-1.0, 1.0)),
+                right: Sample::new(self.current_sample.right.get_value().clamp(-1.0, 1.0)),
+            },
+        };
 
         // reset the sample accumulator
         self.current_sample = StereoSample::default();
@@ -170,11 +293,18 @@ impl AudioOutput {
         if self.buffer_insert_pos >= SAMPLE_BUFFER_SIZE {
             // send it to the receiver, if any channel was opened
             if let Some(sender) = &self.sender {
-                let result = sender.send(self.buffer.clone());
+                // hand over the current buffer and continue with a recycled one, if available
+                let next_buffer = match self.recycled_buffers.as_ref().and_then(|r| r.try_recv().ok()) {
+                    Some(buffer) => buffer,
+                    None         => self.buffer.clone(),
+                };
+
+                let result = sender.send(std::mem::replace(&mut self.buffer, next_buffer));
 
                 // disconnect on error
                 if result.is_err() {
-                    self.sender = None;
+                    self.sender           = None;
+                    self.recycled_buffers = None;
                 }
             }
 
@@ -184,17 +314,44 @@ impl AudioOutput {
     }
 
 
+    /// Takes over the channel opened on another audio output, if any,
+    /// so the receiver of the other channel will get the samples of this output.
+    pub(crate) fn take_channel_from(&mut self, other: &mut AudioOutput) {
+        if let Some(sender) = other.sender.take() {
+            self.set_sample_rate(other.sample_rate);
+            self.format           = other.format;
+            self.sender           = Some(sender);
+            self.recycled_buffers = other.recycled_buffers.take();
+        }
+    }
+
+
+    /// Get the number of sample buffers dropped, because the receiver of the
+    /// current channel did not take them in time.
+    pub fn get_dropped_buffers(&self) -> u64 {
+        match &self.sender {
+            Some(sender) => sender.get_dropped_buffers(),
+            None         => 0,
+        }
+    }
+
+
     /// Open a channel in order to receive audio samples from the emulator backend.
     /// This function requires to specify a sample rate and returns a receiver object
     /// which will receive all samples generated by the APU.
+    /// The sample rate may be changed later via [AudioOutput::set_sample_rate].
+    /// The channel holds up to [SAMPLE_QUEUE_CAPACITY] buffers; if the receiver does not
+    /// take them in time, the oldest buffers will be dropped.
     pub fn open_channel(&mut self, spec: AudioOutputSpec) -> Option<SamplesReceiver> {
-        let (s, r) = channel::<Box<SampleBuffer>>();
+        let (s, r) = sample_queue(SAMPLE_QUEUE_CAPACITY);
 
         assert_ne!(spec.sample_rate, 0);
 
         if spec.sample_rate > 0 {
-            self.sample_rate = spec.sample_rate;
-            self.sender      = Some(s);
+            self.set_sample_rate(spec.sample_rate);
+            self.format           = spec.format;
+            self.sender           = Some(s);
+            self.recycled_buffers = None;
 
             Some(r)
         }
@@ -202,4 +359,61 @@ impl AudioOutput {
             None
         }
     }
+
+
+    /// Open a channel in order to receive audio samples from the emulator backend via callback.
+    /// This spawns a background thread, which receives the sample data generated by the APU
+    /// and invokes the callback function for each completed buffer.
+    /// The thread keeps running until the returned [CallbackHandle] is dropped.
+    /// Buffers are passed back to the audio output after the callback returned,
+    /// so no allocations are needed while generating sample data.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_callback_channel(
+        &mut self,
+        spec: AudioOutputSpec,
+        callback: impl Fn(&SampleBuffer) + Send + 'static
+    ) -> Option<CallbackHandle> {
+        let receiver       = self.open_channel(spec)?;
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let stop_flag      = stop_requested.clone();
+
+        // return channel to pass the buffers back into the audio output
+        let (recycle_sender, recycle_receiver) = std::sync::mpsc::channel();
+        self.recycled_buffers = Some(recycle_receiver);
+
+        let thread = std::thread::spawn(move || {
+            while !stop_flag.load(Ordering::Relaxed) {
+                match receiver.recv_timeout(CALLBACK_THREAD_POLL_INTERVAL) {
+                    Ok(samples) => {
+                        callback(&samples);
+
+                        // the audio output may already be gone, in which case the buffer is dropped
+                        _ = recycle_sender.send(samples);
+                    }
+
+                    Err(RecvTimeoutError::Timeout) => { }
+
+                    // the sender was closed, so no more data will arrive
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        Some(CallbackHandle {
+            stop_requested,
+            thread: Some(thread),
+        })
+    }
+}
+
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for CallbackHandle {
+    fn drop(&mut self) {
+        self.stop_requested.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
 }
@@ -0,0 +1,184 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::apu::audio_output::SamplesReceiver;
+use crate::apu::sample::{Sample, StereoSample};
+
+
+/// The number of channels stored in the recorded file.
+const WAV_CHANNELS: u16 = 2;
+
+/// The number of bits per sample stored in the recorded file.
+const WAV_BITS_PER_SAMPLE: u16 = 16;
+
+/// The number of bytes of a single stereo sample.
+const WAV_BLOCK_ALIGN: u16 = WAV_CHANNELS * WAV_BITS_PER_SAMPLE / 8;
+
+/// The size of the WAV header preceding the sample data.
+pub const WAV_HEADER_SIZE: u64 = 44;
+
+
+/// Records the audio samples generated by the emulator into a 16 bit stereo WAV file.
+/// The header of the file will be completed when calling [AudioRecorder::finish]
+/// or when the recorder gets dropped, so the file stays valid if the recording
+/// is not stopped explicitly.
+pub struct AudioRecorder<W: Write + Seek = BufWriter<File>> {
+    /// The target to write the WAV data into.
+    /// This will be `None` after the recording was finished.
+    writer: Option<W>,
+
+    /// The sample rate of the recorded samples.
+    sample_rate: u32,
+
+    /// The number of stereo samples recorded so far.
+    sample_count: u64,
+}
+
+
+impl AudioRecorder {
+    /// Creates a new file to record audio samples with the given sample rate.
+    pub fn create(path: &Path, sample_rate: u32) -> io::Result<Self> {
+        let file = File::create(path)?;
+
+        Self::new(BufWriter::new(file), sample_rate)
+    }
+}
+
+
+impl<W: Write + Seek> AudioRecorder<W> {
+    /// Creates a new recorder writing into the given target. This writes a preliminary
+    /// header, which will be updated with the actual data size when finishing the recording.
+    pub fn new(mut writer: W, sample_rate: u32) -> io::Result<Self> {
+        Self::write_header(&mut writer, sample_rate, 0)?;
+
+        Ok(Self {
+            writer: Some(writer),
+            sample_rate,
+            sample_count: 0,
+        })
+    }
+
+
+    /// Get the sample rate of the recorded samples.
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+
+    /// Get the number of stereo samples recorded so far.
+    pub fn get_sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+
+    /// Writes a list of samples into the recording.
+    pub fn write_samples(&mut self, samples: &[StereoSample]) -> io::Result<()> {
+        if let Some(writer) = &mut self.writer {
+            for sample in samples {
+                writer.write_all(&Self::to_pcm(sample.left).to_le_bytes())?;
+                writer.write_all(&Self::to_pcm(sample.right).to_le_bytes())?;
+            }
+
+            self.sample_count += samples.len() as u64;
+        }
+
+        Ok(())
+    }
+
+
+    /// Writes all sample buffers currently available on the receiver into the recording.
+    /// This does not wait for new samples to arrive.
+    pub fn receive_samples(&mut self, receiver: &SamplesReceiver) -> io::Result<()> {
+        while let Ok(samples) = receiver.try_recv() {
+            self.write_samples(&samples[..])?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Stops the recording and completes the header of the recorded file.
+    /// Returns the target the data was written into.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.finalize()? {
+            Some(writer) => Ok(writer),
+            None         => Err(io::Error::other("Recording already finished")),
+        }
+    }
+
+
+    /// Completes the header with the final data size and flushes the data written.
+    fn finalize(&mut self) -> io::Result<Option<W>> {
+        if let Some(mut writer) = self.writer.take() {
+            let data_size = self.sample_count * (WAV_BLOCK_ALIGN as u64);
+
+            writer.seek(SeekFrom::Start(0))?;
+            Self::write_header(&mut writer, self.sample_rate, data_size)?;
+            writer.seek(SeekFrom::End(0))?;
+            writer.flush()?;
+
+            return Ok(Some(writer));
+        }
+
+        Ok(None)
+    }
+
+
+    /// Writes the RIFF header for a PCM WAV file with the given size of sample data.
+    fn write_header(writer: &mut W, sample_rate: u32, data_size: u64) -> io::Result<()> {
+        // sizes are limited to 32 bit in the WAV format
+        let data_size   = data_size.min((u32::MAX as u64) - WAV_HEADER_SIZE) as u32;
+        let riff_size   = data_size + (WAV_HEADER_SIZE as u32) - 8;
+        let byte_rate   = sample_rate * (WAV_BLOCK_ALIGN as u32);
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&riff_size.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&WAV_CHANNELS.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        writer.write_all(&WAV_BLOCK_ALIGN.to_le_bytes())?;
+        writer.write_all(&WAV_BITS_PER_SAMPLE.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+
+        Ok(())
+    }
+
+
+    /// Converts a sample into a signed 16 bit PCM value.
+    fn to_pcm(sample: Sample) -> i16 {
+        (sample.get_value().clamp(-1.0, 1.0) * (i16::MAX as f32)) as i16
+    }
+}
+
+
+impl<W: Write + Seek> Drop for AudioRecorder<W> {
+    fn drop(&mut self) {
+        _ = self.finalize();
+    }
+}
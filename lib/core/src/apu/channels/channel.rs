@@ -43,6 +43,7 @@ pub mod features {
 
 
 /// The type of a channel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChannelType {
     Ch1Pulse1,
@@ -52,6 +53,17 @@ pub enum ChannelType {
 }
 
 
+impl ChannelType {
+    /// A list of all channels in the order of their channel numbers.
+    pub const ALL: [ChannelType; 4] = [
+        ChannelType::Ch1Pulse1,
+        ChannelType::Ch2Pulse2,
+        ChannelType::Ch3Wave,
+        ChannelType::Ch4Noise,
+    ];
+}
+
+
 flags! {
     /// An action to be performed as the result of a `on_trigger` or `on_register_changed`
     /// invocation of a `ChannelComponent`.
@@ -240,6 +252,12 @@ impl<
     }
 
 
+    /// Get the sound generator of this channel.
+    pub fn get_generator(&self) -> &G {
+        &self.generator
+    }
+
+
     /// Get the sound generator of this channel.
     pub fn get_generator_mut(&mut self) -> &mut G {
         &mut self.generator
@@ -447,7 +465,7 @@ impl<
             match result {
                 // the channel was disabled because of an overflow
                 FrequencySweepResult::DisableChannel => {
-                    self.channel_enabled = false;
+                    self.apply_actions(TriggerAction::DisableChannel);
                 }
 
                 // apply the changed frequency
@@ -475,10 +493,11 @@ impl<
     }
 
 
-    /// Get the audio sample generated by the channels sound generator and
-    /// converted by the channels DAC.
-    pub fn get_sample(&self, apu_state: &ApuState) -> SampleResult<Sample> {
-        let value = if self.channel_enabled {
+    /// Get the digital output of this channel before being converted by the DAC.
+    /// This is the sample of the sound generator multiplied with the current volume
+    /// in the range of 0x00 to 0x0f, or zero while the channel is disabled.
+    pub fn get_digital_output(&self, apu_state: &ApuState) -> u8 {
+        if self.channel_enabled {
             // take the current sample from the sound generator
             let generated_sample = self.generator.get_sample(apu_state);
 
@@ -496,7 +515,14 @@ impl<
         else {
             // a disabled channel just spawns zero
             0
-        };
+        }
+    }
+
+
+    /// Get the audio sample generated by the channels sound generator and
+    /// converted by the channels DAC.
+    pub fn get_sample(&self, apu_state: &ApuState) -> SampleResult<Sample> {
+        let value = self.get_digital_output(apu_state);
 
         // convert into 'analogue' signal via DAC
         let sample = self.dac.convert(value);
@@ -0,0 +1,80 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::utils::get_bit;
+
+/// Patterns to be used for each selectable duty cycle.
+/// Each bit represents one step of the pattern, starting with step 0 on the most significant bit.
+pub const DUTY_CYCLE_PATTERN: [u8; 4] = [
+    0b_0000_0001, // 12.5%
+    0b_1000_0001, // 25%
+    0b_1000_0111, // 50%
+    0b_0111_1110, // 75%
+];
+
+
+/// A duty cycle describes the ratio between peaks and valleys of a sound wave.
+/// This creates a wave pattern of alternating valleys and peaks with their
+/// duration depending on the ratio between valleys and peaks.
+/// The duty cycle runs through the 8 steps of the selected pattern.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DutyCycle {
+    /// The index of the selected pattern, as it is referenced by the NRx1 register.
+    pattern_index: u8,
+
+    /// The current step within the pattern.
+    step: u8,
+}
+
+
+impl DutyCycle {
+    /// Selects the pattern of the duty cycle by it's index.
+    /// * 0 -> 12.5%
+    /// * 1 -> 25%
+    /// * 2 -> 50%
+    /// * 3 -> 75%
+    pub fn set_pattern_index(&mut self, index: u8) {
+        self.pattern_index = index & 0b11;
+    }
+
+
+    /// Get the index of the selected pattern, as it is referenced by the NRx1 register.
+    pub fn get_pattern_index(&self) -> u8 {
+        self.pattern_index
+    }
+
+
+    /// Resets the current step to the beginning of the pattern.
+    pub fn reset_step(&mut self) {
+        self.step = 0;
+    }
+
+
+    /// Advances to the next step of the pattern.
+    /// The pattern contains 8 steps, so it will repeat after step 7.
+    pub fn advance(&mut self) {
+        self.step = (self.step + 1) & 0x07;
+    }
+
+
+    /// Get the value of the wave on the current step, which is 1 for the high
+    /// portion of the pattern and 0 otherwise.
+    pub fn get_sample(&self) -> u8 {
+        let pattern = DUTY_CYCLE_PATTERN[self.pattern_index as usize];
+        get_bit(pattern, 7 - self.step) as u8
+    }
+}
@@ -21,6 +21,7 @@ use crate::utils::get_bit;
 
 
 /// Whether to increment or decrement the volume
+#[derive(Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Decrement,
@@ -35,11 +36,13 @@ pub enum Direction {
 /// on the setting in NRx2 and the timer restarted.
 /// Additionally, setting the volume and the direction bit to 0 will disable the channel's DAC
 /// as well, which is also covered by this object.
+/// Writing NRx2 while the channel is running does not reload the volume, but modifies the
+/// current volume in a rather unintuitive way, which is known as "zombie mode".
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Envelope {
-    /// Flag to store whether the period timer is enabled or not.
+    /// Flag to store whether the envelope is still doing automatic updates.
     /// This is used to stop the timer once the volume reached it's minimum or maximum value
-    /// and further calls wont have any effect.
+    /// and further calls wont have any effect. The flag is restored when the channel gets triggered.
     enabled: bool,
 
     /// The initial volume as read from NRx2.
@@ -72,7 +75,7 @@ impl Direction {
 
 
     /// Get the value, which should be written into the NRx2 register.
-    pub fn to_register_value(&self) -> u8 {
+    pub fn to_register_value(self) -> u8 {
         match self {
             Direction::Decrement => 0b_0000_0000,
             Direction::Increment => 0b_0000_1000,
@@ -94,10 +97,36 @@ impl Envelope {
     }
 
 
+    /// Applies the "zombie mode" modification of the current volume, when NRx2 was written
+    /// with a new value while the channel is running. Based on the previous configuration:
+    /// * when the previous period was zero and the envelope is still running, the volume is incremented by 1
+    /// * otherwise, when the previous direction was 'decrement', the volume is incremented by 2
+    /// * when the direction was changed, the volume will be set to 16 - volume
+    /// * the result will wrap around to stay within 4 bits
+    fn apply_zombie_mode(&mut self, new_value: u8) {
+        let new_direction = Direction::from_register_value(new_value);
+        let mut volume    = self.volume;
+
+        if self.period_length == 0 && self.enabled {
+            volume = volume.wrapping_add(1);
+        }
+        else if self.direction == Direction::Decrement {
+            volume = volume.wrapping_add(2);
+        }
+
+        if self.direction != new_direction {
+            volume = 16u8.wrapping_sub(volume);
+        }
+
+        self.volume = volume & 0x0f;
+    }
+
+
     /// Receives the periodic call from the frame sequencer.
     /// Decrease the timer and modify the volume once the timer becomes zero.
+    /// A period of zero will stop any automatic updates of the volume.
     pub fn tick(&mut self) {
-        if self.enabled {
+        if self.enabled && self.period_length != 0 {
             self.period_timer = self.period_timer.saturating_sub(1);
 
             // when the timer elapses
@@ -151,11 +180,12 @@ impl ChannelComponent for Envelope {
                 let volume        = (value >> 4) & 0x0f;
                 let period        = (value >> 0) & 0x07;
                 let dac_enabled   = (value & 0xf8) != 0;
-                let enabled       = period != 0;
 
-                self.enabled        = enabled;
+                // the current volume is not reloaded,
+                // but modified based on the previous configuration
+                self.apply_zombie_mode(value);
+
                 self.initial_volume = volume;
-                self.volume         = volume;
                 self.period_length  = period;
                 self.direction      = Direction::from_register_value(value);
 
@@ -178,7 +208,9 @@ impl ChannelComponent for Envelope {
         self.reload_envelope_timer();
 
         // initialize the volume from it's configured value
-        self.volume = self.initial_volume;
+        // and restart automatic updates of the envelope
+        self.volume  = self.initial_volume;
+        self.enabled = true;
 
         default_on_trigger_event(apu_state)
     }
@@ -37,7 +37,11 @@ pub enum FrequencySweepResult {
 }
 
 
-// todo: doc
+/// The frequency sweep unit of channel 1, which periodically increases or decreases
+/// the channel's frequency. The calculation is based on a shadow copy of the frequency
+/// and disables the channel if the new frequency would overflow. Besides the periodic
+/// calculation, an overflow check also happens when the channel gets triggered and
+/// leaving negate mode after a calculation in negate mode disables the channel as well.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencySweep {
     /// Flag to store whether the frequency sweep is enabled or not.
@@ -115,8 +115,13 @@ impl<const LENGTH_BITS: u8> ChannelComponent for LengthTimer<LENGTH_BITS> {
                     &&  self.length_timer != 0
                     &&  !apu_state.fs.is_length_timer_active()
                 {
-                    // this may also disable the channel
-                    return self.tick();
+                    // this may also disable the channel, unless the channel gets triggered
+                    // by the same write, which reloads the length timer when it became zero
+                    let action = self.tick();
+
+                    if !get_bit(value, 7) {
+                        return action;
+                    }
                 }
             }
 
@@ -25,5 +25,5 @@ mod envelope;
 mod freq_sweep;
 mod frequency;
 mod length_timer;
-mod wave_duty;
+mod duty_cycle;
 mod wave_ram;
@@ -68,6 +68,12 @@ impl NoiseGenerator {
     }
 
 
+    /// Get the current state of the Linear Feedback Shift Register.
+    pub fn lfsr_state(&self) -> u16 {
+        self.lfsr
+    }
+
+
     /// Compute the time until next LFSR iteration.
     pub fn reset_timer(&mut self) {
         // frequency is 4194304 / (divider << shift)
@@ -173,12 +179,18 @@ impl SoundGenerator for NoiseGenerator {
                 // timer will be restarted
                 self.reset_timer();
 
-                // determine the new bit to insert by XOR of bit 0 and 1
+                // determine the new bit to insert by XNOR of bit 0 and 1
                 let insert_bit = (self.lfsr ^ (self.lfsr >> 1) ^ 1) & 0x01;
 
-                // the XOR bit will be inserted at the position left of the lfsr width;
-                // the following shift will move the new bit in it's desired position
-                self.lfsr |= insert_bit << self.lfsr_width;
+                // the XNOR bit will replace bit 15 and, in short mode, also bit 7;
+                // the following shift will move the new bit into bit 14 and 6.
+                // This equals the common description of the LFSR starting with all bits set,
+                // using XOR and an inverted output, just with all bits inverted.
+                self.lfsr = (self.lfsr & !(1 << 15)) | (insert_bit << 15);
+
+                if self.lfsr_width == 7 {
+                    self.lfsr = (self.lfsr & !(1 << 7)) | (insert_bit << 7);
+                }
 
                 // the whole register gets shifted to the right
                 self.lfsr >>= 1;
@@ -18,10 +18,10 @@
 use std::cmp::min;
 
 use crate::apu::apu::ApuState;
-use crate::apu::channels::channel::{ChannelComponent, default_on_read_register, default_on_write_register, TriggerAction};
+use crate::apu::channels::channel::{ChannelComponent, default_on_read_register, default_on_trigger_event, default_on_write_register, TriggerAction};
+use crate::apu::channels::duty_cycle::DutyCycle;
 use crate::apu::channels::frequency::Frequency;
 use crate::apu::channels::generator::SoundGenerator;
-use crate::apu::channels::wave_duty::WaveDuty;
 use crate::gameboy::Clock;
 
 
@@ -35,12 +35,13 @@ const NRX4_WRITE_ONLY_TRIGGER_BIT : u8  = 0b_1000_0000;
 const FREQUENCY_CYCLES : Clock = 4;
 
 
-/// A sound generator to generate a pulse wave. The wave is based is based on a wave duty value
+/// A sound generator to generate a pulse wave. The wave is based is based on a duty cycle
 /// and a volume computed by an envelope function.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PulseGenerator {
     /// The base of the sound wave to be played, represented by a square wave.
-    wave_duty: WaveDuty,
+    /// The duty cycle also tracks the current step within it's pattern.
+    duty_cycle: DutyCycle,
 
     /// The frequency is controlling how fast the wave will be played.
     /// The value will be read from NRx3 and NRx4 register of the channel.
@@ -50,19 +51,15 @@ pub struct PulseGenerator {
     /// configured by the application. It will be decreased with each CPU T-Cycle
     /// and restarts when reaching zero.
     wave_timer: Clock,
-
-    /// The current position where to read the value from the wave duty.
-    wave_duty_step: u8,
 }
 
 
 impl PulseGenerator {
     pub fn new() -> Self {
         Self {
-            wave_duty:          WaveDuty::default(),
+            duty_cycle:         DutyCycle::default(),
             frequency:          Frequency::default(),
             wave_timer:         0,
-            wave_duty_step:     0,
         }
     }
 }
@@ -71,7 +68,7 @@ impl PulseGenerator {
 impl ChannelComponent for PulseGenerator {
     fn on_read_register(&self, number: u16, apu_state: &ApuState) -> u8 {
         match number {
-            1 => self.wave_duty.get_index() << 6,
+            1 => self.duty_cycle.get_pattern_index() << 6,
             3 => NRX3_WRITE_ONLY_FREQUENCY,
             4 => NRX4_WRITE_ONLY_FREQUENCY | NRX4_NON_READABLE_BITS | NRX4_WRITE_ONLY_TRIGGER_BIT,
             _ => default_on_read_register(number, apu_state)
@@ -82,8 +79,8 @@ impl ChannelComponent for PulseGenerator {
     fn on_write_register(&mut self, number: u16, value: u8, apu_state: &ApuState) -> TriggerAction {
         match number {
             1 => {
-                let wave_duty_index = (value >> 6) & 0x03;
-                self.wave_duty = WaveDuty::by_index(wave_duty_index);
+                let pattern_index = (value >> 6) & 0x03;
+                self.duty_cycle.set_pattern_index(pattern_index);
             }
 
             3 | 4 => {
@@ -97,6 +94,16 @@ impl ChannelComponent for PulseGenerator {
     }
 
 
+    fn on_trigger_event(&mut self, apu_state: &ApuState) -> TriggerAction {
+        // triggering the channel restarts the pattern of the duty cycle
+        // and reloads the wave timer with the current frequency
+        self.duty_cycle.reset_step();
+        self.wave_timer = self.frequency.to_countdown(FREQUENCY_CYCLES);
+
+        default_on_trigger_event(apu_state)
+    }
+
+
     fn on_reset(&mut self, _apu_state: &ApuState) {
         *self = Self::new();
     }
@@ -128,10 +135,10 @@ impl SoundGenerator for PulseGenerator {
             self.wave_timer  = self.wave_timer.saturating_sub(run_cycles);
 
             // when the wave timer expires, it will be restarted and the
-            // position inside the wave duty proceeds
+            // duty cycle proceeds to it's next step
             if self.wave_timer == 0 {
-                self.wave_timer     = self.frequency.to_countdown(FREQUENCY_CYCLES);
-                self.wave_duty_step = self.wave_duty_step.wrapping_add(1);
+                self.wave_timer = self.frequency.to_countdown(FREQUENCY_CYCLES);
+                self.duty_cycle.advance();
             }
 
             remaining_cycles = remaining_cycles.saturating_sub(run_cycles);
@@ -140,7 +147,6 @@ impl SoundGenerator for PulseGenerator {
 
 
     fn get_sample(&self, _apu_state: &ApuState) -> u8 {
-        let wave = self.wave_duty.get_wave_at(self.wave_duty_step);
-        wave
+        self.duty_cycle.get_sample()
     }
 }
@@ -16,7 +16,7 @@
  */
 
 use crate::apu::apu::ApuState;
-use crate::apu::channels::channel::Channel;
+use crate::apu::channels::channel::{Channel, ChannelType};
 use crate::apu::channels::generator::SoundGenerator;
 use crate::apu::sample::{Sample, SampleResult, StereoSample};
 use crate::utils::{as_bit_flag, change_bit, get_bit};
@@ -30,8 +30,34 @@ pub struct Mixer {
     vin_left:  bool,
     vin_right: bool,
 
+    /// The audio signal provided by the cartridge via the VIN pin.
+    vin_in: SampleResult<Sample>,
+
     volume_left:  u8,
     volume_right: u8,
+
+    /// Channels muted by the frontend, indexed by the channel ordinal.
+    /// This is not part of the emulated state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    muted: [bool; 4],
+
+    /// A single channel selected by the frontend to be played exclusively.
+    /// This is not part of the emulated state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    solo: Option<ChannelType>,
+}
+
+
+/// The decoded panning settings of NR51, which tell
+/// whether each channel is routed to the left and right output.
+/// The arrays are indexed by the channel ordinal, starting with 0 for channel 1.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PanningState {
+    /// Whether each channel is routed to the left output.
+    pub left: [bool; 4],
+
+    /// Whether each channel is routed to the right output.
+    pub right: [bool; 4],
 }
 
 
@@ -51,8 +77,11 @@ impl Mixer {
             channels_in:  [InputChannel::default(); 4],
             vin_left:     false,
             vin_right:    false,
+            vin_in:       SampleResult::Silence,
             volume_left:  0,
             volume_right: 0,
+            muted:        [false; 4],
+            solo:         None,
         }
     }
 
@@ -98,6 +127,90 @@ impl Mixer {
     }
 
 
+    /// Get the decoded panning settings of all channels.
+    pub fn panning(&self) -> PanningState {
+        let mut panning = PanningState::default();
+
+        for (i, channel) in self.channels_in.iter().enumerate() {
+            panning.left[i]  = channel.pan_left;
+            panning.right[i] = channel.pan_right;
+        }
+
+        panning
+    }
+
+
+    /// Get the master volumes of the left and right output.
+    /// The volumes are in a range from 1 to 8, where 1 is the lowest volume,
+    /// which is still audible.
+    pub fn master_volumes(&self) -> (u8, u8) {
+        (self.volume_left, self.volume_right)
+    }
+
+
+    /// Checks whether the VIN input of the cartridge is routed to the left and right output.
+    pub fn vin_enabled(&self) -> (bool, bool) {
+        (self.vin_left, self.vin_right)
+    }
+
+
+    /// Sets the audio signal provided by the cartridge via the VIN pin.
+    /// Depending on NR50 this will be mixed into the left and right output,
+    /// scaled by the master volume of each side.
+    pub fn set_vin_sample(&mut self, sample: SampleResult<Sample>) {
+        self.vin_in = sample;
+    }
+
+
+    /// Get the audio signal currently provided by the cartridge via the VIN pin.
+    pub fn get_vin_sample(&self) -> SampleResult<Sample> {
+        self.vin_in
+    }
+
+
+    /// Mutes or unmutes a single channel. This only affects the mixing of the output,
+    /// while the channel itself keeps running, so its state stays visible to the program.
+    pub fn set_channel_muted(&mut self, channel: ChannelType, muted: bool) {
+        self.muted[PanningState::index_of(channel)] = muted;
+    }
+
+
+    /// Checks whether a channel was muted via [Mixer::set_channel_muted].
+    pub fn is_channel_muted(&self, channel: ChannelType) -> bool {
+        self.muted[PanningState::index_of(channel)]
+    }
+
+
+    /// Selects a single channel to be played exclusively, or `None` to play all channels.
+    /// Like muting, this only affects the mixing of the output.
+    pub fn set_channel_solo(&mut self, channel: Option<ChannelType>) {
+        self.solo = channel;
+    }
+
+
+    /// Get the channel selected to be played exclusively, if any.
+    pub fn get_channel_solo(&self) -> Option<ChannelType> {
+        self.solo
+    }
+
+
+    /// Takes over the mute and solo settings of another mixer.
+    pub(crate) fn take_settings_from(&mut self, other: &Mixer) {
+        self.muted = other.muted;
+        self.solo  = other.solo;
+    }
+
+
+    /// Checks whether the channel with the given ordinal will be mixed into the output,
+    /// according to the mute and solo settings.
+    fn is_channel_audible(&self, index: usize) -> bool {
+        match self.solo {
+            Some(solo) => PanningState::index_of(solo) == index,
+            None       => !self.muted[index],
+        }
+    }
+
+
     /// Called when the APU was reset by turning it off.
     pub fn reset(&mut self) {
         for channel in &mut self.channels_in {
@@ -125,7 +238,12 @@ impl Mixer {
         let mut sample    = StereoSample::default();
         let mut is_silent = true;
 
-        for channel in &self.channels_in {
+        for (index, channel) in self.channels_in.iter().enumerate() {
+            // channels muted by the frontend are handled like silent channels
+            if !self.is_channel_audible(index) {
+                continue;
+            }
+
             match channel.sample {
                 // when the channel did produce an audio sample, it's value
                 // will be added to the value of the current sampling result
@@ -145,6 +263,20 @@ impl Mixer {
             }
         }
 
+        // the VIN input is routed to each side depending on NR50
+        // and scaled by the master volume, which ranges from 1 to 8
+        if let SampleResult::Audio(vin_sample) = self.vin_in {
+            if self.vin_left {
+                sample.left += vin_sample * (self.volume_left as f32 / 8.0) / 4.0;
+                is_silent = false;
+            }
+
+            if self.vin_right {
+                sample.right += vin_sample * (self.volume_right as f32 / 8.0) / 4.0;
+                is_silent = false;
+            }
+        }
+
         // return audio data if we got audio data from at least one channel
         if is_silent {
             SampleResult::Silence
@@ -155,6 +287,31 @@ impl Mixer {
     }
 }
 
+impl PanningState {
+    /// Checks whether a channel is routed to the left output.
+    pub fn is_left(&self, channel: ChannelType) -> bool {
+        self.left[Self::index_of(channel)]
+    }
+
+
+    /// Checks whether a channel is routed to the right output.
+    pub fn is_right(&self, channel: ChannelType) -> bool {
+        self.right[Self::index_of(channel)]
+    }
+
+
+    /// Get the index of a channel within the panning arrays.
+    fn index_of(channel: ChannelType) -> usize {
+        match channel {
+            ChannelType::Ch1Pulse1 => 0,
+            ChannelType::Ch2Pulse2 => 1,
+            ChannelType::Ch3Wave   => 2,
+            ChannelType::Ch4Noise  => 3,
+        }
+    }
+}
+
+
 impl Default for InputChannel {
     fn default() -> Self {
         Self {
@@ -17,8 +17,10 @@
 
 pub mod apu;
 pub mod audio_output;
+pub mod audio_recorder;
 pub mod channels;
 pub mod dac;
 pub mod hpf;
 pub mod mixer;
 pub mod sample;
+pub mod sample_queue;
@@ -0,0 +1,238 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::apu::audio_output::SampleBuffer;
+use crate::apu::sample::SampleType;
+
+
+/// The default number of sample buffers a queue can hold before the oldest
+/// buffers will be overwritten.
+pub const SAMPLE_QUEUE_CAPACITY: usize = 16;
+
+
+/// The data shared between sender and receiver of a sample queue.
+struct SampleQueueShared {
+    /// The sample buffers sent, but not yet received.
+    buffers: Mutex<VecDeque<Box<SampleBuffer>>>,
+
+    /// Notifies a receiver waiting for new buffers.
+    available: Condvar,
+
+    /// The maximum number of buffers to be stored.
+    capacity: usize,
+
+    /// The number of buffers being overwritten before being received.
+    dropped_buffers: AtomicU64,
+
+    /// Set when the sender was dropped.
+    sender_disconnected: AtomicBool,
+
+    /// Set when the receiver was dropped.
+    receiver_disconnected: AtomicBool,
+}
+
+
+/// Sender part of the queue to transfer audio samples from the APU to the consumer.
+/// When the queue is full, sending a new buffer overwrites the oldest one, so
+/// a consumer not receiving the samples won't let the queue grow without limit.
+pub struct SamplesSender {
+    shared: Arc<SampleQueueShared>,
+}
+
+
+/// Receiver part of the queue to transfer audio samples from the APU to the consumer.
+pub struct SamplesReceiver {
+    shared: Arc<SampleQueueShared>,
+}
+
+
+/// Creates a new queue, which can hold up to `capacity` sample buffers.
+/// Returns the sender and receiver parts of the queue.
+pub fn sample_queue(capacity: usize) -> (SamplesSender, SamplesReceiver) {
+    assert_ne!(capacity, 0);
+
+    let shared = Arc::new(SampleQueueShared {
+        buffers:                Mutex::new(VecDeque::with_capacity(capacity)),
+        available:              Condvar::new(),
+        capacity,
+        dropped_buffers:        AtomicU64::new(0),
+        sender_disconnected:    AtomicBool::new(false),
+        receiver_disconnected:  AtomicBool::new(false),
+    });
+
+    (
+        SamplesSender   { shared: shared.clone() },
+        SamplesReceiver { shared },
+    )
+}
+
+
+impl SampleQueueShared {
+    /// Get access to the buffers stored in the queue.
+    /// A poisoned lock is ignored, since the queue cannot get into an inconsistent state.
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Box<SampleBuffer>>> {
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+
+impl SamplesSender {
+    /// Sends a sample buffer to the receiver. If the queue is full, the oldest
+    /// buffer will be dropped. Fails, if the receiver was dropped, returning
+    /// the buffer which could not be sent.
+    pub fn send(&self, buffer: Box<SampleBuffer>) -> Result<(), Box<SampleBuffer>> {
+        if self.shared.receiver_disconnected.load(Ordering::Relaxed) {
+            return Err(buffer);
+        }
+
+        {
+            let mut buffers = self.shared.lock();
+
+            if buffers.len() >= self.shared.capacity {
+                buffers.pop_front();
+                self.shared.dropped_buffers.fetch_add(1, Ordering::Relaxed);
+            }
+
+            buffers.push_back(buffer);
+        }
+
+        self.shared.available.notify_one();
+
+        Ok(())
+    }
+
+
+    /// Get the number of buffers dropped, because the receiver did not take them in time.
+    pub fn get_dropped_buffers(&self) -> u64 {
+        self.shared.dropped_buffers.load(Ordering::Relaxed)
+    }
+}
+
+
+impl SamplesReceiver {
+    /// Takes the oldest buffer from the queue without waiting for new buffers to arrive.
+    pub fn try_recv(&self) -> Result<Box<SampleBuffer>, TryRecvError> {
+        match self.shared.lock().pop_front() {
+            Some(buffer) => Ok(buffer),
+            None if self.is_disconnected() => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+
+    /// Takes the oldest buffer from the queue. If the queue is empty, this waits until
+    /// a new buffer arrives or the timeout has elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Box<SampleBuffer>, RecvTimeoutError> {
+        let deadline    = Instant::now() + timeout;
+        let mut buffers = self.shared.lock();
+
+        loop {
+            if let Some(buffer) = buffers.pop_front() {
+                return Ok(buffer);
+            }
+
+            if self.is_disconnected() {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+
+            buffers = self.shared.available.wait_timeout(buffers, remaining)
+                .map(|(buffers, _)| buffers)
+                .unwrap_or_else(|e| e.into_inner().0)
+            ;
+        }
+    }
+
+
+    /// Iterates over all buffers currently stored in the queue without waiting for new ones.
+    pub fn try_iter(&self) -> impl Iterator<Item = Box<SampleBuffer>> + '_ {
+        std::iter::from_fn(|| self.try_recv().ok())
+    }
+
+
+    /// Takes all buffers currently stored in the queue without waiting for new ones
+    /// and appends their samples to `out`, alternating between left and right channel values.
+    /// Returns the number of stereo samples taken.
+    pub fn take_interleaved(&self, out: &mut Vec<SampleType>) -> usize {
+        let mut count = 0;
+
+        for buffer in self.try_iter() {
+            out.reserve(buffer.len() * 2);
+            out.extend(buffer.iter().flat_map(|sample| [sample.left.get_value(), sample.right.get_value()]));
+
+            count += buffer.len();
+        }
+
+        count
+    }
+
+
+    /// Get the number of buffers currently waiting to be received.
+    pub fn len(&self) -> usize {
+        self.shared.lock().len()
+    }
+
+
+    /// Checks whether there are no buffers waiting to be received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+
+    /// Get the maximum number of buffers the queue can hold.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+
+    /// Get the number of buffers dropped, because they were not received in time.
+    /// This allows the consumer to detect it fell behind the emulator.
+    pub fn get_dropped_buffers(&self) -> u64 {
+        self.shared.dropped_buffers.load(Ordering::Relaxed)
+    }
+
+
+    /// Checks whether the sender was dropped, so no more buffers will arrive.
+    fn is_disconnected(&self) -> bool {
+        self.shared.sender_disconnected.load(Ordering::Relaxed)
+    }
+}
+
+
+impl Drop for SamplesSender {
+    fn drop(&mut self) {
+        self.shared.sender_disconnected.store(true, Ordering::Relaxed);
+        self.shared.available.notify_all();
+    }
+}
+
+
+impl Drop for SamplesReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_disconnected.store(true, Ordering::Relaxed);
+    }
+}
@@ -14,47 +14,129 @@
  * You should have received a copy of the GNU General Public License
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
-
 use crate::utils::SerializableArray;
 use std::fs::File;
 use std::io;
 use std::io::Read;
 
-/// A data object containing a 256 byte boot ROM.
+
+/// Size of the boot ROM of the classic GameBoy.
+pub const BOOT_ROM_SIZE_DMG: usize = 0x0100;
+
+/// Size of the GameBoy Color boot ROM image. The image covers the address range
+/// of 0x0000 - 0x08ff, where the range 0x0100 - 0x01ff is not part of the boot ROM,
+/// but maps the cartridge header instead.
+pub const BOOT_ROM_SIZE_GBC: usize = 0x0900;
+
+
+/// The data of a boot ROM, which may be either the boot ROM of the classic GameBoy
+/// or the larger boot ROM of the GameBoy Color.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BootRomData {
+    /// A 256 byte boot ROM of the classic GameBoy.
+    Dmg(Box<SerializableArray<u8, BOOT_ROM_SIZE_DMG>>),
+
+    /// The 2048+256 byte boot ROM of the GameBoy Color.
+    Gbc(Box<SerializableArray<u8, BOOT_ROM_SIZE_GBC>>),
+}
+
+
+/// A data object containing a boot ROM.
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BootRom {
-    rom: Box<SerializableArray<u8, 256>>,
+    rom: BootRomData,
 }
 
 
 impl BootRom {
     /// Load a boot rom from a file.
+    /// The type of the boot ROM will be detected by the file size.
     pub fn load_file(filepath: &String) -> Result<BootRom, io::Error> {
         let mut file = File::open(filepath)?;
-        let metadata  = file.metadata()?;
-        let file_size = metadata.len();
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
 
-        // fail when the boot rom image has an unexpected size
-        if file_size != 256 {
+        Self::load_from_bytes(&data).map_err(|_| {
             let msg = format!(
-                "Unexpected Boot ROM size: {} is {} bytes, expected: 256 bytes",
+                "Unexpected Boot ROM size: {} is {} bytes, expected: {} or {} bytes",
                 *filepath,
-                file_size
+                data.len(),
+                BOOT_ROM_SIZE_DMG,
+                BOOT_ROM_SIZE_GBC
             );
 
-            return Err(io::Error::new(io::ErrorKind::Other, msg));
-        }
+            io::Error::new(io::ErrorKind::Other, msg)
+        })
+    }
+
+
+    /// Load a boot rom from a byte buffer.
+    /// The type of the boot ROM will be detected by the size of the buffer.
+    pub fn load_from_bytes(data: &[u8]) -> Result<BootRom, io::Error> {
+        let rom = match data.len() {
+            BOOT_ROM_SIZE_DMG => {
+                let mut buffer = [0u8; BOOT_ROM_SIZE_DMG];
+                buffer.copy_from_slice(data);
+                BootRomData::Dmg(Box::new(buffer.into()))
+            }
 
-        let mut buffer = [0u8; 256];
-        file.read_exact(&mut buffer)?;
+            BOOT_ROM_SIZE_GBC => {
+                let mut buffer = [0u8; BOOT_ROM_SIZE_GBC];
+                buffer.copy_from_slice(data);
+                BootRomData::Gbc(Box::new(buffer.into()))
+            }
+
+            size => {
+                let msg = format!(
+                    "Unexpected Boot ROM size: {} bytes, expected: {} or {} bytes",
+                    size,
+                    BOOT_ROM_SIZE_DMG,
+                    BOOT_ROM_SIZE_GBC
+                );
+
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+        };
 
         Ok(BootRom {
-            rom: Box::new(buffer.into())
+            rom
         })
     }
 
+
+    /// Get the data of this boot ROM.
+    pub fn get_data(&self) -> &BootRomData {
+        &self.rom
+    }
+
+
+    /// Checks whether this is a GameBoy Color boot ROM.
+    pub fn is_gbc_boot_rom(&self) -> bool {
+        match self.rom {
+            BootRomData::Dmg(_) => false,
+            BootRomData::Gbc(_) => true,
+        }
+    }
+
+
+    /// Checks whether an address is mapped to the boot ROM while it is active.
+    /// The classic boot ROM covers 0x0000 - 0x00ff, the GameBoy Color boot ROM
+    /// additionally covers 0x0200 - 0x08ff.
+    pub fn is_mapped(&self, address: u16) -> bool {
+        match self.rom {
+            BootRomData::Dmg(_) => matches!(address, 0x0000 ..= 0x00ff),
+            BootRomData::Gbc(_) => matches!(address, 0x0000 ..= 0x00ff | 0x0200 ..= 0x08ff),
+        }
+    }
+
+
     /// Get data from the boot ROM.
     pub fn read(&self, address: u16) -> u8 {
-        self.rom[address as usize]
+        match &self.rom {
+            BootRomData::Dmg(rom) => rom[address as usize],
+            BootRomData::Gbc(rom) => rom[address as usize],
+        }
     }
 }
@@ -47,6 +47,38 @@ pub enum GameBoyColorSupport {
 }
 
 
+/// Errors found when validating a cartridge header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CartridgeHeaderError {
+    /// The header does not contain the Nintendo logo.
+    InvalidNintendoLogo,
+
+    /// The header checksum does not match the header data.
+    /// `expected` is the value stored in the header, `actual` is computed from the header data.
+    HeaderChecksumMismatch {
+        expected: u8,
+        actual:   u8,
+    },
+
+    /// The global checksum does not match the ROM data.
+    /// `expected` is the value stored in the header, `actual` is computed from the ROM data.
+    /// Since this checksum is not verified by the original hardware, it's a warning only.
+    GlobalChecksumMismatch {
+        expected: u16,
+        actual:   u16,
+    },
+
+    /// The cartridge type stored in the header is unknown.
+    UnknownCartridgeType(u8),
+
+    /// The ROM size code stored in the header is unknown.
+    UnknownRomSize(u8),
+
+    /// The RAM size code stored in the header is unknown.
+    UnknownRamSize(u8),
+}
+
+
 /// Hold the licensee code (either it's old or new version)
 #[derive(Copy, Clone)]
 pub enum LicenseeCode {
@@ -123,6 +155,24 @@ pub const ROM_OFFSET_ROM_SIZE:              usize = 0x0148;
 pub const ROM_OFFSET_RAM_SIZE:              usize = 0x0149;
 pub const ROM_OFFSET_DESTINATION_CODE:      usize = 0x014A;
 pub const ROM_OFFSET_OLD_LICENSEE_CODE:     usize = 0x014B;
+pub const ROM_OFFSET_HEADER_CHECKSUM:       usize = 0x014D;
+pub const ROM_OFFSET_GLOBAL_CHECKSUM:       usize = 0x014E;
+
+/// The end of the cartridge header. Any ROM needs to be at least this size.
+pub const ROM_HEADER_END:                   usize = 0x0150;
+
+/// The size of MBC1M multi cartridge ROMs, which contain four games of 256kiB each.
+pub const MBC1M_ROM_SIZE:                   usize = 1024 * 1024;
+
+/// The offset of each game within a MBC1M multi cartridge ROM.
+pub const MBC1M_GAME_SIZE:                  usize = 256 * 1024;
+
+/// The Nintendo logo, which is required to be present in each cartridge header.
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xce, 0xed, 0x66, 0x66, 0xcc, 0x0d, 0x00, 0x0b, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0c, 0x00, 0x0d,
+    0x00, 0x08, 0x11, 0x1f, 0x88, 0x89, 0x00, 0x0e, 0xdc, 0xcc, 0x6e, 0xe6, 0xdd, 0xdd, 0xd9, 0x99,
+    0xbb, 0xbb, 0x67, 0x63, 0x6e, 0x0e, 0xec, 0xcc, 0xdd, 0xdc, 0x99, 0x9f, 0xbb, 0xb9, 0x33, 0x3e,
+];
 
 
 
@@ -259,7 +309,16 @@ impl Cartridge {
 
 
     /// Loads a cartridge and optionally its RAM from a byte buffer.
+    /// Fails if the ROM data is too small to contain a cartridge header.
+    /// The header itself will not be validated, see [Cartridge::validate_header].
     pub fn load_from_bytes(rom_data: Vec<u8>, ram_data: Option<Vec<u8>>) -> io::Result<Cartridge> {
+        if rom_data.len() < ROM_HEADER_END {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("ROM too small: {} bytes, expected at least {} bytes", rom_data.len(), ROM_HEADER_END)
+            ));
+        }
+
         let rom = RomData {
             data: rom_data,
         };
@@ -303,7 +362,9 @@ impl Cartridge {
             0x19..=0x1E => MemoryBankController::MBC5,
             0x20        => MemoryBankController::MBC6,
             0x22        => MemoryBankController::MBC7,
-            _           => MemoryBankController::None,
+            0x00        => MemoryBankController::None,
+            0x08..=0x09 => MemoryBankController::None,
+            _           => MemoryBankController::Unknown(rom_type),
         };
 
         let has_ram = match rom_type {
@@ -392,16 +453,91 @@ impl Cartridge {
 
     /// Checks if a ROM is a MBC1 multi cart ROM
     fn check_is_mbc1m_multi_cart(rom: &RomData) -> bool {
+        // All known multi cartridges are wired as 1MiB ROM containing four games
+        // of 256kiB each, so any other size cannot be a multi cartridge.
+        if rom.get_data().len() != MBC1M_ROM_SIZE {
+            return false;
+        }
+
         // A ROM will be considered as 'multi cartridge' if it contains a cartridge header with
         // a nintendo logo, which is required for startup at address 0x40000, which is the
         // expected location of the 2nd ROM.
-        if let Some(slice) = rom.get_slice(0x40104 .. 0x40134) {
-            let checksum = compute_checksum(slice);
-            checksum == 0x46
+        let logo_begin = MBC1M_GAME_SIZE + ROM_OFFSET_LOGO_BITMAP;
+        let logo_end   = logo_begin + NINTENDO_LOGO.len();
+
+        match rom.get_slice(logo_begin .. logo_end) {
+            Some(logo) => logo == NINTENDO_LOGO,
+            None       => false,
         }
-        else {
-            false
+    }
+
+
+    /// Validates the cartridge header and returns all errors found, if any.
+    /// See [CartridgeHeaderError::is_warning] for errors, which may be ignored.
+    pub fn validate_header(&self) -> Vec<CartridgeHeaderError> {
+        let mut errors = Vec::new();
+        let data       = self.rom.get_data();
+
+        // the logo is checked by the boot ROM, which refuses to start otherwise
+        let logo = &data[ROM_OFFSET_LOGO_BITMAP .. ROM_OFFSET_LOGO_BITMAP + NINTENDO_LOGO.len()];
+        if logo != NINTENDO_LOGO {
+            errors.push(CartridgeHeaderError::InvalidNintendoLogo);
         }
+
+        // the header checksum is checked by the boot ROM as well
+        let header_checksum = self.compute_header_checksum();
+        if header_checksum != self.get_header_checksum() {
+            errors.push(CartridgeHeaderError::HeaderChecksumMismatch {
+                expected: self.get_header_checksum(),
+                actual:   header_checksum,
+            });
+        }
+
+        let global_checksum = self.compute_global_checksum();
+        if global_checksum != self.get_global_checksum() {
+            errors.push(CartridgeHeaderError::GlobalChecksumMismatch {
+                expected: self.get_global_checksum(),
+                actual:   global_checksum,
+            });
+        }
+
+        let cartridge_type = data[ROM_OFFSET_ROM_TYPE];
+        match cartridge_type {
+            0x00 ..= 0x03 | 0x05 | 0x06 | 0x08 | 0x09 | 0x0B ..= 0x0D => {}
+            0x0F ..= 0x13 | 0x19 ..= 0x1E | 0x20 | 0x22 | 0xFC ..= 0xFF => {}
+            _ => errors.push(CartridgeHeaderError::UnknownCartridgeType(cartridge_type)),
+        }
+
+        let rom_size = data[ROM_OFFSET_ROM_SIZE];
+        if rom_size > 0x08 {
+            errors.push(CartridgeHeaderError::UnknownRomSize(rom_size));
+        }
+
+        let ram_size = data[ROM_OFFSET_RAM_SIZE];
+        if ram_size > 0x05 {
+            errors.push(CartridgeHeaderError::UnknownRamSize(ram_size));
+        }
+
+        errors
+    }
+
+
+    /// Computes the header checksum over the header bytes 0x0134 - 0x014c,
+    /// like it's done by the boot ROM.
+    pub fn compute_header_checksum(&self) -> u8 {
+        self.rom.get_data()[ROM_OFFSET_TITLE_STRING .. ROM_OFFSET_HEADER_CHECKSUM]
+            .iter()
+            .fold(0u8, |checksum, b| checksum.wrapping_sub(*b).wrapping_sub(1))
+    }
+
+
+    /// Computes the global checksum over all bytes of the ROM, except the checksum itself.
+    pub fn compute_global_checksum(&self) -> u16 {
+        self.rom.get_data()
+            .iter()
+            .enumerate()
+            .filter(|(offset, _)| *offset != ROM_OFFSET_GLOBAL_CHECKSUM && *offset != ROM_OFFSET_GLOBAL_CHECKSUM + 1)
+            .fold(0u16, |checksum, (_, b)| checksum.wrapping_add(*b as u16))
     }
 
 
@@ -411,6 +547,16 @@ impl Cartridge {
         self.source_file.as_ref()
     }
 
+    /// Set the source file of this cartridge.
+    pub(crate) fn set_source_file(&mut self, source_file: Option<PathBuf>) {
+        self.source_file = source_file;
+    }
+
+    /// Checks whether another cartridge contains the same ROM data as this one.
+    pub fn has_same_rom(&self, other: &Cartridge) -> bool {
+        self.rom.get_data() == other.rom.get_data()
+    }
+
     /// get the plain data of this cartridge
     pub fn get_rom(&self) -> &RomData {
         &self.rom
@@ -461,6 +607,20 @@ impl Cartridge {
         }
     }
 
+    /// Get the header checksum stored in the cartridge header.
+    pub fn get_header_checksum(&self) -> u8 {
+        self.rom.get_slice(ROM_OFFSET_HEADER_CHECKSUM .. ROM_OFFSET_HEADER_CHECKSUM + 1)
+            .map(|s| s[0])
+            .unwrap_or(0x00)
+    }
+
+    /// Get the global checksum over the whole ROM stored in the cartridge header.
+    pub fn get_global_checksum(&self) -> u16 {
+        self.rom.get_slice(ROM_OFFSET_GLOBAL_CHECKSUM .. ROM_OFFSET_GLOBAL_CHECKSUM + 2)
+            .map(|s| u16::from_be_bytes([s[0], s[1]]))
+            .unwrap_or(0x0000)
+    }
+
     /// get the game's manufacturer code
     pub fn get_manufacturer_code(&self) -> &String {
         &self.manufacturer_code
@@ -484,6 +644,12 @@ impl Cartridge {
         }
     }
 
+    /// checks whether this cartridge requires game boy color features
+    /// and therefore cannot be played on a classic game boy
+    pub fn requires_cgb(&self) -> bool {
+        matches!(self.supports_cgb, GameBoyColorSupport::Required)
+    }
+
     /// checks whether this cartridge supports super game boy features
     pub fn supports_sgb(&self) -> bool {
         self.supports_sgb
@@ -494,6 +660,12 @@ impl Cartridge {
         &self.mbc
     }
 
+    /// get the cartridge type as stored in the cartridge header,
+    /// which describes the memory bank controller and additional hardware.
+    pub fn get_cartridge_type(&self) -> u8 {
+        self.rom.get_at(ROM_OFFSET_ROM_TYPE)
+    }
+
     /// get the number of ROM banks in this cartridge
     pub fn get_rom_bank_count(&self) -> u32 {
         self.rom_bank_count
@@ -563,6 +735,48 @@ impl From<Cartridge> for CartridgeSerdeHelper {
 }
 
 
+impl CartridgeHeaderError {
+    /// Checks whether this error is just a warning, which does not prevent the cartridge from running.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, CartridgeHeaderError::GlobalChecksumMismatch { .. })
+    }
+}
+
+
+impl Display for CartridgeHeaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartridgeHeaderError::InvalidNintendoLogo => {
+                write!(f, "Invalid Nintendo logo")
+            }
+
+            CartridgeHeaderError::HeaderChecksumMismatch { expected, actual } => {
+                write!(f, "Header checksum mismatch: expected {expected:02x}, found {actual:02x}")
+            }
+
+            CartridgeHeaderError::GlobalChecksumMismatch { expected, actual } => {
+                write!(f, "Global checksum mismatch: expected {expected:04x}, found {actual:04x}")
+            }
+
+            CartridgeHeaderError::UnknownCartridgeType(value) => {
+                write!(f, "Unknown cartridge type: {value:02x}")
+            }
+
+            CartridgeHeaderError::UnknownRomSize(value) => {
+                write!(f, "Unknown ROM size: {value:02x}")
+            }
+
+            CartridgeHeaderError::UnknownRamSize(value) => {
+                write!(f, "Unknown RAM size: {value:02x}")
+            }
+        }
+    }
+}
+
+
+impl std::error::Error for CartridgeHeaderError {}
+
+
 impl Display for LicenseeCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
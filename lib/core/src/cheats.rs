@@ -0,0 +1,215 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{Display, Formatter};
+
+
+/// The highest address which can be patched by Game Genie codes,
+/// which is the end of the cartridge ROM area.
+const GAME_GENIE_MAX_ADDRESS: u16 = 0x7fff;
+
+/// The value XORed into the compare byte of Game Genie codes.
+const GAME_GENIE_COMPARE_XOR: u8 = 0xba;
+
+
+/// A single cheat code, which replaces the value read from a ROM address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheatCode {
+    /// The code as entered by the user.
+    pub code: String,
+
+    /// The address to be patched.
+    pub address: u16,
+
+    /// The value to be returned when reading the patched address.
+    pub value: u8,
+
+    /// When set, the address will only be patched if the original
+    /// value matches this value.
+    pub compare: Option<u8>,
+}
+
+
+/// Errors which may occur when decoding a cheat code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheatDecodeError {
+    /// The code does not have the expected number of digits.
+    InvalidLength(usize),
+
+    /// The code contains a character which is not a hex digit.
+    InvalidCharacter(char),
+
+    /// The address decoded is not within the cartridge ROM area.
+    AddressOutOfRange(u16),
+}
+
+
+/// Manages the cheat codes currently active.
+/// Each value read from the cartridge ROM will be passed through
+/// the manager, which may replace it with the value of a cheat code.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheatCodeManager {
+    codes: Vec<CheatCode>,
+}
+
+
+impl CheatCode {
+    /// Decodes a Game Genie code in the format `ABC-DEF` or `ABC-DEF-GHI`.
+    /// * `AB` is the new value
+    /// * `FCDE` is the address, XORed with 0xf000
+    /// * `GI` is the compare value, rotated right by two and XORed with 0xba
+    /// * `H` is not used
+    ///
+    /// Dashes and whitespace are optional.
+    pub fn from_game_genie(code: &str) -> Result<Self, CheatDecodeError> {
+        let digits = code
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .map(|c| c.to_digit(16).map(|d| d as u8).ok_or(CheatDecodeError::InvalidCharacter(c)))
+            .collect::<Result<Vec<u8>, CheatDecodeError>>()
+            ?
+        ;
+
+        if digits.len() != 6 && digits.len() != 9 {
+            return Err(CheatDecodeError::InvalidLength(digits.len()));
+        }
+
+        let value   = (digits[0] << 4) | digits[1];
+        let address = (
+                ((digits[5] as u16) << 12)
+            |   ((digits[2] as u16) <<  8)
+            |   ((digits[3] as u16) <<  4)
+            |    (digits[4] as u16)
+        ) ^ 0xf000;
+
+        if address > GAME_GENIE_MAX_ADDRESS {
+            return Err(CheatDecodeError::AddressOutOfRange(address));
+        }
+
+        let compare = if digits.len() == 9 {
+            let encoded = (digits[6] << 4) | digits[8];
+            Some(encoded.rotate_right(2) ^ GAME_GENIE_COMPARE_XOR)
+        }
+        else {
+            None
+        };
+
+        Ok(Self {
+            code: code.to_string(),
+            address,
+            value,
+            compare,
+        })
+    }
+
+
+    /// Applies this cheat code to a value read from a given address.
+    /// Returns the patched value, or `None` if this code does not apply.
+    pub fn apply(&self, address: u16, original: u8) -> Option<u8> {
+        if address != self.address {
+            return None;
+        }
+
+        match self.compare {
+            Some(compare) if compare != original => None,
+            _                                    => Some(self.value),
+        }
+    }
+}
+
+
+impl CheatCodeManager {
+    /// Creates a new empty [CheatCodeManager].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+
+    /// Decodes a Game Genie code and adds it to the list of active cheat codes.
+    /// Returns the decoded cheat code.
+    pub fn add_game_genie(&mut self, code: &str) -> Result<CheatCode, CheatDecodeError> {
+        let cheat_code = CheatCode::from_game_genie(code)?;
+        self.add(cheat_code.clone());
+
+        Ok(cheat_code)
+    }
+
+
+    /// Adds a cheat code to the list of active cheat codes.
+    pub fn add(&mut self, cheat_code: CheatCode) {
+        self.codes.push(cheat_code);
+    }
+
+
+    /// Removes a cheat code from the list of active cheat codes.
+    pub fn remove(&mut self, cheat_code: &CheatCode) {
+        self.codes.retain(|c| c != cheat_code);
+    }
+
+
+    /// Removes all cheat codes.
+    pub fn clear(&mut self) {
+        self.codes.clear();
+    }
+
+
+    /// Get the list of all active cheat codes.
+    pub fn get_codes(&self) -> &Vec<CheatCode> {
+        &self.codes
+    }
+
+
+    /// Checks whether there are any active cheat codes.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+
+    /// Applies all active cheat codes to a value read from a given address.
+    /// Returns the patched value or the original value, if no cheat code applies.
+    pub fn apply(&self, address: u16, original: u8) -> u8 {
+        self.codes
+            .iter()
+            .find_map(|cheat_code| cheat_code.apply(address, original))
+            .unwrap_or(original)
+    }
+}
+
+
+impl Display for CheatDecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheatDecodeError::InvalidLength(length) => {
+                write!(f, "Invalid code length: expected 6 or 9 digits, got {length}")
+            }
+
+            CheatDecodeError::InvalidCharacter(c) => {
+                write!(f, "Invalid character '{c}' in code")
+            }
+
+            CheatDecodeError::AddressOutOfRange(address) => {
+                write!(f, "Address {address:04x} is outside of the cartridge ROM")
+            }
+        }
+    }
+}
+
+
+impl std::error::Error for CheatDecodeError {
+}
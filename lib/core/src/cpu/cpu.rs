@@ -17,6 +17,7 @@
 
 use std::fmt::{Display, Formatter};
 
+use crate::cpu::interrupts::InterruptState;
 use crate::cpu::opcode::{Instruction, OpCode};
 use crate::cpu::opcodes::{OPCODE_TABLE, OPCODE_TABLE_EXTENDED};
 use crate::gameboy::Clock;
@@ -27,6 +28,11 @@ use crate::utils::{change_bit, get_bit, to_u16, to_u8};
 /// Number of cycles per second.
 pub const CPU_CLOCK_SPEED: Clock = 4_194_304;
 
+/// The number of clock cycles needed to dispatch an interrupt,
+/// which are 5 machine cycles: two wait states, pushing the program counter
+/// onto the stack in two steps and setting the program counter to the interrupt vector.
+pub const INTERRUPT_DISPATCH_CYCLES: Clock = 20;
+
 
 /// Definition for each supported 8 bit Register.
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -48,6 +54,7 @@ pub enum RegisterR16 {
     BC,
     DE,
     HL,
+    SP,
 }
 
 /// A list of CPU flags.
@@ -73,7 +80,7 @@ pub enum ImeState {
     EnabledInCycles(Clock),
 }
 
-/// Determines the CPU's state, when suspended by the HALT instruction.
+/// Determines the CPU's state, when suspended by the HALT or STOP instruction.
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HaltState {
@@ -82,6 +89,10 @@ pub enum HaltState {
 
     /// The CPU was suspended by the HALT command.
     Halt,
+
+    /// The CPU and the system clock were stopped by the STOP command.
+    /// Only a button press is able to wake up the CPU again.
+    Stop,
 }
 
 /// An object representing the gameboy's CPU
@@ -107,39 +118,53 @@ pub struct Cpu {
     /// The state whether interrupts are enabled or not.
     ime: ImeState,
 
-    /// The state whether the CPU was suspended by the HALT command.
+    /// The state whether the CPU was suspended by the HALT or STOP command.
     halt: HaltState,
+
+    /// Set when HALT was executed with interrupts disabled, but already pending.
+    /// In this case, the CPU fails to increment the program counter after
+    /// reading the next opcode, so the byte following HALT is read twice.
+    halt_bug: bool,
+
+    /// Optional bounds of the stack set by a debugger, which are
+    /// not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stack_bounds: Option<(u16, u16)>,
+
+    /// Set when the stack pointer left the stack bounds, until the violation was reported.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stack_violation: bool,
 }
 
 
 impl RegisterR16 {
     /// Get the 8 bit registers which contains the high and low bytes of a 16 bit register.
-    pub const fn to_r8(self) -> (RegisterR8, RegisterR8) {
+    /// The stack pointer is not built of 8 bit registers and returns [None].
+    pub const fn to_r8(self) -> Option<(RegisterR8, RegisterR8)> {
         match self {
-            RegisterR16::AF => (RegisterR8::A, RegisterR8::F),
-            RegisterR16::BC => (RegisterR8::B, RegisterR8::C),
-            RegisterR16::DE => (RegisterR8::D, RegisterR8::E),
-            RegisterR16::HL => (RegisterR8::H, RegisterR8::L),
+            RegisterR16::AF => Some((RegisterR8::A, RegisterR8::F)),
+            RegisterR16::BC => Some((RegisterR8::B, RegisterR8::C)),
+            RegisterR16::DE => Some((RegisterR8::D, RegisterR8::E)),
+            RegisterR16::HL => Some((RegisterR8::H, RegisterR8::L)),
+            RegisterR16::SP => None,
         }
     }
 
     /// Get the 8 bit register which contains the high byte of a 16 bit register.
-    pub const fn get_high(self) -> RegisterR8 {
-        match self {
-            RegisterR16::AF => RegisterR8::A,
-            RegisterR16::BC => RegisterR8::B,
-            RegisterR16::DE => RegisterR8::D,
-            RegisterR16::HL => RegisterR8::H,
+    /// The stack pointer is not built of 8 bit registers and returns [None].
+    pub const fn get_high(self) -> Option<RegisterR8> {
+        match self.to_r8() {
+            Some((high, _)) => Some(high),
+            None            => None,
         }
     }
 
     /// Get the 8 bit register which contains the low byte of a 16 bit register.
-    pub const fn get_low(self) -> RegisterR8 {
-        match self {
-            RegisterR16::AF => RegisterR8::F,
-            RegisterR16::BC => RegisterR8::C,
-            RegisterR16::DE => RegisterR8::E,
-            RegisterR16::HL => RegisterR8::L,
+    /// The stack pointer is not built of 8 bit registers and returns [None].
+    pub const fn get_low(self) -> Option<RegisterR8> {
+        match self.to_r8() {
+            Some((_, low)) => Some(low),
+            None           => None,
         }
     }
 }
@@ -166,6 +191,7 @@ impl Display for RegisterR16 {
             RegisterR16::BC => "BC",
             RegisterR16::DE => "DE",
             RegisterR16::HL => "HL",
+            RegisterR16::SP => "SP",
         })
     }
 }
@@ -190,6 +216,7 @@ impl HaltState {
         match self {
             HaltState::Running => true,
             HaltState::Halt => false,
+            HaltState::Stop => false,
         }
     }
 }
@@ -209,14 +236,30 @@ impl Cpu {
 
             ime:  ImeState::Disabled,
             halt: HaltState::Running,
+
+            halt_bug: false,
+
+            stack_bounds:    None,
+            stack_violation: false,
         }
     }
 
-    /// Checks whether the CPU is currently running or being suspended by HALT state.
+    /// Checks whether the CPU is currently running or being suspended by HALT or STOP state.
     pub fn is_running(&self) -> bool {
         self.halt.is_cpu_running()
     }
 
+    /// Checks whether the CPU was stopped by the STOP command.
+    pub fn is_stopped(&self) -> bool {
+        matches!(self.halt, HaltState::Stop)
+    }
+
+    /// Forces the CPU to leave the HALT or STOP state, if active.
+    pub(crate) fn leave_halt_state(&mut self) {
+        self.halt     = HaltState::Running;
+        self.halt_bug = false;
+    }
+
     /// Let the CPU process their data.
     /// This function takes the amount of ticks to be processed.
     pub fn update(&mut self, cycles: Clock) {
@@ -239,21 +282,33 @@ impl Cpu {
     }
 
     /// Handles any pending interrupts.
+    /// If an interrupt is being handled, IME gets disabled, only the IF bit of the interrupt
+    /// being handled gets cleared and the program counter jumps to the interrupt's vector.
+    /// Pending interrupts are handled in priority order VBlank > STAT > Timer > Serial > Joypad.
+    /// The interrupt to be handled is selected after pushing the upper byte of the program counter,
+    /// which may overwrite the IE register. If no interrupt is pending anymore at this point,
+    /// the dispatch gets cancelled and the program counter jumps to 0x0000 instead.
+    /// Returns the number of cycles spent to dispatch the interrupt, if any.
     pub fn handle_interrupts(&mut self) -> Option<Clock> {
         match self.ime {
-            ImeState::Enabled => {
-                let cpu_state = &mut self.get_mmu_mut().get_peripherals_mut().interrupts;
+            ImeState::Enabled if self.get_mmu().get_peripherals().interrupts.has_interrupts_pending() => {
+                // disable further interrupts when a interrupt is being handled
+                self.ime = ImeState::Disabled;
 
-                if let Some(interrupt) = cpu_state.take_pending_interrupt() {
-                    // disable further interrupts when a interrupt is being handled
-                    self.ime = ImeState::Disabled;
+                let (pc_high, pc_low) = to_u8(self.get_instruction_pointer());
+                self.push_u8(pc_high);
 
-                    // call the address of the interrupt
-                    self.call_addr(interrupt.address());
+                // select the interrupt after IE may have been modified by the push
+                let interrupt = self.get_mmu_mut().get_peripherals_mut().interrupts.take_pending_interrupt();
 
-                    // stop handling other interrupts
-                    return Some(20);
-                }
+                self.push_u8(pc_low);
+
+                // jump to the address of the interrupt
+                let address = interrupt.map(|interrupt| interrupt.address()).unwrap_or(0x0000);
+                self.set_instruction_pointer(address);
+
+                // stop handling other interrupts
+                return Some(INTERRUPT_DISPATCH_CYCLES);
             },
 
             _ => { },
@@ -309,8 +364,16 @@ impl Cpu {
         }
     }
 
+    /// Get the decoded state of the IME flag and the interrupt registers.
+    pub fn get_interrupt_state(&self) -> InterruptState {
+        self.get_mmu().get_peripherals().interrupts.state(self.is_interrupts_enabled())
+    }
+
     /// Enters the HALT mode.
-    pub fn enter_halt_mode(&mut self) {
+    /// When `halt_bug` is set, executing HALT while interrupts are disabled, but pending,
+    /// triggers the HALT bug, causing the next byte to be read twice. Otherwise,
+    /// the CPU just continues with the next instruction.
+    pub fn enter_halt_mode(&mut self, halt_bug: bool) {
         match self.ime {
             ImeState::EnabledInCycles(_) => {
                 // when interrupts are about to be enabled, enable them immediately
@@ -320,6 +383,10 @@ impl Cpu {
                 self.instruction_pointer -= 1;
             }
 
+            ImeState::Disabled if halt_bug && self.get_mmu().get_peripherals().interrupts.has_interrupts_pending() => {
+                self.halt_bug = true;
+            }
+
             _ => {
                 self.halt = HaltState::Halt;
             }
@@ -327,6 +394,12 @@ impl Cpu {
     }
 
 
+    /// Enters the STOP mode, which can only be left by pressing a button.
+    pub fn enter_stop_mode(&mut self) {
+        self.halt = HaltState::Stop;
+    }
+
+
     /// Get access to the memory unit linked to the CPU.
     pub fn get_mmu(&self) -> &Mmu {
         &self.mmu
@@ -341,43 +414,60 @@ impl Cpu {
     /// Fetches the next opcode on the current location of the instruction pointer.
     /// The instruction pointer will be forwarded to the next instruction.
     pub fn fetch_next_opcode(&mut self) -> &'static OpCode {
+        let (_, opcode) = self.fetch_next_opcode_with_id();
+        opcode
+    }
+
+    /// Fetches the next opcode on the current location of the instruction pointer
+    /// together with its 16 bit ID, which includes the 0xCB prefix for extended opcodes.
+    /// The instruction pointer will be forwarded to the next instruction.
+    fn fetch_next_opcode_with_id(&mut self) -> (u16, &'static OpCode) {
         let opcode_byte = self.fetch_u8();
+
+        // after the HALT bug, the program counter is not incremented after reading the opcode
+        if self.halt_bug {
+            self.halt_bug = false;
+            self.instruction_pointer = self.instruction_pointer.wrapping_sub(1);
+        }
+
         if opcode_byte != 0xCB {
-            &OPCODE_TABLE[opcode_byte as usize]
+            (opcode_byte as u16, &OPCODE_TABLE[opcode_byte as usize])
         }
         else {
             let opcode_byte_extended = self.fetch_u8();
-            &OPCODE_TABLE_EXTENDED[opcode_byte_extended as usize]
+            (to_u16(opcode_byte_extended, opcode_byte), &OPCODE_TABLE_EXTENDED[opcode_byte_extended as usize])
         }
     }
 
     /// Fetches the next instruction on the current location of the instruction pointer.
     /// The instruction pointer will be forwarded to the next instruction.
+    /// Like any other read of the CPU, the opcode is read via [Mmu::cpu_read_u8], so
+    /// executing code from video memory while it's locked by the PPU will read 0xff.
     pub fn fetch_next_instruction(&mut self) -> Instruction {
-        let opcode_address = self.instruction_pointer;
-        let opcode_byte    = self.get_next_byte() as u16;
-        let opcode_id      = if opcode_byte == 0xCB { self.get_next_u16() } else { opcode_byte };
-        let opcode         = self.fetch_next_opcode();
+        let opcode_address      = self.instruction_pointer;
+        let (opcode_id, opcode) = self.fetch_next_opcode_with_id();
 
         Instruction {
             opcode,
             opcode_id,
             opcode_address,
             arg: [
-                self.mmu.read_u8(self.instruction_pointer),
-                self.mmu.read_u8(self.instruction_pointer.wrapping_add(1))
+                self.get_next_byte_at(0),
+                self.get_next_byte_at(1),
             ]
         }
     }
 
     /// Get the next byte on the current location of the instruction pointer, without moving it.
+    /// The byte is read as seen by the CPU, but without any side effects.
     pub fn get_next_byte(&self) -> u8 {
-        self.mmu.read_u8(self.instruction_pointer)
+        self.mmu.cpu_peek_u8(self.instruction_pointer)
     }
 
     /// Get the next byte relative to the current location of the instruction pointer, without moving it.
+    /// The byte is read as seen by the CPU, but without any side effects.
     pub fn get_next_byte_at(&self, offset: u16) -> u8 {
-        self.mmu.read_u8(self.instruction_pointer + offset)
+        self.mmu.cpu_peek_u8(self.instruction_pointer.wrapping_add(offset))
     }
 
     /// Get the next i8 value on the current location of the instruction pointer, without moving it.
@@ -400,7 +490,7 @@ impl Cpu {
     /// Fetches the next u8 value on the current location of the instruction pointer.
     /// The instruction pointer will be forwarded to the next instruction.
     pub fn fetch_u8(&mut self) -> u8 {
-        let value = self.get_next_byte();
+        let value = self.mmu.cpu_read_u8(self.instruction_pointer);
         self.instruction_pointer += 1;
         value
     }
@@ -428,7 +518,13 @@ impl Cpu {
     /// Pushes a 8bit value on the stack, moving the stack pointer.
     pub fn push_u8(&mut self, value: u8) {
         self.stack_pointer = self.stack_pointer.wrapping_sub(1);
-        self.mmu.write_u8(self.stack_pointer, value);
+        self.mmu.cpu_write_u8(self.stack_pointer, value);
+
+        if let Some((low, _)) = self.stack_bounds {
+            if self.stack_pointer < low {
+                self.stack_violation = true;
+            }
+        }
     }
 
     /// Pushes a 16bit value on the stack, moving the stack pointer.
@@ -440,8 +536,15 @@ impl Cpu {
 
     /// Pops a 8bit value from the stack, moving the stack pointer.
     pub fn pop_u8(&mut self) -> u8 {
-        let value = self.mmu.read_u8(self.stack_pointer);
+        let value = self.mmu.cpu_read_u8(self.stack_pointer);
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
+
+        if let Some((_, high)) = self.stack_bounds {
+            if self.stack_pointer > high {
+                self.stack_violation = true;
+            }
+        }
+
         value
     }
 
@@ -464,18 +567,34 @@ impl Cpu {
 
     /// Get the value of a 16 bit register.
     pub fn get_r16(&self, register: RegisterR16) -> u16 {
-        let (high_r8, low_r8) = register.to_r8();
-        let high = self.registers[high_r8 as usize];
-        let low  = self.registers[low_r8 as usize];
-        to_u16(high, low)
+        match register.to_r8() {
+            Some((high_r8, low_r8)) => {
+                let high = self.registers[high_r8 as usize];
+                let low  = self.registers[low_r8 as usize];
+                to_u16(high, low)
+            }
+
+            None => self.stack_pointer,
+        }
     }
 
     /// Set the value of a 16 bit register.
+    /// When writing AF, the lower 4 bits of F are always zero.
     pub fn set_r16(&mut self, register: RegisterR16, value: u16) {
-        let (high_r8, low_r8) = register.to_r8();
-        let (high, low) = to_u8(value);
-        self.registers[high_r8 as usize] = high;
-        self.registers[low_r8 as usize]  = low;
+        match register.to_r8() {
+            Some((high_r8, low_r8)) => {
+                let (high, low) = to_u8(value);
+                let low = match register {
+                    RegisterR16::AF => low & 0xf0,
+                    _               => low,
+                };
+
+                self.registers[high_r8 as usize] = high;
+                self.registers[low_r8 as usize]  = low;
+            }
+
+            None => self.stack_pointer = value,
+        }
     }
 
     /// Checks whether a specific CPU flag is set.
@@ -546,6 +665,30 @@ impl Cpu {
         self.stack_pointer = address;
     }
 
+    /// Set the range the stack pointer is expected to stay in, to detect stack overflows
+    /// and underflows. When pushing a value moves the stack pointer below `low` or popping
+    /// a value moves it above `high`, a [crate::debug::DebugEvent::StackViolation] will be reported.
+    pub fn set_stack_bounds(&mut self, low: u16, high: u16) {
+        self.stack_bounds = Some((low, high));
+    }
+
+    /// Removes the stack bounds, so no stack violations will be reported.
+    pub fn clear_stack_bounds(&mut self) {
+        self.stack_bounds    = None;
+        self.stack_violation = false;
+    }
+
+    /// Get the range the stack pointer is expected to stay in, if any.
+    pub fn get_stack_bounds(&self) -> Option<(u16, u16)> {
+        self.stack_bounds
+    }
+
+    /// Checks whether the stack pointer left the stack bounds since the last call
+    /// and clears the violation state.
+    pub fn take_stack_violation(&mut self) -> bool {
+        std::mem::take(&mut self.stack_violation)
+    }
+
     /// Get the intermediate value.
     pub fn get_intermediate_value(&self) -> u8 {
         self.intermediate_value
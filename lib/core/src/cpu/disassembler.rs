@@ -0,0 +1,216 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use crate::cpu::opcode::Instruction;
+
+
+/// Names of hardware defined locations, like the cartridge entry point,
+/// RST vectors and interrupt handlers.
+const HARDWARE_SYMBOLS: [(u16, &str); 14] = [
+    (0x0000, "RST_00"),
+    (0x0008, "RST_08"),
+    (0x0010, "RST_10"),
+    (0x0018, "RST_18"),
+    (0x0020, "RST_20"),
+    (0x0028, "RST_28"),
+    (0x0030, "RST_30"),
+    (0x0038, "RST_38"),
+    (0x0040, "VBlankInterrupt"),
+    (0x0048, "LcdStatInterrupt"),
+    (0x0050, "TimerInterrupt"),
+    (0x0058, "SerialInterrupt"),
+    (0x0060, "JoypadInterrupt"),
+    (0x0100, "EntryPoint"),
+];
+
+
+/// A disassembler to read instructions from any memory source.
+/// The memory is accessed via a read function, so the disassembler
+/// can be used on the emulator's memory bus as well as on raw ROM data.
+pub struct Disassembler<F>
+    where F: Fn(u16) -> u8
+{
+    /// The function to read a single byte from any address.
+    read: F,
+}
+
+
+impl<F> Disassembler<F>
+    where F: Fn(u16) -> u8
+{
+    /// Creates a new disassembler reading memory via the given function.
+    pub fn new(read: F) -> Self {
+        Self {
+            read,
+        }
+    }
+
+
+    /// Reads a single instruction from the given address.
+    /// Bytes not forming a valid opcode are read as a single byte `DB` instruction.
+    pub fn disassemble_at(&self, address: u16) -> Instruction {
+        Instruction::read_instruction(address, &self.read)
+    }
+
+
+    /// Disassembles all instructions starting within the range from `start` up to,
+    /// but not including `end`. The last instruction may exceed the end address.
+    pub fn disassemble_range(&self, start: u16, end: u16) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        let mut address      = start;
+
+        while address < end {
+            let instruction = self.disassemble_at(address);
+            let length      = instruction.get_instruction_length();
+
+            instructions.push(instruction);
+
+            // stop when reaching the end of the address space
+            match address.checked_add(length) {
+                Some(next) => address = next,
+                None       => break,
+            }
+        }
+
+        instructions
+    }
+
+
+    /// Disassembles up to `count` instructions starting at the given address.
+    /// This will stop before reaching the end of the address space.
+    pub fn disassemble_count(&self, start: u16, count: usize) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(count);
+        let mut address      = start;
+
+        while instructions.len() < count && address < 0xffff {
+            let instruction = self.disassemble_at(address);
+            address = address.saturating_add(instruction.get_instruction_length());
+
+            instructions.push(instruction);
+        }
+
+        instructions
+    }
+
+
+    /// Reads the bytes forming a given instruction.
+    pub fn read_instruction_bytes(&self, instruction: &Instruction) -> Vec<u8> {
+        (0..instruction.get_instruction_length())
+            .map(|offset| instruction.opcode_address.wrapping_add(offset))
+            .map(|address| (self.read)(address))
+            .collect()
+    }
+}
+
+
+/// A table of names assigned to memory addresses, like the names of routines
+/// or variables taken from the debug symbols of a ROM.
+/// Hardware defined locations like RST vectors, interrupt handlers and the
+/// entry point are always labeled, unless a different name was assigned to them.
+pub struct SymbolTable {
+    /// Maps each address to the name of its symbol.
+    symbols: HashMap<u16, String>,
+}
+
+
+impl SymbolTable {
+    /// Creates a new symbol table containing only the hardware defined locations.
+    pub fn new() -> Self {
+        let symbols = HARDWARE_SYMBOLS
+                .iter()
+                .map(|(address, name)| (*address, name.to_string()))
+                .collect()
+        ;
+
+        Self {
+            symbols,
+        }
+    }
+
+
+    /// Parses the content of a `.sym` file as generated by common assemblers and
+    /// read by no$gmb and other emulators. Each line contains a symbol in the format
+    /// `bank:address name`, like `00:0150 main`. Comments starting with `;` and
+    /// lines which cannot be parsed will be ignored.
+    /// Since the symbol table does not distinguish between ROM banks, the first
+    /// symbol found for each address will be used.
+    pub fn from_no_gba_sym(data: &str) -> Self {
+        let mut table   = Self::new();
+        let mut defined = HashSet::new();
+
+        for line in data.lines() {
+            // strip comments
+            let line = match line.find(';') {
+                Some(index) => &line[..index],
+                None        => line,
+            };
+
+            let mut parts = line.split_whitespace();
+            let (Some(location), Some(name)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+
+            let Some((bank, address)) = location.split_once(':') else {
+                continue;
+            };
+
+            let (Ok(_), Ok(address)) = (u16::from_str_radix(bank, 16), u16::from_str_radix(address, 16)) else {
+                continue;
+            };
+
+            // the first definition of each address replaces the hardware defined name
+            if defined.insert(address) {
+                table.add_symbol(address, name);
+            }
+        }
+
+        table
+    }
+
+
+    /// Assigns a name to an address, replacing the previous name, if any.
+    pub fn add_symbol(&mut self, address: u16, name: &str) {
+        self.symbols.insert(address, name.to_string());
+    }
+
+
+    /// Get the name of the symbol at the given address, if any.
+    pub fn get_symbol(&self, address: u16) -> Option<&str> {
+        self.symbols.get(&address).map(|name| name.as_str())
+    }
+
+
+    /// Get the number of symbols in this table.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+
+    /// Checks whether this table contains no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -50,6 +50,42 @@ pub struct InterruptRegisters {
 }
 
 
+/// The decoded state of a single interrupt.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InterruptInfo {
+    /// The interrupt described.
+    pub interrupt: Interrupt,
+
+    /// Whether the interrupt is enabled via IE register.
+    pub enabled: bool,
+
+    /// Whether the interrupt was requested via IF register.
+    pub pending: bool,
+
+    /// Whether the interrupt is enabled, pending and IME is set,
+    /// so the CPU would handle it on the next opportunity.
+    pub would_fire: bool,
+}
+
+
+/// A decoded snapshot of the interrupt state, containing
+/// the IME flag as well as the IE and IF registers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InterruptState {
+    /// Whether interrupts are globally enabled via IME.
+    pub ime: bool,
+
+    /// The raw value of the IE register (0xffff).
+    pub interrupts_enabled: u8,
+
+    /// The raw value of the IF register (0xff0f).
+    pub interrupts_flagged: u8,
+
+    /// The state of each interrupt, ordered by their priority.
+    pub interrupts: [InterruptInfo; 5],
+}
+
+
 impl Interrupt {
     /// An array containing all possible interrupts for easier iteration.
     const ALL_INTERRUPTS : [Interrupt; 5] = [
@@ -74,6 +110,26 @@ impl Interrupt {
 }
 
 
+impl InterruptState {
+    /// Get the state of a single interrupt.
+    pub fn get(&self, interrupt: Interrupt) -> &InterruptInfo {
+        self.interrupts
+            .iter()
+            .find(|info| info.interrupt == interrupt)
+            .unwrap()
+    }
+
+
+    /// Get the interrupt with the highest priority, which would be handled next, if any.
+    pub fn get_next_interrupt(&self) -> Option<Interrupt> {
+        self.interrupts
+            .iter()
+            .find(|info| info.would_fire)
+            .map(|info| info.interrupt)
+    }
+}
+
+
 impl InterruptRegisters {
     pub fn new() -> Self {
         Self {
@@ -102,6 +158,13 @@ impl InterruptRegisters {
     }
 
 
+    /// Checks whether an interrupt was requested and is not yet handled,
+    /// regardless of whether it was enabled via IE register.
+    pub fn is_interrupt_flagged(&self, interrupt: Interrupt) -> bool {
+        self.interrupts_flagged.contains(interrupt)
+    }
+
+
     /// Get pending interrupts in form of an integer with each bit representing it's according interrupt.
     pub fn get_interrupts_pending(&self) -> Interrupts {
         self.interrupts_flagged & self.interrupts_enabled
@@ -115,6 +178,30 @@ impl InterruptRegisters {
     }
 
 
+    /// Decodes the interrupt registers into an [InterruptState].
+    /// Since the IME flag is stored within the CPU, it has to be provided by the caller.
+    pub fn state(&self, ime: bool) -> InterruptState {
+        let interrupts = Interrupt::ALL_INTERRUPTS.map(|interrupt| {
+            let enabled = self.interrupts_enabled.contains(interrupt);
+            let pending = self.interrupts_flagged.contains(interrupt);
+
+            InterruptInfo {
+                interrupt,
+                enabled,
+                pending,
+                would_fire: ime && enabled && pending,
+            }
+        });
+
+        InterruptState {
+            ime,
+            interrupts_enabled: self.interrupts_enabled.bits() | self.ie_unused_bits,
+            interrupts_flagged: self.interrupts_flagged.bits() | 0b_1110_0000,
+            interrupts,
+        }
+    }
+
+
     /// Checks the pending interrupts and returns the next pending interrupt,
     /// which was enabled via IE register.
     pub fn take_pending_interrupt(&mut self) -> Option<Interrupt> {
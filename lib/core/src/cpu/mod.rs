@@ -16,6 +16,7 @@
  */
 
 pub mod cpu;
+pub mod disassembler;
 pub mod interrupts;
 pub mod opcode;
 pub mod opcodes;
@@ -17,12 +17,16 @@
 
 use std::fmt::{Display, Formatter};
 
-use crate::cpu::opcodes::{OPCODE_TABLE, OPCODE_TABLE_EXTENDED};
+use crate::cpu::opcodes::{OPCODE_INVALID, OPCODE_TABLE, OPCODE_TABLE_EXTENDED, OPCODE_UNASSIGNED};
 use crate::gameboy::{Clock, GameBoy};
 use crate::utils::{to_u16, to_u8};
 
 type ProcessOpCode = fn(gb: &mut GameBoy, ctx: &mut OpCodeContext) -> OpCodeResult;
 
+/// The label of a pseudo instruction to display bytes which do not form a valid opcode.
+/// This allows to still read the data stored in regions which are not meant to be executed.
+const DATA_BYTE_LABEL: &str = "DB ${op8}";
+
 
 /// A macro to generate an opcode implementation function.
 macro_rules! opcode {
@@ -187,55 +191,70 @@ impl From<()> for OpCodeResult {
 
 
 impl OpCode {
+    /// Checks whether this opcode is invalid or has no functionality assigned,
+    /// so it cannot be executed by the CPU.
+    pub fn is_invalid(&self) -> bool {
+            self.name == OPCODE_INVALID.name
+        ||  self.name == OPCODE_UNASSIGNED.name
+    }
+
+
     /// Split the attribute string into tokens.
-    pub fn tokenize(&self) -> Vec<Token> {
-        let mut characters = self.name;
-        let mut tokens     = Vec::new();
-
-        // take the text up to the first space as command
-        if let Some(space_index) = characters.find(' ') {
-            let opcode_name = &characters[..space_index];
-            let opcode_args = &characters[space_index + 1..];
-            tokens.push(Token::Command(opcode_name));
-            characters = opcode_args;
-        }
-        else {
-            tokens.push(Token::Command(characters));
-            characters = "";
-        }
+    pub fn tokenize(&self) -> Vec<Token<'_>> {
+        tokenize_label(self.name)
+    }
+}
 
-        // parse remaining label
-        while !characters.is_empty() {
-            let begin = characters.find('{');
-            let end   = characters.find('}');
-
-            match (begin, end) {
-                // argument placeholder
-                (Some(begin_index), Some(end_index)) if begin_index < end_index => {
-                    // characters in front of the placeholder
-                    if begin_index > 0 {
-                        tokens.push(Token::Text(&characters[..begin_index]));
-                    }
 
-                    // the placeholder itself
-                    if (end_index - begin_index) > 0 {
-                        tokens.push(Token::Argument(&characters[begin_index+1..end_index]));
-                    }
+/// Split an opcode label into tokens.
+/// See [OpCode::tokenize]
+fn tokenize_label(label: &str) -> Vec<Token<'_>> {
+    let mut characters = label;
+    let mut tokens     = Vec::new();
+
+    // take the text up to the first space as command
+    if let Some(space_index) = characters.find(' ') {
+        let opcode_name = &characters[..space_index];
+        let opcode_args = &characters[space_index + 1..];
+        tokens.push(Token::Command(opcode_name));
+        characters = opcode_args;
+    }
+    else {
+        tokens.push(Token::Command(characters));
+        characters = "";
+    }
 
-                    // remaining characters for the next iteration
-                    characters = &characters[end_index+1..];
+    // parse remaining label
+    while !characters.is_empty() {
+        let begin = characters.find('{');
+        let end   = characters.find('}');
+
+        match (begin, end) {
+            // argument placeholder
+            (Some(begin_index), Some(end_index)) if begin_index < end_index => {
+                // characters in front of the placeholder
+                if begin_index > 0 {
+                    tokens.push(Token::Text(&characters[..begin_index]));
                 }
 
-                // no placeholder found, take the whole line as text token
-                _ => {
-                    tokens.push(Token::Text(characters));
-                    characters = "";
+                // the placeholder itself
+                if (end_index - begin_index) > 0 {
+                    tokens.push(Token::Argument(&characters[begin_index+1..end_index]));
                 }
+
+                // remaining characters for the next iteration
+                characters = &characters[end_index+1..];
             }
-        }
 
-        tokens
+            // no placeholder found, take the whole line as text token
+            _ => {
+                tokens.push(Token::Text(characters));
+                characters = "";
+            }
+        }
     }
+
+    tokens
 }
 
 
@@ -291,6 +310,32 @@ impl Instruction {
     }
 
 
+    /// Checks whether this instruction does not contain a valid opcode and will be
+    /// displayed as a single data byte instead.
+    pub fn is_data_byte(&self) -> bool {
+        self.opcode.is_invalid()
+    }
+
+
+    /// Get the label to display this instruction. Invalid opcodes are displayed
+    /// as a `DB` pseudo instruction containing the opcode byte.
+    pub fn get_label(&self) -> &'static str {
+        if self.is_data_byte() {
+            DATA_BYTE_LABEL
+        }
+        else {
+            self.opcode.name
+        }
+    }
+
+
+    /// Split the label of this instruction into tokens.
+    /// See [OpCode::tokenize]
+    pub fn tokenize(&self) -> Vec<Token<'_>> {
+        tokenize_label(self.get_label())
+    }
+
+
     /// Get the number of bytes forming this instruction.
     pub fn get_instruction_length(&self) -> u16 {
         // opcode length + 1 byte for 0xcb opcodes
@@ -299,6 +344,37 @@ impl Instruction {
     }
 
 
+    /// Get the absolute address this instruction may jump to, if it is
+    /// a `JP`, `JR`, `CALL` or `RST` instruction with a constant target.
+    /// For relative jumps, the target is computed from the address of the
+    /// instruction following this one.
+    /// Jumps to a register like `JP HL` and returns won't provide a target.
+    pub fn get_jump_target(&self) -> Option<u16> {
+        match self.opcode_id {
+            // JR i8, JR cc, i8
+            0x18 | 0x20 | 0x28 | 0x30 | 0x38 => {
+                let offset = self.arg[0] as i8;
+                let next   = self.opcode_address.wrapping_add(self.get_instruction_length());
+
+                Some(next.wrapping_add_signed(offset as i16))
+            }
+
+            // JP u16, JP cc, u16, CALL u16, CALL cc, u16
+            0xc2 | 0xc3 | 0xca | 0xd2 | 0xda |
+            0xc4 | 0xcc | 0xcd | 0xd4 | 0xdc => {
+                Some(to_u16(self.arg[1], self.arg[0]))
+            }
+
+            // RST vectors
+            0xc7 | 0xcf | 0xd7 | 0xdf | 0xe7 | 0xef | 0xf7 | 0xff => {
+                Some(self.opcode_id & 0x0038)
+            }
+
+            _ => None,
+        }
+    }
+
+
     /// Get the replacement string for an argument placeholder.
     pub fn resolve_argument(&self, arg: &str) -> String {
         let arg0 = self.arg[0];
@@ -335,6 +411,11 @@ impl Instruction {
                 format!("{:04x}", value)
             }
 
+            "op8" => {
+                let (_, value) = to_u8(self.opcode_id);
+                format!("{:02x}", value)
+            }
+
             _ => arg.to_string()
         }
     }
@@ -343,7 +424,7 @@ impl Instruction {
 
 impl Display for Instruction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut label = self.opcode.name.to_string();
+        let mut label = self.get_label().to_string();
 
         loop {
             let begin = label.find('{');
@@ -81,7 +81,7 @@ pub mod inc {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16_ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -91,7 +91,7 @@ pub mod inc {
                 let address = gb.cpu.get_r16(r16_ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = increment_u8v(gb, value);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -114,16 +114,10 @@ pub mod inc {
     opcode!(inc_bc, [gb] increment_r16(gb, RegisterR16::BC));
     opcode!(inc_de, [gb] increment_r16(gb, RegisterR16::DE));
     opcode!(inc_hl, [gb] increment_r16(gb, RegisterR16::HL));
+    opcode!(inc_sp, [gb] increment_r16(gb, RegisterR16::SP));
 
     // INC (r16)
     opcode!(inc_hlptr, [gb, ctx] increment_r16ptr(gb, ctx, RegisterR16::HL));
-
-    // INC SP
-    opcode!(inc_sp, [gb] {
-        let sp_old = gb.cpu.get_stack_pointer();
-        let sp_new = sp_old.wrapping_add(1);
-        gb.cpu.set_stack_pointer(sp_new);
-    });
 }
 
 
@@ -171,7 +165,7 @@ pub mod dec {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16_ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -181,7 +175,7 @@ pub mod dec {
                 let address = gb.cpu.get_r16(r16_ptr);
                 let value = gb.cpu.get_intermediate_value();
                 let result  = decrement_u8v(gb, value);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             },
@@ -204,16 +198,10 @@ pub mod dec {
     opcode!(dec_bc, [gb] decrement_r16(gb, RegisterR16::BC));    
     opcode!(dec_de, [gb] decrement_r16(gb, RegisterR16::DE));    
     opcode!(dec_hl, [gb] decrement_r16(gb, RegisterR16::HL));
+    opcode!(dec_sp, [gb] decrement_r16(gb, RegisterR16::SP));
 
     // DEC (r16)
     opcode!(dec_hlptr, [gb, ctx] decrement_r16ptr(gb, ctx, RegisterR16::HL));
-
-    // DEC SP
-    opcode!(dec_sp, [gb] {
-        let sp_old = gb.cpu.get_stack_pointer();
-        let sp_new = sp_old.wrapping_sub(1);
-        gb.cpu.set_stack_pointer(sp_new);
-    });
 }
 
 
@@ -254,7 +242,7 @@ pub mod add {
     /// dst <- dst + (src_ptr) + (carry flag, if add_carry)
     fn add_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16, add_carry: bool) {
         let address = gb.cpu.get_r16(src_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         add_r8_u8v(gb, dst, value, add_carry);
     }
 
@@ -305,7 +293,7 @@ pub mod add {
     opcode!(add_hl_bc, [gb] add_r16_r16(gb, RegisterR16::HL, RegisterR16::BC));
     opcode!(add_hl_de, [gb] add_r16_r16(gb, RegisterR16::HL, RegisterR16::DE));
     opcode!(add_hl_hl, [gb] add_r16_r16(gb, RegisterR16::HL, RegisterR16::HL));
-    opcode!(add_hl_sp, [gb] add_r16_u16v(gb, RegisterR16::HL, gb.cpu.get_stack_pointer()));
+    opcode!(add_hl_sp, [gb] add_r16_r16(gb, RegisterR16::HL, RegisterR16::SP));
 }
 
 
@@ -346,7 +334,7 @@ pub mod sub {
     /// dst <- dst - (src_ptr) - (carry flag, if sub_carry)
     fn sub_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16, sub_carry: bool) {
         let address = gb.cpu.get_r16(src_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         sub_r8_u8v(gb, dst, value, sub_carry);
     }
 
@@ -416,7 +404,7 @@ pub mod rl {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -426,7 +414,7 @@ pub mod rl {
                 let address = gb.cpu.get_r16(r16ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = shift_left_u8v(gb, value, op);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -557,7 +545,7 @@ pub mod rr {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -567,7 +555,7 @@ pub mod rr {
                 let address = gb.cpu.get_r16(r16ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = shift_right_u8v(gb, value, op);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -709,7 +697,7 @@ pub mod swap {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16_ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -719,7 +707,7 @@ pub mod swap {
                 let address = gb.cpu.get_r16(r16_ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = swap_nibbles_u8v(gb, value);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -767,7 +755,7 @@ pub mod set_bit {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16_ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -777,7 +765,7 @@ pub mod set_bit {
                 let address = gb.cpu.get_r16(r16_ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = set_bit_u8v(gb, value, bit);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -887,7 +875,7 @@ pub mod res_bit {
         match ctx.get_stage() {
             0 => {
                 let address = gb.cpu.get_r16(r16_ptr);
-                let value   = gb.get_mmu().read_u8(address);
+                let value   = gb.get_mmu_mut().cpu_read_u8(address);
                 gb.cpu.set_intermediate_value(value);
 
                 OpCodeResult::StageDone(4)
@@ -897,7 +885,7 @@ pub mod res_bit {
                 let address = gb.cpu.get_r16(r16_ptr);
                 let value   = gb.cpu.get_intermediate_value();
                 let result  = res_bit_u8v(gb, value, bit);
-                gb.get_mmu_mut().write_u8(address, result);
+                gb.get_mmu_mut().cpu_write_u8(address, result);
 
                 OpCodeResult::Done
             }
@@ -1006,7 +994,7 @@ pub mod chk_bit {
     /// Set the Zero flag, if the bit was 0.
     fn check_bit_r16ptr(gb: &mut GameBoy, r16_ptr: RegisterR16, bit: u8) {
         let address = gb.cpu.get_r16(r16_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         check_bit_u8v(gb, value, bit);
     }
 
@@ -1120,7 +1108,7 @@ pub mod cp {
     fn cp_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16) {
         let value1  = gb.cpu.get_r8(dst);
         let address = gb.cpu.get_r16(src_ptr);
-        let value2  = gb.get_mmu().read_u8(address);
+        let value2  = gb.get_mmu_mut().cpu_read_u8(address);
         cp_u8v_u8v(gb, value1, value2);
     }
 
@@ -1171,7 +1159,7 @@ pub mod and {
     /// dst <- dst & (src_ptr)
     fn and_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16) {
         let address = gb.cpu.get_r16(src_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         and_r8_u8v(gb, dst, value);
     }
 
@@ -1222,7 +1210,7 @@ pub mod or {
     /// dst <- dst | (src_ptr)
     fn or_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16) {
         let address = gb.cpu.get_r16(src_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         or_r8_u8v(gb, dst, value);
     }
 
@@ -1266,7 +1254,7 @@ pub mod xor {
     /// dst <- dst ^ (src_ptr)
     fn xor_r8_r16ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR16) {
         let address = gb.cpu.get_r16(src_ptr);
-        let value   = gb.get_mmu().read_u8(address);
+        let value   = gb.get_mmu_mut().cpu_read_u8(address);
         xor_r8_u8v(gb, dst, value);
     }
 
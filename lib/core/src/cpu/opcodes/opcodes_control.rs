@@ -16,17 +16,25 @@
  */
 
 use crate::cpu::cpu::CpuFlag;
-use crate::gameboy::GameBoy;
+use crate::gameboy::{AccuracyLevel, GameBoy};
 use crate::cpu::opcode::{opcode, OpCodeContext};
 use crate::utils::signed_overflow_add_u16;
 
 opcode!(nop, []);
 
-opcode!(stop, [] {
+opcode!(stop, [gb] {
+    // on GameBoy Color, STOP performs a speed switch, if requested via KEY1 before
+    if gb.get_peripherals().mem.is_speed_switch_armed() {
+        gb.perform_speed_switch();
+    }
+    else {
+        gb.enter_stop_mode();
+    }
 });
 
 opcode!(halt, [gb] {
-    gb.cpu.enter_halt_mode();
+    let halt_bug = gb.get_config().accuracy >= AccuracyLevel::Accurate;
+    gb.cpu.enter_halt_mode(halt_bug);
 });
 
 opcode!(enable_interrupts, [gb, ctx] {
@@ -35,22 +35,22 @@ fn ld_r8_u8(gb: &mut GameBoy, dst: RegisterR8) {
 /// Loads the content of a 8bit register into the device memory.
 fn ld_addr_r8(gb: &mut GameBoy, dst_address: u16, src: RegisterR8) {
     let value = gb.cpu.get_r8(src);
-    gb.get_mmu_mut().write_u8(dst_address, value);
+    gb.get_mmu_mut().cpu_write_u8(dst_address, value);
 }
 
 /// Writes a 8bit value to a given address in the device memory.
 fn ld_addr_u8(gb: &mut GameBoy, dst_address: u16, value: u8) {
-    gb.get_mmu_mut().write_u8(dst_address, value);
+    gb.get_mmu_mut().cpu_write_u8(dst_address, value);
 }
 
 /// Writes a 8bit value to a given address in the device memory.
 fn ld_addr_u16(gb: &mut GameBoy, dst_address: u16, value: u16) {
-    gb.get_mmu_mut().write_u16(dst_address, value);
+    gb.get_mmu_mut().cpu_write_u16(dst_address, value);
 }
 
 /// Loads the value on a given address into a 8bit register.
 fn ld_r8_addr(gb: &mut GameBoy, dst: RegisterR8, src_address: u16) {
-    let value = gb.get_mmu().read_u8(src_address);
+    let value = gb.get_mmu_mut().cpu_read_u8(src_address);
     gb.cpu.set_r8(dst, value);
 }
 
@@ -105,14 +105,14 @@ fn ldh_u8_r8(gb: &mut GameBoy, src: RegisterR8) {
     let value     = gb.cpu.get_r8(src);
     let address_h = gb.cpu.fetch_u8();
     let address   = 0xff00 | (address_h as u16);
-    gb.get_mmu_mut().write_u8(address, value);
+    gb.get_mmu_mut().cpu_write_u8(address, value);
 }
 
 /// Loads a value from the device memory at the address (0xff00 + u8) into a 8bit register.
 fn ldh_r8_u8(gb: &mut GameBoy, dst: RegisterR8) {
     let address_h = gb.cpu.fetch_u8();
     let address   = 0xff00 | (address_h as u16);
-    let value     = gb.get_mmu().read_u8(address);
+    let value     = gb.get_mmu_mut().cpu_read_u8(address);
     gb.cpu.set_r8(dst, value);
 }
 
@@ -121,14 +121,14 @@ fn ldh_r8ptr_r8(gb: &mut GameBoy, dst_ptr: RegisterR8, src: RegisterR8) {
     let address_h = gb.cpu.get_r8(dst_ptr);
     let address   = 0xff00 | (address_h as u16);
     let value     = gb.cpu.get_r8(src);
-    gb.get_mmu_mut().write_u8(address, value);
+    gb.get_mmu_mut().cpu_write_u8(address, value);
 }
 
 /// Loads a value from the device memory at the address (0xff00 + r8) into a 8bit register.
 fn ldh_r8_r8ptr(gb: &mut GameBoy, dst: RegisterR8, src_ptr: RegisterR8) {
     let address_h = gb.cpu.get_r8(src_ptr);
     let address   = 0xff00 | (address_h as u16);
-    let value     = gb.get_mmu().read_u8(address);
+    let value     = gb.get_mmu_mut().cpu_read_u8(address);
     gb.cpu.set_r8(dst, value);
 }
 
@@ -144,14 +144,6 @@ fn pop_r16(gb: &mut GameBoy, r16: RegisterR16) {
     gb.cpu.set_r16(r16, value);
 }
 
-/// Pops a 16bit value from the stack into a 16bit register.
-/// Applies a bitmask to the value before writing into the register.
-fn pop_r16_mask(gb: &mut GameBoy, r16: RegisterR16, mask: u16) {
-    let value = gb.cpu.pop_u16();
-    let value_masked = value & mask;
-    gb.cpu.set_r16(r16, value_masked);
-}
-
 
 // LD r8, r8
 opcode!(ld_a_a, [gb] ld_r8_r8(gb, RegisterR8::A, RegisterR8::A));
@@ -318,15 +310,12 @@ opcode!(ld_hl_sp_i8, [gb] {
 });
 
 // LD SP, u16
-opcode!(ld_sp_u16, [gb] {
-    let value = gb.cpu.fetch_u16();
-    gb.cpu.set_stack_pointer(value);
-});
+opcode!(ld_sp_u16, [gb] ld_r16_u16(gb, RegisterR16::SP));
 
 // LD SP, HL
 opcode!(ld_sp_hl, [gb] {
     let value = gb.cpu.get_r16(RegisterR16::HL);
-    gb.cpu.set_stack_pointer(value);
+    gb.cpu.set_r16(RegisterR16::SP, value);
 });
 
 // PUSH r16
@@ -336,7 +325,7 @@ opcode!(push_de, [gb] push_r16(gb, RegisterR16::DE));
 opcode!(push_hl, [gb] push_r16(gb, RegisterR16::HL));
 
 // POP r16
-opcode!(pop_af, [gb] pop_r16_mask(gb, RegisterR16::AF, 0xfff0));
+opcode!(pop_af, [gb] pop_r16(gb, RegisterR16::AF));
 opcode!(pop_bc, [gb] pop_r16(gb, RegisterR16::BC));
 opcode!(pop_de, [gb] pop_r16(gb, RegisterR16::DE));
 opcode!(pop_hl, [gb] pop_r16(gb, RegisterR16::HL));
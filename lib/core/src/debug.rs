@@ -15,8 +15,14 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::cell::Cell;
+use std::collections::VecDeque;
+
 use flagset::{flags, FlagSet};
 
+use crate::cpu::interrupts::Interrupt;
+use crate::gameboy::Clock;
+
 flags! {
     /// An enumeration events that may occur during updating the emulator.
     /// This does not represent actual signals sent from a GameBoy's
@@ -28,9 +34,463 @@ flags! {
 
         /// The PPU completed rendering a frame.
         PpuFrameCompleted   = 0b_0000_0010,
+
+        /// The CPU reached the address of a breakpoint.
+        BreakpointReached   = 0b_0000_0100,
+
+        /// The CPU tried to execute an invalid opcode, which locks up the CPU.
+        InvalidOpcode       = 0b_0000_1000,
+
+        /// The stack pointer left the bounds set via [crate::cpu::cpu::Cpu::set_stack_bounds].
+        StackViolation      = 0b_0001_0000,
     }
 }
 
 
 /// A set of events occurred during updating the emulator.
 pub type DebugEvents = FlagSet<DebugEvent>;
+
+
+/// A breakpoint, which causes the emulator to report a [DebugEvent::BreakpointReached]
+/// event when the CPU reaches its address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// The address of the instruction to stop at.
+    pub address: u16,
+
+    /// When set, the breakpoint will be removed after it was reached once.
+    pub one_shot: bool,
+
+    /// Disabled breakpoints are kept in the list, but will not stop the emulator.
+    pub enabled: bool,
+}
+
+
+/// A list of breakpoints, which will be checked each time the CPU
+/// is about to execute the next instruction.
+#[derive(Default, Clone)]
+pub struct Breakpoints {
+    entries: Vec<Breakpoint>,
+}
+
+
+impl Breakpoints {
+    /// Creates an empty list of breakpoints.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+
+    /// Adds a breakpoint on the given address.
+    /// Any breakpoint on the same address will be replaced.
+    pub fn add(&mut self, address: u16) {
+        self.insert(Breakpoint { address, one_shot: false, enabled: true });
+    }
+
+
+    /// Adds a breakpoint on the given address, which will be removed
+    /// after it was reached once. This can be used to implement
+    /// functions like 'run to address'.
+    /// A permanent breakpoint on the same address will not be replaced.
+    pub fn add_one_shot(&mut self, address: u16) {
+        if !self.contains(address) {
+            self.insert(Breakpoint { address, one_shot: true, enabled: true });
+        }
+    }
+
+
+    /// Removes the breakpoint from the given address, if any.
+    pub fn remove(&mut self, address: u16) {
+        self.entries.retain(|breakpoint| breakpoint.address != address);
+    }
+
+
+    /// Enables or disables the breakpoint on the given address, if any.
+    pub fn set_enabled(&mut self, address: u16, enabled: bool) {
+        if let Some(breakpoint) = self.entries.iter_mut().find(|breakpoint| breakpoint.address == address) {
+            breakpoint.enabled = enabled;
+        }
+    }
+
+
+    /// Removes all breakpoints.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+
+    /// Removes all one-shot breakpoints, which were not reached yet.
+    pub fn clear_one_shots(&mut self) {
+        self.entries.retain(|breakpoint| !breakpoint.one_shot);
+    }
+
+
+    /// Checks whether there's a breakpoint on the given address.
+    pub fn contains(&self, address: u16) -> bool {
+        self.get(address).is_some()
+    }
+
+
+    /// Get the breakpoint on the given address, if any.
+    pub fn get(&self, address: u16) -> Option<&Breakpoint> {
+        self.entries.iter().find(|breakpoint| breakpoint.address == address)
+    }
+
+
+    /// Checks whether the list of breakpoints is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+
+    /// Get an iterator over all breakpoints.
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.entries.iter()
+    }
+
+
+    /// Checks whether a breakpoint was reached on the given address.
+    /// Disabled breakpoints will be ignored.
+    /// If a one-shot breakpoint was reached, it will be removed.
+    pub fn check(&mut self, address: u16) -> bool {
+        match self.get(address).copied() {
+            Some(breakpoint) if breakpoint.enabled => {
+                if breakpoint.one_shot {
+                    self.remove(address);
+                }
+
+                true
+            }
+
+            _ => false,
+        }
+    }
+
+
+    /// Inserts a breakpoint and replaces any other breakpoint on the same address.
+    fn insert(&mut self, breakpoint: Breakpoint) {
+        self.remove(breakpoint.address);
+        self.entries.push(breakpoint);
+    }
+}
+
+
+/// The number of opcodes counted by [InstructionCounter],
+/// 256 regular opcodes followed by 256 0xCB prefixed opcodes.
+pub const INSTRUCTION_COUNTER_SLOTS: usize = 512;
+
+
+/// Counts how often each opcode was executed, which helps to find hot spots when profiling a ROM.
+#[derive(Clone)]
+pub struct InstructionCounter {
+    /// Whether counting is enabled.
+    enabled: bool,
+
+    /// The number of executions of each opcode.
+    counts: Box<[u64; INSTRUCTION_COUNTER_SLOTS]>,
+}
+
+
+impl InstructionCounter {
+    /// Creates a new disabled counter.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            counts:  Box::new([0; INSTRUCTION_COUNTER_SLOTS]),
+        }
+    }
+
+
+    /// Enables or disables counting instructions.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+
+    /// Checks whether counting instructions is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+
+    /// Get the index of an opcode within the list of counters.
+    /// Regular opcodes are stored in 0x000-0x0ff, 0xCB prefixed opcodes in 0x100-0x1ff.
+    /// The ID of prefixed opcodes contains the prefix in its lower byte, so even
+    /// the ID of 0xCB 0x00 can be distinguished from any regular opcode.
+    pub fn get_index_of(opcode_id: u16) -> usize {
+        if (opcode_id & 0xff) == 0xcb {
+            0x100 + (opcode_id >> 8) as usize
+        }
+        else {
+            opcode_id as usize
+        }
+    }
+
+
+    /// Counts the execution of an opcode, if counting is enabled.
+    pub fn count(&mut self, opcode_id: u16) {
+        if self.enabled {
+            self.counts[Self::get_index_of(opcode_id)] += 1;
+        }
+    }
+
+
+    /// Get the number of executions of each opcode.
+    pub fn get_counts(&self) -> &[u64; INSTRUCTION_COUNTER_SLOTS] {
+        &self.counts
+    }
+
+
+    /// Resets all counters to zero.
+    pub fn reset(&mut self) {
+        self.counts.fill(0);
+    }
+}
+
+
+impl Default for InstructionCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// The maximum number of events stored by [InterruptHistory].
+pub const INTERRUPT_HISTORY_SIZE: usize = 200;
+
+
+/// A single interrupt recorded by [InterruptHistory].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InterruptEvent {
+    /// The interrupt being requested.
+    pub interrupt: Interrupt,
+
+    /// The total number of cycles processed by the emulator when the interrupt was requested.
+    pub cycle: Clock,
+
+    /// The line the PPU was processing when the interrupt was requested.
+    pub ly: u8,
+}
+
+
+/// Records the most recent interrupts requested by any component,
+/// which helps to find bugs related to the order and timing of interrupts.
+#[derive(Clone, Default)]
+pub struct InterruptHistory {
+    /// Whether recording is enabled.
+    enabled: bool,
+
+    /// The recorded events, starting with the oldest one.
+    events: VecDeque<InterruptEvent>,
+}
+
+
+impl InterruptHistory {
+    /// Creates a new disabled history.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            events:  VecDeque::with_capacity(INTERRUPT_HISTORY_SIZE),
+        }
+    }
+
+
+    /// Enables or disables recording interrupts.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+
+    /// Checks whether recording interrupts is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+
+    /// Records an interrupt, if recording is enabled.
+    /// When the history is full, the oldest event will be dropped.
+    pub fn record(&mut self, event: InterruptEvent) {
+        if self.enabled {
+            if self.events.len() >= INTERRUPT_HISTORY_SIZE {
+                self.events.pop_front();
+            }
+
+            self.events.push_back(event);
+        }
+    }
+
+
+    /// Get an iterator over all recorded events, starting with the oldest one.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InterruptEvent> {
+        self.events.iter()
+    }
+
+
+    /// Get the number of recorded events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+
+    /// Checks whether no events were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+
+    /// Removes all recorded events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+
+/// The maximum number of transfers stored by [DmaHistory].
+pub const DMA_HISTORY_SIZE: usize = 10;
+
+
+/// A single OAM DMA transfer recorded by [DmaHistory].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DmaTransferEvent {
+    /// The address the data was copied from.
+    pub source_address: u16,
+
+    /// The total number of cycles processed by the emulator when the transfer was completed.
+    pub cycle: Clock,
+}
+
+
+/// Records the most recent OAM DMA transfers completed,
+/// which helps to verify the source and timing of transfers.
+#[derive(Clone, Default)]
+pub struct DmaHistory {
+    /// Whether recording is enabled.
+    enabled: bool,
+
+    /// The recorded transfers, starting with the oldest one.
+    events: VecDeque<DmaTransferEvent>,
+}
+
+
+impl DmaHistory {
+    /// Creates a new disabled history.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            events:  VecDeque::with_capacity(DMA_HISTORY_SIZE),
+        }
+    }
+
+
+    /// Enables or disables recording DMA transfers.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+
+    /// Checks whether recording DMA transfers is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+
+    /// Records a completed transfer, if recording is enabled.
+    /// When the history is full, the oldest transfer will be dropped.
+    pub fn record(&mut self, event: DmaTransferEvent) {
+        if self.enabled {
+            if self.events.len() >= DMA_HISTORY_SIZE {
+                self.events.pop_front();
+            }
+
+            self.events.push_back(event);
+        }
+    }
+
+
+    /// Get an iterator over all recorded transfers, starting with the oldest one.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &DmaTransferEvent> {
+        self.events.iter()
+    }
+
+
+    /// Get the number of recorded transfers.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+
+    /// Checks whether no transfers were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+
+    /// Removes all recorded transfers.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+
+/// The number of 256 byte pages counted by [MemoryAccessProfiler].
+pub const MEMORY_ACCESS_PROFILE_PAGES: usize = 256;
+
+
+/// Counts reads and writes on each 256 byte page of the address space,
+/// which helps to find hot loops polling IO registers when profiling a ROM.
+/// Since reading memory does not require mutable access, the counters use interior mutability.
+#[derive(Clone)]
+pub struct MemoryAccessProfiler {
+    /// Whether counting is enabled.
+    enabled: bool,
+
+    /// The number of reads and writes on each page.
+    counts: Box<[Cell<u32>; MEMORY_ACCESS_PROFILE_PAGES]>,
+}
+
+
+impl MemoryAccessProfiler {
+    /// Creates a new disabled profiler.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            counts:  Box::new(std::array::from_fn(|_| Cell::new(0))),
+        }
+    }
+
+
+    /// Enables or disables counting memory accesses.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+
+    /// Checks whether counting memory accesses is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+
+    /// Counts an access on the given address, if counting is enabled.
+    pub fn count(&self, address: u16) {
+        if self.enabled {
+            let counter = &self.counts[(address >> 8) as usize];
+            counter.set(counter.get().saturating_add(1));
+        }
+    }
+
+
+    /// Get the number of accesses on each page and resets all counters to zero.
+    pub fn take_counts(&self) -> [u32; MEMORY_ACCESS_PROFILE_PAGES] {
+        std::array::from_fn(|page| self.counts[page].take())
+    }
+}
+
+
+impl Default for MemoryAccessProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
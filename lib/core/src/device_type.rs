@@ -134,6 +134,12 @@ impl DeviceType {
     }
 
 
+    /// Checks whether this device type is a SuperGameBoy.
+    pub fn is_super_gameboy(&self) -> bool {
+        matches!(self, DeviceType::SuperGameBoy | DeviceType::SuperGameBoy2)
+    }
+
+
     /// Get a string representation of the device type.
     pub fn to_string(&self) -> &'static str {
         match self {
@@ -0,0 +1,90 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::time::Instant;
+
+use crate::cpu::cpu::CPU_CLOCK_SPEED;
+use crate::gameboy::Clock;
+
+
+/// Timing statistics about the frames processed by the emulator,
+/// since it was created or the statistics were reset.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameTimingStats {
+    /// The time in milliseconds which passed on the emulated device.
+    pub emulated_ms: f64,
+
+    /// The wall clock time in milliseconds which passed while running the emulator.
+    pub real_ms: f64,
+
+    /// The number of frames processed.
+    pub frame_count: u64,
+
+    /// The average number of frames per second in wall clock time.
+    pub average_fps: f64,
+}
+
+
+/// Collects the timing statistics while running frames.
+#[derive(Default)]
+pub(crate) struct FrameTimingTracker {
+    /// The statistics collected so far.
+    stats: FrameTimingStats,
+
+    /// The wall clock time and total cycles count when the first frame started after a reset.
+    start: Option<(Instant, Clock)>,
+}
+
+
+impl FrameTimingTracker {
+    /// Get the statistics collected so far.
+    pub fn get_stats(&self) -> &FrameTimingStats {
+        &self.stats
+    }
+
+
+    /// Resets all counters, so the next frame starts a new measurement.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+
+    /// To be called before running a frame.
+    pub fn on_frame_started(&mut self, total_cycles: Clock) {
+        if self.start.is_none() {
+            self.start = Some((Instant::now(), total_cycles));
+        }
+    }
+
+
+    /// To be called after a frame was completed to update the statistics.
+    pub fn on_frame_completed(&mut self, total_cycles: Clock) {
+        if let Some((start_time, start_cycles)) = self.start {
+            let emulated_cycles = total_cycles.saturating_sub(start_cycles);
+
+            self.stats.frame_count += 1;
+            self.stats.emulated_ms  = (emulated_cycles as f64) / (CPU_CLOCK_SPEED as f64) * 1000.0;
+            self.stats.real_ms      = start_time.elapsed().as_secs_f64() * 1000.0;
+
+            self.stats.average_fps = if self.stats.real_ms > 0.0 {
+                (self.stats.frame_count as f64) * 1000.0 / self.stats.real_ms
+            }
+            else {
+                0.0
+            };
+        }
+    }
+}
@@ -17,19 +17,26 @@
 
 use crate::apu::apu::Apu;
 use crate::boot_rom::BootRom;
-use crate::cartridge::{Cartridge, GameBoyColorSupport, LicenseeCode};
+use crate::cartridge::{Cartridge, CartridgeHeaderError, GameBoyColorSupport, LicenseeCode};
+use crate::cheats::CheatCodeManager;
 use crate::cpu::cpu::{Cpu, CpuFlag, RegisterR8, CPU_CLOCK_SPEED};
-use crate::cpu::interrupts::InterruptRegisters;
+use crate::cpu::interrupts::{Interrupt, InterruptRegisters};
 use crate::cpu::opcode::{OpCodeContext, OpCodeResult};
-use crate::debug::{DebugEvent, DebugEvents};
+use crate::debug::{Breakpoints, DebugEvent, DebugEvents, DmaHistory, DmaTransferEvent, InstructionCounter, InterruptEvent, InterruptHistory, INSTRUCTION_COUNTER_SLOTS};
+use crate::frame_stats::{FrameTimingStats, FrameTimingTracker};
 // re-export some types
 pub use crate::device_type::{DeviceType, EmulationType};
+use crate::infrared::InfraredPort;
 use crate::input::Input;
-use crate::mmu::memory::Memory;
+use crate::mmu::locations::{MEMORY_LOCATION_APU_NR52, MEMORY_LOCATION_LCD_CONTROL, MEMORY_LOCATION_RP};
+use crate::mmu::memory::{DmaTransferState, Memory};
+use crate::mmu::memory_data::MemoryData;
 use crate::mmu::memory_bus::{MemoryBusConnection, MemoryBusSignals};
 use crate::mmu::mmu::Mmu;
-use crate::ppu::ppu::{Ppu, CPU_CYCLES_PER_FRAME};
+use crate::ppu::frame_log::FrameLogEventKind;
+use crate::ppu::ppu::{Layer, Ppu, CPU_CYCLES_PER_FRAME};
 use crate::serial::SerialPort;
+use crate::sgb::SgbState;
 use crate::timer::Timer;
 use crate::utils::{carrying_add_u8, get_high};
 
@@ -50,16 +57,94 @@ pub struct DeviceConfig {
 
     /// Flag if opcodes should be printed
     pub print_opcodes: bool,
+
+    /// The level of accuracy the device will be emulated with.
+    pub accuracy: AccuracyLevel,
+}
+
+
+/// Selects how accurately the hardware will be emulated, which allows to trade
+/// accuracy for speed on devices with limited resources.
+/// The levels are ordered, so each level includes all behaviors of the levels below.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccuracyLevel {
+    /// Skips hardware quirks being expensive to emulate and rarely relied on by games,
+    /// like the variable duration of the pixel transfer or the HALT bug.
+    Fast,
+
+    /// Emulates all common hardware quirks.
+    #[default]
+    Accurate,
+
+    /// Additionally emulates obscure hardware behaviors, which are usually
+    /// only checked by test ROMs.
+    Strict,
 }
 
 
 /// A factory class to construct a GameBoy device object.
 /// Usually created via GameBoy::build()
 pub struct Builder {
-    boot_rom:      Option<BootRom>,
-    cartridge:     Option<Cartridge>,
-    device_type:   Option<DeviceType>,
-    print_opcodes: bool,
+    boot_rom:             Option<BootRom>,
+    cartridge:            Option<Cartridge>,
+    device_type:          Option<DeviceType>,
+    print_opcodes:        bool,
+    allow_invalid_header: bool,
+    accuracy:             AccuracyLevel,
+}
+
+
+/// Errors which may occur when building a GameBoy device via [Builder].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuilderErrorCode {
+    /// The cartridge header is invalid, so the cartridge would not boot on the original hardware.
+    InvalidCartridgeHeader(CartridgeHeaderError),
+
+    /// The cartridge requires GameBoy Color features,
+    /// but the selected device does not support them.
+    GameBoyColorNotSupported,
+
+    /// A GameBoy Color boot ROM was provided for a device without GameBoy Color support.
+    GameBoyColorBootRomNotSupported,
+
+    /// The memory bank controller of the cartridge is not supported by the emulator.
+    /// Contains the cartridge type from the cartridge header.
+    UnsupportedMapper(u8),
+
+    /// The device could not be created.
+    DeviceCreationFailed(String),
+}
+
+
+/// Returned by [GameBoy::run_until_serial_output] when the expected output
+/// was not received in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeoutError {
+    /// The serial output received until the emulator was stopped.
+    pub output_so_far: String,
+
+    /// The number of cycles processed until the emulator was stopped.
+    pub cycles_elapsed: Clock,
+
+    /// If the emulator was stopped by the CPU reaching an invalid opcode,
+    /// this contains the address of this opcode.
+    pub invalid_opcode: Option<u16>,
+}
+
+
+/// The kind of reset to be performed via [GameBoy::reset].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResetKind {
+    /// Like pulling the reset line of the device: the CPU, the IO registers and the
+    /// memory bank controller return to their initial state and the boot sequence starts
+    /// over, while the content of WRAM, VRAM and the cartridge RAM is kept.
+    Soft,
+
+    /// Like switching the device off and on again with a fresh cartridge: all components
+    /// are reinitialized, the boot sequence starts over and the cartridge RAM is cleared,
+    /// even if buffered by a battery.
+    Hard,
 }
 
 
@@ -71,6 +156,54 @@ pub struct GameBoy {
     pub cpu: Cpu,
 
     total_cycles: Clock,
+
+    /// The number of cycles of the system clock processed, which does not run
+    /// faster while the CPU is in double speed mode.
+    total_system_cycles: Clock,
+
+    /// The number of instructions executed for profiling,
+    /// which is not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    total_instructions: u64,
+
+    /// Breakpoints set by a debugger. They're not part of the
+    /// device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    breakpoints: Breakpoints,
+
+    /// Timing statistics of the frames processed, which are
+    /// not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame_stats: FrameTimingTracker,
+
+    /// Counts the executions of each opcode for profiling,
+    /// which is not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    instruction_counter: InstructionCounter,
+
+    /// Records the interrupts requested for debugging,
+    /// which is not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    interrupt_history: InterruptHistory,
+
+    /// Records the OAM DMA transfers completed for debugging,
+    /// which is not part of the device's state and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dma_history: DmaHistory,
+
+    /// The number of frames to be emulated with each call of [GameBoy::run_frame].
+    /// This is a setting of the frontend and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_speed_multiplier"))]
+    speed_multiplier: f64,
+
+    /// The number of cycles remaining to be run while a speed multiplier other than 1.0 is set.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cycles_budget: f64,
+
+    /// A copy of the boot ROM, if any, to run the boot sequence again on a reset.
+    /// This is provided by the frontend and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    boot_rom: Option<BootRom>,
 }
 
 
@@ -83,7 +216,10 @@ pub struct Peripherals {
     pub timer:      Box<Timer>,
     pub input:      Box<Input>,
     pub serial:     Box<SerialPort>,
+    pub infrared:   Box<InfraredPort>,
     pub interrupts: Box<InterruptRegisters>,
+    pub cheats:     Box<CheatCodeManager>,
+    pub sgb:        Option<Box<SgbState>>,
 }
 
 
@@ -93,11 +229,72 @@ pub struct EmulatorUpdateResults {
     /// The number of cycles being processed.
     pub cycles: Clock,
 
+    /// The number of cycles processed, broken down by what the CPU was doing.
+    pub cycle_breakdown: CycleBreakdown,
+
     /// Any debug events occurred during updating the emulator.
     pub events: DebugEvents,
 }
 
 
+/// The number of cycles processed, broken down by what the CPU was doing.
+/// The sum of all categories equals the total number of cycles processed.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CycleBreakdown {
+    /// Cycles spent executing instructions.
+    pub cpu: Clock,
+
+    /// Cycles spent dispatching interrupts.
+    pub interrupt_dispatch: Clock,
+
+    /// Cycles the CPU was suspended by HALT or STOP, or locked up by an invalid opcode.
+    pub halt: Clock,
+}
+
+
+impl Peripherals {
+    /// Creates a new set of peripherals in their initial state.
+    pub fn new(device_config: DeviceConfig) -> Self {
+        Self {
+            apu:        Box::new(Apu::new(device_config)),
+            ppu:        Box::new(Ppu::new(device_config)),
+            mem:        Box::new(Memory::new(device_config)),
+            timer:      Box::new(Timer::new()),
+            input:      Box::new(Input::new()),
+            serial:     Box::new(SerialPort::new(device_config)),
+            infrared:   Box::new(InfraredPort::new(device_config)),
+            interrupts: Box::new(InterruptRegisters::new()),
+            cheats:     Box::new(CheatCodeManager::new()),
+            sgb:        device_config.device.is_super_gameboy().then(|| Box::new(SgbState::new())),
+        }
+    }
+
+    /// Moves the cartridge and anything configured by the frontend,
+    /// like open audio channels or cheats, from another set of peripherals.
+    fn take_settings_from(&mut self, other: &mut Peripherals) {
+        self.apu.take_settings_from(&mut other.apu);
+
+        if let Some(cartridge) = other.mem.remove_cartridge() {
+            self.mem.set_cartridge(cartridge);
+        }
+
+        self.ppu.set_dmg_display_palette(*other.ppu.get_dmg_display_palette());
+        self.ppu.set_color_correction(other.ppu.get_color_correction());
+        self.ppu.set_frame_log_enabled(other.ppu.is_frame_log_enabled());
+        self.ppu.set_render_enabled(other.ppu.is_render_enabled());
+        self.ppu.set_sprite_limit(other.ppu.get_sprite_limit());
+
+        for layer in Layer::ALL {
+            self.ppu.set_layer_visible(layer, other.ppu.is_layer_visible(layer));
+        }
+        self.serial.enable_output_queue(other.serial.is_output_queue_enabled());
+        self.serial.take_device_from(&mut other.serial);
+
+        std::mem::swap(&mut self.cheats, &mut other.cheats);
+    }
+}
+
+
 impl DeviceConfig {
     /// Checks whether the current device is running with GameBoyColor support enabled.
     /// The running device needs to be a GBC or GBA *and* running a cartridge
@@ -115,10 +312,12 @@ impl Builder {
     /// Creates a new empty GameBoy builder
     pub fn new() -> Self {
         Self {
-            boot_rom:      None,
-            cartridge:     None,
-            device_type:   None,
-            print_opcodes: false,
+            boot_rom:             None,
+            cartridge:            None,
+            device_type:          None,
+            print_opcodes:        false,
+            allow_invalid_header: false,
+            accuracy:             AccuracyLevel::default(),
         }
     }
 
@@ -143,6 +342,18 @@ impl Builder {
         self.print_opcodes = print;
     }
 
+    /// Configures whether to accept cartridges with an invalid header, like a missing
+    /// Nintendo logo or a wrong header checksum. Useful for test ROMs, which may not
+    /// contain a valid header.
+    pub fn set_allow_invalid_header(&mut self, allow: bool) {
+        self.allow_invalid_header = allow;
+    }
+
+    /// Set the level of accuracy the device will be emulated with.
+    pub fn set_accuracy(&mut self, accuracy: AccuracyLevel) {
+        self.accuracy = accuracy;
+    }
+
     /// Get the preferred device type, which is either specified explicitly
     /// or selected by the cartridge properties.
     pub fn select_preferred_device_type(&self) -> DeviceType {
@@ -166,40 +377,50 @@ impl Builder {
 
     /// Check the emulation type based on the selected device and GameBoyColor
     /// support of the selected cartridge.
+    /// Cartridges supporting GameBoy Color features will enable them only on
+    /// devices with GameBoy Color support, but run in DMG mode on any other device.
     pub fn select_emulation_type(&self, device_type: &DeviceType) -> EmulationType {
-        match device_type {
-            DeviceType::GameBoyDmg => {}
-            _ => {
-                if let Some(cartridge) = &self.cartridge {
-                    if cartridge.supports_cgb() {
-                        return EmulationType::GBC;
-                    }
-                }
-            }
-        }
-
-        EmulationType::DMG
+        select_emulation_type_for(device_type, self.cartridge.as_ref())
     }
 
     /// Build the GameBoy device emulator based on the properties specified with this builder.
-    pub fn finish(mut self) -> Result<GameBoy, String> {
+    /// Fails if the cartridge header is invalid, unless invalid headers are allowed.
+    /// Fails if the cartridge requires GameBoy Color features or a GameBoy Color boot ROM
+    /// was provided for a device without GameBoy Color support.
+    pub fn finish(mut self) -> Result<GameBoy, BuilderErrorCode> {
         // select the preferred device type based on the current config and cartridge
         let device_type    = self.select_preferred_device_type();
         let emulation_type = self.select_emulation_type(&device_type);
 
+        // refuse to run cartridges, which cannot be run on the selected device
+        if let Some(cartridge) = &self.cartridge {
+            check_cartridge_compatibility(cartridge, &device_type, self.allow_invalid_header)?;
+        }
+
+        // a GameBoy Color boot ROM cannot run on classic devices
+        if let Some(boot_rom) = &self.boot_rom {
+            if boot_rom.is_gbc_boot_rom() && !device_type.has_gbc_support() {
+                return Err(BuilderErrorCode::GameBoyColorBootRomNotSupported);
+            }
+        }
+
         // setup device config based on the current configuration
         let device_config = DeviceConfig {
             device: device_type,
             emulation: emulation_type,
-            print_opcodes: self.print_opcodes
+            print_opcodes: self.print_opcodes,
+            accuracy: self.accuracy,
         };
 
         // construct the GameBoy object
-        let mut gb = GameBoy::new(device_config)?;
+        let mut gb = GameBoy::new(device_config)
+                .map_err(BuilderErrorCode::DeviceCreationFailed)?
+        ;
 
         // set boot ROM, if any
         if let Some(boot_rom) = self.boot_rom.take() {
-            gb.get_peripherals_mut().mem.set_boot_rom(boot_rom);
+            gb.get_peripherals_mut().mem.set_boot_rom(boot_rom.clone());
+            gb.boot_rom = Some(boot_rom);
         }
 
         // insert cartridge, if any
@@ -212,6 +433,52 @@ impl Builder {
 }
 
 
+/// Selects the emulation type based on the device and GameBoy Color support of a cartridge.
+/// Cartridges supporting GameBoy Color features will enable them only on devices
+/// with GameBoy Color support, but run in DMG mode on any other device.
+fn select_emulation_type_for(device_type: &DeviceType, cartridge: Option<&Cartridge>) -> EmulationType {
+    match cartridge {
+        Some(cartridge) if device_type.has_gbc_support() && cartridge.supports_cgb() => EmulationType::GBC,
+        _ => EmulationType::DMG,
+    }
+}
+
+
+/// Checks whether a cartridge can be run on the given device.
+/// Fails if the cartridge header is invalid, unless invalid headers are allowed,
+/// if the memory bank controller is not implemented yet or if the cartridge
+/// requires GameBoy Color features on a device without GameBoy Color support.
+fn check_cartridge_compatibility(
+    cartridge: &Cartridge,
+    device_type: &DeviceType,
+    allow_invalid_header: bool
+) -> Result<(), BuilderErrorCode> {
+    // refuse to run cartridges with an invalid header, ignoring any warnings
+    if !allow_invalid_header {
+        let error = cartridge.validate_header()
+            .into_iter()
+            .find(|error| !error.is_warning())
+        ;
+
+        if let Some(error) = error {
+            return Err(BuilderErrorCode::InvalidCartridgeHeader(error));
+        }
+    }
+
+    // refuse to run cartridges with a memory bank controller not implemented yet
+    if !cartridge.get_mbc().is_supported() {
+        return Err(BuilderErrorCode::UnsupportedMapper(cartridge.get_cartridge_type()));
+    }
+
+    // refuse to run GameBoy Color only cartridges on classic devices
+    if cartridge.requires_cgb() && !device_type.has_gbc_support() {
+        return Err(BuilderErrorCode::GameBoyColorNotSupported);
+    }
+
+    Ok(())
+}
+
+
 impl GameBoy {
     /// Creates a builder to build up the device.
     pub fn build() -> Builder {
@@ -226,19 +493,29 @@ impl GameBoy {
 
                 cpu: Cpu::new(
                     Mmu::new(
-                        Peripherals {
-                            apu:        Box::new(Apu::new(device_config)),
-                            ppu:        Box::new(Ppu::new(device_config)),
-                            mem:        Box::new(Memory::new(device_config)),
-                            timer:      Box::new(Timer::new()),
-                            input:      Box::new(Input::new()),
-                            serial:     Box::new(SerialPort::new()),
-                            interrupts: Box::new(InterruptRegisters::new()),
-                        }
+                        device_config,
+                        Peripherals::new(device_config)
                     )
                 ),
 
                 total_cycles: 0,
+                total_system_cycles: 0,
+                total_instructions: 0,
+
+                breakpoints: Breakpoints::new(),
+
+                frame_stats: FrameTimingTracker::default(),
+
+                instruction_counter: InstructionCounter::new(),
+
+                interrupt_history: InterruptHistory::new(),
+
+                dma_history: DmaHistory::new(),
+
+                speed_multiplier: 1.0,
+                cycles_budget:    0.0,
+
+                boot_rom: None,
             }
         )
     }
@@ -258,6 +535,117 @@ impl GameBoy {
         }
     }
 
+    /// Inserts a new cartridge while the device is running, replacing the current one.
+    /// The memory bank controller of the new cartridge starts with its initial banking
+    /// and RAM disabled, and the device continues at the entry point of the new cartridge.
+    /// The emulation type is selected for the new cartridge like [Builder::finish] does.
+    /// The cartridge will be refused, if it could not be run on the current device
+    /// or has an invalid header, unless `allow_invalid_header` is set.
+    pub fn load_cartridge(&mut self, cartridge: Cartridge, allow_invalid_header: bool) -> Result<(), BuilderErrorCode> {
+        check_cartridge_compatibility(&cartridge, &self.device_config.device, allow_invalid_header)?;
+
+        let emulation = select_emulation_type_for(&self.device_config.device, Some(&cartridge));
+
+        if emulation != self.device_config.emulation {
+            // components are set up for the emulation type when being created,
+            // so they need to be replaced, taking over the settings of the frontend
+            self.eject_cartridge();
+            self.device_config.emulation = emulation;
+
+            let mut peripherals = Peripherals::new(self.device_config);
+            peripherals.take_settings_from(self.get_peripherals_mut());
+
+            self.cpu = Cpu::new(Mmu::new(self.device_config, peripherals));
+            self.update_audio_clock_speed();
+        }
+
+        self.get_peripherals_mut().mem.set_cartridge(cartridge);
+        self.reset(ResetKind::Soft);
+
+        Ok(())
+    }
+
+    /// Resets the device, which starts over running the boot ROM, if any,
+    /// or the cartridge's entry point otherwise. See [ResetKind] for the
+    /// state being kept on each kind of reset.
+    /// Settings of the frontend, like open audio channels, breakpoints,
+    /// cheats or the display palette, are not affected.
+    /// The number of cycles and instructions processed starts over from zero.
+    pub fn reset(&mut self, kind: ResetKind) {
+        match kind {
+            ResetKind::Soft => self.reset_soft(),
+            ResetKind::Hard => self.reset_hard(),
+        }
+
+        self.total_cycles        = 0;
+        self.total_system_cycles = 0;
+        self.total_instructions  = 0;
+
+        // map the boot ROM again to run the boot sequence
+        if let Some(boot_rom) = &self.boot_rom {
+            let boot_rom = boot_rom.clone();
+            self.get_peripherals_mut().mem.set_boot_rom(boot_rom);
+        }
+
+        self.initialize();
+    }
+
+    /// Resets the CPU, the memory bank controller, the PPU, the timer, the APU,
+    /// the serial and infrared ports and the SuperGameBoy state, keeping the memory content.
+    /// The CPU returns into normal speed mode.
+    fn reset_soft(&mut self) {
+        // inserting the cartridge again resets the state of the memory bank controller
+        if let Some(cartridge) = self.get_peripherals_mut().mem.remove_cartridge() {
+            self.get_peripherals_mut().mem.set_cartridge(cartridge);
+        }
+
+        // switch off the LCD, so the PPU starts over with a new frame once being re-initialized
+        self.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+        // switching off the APU resets all channels and registers;
+        // it will be switched on again by the boot ROM or the initial register values
+        self.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+
+        // the timer starts over with its internal counter
+        *self.get_peripherals_mut().timer = Timer::new();
+
+        // abort any serial transfer and switch off the infrared LED
+        self.get_peripherals_mut().serial.reset();
+        self.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, 0x00);
+
+        // the SuperGameBoy discards any packets, transfers and the border received
+        if let Some(sgb) = &mut self.get_peripherals_mut().sgb {
+            **sgb = SgbState::new();
+        }
+
+        // the CPU starts over in normal speed
+        self.get_peripherals_mut().mem.reset_speed();
+
+        self.cpu.disable_interrupts();
+        self.cpu.leave_halt_state();
+    }
+
+    /// Replaces the CPU and all peripherals with new instances, moving the cartridge
+    /// and the settings of the frontend into the new instances.
+    /// The cartridge RAM is cleared, including battery buffered RAM.
+    fn reset_hard(&mut self) {
+        let mut peripherals = Peripherals::new(self.device_config);
+        peripherals.take_settings_from(self.get_peripherals_mut());
+
+        if let Some(cartridge) = peripherals.mem.get_cartridge_mut() {
+            cartridge.get_ram_mut().as_slice_mut().fill(0x00);
+        }
+
+        self.cpu = Cpu::new(Mmu::new(self.device_config, peripherals));
+        self.update_audio_clock_speed();
+    }
+
+    /// Removes the current cartridge, if any, and returns it to the caller.
+    /// Without a cartridge, reading from the cartridge address space returns 0xff.
+    pub fn eject_cartridge(&mut self) -> Option<Cartridge> {
+        self.get_peripherals_mut().mem.remove_cartridge()
+    }
+
     /// setup values like expected after the boot rom was executed on the original GameBoy.
     fn setup_initial_values(&mut self) {
         let pc = 0x0100;
@@ -380,6 +768,24 @@ impl GameBoy {
                 EmulationType::GBC => 0xfe,
             };
 
+            // infrared port with LED and data reading disabled on GBC
+            let rp = match self.device_config.emulation {
+                EmulationType::DMG => 0xff,
+                EmulationType::GBC => 0x3e,
+            };
+
+            // GBC initially selects the internal fast clock of the serial port
+            let sc = match self.device_config.emulation {
+                EmulationType::DMG => 0x7e,
+                EmulationType::GBC => 0x7f,
+            };
+
+            // GBC starts in normal speed mode without pending speed switch
+            let key1 = match self.device_config.emulation {
+                EmulationType::DMG => 0xff,
+                EmulationType::GBC => 0x7e,
+            };
+
             // GBC prefers object priority by OAM index, DMG by sprite x position
             let opri = match self.device_config.emulation {
                 EmulationType::DMG => 0xff,
@@ -396,12 +802,12 @@ impl GameBoy {
 
             let io_reg_data : [u8; 256] = [
                 /*          0     1     2     3     4     5     6     7     8     9     a     b     c     d     e     f */
-                /* 00 */ 0xcf, 0x00, 0x7e,    X,  div, 0x00, 0x00,  tac,    X,    X,    X,    X,    X,    X,    X, 0xe1,
+                /* 00 */ 0xcf, 0x00,   sc,    X,  div, 0x00, 0x00,  tac,    X,    X,    X,    X,    X,    X,    X, 0xe1,
                 /* 10 */ 0x80, 0xbf, 0xf3, 0xff, 0xbf,    X, 0x3f, 0x00, 0xff, 0xbf, 0x7f, 0xff, 0x9f, 0xff, 0xbf,    X,
                 /* 20 */ 0xff, 0x00, 0x00, 0xbf, 0x77, 0xf3, 0xf1,    X,    X,    X,    X,    X,    X,    X,    X,    X,
                 /* 30 */    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,
-                /* 40 */ 0x91, lcds, 0x00, 0x00,   ly, 0x00,  dma, 0xfc, 0x00, 0x00, 0x00, 0x00,    X, 0xff,    X,  vbk,
-                /* 50 */    X, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,    X,    X,    X,    X,    X,    X,    X,    X,    X,
+                /* 40 */ 0x91, lcds, 0x00, 0x00,   ly, 0x00,  dma, 0xfc, 0x00, 0x00, 0x00, 0x00,    X, key1,    X,  vbk,
+                /* 50 */    X, 0xff, 0xff, 0xff, 0xff, 0xff,   rp,    X,    X,    X,    X,    X,    X,    X,    X,    X,
                 /* 60 */    X,    X,    X,    X,    X,    X,    X,    X, 0xff, 0xff, 0xff, 0xff, opri,    X,    X,    X,
                 /* 70 */ 0xff,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,
                 /* 80 */    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,    X,
@@ -430,9 +836,91 @@ impl GameBoy {
     }
 
 
+    /// Get the number of cycles of the system clock processed since the emulator started.
+    /// Other than the CPU cycles, these do not run faster in double speed mode,
+    /// so they always advance with [CPU_CLOCK_SPEED] cycles per emulated second.
+    pub fn get_total_system_cycles_processed(&self) -> Clock {
+        self.total_system_cycles
+    }
+
+
+    /// Get the number of instructions executed by the CPU since the emulator started.
+    /// This does not include interrupts being dispatched or invalid opcodes.
+    pub fn get_total_instructions_executed(&self) -> u64 {
+        self.total_instructions
+    }
+
+
+    /// Get the effective clock speed of the CPU in cycles per second.
+    /// This is doubled while the GameBoy Color is running in double speed mode,
+    /// while the PPU, APU and serial port keep running at [CPU_CLOCK_SPEED].
+    pub fn effective_clock_speed(&self) -> Clock {
+        if self.get_peripherals().mem.is_double_speed() {
+            CPU_CLOCK_SPEED * 2
+        }
+        else {
+            CPU_CLOCK_SPEED
+        }
+    }
+
+
+    /// Performs a pending speed switch on GameBoy Color, which was requested
+    /// via KEY1 register before executing the STOP instruction.
+    pub(crate) fn perform_speed_switch(&mut self) {
+        self.get_peripherals_mut().mem.perform_speed_switch();
+    }
+
+
+    /// Converts a number of CPU cycles into cycles of the system clock,
+    /// which only advances at half of the CPU's rate in double speed mode.
+    fn to_system_cycles(&self, cycles: Clock) -> Clock {
+        if self.get_peripherals().mem.is_double_speed() {
+            cycles / 2
+        }
+        else {
+            cycles
+        }
+    }
+
+
+    /// Stops the CPU and the system clock until a button gets pressed.
+    pub(crate) fn enter_stop_mode(&mut self) {
+        self.cpu.enter_stop_mode();
+        self.get_peripherals_mut().ppu.on_stop_mode_entered();
+    }
+
+
+    /// Set the number of frames to be emulated with each call of [GameBoy::run_frame].
+    /// With a multiplier of 0.5, only half of a frame will be emulated, while with 2.0
+    /// two frames will be emulated. This changes the amount of emulated time relative
+    /// to the wall clock time of the frontend, not the emulated clock speed.
+    pub fn set_speed_multiplier(&mut self, multiplier: f64) {
+        if multiplier > 0.0 && self.speed_multiplier != multiplier {
+            self.speed_multiplier = multiplier;
+            self.cycles_budget    = 0.0;
+            self.update_audio_clock_speed();
+        }
+    }
+
+
+    /// Get the number of frames to be emulated with each call of [GameBoy::run_frame].
+    pub fn get_speed_multiplier(&self) -> f64 {
+        self.speed_multiplier
+    }
+
+
+    /// Updates the clock speed the audio output is based on. When a speed multiplier
+    /// is set, the audio output generates samples based on the time passing for the
+    /// frontend, so the number of samples per second stays in line with the playback.
+    fn update_audio_clock_speed(&mut self) {
+        let clock_speed = (CPU_CLOCK_SPEED as f64 * self.speed_multiplier) as Clock;
+        self.get_peripherals_mut().apu.get_audio_output().set_clock_speed(clock_speed);
+    }
+
+
     /// Get the time in seconds the emulator did run.
     pub fn get_total_seconds_processed(&self) -> f32 {
-        (self.total_cycles as f32) / (CPU_CLOCK_SPEED as f32)
+        (self.total_system_cycles as f32) / (CPU_CLOCK_SPEED as f32)
     }
 
 
@@ -460,6 +948,121 @@ impl GameBoy {
     }
 
 
+    /// Get the list of breakpoints.
+    pub fn get_breakpoints(&self) -> &Breakpoints {
+        &self.breakpoints
+    }
+
+
+    /// Get the list of breakpoints to be modified.
+    pub fn get_breakpoints_mut(&mut self) -> &mut Breakpoints {
+        &mut self.breakpoints
+    }
+
+
+    /// Get the timing statistics of the frames processed via [GameBoy::run_frame]
+    /// since the emulator was created or the statistics were reset.
+    pub fn get_frame_stats(&self) -> &FrameTimingStats {
+        self.frame_stats.get_stats()
+    }
+
+
+    /// Resets the frame timing statistics, so the next frame starts a new measurement.
+    pub fn reset_frame_stats(&mut self) {
+        self.frame_stats.reset();
+    }
+
+
+    /// Enables or disables counting how often each opcode was executed.
+    pub fn enable_instruction_counting(&mut self, enabled: bool) {
+        self.instruction_counter.set_enabled(enabled);
+    }
+
+
+    /// Get the number of executions of each opcode, while instruction counting was enabled.
+    /// Regular opcodes are stored in 0x000-0x0ff, 0xCB prefixed opcodes in 0x100-0x1ff.
+    pub fn get_instruction_counts(&self) -> &[u64; INSTRUCTION_COUNTER_SLOTS] {
+        self.instruction_counter.get_counts()
+    }
+
+
+    /// Resets the number of executions of all opcodes to zero.
+    pub fn reset_instruction_counts(&mut self) {
+        self.instruction_counter.reset();
+    }
+
+
+    /// Enables or disables recording the interrupts requested by any component.
+    pub fn enable_interrupt_history(&mut self, enabled: bool) {
+        self.interrupt_history.set_enabled(enabled);
+    }
+
+
+    /// Get the most recent interrupts requested, while recording was enabled.
+    pub fn get_interrupt_history(&self) -> &InterruptHistory {
+        &self.interrupt_history
+    }
+
+
+    /// Removes all interrupts recorded so far.
+    pub fn clear_interrupt_history(&mut self) {
+        self.interrupt_history.clear();
+    }
+
+
+    /// Enables or disables recording the OAM DMA transfers completed.
+    pub fn enable_dma_history(&mut self, enabled: bool) {
+        self.dma_history.set_enabled(enabled);
+    }
+
+
+    /// Get the most recent OAM DMA transfers completed, while recording was enabled.
+    pub fn get_dma_history(&self) -> &DmaHistory {
+        &self.dma_history
+    }
+
+
+    /// Removes all DMA transfers recorded so far.
+    pub fn clear_dma_history(&mut self) {
+        self.dma_history.clear();
+    }
+
+
+    /// Replaces the state of this instance with the state of another one, for example
+    /// one restored from a snapshot. Anything connected to the frontend, which is not
+    /// part of the emulator state, will be kept: open audio channels, muted channels,
+    /// serial devices, breakpoints and the source file of the cartridge,
+    /// if both instances run the same ROM.
+    pub fn replace_with(&mut self, mut other: GameBoy) {
+        other.get_peripherals_mut().apu.take_settings_from(
+            &mut self.get_peripherals_mut().apu
+        );
+
+        other.get_peripherals_mut().serial.take_device_from(
+            &mut self.get_peripherals_mut().serial
+        );
+
+        other.breakpoints         = std::mem::take(&mut self.breakpoints);
+        other.instruction_counter = std::mem::take(&mut self.instruction_counter);
+        other.interrupt_history   = std::mem::take(&mut self.interrupt_history);
+        other.dma_history         = std::mem::take(&mut self.dma_history);
+        other.speed_multiplier    = self.speed_multiplier;
+        other.boot_rom            = self.boot_rom.take();
+        other.update_audio_clock_speed();
+
+        if let (Some(current), Some(replaced)) = (
+            self.get_peripherals().mem.get_cartridge(),
+            other.get_peripherals_mut().mem.get_cartridge_mut()
+        ) {
+            if current.has_same_rom(replaced) {
+                replaced.set_source_file(current.get_source_file().cloned());
+            }
+        }
+
+        *self = other;
+    }
+
+
     /// Runs the emulator for a single step, either an instruction
     /// or to process a single HALT cycle.
     pub fn run_single_step(&mut self) -> EmulatorUpdateResults {
@@ -469,8 +1072,17 @@ impl GameBoy {
 
     /// Continues running the program located on the cartridge,
     /// until the PPU has completed one single frame.
+    /// When a speed multiplier is set, the time of the given number of frames
+    /// will be emulated instead, see [GameBoy::set_speed_multiplier].
     pub fn run_frame(&mut self) -> EmulatorUpdateResults {
+        if self.speed_multiplier != 1.0 {
+            return self.run_frame_multiplied();
+        }
+
         let mut results = EmulatorUpdateResults::default();
+        let start_cycles = self.total_system_cycles;
+
+        self.frame_stats.on_frame_started(self.total_system_cycles);
 
         // update until receiving the 'frame completed' event.
         loop {
@@ -481,30 +1093,123 @@ impl GameBoy {
                 break;
             }
 
+            // stop when reaching a breakpoint or leaving the stack bounds
+            if results.events.contains(DebugEvent::BreakpointReached) || results.events.contains(DebugEvent::StackViolation) {
+                break;
+            }
+
             // inc ase the screen was disabled, stop after the time of one frame has passed
-            if results.cycles >= CPU_CYCLES_PER_FRAME {
+            if self.total_system_cycles - start_cycles >= CPU_CYCLES_PER_FRAME {
                 break;
             }
         }
 
+        self.frame_stats.on_frame_completed(self.total_system_cycles);
+
+        results
+    }
+
+
+    /// Runs the emulator frame by frame until the serial output contains the expected text,
+    /// which is commonly used by test ROMs to report their results.
+    /// Returns the whole serial output received so far on success, or a [TimeoutError]
+    /// when the expected text was not received within `max_cycles` or the CPU
+    /// reached an invalid opcode.
+    pub fn run_until_serial_output(&mut self, expected: &str, max_cycles: Clock) -> Result<String, TimeoutError> {
+        let mut cycles_elapsed = 0;
+
+        self.get_peripherals_mut().serial.enable_output_queue(true);
+
+        loop {
+            let results = self.run_frame();
+            cycles_elapsed += results.cycles;
+
+            let output = self.get_peripherals().serial.get_output_as_text();
+
+            if output.contains(expected) {
+                return Ok(output);
+            }
+
+            let invalid_opcode = results.events
+                .contains(DebugEvent::InvalidOpcode)
+                .then(|| self.cpu.get_instruction_pointer())
+            ;
+
+            if invalid_opcode.is_some() || cycles_elapsed >= max_cycles {
+                return Err(TimeoutError {
+                    output_so_far: output,
+                    cycles_elapsed,
+                    invalid_opcode,
+                });
+            }
+        }
+    }
+
+
+    /// Runs the emulator for the time of a single frame multiplied with the current speed multiplier.
+    /// Cycles exceeding this time will be subtracted from the time of the next call.
+    fn run_frame_multiplied(&mut self) -> EmulatorUpdateResults {
+        let mut results = EmulatorUpdateResults::default();
+        let start_cycles = self.total_system_cycles;
+
+        self.frame_stats.on_frame_started(self.total_system_cycles);
+        self.cycles_budget += (CPU_CYCLES_PER_FRAME as f64) * self.speed_multiplier;
+
+        // the budget is based on the system clock, which is not affected by double speed mode
+        while ((self.total_system_cycles - start_cycles) as f64) < self.cycles_budget {
+            results += self.process_next();
+
+            // stop when reaching a breakpoint or leaving the stack bounds and drop the remaining time
+            if results.events.contains(DebugEvent::BreakpointReached) || results.events.contains(DebugEvent::StackViolation) {
+                self.cycles_budget = (self.total_system_cycles - start_cycles) as f64;
+                break;
+            }
+        }
+
+        // any cycles processed in excess will be taken from the next frame
+        self.cycles_budget -= (self.total_system_cycles - start_cycles) as f64;
+
+        self.frame_stats.on_frame_completed(self.total_system_cycles);
+
         results
     }
 
 
     /// Continues processing the next pending operation.
     fn process_next(&mut self) -> EmulatorUpdateResults {
+        if self.cpu.is_stopped() {
+            return self.process_stop_cycle();
+        }
+
         if self.cpu.is_running() {
-            if let Some(cycles) = self.cpu.handle_interrupts() {
+            let mut results = if let Some(cycles) = self.cpu.handle_interrupts() {
                 let signals = self.update_components(cycles);
 
                 EmulatorUpdateResults {
                     cycles,
+                    cycle_breakdown: CycleBreakdown { interrupt_dispatch: cycles, ..Default::default() },
                     events: signals.events,
                 }
             }
             else {
                 self.process_next_opcode()
+            };
+
+            // check whether the next instruction is on a breakpoint
+            if !self.breakpoints.is_empty() {
+                let pc = self.cpu.get_instruction_pointer();
+
+                if self.breakpoints.check(pc) {
+                    results.events |= DebugEvent::BreakpointReached;
+                }
             }
+
+            // check whether the last instruction or interrupt left the stack bounds
+            if self.cpu.take_stack_violation() {
+                results.events |= DebugEvent::StackViolation;
+            }
+
+            results
         }
         else {
             // when in HALT state just pass 4 cycles
@@ -514,19 +1219,54 @@ impl GameBoy {
 
             EmulatorUpdateResults {
                 cycles: halt_cycle,
+                cycle_breakdown: CycleBreakdown { halt: halt_cycle, ..Default::default() },
                 events: signals.events,
             }
         }
     }
 
 
+    /// Process a single cycle while the CPU was stopped by the STOP command.
+    /// The system clock is stopped as well, so no other component than the joypad
+    /// will be updated. Pressing a button raises the joypad interrupt and wakes up the CPU.
+    fn process_stop_cycle(&mut self) -> EmulatorUpdateResults {
+        let stop_cycle = 4;
+        let input      = &mut self.get_peripherals_mut().input;
+
+        input.update();
+        let signals = input.take_signals();
+
+        if signals.interrupts.contains(Interrupt::Input) {
+            self.get_peripherals_mut().interrupts.request_interrupts(signals.interrupts);
+            self.cpu.leave_halt_state();
+        }
+
+        self.total_cycles        += stop_cycle;
+        self.total_system_cycles += self.to_system_cycles(stop_cycle);
+
+        EmulatorUpdateResults {
+            cycles: stop_cycle,
+            cycle_breakdown: CycleBreakdown { halt: stop_cycle, ..Default::default() },
+            events: signals.events,
+        }
+    }
+
+
     /// Process the next opcode.
     fn process_next_opcode(&mut self) -> EmulatorUpdateResults {
         let instruction = self.cpu.fetch_next_instruction();
+
+        if instruction.opcode.is_invalid() {
+            return self.process_invalid_opcode(instruction.opcode_address);
+        }
+
         let mut context = OpCodeContext::for_instruction(&instruction);
         let mut signals = MemoryBusSignals::default();
         let mut total_step_cycles : Clock = 0;
 
+        self.instruction_counter.count(instruction.opcode_id);
+        self.total_instructions += 1;
+
         // process cycles ahead of the actual opcode execution to get read/write operations
         // to be invoked on their expected cycle
         if instruction.opcode.cycles_ahead != 0 {
@@ -574,24 +1314,58 @@ impl GameBoy {
 
         EmulatorUpdateResults {
             cycles: context.get_cycles_consumed(),
+            cycle_breakdown: CycleBreakdown { cpu: context.get_cycles_consumed(), ..Default::default() },
             events: signals.events,
         }
     }
 
 
+    /// Handles the CPU trying to execute an invalid opcode. On the original hardware,
+    /// this locks up the CPU, so it stays on the address of the invalid opcode,
+    /// while other components continue running.
+    fn process_invalid_opcode(&mut self, opcode_address: u16) -> EmulatorUpdateResults {
+        let lockup_cycle = 4;
+        let signals      = self.update_components(lockup_cycle);
+
+        self.cpu.set_instruction_pointer(opcode_address);
+
+        EmulatorUpdateResults {
+            cycles: lockup_cycle,
+            cycle_breakdown: CycleBreakdown { halt: lockup_cycle, ..Default::default() },
+            events: signals.events | DebugEvent::InvalidOpcode,
+        }
+    }
+
+
     /// Applies the time passed during CPU execution to other components as well.
     #[must_use]
     fn update_components(&mut self, cycles: Clock) -> MemoryBusSignals {
+        // the source of a DMA transfer running before the update, to detect its completion
+        let dma_source = match self.get_mmu().get_dma_transfer_state() {
+            DmaTransferState::Transferring(transfer) => Some(transfer.start_address),
+            DmaTransferState::Disabled               => None,
+        };
+
+        // a transfer which did not copy any data yet was started since the last update
+        let dma_started = matches!(
+            self.get_mmu().get_dma_transfer_state(),
+            DmaTransferState::Transferring(transfer) if transfer.next_byte == 0
+        );
+
+        // in double speed mode, the CPU, timer and DMA run twice as fast, while the
+        // PPU, APU and serial port are still driven by the system clock
+        let system_cycles = self.to_system_cycles(cycles);
+
         self.cpu.update(cycles);
         self.get_mmu_mut().update(cycles);
-        self.get_peripherals_mut().apu.update(cycles);
-        self.get_peripherals_mut().ppu.update(cycles);
+        self.get_peripherals_mut().apu.update(system_cycles);
+        self.get_peripherals_mut().ppu.update(system_cycles);
         self.get_peripherals_mut().timer.update(cycles);
-        self.get_peripherals_mut().serial.update(cycles);
+        self.get_peripherals_mut().serial.update(system_cycles);
         self.get_peripherals_mut().input.update();
 
         // collects all signals received from components
-        let signals =
+        let mut signals =
                 self.get_peripherals_mut().apu.take_signals()
             |   self.get_peripherals_mut().ppu.take_signals()
             |   self.get_peripherals_mut().timer.take_signals()
@@ -599,14 +1373,145 @@ impl GameBoy {
             |   self.get_peripherals_mut().input.take_signals()
         ;
 
+        // on DMG, a STAT interrupt is blocked while the previous one was not yet handled
+        if self.is_stat_interrupt_blocked() {
+            signals.interrupts -= Interrupt::LcdStat;
+        }
+
         // forward all requested interrupts into the Interrupts component.
         self.get_peripherals_mut().interrupts.request_interrupts(signals.interrupts);
 
+        // the SuperGameBoy performs pending VRAM transfers with the frame just completed
+        if signals.events.contains(DebugEvent::PpuFrameCompleted) {
+            let peripherals = self.get_peripherals_mut();
+
+            if let Some(sgb) = &mut peripherals.sgb {
+                sgb.on_frame_completed(&peripherals.ppu);
+            }
+        }
+
+        // record interrupts and the start and end of DMA transfers into the PPU's frame log, if enabled
+        if self.get_peripherals().ppu.is_frame_log_enabled() {
+            let dma_completed = dma_source.is_some() && !self.get_mmu().is_dma_transfer_active();
+            let ppu           = &mut self.get_peripherals_mut().ppu;
+
+            for interrupt in signals.interrupts {
+                ppu.log_frame_event(FrameLogEventKind::Interrupt(interrupt));
+            }
+
+            if dma_started {
+                ppu.log_frame_event(FrameLogEventKind::OamDmaStarted);
+            }
+
+            if dma_completed {
+                ppu.log_frame_event(FrameLogEventKind::OamDmaCompleted);
+            }
+        }
+
+        // record the requested interrupts, if enabled
+        if self.interrupt_history.is_enabled() {
+            let ly = self.get_peripherals().ppu.get_current_line();
+
+            for interrupt in signals.interrupts {
+                self.interrupt_history.record(InterruptEvent {
+                    interrupt,
+                    cycle: self.total_cycles + cycles,
+                    ly,
+                });
+            }
+        }
+
+        // record the DMA transfer, if completed with this update
+        if let Some(source_address) = dma_source {
+            if self.dma_history.is_enabled() && !self.get_mmu().is_dma_transfer_active() {
+                self.dma_history.record(DmaTransferEvent {
+                    source_address,
+                    cycle: self.total_cycles + cycles,
+                });
+            }
+        }
+
         // increment clock counters
-        self.total_cycles += cycles;
+        self.total_cycles        += cycles;
+        self.total_system_cycles += system_cycles;
 
         signals
     }
+
+
+    /// Checks whether new STAT interrupts are blocked, because the STAT interrupt flag in IF
+    /// is still set. This only applies to DMG, where a STAT interrupt being requested while
+    /// the previous one was not yet handled does not generate a new interrupt.
+    /// Being an obscure hardware behavior, this is only emulated with [AccuracyLevel::Strict].
+    fn is_stat_interrupt_blocked(&self) -> bool {
+            self.device_config.emulation == EmulationType::DMG
+        &&  self.device_config.accuracy  >= AccuracyLevel::Strict
+        &&  self.get_peripherals().interrupts.is_interrupt_flagged(Interrupt::LcdStat)
+    }
+}
+
+
+/// The default speed multiplier when restoring an emulator instance from a snapshot.
+#[cfg(feature = "serde")]
+fn default_speed_multiplier() -> f64 {
+    1.0
+}
+
+
+impl std::fmt::Display for BuilderErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuilderErrorCode::InvalidCartridgeHeader(e) => {
+                write!(f, "Invalid cartridge header: {e}")
+            }
+
+            BuilderErrorCode::GameBoyColorNotSupported => {
+                write!(f, "The cartridge requires a GameBoy Color compatible device")
+            }
+
+            BuilderErrorCode::GameBoyColorBootRomNotSupported => {
+                write!(f, "The GameBoy Color boot ROM requires a GameBoy Color compatible device")
+            }
+
+            BuilderErrorCode::UnsupportedMapper(cartridge_type) => {
+                write!(f, "Unsupported cartridge type: 0x{cartridge_type:02x}")
+            }
+
+            BuilderErrorCode::DeviceCreationFailed(e) => {
+                write!(f, "Failed to create the device: {e}")
+            }
+        }
+    }
+}
+
+
+impl std::error::Error for BuilderErrorCode {}
+
+
+impl std::fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.invalid_opcode {
+            Some(address) => {
+                write!(f, "Invalid opcode at 0x{address:04x} after {} cycles", self.cycles_elapsed)?;
+            }
+
+            None => {
+                write!(f, "Timeout after {} cycles", self.cycles_elapsed)?;
+            }
+        }
+
+        write!(f, ", serial output: '{}'", self.output_so_far)
+    }
+}
+
+
+impl std::error::Error for TimeoutError {}
+
+
+impl From<BuilderErrorCode> for String {
+    fn from(value: BuilderErrorCode) -> Self {
+        value.to_string()
+    }
 }
 
 
@@ -615,8 +1520,9 @@ impl std::ops::Add for EmulatorUpdateResults {
 
     fn add(self, rhs: Self) -> Self::Output {
         Self {
-            cycles: self.cycles + rhs.cycles,
-            events: self.events | rhs.events,
+            cycles:          self.cycles + rhs.cycles,
+            cycle_breakdown: self.cycle_breakdown + rhs.cycle_breakdown,
+            events:          self.events | rhs.events,
         }
     }
 }
@@ -624,7 +1530,28 @@ impl std::ops::Add for EmulatorUpdateResults {
 
 impl std::ops::AddAssign for EmulatorUpdateResults {
     fn add_assign(&mut self, rhs: Self) {
-        self.cycles += rhs.cycles;
-        self.events |= rhs.events;
+        self.cycles          += rhs.cycles;
+        self.cycle_breakdown += rhs.cycle_breakdown;
+        self.events          |= rhs.events;
+    }
+}
+
+
+impl std::ops::Add for CycleBreakdown {
+    type Output = CycleBreakdown;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            cpu:                self.cpu                + rhs.cpu,
+            interrupt_dispatch: self.interrupt_dispatch + rhs.interrupt_dispatch,
+            halt:               self.halt               + rhs.halt,
+        }
+    }
+}
+
+
+impl std::ops::AddAssign for CycleBreakdown {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
     }
 }
@@ -0,0 +1,136 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::device_type::EmulationType;
+use crate::gameboy::DeviceConfig;
+use crate::mmu::locations::MEMORY_LOCATION_RP;
+use crate::mmu::memory_bus::MemoryBusConnection;
+use crate::utils::{as_bit_flag, get_bit};
+
+/// Bit of the RP register to switch the LED on or off.
+const RP_BIT_LED:           u8 = 0;
+
+/// Bit of the RP register reporting the received signal.
+/// 0 = receiving an IR signal, 1 = no signal.
+const RP_BIT_RECEIVING:     u8 = 1;
+
+/// Bits of the RP register which need to be set to enable reading incoming signals.
+const RP_MASK_READ_ENABLED: u8 = 0b_1100_0000;
+
+/// Bits of the RP register which are unused and always read as 1.
+const RP_MASK_UNUSED:       u8 = 0b_0011_1100;
+
+
+/// An implementation of the GameBoy Color's infrared communication port.
+/// This provides access to the RP register, but does not connect to any
+/// other device. Instead, incoming signals can be injected by the frontend
+/// via [InfraredPort::signal_received].
+/// The device always receives the light of its own LED, so a program
+/// switching the LED on will read this as an incoming signal.
+/// On devices without GameBoy Color support, the RP register is not available.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InfraredPort {
+    device_config: DeviceConfig,
+
+    /// Whether the LED was switched on by the program.
+    led_enabled: bool,
+
+    /// The data read enable bits written by the program.
+    /// Reading incoming signals is only enabled when both bits are set.
+    read_enable_bits: u8,
+
+    /// Whether an external signal is currently received.
+    external_signal: bool,
+}
+
+
+impl InfraredPort {
+    /// Constructs a new instance of the InfraredPort.
+    pub fn new(device_config: DeviceConfig) -> Self {
+        Self {
+            device_config,
+            led_enabled:        false,
+            read_enable_bits:   0x00,
+            external_signal:    false,
+        }
+    }
+
+
+    /// Checks whether the port is available on the current device.
+    pub fn is_available(&self) -> bool {
+        match self.device_config.emulation {
+            EmulationType::DMG => false,
+            EmulationType::GBC => true,
+        }
+    }
+
+
+    /// Sets whether an external IR signal is currently received.
+    pub fn signal_received(&mut self, active: bool) {
+        self.external_signal = active;
+    }
+
+
+    /// Checks whether the LED is currently switched on.
+    pub fn is_led_enabled(&self) -> bool {
+        self.led_enabled
+    }
+
+
+    /// Checks whether the program enabled reading incoming signals.
+    pub fn is_read_enabled(&self) -> bool {
+        self.read_enable_bits == RP_MASK_READ_ENABLED
+    }
+
+
+    /// Checks whether a signal is currently received, either from an external source
+    /// or by the device's own LED.
+    pub fn is_signal_received(&self) -> bool {
+        self.external_signal || self.led_enabled
+    }
+}
+
+
+impl MemoryBusConnection for InfraredPort {
+    fn on_read(&self, address: u16) -> u8 {
+        match address {
+            MEMORY_LOCATION_RP if self.is_available() => {
+                // the receiving bit is 0 while receiving a signal, but only if reading was enabled
+                let receiving = self.is_read_enabled() && self.is_signal_received();
+
+                    RP_MASK_UNUSED
+                |   self.read_enable_bits
+                |   as_bit_flag(!receiving, RP_BIT_RECEIVING)
+                |   as_bit_flag(self.led_enabled, RP_BIT_LED)
+            }
+
+            _ => 0xff
+        }
+    }
+
+
+    fn on_write(&mut self, address: u16, value: u8) {
+        match address {
+            MEMORY_LOCATION_RP if self.is_available() => {
+                self.led_enabled      = get_bit(value, RP_BIT_LED);
+                self.read_enable_bits = value & RP_MASK_READ_ENABLED;
+            }
+
+            _ => { }
+        }
+    }
+}
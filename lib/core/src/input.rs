@@ -90,8 +90,9 @@ impl Input {
         if keys_changed {
             let keys_pressed = self.button_states & !self.previous_button_states;
 
-            // when any key was pressed this frame, fire the input interrupt
-            if keys_pressed != 0 {
+            // when any key of the currently selected button group was pressed,
+            // the according JOYP line falls from high to low and fires the input interrupt
+            if (keys_pressed & self.get_selected_buttons_mask()) != 0 {
                 self.request_interrupt(Interrupt::Input);
             }
 
@@ -109,6 +110,14 @@ impl Input {
         get_bit(self.button_states, button as u8)
     }
 
+    /// Get a bitmask of all buttons within the button groups currently selected via JOYP.
+    fn get_selected_buttons_mask(&self) -> u8 {
+        let dpad_mask   = if get_bit(self.button_selection, 4) { 0x00 } else { 0x0f };
+        let action_mask = if get_bit(self.button_selection, 5) { 0x00 } else { 0xf0 };
+
+        dpad_mask | action_mask
+    }
+
     /// Requests an interrupt to be fired.
     fn request_interrupt(&mut self, interrupt: Interrupt) {
         self.signals.interrupts |= interrupt;
@@ -120,12 +129,13 @@ impl MemoryBusConnection for Input {
     fn on_read(&self, address: u16) -> u8 {
         match address {
             MEMORY_LOCATION_JOYP => {
-                let states = match self.button_selection {
-                    0x00 => 0x00,
-                    0x10 => (!self.button_states >> 4) & 0x0f,
-                    0x20 => (!self.button_states >> 0) & 0x0f,
-                    _    => 0x0f,
-                };
+                // a button group is selected when its select bit is 0;
+                // when both groups are selected, their states are combined
+                let selected_buttons = self.button_states & self.get_selected_buttons_mask();
+                let pressed          = (selected_buttons >> 4) | (selected_buttons & 0x0f);
+
+                // pressed buttons are represented by 0 bits
+                let states = !pressed & 0x0f;
 
                 0b_1100_0000 | states | self.button_selection
             },
@@ -18,14 +18,21 @@
 pub mod apu;
 pub mod boot_rom;
 pub mod cartridge;
+pub mod cheats;
 pub mod cpu;
 pub mod debug;
 pub mod device_type;
+pub mod frame_stats;
 pub mod gameboy;
+pub mod infrared;
 pub mod input;
+pub mod link_cable;
 pub mod mmu;
 pub mod ppu;
+pub mod printer;
+pub mod screenshot;
 pub mod serial;
+pub mod sgb;
 pub mod snapshots;
 pub mod timer;
 pub mod utils;
@@ -0,0 +1,114 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::sync::{Arc, Mutex};
+
+use crate::serial::{SerialDevice, SerialPort};
+
+
+/// A link cable connecting the serial ports of two emulator instances.
+/// The side using its internal clock drives the transfer and exchanges its byte
+/// with the byte of the other side, which needs to wait for a transfer using
+/// the external clock. When the other side is not waiting, the driving side receives 0xff.
+/// Both emulator instances are expected to run in lockstep, so data sent by one side
+/// will be received by the other side with its next update.
+#[derive(Default)]
+pub struct LinkCable {
+    ends: [LinkCableEnd; 2],
+}
+
+
+/// A plug on one end of a [LinkCable], which is connected to the serial port of an emulator.
+pub struct LinkCablePlug {
+    /// The cable shared with the plug on the other end.
+    cable: Arc<Mutex<LinkCable>>,
+
+    /// The index of the end this plug is attached to.
+    end: usize,
+}
+
+
+/// The data transferred through one end of a [LinkCable].
+#[derive(Default)]
+struct LinkCableEnd {
+    /// The byte offered by a GameBoy waiting for a transfer driven by the other side.
+    waiting: Option<u8>,
+
+    /// The byte sent by the other side, which was not yet received.
+    incoming: Option<u8>,
+}
+
+
+impl LinkCable {
+    /// Creates a new link cable and returns the plugs on both of its ends.
+    pub fn create() -> (LinkCablePlug, LinkCablePlug) {
+        let cable = Arc::new(Mutex::new(LinkCable::default()));
+
+        (
+            LinkCablePlug { cable: cable.clone(), end: 0 },
+            LinkCablePlug { cable,                end: 1 },
+        )
+    }
+
+
+    /// Connects the serial ports of two emulator instances with a new link cable.
+    pub fn connect(port_a: &mut SerialPort, port_b: &mut SerialPort) {
+        let (plug_a, plug_b) = Self::create();
+
+        port_a.connect_device(Box::new(plug_a));
+        port_b.connect_device(Box::new(plug_b));
+    }
+}
+
+
+impl SerialDevice for LinkCablePlug {
+    fn on_byte_exchanged(&mut self, sent: u8) -> u8 {
+        let mut cable = self.cable.lock().unwrap();
+
+        // while driving the transfer, this side is not waiting anymore
+        cable.ends[self.end].waiting = None;
+
+        let other = &mut cable.ends[1 - self.end];
+
+        match other.waiting.take() {
+            Some(received) => {
+                other.incoming = Some(sent);
+                received
+            }
+
+            None => 0xff,
+        }
+    }
+
+
+    fn on_external_clock(&mut self, sent: u8) -> Option<u8> {
+        let mut cable = self.cable.lock().unwrap();
+        let this      = &mut cable.ends[self.end];
+
+        match this.incoming.take() {
+            Some(received) => {
+                this.waiting = None;
+                Some(received)
+            }
+
+            None => {
+                this.waiting = Some(sent);
+                None
+            }
+        }
+    }
+}
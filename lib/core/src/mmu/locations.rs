@@ -66,6 +66,7 @@ pub const MEMORY_LOCATION_PALETTE_OBP0:             u16 = 0xff48;
 pub const MEMORY_LOCATION_PALETTE_OBP1:             u16 = 0xff49;
 pub const MEMORY_LOCATION_WY:                       u16 = 0xff4a;
 pub const MEMORY_LOCATION_WX:                       u16 = 0xff4b;
+pub const MEMORY_LOCATION_KEY1:                     u16 = 0xff4d;
 pub const MEMORY_LOCATION_VBK:                      u16 = 0xff4f;
 pub const MEMORY_LOCATION_BOOT_ROM_DISABLE:         u16 = 0xff50;
 pub const MEMORY_LOCATION_HDMA1:                    u16 = 0xff51;
@@ -73,11 +74,14 @@ pub const MEMORY_LOCATION_HDMA2:                    u16 = 0xff52;
 pub const MEMORY_LOCATION_HDMA3:                    u16 = 0xff53;
 pub const MEMORY_LOCATION_HDMA4:                    u16 = 0xff54;
 pub const MEMORY_LOCATION_HDMA5:                    u16 = 0xff55;
+pub const MEMORY_LOCATION_RP:                       u16 = 0xff56;
 pub const MEMORY_LOCATION_BCPS:                     u16 = 0xff68;
 pub const MEMORY_LOCATION_BCPD:                     u16 = 0xff69;
 pub const MEMORY_LOCATION_OCPS:                     u16 = 0xff6a;
 pub const MEMORY_LOCATION_OCPD:                     u16 = 0xff6b;
 pub const MEMORY_LOCATION_OPRI:                     u16 = 0xff6c;
 pub const MEMORY_LOCATION_SVBK:                     u16 = 0xff70;
+pub const MEMORY_LOCATION_PCM12:                    u16 = 0xff76;
+pub const MEMORY_LOCATION_PCM34:                    u16 = 0xff77;
 pub const MEMORY_LOCATION_INTERRUPTS_FLAGGED:       u16 = 0xff0f;
 pub const MEMORY_LOCATION_INTERRUPTS_ENABLED:       u16 = 0xffff;
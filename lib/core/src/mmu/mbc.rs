@@ -35,6 +35,10 @@ pub enum MemoryBankController {
     MBC5,
     MBC6,
     MBC7,
+
+    /// A cartridge type not known by the emulator, containing the type
+    /// value from the cartridge header.
+    Unknown(u8),
 }
 
 
@@ -75,6 +79,21 @@ pub fn create_mbc(kind: &MemoryBankController) -> Mbc {
 }
 
 
+impl MemoryBankController {
+    /// Checks whether the emulator provides an implementation of this memory bank controller.
+    pub fn is_supported(&self) -> bool {
+        matches!(
+            self,
+                MemoryBankController::None
+            |   MemoryBankController::MBC1
+            |   MemoryBankController::MBC1M
+            |   MemoryBankController::MBC2
+            |   MemoryBankController::MBC5
+        )
+    }
+}
+
+
 impl Display for MemoryBankController {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let name = match *self {
@@ -86,6 +105,7 @@ impl Display for MemoryBankController {
             MemoryBankController::MBC5  => "MBC5",
             MemoryBankController::MBC6  => "MBC6",
             MemoryBankController::MBC7  => "MBC7",
+            MemoryBankController::Unknown(_) => "Unknown",
         };
 
         write!(f, "{:}", name)
@@ -25,6 +25,7 @@ use crate::mmu::locations::*;
 use crate::mmu::mbc::{create_mbc, Mbc, MbcImpl, MemoryBankController};
 use crate::mmu::memory_bus::{memory_map, MemoryBusConnection};
 use crate::mmu::memory_data::{MemoryData, MemoryDataFixedSize};
+use crate::utils::{as_bit_flag, get_bit};
 
 
 /// Stores the information of an active OAM DMA transfer
@@ -32,6 +33,7 @@ use crate::mmu::memory_data::{MemoryData, MemoryDataFixedSize};
 /// into OAM memory.
 /// In total, 160 bytes will be transferred, so it takes
 /// 160 cycles to transfer for the transfer to be completed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DmaTransferInfo {
     /// The address where to start copying the memory from.
@@ -44,6 +46,7 @@ pub struct DmaTransferInfo {
 
 /// State of the OAM DMA transfer, whether it be disabled or
 /// in progress, including the time remaining.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DmaTransferState {
     /// No transfer is active.
@@ -57,6 +60,18 @@ pub enum DmaTransferState {
 }
 
 
+impl DmaTransferInfo {
+    /// The total number of bytes copied by a single transfer.
+    pub const TRANSFER_SIZE: u16 = 160;
+
+    /// Get the number of bytes not yet copied, which is
+    /// also the number of cycles until the transfer is completed.
+    pub fn get_remaining_bytes(&self) -> u16 {
+        Self::TRANSFER_SIZE.saturating_sub(self.next_byte)
+    }
+}
+
+
 pub type WRamBank = MemoryDataFixedSize<4096>;
 pub type HRamBank = MemoryDataFixedSize<127>;
 
@@ -75,6 +90,10 @@ pub struct Memory {
     wram_active_bank_0: u8,
     wram_active_bank_1: u8,
 
+    /// GBC only: The bank number written into the SVBK register.
+    /// Bank 0 will select bank 1, but the register still reads as 0.
+    wram_bank_selection: u8,
+
     /// High RAM
     hram: HRamBank,
 
@@ -83,6 +102,13 @@ pub struct Memory {
 
     boot_rom:   Option<BootRom>,
     cartridge:  Option<Cartridge>,
+
+    /// GBC only: Whether the CPU is running in double speed mode.
+    double_speed: bool,
+
+    /// GBC only: Whether a speed switch was requested via KEY1 register,
+    /// which will be performed on the next STOP instruction.
+    speed_switch_armed: bool,
 }
 
 
@@ -100,6 +126,7 @@ impl Memory {
             wram_banks: std::iter::repeat_with(|| WRamBank::new()).take(num_wram_banks).collect(),
             wram_active_bank_0: 0,
             wram_active_bank_1: 1,
+            wram_bank_selection: 0,
 
             hram: HRamBank::new(),
 
@@ -107,6 +134,9 @@ impl Memory {
 
             boot_rom:   None,
             cartridge:  None,
+
+            double_speed:       false,
+            speed_switch_armed: false,
         }
     }
 
@@ -130,11 +160,51 @@ impl Memory {
         self.cartridge = Some(cartridge);
     }
 
+    /// Removes the current cartridge from the memory and returns it, if any.
+    pub fn remove_cartridge(&mut self) -> Option<Cartridge> {
+        self.mbc = create_mbc(&MemoryBankController::None);
+        self.cartridge.take()
+    }
+
     /// Get a reference to the currently assigned cartridge, if any.
     pub fn get_cartridge(&self) -> Option<&Cartridge> {
         self.cartridge.as_ref()
     }
 
+    /// Get a mutable reference to the currently assigned cartridge, if any.
+    pub(crate) fn get_cartridge_mut(&mut self) -> Option<&mut Cartridge> {
+        self.cartridge.as_mut()
+    }
+
+    /// Checks whether the CPU is running in GameBoy Color double speed mode.
+    pub fn is_double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Checks whether a speed switch was requested to be performed on the next STOP instruction.
+    pub fn is_speed_switch_armed(&self) -> bool {
+        self.speed_switch_armed
+    }
+
+    /// Performs a speed switch, if it was requested via KEY1 register before.
+    /// Returns `true` if the speed was changed.
+    pub(crate) fn perform_speed_switch(&mut self) -> bool {
+        if self.speed_switch_armed {
+            self.double_speed       = !self.double_speed;
+            self.speed_switch_armed = false;
+
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns into normal speed mode and drops any pending speed switch request.
+    pub(crate) fn reset_speed(&mut self) {
+        self.double_speed       = false;
+        self.speed_switch_armed = false;
+    }
+
     /// Save the cartridge RAM, if any.
     pub fn save_cartridge_ram_to_file_if_any(&self) -> io::Result<()> {
         if let Some(cartridge) = &self.cartridge {
@@ -147,10 +217,13 @@ impl Memory {
 
 
 impl Memory {
-    /// Reads data from the boot rom, if any, otherwise from the cartridge.
+    /// Reads data from the boot rom, if any and the address is mapped to it,
+    /// otherwise from the cartridge.
     fn read_boot_rom_or_cartridge(&self, address: u16) -> u8 {
         if let Some(boot_rom) = &self.boot_rom {
-            return boot_rom.read(address);
+            if boot_rom.is_mapped(address) {
+                return boot_rom.read(address);
+            }
         }
 
         self.read_from_cartridge(address)
@@ -180,8 +253,8 @@ impl MemoryBusConnection for Memory {
     fn on_read(&self, address: u16) -> u8 {
         memory_map!(
             address => {
-                0x0000 ..= 0x00ff => [] self.read_boot_rom_or_cartridge(address),
-                0x0100 ..= 0x7fff => [] self.read_from_cartridge(address),
+                0x0000 ..= 0x08ff => [] self.read_boot_rom_or_cartridge(address),
+                0x0900 ..= 0x7fff => [] self.read_from_cartridge(address),
                 0xa000 ..= 0xbfff => [] self.read_from_cartridge(address),
 
                 0xc000 ..= 0xcfff => [mapped_address] {
@@ -219,9 +292,21 @@ impl MemoryBusConnection for Memory {
                         },
 
                         MEMORY_LOCATION_SVBK => {
-                            // on GBC: WRAM bank #1
+                            // on GBC: the selected WRAM bank with all unused bits set
+                            if let EmulationType::GBC = self.device_config.emulation {
+                                self.wram_bank_selection | 0b_1111_1000
+                            }
+                            else {
+                                0xff
+                            }
+                        },
+
+                        MEMORY_LOCATION_KEY1 => {
+                            // on GBC: current speed and pending speed switch
                             if let EmulationType::GBC = self.device_config.emulation {
-                                self.wram_active_bank_1 | 0b_1111_1000
+                                    0b_0111_1110
+                                |   as_bit_flag(self.double_speed, 7)
+                                |   as_bit_flag(self.speed_switch_armed, 0)
                             }
                             else {
                                 0xff
@@ -276,7 +361,15 @@ impl MemoryBusConnection for Memory {
                             // on GBC: switch WRAM bank #1
                             if let EmulationType::GBC = self.device_config.emulation {
                                 let bank = value & 0x07;
-                                self.wram_active_bank_1 = max(1, bank);
+                                self.wram_bank_selection = bank;
+                                self.wram_active_bank_1  = max(1, bank);
+                            }
+                        },
+
+                        MEMORY_LOCATION_KEY1 => {
+                            // on GBC: request a speed switch on the next STOP instruction
+                            if let EmulationType::GBC = self.device_config.emulation {
+                                self.speed_switch_armed = get_bit(value, 0);
                             }
                         },
 
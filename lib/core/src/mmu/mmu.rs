@@ -17,7 +17,8 @@
 
 use std::cmp::min;
 
-use crate::gameboy::{Clock, Peripherals};
+use crate::debug::{MemoryAccessProfiler, MEMORY_ACCESS_PROFILE_PAGES};
+use crate::gameboy::{AccuracyLevel, Clock, DeviceConfig, EmulationType, Peripherals};
 use crate::mmu::locations::*;
 use crate::mmu::memory::{DmaTransferInfo, DmaTransferState};
 use crate::mmu::memory_bus::{impl_memory_mapper, MemoryBus, MemoryBusConnection, MemoryMapper};
@@ -30,6 +31,11 @@ use crate::utils::{to_u16, to_u8};
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mmu {
     internal: MmuInternal,
+
+    /// Counts reads and writes of the CPU on each memory page, when enabled.
+    /// This is a debugging feature and not part of the emulator state.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    access_profiler: MemoryAccessProfiler,
 }
 
 
@@ -37,6 +43,8 @@ pub struct Mmu {
 /// IO operations to their actual components.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MmuInternal {
+    device_config: DeviceConfig,
+
     peripherals: Peripherals,
 
     dma: DmaTransferState,
@@ -47,14 +55,18 @@ pub struct MmuInternal {
 
 
 impl Mmu {
-    pub fn new(peripherals: Peripherals) -> Self {
+    pub fn new(device_config: DeviceConfig, peripherals: Peripherals) -> Self {
         Self {
             internal: MmuInternal {
+                device_config,
+
                 peripherals,
 
                 dma: DmaTransferState::Disabled,
                 dma_register_value: 0xff,
-            }
+            },
+
+            access_profiler: MemoryAccessProfiler::new(),
         }
     }
 
@@ -72,6 +84,10 @@ impl Mmu {
 
 
     /// Reads a single byte value from the memory bus on a given address.
+    /// Unlike reads performed by the CPU, this is not restricted by the PPU
+    /// locking video memory and does not have any side effects, which allows
+    /// debuggers and the DMA controller to access the whole memory at any time.
+    /// See [Mmu::cpu_read_u8]
     pub fn read_u8(&self, address: u16) -> u8 {
         self.internal.read(address)
     }
@@ -86,6 +102,9 @@ impl Mmu {
 
 
     /// Writes a single byte value to the memory bus on a given address.
+    /// Unlike writes performed by the CPU, this is not restricted by the PPU
+    /// locking video memory.
+    /// See [Mmu::cpu_write_u8]
     pub fn write_u8(&mut self, address: u16, value: u8) {
         self.internal.write(address, value);
     }
@@ -99,6 +118,103 @@ impl Mmu {
     }
 
 
+    /// Reads a single byte value on a given address as it would be seen by the CPU,
+    /// but without any side effects like counting the access or corrupting OAM.
+    /// See [Mmu::cpu_read_u8]
+    pub fn cpu_peek_u8(&self, address: u16) -> u8 {
+        if !self.internal.is_cpu_accessible(address) {
+            return 0xff;
+        }
+
+        self.internal.read(address)
+    }
+
+
+    /// Reads a single byte value on a given address like it's done by the CPU.
+    /// While the PPU is using video RAM or OAM, the CPU is not able to access them,
+    /// so reading those will return 0xff.
+    /// On DMG, reading OAM during an active DMA transfer corrupts the OAM data.
+    pub fn cpu_read_u8(&mut self, address: u16) -> u8 {
+        self.access_profiler.count(address);
+
+        if !self.internal.is_cpu_accessible(address) {
+            return 0xff;
+        }
+
+        if let 0xfe00 ..= 0xfeff = address {
+            if self.internal.is_oam_corrupted_on_read() {
+                self.internal.apply_oam_corruption();
+                return self.internal.read_corrupted_oam(address);
+            }
+        }
+
+        self.internal.read(address)
+    }
+
+
+    /// Reads two bytes into a 16 bit integer on a given address like it's done by the CPU.
+    /// See [Mmu::cpu_read_u8]
+    pub fn cpu_read_u16(&mut self, address: u16) -> u16 {
+        let l = self.cpu_read_u8(address.wrapping_add(0));
+        let h = self.cpu_read_u8(address.wrapping_add(1));
+        to_u16(h, l)
+    }
+
+
+    /// Writes a single byte value to a given address like it's done by the CPU.
+    /// While the PPU is using video RAM or OAM, the CPU is not able to access them,
+    /// so writing those will be ignored.
+    pub fn cpu_write_u8(&mut self, address: u16, value: u8) {
+        self.access_profiler.count(address);
+
+        if !self.internal.is_cpu_accessible(address) {
+            return;
+        }
+
+        self.internal.write(address, value);
+    }
+
+
+    /// Writes two bytes from a 16 bit integer to a given address like it's done by the CPU.
+    /// See [Mmu::cpu_write_u8]
+    pub fn cpu_write_u16(&mut self, address: u16, value: u16) {
+        let (h, l) = to_u8(value);
+        self.cpu_write_u8(address.wrapping_add(0), l);
+        self.cpu_write_u8(address.wrapping_add(1), h);
+    }
+
+
+    /// Enables or disables counting reads and writes of the CPU on each 256 byte page of memory.
+    /// While disabled, there's no overhead on memory accesses besides checking the flag.
+    pub fn enable_access_profiling(&mut self, enabled: bool) {
+        self.access_profiler.set_enabled(enabled);
+    }
+
+
+    /// Get the number of reads and writes on each 256 byte page of memory,
+    /// counted since profiling was enabled or the last call of this function.
+    /// All counters will be reset to zero.
+    pub fn take_access_profile(&mut self) -> [u32; MEMORY_ACCESS_PROFILE_PAGES] {
+        self.access_profiler.take_counts()
+    }
+
+
+    /// Checks whether an OAM DMA transfer is currently running.
+    pub fn is_dma_transfer_active(&self) -> bool {
+        match self.internal.dma {
+            DmaTransferState::Disabled        => false,
+            DmaTransferState::Transferring(_) => true,
+        }
+    }
+
+
+    /// Get the state of the OAM DMA transfer, including the source and progress
+    /// of a transfer currently running.
+    pub fn get_dma_transfer_state(&self) -> &DmaTransferState {
+        &self.internal.dma
+    }
+
+
     /// Let the memory controller handle it's tasks.
     /// 'cycles' gives the number of ticks passed since
     /// the last call.
@@ -109,6 +225,66 @@ impl Mmu {
 
 
 impl MmuInternal {
+    /// Checks whether the CPU is able to access a given address, which is not the case
+    /// for video RAM and OAM while being used by the PPU.
+    fn is_cpu_accessible(&self, address: u16) -> bool {
+        match address {
+            0x8000 ..= 0x9fff => self.peripherals.ppu.is_vram_accessible(),
+            0xfe00 ..= 0xfe9f => self.peripherals.ppu.is_oam_accessible(),
+            _                 => true,
+        }
+    }
+
+
+    /// Checks whether reading OAM would corrupt its data, which happens on DMG
+    /// when the CPU reads from OAM while a DMA transfer is active.
+    /// Being rarely relied on, this is only emulated with [AccuracyLevel::Strict].
+    fn is_oam_corrupted_on_read(&self) -> bool {
+        matches!(self.dma, DmaTransferState::Transferring(_))
+            && self.device_config.emulation == EmulationType::DMG
+            && self.device_config.accuracy  >= AccuracyLevel::Strict
+    }
+
+
+    /// Computes the value of a single byte of OAM after being corrupted.
+    /// The first byte of each four byte entry gets the value `(oam[n] | oam[n+1]) & oam[n+2]`,
+    /// while all other bytes keep their value.
+    fn get_corrupted_oam_byte(&self, index: usize) -> u8 {
+        let oam = self.peripherals.ppu.get_oam_bank();
+
+        if index.is_multiple_of(4) {
+            (oam.get_at(index) | oam.get_at(index + 1)) & oam.get_at(index + 2)
+        }
+        else {
+            oam.get_at(index)
+        }
+    }
+
+
+    /// Reads a byte from OAM after it was corrupted by the CPU reading it during a DMA transfer.
+    /// Addresses outside of OAM always return 0xff.
+    fn read_corrupted_oam(&self, address: u16) -> u8 {
+        match address {
+            MEMORY_LOCATION_OAM_BEGIN ..= MEMORY_LOCATION_OAM_END => {
+                self.peripherals.ppu.get_oam_bank().get_at((address - MEMORY_LOCATION_OAM_BEGIN) as usize)
+            }
+
+            _ => 0xff,
+        }
+    }
+
+
+    /// Applies the corruption caused by reading OAM during a DMA transfer on the whole OAM.
+    fn apply_oam_corruption(&mut self) {
+        let oam_size = (MEMORY_LOCATION_OAM_END - MEMORY_LOCATION_OAM_BEGIN + 1) as usize;
+
+        for index in (0 .. oam_size).step_by(4) {
+            let value = self.get_corrupted_oam_byte(index);
+            self.peripherals.ppu.get_oam_bank_mut().set_at(index, value);
+        }
+    }
+
+
     /// Handles an OAM DMA transfer, if any active.
     fn handle_dma_transfer(&mut self, cycles: Clock) {
         match self.dma {
@@ -152,6 +328,30 @@ impl MemoryBus<MmuInternal, MmuInternal> for MmuInternal {
     fn get_root_mut(&mut self) -> &mut MmuInternal {
         self
     }
+
+    fn read(&self, address: u16) -> u8 {
+        let value = <MmuInternal as MemoryMapper<MmuInternal>>::map(address, self).on_read(address);
+
+        // values read from the cartridge ROM may be patched by cheat codes
+        match address {
+            0x0000 ..= 0x7fff if !self.peripherals.cheats.is_empty() => {
+                self.peripherals.cheats.apply(address, value)
+            }
+
+            _ => value,
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        <MmuInternal as MemoryMapper<MmuInternal>>::map_mut(address, self).on_write(address, value);
+
+        // the SuperGameBoy receives packets via JOYP
+        if address == MEMORY_LOCATION_JOYP {
+            if let Some(sgb) = &mut self.peripherals.sgb {
+                sgb.on_joyp_write(value);
+            }
+        }
+    }
 }
 
 
@@ -212,14 +412,21 @@ impl_memory_mapper!(
 
         // APU registers
         0xff10 ..= 0xff3f => *root.peripherals.apu,
+        MEMORY_LOCATION_PCM12 ..= MEMORY_LOCATION_PCM34 => *root.peripherals.apu,
+
+        // CPU speed switch register
+        MEMORY_LOCATION_KEY1 => *root.peripherals.mem,
 
         // PPU registers
         0xff40 ..= 0xff45 => *root.peripherals.ppu,
         0xff47 ..= 0xff4f => *root.peripherals.ppu,
-        0xff68 ..= 0xff6b => *root.peripherals.ppu,
+        0xff68 ..= 0xff6c => *root.peripherals.ppu,
 
         MEMORY_LOCATION_DMA_ADDRESS => *root,
 
+        // infrared communication port
+        MEMORY_LOCATION_RP => *root.peripherals.infrared,
+
         MEMORY_LOCATION_INTERRUPTS_FLAGGED => *root.peripherals.interrupts,
         MEMORY_LOCATION_INTERRUPTS_ENABLED => *root.peripherals.interrupts,
 
@@ -0,0 +1,168 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{Display, Formatter};
+
+use crate::ppu::graphic_data::Color;
+
+
+/// Parameters of a color correction, which converts the raw RGB555 colors
+/// into colors looking similar to those on a real device's LCD.
+/// The color channels are linearized with the gamma of the LCD,
+/// mixed using the color matrix and converted back with the gamma of
+/// a common computer display.
+struct ColorCorrectionParams {
+    /// The gamma of the emulated LCD.
+    lcd_gamma: f32,
+
+    /// The gamma of the display showing the emulator output.
+    display_gamma: f32,
+
+    /// A matrix to compute the output channels R, G and B (rows)
+    /// from the linearized input channels R, G and B (columns).
+    matrix: [[f32; 3]; 3],
+}
+
+
+/// Parameters emulating the LCD of the GameBoy Color.
+/// The color matrix is the one used by higan and various other emulators.
+const PARAMS_CGB_LCD: ColorCorrectionParams = ColorCorrectionParams {
+    lcd_gamma:     2.4,
+    display_gamma: 2.2,
+    matrix: [
+        [ 26.0 / 32.0,  4.0 / 32.0,  2.0 / 32.0 ],
+        [  0.0 / 32.0, 24.0 / 32.0,  8.0 / 32.0 ],
+        [  6.0 / 32.0,  4.0 / 32.0, 22.0 / 32.0 ],
+    ],
+};
+
+
+/// Parameters emulating the darker LCD of the GameBoy Advance.
+/// The color matrix is the one used by higan and various other emulators.
+const PARAMS_GBA_LCD: ColorCorrectionParams = ColorCorrectionParams {
+    lcd_gamma:     4.0,
+    display_gamma: 2.2,
+    matrix: [
+        [ 255.0 / 280.0,  50.0 / 280.0,   0.0 / 280.0 ],
+        [  10.0 / 280.0, 230.0 / 280.0,  30.0 / 280.0 ],
+        [  50.0 / 280.0,  10.0 / 280.0, 220.0 / 280.0 ],
+    ],
+};
+
+
+/// The color correction to be applied when translating GameBoy Color palette colors
+/// into RGBA colors.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorCorrection {
+    /// The raw colors are displayed without correction.
+    #[default]
+    Off,
+
+    /// Emulates the colors of the GameBoy Color LCD.
+    CgbLcd,
+
+    /// Emulates the colors of the GameBoy Advance LCD.
+    GbaLcd,
+}
+
+
+impl ColorCorrection {
+    /// A list of all available color correction modes.
+    pub const ALL: [ColorCorrection; 3] = [
+        ColorCorrection::Off,
+        ColorCorrection::CgbLcd,
+        ColorCorrection::GbaLcd,
+    ];
+
+
+    /// Get the next color correction mode, which allows to cycle through all modes.
+    pub fn next(&self) -> Self {
+        match self {
+            ColorCorrection::Off    => ColorCorrection::CgbLcd,
+            ColorCorrection::CgbLcd => ColorCorrection::GbaLcd,
+            ColorCorrection::GbaLcd => ColorCorrection::Off,
+        }
+    }
+
+
+    /// Get the name of this color correction mode.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ColorCorrection::Off    => "Off",
+            ColorCorrection::CgbLcd => "CGB LCD",
+            ColorCorrection::GbaLcd => "GBA LCD",
+        }
+    }
+
+
+    /// Find a color correction mode by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.get_name().eq_ignore_ascii_case(name))
+    }
+
+
+    /// Translates a 16 bit color like read from GBC palettes into an RGBA color,
+    /// applying this color correction.
+    pub fn to_color(&self, color: u16) -> Color {
+        match self {
+            ColorCorrection::Off    => Color::from_rgb_555(color),
+            ColorCorrection::CgbLcd => PARAMS_CGB_LCD.apply(color),
+            ColorCorrection::GbaLcd => PARAMS_GBA_LCD.apply(color),
+        }
+    }
+}
+
+
+impl ColorCorrectionParams {
+    /// Translates a 16 bit color into an RGBA color using these parameters.
+    fn apply(&self, color: u16) -> Color {
+        let linear = [
+            self.linearize(( color        & 0x1f) as u8),
+            self.linearize(((color >>  5) & 0x1f) as u8),
+            self.linearize(((color >> 10) & 0x1f) as u8),
+        ];
+
+        let mix = |row: &[f32; 3]| -> u8 {
+            let value = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+            let value = value.clamp(0.0, 1.0).powf(1.0 / self.display_gamma);
+            (value * 255.0).round() as u8
+        };
+
+        Color {
+            r: mix(&self.matrix[0]),
+            g: mix(&self.matrix[1]),
+            b: mix(&self.matrix[2]),
+            a: 0xff,
+        }
+    }
+
+
+    /// Converts a 5 bit color channel into a linear intensity value from 0.0 to 1.0.
+    fn linearize(&self, value: u8) -> f32 {
+        (value as f32 / 31.0).powf(self.lcd_gamma)
+    }
+}
+
+
+impl Display for ColorCorrection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
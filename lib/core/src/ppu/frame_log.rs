@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::cpu::interrupts::Interrupt;
+use crate::gameboy::Clock;
+use crate::ppu::ppu::Mode;
+
+
+/// The kind of event recorded within a [ScanlineLog].
+#[derive(Copy, Clone)]
+pub enum FrameLogEventKind {
+    /// An interrupt was requested by any component.
+    Interrupt(Interrupt),
+
+    /// The current line matched the value of the LYC register.
+    LycMatch,
+
+    /// An OAM DMA transfer was started.
+    OamDmaStarted,
+
+    /// An OAM DMA transfer was completed.
+    OamDmaCompleted,
+}
+
+
+/// A single event recorded within a [ScanlineLog].
+#[derive(Copy, Clone)]
+pub struct FrameLogEvent {
+    /// The cycle within the scanline, when the event occurred.
+    pub cycle: Clock,
+
+    /// The kind of event.
+    pub kind: FrameLogEventKind,
+}
+
+
+/// Stores the point in time, when the PPU switched into another mode.
+#[derive(Copy, Clone)]
+pub struct ModeChange {
+    /// The cycle within the scanline, when the mode was entered.
+    pub cycle: Clock,
+
+    /// The mode entered.
+    pub mode: Mode,
+}
+
+
+/// Records all mode changes and events which occurred within a single scanline.
+#[derive(Clone, Default)]
+pub struct ScanlineLog {
+    /// All mode changes within this scanline in the order they occurred.
+    pub mode_changes: Vec<ModeChange>,
+
+    /// All events which occurred within this scanline in the order they occurred.
+    pub events: Vec<FrameLogEvent>,
+}
+
+
+/// Records all mode changes and events which occurred within a single frame.
+/// Each scanline is stored by its line number.
+#[derive(Clone, Default)]
+pub struct FrameLog {
+    pub lines: Vec<ScanlineLog>,
+}
+
+
+impl ScanlineLog {
+    /// Get the number of cycles spent in a given mode within this scanline.
+    pub fn get_mode_duration(&self, mode: Mode, cycles_per_line: Clock) -> Clock {
+        self.mode_changes
+            .iter()
+            .enumerate()
+            .filter(|(_, change)| change.mode == mode)
+            .map(|(index, change)| {
+                let end = match self.mode_changes.get(index + 1) {
+                    Some(next) => next.cycle,
+                    None       => cycles_per_line,
+                };
+
+                end.saturating_sub(change.cycle)
+            })
+            .sum()
+    }
+}
+
+
+impl FrameLog {
+    /// Get the log entry of a specific line, creates it if not existing.
+    pub(crate) fn get_line_mut(&mut self, line: u8) -> &mut ScanlineLog {
+        let index = line as usize;
+
+        if self.lines.len() <= index {
+            self.lines.resize_with(index + 1, ScanlineLog::default);
+        }
+
+        &mut self.lines[index]
+    }
+}
@@ -22,7 +22,10 @@ use crate::utils::get_bit;
 
 /// An RGBA color value containing a RGB value with additional alpha channel
 /// ready to be displayed on modern screens.
+/// The memory layout is fixed to RGBA order, so a buffer of colors
+/// can be passed as RGBA byte data to any frontend.
 #[derive(Copy, Clone)]
+#[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     /// 8 bit red channel.
@@ -126,6 +129,13 @@ impl Color {
         }
     }
 
+    /// Creates a color object representing black color.
+    pub fn black() -> Self {
+        Self {
+            r: 0x00, g: 0x00, b: 0x00, a: 0xff
+        }
+    }
+
     /// Creates a color object from a 32bit uint.
     pub fn from_rgba32(rgba: u32) -> Self {
         Self {
@@ -315,7 +325,12 @@ impl GbcPaletteData {
 
     /// Get the RGBA color for a specific pixel value.
     pub fn get_color(&self, pixel: &SpritePixelValue) -> Color {
-        Color::from_rgb_555(self.palette[pixel.0 as usize])
+        Color::from_rgb_555(self.get_raw_color(pixel))
+    }
+
+    /// Get the raw RGB555 value as stored in the palette RAM for a specific pixel value.
+    pub fn get_raw_color(&self, pixel: &SpritePixelValue) -> u16 {
+        self.palette[pixel.0 as usize]
     }
 }
 
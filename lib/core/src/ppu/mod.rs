@@ -15,7 +15,9 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod color_correction;
 pub mod flags;
+pub mod frame_log;
 pub mod graphic_data;
 pub mod ppu;
 pub mod sprite_image;
@@ -21,16 +21,18 @@ use std::mem::take;
 use crate::cpu::interrupts::Interrupt;
 use crate::debug::DebugEvent;
 use crate::device_type::DeviceType;
-use crate::gameboy::{Clock, DeviceConfig, EmulationType};
+use crate::gameboy::{AccuracyLevel, Clock, DeviceConfig, EmulationType};
 use crate::mmu::locations::*;
 use crate::mmu::memory_bus::{memory_map, MemoryBusConnection, MemoryBusSignals};
 use crate::mmu::memory_data::mapped::MemoryDataMapped;
 use crate::mmu::memory_data::MemoryData;
+use crate::ppu::color_correction::ColorCorrection;
+use crate::ppu::frame_log::{FrameLog, FrameLogEvent, FrameLogEventKind, ModeChange};
 use crate::ppu::flags::{LcdControl, LcdControlFlag, LcdInterruptFlag, LcdInterruptFlags};
 use crate::ppu::graphic_data::*;
 use crate::ppu::sprite_image::SpriteImage;
-use crate::ppu::video_memory::{OamRam, OamRamBank, Palettes, VideoMemory};
-use crate::utils::{get_bit, SerializableArray};
+use crate::ppu::video_memory::{OamRam, OamRamBank, Palettes, VideoMemory, VRAM_TILES_PER_BANK};
+use crate::utils::{fnv1a_hash_64, get_bit, SerializableArray};
 
 pub const SCREEN_W: u32 = 160;
 pub const SCREEN_H: u32 = 144;
@@ -44,6 +46,14 @@ pub const CPU_CYCLES_DRAW_MIN:                  Clock =    172;
 pub const CPU_CYCLES_DRAW_MAX:                  Clock =    289;
 pub const CPU_CYCLES_HBLANK_MIN:                Clock =     87;
 pub const CPU_CYCLES_HBLANK_MAX:                Clock =    204;
+pub const CPU_CYCLES_DRAW_PER_SPRITE:           Clock =     10;
+pub const CPU_CYCLES_DRAW_WINDOW:               Clock =      6;
+
+/// The number of sprites the hardware is able to display within a single scanline.
+pub const SPRITES_PER_LINE:                     u8 = 10;
+
+/// The total number of sprites stored in OAM.
+pub const SPRITES_TOTAL:                        usize = 40;
 
 pub const TILE_ATTR_BIT_VRAM_BANK:                  u8 = 3;
 pub const TILE_ATTR_BIT_H_FLIP:                     u8 = 5;
@@ -61,7 +71,7 @@ pub struct LcdBuffer {
 }
 
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     HBlank      = 0,
@@ -71,6 +81,15 @@ pub enum Mode {
 }
 
 
+/// The layers composing the image generated by the PPU.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Background  = 0,
+    Window      = 1,
+    Objects     = 2,
+}
+
+
 /// Defines the state whether the PPU is enabled or not.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LcdState {
@@ -92,13 +111,17 @@ pub struct ScanlineData {
     line: u8,
 
     /// Stores the sprites to be displayed within the current scanline.
-    sprites: [Sprite; 10],
+    /// This has space for all sprites in OAM, in case the sprite limit was disabled.
+    sprites: SerializableArray<Sprite, SPRITES_TOTAL>,
 
     /// The number of sprites found.
     sprites_found: u8,
 
     /// Stores if the window was enabled for this scanline.
     window_enabled: bool,
+
+    /// The number of cycles to be spent in Mode::DrawLine for this scanline.
+    draw_cycles: Clock,
 }
 
 
@@ -236,8 +259,36 @@ pub struct Ppu {
     /// into RGB colors to be displayed on color screens.
     dmg_display_palette: DmgDisplayPalette,
 
+    /// If in GBC mode, the color correction to be applied when translating
+    /// palette colors into RGB colors.
+    color_correction: ColorCorrection,
+
     /// The data buffer to store the actual viewport content presented to the display.
     lcd_buffer: LcdBuffer,
+
+    /// Stores for each [Layer] whether it is visible. This allows to hide layers for debugging
+    /// without changing the emulated LCDC register.
+    layers_visible: [bool; 3],
+
+    /// The maximum number of sprites to be displayed within a single scanline,
+    /// or `None` to display all sprites. The hardware limit is 10 sprites.
+    sprite_limit: Option<u8>,
+
+    /// When disabled, the PPU keeps its timing but skips writing pixels into the LCD buffer.
+    /// This is a setting of the frontend to implement frame skipping and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_render_enabled"))]
+    render_enabled: bool,
+
+    /// When enabled, the PPU records mode changes and events of each scanline into a frame log.
+    frame_log_enabled: bool,
+
+    /// The log of the frame currently being processed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame_log_current: FrameLog,
+
+    /// The log of the most recently completed frame, if any.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame_log_completed: Option<FrameLog>,
 }
 
 
@@ -284,10 +335,16 @@ impl LcdBuffer {
         &self.pixels.get()[index as usize]
     }
 
-    /// Set the value of a specific pixel.
-    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+    /// Set the RGBA color of a specific pixel.
+    pub fn set_pixel_rgba(&mut self, x: u32, y: u32, color: &Color) {
         let index = x + (y * SCREEN_W);
-        self.pixels.get_mut()[index as usize] = color;
+        self.pixels.get_mut()[index as usize] = *color;
+    }
+
+    /// Set the color of a specific pixel by a DMG color index,
+    /// which will be translated into a RGBA color by the given palette.
+    pub fn set_pixel_indexed(&mut self, x: u32, y: u32, index: &DmgLcdPixel, palette: &DmgDisplayPalette) {
+        self.set_pixel_rgba(x, y, palette.get_color(index));
     }
 
     /// Fill the whole screen with a single solid color.
@@ -303,9 +360,22 @@ impl LcdBuffer {
     }
 
     /// Get the pixel data to be displayed as a slice of bytes.
+    /// Each pixel is represented by four bytes in RGBA order with all palettes already applied.
     pub fn get_pixels_as_slice(&self) -> &[u8] {
         self.pixels.as_slice()
     }
+
+    /// Computes a hash value of the current pixel data, which can be used to compare
+    /// frames against a known reference. The hash is stable across platforms.
+    pub fn frame_hash(&self) -> u64 {
+        fnv1a_hash_64(self.get_pixels_as_slice())
+    }
+}
+
+
+impl Layer {
+    /// All layers in the order they are composed.
+    pub const ALL: [Layer; 3] = [Layer::Background, Layer::Window, Layer::Objects];
 }
 
 
@@ -313,11 +383,18 @@ impl ScanlineData {
     pub fn new() -> ScanlineData {
         ScanlineData {
             line: 0,
-            sprites: [Sprite::empty(); 10],
+            sprites: [Sprite::empty(); SPRITES_TOTAL].into(),
             sprites_found: 0,
             window_enabled: false,
+            draw_cycles: CPU_CYCLES_DRAW_MIN,
         }
     }
+
+
+    /// Get the sprites found to be displayed within this scanline.
+    pub fn get_sprites(&self) -> &[Sprite] {
+        &self.sprites[0 .. self.sprites_found as usize]
+    }
 }
 
 
@@ -335,9 +412,9 @@ impl Ppu {
             clock: 0,
             device_config,
             signals: MemoryBusSignals::default(),
-            lcd_state: LcdState::On,
+            lcd_state: LcdState::Off,
             is_first_frame: true,
-            mode: Mode::OamScan,
+            mode: Mode::HBlank,
             memory: VideoMemory::new(device_config),
             registers: PpuRegisters::default(),
             current_line: 0,
@@ -346,7 +423,14 @@ impl Ppu {
             current_scanline: ScanlineData::new(),
             window_line: 0,
             dmg_display_palette,
+            color_correction: ColorCorrection::Off,
             lcd_buffer: LcdBuffer::allow_with_color(blank_color),
+            layers_visible: [true; 3],
+            sprite_limit: Some(SPRITES_PER_LINE),
+            render_enabled: true,
+            frame_log_enabled: false,
+            frame_log_current: FrameLog::default(),
+            frame_log_completed: None,
         }
     }
     
@@ -396,7 +480,6 @@ impl Ppu {
                         if self.clock >= CPU_CYCLES_DRAW_MIN {
                             self.clock -= CPU_CYCLES_DRAW_MIN;
                             self.current_line_cycles += CPU_CYCLES_DRAW_MIN;
-                            self.current_line_cycles += 4;
                             self.enter_mode(Mode::HBlank);
 
                             self.lcd_state = LcdState::On;
@@ -423,6 +506,8 @@ impl Ppu {
             self.current_line_pixel  = 0;
             self.current_line_cycles = 80;
 
+            self.current_scanline.draw_cycles = self.compute_draw_line_cycles();
+
             self.enter_mode(Mode::DrawLine);
         }
     }
@@ -446,17 +531,45 @@ impl Ppu {
             self.process_draw_line_pixels(pixels_to_update);
         }
         else {
-            self.current_line_cycles += self.clock;
-            self.clock = 0;
+            // wait until the time computed for mode 2+3 of this scanline elapsed
+            let draw_end_cycle   = CPU_CYCLES_OAMSCAN + self.current_scanline.draw_cycles;
+            let remaining_cycles = draw_end_cycle.saturating_sub(self.current_line_cycles);
+            let cycles           = min(self.clock, remaining_cycles);
+
+            self.current_line_cycles += cycles;
+            self.clock -= cycles;
 
-            // wait for the minimum time mode 2+3 could take
-            if self.current_line_cycles >= CPU_CYCLES_OAMSCAN + CPU_CYCLES_DRAW_MIN {
+            if self.current_line_cycles >= draw_end_cycle {
                 self.enter_mode(Mode::HBlank);
             }
         }
     }
 
 
+    /// Computes the number of cycles the PPU will spend in Mode::DrawLine for the current
+    /// scanline. Each sprite found during the OAM scan and a window being visible in this
+    /// scanline will delay the pixel transfer and therefore shorten the following HBlank.
+    /// With [AccuracyLevel::Fast], the pixel transfer always takes the minimum time.
+    fn compute_draw_line_cycles(&self) -> Clock {
+        if self.device_config.accuracy < AccuracyLevel::Accurate {
+            return CPU_CYCLES_DRAW_MIN;
+        }
+
+        let window_visible =
+                self.check_lcdc(LcdControlFlag::WindowEnabled)
+            &&  self.registers.window_y <= self.current_line
+            &&  (self.registers.window_x as u32) < SCREEN_W + 7
+        ;
+
+        // sprites exceeding the hardware limit do not take additional time
+        let sprites       = min(self.current_scanline.sprites_found, SPRITES_PER_LINE);
+        let sprite_cycles = (sprites as Clock) * CPU_CYCLES_DRAW_PER_SPRITE;
+        let window_cycles = if window_visible { CPU_CYCLES_DRAW_WINDOW } else { 0 };
+
+        min(CPU_CYCLES_DRAW_MIN + sprite_cycles + window_cycles, CPU_CYCLES_DRAW_MAX)
+    }
+
+
     /// Process a number of pixels within the current scanline.
     fn process_draw_line_pixels(&mut self, pixels_to_update: Clock) {
         let window_enabled   = self.check_lcdc(LcdControlFlag::WindowEnabled);
@@ -470,11 +583,18 @@ impl Ppu {
         for _ in 0..pixels_to_update {
             // check if the window is enabled and the current screen pixel is inside the area covered by wx/wy
             if !self.current_scanline.window_enabled && window_enabled {
-                if (self.current_line_pixel+7 >= wx) && ((wy as u32) < SCREEN_H) && (wy <= self.current_line) {
+                if self.get_position_in_window_x(wx).is_some() && ((wy as u32) < SCREEN_H) && (wy <= self.current_line) {
                     self.current_scanline.window_enabled = true;
                 }
             }
 
+            // skip fetching and drawing pixels, if rendering was disabled or the first frame is processed,
+            // which does not draw any pixels
+            if !self.render_enabled || self.is_first_frame {
+                self.current_line_pixel += 1;
+                continue;
+            }
+
             // fetch background and foreground pixels, if any
             let fetched_pixel_background = self.fetch_background_pixel();
             let fetched_pixel_foreground = self.fetch_foreground_pixel();
@@ -499,26 +619,22 @@ impl Ppu {
                     &pixel_foreground
             );
 
-            // the first frame does not draw pixels
-            if !self.is_first_frame {
-                // resolve pixel color using the according palette
-                let pixel_color = match self.device_config.emulation {
-                    EmulationType::DMG => {
-                        let lcd_pixel = pixel.palette_dmg.get_color(&pixel.data.value);
-                        *self.translate_dmg_color_index(&lcd_pixel)
-                    }
+            let x = self.current_line_pixel as u32;
+            let y = self.current_line as u32;
 
-                    EmulationType::GBC => {
-                        pixel.palette_gbc.get_color(&pixel.data.value)
-                    }
-                };
+            // resolve pixel color using the according palette
+            // and write it into the LCD buffer
+            match self.device_config.emulation {
+                EmulationType::DMG => {
+                    let lcd_pixel = pixel.palette_dmg.get_color(&pixel.data.value);
+                    self.lcd_buffer.set_pixel_indexed(x, y, &lcd_pixel, &self.dmg_display_palette);
+                }
 
-                // write pixel into LCD buffer
-                self.lcd_buffer.set_pixel(
-                    self.current_line_pixel as u32,
-                    self.current_line as u32,
-                    pixel_color
-                );
+                EmulationType::GBC => {
+                    let raw_color = pixel.palette_gbc.get_raw_color(&pixel.data.value);
+                    let color     = self.color_correction.to_color(raw_color);
+                    self.lcd_buffer.set_pixel_rgba(x, y, &color);
+                }
             }
 
             // set next pixel to compute
@@ -538,10 +654,10 @@ impl Ppu {
         // disabled by clearing the LCDC bit 0
         if bg_enabled || self.device_config.is_gbc_enabled() {
             // process window pixels instead of background, if the window was enabled for this scanline
-            let tile_info = if self.current_scanline.window_enabled {
+            let tile_info = if self.current_scanline.window_enabled && self.is_layer_visible(Layer::Window) {
                 let window_tilemap_select = self.check_lcdc(LcdControlFlag::WindowTileMapSelect);
                 let window_tilemap        = TileMap::by_select_bit(window_tilemap_select);
-                let position_in_window_x  = self.current_line_pixel+7 - self.registers.window_x;
+                let position_in_window_x  = self.get_position_in_window_x(self.registers.window_x).unwrap_or(0);
                 let position_in_window_y  = self.window_line;
 
                 self.read_tilemap_properties(
@@ -551,7 +667,7 @@ impl Ppu {
                     position_in_window_y
                 )
             }
-            else {
+            else if self.is_layer_visible(Layer::Background) {
                 // otherwise just handle the normal background
 
                 let bg_tilemap_select = self.check_lcdc(LcdControlFlag::BackgroundTileMapSelect);
@@ -568,6 +684,9 @@ impl Ppu {
                     background_x,
                     background_y
                 )
+            }
+            else {
+                return PixelFetchResult::none();
             };
 
             self.read_tile_pixel(&tile_info)
@@ -578,12 +697,28 @@ impl Ppu {
     }
 
 
+    /// Get the horizontal position within the window for the current pixel of the active scanline.
+    /// The window begins at screen position `WX - 7`, so any value of WX below 7 lets the window
+    /// start left of the visible screen area, which skips the first `7 - WX` pixels of the window.
+    /// Returns `None` if the current pixel is left of the window.
+    fn get_position_in_window_x(&self, wx: u8) -> Option<u8> {
+        let screen_x_shifted = (self.current_line_pixel as u16) + 7;
+
+        if screen_x_shifted >= (wx as u16) {
+            Some((screen_x_shifted - (wx as u16)) as u8)
+        }
+        else {
+            None
+        }
+    }
+
+
     /// Fetch the foreground pixel by reading the color of any sprite on the current
     /// position within the active scanline
     pub fn fetch_foreground_pixel(&self) -> PixelFetchResult {
         let sprites_enabled = self.check_lcdc(LcdControlFlag::SpritesEnabled);
 
-        if sprites_enabled {
+        if sprites_enabled && self.is_layer_visible(Layer::Objects) {
             self.read_scanline_sprite_pixel(
                 &self.current_scanline,
                 self.current_line_pixel
@@ -661,6 +796,8 @@ impl Ppu {
     fn enter_mode(&mut self, mode: Mode) {
         self.mode = mode;
 
+        self.log_mode_change(mode);
+
         // request interrupt when entering VBlank
         match mode {
             Mode::HBlank => {
@@ -711,6 +848,11 @@ impl Ppu {
             self.current_line += 1;
         }
 
+        // after the last line, the frame log is completed
+        if self.current_line == 0 && self.frame_log_enabled {
+            self.frame_log_completed = Some(take(&mut self.frame_log_current));
+        }
+
         // also progress window line counter,
         // if the window was drawn in this line
         if self.current_scanline.window_enabled {
@@ -744,11 +886,13 @@ impl Ppu {
 
     /// Tests whether LY matches LYC and raises the according interrupt, if so.
     fn test_coincidence(&mut self) {
-        if self.is_interrupt_enabled(LcdInterruptFlag::InterruptByCoincidence) {
-            let coincidence = self.current_line == self.registers.line_compare;
+        let coincidence = self.current_line == self.registers.line_compare;
+
+        if coincidence {
+            self.log_frame_event(FrameLogEventKind::LycMatch);
 
             // fire interrupt on match
-            if coincidence {
+            if self.is_interrupt_enabled(LcdInterruptFlag::InterruptByCoincidence) {
                 self.request_interrupt(Interrupt::LcdStat);
             }
         }
@@ -762,6 +906,13 @@ impl Ppu {
     }
 
 
+    /// Called when the CPU was stopped by the STOP command.
+    /// The LCD stops displaying any image until the CPU wakes up again.
+    pub(crate) fn on_stop_mode_entered(&mut self) {
+        self.clear_screen();
+    }
+
+
     /// Clears the screen with a 'blank' color.
     fn clear_screen(&mut self) {
         self.lcd_buffer.fill(Self::get_blank_color(
@@ -772,6 +923,8 @@ impl Ppu {
 
 
     /// Reset the PPU once it get disabled.
+    /// The PPU stops immediately, regardless of the current mode and LY is reset to zero.
+    /// Registers like LYC or the scroll positions are not affected.
     fn on_ppu_reset(&mut self) {
         self.clock                  = 0;
         self.lcd_state              = LcdState::Off;
@@ -779,15 +932,22 @@ impl Ppu {
         self.current_line           = 0;
         self.current_line_cycles    = 0;
         self.current_line_pixel     = 0;
-        self.registers.line_compare = 0;
+        self.current_scanline       = ScanlineData::new();
+        self.window_line            = 0;
+        self.frame_log_current      = FrameLog::default();
         self.clear_screen();
     }
 
 
     /// The PPU just got enabled from the disabled state.
+    /// The PPU starts over at line 0, but the first line after enabling the LCD
+    /// does not perform an OAM scan, so STAT reports Mode 0 instead of Mode 2
+    /// until the PPU starts drawing. All following lines start with Mode 2 as usual.
+    /// The first line is also 4 dots shorter, so it starts at dot 4 instead of 0.
     fn on_ppu_enabled(&mut self) {
-        self.lcd_state      = LcdState::EnableFirstFrame;
-        self.is_first_frame = true;
+        self.lcd_state           = LcdState::EnableFirstFrame;
+        self.is_first_frame      = true;
+        self.current_line_cycles = 4;
 
         self.enter_mode(Mode::HBlank);
     }
@@ -798,6 +958,109 @@ impl Ppu {
         self.signals.interrupts |= interrupt;
     }
 
+
+    /// Shows or hides a layer of the image generated by the PPU. This is intended for debugging
+    /// to isolate the content of each layer and does not change the emulated LCDC register.
+    /// A hidden background or window displays as color #0 of the background palette.
+    pub fn set_layer_visible(&mut self, layer: Layer, visible: bool) {
+        self.layers_visible[layer as usize] = visible;
+    }
+
+
+    /// Checks whether a layer of the image generated by the PPU is visible.
+    pub fn is_layer_visible(&self, layer: Layer) -> bool {
+        self.layers_visible[layer as usize]
+    }
+
+
+    /// Sets the maximum number of sprites to be displayed within a single scanline.
+    /// The hardware limit is 10 sprites, any further sprites on the same scanline are dropped.
+    /// For debugging, the limit may be changed or removed by passing `None`, which allows
+    /// to display all sprites overlapping on a scanline. This does not affect the timing
+    /// of the pixel transfer, which is always based on the hardware limit.
+    pub fn set_sprite_limit(&mut self, limit: Option<u8>) {
+        self.sprite_limit = limit;
+    }
+
+
+    /// Get the maximum number of sprites to be displayed within a single scanline, if any.
+    pub fn get_sprite_limit(&self) -> Option<u8> {
+        self.sprite_limit
+    }
+
+
+    /// Enables or disables writing pixels into the LCD buffer.
+    /// While disabled, the PPU still runs through all of its modes with the same timing
+    /// and fires all interrupts and frame events, but the LCD buffer keeps its previous content.
+    /// This allows frontends to skip rendering of single frames.
+    pub fn set_render_enabled(&mut self, enabled: bool) {
+        self.render_enabled = enabled;
+    }
+
+
+    /// Checks whether writing pixels into the LCD buffer is enabled.
+    pub fn is_render_enabled(&self) -> bool {
+        self.render_enabled
+    }
+
+
+    /// Enables or disables recording mode changes and events into a frame log.
+    pub fn set_frame_log_enabled(&mut self, enabled: bool) {
+        self.frame_log_enabled = enabled;
+
+        if !enabled {
+            self.frame_log_current   = FrameLog::default();
+            self.frame_log_completed = None;
+        }
+    }
+
+
+    /// Checks whether the frame log is enabled.
+    pub fn is_frame_log_enabled(&self) -> bool {
+        self.frame_log_enabled
+    }
+
+
+    /// Takes the log of the most recently completed frame, if any.
+    pub fn take_frame_log(&mut self) -> Option<FrameLog> {
+        self.frame_log_completed.take()
+    }
+
+
+    /// Records an event into the log of the current scanline, if the frame log is enabled.
+    pub(crate) fn log_frame_event(&mut self, kind: FrameLogEventKind) {
+        if self.frame_log_enabled {
+            let cycle = self.get_line_cycle();
+
+            self.frame_log_current.get_line_mut(self.current_line).events.push(FrameLogEvent {
+                cycle,
+                kind,
+            });
+        }
+    }
+
+
+    /// Records a mode change into the log of the current scanline, if the frame log is enabled.
+    fn log_mode_change(&mut self, mode: Mode) {
+        if self.frame_log_enabled {
+            let cycle = self.get_line_cycle();
+
+            self.frame_log_current.get_line_mut(self.current_line).mode_changes.push(ModeChange {
+                cycle,
+                mode,
+            });
+        }
+    }
+
+
+    /// Get the number of cycles passed since the beginning of the current scanline.
+    fn get_line_cycle(&self) -> Clock {
+        match self.mode {
+            Mode::OamScan | Mode::VBlank  => self.clock,
+            Mode::DrawLine | Mode::HBlank => self.current_line_cycles + self.clock,
+        }
+    }
+
     /// Set the palette to be used to translate DMG LCD color values into RGBA colors.
     pub fn set_dmg_display_palette(&mut self, palette: DmgDisplayPalette) {
         self.dmg_display_palette = palette;
@@ -813,11 +1076,50 @@ impl Ppu {
         &self.dmg_display_palette
     }
 
+    /// Set the color correction to be applied when translating GBC palette colors into RGBA colors.
+    pub fn set_color_correction(&mut self, color_correction: ColorCorrection) {
+        self.color_correction = color_correction;
+    }
+
+    /// Get the color correction applied when translating GBC palette colors into RGBA colors.
+    pub fn get_color_correction(&self) -> ColorCorrection {
+        self.color_correction
+    }
+
     /// Get the RGBA color for any color color index.
     pub fn translate_dmg_color_index(&self, pixel: &DmgLcdPixel) -> &Color {
         self.get_dmg_display_palette().get_color(pixel)
     }
 
+
+    /// Get the PPU's current mode.
+    pub fn get_mode(&self) -> Mode {
+        self.mode
+    }
+
+
+    /// Checks whether the CPU is able to access the video RAM.
+    /// While the PPU is drawing a scanline, VRAM is locked, so reads by the CPU
+    /// will return 0xff and writes will be ignored. DMA and debugger accesses are not affected.
+    pub fn is_vram_accessible(&self) -> bool {
+        !matches!(self.mode, Mode::DrawLine)
+    }
+
+
+    /// Checks whether the CPU is able to access the GameBoy Color palette memory.
+    /// Like VRAM, palette memory is locked while drawing a scanline.
+    pub fn is_palette_accessible(&self) -> bool {
+        self.is_vram_accessible()
+    }
+
+
+    /// Checks whether the CPU is able to access the object attribute memory.
+    /// OAM is locked during both OAM scan and drawing a scanline.
+    pub fn is_oam_accessible(&self) -> bool {
+        !matches!(self.mode, Mode::OamScan | Mode::DrawLine)
+    }
+
+
     /// Get the index of the line currently being drawn.
     pub fn get_current_line(&self) -> u8 {
         self.current_line
@@ -828,6 +1130,13 @@ impl Ppu {
         self.current_line_pixel
     }
 
+    /// Get the number of dots passed since the beginning of the current frame,
+    /// in the range of 0 until [CPU_CYCLES_PER_FRAME]. This allows to locate events like
+    /// register writes within the frame. While the LCD is disabled, this stays at zero.
+    pub fn frame_dot(&self) -> Clock {
+        (self.current_line as Clock) * CPU_CYCLES_PER_LINE + self.get_line_cycle()
+    }
+
     /// Get the LCD buffer which contains the actual data sent to the device's display.
     pub fn get_lcd(&self) -> &LcdBuffer {
         &self.lcd_buffer
@@ -843,6 +1152,11 @@ impl Ppu {
         self.memory.oam.get_mut()
     }
 
+    /// Get the OAM table.
+    pub fn get_oam_bank(&self) -> &OamRamBank {
+        &self.memory.oam
+    }
+
     /// Get the OAM table.
     pub fn get_oam_bank_mut(&mut self) -> &mut OamRamBank {
         &mut self.memory.oam
@@ -854,10 +1168,22 @@ impl Ppu {
     }
 
     /// Get a VRAM memory bank by its index.
+    /// Since any data of the bank may be modified, all tiles
+    /// of the bank will be marked as modified.
     pub fn get_vram_mut(&mut self, bank: usize) -> &mut [u8] {
+        for tile_index in 0..VRAM_TILES_PER_BANK {
+            self.memory.mark_vram_modified(bank, tile_index);
+        }
+
         self.memory.vram_banks[bank].as_slice_mut()
     }
 
+    /// Get the index of the VRAM bank currently selected via the VBK register.
+    /// This is always 0 in DMG mode.
+    pub fn get_vram_active_bank(&self) -> u8 {
+        self.memory.vram_active_bank
+    }
+
     /// Get all palettes stored inside the PPU.
     /// This also contains the GBC palettes, even when not in GBC mode.
     pub fn get_palettes(&self) -> &Palettes {
@@ -901,8 +1227,8 @@ impl Ppu {
         let ly_plus_16 = line_number + 16;
 
         // iterate through all OAM entries
-        for oam_entry in 0..40 {
-            let sprite = self.memory.oam.get()[oam_entry as usize];
+        for oam_entry in 0..SPRITES_TOTAL {
+            let sprite = self.memory.oam.get()[oam_entry];
 
             // take a sprite if x > 0 and intersects the current scanline
             if
@@ -913,8 +1239,10 @@ impl Ppu {
                 scanline.sprites[scanline.sprites_found as usize] = sprite;
                 scanline.sprites_found += 1;
 
-                if scanline.sprites_found >= 10 {
-                    break;
+                if let Some(sprite_limit) = self.sprite_limit {
+                    if scanline.sprites_found >= sprite_limit {
+                        break;
+                    }
                 }
             }
         }
@@ -925,6 +1253,8 @@ impl Ppu {
         // On GBC this behaviour can be switched by the object priority bit in 0xff6c
         //  - 0 means OAM position has priority
         //  - 1 means X position has priority
+        // sort_by is a stable sort, so sprites sharing the same x position keep their
+        // OAM order, which lets the sprite with the lower OAM index win.
         if !self.device_config.is_gbc_enabled() || self.registers.object_priority {
             scanline.sprites[0 .. scanline.sprites_found as usize].sort_by(
                 |a, b| {
@@ -1098,6 +1428,14 @@ impl Ppu {
         SpritePixelValue::new(pixel)
     }
 
+    /// Get the generation of a sprite image in VRAM, which will be incremented
+    /// each time the image data did change. This allows to detect changes
+    /// without comparing the actual image data.
+    pub fn get_sprite_image_generation(&self, sprite_index: usize, bank: u8) -> u32 {
+        self.memory.get_vram_generation((bank & 0x01) as usize, sprite_index)
+    }
+
+
     /// Get the raw data of a sprite, as it is stored in the VRAM.
     pub fn get_sprite_image(&self, sprite_index: usize, bank: u8) -> SpriteImage {
         let vram = &self.memory.vram_banks[(bank & 0x01) as usize];
@@ -1172,6 +1510,7 @@ impl MemoryBusConnection for Ppu {
                     MEMORY_LOCATION_BCPD => {
                         match self.device_config.emulation {
                             EmulationType::DMG => 0xff,
+                            EmulationType::GBC if !self.is_palette_accessible() => 0xff,
                             EmulationType::GBC => 
                                     self.memory.palettes.gbc_background_palette_pointer.read(
                                         &self.memory.palettes.gbc_background_palette
@@ -1189,6 +1528,7 @@ impl MemoryBusConnection for Ppu {
                     MEMORY_LOCATION_OCPD => {
                         match self.device_config.emulation {
                             EmulationType::DMG => 0xff,
+                            EmulationType::GBC if !self.is_palette_accessible() => 0xff,
                             EmulationType::GBC => 
                                     self.memory.palettes.gbc_object_palette_pointer.read(
                                         &self.memory.palettes.gbc_object_palette
@@ -1210,8 +1550,8 @@ impl MemoryBusConnection for Ppu {
         memory_map!(address => {
             // Video RAM
             0x8000 ..= 0x9fff => [mapped_address] {
-                let bank = &mut self.memory.vram_banks[self.memory.vram_active_bank as usize];
-                bank.set_at(mapped_address, value)
+                let bank = self.memory.vram_active_bank as usize;
+                self.memory.write_vram(bank, mapped_address, value)
             },
 
             // OAM memory
@@ -1260,10 +1600,15 @@ impl MemoryBusConnection for Ppu {
                     }
 
                     MEMORY_LOCATION_BCPD => {
-                        self.memory.palettes.gbc_background_palette_pointer.write(
-                            &mut self.memory.palettes.gbc_background_palette,
-                            value
-                        )
+                        if self.is_palette_accessible() {
+                            self.memory.palettes.gbc_background_palette_pointer.write(
+                                &mut self.memory.palettes.gbc_background_palette,
+                                value
+                            )
+                        }
+                        else {
+                            self.memory.palettes.gbc_background_palette_pointer.skip()
+                        }
                     }
 
                     MEMORY_LOCATION_OCPS => {
@@ -1271,10 +1616,15 @@ impl MemoryBusConnection for Ppu {
                     }
 
                     MEMORY_LOCATION_OCPD => {
-                        self.memory.palettes.gbc_object_palette_pointer.write(
-                            &mut self.memory.palettes.gbc_object_palette,
-                            value
-                        )
+                        if self.is_palette_accessible() {
+                            self.memory.palettes.gbc_object_palette_pointer.write(
+                                &mut self.memory.palettes.gbc_object_palette,
+                                value
+                            )
+                        }
+                        else {
+                            self.memory.palettes.gbc_object_palette_pointer.skip()
+                        }
                     }
 
                     MEMORY_LOCATION_OPRI => {
@@ -1311,3 +1661,10 @@ fn flip_if(value: &mut u8, max_value: u8, flip: bool) {
         *value = max_value - *value - 1;
     }
 }
+
+
+/// Rendering is always enabled when restoring an emulator instance from a snapshot.
+#[cfg(feature = "serde")]
+fn default_render_enabled() -> bool {
+    true
+}
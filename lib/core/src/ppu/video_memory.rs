@@ -22,6 +22,13 @@ use crate::mmu::memory_data::{MemoryData, MemoryDataFixedSize};
 use crate::ppu::graphic_data::{DmgPalette, GbcPaletteData, Sprite};
 use crate::utils::{get_bit, SerializableArray};
 
+/// The size of a single tile image in VRAM in bytes.
+pub const VRAM_TILE_SIZE:       usize = 16;
+
+/// The number of 16 byte blocks within a single VRAM bank,
+/// including the area of the tilemaps.
+pub const VRAM_TILES_PER_BANK:  usize = 8192 / VRAM_TILE_SIZE;
+
 pub type OamRam         = SerializableArray<Sprite, 40>;
 pub type GbcPaletteRam  = SerializableArray<GbcPaletteData, 8>;
 
@@ -29,6 +36,7 @@ pub type VRamBank       = MemoryDataFixedSize<8192>;
 pub type WRamBank       = MemoryDataFixedSize<4096>;
 pub type OamRamBank     = MemoryDataMapped<OamRam>;
 pub type GbcPaletteBank = MemoryDataMapped<GbcPaletteRam>;
+pub type VRamGenerations = SerializableArray<u32, VRAM_TILES_PER_BANK>;
 
 
 /// Utility to read and write data into GameBoy Color palettes
@@ -52,6 +60,10 @@ pub struct VideoMemory {
     /// Active Video RAM Bank (0-1, CGB only)
     pub vram_active_bank: u8,
 
+    /// A generation counter for each 16 byte block of each VRAM bank,
+    /// which will be incremented each time the block's content changes.
+    pub vram_generations: Vec<VRamGenerations>,
+
     /// OAM memory: 40 sprites, 4 bytes each = 160B
     pub oam: OamRamBank,
 
@@ -100,9 +112,10 @@ impl GbcPalettePointer {
     }
 
 
-    /// Get the pointer value
+    /// Get the pointer value as read from the BCPS/OCPS register.
+    /// The unused bit #6 always reads as 1.
     pub fn get(&self) -> u8 {
-        self.pointer | ((self.auto_increment as u8) << 7)
+        self.pointer | 0b_0100_0000 | ((self.auto_increment as u8) << 7)
     }
 
 
@@ -110,7 +123,13 @@ impl GbcPalettePointer {
     /// Performs the auto increment when enabled.
     pub fn write(&mut self, palette: &mut GbcPaletteBank, value: u8) {
         palette.set_at(self.pointer as usize, value);
+        self.skip();
+    }
+
 
+    /// Handles a write operation which could not access the palette memory,
+    /// which still performs the auto increment when enabled.
+    pub fn skip(&mut self) {
         if self.auto_increment {
             self.pointer = self.pointer.wrapping_add(1) & 0x3f;
         }
@@ -134,6 +153,7 @@ impl VideoMemory {
         Self {
             vram_banks: std::iter::repeat_with(|| VRamBank::new()).take(num_vram_banks).collect(),
             vram_active_bank: 0,
+            vram_generations: std::iter::repeat_with(|| VRamGenerations::from([0; VRAM_TILES_PER_BANK])).take(num_vram_banks).collect(),
 
             oam: OamRamBank::new([Sprite::empty(); 40]),
 
@@ -143,6 +163,33 @@ impl VideoMemory {
 }
 
 
+impl VideoMemory {
+    /// Writes a value into a VRAM bank and increments the generation
+    /// of the affected tile, if the value did change.
+    pub fn write_vram(&mut self, bank: usize, mapped_address: usize, value: u8) {
+        let vram_bank = &mut self.vram_banks[bank];
+
+        if vram_bank.get_at(mapped_address) != value {
+            vram_bank.set_at(mapped_address, value);
+            self.mark_vram_modified(bank, mapped_address / VRAM_TILE_SIZE);
+        }
+    }
+
+
+    /// Increments the generation of a single tile within a VRAM bank.
+    pub fn mark_vram_modified(&mut self, bank: usize, tile_index: usize) {
+        let generation = &mut self.vram_generations[bank][tile_index];
+        *generation = generation.wrapping_add(1);
+    }
+
+
+    /// Get the generation of a single tile within a VRAM bank.
+    pub fn get_vram_generation(&self, bank: usize, tile_index: usize) -> u32 {
+        self.vram_generations[bank][tile_index]
+    }
+}
+
+
 impl Palettes {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,409 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::serial::SerialDevice;
+use crate::utils::{as_bit_flag, get_bit};
+
+/// The first magic byte starting each packet.
+pub const PRINTER_MAGIC_1: u8 = 0x88;
+
+/// The second magic byte starting each packet.
+pub const PRINTER_MAGIC_2: u8 = 0x33;
+
+/// The device ID sent by the printer after receiving the checksum of a packet.
+pub const PRINTER_DEVICE_ID: u8 = 0x81;
+
+/// Command to clear the image buffer and reset the printer's state.
+pub const PRINTER_COMMAND_INIT: u8 = 0x01;
+
+/// Command to print the data in the image buffer.
+pub const PRINTER_COMMAND_PRINT: u8 = 0x02;
+
+/// Command to append data to the image buffer.
+pub const PRINTER_COMMAND_DATA: u8 = 0x04;
+
+/// Command to request the printer's status without any further action.
+pub const PRINTER_COMMAND_STATUS: u8 = 0x0f;
+
+/// Status bit reporting the checksum of the last packet did not match.
+pub const PRINTER_STATUS_CHECKSUM_ERROR: u8 = 0;
+
+/// Status bit reporting the printer is currently printing.
+pub const PRINTER_STATUS_PRINTING: u8 = 1;
+
+/// Status bit reporting the image buffer is full.
+pub const PRINTER_STATUS_IMAGE_DATA_FULL: u8 = 2;
+
+/// Status bit reporting the image buffer contains data, which was not yet printed.
+pub const PRINTER_STATUS_UNPROCESSED_DATA: u8 = 3;
+
+/// The width of a printout in pixels.
+pub const PRINTOUT_WIDTH: u32 = 160;
+
+/// The number of bytes of a single band with 2 rows of 20 tiles each.
+const BAND_SIZE: usize = 640;
+
+/// The maximum number of bands stored in the image buffer.
+const MAX_BANDS: usize = 9;
+
+/// The number of bytes of a single tile.
+const TILE_SIZE: usize = 16;
+
+/// The number of tiles in each row of a printout.
+const TILES_PER_ROW: usize = PRINTOUT_WIDTH as usize / 8;
+
+/// The palette used when a game sends a palette value of zero.
+const DEFAULT_PALETTE: u8 = 0xe4;
+
+
+/// The image printed by a [GameBoyPrinter].
+/// Each pixel is stored as shade from 0 (white) to 3 (black) with the palette
+/// sent by the program already applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Printout {
+    /// The number of pixel rows of the image.
+    height: u32,
+
+    /// The shade of each pixel, stored row by row.
+    pixels: Vec<u8>,
+}
+
+
+/// The part of a packet the printer expects to receive next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PacketState {
+    Magic1,
+    Magic2,
+    Command,
+    Compression,
+    LengthLow,
+    LengthHigh,
+    Data,
+    ChecksumLow,
+    ChecksumHigh,
+    DeviceId,
+    Status,
+}
+
+
+/// An emulated GameBoy Printer, which can be connected to the serial port.
+/// The printer receives packets from the GameBoy, each starting with two magic bytes,
+/// followed by command, compression flag, data length, the data itself and a checksum.
+/// The printer answers each packet with its device ID and its current status.
+/// Tile data received is collected in bands of 160x16 pixels, which will be
+/// rendered into a [Printout] and passed to a callback on receiving the print command.
+pub struct GameBoyPrinter {
+    /// The part of the current packet to be received next.
+    state: PacketState,
+
+    /// The command of the current packet.
+    command: u8,
+
+    /// Whether the data of the current packet is compressed.
+    compressed: bool,
+
+    /// The length of the data of the current packet.
+    length: u16,
+
+    /// The data received with the current packet.
+    data: Vec<u8>,
+
+    /// The checksum computed from the data received.
+    checksum: u16,
+
+    /// The checksum sent with the current packet.
+    checksum_received: u16,
+
+    /// The status reported to the GameBoy.
+    status: u8,
+
+    /// The tile data received since the last print command.
+    image_buffer: Vec<u8>,
+
+    /// Invoked with each printout completed.
+    on_printed: Box<dyn FnMut(Printout) + Send>,
+}
+
+
+impl Printout {
+    /// Renders the tile data of the image buffer into a new printout.
+    /// The palette maps each color index to a shade, where bits 0 and 1 define the shade
+    /// of color 0, bits 2 and 3 the shade of color 1 and so on.
+    fn render(tile_data: &[u8], palette: u8) -> Self {
+        let palette   = if palette == 0 { DEFAULT_PALETTE } else { palette };
+        let tiles     = tile_data.len() / TILE_SIZE;
+        let tile_rows = tiles / TILES_PER_ROW;
+        let height    = tile_rows as u32 * 8;
+        let mut pixels = vec![0; (PRINTOUT_WIDTH * height) as usize];
+
+        for tile in 0 .. tile_rows * TILES_PER_ROW {
+            let tile_x = (tile % TILES_PER_ROW) * 8;
+            let tile_y = (tile / TILES_PER_ROW) * 8;
+
+            for row in 0..8 {
+                let low  = tile_data[tile * TILE_SIZE + row * 2];
+                let high = tile_data[tile * TILE_SIZE + row * 2 + 1];
+
+                for column in 0..8 {
+                    let bit   = 7 - column as u8;
+                    let color = (as_bit_flag(get_bit(high, bit), 1) | as_bit_flag(get_bit(low, bit), 0)) as usize;
+                    let shade = (palette >> (color * 2)) & 0x03;
+                    let x     = tile_x + column;
+                    let y     = tile_y + row;
+
+                    pixels[y * PRINTOUT_WIDTH as usize + x] = shade;
+                }
+            }
+        }
+
+        Self {
+            height,
+            pixels,
+        }
+    }
+
+
+    /// Get the width of the printout in pixels.
+    pub fn get_width(&self) -> u32 {
+        PRINTOUT_WIDTH
+    }
+
+
+    /// Get the height of the printout in pixels.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+
+    /// Get the shade of a specific pixel from 0 (white) to 3 (black).
+    pub fn get_shade(&self, x: u32, y: u32) -> u8 {
+        self.pixels[(y * PRINTOUT_WIDTH + x) as usize]
+    }
+
+
+    /// Get an iterator over all rows of the image, starting with the topmost one.
+    /// Each row contains the shade of each pixel from 0 (white) to 3 (black).
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.pixels.chunks(PRINTOUT_WIDTH as usize)
+    }
+}
+
+
+impl GameBoyPrinter {
+    /// Creates a new printer, which invokes the given callback with each printout completed.
+    pub fn new(on_printed: impl FnMut(Printout) + Send + 'static) -> Self {
+        Self {
+            state:              PacketState::Magic1,
+            command:            0x00,
+            compressed:         false,
+            length:             0,
+            data:               Vec::new(),
+            checksum:           0,
+            checksum_received:  0,
+            status:             0x00,
+            image_buffer:       Vec::new(),
+            on_printed:         Box::new(on_printed),
+        }
+    }
+
+
+    /// Get the status currently reported to the GameBoy.
+    pub fn get_status(&self) -> u8 {
+        self.status
+    }
+
+
+    /// Receives a single byte of the current packet and returns the byte sent in exchange.
+    fn receive(&mut self, byte: u8) -> u8 {
+        match self.state {
+            PacketState::Magic1 => {
+                if byte == PRINTER_MAGIC_1 {
+                    self.state = PacketState::Magic2;
+                }
+            }
+
+            PacketState::Magic2 => {
+                self.state = match byte {
+                    PRINTER_MAGIC_2 => PacketState::Command,
+                    PRINTER_MAGIC_1 => PacketState::Magic2,
+                    _               => PacketState::Magic1,
+                };
+            }
+
+            PacketState::Command => {
+                self.command  = byte;
+                self.checksum = byte as u16;
+                self.data.clear();
+                self.state    = PacketState::Compression;
+            }
+
+            PacketState::Compression => {
+                self.compressed = get_bit(byte, 0);
+                self.checksum   = self.checksum.wrapping_add(byte as u16);
+                self.state      = PacketState::LengthLow;
+            }
+
+            PacketState::LengthLow => {
+                self.length   = byte as u16;
+                self.checksum = self.checksum.wrapping_add(byte as u16);
+                self.state    = PacketState::LengthHigh;
+            }
+
+            PacketState::LengthHigh => {
+                self.length  |= (byte as u16) << 8;
+                self.checksum = self.checksum.wrapping_add(byte as u16);
+
+                self.state = match self.length {
+                    0 => PacketState::ChecksumLow,
+                    _ => PacketState::Data,
+                };
+            }
+
+            PacketState::Data => {
+                self.data.push(byte);
+                self.checksum = self.checksum.wrapping_add(byte as u16);
+
+                if self.data.len() >= self.length as usize {
+                    self.state = PacketState::ChecksumLow;
+                }
+            }
+
+            PacketState::ChecksumLow => {
+                self.checksum_received = byte as u16;
+                self.state             = PacketState::ChecksumHigh;
+            }
+
+            PacketState::ChecksumHigh => {
+                self.checksum_received |= (byte as u16) << 8;
+                self.state              = PacketState::DeviceId;
+            }
+
+            PacketState::DeviceId => {
+                self.process_packet();
+                self.state = PacketState::Status;
+
+                return PRINTER_DEVICE_ID;
+            }
+
+            PacketState::Status => {
+                let status = self.status;
+
+                // the printing status is reported once, after the printout was completed
+                self.status &= !as_bit_flag(true, PRINTER_STATUS_PRINTING);
+                self.state   = PacketState::Magic1;
+
+                return status;
+            }
+        }
+
+        0x00
+    }
+
+
+    /// Handles the command of a packet after it was received completely.
+    fn process_packet(&mut self) {
+        if self.checksum != self.checksum_received {
+            self.status |= as_bit_flag(true, PRINTER_STATUS_CHECKSUM_ERROR);
+            return;
+        }
+
+        self.status &= !as_bit_flag(true, PRINTER_STATUS_CHECKSUM_ERROR);
+
+        match self.command {
+            PRINTER_COMMAND_INIT => {
+                self.image_buffer.clear();
+                self.status = 0x00;
+            }
+
+            PRINTER_COMMAND_DATA => {
+                let data = if self.compressed {
+                    decompress(&self.data)
+                }
+                else {
+                    self.data.clone()
+                };
+
+                let capacity = BAND_SIZE * MAX_BANDS - self.image_buffer.len();
+                self.image_buffer.extend(data.into_iter().take(capacity));
+
+                self.update_buffer_status();
+            }
+
+            PRINTER_COMMAND_PRINT => {
+                // the print command contains number of sheets, margins, palette and exposure
+                let sheets  = self.data.first().copied().unwrap_or(0);
+                let palette = self.data.get(2).copied().unwrap_or(DEFAULT_PALETTE);
+
+                if sheets > 0 && !self.image_buffer.is_empty() {
+                    let printout = Printout::render(&self.image_buffer, palette);
+                    (self.on_printed)(printout);
+
+                    self.status |= as_bit_flag(true, PRINTER_STATUS_PRINTING);
+                }
+
+                self.image_buffer.clear();
+                self.update_buffer_status();
+            }
+
+            _ => { }
+        }
+    }
+
+
+    /// Updates the status bits reporting the state of the image buffer.
+    fn update_buffer_status(&mut self) {
+        let full        = self.image_buffer.len() >= BAND_SIZE * MAX_BANDS;
+        let unprocessed = !self.image_buffer.is_empty();
+
+        self.status = (self.status & !0b_0000_1100)
+            | as_bit_flag(full,        PRINTER_STATUS_IMAGE_DATA_FULL)
+            | as_bit_flag(unprocessed, PRINTER_STATUS_UNPROCESSED_DATA)
+        ;
+    }
+}
+
+
+impl SerialDevice for GameBoyPrinter {
+    fn on_byte_exchanged(&mut self, sent: u8) -> u8 {
+        self.receive(sent)
+    }
+}
+
+
+/// Decompresses data sent with the compression flag set.
+/// Each chunk starts with a control byte. If bit 7 is set, the following byte is repeated
+/// `(control & 0x7f) + 2` times, otherwise the following `control + 1` bytes are copied as they are.
+pub fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut input  = data.iter().copied();
+
+    while let Some(control) = input.next() {
+        if get_bit(control, 7) {
+            let count = (control & 0x7f) as usize + 2;
+
+            if let Some(value) = input.next() {
+                result.extend(std::iter::repeat_n(value, count));
+            }
+        }
+        else {
+            let count = control as usize + 1;
+            result.extend(input.by_ref().take(count));
+        }
+    }
+
+    result
+}
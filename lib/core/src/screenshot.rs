@@ -0,0 +1,76 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "png")]
+pub use with_png::ScreenshotError as ScreenshotError;
+
+#[cfg(feature = "png")]
+mod with_png {
+    use crate::gameboy::GameBoy;
+    use crate::ppu::ppu::LcdBuffer;
+    use crate::printer::Printout;
+    use image::{GrayImage, ImageFormat, Luma, RgbaImage};
+    use std::path::Path;
+
+    /// Error type returned when saving a screenshot failed.
+    pub type ScreenshotError = image::ImageError;
+
+
+    impl LcdBuffer {
+        /// Creates an RGBA image of the current LCD content.
+        pub fn to_rgba_image(&self) -> RgbaImage {
+            RgbaImage::from_raw(
+                self.get_width(),
+                self.get_height(),
+                self.get_pixels_as_slice().to_vec()
+            )
+            .expect("LCD buffer size does not match its dimensions")
+        }
+
+
+        /// Saves the current LCD content into a PNG file.
+        pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ScreenshotError> {
+            self.to_rgba_image().save_with_format(path, ImageFormat::Png)
+        }
+    }
+
+
+    impl GameBoy {
+        /// Saves the currently displayed image into a PNG file.
+        /// The image contains the 160x144 pixels of the LCD with the current display palette applied.
+        pub fn save_screenshot(&self, path: impl AsRef<Path>) -> Result<(), ScreenshotError> {
+            self.get_peripherals().ppu.get_lcd().save_png(path)
+        }
+    }
+
+
+    impl Printout {
+        /// Creates a grayscale image of the printout, mapping each shade
+        /// from 0 (white) to 3 (black) to the according gray level.
+        pub fn to_gray_image(&self) -> GrayImage {
+            GrayImage::from_fn(self.get_width(), self.get_height(), |x, y| {
+                Luma([0xff - self.get_shade(x, y) * 0x55])
+            })
+        }
+
+
+        /// Saves the printout into a PNG file.
+        pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ScreenshotError> {
+            self.to_gray_image().save_with_format(path, ImageFormat::Png)
+        }
+    }
+}
@@ -15,28 +15,74 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::collections::VecDeque;
 use std::mem::take;
 
 use crate::cpu::interrupts::Interrupt;
-use crate::gameboy::Clock;
+use crate::device_type::EmulationType;
+use crate::gameboy::{Clock, DeviceConfig};
 use crate::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
 use crate::mmu::memory_bus::{MemoryBusConnection, MemoryBusSignals};
 use crate::utils::{as_bit_flag, get_bit};
 
-const UPDATE_TIME_SERIAL_TRANSFER:      Clock = 4096;
+/// The number of cycles to shift a single bit using the internal clock of 8192 Hz.
+pub const SERIAL_BIT_CYCLES_NORMAL:      Clock = 512;
+
+/// The number of cycles to shift a single bit using the internal fast clock of 262144 Hz,
+/// which is only available on GameBoy Color.
+pub const SERIAL_BIT_CYCLES_FAST:        Clock = 16;
+
+/// The number of cycles to transfer a byte using the internal clock of 8192 Hz.
+pub const SERIAL_TRANSFER_CYCLES_NORMAL: Clock = SERIAL_BIT_CYCLES_NORMAL * 8;
+
+/// The number of cycles to transfer a byte using the internal fast clock of 262144 Hz.
+pub const SERIAL_TRANSFER_CYCLES_FAST:   Clock = SERIAL_BIT_CYCLES_FAST * 8;
+
+/// Bit of the SC register to select the internal clock.
+const SC_BIT_INTERNAL_CLOCK:    u8 = 0;
+
+/// Bit of the SC register to select the fast clock on GameBoy Color.
+const SC_BIT_FAST_CLOCK:        u8 = 1;
+
+/// Bit of the SC register to start a transfer.
+const SC_BIT_TRANSFER_ENABLED:  u8 = 7;
+
+
+/// A device which can be connected to the GameBoy's serial port,
+/// like another GameBoy via [LinkCable](crate::link_cable::LinkCable).
+/// Both sides of a connection exchange one byte simultaneously with each transfer,
+/// which is driven by the side providing the clock.
+pub trait SerialDevice : Send {
+    /// Invoked when the GameBoy completed a transfer driven by its internal clock.
+    /// Receives the byte sent by the GameBoy and returns the byte sent by the device in exchange.
+    fn on_byte_exchanged(&mut self, sent: u8) -> u8;
+
+    /// Invoked while the GameBoy is waiting for a transfer driven by an external clock.
+    /// When the device provides the clock, it receives the byte sent by the GameBoy
+    /// and returns the byte sent in exchange. Otherwise `None` keeps the GameBoy waiting.
+    fn on_external_clock(&mut self, sent: u8) -> Option<u8> {
+        _ = sent;
+        None
+    }
+}
 
 
 /// An implementation of the GameBoy's serial port.
-/// This is a simplified implementation with the main
-/// purpose of receiving data from test ROMs like Blargg's.
-/// It currently does not provide precise timing, receiving
-/// input or double speed mode.
+/// Transfers using the internal clock shift one bit of SB per clock tick, while receiving
+/// the byte of the connected [SerialDevice]. Without any device connected, the next byte
+/// queued via [SerialPort::queue_incoming] will be received, or 0xff if the queue is empty.
+/// Transfers using the external clock are waiting until the connected device provides the clock,
+/// or, without any device connected, until any incoming data was queued.
+/// Since the clock of the serial port is doubled in double speed mode as well,
+/// the duration of a transfer in CPU cycles only depends on the clock selected via SC.
 ///
 /// The output queue is disabled by default and needs to be
 /// enabled in order to store data sent.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SerialPort {
-    /// The SerialPort's clock to measure time between the transfer of each byte.
+    device_config: DeviceConfig,
+
+    /// The SerialPort's clock to measure the time since the last bit was shifted.
     clock: Clock,
 
     /// Pending output to be sent back through the memory bus.
@@ -45,67 +91,244 @@ pub struct SerialPort {
     /// The flag written by SC register to enable or disable serial data transfer.
     transfer_enabled: bool,
 
+    /// The flag written by SC register to select the internal or external clock.
+    internal_clock: bool,
+
+    /// The flag written by SC register to select the fast internal clock on GameBoy Color.
+    fast_clock: bool,
+
     /// The current byte written via SB register to be transferred.
+    /// During a transfer, the bits received are shifted into this byte.
     transfer_byte: u8,
 
+    /// The number of bits shifted in the current transfer.
+    transferred_bits: u8,
+
+    /// The byte sent with the current transfer.
+    sent_byte: u8,
+
+    /// The byte received with the current transfer.
+    received_byte: u8,
+
     /// A queue of all bytes sent by the device.
     output_queue: Vec<u8>,
 
     /// A flag to enable or disable the output queue.
     output_queue_enabled: bool,
+
+    /// A queue of bytes to be received with the next transfers, when no device is connected.
+    input_queue: VecDeque<u8>,
+
+    /// The total number of bytes transferred since the device was started.
+    transferred_bytes: u64,
+
+    /// The device connected to the serial port, if any.
+    /// This is provided by the frontend and therefore not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    device: Option<Box<dyn SerialDevice>>,
 }
 
 
 impl SerialPort {
     /// Constructs a new instance of the SerialPort.
-    pub fn new() -> SerialPort {
+    pub fn new(device_config: DeviceConfig) -> SerialPort {
         SerialPort {
+            device_config,
             clock:                  0,
             signals:                MemoryBusSignals::default(),
             transfer_enabled:       false,
+            internal_clock:         false,
+            fast_clock:             false,
             transfer_byte:          0x00,
+            transferred_bits:       0,
+            sent_byte:              0x00,
+            received_byte:          0xff,
             output_queue:           vec![],
             output_queue_enabled:   false,
+            input_queue:            VecDeque::new(),
+            transferred_bytes:      0,
+            device:                 None,
         }
     }
 
 
+    /// Resets the registers and aborts any transfer in progress.
+    /// The connected device and the input and output queues are kept.
+    pub(crate) fn reset(&mut self) {
+        self.clock              = 0;
+        self.signals            = MemoryBusSignals::default();
+        self.transfer_enabled   = false;
+        self.internal_clock     = false;
+        self.fast_clock         = false;
+        self.transfer_byte      = 0x00;
+        self.transferred_bits   = 0;
+        self.sent_byte          = 0x00;
+        self.received_byte      = 0xff;
+        self.transferred_bytes  = 0;
+    }
+
+
     /// Updates the SerialPort, perform data transfer if any data is pending.
     pub fn update(&mut self, cycles: Clock) {
-        self.clock += cycles;
-
-        if self.clock >= UPDATE_TIME_SERIAL_TRANSFER {
-            if self.transfer_enabled {
-                // store the data only if the output queue is enabled
-                if self.output_queue_enabled {
-                    self.output_queue.push(self.transfer_byte);
-                }
+        if !self.transfer_enabled {
+            return;
+        }
 
-                // after transfer completion, disable the transfer status bit
-                self.transfer_enabled = false;
+        if self.internal_clock {
+            self.clock += cycles;
 
-                // ..  and raise serial transfer interrupt
-                self.request_interrupt(Interrupt::Serial);
+            while self.transfer_enabled && self.clock >= self.get_bit_cycles() {
+                self.clock -= self.get_bit_cycles();
+                self.shift_bit();
+            }
+        }
+        else {
+            // using the external clock, the transfer waits for the connected device to provide the clock
+            let received = match &mut self.device {
+                Some(device) => device.on_external_clock(self.transfer_byte),
+                None         => self.input_queue.pop_front(),
+            };
+
+            if let Some(received) = received {
+                self.sent_byte     = self.transfer_byte;
+                self.transfer_byte = received;
+                self.complete_transfer();
             }
+        }
+    }
+
+
+    /// Shifts a single bit of the current transfer using the internal clock.
+    /// The byte sent will be exchanged with the connected device with the first bit,
+    /// the transfer is completed with the 8th bit.
+    fn shift_bit(&mut self) {
+        if self.transferred_bits == 0 {
+            self.sent_byte = self.transfer_byte;
+
+            // without any device connected, the GameBoy receives the queued data or 0xff
+            self.received_byte = match &mut self.device {
+                Some(device) => device.on_byte_exchanged(self.sent_byte),
+                None         => self.input_queue.pop_front().unwrap_or(0xff),
+            };
+        }
+
+        let bit_in = get_bit(self.received_byte, 7 - self.transferred_bits);
+
+        self.transfer_byte     = (self.transfer_byte << 1) | as_bit_flag(bit_in, 0);
+        self.transferred_bits += 1;
+
+        if self.transferred_bits == 8 {
+            self.complete_transfer();
+        }
+    }
 
 
-            self.clock -= UPDATE_TIME_SERIAL_TRANSFER;
+    /// Checks whether the fast clock is available, which is only on GameBoy Color.
+    fn is_fast_clock_available(&self) -> bool {
+        match self.device_config.emulation {
+            EmulationType::DMG => false,
+            EmulationType::GBC => true,
         }
     }
 
 
+    /// Get the number of cycles to shift a single bit using the internal clock
+    /// with the clock speed currently selected.
+    pub fn get_bit_cycles(&self) -> Clock {
+        if self.fast_clock {
+            SERIAL_BIT_CYCLES_FAST
+        }
+        else {
+            SERIAL_BIT_CYCLES_NORMAL
+        }
+    }
+
+
+    /// Get the number of cycles to transfer a byte using the internal clock
+    /// with the clock speed currently selected.
+    pub fn get_transfer_cycles(&self) -> Clock {
+        self.get_bit_cycles() * 8
+    }
+
+
+    /// Completes the current transfer after the byte sent was exchanged with the byte received.
+    fn complete_transfer(&mut self) {
+        // store the data only if the output queue is enabled
+        if self.output_queue_enabled {
+            self.output_queue.push(self.sent_byte);
+        }
+
+        self.transferred_bytes += 1;
+
+        // after transfer completion, disable the transfer status bit
+        self.transfer_enabled = false;
+
+        // ..  and raise serial transfer interrupt
+        self.request_interrupt(Interrupt::Serial);
+    }
+
+
     /// Requests an interrupt to be fired.
     fn request_interrupt(&mut self, interrupt: Interrupt) {
         self.signals.interrupts |= interrupt;
     }
 
 
+    /// Connects a device to the serial port, replacing any device connected before.
+    pub fn connect_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.device = Some(device);
+    }
+
+
+    /// Disconnects the device currently connected to the serial port and returns it, if any.
+    pub fn disconnect_device(&mut self) -> Option<Box<dyn SerialDevice>> {
+        self.device.take()
+    }
+
+
+    /// Checks whether any device is connected to the serial port.
+    pub fn is_device_connected(&self) -> bool {
+        self.device.is_some()
+    }
+
+
+    /// Takes over the device connected to another serial port.
+    pub(crate) fn take_device_from(&mut self, other: &mut SerialPort) {
+        self.device = other.device.take();
+    }
+
+
+    /// Queues data to be received by the program with the next transfers, like the response
+    /// of a peripheral device. This is only used while no [SerialDevice] is connected.
+    pub fn queue_incoming(&mut self, data: &[u8]) {
+        self.input_queue.extend(data);
+    }
+
+
+    /// Get the number of bytes queued via [SerialPort::queue_incoming], which were not yet received.
+    pub fn get_incoming_queue_len(&self) -> usize {
+        self.input_queue.len()
+    }
+
+
     /// Enables the output queue to store data sent by the program.
     pub fn enable_output_queue(&mut self, enabled: bool) {
         self.output_queue_enabled = enabled;
     }
 
 
+    /// Checks whether the output queue is enabled.
+    pub fn is_output_queue_enabled(&self) -> bool {
+        self.output_queue_enabled
+    }
+
+
+    /// Get the total number of bytes transferred since the device was started.
+    pub fn get_transferred_bytes(&self) -> u64 {
+        self.transferred_bytes
+    }
+
+
     /// Get the data currently in the output queue.
     pub fn get_output(&self) -> Vec<u8> {
         self.output_queue.clone()
@@ -154,7 +377,15 @@ impl MemoryBusConnection for SerialPort {
     fn on_read(&self, address: u16) -> u8 {
         match address {
             MEMORY_LOCATION_SB => self.transfer_byte,
-            MEMORY_LOCATION_SC => 0b_0111_1111 | as_bit_flag(self.transfer_enabled, 7),
+            MEMORY_LOCATION_SC => {
+                // the fast clock bit is unused and always read as 1 on DMG
+                let fast_clock = self.fast_clock || !self.is_fast_clock_available();
+
+                    0b_0111_1100
+                |   as_bit_flag(self.transfer_enabled, SC_BIT_TRANSFER_ENABLED)
+                |   as_bit_flag(fast_clock,            SC_BIT_FAST_CLOCK)
+                |   as_bit_flag(self.internal_clock,   SC_BIT_INTERNAL_CLOCK)
+            },
             _ => 0xff
         }
     }
@@ -163,7 +394,15 @@ impl MemoryBusConnection for SerialPort {
     fn on_write(&mut self, address: u16, value: u8) {
         match address {
             MEMORY_LOCATION_SB => self.transfer_byte    = value,
-            MEMORY_LOCATION_SC => self.transfer_enabled = get_bit(value, 7),
+            MEMORY_LOCATION_SC => {
+                self.transfer_enabled = get_bit(value, SC_BIT_TRANSFER_ENABLED);
+                self.internal_clock   = get_bit(value, SC_BIT_INTERNAL_CLOCK);
+                self.fast_clock       = get_bit(value, SC_BIT_FAST_CLOCK) && self.is_fast_clock_available();
+
+                // each transfer starts with the full duration
+                self.clock            = 0;
+                self.transferred_bits = 0;
+            },
             _ => { }
         };
     }
@@ -0,0 +1,427 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::ppu::flags::LcdControlFlag;
+use crate::ppu::graphic_data::{Color, TileMap, TileSet};
+use crate::ppu::ppu::Ppu;
+
+
+/// The width of the SuperGameBoy border in pixels.
+pub const SGB_BORDER_W: u32 = 256;
+
+/// The height of the SuperGameBoy border in pixels.
+pub const SGB_BORDER_H: u32 = 224;
+
+/// The horizontal position of the GameBoy screen within the border.
+pub const SGB_SCREEN_OFFSET_X: u32 = 48;
+
+/// The vertical position of the GameBoy screen within the border.
+pub const SGB_SCREEN_OFFSET_Y: u32 = 40;
+
+/// The number of bytes within a single packet.
+const SGB_PACKET_SIZE: usize = 16;
+
+/// The number of bytes copied from the GameBoy's VRAM in a single transfer.
+const SGB_TRANSFER_SIZE: usize = 4096;
+
+/// The number of tiles per row in the screen area being transferred.
+const SGB_TRANSFER_TILES_PER_ROW: usize = 20;
+
+/// The number of tiles per row in the GameBoy's tilemap.
+const GB_TILEMAP_W: usize = 32;
+
+/// The number of bytes of a single 2 bpp GameBoy tile.
+const GB_TILE_SIZE: usize = 16;
+
+/// The number of bytes of a single 4 bpp SNES tile.
+const SNES_TILE_SIZE: usize = 32;
+
+/// The number of tiles per row in the border tilemap.
+const BORDER_TILEMAP_W: usize = 32;
+
+/// The offset of the border tile data within the border buffer.
+const BORDER_OFFSET_TILES: usize = 0x0000;
+
+/// The offset of the border tilemap within the border buffer.
+const BORDER_OFFSET_TILEMAP: usize = 0x2000;
+
+/// The offset of the border palettes within the border buffer.
+const BORDER_OFFSET_PALETTES: usize = 0x2800;
+
+/// The total size of the border buffer.
+const BORDER_BUFFER_SIZE: usize = 0x2880;
+
+
+/// Commands sent from the GameBoy to the SuperGameBoy, which are supported by the emulator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SgbCommand {
+    /// CHR_TRN: transfers tile data of the border.
+    ChrTrn,
+
+    /// PCT_TRN: transfers the tilemap and palettes of the border.
+    PctTrn,
+}
+
+
+/// A VRAM transfer requested by a command, which will be performed
+/// with the content displayed on the next frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SgbTransfer {
+    /// Transfers 128 border tiles, either the lower or the upper half.
+    BorderTiles { upper_half: bool },
+
+    /// Transfers the border tilemap and palettes.
+    BorderTilemap,
+}
+
+
+/// Decodes packets sent by the GameBoy via writes into the JOYP register.
+/// Each packet starts with a reset pulse, where both P14 and P15 are low,
+/// followed by 128 bits where P14 low means 0 and P15 low means 1,
+/// each separated by a write with both lines high.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SgbPacketDecoder {
+    /// The value of P14 and P15 on the last write.
+    previous_lines: u8,
+
+    /// Whether a packet is currently being received.
+    receiving: bool,
+
+    /// The number of bits received for the current packet.
+    bits_received: usize,
+
+    /// The data of the current packet.
+    packet: [u8; SGB_PACKET_SIZE],
+
+    /// The data of all packets received for the current command.
+    command_data: Vec<u8>,
+}
+
+
+/// The data of the SuperGameBoy border, which was received via VRAM transfers.
+/// This contains 256 tiles in 4bpp SNES format, a 32x32 tilemap
+/// and four palettes with 16 colors each.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SgbBorderBuffer(Vec<u8>);
+
+
+/// The state of the SuperGameBoy.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SgbState {
+    /// Decoder for packets sent via JOYP.
+    decoder: SgbPacketDecoder,
+
+    /// A VRAM transfer to be performed on the next frame.
+    pending_transfer: Option<SgbTransfer>,
+
+    /// The border data received.
+    border: SgbBorderBuffer,
+
+    /// Whether a border tilemap was received, so the border can be displayed.
+    has_border: bool,
+
+    /// Incremented each time the border data changes.
+    border_generation: u32,
+}
+
+
+impl SgbCommand {
+    /// Get the command by its command code, if supported.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x13 => Some(SgbCommand::ChrTrn),
+            0x14 => Some(SgbCommand::PctTrn),
+            _    => None,
+        }
+    }
+}
+
+
+impl SgbPacketDecoder {
+    /// Creates a new packet decoder.
+    pub fn new() -> Self {
+        Self {
+            previous_lines: 0x30,
+            .. Self::default()
+        }
+    }
+
+
+    /// Handles a value written into JOYP.
+    /// Returns the data of a command, once all of its packets were received.
+    pub fn write(&mut self, value: u8) -> Option<Vec<u8>> {
+        let lines          = value & 0x30;
+        let previous_lines = self.previous_lines;
+        self.previous_lines = lines;
+
+        // bits are only sent after both lines were high
+        if previous_lines != 0x30 {
+            return None;
+        }
+
+        match lines {
+            // reset pulse: starts a new packet
+            0x00 => {
+                self.receiving     = true;
+                self.bits_received = 0;
+                self.packet        = [0; SGB_PACKET_SIZE];
+                None
+            }
+
+            // P14 low: 0 bit, P15 low: 1 bit
+            0x10 | 0x20 if self.receiving => {
+                self.receive_bit(lines == 0x10)
+            }
+
+            _ => None,
+        }
+    }
+
+
+    /// Stores a single received bit.
+    /// Returns the command data, once all packets of a command were received.
+    fn receive_bit(&mut self, bit: bool) -> Option<Vec<u8>> {
+        let byte_index = self.bits_received / 8;
+        let bit_index  = self.bits_received % 8;
+
+        if bit {
+            self.packet[byte_index] |= 1 << bit_index;
+        }
+
+        self.bits_received += 1;
+
+        if self.bits_received < SGB_PACKET_SIZE * 8 {
+            return None;
+        }
+
+        // the packet is completed; the following stop bit will be ignored
+        self.receiving = false;
+        self.command_data.extend_from_slice(&self.packet);
+
+        // the lower three bits of the first byte contain the number of packets of this command
+        let packets_expected = (self.command_data[0] & 0x07).max(1) as usize;
+
+        if self.command_data.len() >= packets_expected * SGB_PACKET_SIZE {
+            return Some(std::mem::take(&mut self.command_data));
+        }
+
+        None
+    }
+}
+
+
+impl SgbBorderBuffer {
+    /// Creates a new empty border buffer.
+    pub fn new() -> Self {
+        Self(vec![0x00; BORDER_BUFFER_SIZE])
+    }
+
+
+    /// Get the raw border data.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+
+    /// Get the color of a border pixel.
+    /// Returns `None` for transparent pixels.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        let tilemap_index = (y as usize / 8) * BORDER_TILEMAP_W + (x as usize / 8);
+        let entry_address = BORDER_OFFSET_TILEMAP + tilemap_index * 2;
+        let entry         = (self.0[entry_address] as u16) | ((self.0[entry_address + 1] as u16) << 8);
+
+        let tile    = (entry & 0x00ff) as usize;
+        let palette = ((entry >> 10) & 0x03) as usize;
+        let flip_x  = (entry & 0x4000) != 0;
+        let flip_y  = (entry & 0x8000) != 0;
+
+        let tile_x  = if flip_x { 7 - (x as usize % 8) } else { x as usize % 8 };
+        let tile_y  = if flip_y { 7 - (y as usize % 8) } else { y as usize % 8 };
+
+        // 4bpp SNES tiles store bitplanes 0+1 in the first and bitplanes 2+3 in the second half
+        let tile_address = BORDER_OFFSET_TILES + tile * SNES_TILE_SIZE + tile_y * 2;
+        let shift        = 7 - tile_x;
+        let color_index  =
+                ((self.0[tile_address     ] >> shift) & 1)
+            |   (((self.0[tile_address +  1] >> shift) & 1) << 1)
+            |   (((self.0[tile_address + 16] >> shift) & 1) << 2)
+            |   (((self.0[tile_address + 17] >> shift) & 1) << 3)
+        ;
+
+        // color #0 of each palette is transparent
+        if color_index == 0 {
+            return None;
+        }
+
+        let color_address = BORDER_OFFSET_PALETTES + (palette * 16 + color_index as usize) * 2;
+        let color         = (self.0[color_address] as u16) | ((self.0[color_address + 1] as u16) << 8);
+
+        Some(Color::from_rgb_555(color))
+    }
+
+
+    /// Renders the whole border into a list of RGBA colors with a size of
+    /// [SGB_BORDER_W] x [SGB_BORDER_H]. Transparent pixels will be filled with the given color.
+    pub fn render(&self, background: Color) -> Vec<Color> {
+        let mut pixels = Vec::with_capacity((SGB_BORDER_W * SGB_BORDER_H) as usize);
+
+        for y in 0..SGB_BORDER_H {
+            for x in 0..SGB_BORDER_W {
+                pixels.push(self.get_pixel(x, y).unwrap_or(background));
+            }
+        }
+
+        pixels
+    }
+
+
+    /// Copies the data of a VRAM transfer into the border buffer.
+    fn apply_transfer(&mut self, transfer: SgbTransfer, data: &[u8]) {
+        match transfer {
+            SgbTransfer::BorderTiles { upper_half } => {
+                let offset = BORDER_OFFSET_TILES + if upper_half { SGB_TRANSFER_SIZE } else { 0 };
+                self.0[offset .. offset + SGB_TRANSFER_SIZE].copy_from_slice(data);
+            }
+
+            SgbTransfer::BorderTilemap => {
+                let size = BORDER_BUFFER_SIZE - BORDER_OFFSET_TILEMAP;
+                self.0[BORDER_OFFSET_TILEMAP ..].copy_from_slice(&data[0 .. size]);
+            }
+        }
+    }
+}
+
+
+impl Default for SgbBorderBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl SgbState {
+    /// Creates a new SuperGameBoy state.
+    pub fn new() -> Self {
+        Self {
+            decoder:           SgbPacketDecoder::new(),
+            pending_transfer:  None,
+            border:            SgbBorderBuffer::new(),
+            has_border:        false,
+            border_generation: 0,
+        }
+    }
+
+
+    /// Handles a value written into the JOYP register.
+    pub fn on_joyp_write(&mut self, value: u8) {
+        if let Some(command_data) = self.decoder.write(value) {
+            self.handle_command(&command_data);
+        }
+    }
+
+
+    /// Handles a command received from the GameBoy.
+    fn handle_command(&mut self, data: &[u8]) {
+        let code = data[0] >> 3;
+
+        match SgbCommand::from_code(code) {
+            Some(SgbCommand::ChrTrn) => {
+                self.pending_transfer = Some(SgbTransfer::BorderTiles { upper_half: (data[1] & 0x01) != 0 });
+            }
+
+            Some(SgbCommand::PctTrn) => {
+                self.pending_transfer = Some(SgbTransfer::BorderTilemap);
+            }
+
+            None => { }
+        }
+    }
+
+
+    /// Get the VRAM transfer to be performed on the next frame, if any.
+    pub fn get_pending_transfer(&self) -> Option<SgbTransfer> {
+        self.pending_transfer
+    }
+
+
+    /// To be invoked when the PPU completed a frame.
+    /// Performs any pending VRAM transfer, which copies the tile data
+    /// of the currently displayed background into the SuperGameBoy.
+    pub fn on_frame_completed(&mut self, ppu: &Ppu) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            let data = read_transfer_data(ppu);
+            self.border.apply_transfer(transfer, &data);
+
+            if transfer == SgbTransfer::BorderTilemap {
+                self.has_border = true;
+            }
+
+            self.border_generation = self.border_generation.wrapping_add(1);
+        }
+    }
+
+
+    /// Get the border data, if a border was received.
+    pub fn get_border(&self) -> Option<&SgbBorderBuffer> {
+        if self.has_border {
+            Some(&self.border)
+        }
+        else {
+            None
+        }
+    }
+
+
+    /// Get a counter, which will be incremented each time the border data changes.
+    /// This can be used by frontends to detect when the border needs to be rendered again.
+    pub fn get_border_generation(&self) -> u32 {
+        self.border_generation
+    }
+}
+
+
+impl Default for SgbState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Reads the data transferred to the SuperGameBoy via VRAM transfer.
+/// The data is taken from the tiles displayed in the background, row by row
+/// with 20 tiles each, starting at the top left corner of the tilemap.
+fn read_transfer_data(ppu: &Ppu) -> Vec<u8> {
+    let tilemap = TileMap::by_select_bit(ppu.check_lcdc(LcdControlFlag::BackgroundTileMapSelect));
+    let tileset = TileSet::by_select_bit(ppu.check_lcdc(LcdControlFlag::TileDataSelect));
+    let vram    = ppu.get_vram(0);
+    let mut data = Vec::with_capacity(SGB_TRANSFER_SIZE);
+
+    for index in 0 .. (SGB_TRANSFER_SIZE / GB_TILE_SIZE) {
+        let row          = index / SGB_TRANSFER_TILES_PER_ROW;
+        let column       = index % SGB_TRANSFER_TILES_PER_ROW;
+        let map_address  = tilemap.base_address() as usize + row * GB_TILEMAP_W + column;
+        let tile         = vram[map_address - 0x8000];
+        let tile_address = tileset.address_of_tile(tile) as usize - 0x8000;
+
+        data.extend_from_slice(&vram[tile_address .. tile_address + GB_TILE_SIZE]);
+    }
+
+    data
+}
@@ -18,49 +18,134 @@
 #[cfg(feature = "snapshots")]
 pub use with_snapshots::Snapshot as Snapshot;
 
+#[cfg(feature = "snapshots")]
+pub use with_snapshots::SnapshotError as SnapshotError;
+
+#[cfg(feature = "snapshots")]
+pub use with_snapshots::SNAPSHOT_VERSION as SNAPSHOT_VERSION;
+
 #[cfg(feature = "snapshots")]
 mod with_snapshots {
     use crate::gameboy::GameBoy;
     use crate::utils::SerializableBuffer;
+    use std::borrow::Cow;
+    use std::fmt::{Display, Formatter};
     use std::fs::File;
     use std::io;
     use std::io::{Read, Write};
     use std::path::Path;
 
+    /// The current version of the snapshot format.
+    /// This needs to be increased each time the serialized emulator state changes
+    /// in a way which makes it incompatible with snapshots created by previous versions.
+    pub const SNAPSHOT_VERSION: u32 = 17;
+
+    /// Magic bytes at the beginning of each snapshot to identify the data.
+    const SNAPSHOT_MAGIC: [u8; 4] = *b"GEMI";
+
+    /// The size of the header preceding the serialized emulator state.
+    /// The header contains the magic bytes followed by the version number
+    /// as little endian 32 bit value.
+    const SNAPSHOT_HEADER_SIZE: usize = SNAPSHOT_MAGIC.len() + size_of::<u32>();
+
+
+    /// Errors which may occur when creating or restoring a snapshot.
+    #[derive(Debug)]
+    pub enum SnapshotError {
+        /// Reading or writing the snapshot data failed.
+        Io(io::Error),
+
+        /// The snapshot data does not start with a valid snapshot header.
+        InvalidHeader,
+
+        /// The snapshot was created with a version which is not compatible
+        /// with the current one and could not be migrated.
+        IncompatibleVersion {
+            found:    u32,
+            expected: u32,
+        },
+
+        /// Serializing or deserializing the emulator state failed.
+        InvalidData(String),
+    }
+
+
     /// Contains a full snapshot of an emulator instance,
     /// including the whole ROM and RAM.
     /// The snapshot can be used to restore the emulator
     /// instance.
     #[derive(serde::Serialize, serde::Deserialize)]
     pub struct Snapshot {
-        /// Binary form of the serialized emulator state.
+        /// Binary form of the serialized emulator state,
+        /// preceded by the snapshot header.
         data: SerializableBuffer<u8>,
     }
 
 
     impl Snapshot {
         /// Creates a new snapshot from an existing emulator instance.
-        pub fn create_from(gb: &GameBoy) -> io::Result<Self> {
-            let data = bincode::serde::encode_to_vec(
+        pub fn create_from(gb: &GameBoy) -> Result<Self, SnapshotError> {
+            let mut data = Vec::new();
+            data.extend_from_slice(&SNAPSHOT_MAGIC);
+            data.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+
+            bincode::serde::encode_into_std_write(
                 gb,
+                &mut data,
                 bincode::config::standard()
-            ).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ).map_err(|e| SnapshotError::InvalidData(e.to_string()))?;
 
             Ok(Self { data: data.into() })
         }
 
 
+        /// Creates a snapshot from its binary representation,
+        /// like it was returned by [Snapshot::get_data].
+        /// The data will not be validated until the snapshot gets restored.
+        pub fn from_data(data: Vec<u8>) -> Self {
+            Self { data: data.into() }
+        }
+
+
         /// Restores a previously serialized snapshot into a new [GameBoy] instance.
-        pub fn restore(&self) -> io::Result<GameBoy> {
+        /// Fails with [SnapshotError::IncompatibleVersion] if the snapshot was created
+        /// with an incompatible version of the emulator.
+        pub fn restore(&self) -> Result<GameBoy, SnapshotError> {
+            let version = self.get_version()?;
+            let payload = migrate(version, &self.data[SNAPSHOT_HEADER_SIZE..])?;
+
             let (result, _) = bincode::serde::decode_from_slice::<GameBoy, _>(
-                &self.data,
+                &payload,
                 bincode::config::standard()
-            ).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ).map_err(|e| SnapshotError::InvalidData(e.to_string()))?;
 
             Ok(result)
         }
 
 
+        /// Restores a previously serialized snapshot into an existing [GameBoy] instance,
+        /// replacing its current state. See [GameBoy::replace_with] for details.
+        pub fn restore_into(&self, gb: &mut GameBoy) -> Result<(), SnapshotError> {
+            let restored = self.restore()?;
+            gb.replace_with(restored);
+
+            Ok(())
+        }
+
+
+        /// Get the version of the snapshot format this snapshot was created with.
+        pub fn get_version(&self) -> Result<u32, SnapshotError> {
+            if self.data.len() < SNAPSHOT_HEADER_SIZE || self.data[0..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+                return Err(SnapshotError::InvalidHeader);
+            }
+
+            let version_bytes = &self.data[SNAPSHOT_MAGIC.len()..SNAPSHOT_HEADER_SIZE];
+            let version       = u32::from_le_bytes(version_bytes.try_into().unwrap());
+
+            Ok(version)
+        }
+
+
         /// Reads a snapshot from a file path.
         pub fn read_from_file(filepath: &Path) -> io::Result<Self> {
             let mut file = File::open(filepath)?;
@@ -85,4 +170,56 @@ mod with_snapshots {
             &self.data
         }
     }
+
+
+    /// Converts the serialized emulator state of a snapshot created with an older
+    /// version into the format of the current version.
+    /// Migrations for older versions can be added here whenever [SNAPSHOT_VERSION]
+    /// is increased, otherwise those snapshots will be rejected.
+    fn migrate(version: u32, payload: &[u8]) -> Result<Cow<'_, [u8]>, SnapshotError> {
+        match version {
+            SNAPSHOT_VERSION => Ok(Cow::Borrowed(payload)),
+
+            _ => Err(SnapshotError::IncompatibleVersion {
+                found:    version,
+                expected: SNAPSHOT_VERSION,
+            }),
+        }
+    }
+
+
+    impl Display for SnapshotError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            match self {
+                SnapshotError::Io(e)          => write!(f, "I/O error: {e}"),
+                SnapshotError::InvalidHeader  => write!(f, "Invalid snapshot header"),
+                SnapshotError::InvalidData(e) => write!(f, "Invalid snapshot data: {e}"),
+
+                SnapshotError::IncompatibleVersion { found, expected } => {
+                    write!(f, "Incompatible snapshot version {found}, expected {expected}")
+                }
+            }
+        }
+    }
+
+
+    impl std::error::Error for SnapshotError {
+    }
+
+
+    impl From<io::Error> for SnapshotError {
+        fn from(e: io::Error) -> Self {
+            SnapshotError::Io(e)
+        }
+    }
+
+
+    impl From<SnapshotError> for io::Error {
+        fn from(e: SnapshotError) -> Self {
+            match e {
+                SnapshotError::Io(e) => e,
+                _                    => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+            }
+        }
+    }
 }
@@ -61,6 +61,7 @@ struct InternalCounter {
 
 
 /// State of the TIMA counter.
+/// TIMA keeps reading 0xff until the increment which lets it overflow.
 /// When TIMA overflows from incrementing at 0xff, it will not immediately reset to TMA
 /// and fire the interrupt. Instead, it will remain in an overflow state for 4 cycles.
 /// In this state, TIMA will have the value of 0x00. After 4 cycles it will reset into
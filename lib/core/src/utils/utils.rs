@@ -155,3 +155,21 @@ pub const fn signed_overflow_add_u16(a: u16, b: i16) -> (u16, bool, bool) {
         (result, !half_carry, !carry)
     }
 }
+
+/// Computes a 64 bit FNV-1a hash of the given data.
+/// Unlike the hashers of the standard library, the result is stable across platforms and versions.
+pub const fn fnv1a_hash_64(data: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME:        u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash  = FNV_OFFSET_BASIS;
+    let mut index = 0;
+
+    while index < data.len() {
+        hash  ^= data[index] as u64;
+        hash   = hash.wrapping_mul(FNV_PRIME);
+        index += 1;
+    }
+
+    hash
+}
@@ -0,0 +1,126 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cpu::cpu::RegisterR8;
+use gemi_core::gameboy::{AccuracyLevel, GameBoy};
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_DRAW_MIN, CPU_CYCLES_PER_LINE};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Creates a new emulator instance with the given accuracy level.
+fn create_gameboy(accuracy: AccuracyLevel) -> GameBoy {
+    let mut builder = GameBoy::build();
+    builder.set_accuracy(accuracy);
+    builder.finish().unwrap()
+}
+
+
+/// Runs a program executing HALT while interrupts are disabled, but pending,
+/// followed by INC A. Returns the value of register A afterwards.
+fn run_halt_with_pending_interrupt(accuracy: AccuracyLevel) -> u8 {
+    let mut gb = create_gameboy(accuracy);
+
+    // HALT; INC A; JR -2
+    for (offset, byte) in [0x76, 0x3c, 0x18, 0xfe].iter().enumerate() {
+        gb.get_mmu_mut().write_u8(0xc000 + offset as u16, *byte);
+    }
+
+    gb.cpu.set_instruction_pointer(0xc000);
+    gb.cpu.set_r8(RegisterR8::A, 0x00);
+    gb.cpu.disable_interrupts();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x01);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x01);
+
+    for _ in 0..10 {
+        gb.run_single_step();
+    }
+
+    gb.cpu.get_r8(RegisterR8::A)
+}
+
+
+/// Measures the number of cycles spent in Mode 3 on the third scanline with 10 sprites on it.
+fn measure_draw_cycles_with_sprites(accuracy: AccuracyLevel) -> u64 {
+    let mut gb = create_gameboy(accuracy);
+
+    {
+        let mmu = gb.cpu.get_mmu_mut();
+
+        for i in 0..10 {
+            let address = MEMORY_LOCATION_OAM_BEGIN + i * 4;
+            mmu.write_u8(address,     16);
+            mmu.write_u8(address + 1, 8 + (i as u8) * 8);
+        }
+
+        // LCD, sprites and background enabled
+        mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0b_1000_0011);
+    }
+
+    let read_stat = |gb: &GameBoy| gb.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11;
+    let read_ly   = |gb: &GameBoy| gb.get_mmu().read_u8(MEMORY_LOCATION_LY);
+
+    // run until mode 3 of line 2
+    for _ in 0..(4 * CPU_CYCLES_PER_LINE) {
+        if read_ly(&gb) == 2 && read_stat(&gb) == 3 {
+            break;
+        }
+
+        gb.get_peripherals_mut().ppu.update(1);
+    }
+
+    let mut cycles = 0;
+    while read_stat(&gb) == 3 {
+        gb.get_peripherals_mut().ppu.update(1);
+        cycles += 1;
+    }
+
+    cycles
+}
+
+
+#[test]
+fn test_default_accuracy() {
+    let gb = create_gameboy_dmg();
+    assert_eq!(AccuracyLevel::Accurate, gb.get_config().accuracy);
+
+    assert!(AccuracyLevel::Fast     < AccuracyLevel::Accurate);
+    assert!(AccuracyLevel::Accurate < AccuracyLevel::Strict);
+}
+
+
+#[test]
+fn test_halt_bug() {
+    // the byte following HALT is read twice, so INC A is executed twice
+    assert_eq!(2, run_halt_with_pending_interrupt(AccuracyLevel::Accurate));
+    assert_eq!(2, run_halt_with_pending_interrupt(AccuracyLevel::Strict));
+
+    // the HALT bug is skipped in fast mode
+    assert_eq!(1, run_halt_with_pending_interrupt(AccuracyLevel::Fast));
+}
+
+
+#[test]
+fn test_draw_duration() {
+    // sprites delay the pixel transfer
+    assert!(measure_draw_cycles_with_sprites(AccuracyLevel::Accurate) > CPU_CYCLES_DRAW_MIN);
+
+    // the pixel transfer always takes the minimum time in fast mode
+    assert_eq!(CPU_CYCLES_DRAW_MIN, measure_draw_cycles_with_sprites(AccuracyLevel::Fast));
+}
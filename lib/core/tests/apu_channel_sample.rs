@@ -0,0 +1,85 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_with_channel2;
+
+
+/// Runs the APU for a number of cycles and collects the sample of channel 2 after each cycle.
+fn collect_channel2_samples(gb: &mut GameBoy, cycles: usize) -> Vec<i16> {
+    let mut samples = Vec::new();
+
+    for _ in 0..cycles {
+        gb.get_peripherals_mut().apu.update(1);
+        samples.push(gb.get_peripherals().apu.channel_sample(ChannelType::Ch2Pulse2));
+    }
+
+    samples
+}
+
+
+#[test]
+fn test_channel2_sample_amplitude() {
+    let mut gb  = create_gameboy_with_channel2(0b10, 0xf0);
+    let samples = collect_channel2_samples(&mut gb, 4096);
+
+    // with volume 15, the DAC output swings between both ends of the value range
+    assert!(samples.iter().all(|s| *s == i16::MAX || *s == -i16::MAX));
+    assert!(samples.contains(&i16::MAX));
+    assert!(samples.contains(&-i16::MAX));
+}
+
+
+#[test]
+fn test_channel2_sample_amplitude_with_lower_volume() {
+    let mut gb  = create_gameboy_with_channel2(0b10, 0x50);
+    let samples = collect_channel2_samples(&mut gb, 4096);
+
+    // volume 5 results in an amplitude of 1.0 - (5 / 7.5)
+    let expected_high = i16::MAX;
+    let expected_low  = ((1.0 - 5.0 / 7.5) * (i16::MAX as f32)) as i16;
+
+    assert!(samples.iter().all(|s| *s == expected_high || *s == expected_low));
+    assert!(samples.contains(&expected_high));
+    assert!(samples.contains(&expected_low));
+}
+
+
+#[test]
+fn test_channel_sample_does_not_advance_state() {
+    let mut gb = create_gameboy_with_channel2(0b10, 0xf0);
+    gb.get_peripherals_mut().apu.update(100);
+
+    let apu     = &gb.get_peripherals().apu;
+    let sample1 = apu.channel_sample(ChannelType::Ch2Pulse2);
+    let sample2 = apu.channel_sample(ChannelType::Ch2Pulse2);
+
+    assert_eq!(sample1, sample2);
+}
+
+
+#[test]
+fn test_silent_channel_sample() {
+    let mut gb = create_gameboy_with_channel2(0b10, 0xf0);
+
+    // disabling the DAC of channel 1 makes it silent
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR12, 0x00);
+    assert_eq!(0, gb.get_peripherals().apu.channel_sample(ChannelType::Ch1Pulse1));
+}
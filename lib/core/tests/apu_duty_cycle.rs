@@ -0,0 +1,128 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::{Clock, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_with_channel2, PULSE_CYCLES_PER_STEP};
+
+
+/// The expected patterns of each duty cycle, starting with step 0 on the most significant bit.
+const EXPECTED_PATTERNS: [u8; 4] = [
+    0b_0000_0001,
+    0b_1000_0001,
+    0b_1000_0111,
+    0b_0111_1110,
+];
+
+
+/// Runs the APU for a number of cycles.
+fn run_apu(gb: &mut GameBoy, cycles: Clock) {
+    gb.get_peripherals_mut().apu.update(cycles);
+}
+
+
+/// Checks whether channel 2 currently outputs the high portion of its wave.
+/// With full volume, the DAC converts the high portion into the negative end of the value range.
+fn is_channel2_high(gb: &GameBoy) -> bool {
+    match gb.get_peripherals().apu.channel_sample(ChannelType::Ch2Pulse2) {
+        sample if sample == -i16::MAX => true,
+        sample if sample ==  i16::MAX => false,
+        sample => panic!("Unexpected sample value {sample}"),
+    }
+}
+
+
+/// Samples the state of channel 2 in the middle of each of the next 8 steps
+/// and returns them as a pattern with step 0 on the most significant bit.
+fn collect_pattern(gb: &mut GameBoy) -> u8 {
+    let mut pattern = 0;
+
+    run_apu(gb, PULSE_CYCLES_PER_STEP / 2);
+
+    for step in 0..8 {
+        if is_channel2_high(gb) {
+            pattern |= 1 << (7 - step);
+        }
+
+        run_apu(gb, PULSE_CYCLES_PER_STEP);
+    }
+
+    pattern
+}
+
+
+#[test]
+fn test_duty_cycle_patterns() {
+    for (duty, expected_pattern) in EXPECTED_PATTERNS.iter().enumerate() {
+        let mut gb  = create_gameboy_with_channel2(duty as u8, 0xf0);
+        let pattern = collect_pattern(&mut gb);
+
+        assert_eq!(
+            *expected_pattern, pattern,
+            "Duty {duty}: expected pattern {expected_pattern:#010b}, got {pattern:#010b}"
+        );
+    }
+}
+
+
+#[test]
+fn test_duty_cycle_pattern_repeats() {
+    for (duty, expected_pattern) in EXPECTED_PATTERNS.iter().enumerate() {
+        let mut gb = create_gameboy_with_channel2(duty as u8, 0xf0);
+
+        // skip the first full pattern
+        run_apu(&mut gb, PULSE_CYCLES_PER_STEP * 8);
+
+        let pattern = collect_pattern(&mut gb);
+        assert_eq!(*expected_pattern, pattern);
+    }
+}
+
+
+#[test]
+fn test_duty_change_keeps_step() {
+    let mut gb = create_gameboy_with_channel2(0b00, 0xf0);
+
+    // proceed to step 7, which is the only high step on 12.5%
+    run_apu(&mut gb, PULSE_CYCLES_PER_STEP * 7 + PULSE_CYCLES_PER_STEP / 2);
+    assert!(is_channel2_high(&gb));
+
+    // switching to 75% without trigger continues on step 7, which is low
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR21, 0b11 << 6);
+    assert!(!is_channel2_high(&gb));
+
+    // the duty index can be read back from NR21
+    assert_eq!(0b11 << 6, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_APU_NR21) & 0b1100_0000);
+}
+
+
+#[test]
+fn test_trigger_resets_step() {
+    let mut gb = create_gameboy_with_channel2(0b10, 0xf0);
+
+    // run into the middle of the pattern, which is within the low portion on 50%
+    run_apu(&mut gb, PULSE_CYCLES_PER_STEP * 3 + PULSE_CYCLES_PER_STEP / 2);
+    assert!(!is_channel2_high(&gb));
+
+    // retrigger the channel, which restarts the pattern on step 0
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x87);
+
+    let pattern = collect_pattern(&mut gb);
+    assert_eq!(EXPECTED_PATTERNS[0b10], pattern);
+}
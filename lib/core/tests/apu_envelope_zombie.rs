@@ -0,0 +1,180 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_with_channel2, PULSE_CYCLES_PER_STEP};
+
+
+/// Creates a new emulator instance with channel 2 triggered using the given NR22 value.
+/// The channel uses the 75% duty cycle and will be moved into the high portion of it's wave,
+/// so the current volume can be observed on the channel's output.
+fn create_gameboy_in_high_step(nr22: u8) -> GameBoy {
+    let mut gb = create_gameboy_with_channel2(0b11, nr22);
+
+    // move into step 1 of the duty cycle, which is the first high step
+    gb.get_peripherals_mut().apu.update(PULSE_CYCLES_PER_STEP + PULSE_CYCLES_PER_STEP / 2);
+
+    gb
+}
+
+
+/// Writes a value into the NR22 register.
+fn write_nr22(gb: &mut GameBoy, value: u8) {
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR22, value);
+}
+
+
+/// Computes the current volume of channel 2 based on it's output while being
+/// in the high portion of the duty cycle.
+fn get_channel2_volume(gb: &GameBoy) -> u8 {
+    let sample = gb.get_peripherals().apu.channel_sample(ChannelType::Ch2Pulse2);
+    let volume = (1.0 - (sample as f32) / (i16::MAX as f32)) * 7.5;
+
+    volume.round() as u8
+}
+
+
+/// Writes a sequence of values into NR22 and checks the resulting volume after each write.
+fn check_write_sequence(gb: &mut GameBoy, sequence: &[(u8, u8)]) {
+    for (index, (value, expected_volume)) in sequence.iter().enumerate() {
+        write_nr22(gb, *value);
+
+        assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+        assert_eq!(
+            *expected_volume, get_channel2_volume(gb),
+            "Unexpected volume after write #{index} of {value:#04x}"
+        );
+    }
+}
+
+
+#[test]
+fn test_trigger_loads_volume() {
+    for volume in 1..=15 {
+        let gb = create_gameboy_in_high_step(volume << 4);
+        assert_eq!(volume, get_channel2_volume(&gb));
+    }
+}
+
+
+#[test]
+fn test_increment_with_period_zero() {
+    // volume 0, increment, period 0 increments the volume by one on each write
+    let mut gb = create_gameboy_in_high_step(0x08);
+    assert_eq!(0, get_channel2_volume(&gb));
+
+    check_write_sequence(&mut gb, &[
+        (0x08,  1),
+        (0x08,  2),
+        (0x08,  3),
+        (0x08,  4),
+    ]);
+}
+
+
+#[test]
+fn test_increment_wraps_around() {
+    let mut gb = create_gameboy_in_high_step(0xe8);
+    assert_eq!(14, get_channel2_volume(&gb));
+
+    check_write_sequence(&mut gb, &[
+        (0x08, 15),
+        (0x08,  0),
+        (0x08,  1),
+    ]);
+}
+
+
+#[test]
+fn test_decrement_mode_adds_two() {
+    // volume 5, decrement, period 1
+    let mut gb = create_gameboy_in_high_step(0x51);
+    assert_eq!(5, get_channel2_volume(&gb));
+
+    check_write_sequence(&mut gb, &[
+        (0x51,  7),
+        (0x51,  9),
+        (0x51, 11),
+        (0x51, 13),
+        (0x51, 15),
+        (0x51,  1),
+    ]);
+}
+
+
+#[test]
+fn test_increment_with_period_does_not_change_volume() {
+    // volume 5, increment, period 1
+    let mut gb = create_gameboy_in_high_step(0x59);
+    assert_eq!(5, get_channel2_volume(&gb));
+
+    check_write_sequence(&mut gb, &[
+        (0x59,  5),
+        (0x5f,  5),
+    ]);
+}
+
+
+#[test]
+fn test_direction_change_inverts_volume() {
+    // volume 5, decrement, period 1
+    let mut gb = create_gameboy_in_high_step(0x51);
+    assert_eq!(5, get_channel2_volume(&gb));
+
+    check_write_sequence(&mut gb, &[
+        // decrement: 5 + 2 = 7; direction changed: 16 - 7 = 9
+        (0x59,  9),
+
+        // increment with period 1, direction changed: 16 - 9 = 7
+        (0x51,  7),
+    ]);
+}
+
+
+#[test]
+fn test_volume_not_reloaded_until_trigger() {
+    let mut gb = create_gameboy_in_high_step(0x59);
+    assert_eq!(5, get_channel2_volume(&gb));
+
+    // the written volume is not applied without trigger
+    write_nr22(&mut gb, 0xf9);
+    assert_eq!(5, get_channel2_volume(&gb));
+
+    // retrigger the channel and move into the high portion of the duty cycle again
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x87);
+    gb.get_peripherals_mut().apu.update(PULSE_CYCLES_PER_STEP + PULSE_CYCLES_PER_STEP / 2);
+    assert_eq!(15, get_channel2_volume(&gb));
+}
+
+
+#[test]
+fn test_dac_off_disables_channel() {
+    let mut gb = create_gameboy_in_high_step(0x08);
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+    // any of the upper 5 bits keeps the DAC enabled
+    write_nr22(&mut gb, 0x08);
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+    // the DAC gets disabled, when the upper 5 bits become zero, regardless of the period
+    write_nr22(&mut gb, 0x07);
+    assert!(!gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+    assert_eq!(0, gb.get_peripherals().apu.channel_sample(ChannelType::Ch2Pulse2));
+}
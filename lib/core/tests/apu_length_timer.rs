@@ -0,0 +1,291 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::apu::APU_UPDATE_PERIOD;
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// NRx4 bit to enable the length timer.
+const NRX4_LENGTH_ENABLE: u8 = 0b_0100_0000;
+
+/// NRx4 bit to trigger the channel.
+const NRX4_TRIGGER: u8 = 0b_1000_0000;
+
+/// The maximum number of frame sequencer steps to run until a channel is expected to be disabled.
+const MAX_STEPS: usize = 1024;
+
+
+/// An emulator instance with the APU enabled, which keeps track of the frame sequencer steps.
+struct TestApu {
+    gb: GameBoy,
+
+    /// The number of frame sequencer steps since the APU was enabled.
+    steps: usize,
+}
+
+
+impl TestApu {
+    /// Creates a new emulator instance with the APU enabled and the DAC of channel 2 and 3 turned on.
+    fn new() -> Self {
+        let mut gb = create_gameboy_dmg();
+
+        let mmu = gb.get_mmu_mut();
+        mmu.write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+        mmu.write_u8(MEMORY_LOCATION_APU_NR22, 0xf0);
+        mmu.write_u8(MEMORY_LOCATION_APU_NR30, 0x80);
+
+        Self {
+            gb,
+            steps: 0,
+        }
+    }
+
+
+    /// Checks whether the next frame sequencer step will clock the length timers.
+    fn is_next_step_clocking_length(&self) -> bool {
+        self.steps.is_multiple_of(2)
+    }
+
+
+    /// Runs the APU until the next frame sequencer step was processed.
+    fn step(&mut self) {
+        self.gb.get_peripherals_mut().apu.update(APU_UPDATE_PERIOD);
+        self.steps += 1;
+    }
+
+
+    /// Runs the APU until the next frame sequencer step does not clock the length timers,
+    /// which is the first half of the length timer period.
+    fn enter_first_half(&mut self) {
+        if self.is_next_step_clocking_length() {
+            self.step();
+        }
+
+        assert!(!self.is_next_step_clocking_length());
+    }
+
+
+    /// Runs the APU until the next frame sequencer step will clock the length timers,
+    /// which is the second half of the length timer period.
+    fn enter_second_half(&mut self) {
+        if !self.is_next_step_clocking_length() {
+            self.step();
+        }
+
+        assert!(self.is_next_step_clocking_length());
+    }
+
+
+    /// Writes a value into an APU register.
+    fn write(&mut self, address: u16, value: u8) {
+        self.gb.get_mmu_mut().write_u8(address, value);
+    }
+
+
+    /// Checks whether a channel is currently enabled.
+    fn is_enabled(&self, channel: ChannelType) -> bool {
+        self.gb.get_peripherals().apu.is_channel_enabled(channel)
+    }
+
+
+    /// Runs the APU until a channel gets disabled and returns the number of length timer clocks
+    /// processed until then.
+    fn count_length_clocks(&mut self, channel: ChannelType) -> usize {
+        let mut clocks = 0;
+
+        for _ in 0..MAX_STEPS {
+            if !self.is_enabled(channel) {
+                return clocks;
+            }
+
+            if self.is_next_step_clocking_length() {
+                clocks += 1;
+            }
+
+            self.step();
+        }
+
+        panic!("Channel was not disabled after {MAX_STEPS} steps");
+    }
+
+
+    /// Lets the length timer of channel 2 expire, so it's length becomes zero.
+    fn expire_length_ch2(&mut self) {
+        self.write(MEMORY_LOCATION_APU_NR21, 63);
+        self.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+        assert_eq!(1, self.count_length_clocks(ChannelType::Ch2Pulse2));
+
+        // disable the length timer again
+        self.write(MEMORY_LOCATION_APU_NR24, 0x00);
+    }
+}
+
+
+#[test]
+fn test_enable_length_in_first_half_clocks_extra() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 60);
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER);
+
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    // the length of 4 was already clocked once when enabling the length timer
+    assert_eq!(3, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_enable_length_in_second_half_does_not_clock() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 60);
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER);
+
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    assert_eq!(4, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_extra_clock_to_zero_disables_channel() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 63);
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER);
+    assert!(apu.is_enabled(ChannelType::Ch2Pulse2));
+
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    assert!(!apu.is_enabled(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_length_already_enabled_does_not_clock() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 60);
+
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+
+    // writing the length enable bit again has no effect
+    apu.step();
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    assert_eq!(3, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_disabling_length_does_not_clock() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 60);
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+
+    // disabling the length timer freezes the counter, enabling it again in the second half continues
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, 0x00);
+    apu.step();
+    apu.step();
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    assert_eq!(3, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_enable_length_with_zero_length_does_not_clock() {
+    let mut apu = TestApu::new();
+    apu.expire_length_ch2();
+
+    // with zero length, enabling the length timer has no effect
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+    assert!(!apu.is_enabled(ChannelType::Ch2Pulse2));
+
+    // triggering the channel reloads the length in the second half to the maximum
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+    assert_eq!(64, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_trigger_with_zero_length_reloads_maximum() {
+    let mut apu = TestApu::new();
+    apu.expire_length_ch2();
+
+    // triggering without the length timer enabled reloads the maximum without an extra clock
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER);
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_LENGTH_ENABLE);
+
+    assert_eq!(64, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_trigger_with_zero_length_in_first_half_reloads_maximum_minus_one() {
+    let mut apu = TestApu::new();
+    apu.expire_length_ch2();
+
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+
+    assert_eq!(63, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
+
+
+#[test]
+fn test_trigger_with_zero_length_reloads_maximum_ch3() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR31, 255);
+    apu.write(MEMORY_LOCATION_APU_NR34, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+    assert_eq!(1, apu.count_length_clocks(ChannelType::Ch3Wave));
+
+    apu.enter_second_half();
+    apu.write(MEMORY_LOCATION_APU_NR34, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+    assert_eq!(256, apu.count_length_clocks(ChannelType::Ch3Wave));
+
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR34, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+    assert_eq!(255, apu.count_length_clocks(ChannelType::Ch3Wave));
+}
+
+
+#[test]
+fn test_trigger_after_extra_clock_to_zero_keeps_channel_enabled() {
+    let mut apu = TestApu::new();
+    apu.write(MEMORY_LOCATION_APU_NR21, 63);
+
+    // the extra clock makes the length zero, but the trigger reloads it
+    apu.enter_first_half();
+    apu.write(MEMORY_LOCATION_APU_NR24, NRX4_TRIGGER | NRX4_LENGTH_ENABLE);
+    assert!(apu.is_enabled(ChannelType::Ch2Pulse2));
+
+    assert_eq!(63, apu.count_length_clocks(ChannelType::Ch2Pulse2));
+}
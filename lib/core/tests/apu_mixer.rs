@@ -0,0 +1,275 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SamplesReceiver, SAMPLE_BUFFER_SIZE};
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::apu::mixer::PanningState;
+use gemi_core::apu::sample::{Sample, SampleResult, SampleType};
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// Creates a new emulator instance with the APU enabled.
+fn create_gameboy_with_apu_enabled() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    gb
+}
+
+
+/// Creates a new emulator instance running an endless loop with channel 1 playing a tone
+/// on the left output and channel 2 playing a tone on the right output.
+fn create_gameboy_playing_tones() -> GameBoy {
+    let mut gb = create_gameboy_with_apu_enabled();
+
+    start_idle_loop(&mut gb);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0x77);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0b_0001_0010);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR11, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR12, 0xf0);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR13, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR14, 0x87);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR21, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR22, 0xf0);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR23, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x87);
+
+    gb
+}
+
+
+/// Opens an audio channel to receive the mixed output.
+fn open_channel(gb: &mut GameBoy) -> SamplesReceiver {
+    gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::Native }
+    ).unwrap()
+}
+
+
+/// Runs a number of frames and returns the samples received for the left and right output.
+fn run_frames(gb: &mut GameBoy, receiver: &SamplesReceiver, frames: usize) -> (Vec<SampleType>, Vec<SampleType>) {
+    let mut samples = Vec::new();
+
+    for _ in 0..frames {
+        gb.run_frame();
+        receiver.take_interleaved(&mut samples);
+    }
+
+    assert!(!samples.is_empty());
+
+    (
+        samples.iter().step_by(2).copied().collect(),
+        samples.iter().skip(1).step_by(2).copied().collect(),
+    )
+}
+
+
+/// Checks whether all samples are silent, allowing a small offset left by the high pass filter.
+fn is_silent(samples: &[SampleType]) -> bool {
+    samples.iter().all(|sample| sample.abs() < 0.001)
+}
+
+
+#[test]
+fn test_decoded_panning() {
+    let mut gb = create_gameboy_with_apu_enabled();
+
+    // CH1 left + right, CH2 right only, CH3 left only, CH4 muted
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0b_0101_0011);
+
+    let panning = gb.get_peripherals().apu.get_mixer().panning();
+
+    assert_eq!(
+        panning,
+        PanningState {
+            left:  [true, false, true,  false],
+            right: [true, true,  false, false],
+        }
+    );
+
+    assert!( panning.is_left(ChannelType::Ch1Pulse1));
+    assert!( panning.is_right(ChannelType::Ch1Pulse1));
+    assert!(!panning.is_left(ChannelType::Ch2Pulse2));
+    assert!( panning.is_right(ChannelType::Ch2Pulse2));
+    assert!( panning.is_left(ChannelType::Ch3Wave));
+    assert!(!panning.is_right(ChannelType::Ch3Wave));
+    assert!(!panning.is_left(ChannelType::Ch4Noise));
+    assert!(!panning.is_right(ChannelType::Ch4Noise));
+}
+
+
+#[test]
+fn test_decoded_master_volumes() {
+    let mut gb = create_gameboy_with_apu_enabled();
+
+    // left volume 5 + VIN left, right volume 2
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0b_1100_0001);
+
+    let mixer = gb.get_peripherals().apu.get_mixer();
+    assert_eq!(mixer.master_volumes(), (5, 2));
+    assert_eq!(mixer.vin_enabled(), (true, false));
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0b_0000_1111);
+
+    let mixer = gb.get_peripherals().apu.get_mixer();
+    assert_eq!(mixer.master_volumes(), (1, 8));
+    assert_eq!(mixer.vin_enabled(), (false, true));
+}
+
+
+#[test]
+fn test_panning_cleared_on_power_off() {
+    let mut gb = create_gameboy_with_apu_enabled();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0x77);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0xff);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+
+    let mixer = gb.get_peripherals().apu.get_mixer();
+    assert_eq!(mixer.panning(), PanningState::default());
+    assert_eq!(mixer.master_volumes(), (1, 1));
+}
+
+
+#[test]
+fn test_channels_mixed_into_output() {
+    let mut gb        = create_gameboy_playing_tones();
+    let receiver      = open_channel(&mut gb);
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    assert!(!is_silent(&left));
+    assert!(!is_silent(&right));
+}
+
+
+#[test]
+fn test_muted_channel_is_silent() {
+    let mut gb = create_gameboy_playing_tones();
+    let receiver = open_channel(&mut gb);
+
+    gb.get_peripherals_mut().apu.set_channel_muted(ChannelType::Ch1Pulse1, true);
+    assert!(gb.get_peripherals().apu.get_mixer().is_channel_muted(ChannelType::Ch1Pulse1));
+    assert!(!gb.get_peripherals().apu.get_mixer().is_channel_muted(ChannelType::Ch2Pulse2));
+
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    assert!(is_silent(&left));
+    assert!(!is_silent(&right));
+
+    // the channel itself keeps running
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch1Pulse1));
+    assert_eq!(0x01, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52) & 0x01);
+
+    // unmuting the channel makes it audible again
+    gb.get_peripherals_mut().apu.set_channel_muted(ChannelType::Ch1Pulse1, false);
+    let (left, _) = run_frames(&mut gb, &receiver, 5);
+    assert!(!is_silent(&left));
+}
+
+
+#[test]
+fn test_solo_channel() {
+    let mut gb = create_gameboy_playing_tones();
+    let receiver = open_channel(&mut gb);
+
+    gb.get_peripherals_mut().apu.set_channel_solo(Some(ChannelType::Ch2Pulse2));
+    assert_eq!(Some(ChannelType::Ch2Pulse2), gb.get_peripherals().apu.get_mixer().get_channel_solo());
+
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    assert!(is_silent(&left));
+    assert!(!is_silent(&right));
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch1Pulse1));
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+    // soloing a muted channel makes it audible
+    gb.get_peripherals_mut().apu.set_channel_muted(ChannelType::Ch1Pulse1, true);
+    gb.get_peripherals_mut().apu.set_channel_solo(Some(ChannelType::Ch1Pulse1));
+
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    // the output of the high pass filter needs some time to decay after the change
+    assert!(!is_silent(&left));
+    assert!(is_silent(&right[right.len() - SAMPLE_BUFFER_SIZE..]));
+}
+
+
+#[test]
+fn test_mute_settings_not_affected_by_power_off() {
+    let mut gb = create_gameboy_playing_tones();
+
+    gb.get_peripherals_mut().apu.set_channel_muted(ChannelType::Ch4Noise, true);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    assert!(gb.get_peripherals().apu.get_mixer().is_channel_muted(ChannelType::Ch4Noise));
+}
+
+
+#[test]
+fn test_nr50_read_write() {
+    let mut gb = create_gameboy_with_apu_enabled();
+
+    for value in 0x00..=0xffu8 {
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, value);
+        assert_eq!(value, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR50));
+    }
+}
+
+
+#[test]
+fn test_vin_mixed_into_enabled_side_only() {
+    let mut gb = create_gameboy_with_apu_enabled();
+    let receiver = open_channel(&mut gb);
+
+    start_idle_loop(&mut gb);
+
+    // VIN to the left output only, all channels disabled
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0b_1111_0111);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0x00);
+    gb.get_peripherals_mut().apu.set_vin_sample(SampleResult::Audio(Sample::new(1.0)));
+
+    // the constant level will be pulled towards zero by the high pass filter,
+    // so only the first frames are checked
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    assert!(!is_silent(&left));
+    assert!(is_silent(&right));
+}
+
+
+#[test]
+fn test_vin_silent_by_default() {
+    let mut gb = create_gameboy_with_apu_enabled();
+    let receiver = open_channel(&mut gb);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0b_1111_1111);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0x00);
+
+    let (left, right) = run_frames(&mut gb, &receiver, 5);
+
+    assert!(is_silent(&left));
+    assert!(is_silent(&right));
+}
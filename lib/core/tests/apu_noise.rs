@@ -0,0 +1,86 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Number of cycles between two LFSR iterations with divider code 0 and shift 0.
+const LFSR_PERIOD: u64 = 8;
+
+/// The first output bits of the LFSR in 15 bit mode after triggering the channel.
+const REFERENCE_SEQUENCE_15_BIT: &str = "000000000000001111111111111101111111111111001111";
+
+/// The first output bits of the LFSR in 7 bit mode after triggering the channel.
+const REFERENCE_SEQUENCE_7_BIT: &str  = "000000111111011111001111010111000011011101001100";
+
+
+/// Creates a new emulator instance with the noise channel triggered using the given NR43 value.
+fn create_gameboy_with_noise(nr43: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR42, 0xf0);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR43, nr43);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR44, 0x80);
+
+    gb
+}
+
+
+/// Runs the LFSR for a number of iterations and collects the output bit of each iteration.
+fn collect_output_bits(gb: &mut GameBoy, count: usize) -> String {
+    let mut result = String::new();
+
+    for _ in 0..count {
+        gb.get_peripherals_mut().apu.update(LFSR_PERIOD);
+
+        let lfsr = gb.get_peripherals().apu.get_noise_generator().lfsr_state();
+        result.push(if (lfsr & 0x01) != 0 { '1' } else { '0' });
+    }
+
+    result
+}
+
+
+#[test]
+fn test_lfsr_reset_on_trigger() {
+    let gb = create_gameboy_with_noise(0x00);
+
+    assert_eq!(0x0000, gb.get_peripherals().apu.get_noise_generator().lfsr_state());
+}
+
+
+#[test]
+fn test_lfsr_sequence_15_bit() {
+    let mut gb = create_gameboy_with_noise(0x00);
+    let output = collect_output_bits(&mut gb, REFERENCE_SEQUENCE_15_BIT.len());
+
+    assert_eq!(REFERENCE_SEQUENCE_15_BIT, output);
+}
+
+
+#[test]
+fn test_lfsr_sequence_7_bit() {
+    let mut gb = create_gameboy_with_noise(0x08);
+    let output = collect_output_bits(&mut gb, REFERENCE_SEQUENCE_7_BIT.len());
+
+    assert_eq!(REFERENCE_SEQUENCE_7_BIT, output);
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::{Clock, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_for, trigger_channel2, PULSE_CYCLES_PER_STEP};
+
+
+/// Creates a new emulator instance of the given device type with the APU enabled.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    gb
+}
+
+
+/// Runs the APU for a number of cycles.
+fn run_apu(gb: &mut GameBoy, cycles: Clock) {
+    gb.get_peripherals_mut().apu.update(cycles);
+}
+
+
+#[test]
+fn test_pcm12_reads_channel2_output() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    trigger_channel2(&mut gb, 0b10, 0xa0);
+
+    // the first step of the duty is high, so the channel outputs its volume
+    run_apu(&mut gb, PULSE_CYCLES_PER_STEP / 2);
+    assert_eq!(0x0a, gb.get_peripherals().apu.channel_digital_output(ChannelType::Ch2Pulse2));
+    assert_eq!(0xa0, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM12));
+
+    // the second step is low
+    run_apu(&mut gb, PULSE_CYCLES_PER_STEP);
+    assert_eq!(0x00, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM12));
+
+    // other channels are silent
+    assert_eq!(0x00, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM34));
+}
+
+
+#[test]
+fn test_pcm_registers_are_read_only() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_PCM12, 0x55);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_PCM34, 0x55);
+
+    assert_eq!(0x00, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM12));
+    assert_eq!(0x00, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM34));
+}
+
+
+#[test]
+fn test_pcm_registers_not_available_on_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    trigger_channel2(&mut gb, 0b10, 0xa0);
+    run_apu(&mut gb, PULSE_CYCLES_PER_STEP / 2);
+
+    assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM12));
+    assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_PCM34));
+}
@@ -0,0 +1,188 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::apu::APU_UPDATE_PERIOD;
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// Bits of NR52, which are always read as '1'.
+const NR52_NON_READABLE_BITS: u8 = 0b_0111_0000;
+
+/// Bits of each APU register, which are always read as '1'.
+const APU_REGISTER_NON_READABLE_BITS: [u8; 22] = [
+    /* CH1 */   0x80, 0x3f, 0x00, 0xff, 0xbf,
+    /* CH2 */   0xff, 0x3f, 0x00, 0xff, 0xbf,
+    /* CH3 */   0x7f, 0xff, 0x9f, 0xff, 0xbf,
+    /* CH4 */   0xff, 0xff, 0x00, 0x00, 0xbf,
+    /* NR5x */  0x00, 0x00,
+];
+
+
+/// Creates a new emulator instance for the given device with the APU enabled
+/// after being powered off, so all channels are disabled.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    power_cycle(&mut gb);
+
+    gb
+}
+
+
+/// Turns the APU off and on again.
+fn power_cycle(gb: &mut GameBoy) {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+}
+
+
+/// Checks all registers NR10-NR51 to contain only their non readable bits.
+fn check_registers_cleared(gb: &GameBoy) {
+    for register in MEMORY_LOCATION_APU_NR10 ..= MEMORY_LOCATION_APU_NR51 {
+        let expected = APU_REGISTER_NON_READABLE_BITS[(register - MEMORY_LOCATION_APU_NR10) as usize];
+        let value    = gb.get_mmu().read_u8(register);
+
+        assert_eq!(
+            expected, value,
+            "Register {register:04x} expected value: '{expected:08b}' got '{value:08b}'"
+        );
+    }
+}
+
+
+/// Sets the length of channel 2 to 1 by writing into NR21.
+fn write_length_one(gb: &mut GameBoy) {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR21, 0x3f);
+}
+
+
+/// Triggers channel 2 with the length timer enabled and runs a single frame sequencer step,
+/// which clocks the length timer. Returns whether the channel is still enabled afterwards.
+fn is_channel2_enabled_after_length_clock(gb: &mut GameBoy) -> bool {
+    // after the APU was powered on, the first step of the frame sequencer clocks the length timer
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR22, 0xf0);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0xc0);
+    assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+    gb.get_peripherals_mut().apu.update(APU_UPDATE_PERIOD);
+
+    gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2)
+}
+
+
+#[test]
+fn test_power_off_clears_registers() {
+    for mut gb in [create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG), create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC)] {
+        for register in MEMORY_LOCATION_APU_NR10 ..= MEMORY_LOCATION_APU_NR51 {
+            gb.get_mmu_mut().write_u8(register, 0xff);
+        }
+
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+        check_registers_cleared(&gb);
+    }
+}
+
+
+#[test]
+fn test_writes_ignored_while_powered_off() {
+    for mut gb in [create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG), create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC)] {
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+
+        for register in MEMORY_LOCATION_APU_NR10 ..= MEMORY_LOCATION_APU_NR51 {
+            gb.get_mmu_mut().write_u8(register, 0xff);
+        }
+
+        check_registers_cleared(&gb);
+
+        // still cleared after powering on again
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+        check_registers_cleared(&gb);
+    }
+}
+
+
+#[test]
+fn test_nr52_while_powered_off() {
+    for mut gb in [create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG), create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC)] {
+        // enable channel 2
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR22, 0xf0);
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x80);
+        assert_eq!(NR52_NON_READABLE_BITS | 0x80 | 0x02, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52));
+
+        // powering off disables all channels
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+        assert_eq!(NR52_NON_READABLE_BITS, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52));
+
+        // channels can't be triggered while powered off
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x80);
+        assert_eq!(NR52_NON_READABLE_BITS, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52));
+
+        // powering on keeps all channels disabled
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+        assert_eq!(NR52_NON_READABLE_BITS | 0x80, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52));
+    }
+}
+
+
+#[test]
+fn test_dmg_length_writable_while_powered_off() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+    write_length_one(&mut gb);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    // the length written while powered off expires after a single clock
+    assert!(!is_channel2_enabled_after_length_clock(&mut gb));
+}
+
+
+#[test]
+fn test_dmg_length_kept_on_power_off() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    write_length_one(&mut gb);
+    power_cycle(&mut gb);
+
+    // the length written before powering off expires after a single clock
+    assert!(!is_channel2_enabled_after_length_clock(&mut gb));
+}
+
+
+#[test]
+fn test_gbc_length_not_writable_while_powered_off() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x00);
+    write_length_one(&mut gb);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    // the write was ignored, so the channel runs with the maximum length
+    assert!(is_channel2_enabled_after_length_clock(&mut gb));
+}
+
+
+#[test]
+fn test_gbc_length_reset_on_power_off() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    write_length_one(&mut gb);
+    power_cycle(&mut gb);
+
+    // the length was reset, so the channel runs with the maximum length
+    assert!(is_channel2_enabled_after_length_clock(&mut gb));
+}
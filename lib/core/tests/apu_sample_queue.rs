@@ -0,0 +1,129 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleBuffer, SampleFormat, SAMPLE_BUFFER_SIZE};
+use gemi_core::apu::sample::{Sample, StereoSample};
+use gemi_core::apu::sample_queue::{sample_queue, SAMPLE_QUEUE_CAPACITY};
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// Creates a sample buffer with all samples set to the given value.
+fn create_buffer(value: f32) -> Box<SampleBuffer> {
+    let sample = StereoSample { left: Sample::new(value), right: Sample::new(value) };
+    Box::new([sample; SAMPLE_BUFFER_SIZE].into())
+}
+
+
+/// Creates a new emulator instance running an endless loop.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+#[test]
+fn test_receive_in_order() {
+    let (sender, receiver) = sample_queue(4);
+
+    assert!(sender.send(create_buffer(1.0)).is_ok());
+    assert!(sender.send(create_buffer(2.0)).is_ok());
+    assert_eq!(2, receiver.len());
+
+    assert_eq!(1.0, receiver.try_recv().unwrap()[0].left.get_value());
+    assert_eq!(2.0, receiver.try_recv().unwrap()[0].left.get_value());
+    assert_eq!(Err(TryRecvError::Empty), receiver.try_recv().map(|_| ()));
+    assert_eq!(0, receiver.get_dropped_buffers());
+}
+
+
+#[test]
+fn test_overwrite_oldest_when_full() {
+    let (sender, receiver) = sample_queue(4);
+
+    for i in 0..10 {
+        assert!(sender.send(create_buffer(i as f32)).is_ok());
+    }
+
+    assert_eq!(4, receiver.len());
+    assert_eq!(6, receiver.get_dropped_buffers());
+    assert_eq!(6, sender.get_dropped_buffers());
+
+    // only the most recent buffers are kept
+    let values : Vec<f32> = receiver.try_iter().map(|buffer| buffer[0].left.get_value()).collect();
+    assert_eq!(vec![6.0, 7.0, 8.0, 9.0], values);
+}
+
+
+#[test]
+fn test_disconnect() {
+    let (sender, receiver) = sample_queue(4);
+    assert!(sender.send(create_buffer(1.0)).is_ok());
+    drop(sender);
+
+    // buffers sent before disconnecting are still received
+    assert!(receiver.try_recv().is_ok());
+    assert_eq!(Err(TryRecvError::Disconnected), receiver.try_recv().map(|_| ()));
+    assert_eq!(Err(RecvTimeoutError::Disconnected), receiver.recv_timeout(Duration::from_millis(1)).map(|_| ()));
+
+    // sending fails after the receiver was dropped
+    let (sender, receiver) = sample_queue(4);
+    drop(receiver);
+    assert!(sender.send(create_buffer(1.0)).is_err());
+}
+
+
+#[test]
+fn test_recv_timeout() {
+    let (sender, receiver) = sample_queue(4);
+    assert_eq!(Err(RecvTimeoutError::Timeout), receiver.recv_timeout(Duration::from_millis(1)).map(|_| ()));
+
+    let thread = std::thread::spawn(move || {
+        assert!(sender.send(create_buffer(1.0)).is_ok());
+    });
+
+    assert!(receiver.recv_timeout(Duration::from_secs(10)).is_ok());
+    thread.join().unwrap();
+}
+
+
+#[test]
+fn test_undrained_channel_stays_bounded() {
+    let mut gb   = create_gameboy();
+    let receiver = gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::Native }
+    ).unwrap();
+
+    // run for about 10 seconds without receiving any samples
+    for _ in 0..600 {
+        gb.run_frame();
+        assert!(receiver.len() <= SAMPLE_QUEUE_CAPACITY);
+    }
+
+    // the number of buffers produced exceeds the capacity by far, so most of them were dropped
+    let dropped = receiver.get_dropped_buffers();
+    assert_eq!(SAMPLE_QUEUE_CAPACITY, receiver.len());
+    assert!(dropped > (9 * 48_000 / SAMPLE_BUFFER_SIZE) as u64);
+    assert_eq!(dropped, gb.get_peripherals_mut().apu.get_audio_output().get_dropped_buffers());
+}
@@ -0,0 +1,186 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::sync::{Arc, Mutex};
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SamplesReceiver, SAMPLE_BUFFER_SIZE};
+use gemi_core::apu::sample::SampleType;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// The number of frames to be run for each measurement.
+const FRAMES: usize = 30;
+
+
+/// Creates a new emulator instance running an endless loop with channel 3
+/// playing a constant wave, which results in a DC signal on the output.
+fn create_gameboy_playing_dc() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    start_idle_loop(&mut gb);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0x77);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0x40);
+
+    for address in MEMORY_LOCATION_APU_WAVE_RAM_BEGIN ..= MEMORY_LOCATION_APU_WAVE_RAM_END {
+        gb.get_mmu_mut().write_u8(address, 0xff);
+    }
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR30, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR32, 0x20);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR33, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR34, 0x87);
+
+    gb
+}
+
+
+/// Opens an audio channel with the given sample rate and format.
+fn open_channel(gb: &mut GameBoy, sample_rate: u32, format: SampleFormat) -> SamplesReceiver {
+    gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate, format }
+    ).unwrap()
+}
+
+
+/// Runs a number of frames and collects all samples received as interleaved values.
+fn run_frames(gb: &mut GameBoy, receiver: &SamplesReceiver, frames: usize, samples: &mut Vec<SampleType>) -> usize {
+    let mut count = 0;
+
+    for _ in 0..frames {
+        gb.run_frame();
+        count += receiver.take_interleaved(samples);
+    }
+
+    count
+}
+
+
+#[test]
+fn test_take_interleaved() {
+    let mut gb      = create_gameboy_playing_dc();
+    let receiver    = open_channel(&mut gb, 48_000, SampleFormat::Native);
+    let mut samples = Vec::new();
+
+    let count = run_frames(&mut gb, &receiver, 10, &mut samples);
+
+    // samples are transmitted in full buffers only, with left and right channel alternating
+    assert_ne!(0, count);
+    assert_eq!(0, count % SAMPLE_BUFFER_SIZE);
+    assert_eq!(count * 2, samples.len());
+
+    // channel 3 is routed to the left output only
+    assert!(samples.iter().step_by(2).skip(1).any(|left| *left != 0.0));
+    assert!(samples.iter().skip(1).step_by(2).all(|right| *right == 0.0));
+}
+
+
+#[test]
+fn test_f32_samples_within_range() {
+    let mut gb      = create_gameboy_playing_dc();
+    let receiver    = open_channel(&mut gb, 48_000, SampleFormat::F32);
+    let mut samples = Vec::new();
+
+    assert_eq!(SampleFormat::F32, gb.get_peripherals_mut().apu.get_audio_output().get_sample_format());
+
+    run_frames(&mut gb, &receiver, 10, &mut samples);
+
+    assert!(!samples.is_empty());
+    assert!(samples.iter().all(|sample| (-1.0 ..= 1.0).contains(sample)));
+}
+
+
+#[test]
+fn test_change_sample_rate() {
+    let mut gb      = create_gameboy_playing_dc();
+    let receiver    = open_channel(&mut gb, 48_000, SampleFormat::F32);
+    let mut samples = Vec::new();
+
+    let count_48k = run_frames(&mut gb, &receiver, FRAMES, &mut samples);
+
+    gb.get_peripherals_mut().apu.get_audio_output().set_sample_rate(24_000);
+    assert_eq!(24_000, gb.get_peripherals_mut().apu.get_audio_output().get_sample_rate());
+
+    let count_24k = run_frames(&mut gb, &receiver, FRAMES, &mut samples);
+
+    // about half the number of samples is generated, with a tolerance of one buffer
+    // for the samples pending in the buffer not yet transmitted
+    let expected = count_48k / 2;
+    assert!(count_24k + SAMPLE_BUFFER_SIZE >= expected, "{count_24k} vs. {expected}");
+    assert!(count_24k <= expected + SAMPLE_BUFFER_SIZE, "{count_24k} vs. {expected}");
+}
+
+
+#[test]
+fn test_no_step_on_sample_rate_change() {
+    for (rate_before, rate_after) in [(48_000, 22_050), (22_050, 48_000), (48_000, 44_100)] {
+        let mut gb      = create_gameboy_playing_dc();
+        let receiver    = open_channel(&mut gb, rate_before, SampleFormat::Native);
+        let mut samples = Vec::new();
+
+        // change the rate in the middle of a sample, before the signal decayed
+        run_frames(&mut gb, &receiver, 1, &mut samples);
+        for _ in 0..5 {
+            gb.run_single_step();
+        }
+
+        gb.get_peripherals_mut().apu.get_audio_output().set_sample_rate(rate_after);
+        run_frames(&mut gb, &receiver, 4, &mut samples);
+
+        // the DC signal only decays slowly through the high pass filter, so except
+        // for the channel being turned on, there must not be any step between two samples
+        let left : Vec<SampleType> = samples.iter().step_by(2).skip(2).copied().collect();
+        assert!(left.len() >= SAMPLE_BUFFER_SIZE - 2);
+        assert!(left[0].abs() > 0.1);
+
+        let max_step = left.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, SampleType::max);
+
+        assert!(max_step < 0.005, "step of {max_step} when changing from {rate_before} to {rate_after}");
+    }
+}
+
+
+#[test]
+fn test_callback_channel_receives_samples() {
+    let mut gb   = create_gameboy_playing_dc();
+    let received = Arc::new(Mutex::new(Vec::<SampleType>::new()));
+    let target   = received.clone();
+
+    let handle = gb.get_peripherals_mut().apu.get_audio_output().open_callback_channel(
+        AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::F32 },
+        move |buffer| {
+            target.lock().unwrap().extend(buffer.iter().map(|sample| sample.left.get_value()));
+        }
+    ).unwrap();
+
+    for _ in 0..FRAMES {
+        gb.run_frame();
+    }
+
+    // give the background thread time to process the pending buffers before stopping it
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    drop(handle);
+
+    let samples = received.lock().unwrap();
+    assert!(samples.len() >= SAMPLE_BUFFER_SIZE * 4, "received {} samples", samples.len());
+    assert_eq!(0, samples.len() % SAMPLE_BUFFER_SIZE);
+    assert!(samples.iter().any(|left| *left != 0.0));
+}
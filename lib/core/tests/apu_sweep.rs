@@ -0,0 +1,208 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Creates a new emulator instance with the APU and the DAC of channel 1 enabled.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR12, 0xf0);
+
+    gb
+}
+
+
+/// Triggers channel 1 with the given sweep settings and frequency.
+fn trigger_channel1(gb: &mut GameBoy, nr10: u8, frequency: u16) {
+    let mmu = gb.cpu.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR10, nr10);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR13, (frequency & 0xff) as u8);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR14, 0x80 | ((frequency >> 8) & 0x07) as u8);
+}
+
+
+/// Checks whether channel 1 is enabled by reading NR52.
+fn is_channel1_enabled(gb: &GameBoy) -> bool {
+    (gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_APU_NR52) & 0x01) != 0
+}
+
+
+#[test]
+fn test_trigger_without_overflow() {
+    let mut gb = create_gameboy();
+
+    // 0x400 + (0x400 >> 1) does not overflow
+    trigger_channel1(&mut gb, 0b_0000_0001, 0x400);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_trigger_with_overflow_disables_channel() {
+    let mut gb = create_gameboy();
+
+    // 0x7ff + (0x7ff >> 1) overflows on the calculation on trigger
+    trigger_channel1(&mut gb, 0b_0000_0001, 0x7ff);
+    assert!(!is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_trigger_with_overflow_and_shift_zero() {
+    let mut gb = create_gameboy();
+
+    // without shift there's no calculation on trigger
+    trigger_channel1(&mut gb, 0b_0001_0000, 0x7ff);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_negate_clear_after_calculation_disables_channel() {
+    let mut gb = create_gameboy();
+
+    // trigger with negate mode and non-zero shift performs a calculation in negate mode
+    trigger_channel1(&mut gb, 0b_0000_1001, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // leaving negate mode disables the channel
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0000_0001);
+    assert!(!is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_negate_clear_without_calculation() {
+    let mut gb = create_gameboy();
+
+    // with shift and period being zero, no calculation was performed
+    trigger_channel1(&mut gb, 0b_0000_1000, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // leaving negate mode keeps the channel enabled
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0000_0000);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+/// Runs the APU long enough for the frame sequencer to clock the frequency sweep at least once.
+fn run_until_sweep_clocked(gb: &mut GameBoy) {
+    gb.get_peripherals_mut().apu.update(8192 * 4);
+}
+
+
+#[test]
+fn test_negate_change_within_negate_mode() {
+    let mut gb = create_gameboy();
+
+    trigger_channel1(&mut gb, 0b_0000_1001, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // changing shift or period while staying in negate mode keeps the channel enabled
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0010_1011);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_negate_latch_cleared_on_trigger() {
+    let mut gb = create_gameboy();
+
+    // the calculation on trigger sets the latch
+    trigger_channel1(&mut gb, 0b_0000_1001, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // retrigger without any calculation clears the latch
+    trigger_channel1(&mut gb, 0b_0000_1000, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // leaving negate mode keeps the channel enabled
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0000_0000);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_negate_latch_set_again_after_retrigger() {
+    let mut gb = create_gameboy();
+
+    trigger_channel1(&mut gb, 0b_0000_1001, 0x400);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0000_0001);
+    assert!(!is_channel1_enabled(&gb));
+
+    // retrigger enables the channel again and performs another calculation in negate mode
+    trigger_channel1(&mut gb, 0b_0000_1001, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0000_0001);
+    assert!(!is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_negate_latch_set_by_periodic_calculation() {
+    let mut gb = create_gameboy();
+
+    // with shift zero, there's no calculation on trigger
+    trigger_channel1(&mut gb, 0b_0001_1000, 0x400);
+    assert!(is_channel1_enabled(&gb));
+
+    // the sweep unit performs a calculation once being clocked, even with shift zero
+    run_until_sweep_clocked(&mut gb);
+    assert!(is_channel1_enabled(&gb));
+
+    // leaving negate mode disables the channel
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0001_0000);
+    assert!(!is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_no_negate_latch_in_addition_mode() {
+    let mut gb = create_gameboy();
+
+    // calculations in addition mode do not set the latch
+    trigger_channel1(&mut gb, 0b_0001_0001, 0x100);
+    run_until_sweep_clocked(&mut gb);
+    assert!(is_channel1_enabled(&gb));
+
+    // entering and leaving negate mode without calculation keeps the channel enabled
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0001_1001);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR10, 0b_0001_0001);
+    assert!(is_channel1_enabled(&gb));
+}
+
+
+#[test]
+fn test_second_overflow_check_on_sweep_update() {
+    let mut gb = create_gameboy();
+
+    // 0x500 + (0x500 >> 1) = 0x780 does not overflow on trigger
+    trigger_channel1(&mut gb, 0b_0001_0001, 0x500);
+    assert!(is_channel1_enabled(&gb));
+
+    // the update applies 0x780, but the second check 0x780 + (0x780 >> 1) overflows
+    run_until_sweep_clocked(&mut gb);
+    assert!(!is_channel1_enabled(&gb));
+}
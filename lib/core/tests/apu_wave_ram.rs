@@ -0,0 +1,214 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::{Clock, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// The frequency used for the wave channel.
+const FREQUENCY: u16 = 2046;
+
+/// The number of cycles between two samples being read from Wave RAM with the frequency above.
+const CYCLES_PER_SAMPLE: Clock = (2048 - FREQUENCY as Clock) * 2;
+
+/// The number of cycles after triggering the channel until the first sample is read.
+const CYCLES_UNTIL_FIRST_SAMPLE: Clock = 6;
+
+
+/// The value initially stored in Wave RAM on a given index.
+fn wave_ram_value(index: u16) -> u8 {
+    ((index as u8) << 4) | (0x0f - index as u8)
+}
+
+
+/// Creates a new emulator instance with the APU enabled and Wave RAM filled with a known pattern.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    let mmu = gb.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    for index in 0..16 {
+        mmu.write_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN + index, wave_ram_value(index));
+    }
+
+    gb
+}
+
+
+/// Enables the DAC of the wave channel and triggers it.
+fn trigger_channel(gb: &mut GameBoy) {
+    let mmu = gb.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR30, 0x80);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR33, (FREQUENCY & 0xff) as u8);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR34, 0x80 | ((FREQUENCY >> 8) as u8 & 0x07));
+}
+
+
+/// Disables the DAC of the wave channel, which also disables the channel.
+fn disable_channel(gb: &mut GameBoy) {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR30, 0x00);
+}
+
+
+/// Lets the APU run for a number of cycles.
+fn run_apu(gb: &mut GameBoy, cycles: Clock) {
+    gb.get_peripherals_mut().apu.update(cycles);
+}
+
+
+/// Reads the whole content of the Wave RAM.
+fn read_wave_ram(gb: &GameBoy) -> Vec<u8> {
+    (0..16)
+        .map(|index| gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN + index))
+        .collect()
+}
+
+
+/// Triggers the wave channel and triggers it again two cycles before the given sample
+/// will be read, then returns the content of the Wave RAM.
+fn retrigger_before_sample(device: DeviceType, emulation: EmulationType, sample: Clock) -> Vec<u8> {
+    let mut gb = create_gameboy(device, emulation);
+    trigger_channel(&mut gb);
+    run_apu(&mut gb, CYCLES_UNTIL_FIRST_SAMPLE + sample * CYCLES_PER_SAMPLE - 2);
+    trigger_channel(&mut gb);
+    disable_channel(&mut gb);
+
+    read_wave_ram(&gb)
+}
+
+
+#[test]
+fn test_wave_ram_accessible_while_channel_off() {
+    for (device, emulation) in [(DeviceType::GameBoyDmg, EmulationType::DMG), (DeviceType::GameBoyColor, EmulationType::GBC)] {
+        let gb = create_gameboy(device, emulation);
+        let expected: Vec<u8> = (0..16).map(wave_ram_value).collect();
+
+        assert_eq!(expected, read_wave_ram(&gb));
+    }
+}
+
+
+#[test]
+fn test_wave_ram_read_while_on_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    trigger_channel(&mut gb);
+
+    // before the first sample was read, the Wave RAM is not accessible
+    run_apu(&mut gb, CYCLES_UNTIL_FIRST_SAMPLE - 1);
+    assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN));
+
+    run_apu(&mut gb, 1);
+
+    for sample in 0..32u16 {
+        // right after a sample was read, any address returns the byte currently being played
+        let expected = wave_ram_value(sample / 2);
+        assert_eq!(expected, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN + 0x0f));
+
+        run_apu(&mut gb, 1);
+        assert_eq!(expected, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN));
+
+        // after the access window was closed, reading returns 0xff
+        run_apu(&mut gb, 1);
+        assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN));
+
+        run_apu(&mut gb, CYCLES_PER_SAMPLE - 2);
+    }
+}
+
+
+#[test]
+fn test_wave_ram_read_while_on_gbc() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    trigger_channel(&mut gb);
+    run_apu(&mut gb, CYCLES_UNTIL_FIRST_SAMPLE);
+
+    for sample in 0..32u16 {
+        // any time while the channel is playing, the byte currently being played is returned
+        for _ in 0..CYCLES_PER_SAMPLE {
+            assert_eq!(wave_ram_value(sample / 2), gb.get_mmu().read_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN + 0x07));
+            run_apu(&mut gb, 1);
+        }
+    }
+}
+
+
+#[test]
+fn test_wave_ram_write_while_on_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    trigger_channel(&mut gb);
+
+    // writing outside of the access window is ignored
+    run_apu(&mut gb, CYCLES_UNTIL_FIRST_SAMPLE + 2 * CYCLES_PER_SAMPLE - 1);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN, 0xaa);
+
+    // writing within the access window goes to the byte currently being played
+    run_apu(&mut gb, 1);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_WAVE_RAM_BEGIN, 0x55);
+
+    disable_channel(&mut gb);
+
+    let mut expected: Vec<u8> = (0..16).map(wave_ram_value).collect();
+    expected[1] = 0x55;
+    assert_eq!(expected, read_wave_ram(&gb));
+}
+
+
+#[test]
+fn test_wave_ram_corruption_on_retrigger_dmg() {
+    let original: Vec<u8> = (0..16).map(wave_ram_value).collect();
+
+    // when reading one of the first four bytes, this byte is copied into the first byte
+    let mut expected = original.clone();
+    expected[0] = original[1];
+    assert_eq!(expected, retrigger_before_sample(DeviceType::GameBoyDmg, EmulationType::DMG, 2));
+
+    // above the first four bytes, the aligned block of four bytes is copied to the beginning
+    let mut expected = original.clone();
+    expected[0..4].copy_from_slice(&original[4..8]);
+    assert_eq!(expected, retrigger_before_sample(DeviceType::GameBoyDmg, EmulationType::DMG, 10));
+
+    let mut expected = original.clone();
+    expected[0..4].copy_from_slice(&original[12..16]);
+    assert_eq!(expected, retrigger_before_sample(DeviceType::GameBoyDmg, EmulationType::DMG, 31));
+}
+
+
+#[test]
+fn test_no_wave_ram_corruption_on_retrigger_gbc() {
+    let original: Vec<u8> = (0..16).map(wave_ram_value).collect();
+
+    for sample in [2, 10, 31] {
+        assert_eq!(original, retrigger_before_sample(DeviceType::GameBoyColor, EmulationType::GBC, sample));
+    }
+}
+
+
+#[test]
+fn test_no_wave_ram_corruption_when_retriggered_off_timing() {
+    let original: Vec<u8> = (0..16).map(wave_ram_value).collect();
+
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    trigger_channel(&mut gb);
+    run_apu(&mut gb, CYCLES_UNTIL_FIRST_SAMPLE + 10 * CYCLES_PER_SAMPLE - 1);
+    trigger_channel(&mut gb);
+    disable_channel(&mut gb);
+
+    assert_eq!(original, read_wave_ram(&gb));
+}
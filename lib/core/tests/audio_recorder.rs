@@ -0,0 +1,149 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::io::Cursor;
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SAMPLE_BUFFER_SIZE};
+use gemi_core::apu::audio_recorder::{AudioRecorder, WAV_HEADER_SIZE};
+use gemi_core::apu::sample::{Sample, StereoSample};
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// The sample rate used for recording.
+const SAMPLE_RATE: u32 = 48_000;
+
+
+/// Creates a new emulator instance running an endless loop with channel 1 playing a tone.
+fn create_gameboy_playing_tone() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    start_idle_loop(&mut gb);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR50, 0x77);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR51, 0x11);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR11, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR12, 0xf0);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR13, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR14, 0x87);
+
+    gb
+}
+
+
+/// Reads a 16 bit little endian value from the recorded data.
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+
+/// Reads a 32 bit little endian value from the recorded data.
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+
+/// Checks the header of a recorded WAV file to match the expected number of samples.
+fn check_wav_header(data: &[u8], sample_count: u64) {
+    let data_size = (sample_count * 4) as u32;
+
+    assert_eq!(WAV_HEADER_SIZE + data_size as u64, data.len() as u64);
+
+    assert_eq!(b"RIFF", &data[0..4]);
+    assert_eq!(data_size + 36, read_u32(data, 4));
+    assert_eq!(b"WAVE", &data[8..12]);
+
+    assert_eq!(b"fmt ", &data[12..16]);
+    assert_eq!(16,              read_u32(data, 16));
+    assert_eq!(1,               read_u16(data, 20));
+    assert_eq!(2,               read_u16(data, 22));
+    assert_eq!(SAMPLE_RATE,     read_u32(data, 24));
+    assert_eq!(SAMPLE_RATE * 4, read_u32(data, 28));
+    assert_eq!(4,               read_u16(data, 32));
+    assert_eq!(16,              read_u16(data, 34));
+
+    assert_eq!(b"data", &data[36..40]);
+    assert_eq!(data_size, read_u32(data, 40));
+}
+
+
+#[test]
+fn test_record_samples() {
+    let mut recorder = AudioRecorder::new(Cursor::new(Vec::new()), SAMPLE_RATE).unwrap();
+
+    let samples = [
+        StereoSample { left: Sample::new( 0.0), right: Sample::new( 0.5) },
+        StereoSample { left: Sample::new( 1.0), right: Sample::new(-1.0) },
+        StereoSample { left: Sample::new( 2.0), right: Sample::new(-2.0) },
+    ];
+
+    recorder.write_samples(&samples).unwrap();
+    assert_eq!(3, recorder.get_sample_count());
+
+    let data = recorder.finish().unwrap().into_inner();
+    check_wav_header(&data, 3);
+
+    // samples are converted into 16 bit values, clamping values out of range
+    let pcm : Vec<i16> = data[44..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+    assert_eq!(vec![0, 16383, 32767, -32767, 32767, -32767], pcm);
+}
+
+
+#[test]
+fn test_record_emulator_output() {
+    let mut gb       = create_gameboy_playing_tone();
+    let receiver     = gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate: SAMPLE_RATE, format: SampleFormat::Native }
+    ).unwrap();
+    let mut recorder = AudioRecorder::new(Cursor::new(Vec::new()), SAMPLE_RATE).unwrap();
+
+    // about 1/6 of a second
+    for _ in 0..10 {
+        gb.run_frame();
+        recorder.receive_samples(&receiver).unwrap();
+    }
+
+    // samples are transmitted in full buffers only
+    let sample_count = recorder.get_sample_count();
+    assert!(sample_count >= (SAMPLE_RATE / 6) as u64 - SAMPLE_BUFFER_SIZE as u64);
+    assert_eq!(0, sample_count % (SAMPLE_BUFFER_SIZE as u64));
+
+    let data = recorder.finish().unwrap().into_inner();
+    check_wav_header(&data, sample_count);
+
+    // the tone of channel 1 was recorded
+    assert!(data[44..].iter().any(|b| *b != 0));
+}
+
+
+#[test]
+fn test_header_completed_on_drop() {
+    let path = std::env::temp_dir().join(format!("gemi-audio-recorder-{}.wav", std::process::id()));
+
+    {
+        let mut recorder = AudioRecorder::create(&path, SAMPLE_RATE).unwrap();
+        recorder.write_samples(&[StereoSample::default(); 100]).unwrap();
+    }
+
+    let data = std::fs::read(&path).unwrap();
+    _ = std::fs::remove_file(&path);
+
+    check_wav_header(&data, 100);
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::boot_rom::{BootRom, BOOT_ROM_SIZE_DMG, BOOT_ROM_SIZE_GBC};
+use gemi_core::cartridge::Cartridge;
+use gemi_core::gameboy::{BuilderErrorCode, DeviceType, GameBoy};
+use gemi_core::mmu::locations::MEMORY_LOCATION_BOOT_ROM_DISABLE;
+
+
+/// The size of the ROM image used for testing, which is the size of two ROM banks.
+const ROM_SIZE: usize = 0x8000;
+
+/// The value each byte of the boot ROM images is filled with.
+const BOOT_ROM_VALUE: u8 = 0xbb;
+
+/// The value stored in the cartridge ROM on each address checked by the tests.
+const CARTRIDGE_VALUE: u8 = 0xcc;
+
+/// Addresses to be checked whether they're mapped to the boot ROM or the cartridge.
+const CHECKED_ADDRESSES: [u16; 8] = [0x0000, 0x00ff, 0x0100, 0x0150, 0x01ff, 0x0200, 0x08ff, 0x0900];
+
+
+/// Creates a cartridge without MBC containing a known value on each address checked.
+fn create_cartridge() -> Cartridge {
+    let mut rom = vec![0x00; ROM_SIZE];
+
+    for address in CHECKED_ADDRESSES {
+        rom[address as usize] = CARTRIDGE_VALUE;
+    }
+
+    Cartridge::load_from_bytes(rom, None).unwrap()
+}
+
+
+/// Creates a boot ROM image of the given size.
+fn create_boot_rom(size: usize) -> BootRom {
+    BootRom::load_from_bytes(&vec![BOOT_ROM_VALUE; size]).unwrap()
+}
+
+
+/// Tries to create an emulator instance with the given boot ROM on the given device.
+fn create_gameboy(boot_rom: BootRom, device_type: DeviceType) -> Result<GameBoy, BuilderErrorCode> {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_device_type(device_type);
+    builder.set_boot_rom(boot_rom);
+    builder.set_cartridge(create_cartridge());
+    builder.finish()
+}
+
+
+/// Checks whether an address reads data from the boot ROM or the cartridge.
+fn reads_from_boot_rom(gb: &GameBoy, address: u16) -> bool {
+    match gb.get_mmu().read_u8(address) {
+        BOOT_ROM_VALUE  => true,
+        CARTRIDGE_VALUE => false,
+        value           => panic!("Unexpected value {value:02x} at address {address:04x}"),
+    }
+}
+
+
+#[test]
+fn test_detect_boot_rom_type() {
+    assert!(!create_boot_rom(BOOT_ROM_SIZE_DMG).is_gbc_boot_rom());
+    assert!(create_boot_rom(BOOT_ROM_SIZE_GBC).is_gbc_boot_rom());
+}
+
+
+#[test]
+fn test_reject_invalid_boot_rom_size() {
+    for size in [0, 0x00ff, 0x0101, 0x0800, 0x08ff, 0x0901] {
+        assert!(BootRom::load_from_bytes(&vec![0x00; size]).is_err());
+    }
+}
+
+
+#[test]
+fn test_dmg_boot_rom_mapping() {
+    let gb = create_gameboy(create_boot_rom(BOOT_ROM_SIZE_DMG), DeviceType::GameBoyDmg).unwrap();
+
+    for address in CHECKED_ADDRESSES {
+        assert_eq!(address <= 0x00ff, reads_from_boot_rom(&gb, address));
+    }
+}
+
+
+#[test]
+fn test_gbc_boot_rom_mapping() {
+    let gb = create_gameboy(create_boot_rom(BOOT_ROM_SIZE_GBC), DeviceType::GameBoyColor).unwrap();
+
+    for address in CHECKED_ADDRESSES {
+        let expected = matches!(address, 0x0000 ..= 0x00ff | 0x0200 ..= 0x08ff);
+        assert_eq!(expected, reads_from_boot_rom(&gb, address));
+    }
+}
+
+
+#[test]
+fn test_gbc_boot_rom_unmapped_after_disable() {
+    let mut gb = create_gameboy(create_boot_rom(BOOT_ROM_SIZE_GBC), DeviceType::GameBoyColor).unwrap();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BOOT_ROM_DISABLE, 0x01);
+
+    for address in CHECKED_ADDRESSES {
+        assert!(!reads_from_boot_rom(&gb, address));
+    }
+}
+
+
+#[test]
+fn test_gbc_boot_rom_requires_gbc_device() {
+    for device_type in DeviceType::ALL_DEVICES {
+        let result = create_gameboy(create_boot_rom(BOOT_ROM_SIZE_GBC), device_type);
+
+        if device_type.has_gbc_support() {
+            assert!(result.is_ok());
+        }
+        else {
+            assert_eq!(result.err(), Some(BuilderErrorCode::GameBoyColorBootRomNotSupported));
+        }
+    }
+}
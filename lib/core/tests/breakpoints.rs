@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::opcode::Instruction;
+use gemi_core::debug::DebugEvent;
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// A small loop of NOP instructions: 3x NOP, then JR back to the start.
+const PROGRAM_LOOP: [u8; 5] = [
+    0x00,           // c000: NOP
+    0x00,           // c001: NOP
+    0x00,           // c002: NOP
+    0x18, 0xfb,     // c003: JR -5
+];
+
+
+/// Creates an emulator instance running the test program from WRAM.
+fn create_gameboy_with_program(program: &[u8]) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + offset as u16, *byte);
+    }
+
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+
+    gb
+}
+
+
+/// Decodes a single instruction from a byte sequence located on the given address.
+fn decode(address: u16, bytes: &[u8]) -> Instruction {
+    Instruction::read_instruction(
+        address,
+        |read_address| {
+            let offset = read_address.wrapping_sub(address) as usize;
+            bytes.get(offset).copied().unwrap_or(0x00)
+        }
+    )
+}
+
+
+#[test]
+fn test_jump_targets() {
+    // absolute jumps and calls
+    assert_eq!(decode(0x0150, &[0xc3, 0x34, 0x12]).get_jump_target(), Some(0x1234));
+    assert_eq!(decode(0x0150, &[0xca, 0x00, 0x40]).get_jump_target(), Some(0x4000));
+    assert_eq!(decode(0x0150, &[0xcd, 0xef, 0xbe]).get_jump_target(), Some(0xbeef));
+    assert_eq!(decode(0x0150, &[0xdc, 0x50, 0x01]).get_jump_target(), Some(0x0150));
+
+    // relative jumps are based on the address of the following instruction
+    assert_eq!(decode(0x0150, &[0x18, 0x10]).get_jump_target(), Some(0x0162));
+    assert_eq!(decode(0x0150, &[0x20, 0xfe]).get_jump_target(), Some(0x0150));
+    assert_eq!(decode(0x0150, &[0x38, 0x80]).get_jump_target(), Some(0x00d2));
+
+    // restart vectors
+    assert_eq!(decode(0x0150, &[0xc7]).get_jump_target(), Some(0x0000));
+    assert_eq!(decode(0x0150, &[0xef]).get_jump_target(), Some(0x0028));
+    assert_eq!(decode(0x0150, &[0xff]).get_jump_target(), Some(0x0038));
+
+    // no constant targets
+    assert_eq!(decode(0x0150, &[0xe9]).get_jump_target(), None);
+    assert_eq!(decode(0x0150, &[0xc9]).get_jump_target(), None);
+    assert_eq!(decode(0x0150, &[0x00]).get_jump_target(), None);
+    assert_eq!(decode(0x0150, &[0xcb, 0x18]).get_jump_target(), None);
+}
+
+
+#[test]
+fn test_one_shot_breakpoint() {
+    let mut gb = create_gameboy_with_program(&PROGRAM_LOOP);
+
+    gb.get_breakpoints_mut().add_one_shot(0xc002);
+
+    let results = gb.run_frame();
+    assert!(results.events.contains(DebugEvent::BreakpointReached));
+    assert_eq!(gb.cpu.get_instruction_pointer(), 0xc002);
+
+    // the breakpoint is removed after being reached
+    assert!(gb.get_breakpoints().is_empty());
+
+    let results = gb.run_frame();
+    assert!(!results.events.contains(DebugEvent::BreakpointReached));
+}
+
+
+#[test]
+fn test_permanent_breakpoint() {
+    let mut gb = create_gameboy_with_program(&PROGRAM_LOOP);
+
+    gb.get_breakpoints_mut().add(0xc000);
+
+    // the breakpoint is reached on each iteration of the loop
+    for _ in 0..3 {
+        let results = gb.run_frame();
+        assert!(results.events.contains(DebugEvent::BreakpointReached));
+        assert_eq!(gb.cpu.get_instruction_pointer(), 0xc000);
+    }
+
+    // a one-shot breakpoint does not replace a permanent one
+    gb.get_breakpoints_mut().add_one_shot(0xc000);
+    gb.run_frame();
+    assert!(gb.get_breakpoints().contains(0xc000));
+
+    gb.get_breakpoints_mut().remove(0xc000);
+    let results = gb.run_frame();
+    assert!(!results.events.contains(DebugEvent::BreakpointReached));
+}
+
+
+#[test]
+fn test_disabled_breakpoint() {
+    let mut gb = create_gameboy_with_program(&PROGRAM_LOOP);
+
+    gb.get_breakpoints_mut().add(0xc002);
+    gb.get_breakpoints_mut().set_enabled(0xc002, false);
+
+    // a disabled breakpoint stays in the list, but does not stop the emulator
+    let results = gb.run_frame();
+    assert!(!results.events.contains(DebugEvent::BreakpointReached));
+    assert_eq!(Some(false), gb.get_breakpoints().get(0xc002).map(|breakpoint| breakpoint.enabled));
+
+    gb.get_breakpoints_mut().set_enabled(0xc002, true);
+    let results = gb.run_frame();
+    assert!(results.events.contains(DebugEvent::BreakpointReached));
+    assert_eq!(gb.cpu.get_instruction_pointer(), 0xc002);
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::{BuilderErrorCode, DeviceType, EmulationType, GameBoy};
+
+
+/// The size of the ROM image used for testing, which is the size of two ROM banks.
+const ROM_SIZE: usize = 0x8000;
+
+
+/// Creates a cartridge without MBC and the given value as GameBoy Color flag.
+fn create_cartridge(cgb_flag: u8) -> Cartridge {
+    let mut rom = vec![0x00; ROM_SIZE];
+    rom[ROM_OFFSET_FLAG_CGB] = cgb_flag;
+
+    Cartridge::load_from_bytes(rom, None).unwrap()
+}
+
+
+/// Tries to create an emulator instance running the given cartridge on the given device.
+fn create_gameboy(cgb_flag: u8, device_type: DeviceType) -> Result<GameBoy, BuilderErrorCode> {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_device_type(device_type);
+    builder.set_cartridge(create_cartridge(cgb_flag));
+    builder.finish()
+}
+
+
+/// Get the emulation type selected for the given cartridge on the given device.
+fn emulation_type_of(cgb_flag: u8, device_type: DeviceType) -> EmulationType {
+    create_gameboy(cgb_flag, device_type).unwrap().get_config().emulation
+}
+
+
+#[test]
+fn test_cartridge_cgb_flags() {
+    let cartridge = create_cartridge(0x00);
+    assert!(!cartridge.supports_cgb());
+    assert!(!cartridge.requires_cgb());
+
+    let cartridge = create_cartridge(0x80);
+    assert!(cartridge.supports_cgb());
+    assert!(!cartridge.requires_cgb());
+
+    let cartridge = create_cartridge(0xc0);
+    assert!(cartridge.supports_cgb());
+    assert!(cartridge.requires_cgb());
+}
+
+
+#[test]
+fn test_dmg_cartridge_runs_in_dmg_mode() {
+    for device_type in DeviceType::ALL_DEVICES {
+        assert!(emulation_type_of(0x00, device_type) == EmulationType::DMG);
+    }
+}
+
+
+#[test]
+fn test_cgb_supporting_cartridge() {
+    // GameBoy Color features are only enabled on devices supporting them
+    for device_type in DeviceType::ALL_DEVICES {
+        let expected = if device_type.has_gbc_support() {
+            EmulationType::GBC
+        }
+        else {
+            EmulationType::DMG
+        };
+
+        assert!(emulation_type_of(0x80, device_type) == expected);
+    }
+}
+
+
+#[test]
+fn test_cgb_only_cartridge() {
+    for device_type in DeviceType::ALL_DEVICES {
+        let result = create_gameboy(0xc0, device_type);
+
+        if device_type.has_gbc_support() {
+            assert!(result.unwrap().get_config().emulation == EmulationType::GBC);
+        }
+        else {
+            assert_eq!(result.err(), Some(BuilderErrorCode::GameBoyColorNotSupported));
+        }
+    }
+}
+
+
+#[test]
+fn test_cgb_only_cartridge_selects_gbc_device() {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(create_cartridge(0xc0));
+
+    let gb = builder.finish().unwrap();
+    assert!(gb.get_config().device == DeviceType::GameBoyColor);
+    assert!(gb.get_config().emulation == EmulationType::GBC);
+}
@@ -0,0 +1,190 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::{BuilderErrorCode, GameBoy};
+use gemi_core::mmu::mbc::MemoryBankController;
+
+
+/// The size of the ROM image used for testing, which is the size of two ROM banks.
+const ROM_SIZE: usize = 0x8000;
+
+
+/// Computes the header checksum like the boot ROM does.
+fn compute_header_checksum(rom: &[u8]) -> u8 {
+    rom[ROM_OFFSET_TITLE_STRING .. ROM_OFFSET_HEADER_CHECKSUM]
+        .iter()
+        .fold(0u8, |checksum, b| checksum.wrapping_sub(*b).wrapping_sub(1))
+}
+
+
+/// Computes the global checksum over all bytes except the checksum itself.
+fn compute_global_checksum(rom: &[u8]) -> u16 {
+    let sum: u16 = rom.iter().fold(0u16, |checksum, b| checksum.wrapping_add(*b as u16));
+
+    sum
+        .wrapping_sub(rom[ROM_OFFSET_GLOBAL_CHECKSUM] as u16)
+        .wrapping_sub(rom[ROM_OFFSET_GLOBAL_CHECKSUM + 1] as u16)
+}
+
+
+/// Creates a ROM image with a valid header, which may be modified
+/// by the given function before the checksums are computed.
+fn create_rom(modify: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut rom = vec![0x00; ROM_SIZE];
+
+    rom[ROM_OFFSET_LOGO_BITMAP .. ROM_OFFSET_LOGO_BITMAP + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+    rom[ROM_OFFSET_TITLE_STRING .. ROM_OFFSET_TITLE_STRING + 4].copy_from_slice(b"TEST");
+
+    modify(&mut rom);
+
+    rom[ROM_OFFSET_HEADER_CHECKSUM] = compute_header_checksum(&rom);
+
+    let global_checksum = compute_global_checksum(&rom);
+    rom[ROM_OFFSET_GLOBAL_CHECKSUM .. ROM_OFFSET_GLOBAL_CHECKSUM + 2].copy_from_slice(&global_checksum.to_be_bytes());
+
+    rom
+}
+
+
+/// Loads a cartridge from a ROM image and validates its header.
+fn validate(rom: Vec<u8>) -> Vec<CartridgeHeaderError> {
+    Cartridge::load_from_bytes(rom, None).unwrap().validate_header()
+}
+
+
+/// Tries to create an emulator instance running the given ROM image.
+fn create_gameboy(rom: Vec<u8>, allow_invalid_header: bool) -> Result<GameBoy, BuilderErrorCode> {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(allow_invalid_header);
+    builder.set_cartridge(Cartridge::load_from_bytes(rom, None).unwrap());
+    builder.finish()
+}
+
+
+#[test]
+fn test_valid_header() {
+    assert_eq!(validate(create_rom(|_| {})), vec![]);
+}
+
+
+#[test]
+fn test_invalid_logo() {
+    let rom = create_rom(|rom| rom[ROM_OFFSET_LOGO_BITMAP] = 0x00);
+
+    assert_eq!(validate(rom), vec![CartridgeHeaderError::InvalidNintendoLogo]);
+}
+
+
+#[test]
+fn test_header_checksum_mismatch() {
+    let mut rom = create_rom(|_| {});
+    let checksum = rom[ROM_OFFSET_HEADER_CHECKSUM];
+
+    // modify the title after the checksum was computed
+    rom[ROM_OFFSET_TITLE_STRING] = b'X';
+
+    let errors = validate(rom);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0],
+        CartridgeHeaderError::HeaderChecksumMismatch {
+            expected: checksum,
+            actual:   checksum.wrapping_sub(b'X' - b'T'),
+        }
+    );
+
+    // the global checksum has changed as well
+    assert!(matches!(errors[1], CartridgeHeaderError::GlobalChecksumMismatch { .. }));
+}
+
+
+#[test]
+fn test_global_checksum_mismatch_is_warning() {
+    let mut rom = create_rom(|_| {});
+    rom[ROM_SIZE - 1] = 0x01;
+
+    let errors = validate(rom.clone());
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], CartridgeHeaderError::GlobalChecksumMismatch { .. }));
+    assert!(errors[0].is_warning());
+
+    // warnings do not prevent the cartridge from running
+    assert!(create_gameboy(rom, false).is_ok());
+}
+
+
+#[test]
+fn test_unknown_header_values() {
+    let rom = create_rom(|rom| {
+        rom[ROM_OFFSET_ROM_TYPE] = 0x04;
+        rom[ROM_OFFSET_ROM_SIZE] = 0x09;
+        rom[ROM_OFFSET_RAM_SIZE] = 0x06;
+    });
+
+    assert_eq!(
+        validate(rom),
+        vec![
+            CartridgeHeaderError::UnknownCartridgeType(0x04),
+            CartridgeHeaderError::UnknownRomSize(0x09),
+            CartridgeHeaderError::UnknownRamSize(0x06),
+        ]
+    );
+}
+
+
+#[test]
+fn test_builder_rejects_invalid_header() {
+    let rom = create_rom(|rom| rom[ROM_OFFSET_LOGO_BITMAP] = 0x00);
+
+    assert_eq!(
+        create_gameboy(rom.clone(), false).err(),
+        Some(BuilderErrorCode::InvalidCartridgeHeader(CartridgeHeaderError::InvalidNintendoLogo))
+    );
+
+    assert!(create_gameboy(rom, true).is_ok());
+}
+
+
+#[test]
+fn test_reject_rom_without_header() {
+    assert!(Cartridge::load_from_bytes(vec![0x00; ROM_HEADER_END - 1], None).is_err());
+}
+
+
+#[test]
+fn test_builder_rejects_unknown_mapper() {
+    let rom = create_rom(|rom| rom[ROM_OFFSET_ROM_TYPE] = 0x04);
+
+    let cartridge = Cartridge::load_from_bytes(rom.clone(), None).unwrap();
+    assert!(matches!(cartridge.get_mbc(), MemoryBankController::Unknown(0x04)));
+
+    // the mapper is rejected even when invalid headers are allowed
+    assert_eq!(create_gameboy(rom, true).err(), Some(BuilderErrorCode::UnsupportedMapper(0x04)));
+}
+
+
+#[test]
+fn test_builder_rejects_unsupported_mapper() {
+    // MBC3+RAM+BATTERY is a valid cartridge type, but not implemented yet
+    let rom = create_rom(|rom| {
+        rom[ROM_OFFSET_ROM_TYPE] = 0x13;
+        rom[ROM_OFFSET_RAM_SIZE] = 0x03;
+    });
+
+    assert_eq!(create_gameboy(rom, false).err(), Some(BuilderErrorCode::UnsupportedMapper(0x13)));
+}
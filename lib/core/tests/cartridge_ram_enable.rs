@@ -0,0 +1,114 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::GameBoy;
+
+
+/// Cartridge type MBC1+RAM+BATTERY.
+const ROM_TYPE_MBC1: u8 = 0x03;
+
+/// Cartridge type MBC2+BATTERY.
+const ROM_TYPE_MBC2: u8 = 0x06;
+
+/// Cartridge type MBC5+RAM+BATTERY.
+const ROM_TYPE_MBC5: u8 = 0x1b;
+
+/// RAM size type for 8kiB of cartridge RAM.
+const RAM_SIZE_8K: u8 = 0x02;
+
+/// An address within the external RAM region.
+const RAM_ADDRESS: u16 = 0xa123;
+
+
+/// Creates a new emulator instance with a cartridge of the given type.
+fn create_gameboy(rom_type: u8) -> GameBoy {
+    let mut rom = vec![0x00; 0x8000];
+    rom[ROM_OFFSET_ROM_TYPE] = rom_type;
+    rom[ROM_OFFSET_RAM_SIZE] = RAM_SIZE_8K;
+
+    let cartridge = Cartridge::load_from_bytes(rom, None).unwrap();
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(cartridge);
+    builder.finish().unwrap()
+}
+
+
+/// Writes into the RAM enable register.
+fn set_ram_enable(gb: &mut GameBoy, value: u8) {
+    gb.get_mmu_mut().write_u8(0x0000, value);
+}
+
+
+/// Checks RAM is only accessible after being enabled and writes are dropped while disabled.
+fn check_ram_enable(rom_type: u8, value: u8) {
+    let mut gb = create_gameboy(rom_type);
+
+    // RAM is disabled after startup
+    assert_eq!(0xff, gb.get_mmu().read_u8(RAM_ADDRESS));
+
+    // writes are dropped while RAM is disabled
+    gb.get_mmu_mut().write_u8(RAM_ADDRESS, 0x01);
+    set_ram_enable(&mut gb, 0x0a);
+    assert_ne!(0x01, gb.get_mmu().read_u8(RAM_ADDRESS));
+
+    // writes are stored after RAM was enabled
+    gb.get_mmu_mut().write_u8(RAM_ADDRESS, value);
+    assert_eq!(value, gb.get_mmu().read_u8(RAM_ADDRESS));
+
+    // the value can't be read after disabling RAM again, but is still stored
+    set_ram_enable(&mut gb, 0x00);
+    assert_eq!(0xff, gb.get_mmu().read_u8(RAM_ADDRESS));
+
+    set_ram_enable(&mut gb, 0x0a);
+    assert_eq!(value, gb.get_mmu().read_u8(RAM_ADDRESS));
+}
+
+
+#[test]
+fn test_mbc1_ram_enable() {
+    check_ram_enable(ROM_TYPE_MBC1, 0x42);
+}
+
+
+#[test]
+fn test_mbc2_ram_enable() {
+    // MBC2 only stores the lower nibble, the upper nibble reads as 1
+    check_ram_enable(ROM_TYPE_MBC2, 0xf5);
+}
+
+
+#[test]
+fn test_mbc5_ram_enable() {
+    check_ram_enable(ROM_TYPE_MBC5, 0x42);
+}
+
+
+#[test]
+fn test_only_lower_nibble_enables_ram() {
+    let mut gb = create_gameboy(ROM_TYPE_MBC1);
+
+    // only the lower nibble has to be 0xa
+    set_ram_enable(&mut gb, 0x5a);
+    gb.get_mmu_mut().write_u8(RAM_ADDRESS, 0x42);
+    assert_eq!(0x42, gb.get_mmu().read_u8(RAM_ADDRESS));
+
+    // any other value disables RAM
+    set_ram_enable(&mut gb, 0x0b);
+    assert_eq!(0xff, gb.get_mmu().read_u8(RAM_ADDRESS));
+}
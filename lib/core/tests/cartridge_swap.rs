@@ -0,0 +1,182 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::{BuilderErrorCode, DeviceType, EmulationType, GameBoy};
+
+
+/// Cartridge type MBC1.
+const ROM_TYPE_MBC1: u8 = 0x01;
+
+/// Cartridge type MBC3, which is not supported yet.
+const ROM_TYPE_MBC3: u8 = 0x11;
+
+/// ROM size type for 64kiB of ROM, which are 4 banks.
+const ROM_SIZE_64K: u8 = 0x01;
+
+/// The number of ROM banks for a 64kiB ROM.
+const ROM_BANKS: usize = 4;
+
+
+/// Creates a ROM with a loop at the entry point, with each ROM bank
+/// starting with a marker value built from the given tag and the bank number.
+fn create_rom(rom_type: u8, tag: u8) -> Cartridge {
+    create_rom_with_cgb_flag(rom_type, tag, 0x00)
+}
+
+
+/// Creates a ROM like [create_rom] with the given GameBoy Color flag in its header.
+fn create_rom_with_cgb_flag(rom_type: u8, tag: u8, cgb_flag: u8) -> Cartridge {
+    let mut rom = vec![0x00; ROM_BANKS * 0x4000];
+    rom[ROM_OFFSET_ROM_TYPE] = rom_type;
+    rom[ROM_OFFSET_ROM_SIZE] = ROM_SIZE_64K;
+    rom[ROM_OFFSET_FLAG_CGB] = cgb_flag;
+
+    // NOP; JR -2
+    rom[ROM_OFFSET_ENTRY_POINT]     = 0x00;
+    rom[ROM_OFFSET_ENTRY_POINT + 1] = 0x18;
+    rom[ROM_OFFSET_ENTRY_POINT + 2] = 0xfe;
+
+    // the byte following the loop identifies the ROM
+    rom[ROM_OFFSET_ENTRY_POINT + 3] = tag;
+
+    for bank in 0..ROM_BANKS {
+        rom[bank * 0x4000] = tag | (bank as u8);
+    }
+
+    Cartridge::load_from_bytes(rom, None).unwrap()
+}
+
+
+/// Creates a new emulator instance running the given cartridge.
+fn create_gameboy(cartridge: Cartridge) -> GameBoy {
+    create_gameboy_on_device(cartridge, DeviceType::GameBoyDmg)
+}
+
+
+/// Creates a new emulator instance of the given device running the given cartridge.
+fn create_gameboy_on_device(cartridge: Cartridge, device: DeviceType) -> GameBoy {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_device_type(device);
+    builder.set_cartridge(cartridge);
+
+    let mut gb = builder.finish().unwrap();
+    gb.initialize();
+    gb
+}
+
+
+#[test]
+fn test_swap_cartridge() {
+    let mut gb = create_gameboy(create_rom(ROM_TYPE_MBC1, 0xa0));
+
+    // run the first cartridge and switch to another ROM bank
+    gb.run_frame();
+    gb.get_mmu_mut().write_u8(0x2000, 0x02);
+
+    assert_eq!(0xa0, gb.get_mmu().read_u8(0x0103));
+    assert_eq!(0xa2, gb.get_mmu().read_u8(0x4000));
+    assert_ne!(0x0100, gb.cpu.get_instruction_pointer());
+
+    // insert the second cartridge
+    gb.load_cartridge(create_rom(ROM_TYPE_MBC1, 0xb0), true).unwrap();
+
+    // the device starts over at the entry point with the initial ROM bank of the new cartridge
+    assert_eq!(0x0100, gb.cpu.get_instruction_pointer());
+    assert_eq!(0xb0, gb.get_mmu().read_u8(0x0000));
+    assert_eq!(0xb0, gb.get_mmu().read_u8(0x0103));
+    assert_eq!(0xb1, gb.get_mmu().read_u8(0x4000));
+
+    // the new cartridge keeps running
+    gb.run_frame();
+    assert_eq!(0xb0, gb.get_mmu().read_u8(0x0103));
+    assert!(gb.cpu.get_instruction_pointer() < 0x0103);
+}
+
+
+#[test]
+fn test_eject_cartridge() {
+    let mut gb = create_gameboy(create_rom(ROM_TYPE_MBC1, 0xa0));
+    gb.run_frame();
+
+    let cartridge = gb.eject_cartridge();
+    assert!(cartridge.is_some());
+    assert!(gb.get_peripherals().mem.get_cartridge().is_none());
+
+    // without a cartridge, the ROM area is not readable anymore
+    assert_eq!(0xff, gb.get_mmu().read_u8(0x0103));
+    assert_eq!(0xff, gb.get_mmu().read_u8(0x4000));
+
+    // ejecting again does not return anything
+    assert!(gb.eject_cartridge().is_none());
+
+    // the ejected cartridge can be inserted again
+    gb.load_cartridge(cartridge.unwrap(), true).unwrap();
+    assert_eq!(0xa0, gb.get_mmu().read_u8(0x0103));
+    assert_eq!(0xa1, gb.get_mmu().read_u8(0x4000));
+}
+
+
+#[test]
+fn test_load_unsupported_cartridge() {
+    let mut gb = create_gameboy(create_rom(ROM_TYPE_MBC1, 0xa0));
+
+    let result = gb.load_cartridge(create_rom(ROM_TYPE_MBC3, 0xb0), true);
+    assert_eq!(Err(BuilderErrorCode::UnsupportedMapper(ROM_TYPE_MBC3)), result);
+
+    // the previous cartridge is still inserted
+    assert_eq!(0xa0, gb.get_mmu().read_u8(0x0103));
+}
+
+
+#[test]
+fn test_swap_to_cgb_only_cartridge() {
+    let mut gb = create_gameboy_on_device(create_rom(ROM_TYPE_MBC1, 0xa0), DeviceType::GameBoyColor);
+    gb.get_peripherals_mut().ppu.set_frame_log_enabled(true);
+    gb.run_frame();
+
+    // a DMG cartridge runs in DMG mode on the GameBoy Color
+    assert!(EmulationType::DMG == gb.get_config().emulation);
+
+    // the GameBoy Color only cartridge enables the GameBoy Color features
+    gb.load_cartridge(create_rom_with_cgb_flag(ROM_TYPE_MBC1, 0xb0, 0xc0), true).unwrap();
+    assert!(DeviceType::GameBoyColor == gb.get_config().device);
+    assert!(EmulationType::GBC == gb.get_config().emulation);
+    assert!(gb.get_config().is_gbc_enabled());
+
+    // the new cartridge starts running, while the settings of the frontend are kept
+    assert_eq!(0x0100, gb.cpu.get_instruction_pointer());
+    assert_eq!(0xb0, gb.get_mmu().read_u8(0x0103));
+    assert!(gb.get_peripherals().ppu.is_frame_log_enabled());
+
+    // switching back into DMG mode with a DMG cartridge
+    gb.load_cartridge(create_rom(ROM_TYPE_MBC1, 0xc0), true).unwrap();
+    assert!(EmulationType::DMG == gb.get_config().emulation);
+}
+
+
+#[test]
+fn test_load_cartridge_with_invalid_header() {
+    let mut gb = create_gameboy(create_rom(ROM_TYPE_MBC1, 0xa0));
+
+    // like when building the device, cartridges with an invalid header are refused
+    let result = gb.load_cartridge(create_rom(ROM_TYPE_MBC1, 0xb0), false);
+    assert!(matches!(result, Err(BuilderErrorCode::InvalidCartridgeHeader(_))));
+
+    // the previous cartridge is still inserted
+    assert_eq!(0xa0, gb.get_mmu().read_u8(0x0103));
+}
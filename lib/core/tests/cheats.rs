@@ -0,0 +1,114 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cartridge::Cartridge;
+use gemi_core::cheats::{CheatCode, CheatCodeManager, CheatDecodeError};
+use gemi_core::gameboy::GameBoy;
+
+
+/// The size of the ROM image used for testing, which is the size of two ROM banks.
+const ROM_SIZE: usize = 0x8000;
+
+
+/// Creates an emulator instance running a cartridge with the given bytes written into its ROM.
+fn create_gameboy(rom_data: &[(u16, u8)]) -> GameBoy {
+    let mut rom = vec![0x00; ROM_SIZE];
+
+    for (address, value) in rom_data {
+        rom[*address as usize] = *value;
+    }
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(Cartridge::load_from_bytes(rom, None).unwrap());
+    builder.finish().unwrap()
+}
+
+
+#[test]
+fn test_decode_six_digit_code() {
+    let code = CheatCode::from_game_genie("00A-17B").unwrap();
+
+    assert_eq!(0x00,   code.value);
+    assert_eq!(0x4a17, code.address);
+    assert_eq!(None,   code.compare);
+}
+
+
+#[test]
+fn test_decode_nine_digit_code() {
+    let code = CheatCode::from_game_genie("C3A-17B-C4E").unwrap();
+
+    assert_eq!(0xc3,   code.value);
+    assert_eq!(0x4a17, code.address);
+    assert_eq!(Some(0x09), code.compare);
+
+    // dashes are optional and digits are case insensitive
+    assert_eq!(code.address, CheatCode::from_game_genie("c3a17bc4e").unwrap().address);
+    assert_eq!(code.compare, CheatCode::from_game_genie("c3a17bc4e").unwrap().compare);
+}
+
+
+#[test]
+fn test_decode_invalid_codes() {
+    assert_eq!(Err(CheatDecodeError::InvalidLength(5)),         CheatCode::from_game_genie("00A-17"));
+    assert_eq!(Err(CheatDecodeError::InvalidLength(7)),         CheatCode::from_game_genie("00A-17B-C"));
+    assert_eq!(Err(CheatDecodeError::InvalidCharacter('X')),    CheatCode::from_game_genie("00X-17B"));
+    assert_eq!(Err(CheatDecodeError::AddressOutOfRange(0x8a17)), CheatCode::from_game_genie("00A-177"));
+}
+
+
+#[test]
+fn test_manager_keeps_codes() {
+    let mut manager = CheatCodeManager::new();
+    assert!(manager.is_empty());
+
+    let code = manager.add_game_genie("00A-17B").unwrap();
+    assert!(manager.add_game_genie("invalid").is_err());
+    assert_eq!(1, manager.get_codes().len());
+
+    manager.remove(&code);
+    assert!(manager.is_empty());
+}
+
+
+#[test]
+fn test_patch_rom_without_compare() {
+    let mut gb = create_gameboy(&[(0x4a17, 0x42)]);
+    assert_eq!(0x42, gb.get_mmu().read_u8(0x4a17));
+
+    gb.get_peripherals_mut().cheats.add_game_genie("C3A-17B").unwrap();
+    assert_eq!(0xc3, gb.get_mmu().read_u8(0x4a17));
+    assert_eq!(0x00, gb.get_mmu().read_u8(0x4a18));
+
+    gb.get_peripherals_mut().cheats.clear();
+    assert_eq!(0x42, gb.get_mmu().read_u8(0x4a17));
+}
+
+
+#[test]
+fn test_patch_rom_with_compare() {
+    // compare value: 0xce rotated right by two, XORed with 0xba
+    let mut gb = create_gameboy(&[(0x4a17, 0x09)]);
+    gb.get_peripherals_mut().cheats.add_game_genie("C3A-17B-C4E").unwrap();
+    assert_eq!(0xc3, gb.get_mmu().read_u8(0x4a17));
+
+    // the code does not apply when the original value does not match
+    let mut gb = create_gameboy(&[(0x4a17, 0x42)]);
+    gb.get_peripherals_mut().cheats.add_game_genie("C3A-17B-C4E").unwrap();
+    assert_eq!(0x42, gb.get_mmu().read_u8(0x4a17));
+}
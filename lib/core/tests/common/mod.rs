@@ -0,0 +1,91 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers shared between the integration tests.
+//! Each test binary only uses a part of them.
+#![allow(dead_code)]
+
+use gemi_core::gameboy::{AccuracyLevel, Clock, DeviceConfig, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+
+
+/// The number of CPU cycles for each step of a pulse channel's duty cycle,
+/// based on a frequency of 0x700 as used by [trigger_channel2].
+pub const PULSE_CYCLES_PER_STEP: Clock = (2048 - 0x700) * 4;
+
+
+/// Creates a new emulator instance without any cartridge for the given device,
+/// emulation type and accuracy level.
+pub fn create_gameboy_with_accuracy(device: DeviceType, emulation: EmulationType, accuracy: AccuracyLevel) -> GameBoy {
+    GameBoy::new(
+        DeviceConfig {
+            device,
+            emulation,
+            print_opcodes: false,
+            accuracy,
+        }
+    ).unwrap()
+}
+
+
+/// Creates a new emulator instance without any cartridge for the given device and emulation type.
+pub fn create_gameboy_for(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    create_gameboy_with_accuracy(device, emulation, AccuracyLevel::Accurate)
+}
+
+
+/// Creates a new emulator instance running as a classic GameBoy.
+pub fn create_gameboy_dmg() -> GameBoy {
+    create_gameboy_for(DeviceType::GameBoyDmg, EmulationType::DMG)
+}
+
+
+/// Creates a new emulator instance running as a GameBoy Color.
+pub fn create_gameboy_color() -> GameBoy {
+    create_gameboy_for(DeviceType::GameBoyColor, EmulationType::GBC)
+}
+
+
+/// Lets the CPU run an endless loop (JR -2) stored in WRAM with interrupts disabled,
+/// so the emulator can be run for any time without a cartridge.
+pub fn start_idle_loop(gb: &mut GameBoy) {
+    gb.get_mmu_mut().write_u8(0xc000, 0x18);
+    gb.get_mmu_mut().write_u8(0xc001, 0xfe);
+    gb.cpu.set_instruction_pointer(0xc000);
+    gb.cpu.disable_interrupts();
+}
+
+
+/// Enables the APU and triggers channel 2 with the given duty and NR22 value
+/// on a frequency of 0x700.
+pub fn trigger_channel2(gb: &mut GameBoy, duty: u8, nr22: u8) {
+    let mmu = gb.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR21, (duty & 0x03) << 6);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR22, nr22);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR23, 0x00);
+    mmu.write_u8(MEMORY_LOCATION_APU_NR24, 0x87);
+}
+
+
+/// Creates a new emulator instance with channel 2 triggered using the given duty and NR22 value.
+pub fn create_gameboy_with_channel2(duty: u8, nr22: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+    trigger_channel2(&mut gb, duty, nr22);
+
+    gb
+}
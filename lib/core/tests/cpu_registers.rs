@@ -0,0 +1,88 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cpu::cpu::{RegisterR16, RegisterR8};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// All 16 bit registers, which are built of two 8 bit registers.
+const REGISTER_PAIRS: [(RegisterR16, RegisterR8, RegisterR8); 4] = [
+    (RegisterR16::AF, RegisterR8::A, RegisterR8::F),
+    (RegisterR16::BC, RegisterR8::B, RegisterR8::C),
+    (RegisterR16::DE, RegisterR8::D, RegisterR8::E),
+    (RegisterR16::HL, RegisterR8::H, RegisterR8::L),
+];
+
+
+#[test]
+fn test_get_r16_combines_r8() {
+    let mut gb = create_gameboy_dmg();
+
+    for (r16, high, low) in REGISTER_PAIRS {
+        gb.cpu.set_r8(high, 0x12);
+        gb.cpu.set_r8(low,  0x30);
+
+        assert_eq!(0x1230, gb.cpu.get_r16(r16), "Register {r16}");
+        assert!(r16.to_r8() == Some((high, low)));
+    }
+}
+
+
+#[test]
+fn test_set_r16_splits_into_r8() {
+    let mut gb = create_gameboy_dmg();
+
+    for (r16, high, low) in REGISTER_PAIRS {
+        gb.cpu.set_r16(r16, 0xabc0);
+
+        assert_eq!(0xab, gb.cpu.get_r8(high), "Register {r16}");
+        assert_eq!(0xc0, gb.cpu.get_r8(low),  "Register {r16}");
+    }
+}
+
+
+#[test]
+fn test_set_af_masks_flags() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_r16(RegisterR16::AF, 0x12ff);
+
+    // the lower 4 bits of F are always zero
+    assert_eq!(0x12f0, gb.cpu.get_r16(RegisterR16::AF));
+    assert_eq!(0xf0,   gb.cpu.get_r8(RegisterR8::F));
+
+    // other registers are not masked
+    gb.cpu.set_r16(RegisterR16::BC, 0x12ff);
+    assert_eq!(0x12ff, gb.cpu.get_r16(RegisterR16::BC));
+}
+
+
+#[test]
+fn test_stack_pointer() {
+    let mut gb = create_gameboy_dmg();
+
+    gb.cpu.set_r16(RegisterR16::SP, 0xd00f);
+    assert_eq!(0xd00f, gb.cpu.get_stack_pointer());
+    assert_eq!(0xd00f, gb.cpu.get_r16(RegisterR16::SP));
+
+    gb.cpu.set_stack_pointer(0xfffe);
+    assert_eq!(0xfffe, gb.cpu.get_r16(RegisterR16::SP));
+
+    // SP is not built of 8 bit registers
+    assert!(RegisterR16::SP.to_r8().is_none());
+    assert_eq!("SP", RegisterR16::SP.to_string());
+}
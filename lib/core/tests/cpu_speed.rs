@@ -0,0 +1,216 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat, SamplesReceiver, SAMPLE_BUFFER_SIZE};
+use gemi_core::cpu::cpu::CPU_CLOCK_SPEED;
+use gemi_core::gameboy::{Clock, DeviceType, EmulationType, GameBoy};
+use gemi_core::input::InputButton;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// The address of an endless loop to keep the CPU busy after switching the speed.
+const LOOP_ADDRESS: u16 = 0xc010;
+
+/// Opcode of the STOP instruction.
+const OPCODE_STOP: u8 = 0x10;
+
+/// The number of frames to be run for each measurement.
+const FRAMES: usize = 30;
+
+/// The maximum number of cycles a single instruction may take.
+const MAX_INSTRUCTION_CYCLES: Clock = 24;
+
+
+/// Creates a new emulator instance of the given device and emulation type,
+/// which will execute STOP instructions stored in WRAM.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    for offset in 0..4 {
+        gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + offset, OPCODE_STOP);
+    }
+
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+
+    gb
+}
+
+
+/// The results of running a number of frames.
+struct FrameMeasurement {
+    /// The number of CPU cycles processed.
+    cpu_cycles: Clock,
+
+    /// The number of system clock cycles processed.
+    system_cycles: Clock,
+
+    /// The emulated time passed in seconds.
+    seconds: f32,
+
+    /// The number of audio samples received.
+    samples: usize,
+}
+
+
+/// Runs a number of frames with the LCD enabled and measures the time and samples generated.
+fn measure_frames(gb: &mut GameBoy, receiver: &SamplesReceiver) -> FrameMeasurement {
+    let mut samples      = Vec::new();
+    let mut sample_count = 0;
+    let mut cpu_cycles   = 0;
+    let system_begin     = gb.get_total_system_cycles_processed();
+    let seconds_begin    = gb.get_total_seconds_processed();
+
+    for _ in 0..FRAMES {
+        cpu_cycles   += gb.run_frame().cycles;
+        sample_count += receiver.take_interleaved(&mut samples);
+    }
+
+    FrameMeasurement {
+        cpu_cycles,
+        system_cycles: gb.get_total_system_cycles_processed() - system_begin,
+        seconds:       gb.get_total_seconds_processed() - seconds_begin,
+        samples:       sample_count,
+    }
+}
+
+
+#[test]
+fn test_normal_speed_on_start() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED);
+    assert_eq!(gb.get_peripherals_mut().apu.get_audio_output().get_clock_speed(), CPU_CLOCK_SPEED);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1), 0x7e);
+}
+
+
+#[test]
+fn test_speed_doubles_after_switch() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    // request the speed switch
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1), 0x7f);
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED);
+
+    // STOP performs the speed switch
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED * 2);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1), 0xfe);
+
+    // the APU keeps running on the system clock, so the audio output is not affected
+    assert_eq!(gb.get_peripherals_mut().apu.get_audio_output().get_clock_speed(), CPU_CLOCK_SPEED);
+
+    // without another request, STOP does not change the speed again, but stops the CPU
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED * 2);
+    assert!(gb.cpu.is_stopped());
+
+    // wake up the CPU by pressing a button
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x00);
+    gb.get_peripherals_mut().input.set_button_pressed(InputButton::A, true);
+    gb.run_single_step();
+    assert!(!gb.cpu.is_stopped());
+
+    // switch back into normal speed
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED);
+    assert_eq!(gb.get_peripherals_mut().apu.get_audio_output().get_clock_speed(), CPU_CLOCK_SPEED);
+}
+
+
+#[test]
+fn test_no_speed_switch_on_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1), 0xff);
+
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED);
+}
+
+
+#[test]
+fn test_frame_timing_unchanged_by_double_speed() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    // JR -2
+    gb.get_mmu_mut().write_u8(LOOP_ADDRESS,     0x18);
+    gb.get_mmu_mut().write_u8(LOOP_ADDRESS + 1, 0xfe);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR52, 0x80);
+
+    let receiver = gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::Native }
+    ).unwrap();
+
+    // perform the speed switch and keep the CPU running in the loop afterwards
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED * 2);
+    gb.cpu.set_instruction_pointer(LOOP_ADDRESS);
+
+    // the first frame after the switch only completes the frame already started
+    gb.run_frame();
+    receiver.take_interleaved(&mut Vec::new());
+
+    let double_speed = measure_frames(&mut gb, &receiver);
+
+    // switch back into normal speed
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.run_single_step();
+    assert_eq!(gb.effective_clock_speed(), CPU_CLOCK_SPEED);
+    gb.cpu.set_instruction_pointer(LOOP_ADDRESS);
+
+    gb.run_frame();
+    receiver.take_interleaved(&mut Vec::new());
+
+    let normal_speed = measure_frames(&mut gb, &receiver);
+
+    // each frame takes the same amount of time on the system clock
+    let frame_cycles = CPU_CYCLES_PER_FRAME * FRAMES as Clock;
+    assert_eq!(frame_cycles, normal_speed.system_cycles);
+    assert_eq!(frame_cycles, double_speed.system_cycles);
+
+    // while the CPU runs twice as many cycles per frame in double speed
+    assert!(normal_speed.cpu_cycles.abs_diff(frame_cycles)     < MAX_INSTRUCTION_CYCLES);
+    assert!(double_speed.cpu_cycles.abs_diff(frame_cycles * 2) < MAX_INSTRUCTION_CYCLES * 2);
+
+    // the number of frames per emulated second stays the same
+    let expected_seconds = (frame_cycles as f32) / (CPU_CLOCK_SPEED as f32);
+    assert!((normal_speed.seconds - expected_seconds).abs() < 0.001);
+    assert!((double_speed.seconds - expected_seconds).abs() < 0.001);
+
+    // as well as the number of samples per frame, with a tolerance of one buffer
+    // for the samples pending in the buffer not yet transmitted
+    assert_ne!(0, normal_speed.samples);
+    assert!(
+        normal_speed.samples.abs_diff(double_speed.samples) <= SAMPLE_BUFFER_SIZE,
+        "{} samples in normal speed vs. {} in double speed", normal_speed.samples, double_speed.samples
+    );
+}
@@ -0,0 +1,234 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::cpu::{CpuFlag, RegisterR16};
+use gemi_core::debug::DebugEvent;
+use gemi_core::gameboy::{Clock, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address in WRAM where test programs are stored.
+const PROGRAM_START: u16 = 0xc000;
+
+/// The initial stack pointer for each test.
+const STACK_START: u16 = 0xdff0;
+
+
+/// Creates a new emulator instance with the given program stored in WRAM
+/// and the instruction pointer set to its beginning.
+fn create_gameboy(program: &[u8]) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(PROGRAM_START + offset as u16, *byte);
+    }
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x00);
+    gb.cpu.disable_interrupts();
+    gb.cpu.set_instruction_pointer(PROGRAM_START);
+    gb.cpu.set_stack_pointer(STACK_START);
+
+    gb
+}
+
+
+/// Runs a single instruction and returns the number of cycles consumed.
+fn step(gb: &mut GameBoy) -> Clock {
+    gb.run_single_step().cycles
+}
+
+
+#[test]
+fn test_push_pop() {
+    // PUSH BC; POP DE
+    let mut gb = create_gameboy(&[0xc5, 0xd1]);
+    gb.cpu.set_r16(RegisterR16::BC, 0x1234);
+
+    assert_eq!(16, step(&mut gb));
+    assert_eq!(STACK_START - 2, gb.cpu.get_stack_pointer());
+
+    // the high byte is stored at the higher address
+    assert_eq!(0x12, gb.get_mmu().read_u8(STACK_START - 1));
+    assert_eq!(0x34, gb.get_mmu().read_u8(STACK_START - 2));
+
+    assert_eq!(12, step(&mut gb));
+    assert_eq!(STACK_START, gb.cpu.get_stack_pointer());
+    assert_eq!(0x1234, gb.cpu.get_r16(RegisterR16::DE));
+}
+
+
+#[test]
+fn test_pop_af_masks_flags() {
+    // PUSH BC; POP AF
+    let mut gb = create_gameboy(&[0xc5, 0xf1]);
+    gb.cpu.set_r16(RegisterR16::BC, 0x12ff);
+
+    step(&mut gb);
+    step(&mut gb);
+
+    // the lower 4 bits of F are always zero
+    assert_eq!(0x12f0, gb.cpu.get_r16(RegisterR16::AF));
+}
+
+
+#[test]
+fn test_call_and_ret() {
+    // CALL $c010; ...; at $c010: RET
+    let mut gb = create_gameboy(&[0xcd, 0x10, 0xc0]);
+    gb.get_mmu_mut().write_u8(PROGRAM_START + 0x10, 0xc9);
+
+    assert_eq!(24, step(&mut gb));
+    assert_eq!(PROGRAM_START + 0x10, gb.cpu.get_instruction_pointer());
+
+    // the address of the next instruction was pushed
+    assert_eq!(STACK_START - 2, gb.cpu.get_stack_pointer());
+    assert_eq!(PROGRAM_START + 3, gb.get_mmu().read_u16(STACK_START - 2));
+
+    assert_eq!(16, step(&mut gb));
+    assert_eq!(PROGRAM_START + 3, gb.cpu.get_instruction_pointer());
+    assert_eq!(STACK_START, gb.cpu.get_stack_pointer());
+}
+
+
+#[test]
+fn test_reti_enables_interrupts() {
+    // RETI
+    let mut gb = create_gameboy(&[0xd9]);
+    gb.cpu.push_u16(0x1234);
+
+    assert_eq!(16, step(&mut gb));
+    assert_eq!(0x1234, gb.cpu.get_instruction_pointer());
+    assert_eq!(STACK_START, gb.cpu.get_stack_pointer());
+    assert!(gb.cpu.is_interrupts_enabled());
+}
+
+
+#[test]
+fn test_conditional_ret_cycles() {
+    // RET NZ
+    for (zero, expected_cycles, expected_pc) in [
+        (true,   8, PROGRAM_START + 1),
+        (false, 20, 0x1234),
+    ] {
+        let mut gb = create_gameboy(&[0xc0]);
+        gb.cpu.push_u16(0x1234);
+        gb.cpu.set_flag(CpuFlag::Zero, zero);
+
+        assert_eq!(expected_cycles, step(&mut gb));
+        assert_eq!(expected_pc, gb.cpu.get_instruction_pointer());
+    }
+}
+
+
+#[test]
+fn test_conditional_call_cycles() {
+    // CALL C, $1234
+    for (carry, expected_cycles, expected_pc, expected_sp) in [
+        (false, 12, PROGRAM_START + 3, STACK_START),
+        (true,  24, 0x1234,            STACK_START - 2),
+    ] {
+        let mut gb = create_gameboy(&[0xdc, 0x34, 0x12]);
+        gb.cpu.set_flag(CpuFlag::Carry, carry);
+
+        assert_eq!(expected_cycles, step(&mut gb));
+        assert_eq!(expected_pc, gb.cpu.get_instruction_pointer());
+        assert_eq!(expected_sp, gb.cpu.get_stack_pointer());
+    }
+}
+
+
+#[test]
+fn test_conditional_jump_cycles() {
+    // JP C, $1234
+    for (carry, expected_cycles, expected_pc) in [
+        (false, 12, PROGRAM_START + 3),
+        (true,  16, 0x1234),
+    ] {
+        let mut gb = create_gameboy(&[0xda, 0x34, 0x12]);
+        gb.cpu.set_flag(CpuFlag::Carry, carry);
+
+        assert_eq!(expected_cycles, step(&mut gb));
+        assert_eq!(expected_pc, gb.cpu.get_instruction_pointer());
+    }
+}
+
+
+#[test]
+fn test_stack_overflow_violation() {
+    // CALL $c000, calling itself recursively
+    let mut gb = create_gameboy(&[0xcd, 0x00, 0xc0]);
+    gb.cpu.set_stack_bounds(STACK_START - 8, STACK_START);
+
+    // four calls stay within the bounds
+    for _ in 0..4 {
+        let results = gb.run_single_step();
+        assert!(!results.events.contains(DebugEvent::StackViolation));
+    }
+
+    // the fifth call exceeds the lower bound
+    let results = gb.run_single_step();
+    assert!(results.events.contains(DebugEvent::StackViolation));
+    assert_eq!(STACK_START - 10, gb.cpu.get_stack_pointer());
+
+    // running a frame stops on the next violation
+    let results = gb.run_frame();
+    assert!(results.events.contains(DebugEvent::StackViolation));
+    assert_eq!(STACK_START - 12, gb.cpu.get_stack_pointer());
+}
+
+
+#[test]
+fn test_stack_underflow_violation() {
+    // RET; RET
+    let mut gb = create_gameboy(&[0xc9]);
+    gb.get_mmu_mut().write_u8(PROGRAM_START + 0x10, 0xc9);
+    gb.cpu.set_stack_bounds(STACK_START - 8, STACK_START);
+    gb.cpu.push_u16(PROGRAM_START + 0x10);
+
+    // returning to the address pushed before stays within the bounds
+    let results = gb.run_single_step();
+    assert!(!results.events.contains(DebugEvent::StackViolation));
+    assert_eq!(STACK_START, gb.cpu.get_stack_pointer());
+
+    // returning again exceeds the upper bound
+    let results = gb.run_single_step();
+    assert!(results.events.contains(DebugEvent::StackViolation));
+}
+
+
+#[test]
+fn test_no_stack_violation_without_bounds() {
+    // CALL $c000, calling itself recursively
+    let mut gb = create_gameboy(&[0xcd, 0x00, 0xc0]);
+
+    for _ in 0..100 {
+        let results = gb.run_single_step();
+        assert!(!results.events.contains(DebugEvent::StackViolation));
+    }
+
+    // violations are only reported while bounds are set
+    gb.cpu.set_stack_bounds(STACK_START - 8, STACK_START);
+    assert!(gb.run_single_step().events.contains(DebugEvent::StackViolation));
+
+    gb.cpu.clear_stack_bounds();
+    assert_eq!(gb.cpu.get_stack_bounds(), None);
+    assert!(!gb.run_single_step().events.contains(DebugEvent::StackViolation));
+}
@@ -0,0 +1,200 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::input::InputButton;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// Opcode of the STOP instruction.
+const OPCODE_STOP: u8 = 0x10;
+
+/// The bit of the joypad interrupt in the IF register.
+const INTERRUPT_BIT_JOYPAD: u8 = 0x10;
+
+
+/// Creates a new emulator instance of the given device and emulation type, which
+/// will execute a STOP instruction followed by an endless loop stored in WRAM.
+/// The background palette is set to display the whole screen in black.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    gb.initialize();
+
+    // STOP; JR -2
+    gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS,     OPCODE_STOP);
+    gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + 1, 0x18);
+    gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + 2, 0xfe);
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.cpu.disable_interrupts();
+
+    // select both button groups to receive joypad interrupts
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_PALETTE_BG, 0xff);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+
+    gb
+}
+
+
+/// Creates a new emulator instance running in classic GameBoy mode, with a frame already rendered.
+fn create_gameboy_dmg() -> GameBoy {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    // skip the STOP instruction to render a frame first
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS + 1);
+    gb.run_frame();
+    gb.run_frame();
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+
+    gb
+}
+
+
+/// Checks whether the screen is completely blank.
+fn is_screen_blank(gb: &GameBoy) -> bool {
+    let ppu   = &gb.get_peripherals().ppu;
+    let blank = ppu.get_dmg_display_palette().get_colors()[0].to_u32();
+
+    (0..SCREEN_H).all(|y| (0..SCREEN_W).all(|x| ppu.get_lcd().get_pixel(x, y).to_u32() == blank))
+}
+
+
+#[test]
+fn test_stop_blanks_screen() {
+    let mut gb = create_gameboy_dmg();
+    assert!(!is_screen_blank(&gb));
+
+    gb.run_single_step();
+
+    assert!(gb.cpu.is_stopped());
+    assert!(!gb.cpu.is_running());
+    assert!(is_screen_blank(&gb));
+
+    // the screen stays blank while stopped
+    gb.run_frame();
+    assert!(is_screen_blank(&gb));
+}
+
+
+#[test]
+fn test_stop_halts_system_clock() {
+    let mut gb = create_gameboy_dmg();
+    gb.run_single_step();
+
+    let ly  = gb.get_mmu().read_u8(MEMORY_LOCATION_LY);
+    let div = gb.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_DIV);
+
+    for _ in 0..1000 {
+        gb.run_single_step();
+    }
+
+    assert!(gb.cpu.is_stopped());
+    assert_eq!(PROGRAM_ADDRESS + 1, gb.cpu.get_instruction_pointer());
+    assert_eq!(ly,  gb.get_mmu().read_u8(MEMORY_LOCATION_LY));
+    assert_eq!(div, gb.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_DIV));
+}
+
+
+#[test]
+fn test_stop_not_left_by_other_interrupts() {
+    let mut gb = create_gameboy_dmg();
+    gb.run_single_step();
+
+    // a pending timer interrupt does not wake up the CPU, unlike in HALT mode
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x04);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x04);
+    gb.run_single_step();
+
+    assert!(gb.cpu.is_stopped());
+}
+
+
+#[test]
+fn test_stop_left_by_button_press() {
+    let mut gb = create_gameboy_dmg();
+    gb.run_single_step();
+    gb.run_single_step();
+    assert!(gb.cpu.is_stopped());
+
+    gb.get_peripherals_mut().input.set_button_pressed(InputButton::Start, true);
+    gb.run_single_step();
+
+    assert!(!gb.cpu.is_stopped());
+    assert!(gb.cpu.is_running());
+    assert_eq!(INTERRUPT_BIT_JOYPAD, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED) & INTERRUPT_BIT_JOYPAD);
+
+    // the CPU continues with the instruction following STOP
+    gb.run_single_step();
+    assert_eq!(PROGRAM_ADDRESS + 1, gb.cpu.get_instruction_pointer());
+
+    // the screen will be displayed again after the next frame
+    gb.run_frame();
+    gb.run_frame();
+    assert!(!is_screen_blank(&gb));
+}
+
+
+#[test]
+fn test_stop_not_left_by_unselected_buttons() {
+    let mut gb = create_gameboy_dmg();
+
+    // select the action buttons only
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x10);
+    gb.run_single_step();
+
+    gb.get_peripherals_mut().input.set_button_pressed(InputButton::DPadUp, true);
+    gb.run_single_step();
+    assert!(gb.cpu.is_stopped());
+
+    gb.get_peripherals_mut().input.set_button_pressed(InputButton::A, true);
+    gb.run_single_step();
+    assert!(!gb.cpu.is_stopped());
+}
+
+
+#[test]
+fn test_speed_switch_does_not_stop_on_gbc() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    // render a frame first
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS + 1);
+    gb.run_frame();
+    gb.run_frame();
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+
+    // with a speed switch requested, STOP just performs the switch
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+    gb.run_single_step();
+
+    assert!(gb.get_peripherals().mem.is_double_speed());
+    assert!(!gb.cpu.is_stopped());
+    assert!(!is_screen_blank(&gb));
+
+    // without a speed switch requested, STOP stops the CPU
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.run_single_step();
+
+    assert!(gb.get_peripherals().mem.is_double_speed());
+    assert!(gb.cpu.is_stopped());
+}
@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::cpu::INTERRUPT_DISPATCH_CYCLES;
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::gameboy::{CycleBreakdown, EmulatorUpdateResults, GameBoy, ResetKind};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address in WRAM where the test program is stored.
+const PROGRAM_START: u16 = 0xc000;
+
+/// A program running a few instructions before entering HALT.
+const PROGRAM: [u8; 6] = [
+    0x00,               // NOP          4 cycles
+    0x21, 0x34, 0x12,   // LD HL, $1234 12 cycles
+    0xe5,               // PUSH HL      16 cycles
+    0x76,               // HALT         4 cycles
+];
+
+
+/// Creates a new emulator instance running the test program with interrupts disabled.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    for (offset, byte) in PROGRAM.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(PROGRAM_START + offset as u16, *byte);
+    }
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x01);
+    gb.cpu.disable_interrupts();
+    gb.cpu.set_instruction_pointer(PROGRAM_START);
+    gb.cpu.set_stack_pointer(0xdff0);
+
+    gb
+}
+
+
+/// Runs a number of steps and sums up their results.
+fn run_steps(gb: &mut GameBoy, steps: usize) -> EmulatorUpdateResults {
+    let mut results = EmulatorUpdateResults::default();
+
+    for _ in 0..steps {
+        results += gb.run_single_step();
+    }
+
+    results
+}
+
+
+#[test]
+fn test_cycle_breakdown() {
+    let mut gb = create_gameboy();
+
+    // the instructions up to and including HALT
+    let results = run_steps(&mut gb, 4);
+    assert_eq!(CycleBreakdown { cpu: 36, interrupt_dispatch: 0, halt: 0 }, results.cycle_breakdown);
+    assert_eq!(36, results.cycles);
+    assert_eq!(4, gb.get_total_instructions_executed());
+
+    // the CPU stays suspended without any interrupt
+    let results = run_steps(&mut gb, 3);
+    assert_eq!(CycleBreakdown { cpu: 0, interrupt_dispatch: 0, halt: 12 }, results.cycle_breakdown);
+
+    // requesting an interrupt leaves HALT on the next cycle and dispatches the interrupt afterwards
+    gb.cpu.enable_interrupts();
+    gb.get_peripherals_mut().interrupts.request_interrupt(Interrupt::VBlank);
+
+    let results = run_steps(&mut gb, 2);
+    assert_eq!(CycleBreakdown { cpu: 0, interrupt_dispatch: INTERRUPT_DISPATCH_CYCLES, halt: 4 }, results.cycle_breakdown);
+    assert_eq!(INTERRUPT_DISPATCH_CYCLES + 4, results.cycles);
+
+    // neither halt cycles nor interrupts count as instructions
+    assert_eq!(4, gb.get_total_instructions_executed());
+}
+
+
+#[test]
+fn test_cycle_breakdown_sums_up_to_total_cycles() {
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    let results   = gb.run_frame();
+    let breakdown = results.cycle_breakdown;
+
+    assert_eq!(results.cycles, breakdown.cpu + breakdown.interrupt_dispatch + breakdown.halt);
+    assert!(gb.get_total_instructions_executed() > 0);
+
+    // the instruction counter starts over after reset
+    gb.reset(ResetKind::Soft);
+    assert_eq!(0, gb.get_total_instructions_executed());
+}
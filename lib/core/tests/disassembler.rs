@@ -0,0 +1,171 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::disassembler::{Disassembler, SymbolTable};
+
+
+/// A small program to be disassembled.
+const PROGRAM: [u8; 12] = [
+    0x00,               // NOP
+    0x21, 0x34, 0x12,   // LD HL, $1234
+    0x3e, 0x42,         // LD A, $42
+    0xcb, 0x37,         // SWAP A
+    0x18, 0xfe,         // JR -2
+    0xc3, 0x00,         // JP 0x..00 (incomplete)
+];
+
+
+/// Reads a byte from the test program. Any address outside the program reads 0x00.
+fn read_program(address: u16) -> u8 {
+    PROGRAM.get(address as usize).copied().unwrap_or(0x00)
+}
+
+
+/// Disassembles the given range of the test program into strings.
+fn disassemble_to_strings(start: u16, end: u16) -> Vec<String> {
+    Disassembler::new(read_program)
+        .disassemble_range(start, end)
+        .iter()
+        .map(|instruction| instruction.to_string())
+        .collect()
+}
+
+
+#[test]
+fn test_disassemble_range() {
+    let instructions = Disassembler::new(read_program).disassemble_range(0x0000, 0x000a);
+
+    let addresses: Vec<u16> = instructions.iter().map(|i| i.opcode_address).collect();
+    assert_eq!(addresses, vec![0x0000, 0x0001, 0x0004, 0x0006, 0x0008]);
+
+    assert_eq!(
+        disassemble_to_strings(0x0000, 0x000a),
+        vec!["NOP", "LD HL, $1234", "LD A, $42", "SWAP A", "JR -2"]
+    );
+}
+
+
+#[test]
+fn test_last_instruction_may_exceed_range() {
+    // the range ends within the LD HL instruction, which is still included
+    assert_eq!(disassemble_to_strings(0x0000, 0x0002), vec!["NOP", "LD HL, $1234"]);
+
+    // the JP instruction reads its argument beyond the end of the program
+    assert_eq!(disassemble_to_strings(0x000a, 0x000b), vec!["JP 0x0000"]);
+}
+
+
+#[test]
+fn test_disassemble_count() {
+    let disassembler = Disassembler::new(read_program);
+
+    let instructions = disassembler.disassemble_count(0x0001, 3);
+    assert_eq!(instructions.len(), 3);
+    assert_eq!(instructions[2].to_string(), "SWAP A");
+    assert_eq!(instructions[2].get_jump_target(), None);
+
+    let instructions = disassembler.disassemble_count(0x0008, 1);
+    assert_eq!(instructions[0].get_jump_target(), Some(0x0008));
+}
+
+
+#[test]
+fn test_read_instruction_bytes() {
+    let disassembler = Disassembler::new(read_program);
+
+    let instruction = disassembler.disassemble_at(0x0001);
+    assert_eq!(disassembler.read_instruction_bytes(&instruction), vec![0x21, 0x34, 0x12]);
+
+    let instruction = disassembler.disassemble_at(0x0006);
+    assert_eq!(disassembler.read_instruction_bytes(&instruction), vec![0xcb, 0x37]);
+}
+
+
+#[test]
+fn test_stop_at_end_of_address_space() {
+    let disassembler = Disassembler::new(|_| 0x00);
+
+    // NOP at 0xfffe is the last instruction before reaching the end of the address space
+    let instructions = disassembler.disassemble_count(0xfffd, 10);
+    assert_eq!(instructions.len(), 2);
+
+    let instructions = disassembler.disassemble_range(0xfff0, 0xffff);
+    assert_eq!(instructions.len(), 15);
+    assert_eq!(instructions.last().unwrap().opcode_address, 0xfffe);
+}
+
+
+#[test]
+fn test_invalid_opcodes_as_data_bytes() {
+    let disassembler = Disassembler::new(|_| 0xdd);
+
+    // each invalid opcode is displayed as a single data byte
+    let instructions = disassembler.disassemble_range(0x4000, 0x4010);
+    assert_eq!(instructions.len(), 16);
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        assert_eq!(instruction.opcode_address, 0x4000 + index as u16);
+        assert_eq!(instruction.get_instruction_length(), 1);
+        assert!(instruction.is_data_byte());
+        assert_eq!(instruction.to_string(), "DB $dd");
+        assert_eq!(disassembler.read_instruction_bytes(instruction), vec![0xdd]);
+    }
+
+    // valid opcodes are not affected
+    assert!(!Disassembler::new(read_program).disassemble_at(0x0000).is_data_byte());
+}
+
+
+#[test]
+fn test_hardware_symbols() {
+    let symbols = SymbolTable::new();
+
+    assert_eq!(symbols.get_symbol(0x0100), Some("EntryPoint"));
+    assert_eq!(symbols.get_symbol(0x0038), Some("RST_38"));
+    assert_eq!(symbols.get_symbol(0x0040), Some("VBlankInterrupt"));
+    assert_eq!(symbols.get_symbol(0x0060), Some("JoypadInterrupt"));
+    assert_eq!(symbols.get_symbol(0x0150), None);
+}
+
+
+#[test]
+fn test_parse_sym_file() {
+    let symbols = SymbolTable::from_no_gba_sym(
+        "; File generated by rgblink\n\
+         00:0150 Main\n\
+         00:0040 VBlankHandler\n\
+         01:4000 BankedRoutine ; comment\n\
+         02:4000 OtherBankedRoutine\n\
+         invalid line\n\
+         00:xyz0 InvalidAddress\n\
+         \n\
+         00:c000 wVariable\n"
+    );
+
+    assert_eq!(symbols.get_symbol(0x0150), Some("Main"));
+    assert_eq!(symbols.get_symbol(0xc000), Some("wVariable"));
+
+    // hardware names are replaced by symbols from the file
+    assert_eq!(symbols.get_symbol(0x0040), Some("VBlankHandler"));
+    assert_eq!(symbols.get_symbol(0x0100), Some("EntryPoint"));
+
+    // for addresses in multiple banks the first symbol is used
+    assert_eq!(symbols.get_symbol(0x4000), Some("BankedRoutine"));
+
+    // 14 hardware symbols + 3 new addresses
+    assert_eq!(symbols.len(), 17);
+}
@@ -0,0 +1,158 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::debug::DMA_HISTORY_SIZE;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::mmu::memory::DmaTransferState;
+use gemi_core::ppu::frame_log::{FrameLog, FrameLogEventKind};
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// Creates a new emulator instance running an endless loop in WRAM,
+/// after the DMA transfer started by the initial register values was completed.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    start_idle_loop(&mut gb);
+
+    run_until_dma_completed(&mut gb);
+
+    gb
+}
+
+
+/// Runs the emulator until the current DMA transfer is completed.
+fn run_until_dma_completed(gb: &mut GameBoy) {
+    while gb.get_mmu().is_dma_transfer_active() {
+        gb.run_single_step();
+    }
+}
+
+
+/// Runs the emulator until the PPU completed the current frame and returns its frame log.
+fn run_until_frame_log_completed(gb: &mut GameBoy) -> FrameLog {
+    loop {
+        gb.run_single_step();
+
+        if let Some(frame_log) = gb.get_peripherals_mut().ppu.take_frame_log() {
+            return frame_log;
+        }
+    }
+}
+
+
+#[test]
+fn test_dma_transfer_state() {
+    let mut gb = create_gameboy();
+    assert_eq!(DmaTransferState::Disabled, *gb.get_mmu().get_dma_transfer_state());
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0xc1);
+
+    let DmaTransferState::Transferring(transfer) = *gb.get_mmu().get_dma_transfer_state() else {
+        panic!("Expected a DMA transfer to be running");
+    };
+
+    assert_eq!(0xc100, transfer.start_address);
+    assert_eq!(160, transfer.get_remaining_bytes());
+
+    gb.run_single_step();
+
+    let DmaTransferState::Transferring(transfer) = *gb.get_mmu().get_dma_transfer_state() else {
+        panic!("Expected a DMA transfer to be running");
+    };
+
+    assert!(transfer.get_remaining_bytes() < 160);
+}
+
+
+#[test]
+fn test_dma_history_disabled_by_default() {
+    let mut gb = create_gameboy();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0xc1);
+    run_until_dma_completed(&mut gb);
+
+    assert!(gb.get_dma_history().is_empty());
+}
+
+
+#[test]
+fn test_dma_history_records_completed_transfers() {
+    let mut gb = create_gameboy();
+    gb.enable_dma_history(true);
+
+    let start_cycle = gb.get_total_cycles_processed();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0xc1);
+
+    // not recorded before being completed
+    gb.run_single_step();
+    assert!(gb.get_dma_history().is_empty());
+
+    run_until_dma_completed(&mut gb);
+
+    let events: Vec<_> = gb.get_dma_history().iter().copied().collect();
+    assert_eq!(1, events.len());
+    assert_eq!(0xc100, events[0].source_address);
+    assert_eq!(gb.get_total_cycles_processed(), events[0].cycle);
+    assert!(events[0].cycle >= start_cycle + 160);
+
+    gb.clear_dma_history();
+    assert!(gb.get_dma_history().is_empty());
+}
+
+
+#[test]
+fn test_dma_history_size_limit() {
+    let mut gb = create_gameboy();
+    gb.enable_dma_history(true);
+
+    for page in 0 .. (DMA_HISTORY_SIZE as u8 + 5) {
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0xc0 + page);
+        run_until_dma_completed(&mut gb);
+    }
+
+    let history = gb.get_dma_history();
+    assert_eq!(DMA_HISTORY_SIZE, history.len());
+
+    // the oldest transfers were dropped
+    assert_eq!(Some(0xc500), history.iter().next().map(|event| event.source_address));
+    assert_eq!(Some(0xce00), history.iter().last().map(|event| event.source_address));
+}
+
+
+#[test]
+fn test_dma_transfer_logged_once_in_frame_log() {
+    let mut gb = create_gameboy();
+    gb.get_peripherals_mut().ppu.set_frame_log_enabled(true);
+
+    // start the transfer at the beginning of a new frame
+    run_until_frame_log_completed(&mut gb);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0xc1);
+
+    let frame_log = run_until_frame_log_completed(&mut gb);
+    let events: Vec<_> = frame_log.lines.iter().flat_map(|line| line.events.iter()).collect();
+
+    let started   = events.iter().filter(|event| matches!(event.kind, FrameLogEventKind::OamDmaStarted)).count();
+    let completed = events.iter().filter(|event| matches!(event.kind, FrameLogEventKind::OamDmaCompleted)).count();
+
+    assert_eq!(1, started);
+    assert_eq!(1, completed);
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cpu::cpu::CPU_CLOCK_SPEED;
+use gemi_core::frame_stats::FrameTimingStats;
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// Creates a new emulator instance running an endless loop in WRAM.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+/// Runs the given number of frames and returns the number of cycles processed.
+fn run_frames(gb: &mut GameBoy, frames: u64) -> u64 {
+    (0..frames).map(|_| gb.run_frame().cycles).sum()
+}
+
+
+#[test]
+fn test_no_stats_before_running() {
+    let gb = create_gameboy();
+
+    assert_eq!(FrameTimingStats::default(), *gb.get_frame_stats());
+}
+
+
+#[test]
+fn test_count_frames() {
+    let mut gb = create_gameboy();
+    let cycles = run_frames(&mut gb, 10);
+    let stats  = gb.get_frame_stats();
+
+    let expected_ms = (cycles as f64) / (CPU_CLOCK_SPEED as f64) * 1000.0;
+
+    assert_eq!(10, stats.frame_count);
+    assert!((stats.emulated_ms - expected_ms).abs() < 0.001);
+    assert!(stats.real_ms > 0.0);
+    assert!(stats.average_fps > 0.0);
+}
+
+
+#[test]
+fn test_single_steps_not_counted() {
+    let mut gb = create_gameboy();
+
+    for _ in 0..100 {
+        gb.run_single_step();
+    }
+
+    assert_eq!(0, gb.get_frame_stats().frame_count);
+}
+
+
+#[test]
+fn test_reset_stats() {
+    let mut gb = create_gameboy();
+    run_frames(&mut gb, 10);
+
+    gb.reset_frame_stats();
+    assert_eq!(FrameTimingStats::default(), *gb.get_frame_stats());
+
+    // emulated time is measured from the reset
+    let cycles      = run_frames(&mut gb, 3);
+    let stats       = gb.get_frame_stats();
+    let expected_ms = (cycles as f64) / (CPU_CLOCK_SPEED as f64) * 1000.0;
+
+    assert_eq!(3, stats.frame_count);
+    assert!((stats.emulated_ms - expected_ms).abs() < 0.001);
+}
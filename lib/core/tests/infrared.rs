@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::MEMORY_LOCATION_RP;
+
+mod common;
+use common::{create_gameboy_color, create_gameboy_for};
+
+
+/// RP value with the LED switched off and reading signals enabled.
+const RP_READ_ENABLED: u8        = 0b_1100_0000;
+
+/// RP value with the LED switched on and reading signals enabled.
+const RP_READ_ENABLED_LED_ON: u8 = 0b_1100_0001;
+
+
+/// Checks whether the RP register reports an incoming signal.
+fn is_receiving(gb: &GameBoy) -> bool {
+    (gb.get_mmu().read_u8(MEMORY_LOCATION_RP) & 0b_0000_0010) == 0
+}
+
+
+#[test]
+fn test_rp_not_available_on_dmg() {
+    let mut gb = create_gameboy_for(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED_LED_ON);
+    gb.get_peripherals_mut().infrared.signal_received(true);
+
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_RP), 0xff);
+    assert!(!gb.get_peripherals().infrared.is_led_enabled());
+}
+
+
+#[test]
+fn test_rp_read_write() {
+    let mut gb = create_gameboy_color();
+
+    // unused bits are always set, no signal received
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, 0x00);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_RP), 0b_0011_1110);
+
+    // the LED bit and the read enable bits are readable
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, 0b_0100_0001);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_RP), 0b_0111_1111);
+    assert!(gb.get_peripherals().infrared.is_led_enabled());
+    assert!(!gb.get_peripherals().infrared.is_read_enabled());
+
+    // the receiving bit is read only
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED & !0b_0000_0010);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_RP), 0b_1111_1110);
+    assert!(gb.get_peripherals().infrared.is_read_enabled());
+}
+
+
+#[test]
+fn test_external_signal() {
+    let mut gb = create_gameboy_color();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED);
+    assert!(!is_receiving(&gb));
+
+    gb.get_peripherals_mut().infrared.signal_received(true);
+    assert!(is_receiving(&gb));
+
+    gb.get_peripherals_mut().infrared.signal_received(false);
+    assert!(!is_receiving(&gb));
+}
+
+
+#[test]
+fn test_signal_requires_read_enabled() {
+    let mut gb = create_gameboy_color();
+
+    gb.get_peripherals_mut().infrared.signal_received(true);
+
+    // only one of both bits set does not enable reading
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, 0b_1000_0000);
+    assert!(!is_receiving(&gb));
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED);
+    assert!(is_receiving(&gb));
+}
+
+
+#[test]
+fn test_led_loopback() {
+    let mut gb = create_gameboy_color();
+
+    // the device receives the light of its own LED
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED_LED_ON);
+    assert!(is_receiving(&gb));
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_RP, RP_READ_ENABLED);
+    assert!(!is_receiving(&gb));
+}
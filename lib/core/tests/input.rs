@@ -0,0 +1,116 @@
+/*
+ * Copyright (C) 2022-2023 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::input::InputButton;
+use gemi_core::mmu::locations::*;
+use gemi_core::mmu::memory_bus::MemoryBusConnection;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Presses a button, updates the input component and checks whether
+/// the joypad interrupt was requested.
+fn press_button_and_check_interrupt(gb: &mut GameBoy, button: InputButton) -> bool {
+    let input = &mut gb.get_peripherals_mut().input;
+    input.set_button_pressed(button, true);
+    input.update();
+
+    input.take_signals().interrupts.contains(Interrupt::Input)
+}
+
+
+#[test]
+fn interrupt_on_selected_button_pressed() {
+    let mut gb = create_gameboy_dmg();
+
+    // select action buttons
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0b_0001_0000);
+
+    assert!(press_button_and_check_interrupt(&mut gb, InputButton::A));
+}
+
+
+#[test]
+fn no_interrupt_on_unselected_button_pressed() {
+    let mut gb = create_gameboy_dmg();
+
+    // select direction buttons
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0b_0010_0000);
+
+    assert!(!press_button_and_check_interrupt(&mut gb, InputButton::A));
+}
+
+
+#[test]
+fn no_interrupt_on_button_held() {
+    let mut gb = create_gameboy_dmg();
+
+    // select action buttons
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0b_0001_0000);
+
+    assert!(press_button_and_check_interrupt(&mut gb, InputButton::A));
+    assert!(!press_button_and_check_interrupt(&mut gb, InputButton::A));
+}
+
+
+/// Selects button groups via JOYP, presses a set of buttons and
+/// returns the lower nibble read from JOYP.
+fn read_joyp_with_buttons_pressed(select_bits: u8, buttons: &[InputButton]) -> u8 {
+    let mut gb = create_gameboy_dmg();
+
+    for button in buttons {
+        gb.get_peripherals_mut().input.set_button_pressed(*button, true);
+    }
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, select_bits);
+    let value = gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_JOYP);
+
+    // select bits are expected to be read back
+    assert_eq!(select_bits | 0b_1100_0000, value & 0b_1111_0000);
+
+    value & 0x0f
+}
+
+
+#[test]
+fn joyp_read_direction_buttons() {
+    let buttons = [InputButton::DPadLeft, InputButton::DPadDown, InputButton::A];
+    assert_eq!(0b_0101, read_joyp_with_buttons_pressed(0b_0010_0000, &buttons));
+}
+
+
+#[test]
+fn joyp_read_action_buttons() {
+    let buttons = [InputButton::DPadLeft, InputButton::DPadDown, InputButton::A];
+    assert_eq!(0b_1110, read_joyp_with_buttons_pressed(0b_0001_0000, &buttons));
+}
+
+
+#[test]
+fn joyp_read_both_groups_combined() {
+    let buttons = [InputButton::DPadLeft, InputButton::DPadDown, InputButton::A];
+    assert_eq!(0b_0100, read_joyp_with_buttons_pressed(0b_0000_0000, &buttons));
+}
+
+
+#[test]
+fn joyp_read_no_group_selected() {
+    let buttons = [InputButton::DPadLeft, InputButton::DPadDown, InputButton::A];
+    assert_eq!(0b_1111, read_joyp_with_buttons_pressed(0b_0011_0000, &buttons));
+}
@@ -0,0 +1,117 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::debug::InstructionCounter;
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Opcode of NOP.
+const OPCODE_NOP: u16 = 0x00;
+
+/// Opcode ID of SWAP A, which is prefixed by 0xCB.
+const OPCODE_SWAP_A: u16 = 0x37cb;
+
+/// Opcode of JR.
+const OPCODE_JR: u16 = 0x18;
+
+
+/// Creates a new emulator instance running a loop of NOP, SWAP A and JR in WRAM.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let program = [
+        0x00,           // NOP
+        0xcb, 0x37,     // SWAP A
+        0x18, 0xfb,     // JR -5
+    ];
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(0xc000 + offset as u16, *byte);
+    }
+
+    gb.cpu.set_instruction_pointer(0xc000);
+    gb.cpu.disable_interrupts();
+
+    gb
+}
+
+
+/// Runs the given number of instructions.
+fn run_steps(gb: &mut GameBoy, steps: usize) {
+    for _ in 0..steps {
+        gb.run_single_step();
+    }
+}
+
+
+/// Get the number of executions of a single opcode.
+fn get_count(gb: &GameBoy, opcode_id: u16) -> u64 {
+    gb.get_instruction_counts()[InstructionCounter::get_index_of(opcode_id)]
+}
+
+
+#[test]
+fn test_opcode_index() {
+    assert_eq!(0x000, InstructionCounter::get_index_of(0x00));
+    assert_eq!(0x0ff, InstructionCounter::get_index_of(0xff));
+    assert_eq!(0x100, InstructionCounter::get_index_of(0x00cb));
+    assert_eq!(0x137, InstructionCounter::get_index_of(0x37cb));
+    assert_eq!(0x1ff, InstructionCounter::get_index_of(0xffcb));
+}
+
+
+#[test]
+fn test_counting_disabled_by_default() {
+    let mut gb = create_gameboy();
+    run_steps(&mut gb, 30);
+
+    assert!(gb.get_instruction_counts().iter().all(|count| *count == 0));
+}
+
+
+#[test]
+fn test_count_instructions() {
+    let mut gb = create_gameboy();
+    gb.enable_instruction_counting(true);
+    run_steps(&mut gb, 30);
+
+    assert_eq!(10, get_count(&gb, OPCODE_NOP));
+    assert_eq!(10, get_count(&gb, OPCODE_SWAP_A));
+    assert_eq!(10, get_count(&gb, OPCODE_JR));
+    assert_eq!(30, gb.get_instruction_counts().iter().sum::<u64>());
+
+    // the regular opcode 0x37 (SCF) was not executed
+    assert_eq!(0, get_count(&gb, 0x37));
+}
+
+
+#[test]
+fn test_stop_and_reset_counting() {
+    let mut gb = create_gameboy();
+    gb.enable_instruction_counting(true);
+    run_steps(&mut gb, 30);
+
+    // counts are kept while disabled
+    gb.enable_instruction_counting(false);
+    run_steps(&mut gb, 30);
+    assert_eq!(30, gb.get_instruction_counts().iter().sum::<u64>());
+
+    gb.reset_instruction_counts();
+    assert_eq!(0, gb.get_instruction_counts().iter().sum::<u64>());
+}
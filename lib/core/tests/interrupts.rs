@@ -0,0 +1,423 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::cpu::INTERRUPT_DISPATCH_CYCLES;
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::debug::INTERRUPT_HISTORY_SIZE;
+use gemi_core::gameboy::{AccuracyLevel, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::{create_gameboy_dmg, create_gameboy_with_accuracy, start_idle_loop};
+
+
+#[test]
+fn test_interrupt_state_initially_empty() {
+    let mut gb = create_gameboy_dmg();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.cpu.disable_interrupts();
+
+    let state = gb.cpu.get_interrupt_state();
+    assert!(!state.ime);
+    assert_eq!(0xe0, state.interrupts_flagged);
+    assert_eq!(None, state.get_next_interrupt());
+
+    for info in state.interrupts {
+        assert!(!info.pending);
+        assert!(!info.would_fire);
+    }
+}
+
+
+#[test]
+fn test_interrupt_state_vblank_requested() {
+    let mut gb = create_gameboy_dmg();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x00);
+    gb.cpu.disable_interrupts();
+
+    gb.get_peripherals_mut().interrupts.request_interrupt(Interrupt::VBlank);
+
+    // pending, but neither enabled via IE nor IME
+    let state = gb.cpu.get_interrupt_state();
+    assert_eq!(0xe1, state.interrupts_flagged);
+    assert!(state.get(Interrupt::VBlank).pending);
+    assert!(!state.get(Interrupt::VBlank).enabled);
+    assert!(!state.get(Interrupt::VBlank).would_fire);
+    assert!(!state.get(Interrupt::Timer).pending);
+
+    // enabled via IE, but IME still not set
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0x01);
+    let state = gb.cpu.get_interrupt_state();
+    assert_eq!(0x01, state.interrupts_enabled);
+    assert!(state.get(Interrupt::VBlank).enabled);
+    assert!(!state.get(Interrupt::VBlank).would_fire);
+
+    // with IE and IME set, the interrupt would fire
+    gb.cpu.enable_interrupts();
+    let state = gb.cpu.get_interrupt_state();
+    assert!(state.ime);
+    assert!(state.get(Interrupt::VBlank).would_fire);
+    assert_eq!(Some(Interrupt::VBlank), state.get_next_interrupt());
+}
+
+
+#[test]
+fn test_interrupt_state_next_by_priority() {
+    let mut gb = create_gameboy_dmg();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0001_0100);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0001_0100);
+    gb.cpu.enable_interrupts();
+
+    let state = gb.cpu.get_interrupt_state();
+    assert!(state.get(Interrupt::Timer).would_fire);
+    assert!(state.get(Interrupt::Input).would_fire);
+    assert_eq!(Some(Interrupt::Timer), state.get_next_interrupt());
+}
+
+
+#[test]
+fn test_dispatch_highest_priority_interrupt() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_instruction_pointer(0x1234);
+    gb.cpu.set_stack_pointer(0xfffe);
+
+    // STAT and Timer interrupts pending simultaneously
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0000_0110);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0001_1111);
+    gb.cpu.enable_interrupts();
+
+    assert_eq!(Some(INTERRUPT_DISPATCH_CYCLES), gb.cpu.handle_interrupts());
+    assert_eq!(20, INTERRUPT_DISPATCH_CYCLES);
+
+    // the STAT interrupt was taken and IME was cleared
+    assert_eq!(Interrupt::LcdStat.address(), gb.cpu.get_instruction_pointer());
+    assert!(!gb.cpu.is_interrupts_enabled());
+
+    // only the IF bit of the STAT interrupt was cleared
+    assert_eq!(0b_1110_0100, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+
+    // the previous program counter was pushed onto the stack
+    assert_eq!(0xfffc, gb.cpu.get_stack_pointer());
+    assert_eq!(0x1234, gb.get_mmu().read_u16(0xfffc));
+
+    // no further interrupt is dispatched while IME is cleared
+    assert_eq!(None, gb.cpu.handle_interrupts());
+}
+
+
+#[test]
+fn test_dispatch_order() {
+    let mut gb = create_gameboy_dmg();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0001_1111);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0001_1111);
+
+    for (vector, remaining) in [
+        (0x0040, 0b_1111_1110),
+        (0x0048, 0b_1111_1100),
+        (0x0050, 0b_1111_1000),
+        (0x0058, 0b_1111_0000),
+        (0x0060, 0b_1110_0000),
+    ] {
+        gb.cpu.set_stack_pointer(0xfffe);
+        gb.cpu.enable_interrupts();
+
+        assert_eq!(Some(INTERRUPT_DISPATCH_CYCLES), gb.cpu.handle_interrupts());
+        assert_eq!(vector,    gb.cpu.get_instruction_pointer());
+        assert_eq!(remaining, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+    }
+}
+
+
+#[test]
+fn test_no_dispatch_of_disabled_interrupt() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_instruction_pointer(0x1234);
+
+    // Timer interrupt pending, but only VBlank enabled
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0000_0100);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0000_0001);
+    gb.cpu.enable_interrupts();
+
+    assert_eq!(None, gb.cpu.handle_interrupts());
+    assert_eq!(0x1234, gb.cpu.get_instruction_pointer());
+    assert_eq!(0b_1110_0100, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+    assert!(gb.cpu.is_interrupts_enabled());
+}
+
+
+#[test]
+fn test_dispatch_cancelled_by_push_into_ie() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_instruction_pointer(0x1234);
+    gb.cpu.set_stack_pointer(0x0000);
+
+    // VBlank pending and enabled
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0000_0001);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0000_0001);
+    gb.cpu.enable_interrupts();
+
+    // pushing the upper byte of PC overwrites IE with 0x12, which disables VBlank
+    assert_eq!(Some(INTERRUPT_DISPATCH_CYCLES), gb.cpu.handle_interrupts());
+    assert_eq!(0x0000, gb.cpu.get_instruction_pointer());
+    assert_eq!(0xfffe, gb.cpu.get_stack_pointer());
+    assert_eq!(0x12,   gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED));
+    assert!(!gb.cpu.is_interrupts_enabled());
+
+    // the interrupt was not handled, so its IF bit is still set
+    assert_eq!(0b_1110_0001, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+}
+
+
+#[test]
+fn test_dispatch_changed_by_push_into_ie() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_instruction_pointer(0x1234);
+    gb.cpu.set_stack_pointer(0x0000);
+
+    // VBlank and STAT pending, but only VBlank enabled
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0000_0011);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0000_0001);
+    gb.cpu.enable_interrupts();
+
+    // IE gets overwritten with 0x12, which enables STAT instead of VBlank
+    assert_eq!(Some(INTERRUPT_DISPATCH_CYCLES), gb.cpu.handle_interrupts());
+    assert_eq!(Interrupt::LcdStat.address(), gb.cpu.get_instruction_pointer());
+    assert_eq!(0b_1110_0001, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+}
+
+
+#[test]
+fn test_dispatch_not_affected_by_lower_byte_push_into_ie() {
+    let mut gb = create_gameboy_dmg();
+    gb.cpu.set_instruction_pointer(0x1234);
+    gb.cpu.set_stack_pointer(0x0001);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0b_0000_0001);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED, 0b_0000_0001);
+    gb.cpu.enable_interrupts();
+
+    // the lower byte is written into IE after the interrupt was already selected
+    assert_eq!(Some(INTERRUPT_DISPATCH_CYCLES), gb.cpu.handle_interrupts());
+    assert_eq!(Interrupt::VBlank.address(), gb.cpu.get_instruction_pointer());
+    assert_eq!(0x34, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_ENABLED));
+    assert_eq!(0b_1110_0000, gb.get_mmu().read_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED));
+}
+
+
+/// Creates a new emulator instance with the LCD enabled, running an endless loop.
+fn create_gameboy_running_loop() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+#[test]
+fn test_interrupt_history_disabled_by_default() {
+    let mut gb = create_gameboy_running_loop();
+
+    gb.run_frame();
+    gb.run_frame();
+
+    assert!(gb.get_interrupt_history().is_empty());
+}
+
+
+#[test]
+fn test_interrupt_history_records_vblank() {
+    let mut gb = create_gameboy_running_loop();
+    gb.enable_interrupt_history(true);
+
+    for _ in 0..3 {
+        gb.run_frame();
+    }
+
+    let vblanks: Vec<_> = gb.get_interrupt_history()
+        .iter()
+        .filter(|event| event.interrupt == Interrupt::VBlank)
+        .collect()
+    ;
+
+    assert!(vblanks.len() >= 2);
+
+    // VBlank is requested when entering line 144 once per frame
+    for event in &vblanks {
+        assert_eq!(144, event.ly);
+    }
+
+    for pair in vblanks.windows(2) {
+        assert_eq!(CPU_CYCLES_PER_FRAME, pair[1].cycle - pair[0].cycle);
+    }
+
+    gb.clear_interrupt_history();
+    assert!(gb.get_interrupt_history().is_empty());
+}
+
+
+#[test]
+fn test_interrupt_history_keeps_most_recent_events() {
+    let mut gb = create_gameboy_running_loop();
+    gb.enable_interrupt_history(true);
+
+    // let the timer overflow every 16 cycles
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_REGISTER_TMA, 0xff);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_REGISTER_TAC, 0x05);
+
+    gb.run_frame();
+
+    let history = gb.get_interrupt_history();
+    assert_eq!(INTERRUPT_HISTORY_SIZE, history.len());
+
+    // events are ordered from the oldest to the most recent one
+    let cycles: Vec<_> = history.iter().map(|event| event.cycle).collect();
+    assert!(cycles.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(*cycles.last().unwrap() <= gb.get_total_cycles_processed());
+}
+
+
+/// STAT value enabling the interrupt on HBlank.
+const STAT_HBLANK: u8 = 0b_0000_1000;
+
+/// STAT value enabling the interrupt on LY matching LYC.
+const STAT_LYC: u8 = 0b_0100_0000;
+
+
+/// Creates a new emulator instance of the given device and emulation type with strict accuracy
+/// and the given STAT interrupt sources enabled, running an endless loop without handling any interrupts.
+fn create_gameboy_requesting_stat_interrupts(device: DeviceType, emulation: EmulationType, stat: u8) -> GameBoy {
+    create_gameboy_requesting_stat_interrupts_with_accuracy(device, emulation, AccuracyLevel::Strict, stat)
+}
+
+
+/// Creates a new emulator instance of the given device, emulation type and accuracy level with
+/// the given STAT interrupt sources enabled, running an endless loop without handling any interrupts.
+fn create_gameboy_requesting_stat_interrupts_with_accuracy(
+    device: DeviceType,
+    emulation: EmulationType,
+    accuracy: AccuracyLevel,
+    stat: u8
+) -> GameBoy {
+    let mut gb = create_gameboy_with_accuracy(device, emulation, accuracy);
+
+    gb.initialize();
+
+    start_idle_loop(&mut gb);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LYC, 10);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_STATUS, stat);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.enable_interrupt_history(true);
+
+    gb
+}
+
+
+/// Runs the emulator until LY and the PPU mode reported by STAT match the given values.
+fn run_until_line_and_mode(gb: &mut GameBoy, ly: u8, mode: u8) {
+    for _ in 0..CPU_CYCLES_PER_FRAME {
+        let current_ly   = gb.get_mmu().read_u8(MEMORY_LOCATION_LY);
+        let current_mode = gb.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11;
+
+        if current_ly == ly && current_mode == mode {
+            return;
+        }
+
+        gb.run_single_step();
+    }
+
+    panic!("LY {ly} with mode {mode} not reached");
+}
+
+
+/// Counts the STAT interrupts recorded in the interrupt history.
+fn count_stat_interrupts(gb: &GameBoy) -> usize {
+    gb.get_interrupt_history()
+        .iter()
+        .filter(|event| event.interrupt == Interrupt::LcdStat)
+        .count()
+}
+
+
+/// Counts the STAT interrupts requested during line 10, which matches LYC,
+/// while the STAT interrupt flag gets cleared before the line starts.
+fn count_stat_interrupts_on_lyc_line(gb: &mut GameBoy) -> usize {
+    run_until_line_and_mode(gb, 9, 0);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    let count_before = count_stat_interrupts(gb);
+
+    run_until_line_and_mode(gb, 11, 2);
+    count_stat_interrupts(gb) - count_before
+}
+
+
+#[test]
+fn test_stat_interrupt_blocked_while_flagged_on_dmg() {
+    let mut gb = create_gameboy_requesting_stat_interrupts(DeviceType::GameBoyDmg, EmulationType::DMG, STAT_HBLANK);
+
+    // without being handled, only the first HBlank requests an interrupt
+    gb.run_frame();
+    assert_eq!(1, count_stat_interrupts(&gb));
+
+    gb.run_frame();
+    assert_eq!(1, count_stat_interrupts(&gb));
+
+    // after clearing the flag, the next HBlank requests a new interrupt
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_INTERRUPTS_FLAGGED, 0x00);
+    gb.run_frame();
+    assert_eq!(2, count_stat_interrupts(&gb));
+}
+
+
+#[test]
+fn test_combined_stat_sources_request_single_interrupt_on_dmg() {
+    let mut gb = create_gameboy_requesting_stat_interrupts(DeviceType::GameBoyDmg, EmulationType::DMG, STAT_HBLANK | STAT_LYC);
+
+    // the LYC match requests an interrupt, which blocks the one of the following HBlank
+    assert_eq!(1, count_stat_interrupts_on_lyc_line(&mut gb));
+}
+
+
+#[test]
+fn test_stat_interrupt_not_blocked_on_gbc() {
+    let mut gb = create_gameboy_requesting_stat_interrupts(DeviceType::GameBoyColor, EmulationType::GBC, STAT_HBLANK);
+
+    // each HBlank requests an interrupt
+    gb.run_frame();
+    assert!(count_stat_interrupts(&gb) >= 144);
+
+    // both the LYC match and HBlank request an interrupt
+    let mut gb = create_gameboy_requesting_stat_interrupts(DeviceType::GameBoyColor, EmulationType::GBC, STAT_HBLANK | STAT_LYC);
+    assert_eq!(2, count_stat_interrupts_on_lyc_line(&mut gb));
+}
+
+
+#[test]
+fn test_stat_interrupt_not_blocked_below_strict_accuracy() {
+    let mut gb = create_gameboy_requesting_stat_interrupts_with_accuracy(
+        DeviceType::GameBoyDmg,
+        EmulationType::DMG,
+        AccuracyLevel::Accurate,
+        STAT_HBLANK | STAT_LYC
+    );
+
+    assert_eq!(2, count_stat_interrupts_on_lyc_line(&mut gb));
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::link_cable::LinkCable;
+use gemi_core::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// The address where the test program stores the byte received.
+const RESULT_ADDRESS: u16 = 0xc100;
+
+/// SC value to start a transfer using the internal clock.
+const SC_INTERNAL_CLOCK: u8 = 0x81;
+
+/// SC value to start a transfer using the external clock.
+const SC_EXTERNAL_CLOCK: u8 = 0x80;
+
+
+/// Creates a new emulator instance, which sends a single byte via the serial port
+/// and stores the byte received on [RESULT_ADDRESS].
+fn create_gameboy(byte: u8, sc: u8) -> GameBoy {
+    let program = [
+        0x3e, byte,                 // LD A, byte
+        0xe0, 0x01,                 // LDH (SB), A
+        0x3e, sc,                   // LD A, sc
+        0xe0, 0x02,                 // LDH (SC), A
+        0xf0, 0x02,                 // LDH A, (SC)
+        0xcb, 0x7f,                 // BIT 7, A
+        0x20, 0xfa,                 // JR NZ, -6
+        0xf0, 0x01,                 // LDH A, (SB)
+        0xea, 0x00, 0xc1,           // LD (RESULT_ADDRESS), A
+        0x18, 0xfe,                 // JR -2
+    ];
+
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + offset as u16, *byte);
+    }
+
+    gb.get_mmu_mut().write_u8(RESULT_ADDRESS, 0x00);
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.cpu.disable_interrupts();
+
+    gb
+}
+
+
+/// Creates two emulator instances connected via link cable.
+fn create_linked_gameboys(byte_a: u8, sc_a: u8, byte_b: u8, sc_b: u8) -> (GameBoy, GameBoy) {
+    let mut gb_a = create_gameboy(byte_a, sc_a);
+    let mut gb_b = create_gameboy(byte_b, sc_b);
+
+    LinkCable::connect(
+        &mut gb_a.get_peripherals_mut().serial,
+        &mut gb_b.get_peripherals_mut().serial,
+    );
+
+    (gb_a, gb_b)
+}
+
+
+/// Runs both emulator instances in lockstep for a number of steps.
+fn run_lockstep(gb_a: &mut GameBoy, gb_b: &mut GameBoy, steps: usize) {
+    for _ in 0..steps {
+        gb_a.run_single_step();
+        gb_b.run_single_step();
+    }
+}
+
+
+/// Checks whether the transfer started by the test program is still pending.
+fn is_transfer_pending(gb: &GameBoy) -> bool {
+    (gb.get_mmu().read_u8(MEMORY_LOCATION_SC) & 0x80) != 0
+}
+
+
+#[test]
+fn test_link_cable_exchange() {
+    let (mut gb_a, mut gb_b) = create_linked_gameboys(0x42, SC_INTERNAL_CLOCK, 0x99, SC_EXTERNAL_CLOCK);
+
+    run_lockstep(&mut gb_a, &mut gb_b, 10_000);
+
+    assert!(!is_transfer_pending(&gb_a));
+    assert!(!is_transfer_pending(&gb_b));
+    assert_eq!(0x99, gb_a.get_mmu().read_u8(RESULT_ADDRESS));
+    assert_eq!(0x42, gb_b.get_mmu().read_u8(RESULT_ADDRESS));
+}
+
+
+#[test]
+fn test_link_cable_exchange_reversed() {
+    let (mut gb_a, mut gb_b) = create_linked_gameboys(0x17, SC_EXTERNAL_CLOCK, 0xc3, SC_INTERNAL_CLOCK);
+
+    run_lockstep(&mut gb_a, &mut gb_b, 10_000);
+
+    assert!(!is_transfer_pending(&gb_a));
+    assert!(!is_transfer_pending(&gb_b));
+    assert_eq!(0xc3, gb_a.get_mmu().read_u8(RESULT_ADDRESS));
+    assert_eq!(0x17, gb_b.get_mmu().read_u8(RESULT_ADDRESS));
+}
+
+
+#[test]
+fn test_external_clock_waits_for_partner() {
+    let (mut gb_a, mut gb_b) = create_linked_gameboys(0x42, SC_EXTERNAL_CLOCK, 0x99, SC_EXTERNAL_CLOCK);
+
+    run_lockstep(&mut gb_a, &mut gb_b, 10_000);
+
+    // with none of both sides providing the clock, both are waiting forever
+    assert!(is_transfer_pending(&gb_a));
+    assert!(is_transfer_pending(&gb_b));
+    assert_eq!(0x42, gb_a.get_mmu().read_u8(MEMORY_LOCATION_SB));
+    assert_eq!(0x99, gb_b.get_mmu().read_u8(MEMORY_LOCATION_SB));
+}
+
+
+#[test]
+fn test_internal_clock_without_partner() {
+    let mut gb = create_gameboy(0x42, SC_INTERNAL_CLOCK);
+
+    for _ in 0..10_000 {
+        gb.run_single_step();
+    }
+
+    // without any device connected, 0xff is received
+    assert!(!is_transfer_pending(&gb));
+    assert_eq!(0xff, gb.get_mmu().read_u8(RESULT_ADDRESS));
+}
+
+
+#[test]
+fn test_external_clock_without_partner() {
+    let mut gb = create_gameboy(0x42, SC_EXTERNAL_CLOCK);
+
+    for _ in 0..10_000 {
+        gb.run_single_step();
+    }
+
+    assert!(is_transfer_pending(&gb));
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::GameBoy;
+
+
+/// The offset within each ROM bank, where the bank number is stored.
+const BANK_NUMBER_OFFSET: usize = 0x1000;
+
+/// The size of a single ROM bank.
+const ROM_BANK_SIZE: usize = 0x4000;
+
+
+/// Creates a ROM image with a MBC1+RAM header, where each bank contains its own bank number.
+fn create_rom(rom_size_type: u8, ram_size_type: u8) -> Vec<u8> {
+    let rom_size = ROM_BANK_SIZE * (2 << rom_size_type);
+    let mut rom  = vec![0x00; rom_size];
+
+    rom[ROM_OFFSET_ROM_TYPE] = 0x02;
+    rom[ROM_OFFSET_ROM_SIZE] = rom_size_type;
+    rom[ROM_OFFSET_RAM_SIZE] = ram_size_type;
+
+    for bank in 0 .. (rom_size / ROM_BANK_SIZE) {
+        rom[bank * ROM_BANK_SIZE + BANK_NUMBER_OFFSET] = bank as u8;
+    }
+
+    rom
+}
+
+
+/// Creates a new emulator instance running a given ROM image.
+fn create_gameboy(rom: Vec<u8>) -> GameBoy {
+    let cartridge = Cartridge::load_from_bytes(rom, None).unwrap();
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(cartridge);
+    builder.finish().unwrap()
+}
+
+
+/// Reads the bank number stored in the ROM bank mapped into the given slot.
+fn read_bank_number(gb: &GameBoy, slot: u16) -> u8 {
+    gb.get_mmu().read_u8(slot * (ROM_BANK_SIZE as u16) + (BANK_NUMBER_OFFSET as u16))
+}
+
+
+/// Writes a marker into each RAM bank, selecting the banks via the 2nd bank selection register.
+fn write_ram_markers(gb: &mut GameBoy, banks: u8) {
+    for bank in 0 .. banks {
+        gb.get_mmu_mut().write_u8(0x4000, bank);
+        gb.get_mmu_mut().write_u8(0xa000, 0x10 + bank);
+    }
+}
+
+
+#[test]
+fn test_mode_0_rom_bank_selection() {
+    let mut gb = create_gameboy(create_rom(0x06, 0x00));
+
+    // upper two bits are used as bit 5 and 6 of the switchable ROM bank
+    gb.get_mmu_mut().write_u8(0x2000, 0x05);
+    gb.get_mmu_mut().write_u8(0x4000, 0x01);
+
+    assert_eq!(0x00, read_bank_number(&gb, 0));
+    assert_eq!(0x25, read_bank_number(&gb, 1));
+
+    // selecting bank 0 on the lower bits selects bank 1 instead
+    gb.get_mmu_mut().write_u8(0x2000, 0x00);
+    gb.get_mmu_mut().write_u8(0x4000, 0x03);
+
+    assert_eq!(0x00, read_bank_number(&gb, 0));
+    assert_eq!(0x61, read_bank_number(&gb, 1));
+}
+
+
+#[test]
+fn test_mode_1_remaps_rom_bank_0() {
+    let mut gb = create_gameboy(create_rom(0x06, 0x00));
+
+    gb.get_mmu_mut().write_u8(0x6000, 0x01);
+    gb.get_mmu_mut().write_u8(0x2000, 0x02);
+
+    for (upper_bits, bank_0) in [(0x00, 0x00), (0x01, 0x20), (0x02, 0x40), (0x03, 0x60)] {
+        gb.get_mmu_mut().write_u8(0x4000, upper_bits);
+
+        assert_eq!(bank_0,        read_bank_number(&gb, 0));
+        assert_eq!(bank_0 | 0x02, read_bank_number(&gb, 1));
+    }
+
+    // switching back to mode 0 maps bank 0 again
+    gb.get_mmu_mut().write_u8(0x6000, 0x00);
+
+    assert_eq!(0x00, read_bank_number(&gb, 0));
+    assert_eq!(0x62, read_bank_number(&gb, 1));
+}
+
+
+#[test]
+fn test_mode_1_rom_bank_0_wraps_on_small_roms() {
+    let mut gb = create_gameboy(create_rom(0x04, 0x00));
+
+    // a 512kiB ROM has only 32 banks, so the upper bits have no effect
+    gb.get_mmu_mut().write_u8(0x6000, 0x01);
+    gb.get_mmu_mut().write_u8(0x4000, 0x01);
+    gb.get_mmu_mut().write_u8(0x2000, 0x03);
+
+    assert_eq!(0x00, read_bank_number(&gb, 0));
+    assert_eq!(0x03, read_bank_number(&gb, 1));
+}
+
+
+#[test]
+fn test_ram_banks_only_selectable_in_mode_1() {
+    let mut gb = create_gameboy(create_rom(0x04, 0x03));
+
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+
+    // in mode 0 all writes go into RAM bank 0, other banks keep their initial value
+    write_ram_markers(&mut gb, 4);
+
+    gb.get_mmu_mut().write_u8(0x6000, 0x01);
+
+    for bank in 0 .. 4 {
+        gb.get_mmu_mut().write_u8(0x4000, bank);
+        assert_eq!(if bank == 0 { 0x13 } else { 0xff }, gb.get_mmu().read_u8(0xa000));
+    }
+
+    // in mode 1 each bank can be selected separately
+    write_ram_markers(&mut gb, 4);
+
+    for bank in 0 .. 4 {
+        gb.get_mmu_mut().write_u8(0x4000, bank);
+        assert_eq!(0x10 + bank, gb.get_mmu().read_u8(0xa000));
+    }
+
+    // switching back to mode 0 maps RAM bank 0, regardless of the upper bits
+    gb.get_mmu_mut().write_u8(0x6000, 0x00);
+    assert_eq!(0x10, gb.get_mmu().read_u8(0xa000));
+}
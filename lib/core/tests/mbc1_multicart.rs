@@ -0,0 +1,118 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::mbc::MemoryBankController;
+
+
+/// The offset within each ROM bank, where the bank number is stored.
+const BANK_NUMBER_OFFSET: usize = 0x1000;
+
+/// The size of a single ROM bank.
+const ROM_BANK_SIZE: usize = 0x4000;
+
+
+/// Creates a ROM image with a MBC1 header, where each bank contains its own bank number.
+/// If `multicart_logo` is set, the nintendo logo will be placed in the header of the 2nd game.
+fn create_rom(rom_size_type: u8, multicart_logo: bool) -> Vec<u8> {
+    let rom_size = ROM_BANK_SIZE * (2 << rom_size_type);
+    let mut rom  = vec![0x00; rom_size];
+
+    rom[ROM_OFFSET_LOGO_BITMAP .. ROM_OFFSET_LOGO_BITMAP + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+    rom[ROM_OFFSET_ROM_TYPE] = 0x01;
+    rom[ROM_OFFSET_ROM_SIZE] = rom_size_type;
+
+    if multicart_logo {
+        let offset = MBC1M_GAME_SIZE + ROM_OFFSET_LOGO_BITMAP;
+        rom[offset .. offset + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+    }
+
+    for bank in 0 .. (rom_size / ROM_BANK_SIZE) {
+        rom[bank * ROM_BANK_SIZE + BANK_NUMBER_OFFSET] = bank as u8;
+    }
+
+    rom
+}
+
+
+/// Creates a new emulator instance running a given ROM image.
+fn create_gameboy(rom: Vec<u8>) -> GameBoy {
+    let cartridge = Cartridge::load_from_bytes(rom, None).unwrap();
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(cartridge);
+    builder.finish().unwrap()
+}
+
+
+/// Reads the bank number stored in the ROM bank mapped into the given slot.
+fn read_bank_number(gb: &GameBoy, slot: u16) -> u8 {
+    gb.get_mmu().read_u8(slot * (ROM_BANK_SIZE as u16) + (BANK_NUMBER_OFFSET as u16))
+}
+
+
+#[test]
+fn test_detect_multicart() {
+    let cartridge = Cartridge::load_from_bytes(create_rom(0x05, true), None).unwrap();
+    assert!(matches!(cartridge.get_mbc(), MemoryBankController::MBC1M));
+}
+
+
+#[test]
+fn test_detect_no_multicart_without_logo() {
+    let cartridge = Cartridge::load_from_bytes(create_rom(0x05, false), None).unwrap();
+    assert!(matches!(cartridge.get_mbc(), MemoryBankController::MBC1));
+}
+
+
+#[test]
+fn test_detect_no_multicart_with_other_rom_size() {
+    let cartridge = Cartridge::load_from_bytes(create_rom(0x06, true), None).unwrap();
+    assert!(matches!(cartridge.get_mbc(), MemoryBankController::MBC1));
+}
+
+
+#[test]
+fn test_multicart_bank_selection_mode_0() {
+    let mut gb = create_gameboy(create_rom(0x05, true));
+
+    // upper two bits select the game, lower four bits the bank within the game
+    gb.get_mmu_mut().write_u8(0x2000, 0x03);
+    gb.get_mmu_mut().write_u8(0x4000, 0x02);
+
+    assert_eq!(0x00, read_bank_number(&gb, 0));
+    assert_eq!(0x23, read_bank_number(&gb, 1));
+
+    // bit 4 of the first register is ignored
+    gb.get_mmu_mut().write_u8(0x2000, 0x15);
+    assert_eq!(0x25, read_bank_number(&gb, 1));
+}
+
+
+#[test]
+fn test_multicart_bank_selection_mode_1() {
+    let mut gb = create_gameboy(create_rom(0x05, true));
+
+    // in mode 1 the first slot maps the first bank of the selected game
+    gb.get_mmu_mut().write_u8(0x6000, 0x01);
+    gb.get_mmu_mut().write_u8(0x4000, 0x03);
+    gb.get_mmu_mut().write_u8(0x2000, 0x01);
+
+    assert_eq!(0x30, read_bank_number(&gb, 0));
+    assert_eq!(0x31, read_bank_number(&gb, 1));
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cartridge::*;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::mbc::MemoryBankController;
+
+
+/// Cartridge type MBC2+BATTERY.
+const ROM_TYPE_MBC2: u8 = 0x06;
+
+/// ROM size type for 256kiB, which is the maximum supported by MBC2.
+const ROM_SIZE_256K: u8 = 0x03;
+
+/// The size of a single ROM bank.
+const ROM_BANK_SIZE: usize = 0x4000;
+
+/// The offset within each ROM bank, where the bank number is stored.
+const BANK_NUMBER_OFFSET: usize = 0x1000;
+
+
+/// Creates a new emulator instance with a MBC2 cartridge, where each ROM bank contains its own bank number.
+fn create_gameboy() -> GameBoy {
+    let rom_size = ROM_BANK_SIZE * (2 << ROM_SIZE_256K);
+    let mut rom  = vec![0x00; rom_size];
+    rom[ROM_OFFSET_ROM_TYPE] = ROM_TYPE_MBC2;
+    rom[ROM_OFFSET_ROM_SIZE] = ROM_SIZE_256K;
+
+    for bank in 0 .. (rom_size / ROM_BANK_SIZE) {
+        rom[bank * ROM_BANK_SIZE + BANK_NUMBER_OFFSET] = bank as u8;
+    }
+
+    let cartridge = Cartridge::load_from_bytes(rom, None).unwrap();
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(cartridge);
+    builder.finish().unwrap()
+}
+
+
+/// Reads the bank number stored in the switchable ROM bank.
+fn read_bank_number(gb: &GameBoy) -> u8 {
+    gb.get_mmu().read_u8((ROM_BANK_SIZE + BANK_NUMBER_OFFSET) as u16)
+}
+
+
+#[test]
+fn test_detect_mbc2() {
+    let gb = create_gameboy();
+    let cartridge = gb.get_peripherals().mem.get_cartridge().unwrap();
+
+    assert!(matches!(cartridge.get_mbc(), MemoryBankController::MBC2));
+}
+
+
+#[test]
+fn test_ram_stores_lower_nibble_only() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+
+    // the upper nibble is not stored and always reads as 1
+    gb.get_mmu_mut().write_u8(0xa000, 0xff);
+    assert_eq!(0x0f, gb.get_mmu().read_u8(0xa000) & 0x0f);
+    assert_eq!(0xff, gb.get_mmu().read_u8(0xa000));
+
+    gb.get_mmu_mut().write_u8(0xa001, 0x3c);
+    assert_eq!(0xfc, gb.get_mmu().read_u8(0xa001));
+}
+
+
+#[test]
+fn test_ram_mirrored() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+
+    // only the lower 9 bits of the address are used
+    gb.get_mmu_mut().write_u8(0xa005, 0x07);
+
+    for mirror in 0..16 {
+        assert_eq!(0xf7, gb.get_mmu().read_u8(0xa005 + mirror * 0x200));
+    }
+
+    gb.get_mmu_mut().write_u8(0xbfff, 0x03);
+    assert_eq!(0xf3, gb.get_mmu().read_u8(0xa1ff));
+}
+
+
+#[test]
+fn test_address_bit_8_selects_register() {
+    let mut gb = create_gameboy();
+
+    // with bit 8 set, the value selects the ROM bank and does not enable RAM
+    gb.get_mmu_mut().write_u8(0x0100, 0x0a);
+    assert_eq!(0x0a, read_bank_number(&gb));
+    assert_eq!(0xff, gb.get_mmu().read_u8(0xa000));
+
+    // with bit 8 cleared, the value enables RAM and does not change the ROM bank
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+    gb.get_mmu_mut().write_u8(0xa000, 0x05);
+    assert_eq!(0xf5, gb.get_mmu().read_u8(0xa000));
+    assert_eq!(0x0a, read_bank_number(&gb));
+
+    // the registers are mirrored over the whole 0x0000-0x3fff area
+    gb.get_mmu_mut().write_u8(0x3f00, 0x03);
+    assert_eq!(0x03, read_bank_number(&gb));
+
+    gb.get_mmu_mut().write_u8(0x3e00, 0x00);
+    assert_eq!(0xff, gb.get_mmu().read_u8(0xa000));
+}
+
+
+#[test]
+fn test_rom_bank_zero_selects_bank_one() {
+    let mut gb = create_gameboy();
+
+    gb.get_mmu_mut().write_u8(0x2100, 0x00);
+    assert_eq!(0x01, read_bank_number(&gb));
+
+    // only 4 bits are used for the bank number
+    gb.get_mmu_mut().write_u8(0x2100, 0x1f);
+    assert_eq!(0x0f, read_bank_number(&gb));
+}
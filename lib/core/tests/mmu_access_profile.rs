@@ -0,0 +1,143 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The page containing the program.
+const PROGRAM_PAGE: usize = 0xc0;
+
+/// The page containing the address accessed by the program.
+const DATA_PAGE: usize = 0xd0;
+
+
+/// Creates a new emulator instance running a loop in WRAM,
+/// which reads and writes the same address on each iteration.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let program = [
+        0x21, 0x42, 0xd0,   // LD HL, 0xd042
+        0x7e,               // LD A, (HL)
+        0x3c,               // INC A
+        0x77,               // LD (HL), A
+        0x18, 0xfb,         // JR -5
+    ];
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(0xc000 + offset as u16, *byte);
+    }
+
+    gb.cpu.set_instruction_pointer(0xc000);
+    gb.cpu.disable_interrupts();
+
+    gb
+}
+
+
+/// Runs the given number of instructions.
+fn run_steps(gb: &mut GameBoy, steps: usize) {
+    for _ in 0..steps {
+        gb.run_single_step();
+    }
+}
+
+
+#[test]
+fn test_profiling_disabled_by_default() {
+    let mut gb = create_gameboy();
+    run_steps(&mut gb, 100);
+
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert!(profile.iter().all(|count| *count == 0));
+}
+
+
+#[test]
+fn test_hammered_page_dominates() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().enable_access_profiling(true);
+
+    // the first instruction loads HL, each following four instructions are a single iteration
+    let iterations = 1000;
+    run_steps(&mut gb, 1 + iterations * 4);
+
+    let profile = gb.get_mmu_mut().take_access_profile();
+
+    // each iteration reads and writes the data address once
+    assert_eq!((iterations * 2) as u32, profile[DATA_PAGE]);
+
+    // apart from fetching the program itself, the data page dominates all other pages
+    for (page, count) in profile.iter().enumerate() {
+        if page != DATA_PAGE && page != PROGRAM_PAGE {
+            assert!(
+                *count < profile[DATA_PAGE],
+                "Page {page:02x} was accessed {count} times"
+            );
+        }
+    }
+}
+
+
+#[test]
+fn test_take_resets_profile() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().enable_access_profiling(true);
+    run_steps(&mut gb, 1 + 4);
+
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert_eq!(2, profile[DATA_PAGE]);
+
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert!(profile.iter().all(|count| *count == 0));
+
+    // counting continues after taking the profile
+    run_steps(&mut gb, 4);
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert_eq!(2, profile[DATA_PAGE]);
+}
+
+
+#[test]
+fn test_disable_profiling() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().enable_access_profiling(true);
+    run_steps(&mut gb, 1 + 4);
+
+    gb.get_mmu_mut().enable_access_profiling(false);
+    run_steps(&mut gb, 40);
+
+    // only accesses while profiling was enabled are counted
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert_eq!(2, profile[DATA_PAGE]);
+}
+
+
+#[test]
+fn test_debugger_accesses_not_counted() {
+    let mut gb = create_gameboy();
+    gb.get_mmu_mut().enable_access_profiling(true);
+
+    // only accesses performed by the CPU are counted
+    gb.get_mmu().read_u8(0xd042);
+    gb.get_mmu_mut().write_u8(0xd042, 0x00);
+
+    let profile = gb.get_mmu_mut().take_access_profile();
+    assert_eq!(0, profile[DATA_PAGE]);
+}
@@ -0,0 +1,100 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::MEMORY_LOCATION_SVBK;
+
+mod common;
+use common::{create_gameboy_color, create_gameboy_for};
+
+
+/// Selects a WRAM bank via the SVBK register.
+fn select_bank(gb: &mut GameBoy, bank: u8) {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SVBK, bank);
+}
+
+
+#[test]
+fn test_svbk_register() {
+    let mut gb = create_gameboy_color();
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_SVBK), 0xf8);
+
+    select_bank(&mut gb, 5);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_SVBK), 0xfd);
+
+    // only the lower 3 bits select the bank
+    select_bank(&mut gb, 0xfb);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_SVBK), 0xfb);
+
+    // bank 0 selects bank 1, but reads back as 0
+    select_bank(&mut gb, 0);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_SVBK), 0xf8);
+}
+
+
+#[test]
+fn test_switch_between_banks() {
+    let mut gb = create_gameboy_color();
+
+    // write a distinct value into each bank
+    for bank in 1..8 {
+        select_bank(&mut gb, bank);
+        gb.get_mmu_mut().write_u8(0xd000, 0x10 + bank);
+    }
+
+    for bank in 1..8 {
+        select_bank(&mut gb, bank);
+        assert_eq!(gb.get_mmu().read_u8(0xd000), 0x10 + bank);
+
+        // echo RAM mirrors the selected bank
+        assert_eq!(gb.get_mmu().read_u8(0xf000), 0x10 + bank);
+    }
+
+    // bank 0 maps to bank 1
+    select_bank(&mut gb, 0);
+    assert_eq!(gb.get_mmu().read_u8(0xd000), 0x11);
+
+    select_bank(&mut gb, 3);
+    gb.get_mmu_mut().write_u8(0xd000, 0x33);
+    select_bank(&mut gb, 1);
+    assert_eq!(gb.get_mmu().read_u8(0xd000), 0x11);
+}
+
+
+#[test]
+fn test_fixed_bank_is_not_switched() {
+    let mut gb = create_gameboy_color();
+
+    select_bank(&mut gb, 1);
+    gb.get_mmu_mut().write_u8(0xc000, 0x42);
+
+    select_bank(&mut gb, 7);
+    assert_eq!(gb.get_mmu().read_u8(0xc000), 0x42);
+}
+
+
+#[test]
+fn test_no_wram_banks_on_dmg() {
+    let mut gb = create_gameboy_for(DeviceType::GameBoyDmg, EmulationType::DMG);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_SVBK), 0xff);
+
+    gb.get_mmu_mut().write_u8(0xd000, 0x11);
+
+    // selecting another bank has no effect
+    select_bank(&mut gb, 2);
+    assert_eq!(gb.get_mmu().read_u8(0xd000), 0x11);
+}
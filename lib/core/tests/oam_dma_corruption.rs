@@ -0,0 +1,162 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::{AccuracyLevel, Clock, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+use gemi_core::mmu::memory_data::MemoryData;
+
+mod common;
+use common::create_gameboy_with_accuracy;
+
+
+/// The page in WRAM used as the source of the DMA transfer.
+const DMA_SOURCE_PAGE: u8 = 0xc1;
+
+/// The number of bytes in OAM.
+const OAM_SIZE: u16 = 0xa0;
+
+
+/// The value initially stored in OAM on a given index.
+fn oam_value(index: u16) -> u8 {
+    (index as u8).wrapping_mul(37) ^ 0x5a
+}
+
+
+/// The value of a byte in OAM after being corrupted.
+fn corrupted_oam_value(index: u16) -> u8 {
+    if index.is_multiple_of(4) {
+        (oam_value(index) | oam_value(index + 1)) & oam_value(index + 2)
+    }
+    else {
+        oam_value(index)
+    }
+}
+
+
+/// Creates a new emulator instance with the LCD disabled and OAM filled with a known pattern.
+fn create_gameboy(device: DeviceType, emulation: EmulationType, accuracy: AccuracyLevel) -> GameBoy {
+    let mut gb = create_gameboy_with_accuracy(device, emulation, accuracy);
+
+    gb.initialize();
+
+    let mmu = gb.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // complete the transfer started by setting the initial value of the DMA register
+    mmu.update(OAM_SIZE as Clock);
+    assert!(!mmu.is_dma_transfer_active());
+
+    for index in 0..OAM_SIZE {
+        mmu.write_u8(MEMORY_LOCATION_OAM_BEGIN + index, oam_value(index));
+        mmu.write_u8(((DMA_SOURCE_PAGE as u16) << 8) + index, 0x00);
+    }
+
+    gb
+}
+
+
+/// Starts a DMA transfer without processing any of its cycles.
+fn start_dma(gb: &mut GameBoy) {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, DMA_SOURCE_PAGE);
+    assert!(gb.get_mmu().is_dma_transfer_active());
+}
+
+
+/// Reads the current content of OAM directly from the PPU.
+fn read_oam(gb: &GameBoy) -> Vec<u8> {
+    (0..OAM_SIZE)
+        .map(|index| gb.get_peripherals().ppu.get_oam_bank().get_at(index as usize))
+        .collect()
+}
+
+
+#[test]
+fn test_oam_read_during_dma_corrupts_oam() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG, AccuracyLevel::Strict);
+    start_dma(&mut gb);
+
+    // the value read is already affected by the corruption
+    assert_eq!(corrupted_oam_value(0), gb.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN));
+
+    // the corruption is applied on OAM immediately
+    let expected: Vec<u8> = (0..OAM_SIZE).map(corrupted_oam_value).collect();
+    assert_eq!(expected, read_oam(&gb));
+
+    assert_eq!(expected[5], gb.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN + 5));
+    assert_eq!(0xff,        gb.get_mmu_mut().cpu_read_u8(0xfeb0));
+}
+
+
+#[test]
+fn test_debugger_read_during_dma_does_not_corrupt() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG, AccuracyLevel::Strict);
+    start_dma(&mut gb);
+
+    // reads not performed by the CPU do not have any side effects
+    for index in 0..OAM_SIZE {
+        gb.get_mmu().read_u8(MEMORY_LOCATION_OAM_BEGIN + index);
+    }
+
+    gb.get_mmu_mut().update(0);
+
+    let expected: Vec<u8> = (0..OAM_SIZE).map(oam_value).collect();
+    assert_eq!(expected, read_oam(&gb));
+}
+
+
+#[test]
+fn test_dma_overwrites_corrupted_oam() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG, AccuracyLevel::Strict);
+    start_dma(&mut gb);
+
+    gb.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN);
+    gb.get_mmu_mut().update(OAM_SIZE as Clock);
+
+    assert!(!gb.get_mmu().is_dma_transfer_active());
+    assert_eq!(vec![0x00; OAM_SIZE as usize], read_oam(&gb));
+}
+
+
+#[test]
+fn test_oam_read_without_dma_does_not_corrupt() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG, AccuracyLevel::Strict);
+
+    assert_eq!(oam_value(0), gb.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN));
+    gb.get_mmu_mut().update(0);
+
+    let expected: Vec<u8> = (0..OAM_SIZE).map(oam_value).collect();
+    assert_eq!(expected, read_oam(&gb));
+}
+
+
+#[test]
+fn test_no_corruption_on_gbc_or_below_strict_accuracy() {
+    let setups = [
+        (DeviceType::GameBoyColor, EmulationType::GBC, AccuracyLevel::Strict),
+        (DeviceType::GameBoyDmg,   EmulationType::DMG, AccuracyLevel::Accurate),
+    ];
+
+    for (device, emulation, accuracy) in setups {
+        let mut gb = create_gameboy(device, emulation, accuracy);
+        start_dma(&mut gb);
+
+        gb.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN);
+        gb.get_mmu_mut().update(0);
+
+        let expected: Vec<u8> = (0..OAM_SIZE).map(oam_value).collect();
+        assert_eq!(expected, read_oam(&gb));
+    }
+}
@@ -0,0 +1,118 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::color_correction::ColorCorrection;
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::create_gameboy_color;
+
+
+/// Pure red as RGB555 value.
+const COLOR_RED: u16 = 0x001f;
+
+/// Pure green as RGB555 value.
+const COLOR_GREEN: u16 = 0x03e0;
+
+/// Pure blue as RGB555 value.
+const COLOR_BLUE: u16 = 0x7c00;
+
+/// 50% gray as RGB555 value.
+const COLOR_GRAY: u16 = 0x4210;
+
+/// White as RGB555 value.
+const COLOR_WHITE: u16 = 0x7fff;
+
+
+/// Translates a RGB555 color with the given color correction and returns its RGB channels.
+fn rgb_of(color_correction: ColorCorrection, color: u16) -> (u8, u8, u8) {
+    let color = color_correction.to_color(color);
+    assert_eq!(0xff, color.a);
+
+    (color.r, color.g, color.b)
+}
+
+
+#[test]
+fn test_no_correction() {
+    assert_eq!((0x00, 0x00, 0x00), rgb_of(ColorCorrection::Off, 0x0000));
+    assert_eq!((0xff, 0xff, 0xff), rgb_of(ColorCorrection::Off, COLOR_WHITE));
+    assert_eq!((0xff, 0x00, 0x00), rgb_of(ColorCorrection::Off, COLOR_RED));
+    assert_eq!((0x84, 0x84, 0x84), rgb_of(ColorCorrection::Off, COLOR_GRAY));
+}
+
+
+#[test]
+fn test_cgb_lcd_correction() {
+    assert_eq!((  0,   0,   0), rgb_of(ColorCorrection::CgbLcd, 0x0000));
+    assert_eq!((255, 255, 255), rgb_of(ColorCorrection::CgbLcd, COLOR_WHITE));
+    assert_eq!((232,   0, 119), rgb_of(ColorCorrection::CgbLcd, COLOR_RED));
+    assert_eq!(( 99, 224,  99), rgb_of(ColorCorrection::CgbLcd, COLOR_GREEN));
+    assert_eq!(( 72, 136, 215), rgb_of(ColorCorrection::CgbLcd, COLOR_BLUE));
+    assert_eq!((124, 124, 124), rgb_of(ColorCorrection::CgbLcd, COLOR_GRAY));
+}
+
+
+#[test]
+fn test_gba_lcd_correction() {
+    assert_eq!((  0,   0,   0), rgb_of(ColorCorrection::GbaLcd, 0x0000));
+    assert_eq!((255, 251, 255), rgb_of(ColorCorrection::GbaLcd, COLOR_WHITE));
+    assert_eq!((244,  56, 117), rgb_of(ColorCorrection::GbaLcd, COLOR_RED));
+    assert_eq!((117, 233,  56), rgb_of(ColorCorrection::GbaLcd, COLOR_GREEN));
+    assert_eq!((  0,  92, 229), rgb_of(ColorCorrection::GbaLcd, COLOR_BLUE));
+    assert_eq!(( 80,  75,  77), rgb_of(ColorCorrection::GbaLcd, COLOR_GRAY));
+}
+
+
+#[test]
+fn test_cycle_through_modes() {
+    let mut mode = ColorCorrection::default();
+    assert_eq!(ColorCorrection::Off, mode);
+
+    for expected in [ColorCorrection::CgbLcd, ColorCorrection::GbaLcd, ColorCorrection::Off] {
+        mode = mode.next();
+        assert_eq!(expected, mode);
+    }
+
+    for mode in ColorCorrection::ALL {
+        assert_eq!(Some(mode), ColorCorrection::from_name(mode.get_name()));
+    }
+}
+
+
+#[test]
+fn test_correction_applied_to_lcd() {
+    let mut gb = create_gameboy_color();
+
+    gb.get_peripherals_mut().ppu.set_color_correction(ColorCorrection::CgbLcd);
+    assert_eq!(ColorCorrection::CgbLcd, gb.get_peripherals().ppu.get_color_correction());
+
+    // all tiles are empty, so the background is drawn with color #0 of palette #0
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPS, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPD, (COLOR_RED & 0xff) as u8);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPD, (COLOR_RED >> 8) as u8);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+
+    // the first frame after enabling the LCD is not drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    let pixel = gb.get_peripherals().ppu.get_lcd().get_pixel(0, 0);
+    assert_eq!(rgb_of(ColorCorrection::CgbLcd, COLOR_RED), (pixel.r, pixel.g, pixel.b));
+}
@@ -0,0 +1,125 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_PER_FRAME, CPU_CYCLES_PER_LINE};
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// Creates a new emulator instance running an endless loop.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+/// Checks the dot counter to match the current LY value.
+fn check_dot_matches_ly(gb: &GameBoy) {
+    let dot = gb.get_peripherals().ppu.frame_dot();
+    let ly  = gb.get_mmu().read_u8(MEMORY_LOCATION_LY);
+
+    assert!(dot < CPU_CYCLES_PER_FRAME);
+    assert_eq!(dot / CPU_CYCLES_PER_LINE, ly as u64);
+}
+
+
+#[test]
+fn test_frame_dot_per_cycle() {
+    let mut gb = create_gameboy();
+    gb.run_frame();
+
+    let first_dot = gb.get_peripherals().ppu.frame_dot();
+
+    // running the PPU cycle by cycle increments the dot counter by one each cycle
+    for cycle in 1 ..= 2 * CPU_CYCLES_PER_FRAME {
+        gb.get_peripherals_mut().ppu.update(1);
+
+        let expected = (first_dot + cycle) % CPU_CYCLES_PER_FRAME;
+        assert_eq!(expected, gb.get_peripherals().ppu.frame_dot());
+        check_dot_matches_ly(&gb);
+    }
+}
+
+
+#[test]
+fn test_frame_dot_follows_cpu_cycles() {
+    let mut gb = create_gameboy();
+    gb.run_frame();
+
+    let first_dot    = gb.get_peripherals().ppu.frame_dot();
+    let first_cycles = gb.get_total_cycles_processed();
+
+    // run partial frames instruction by instruction
+    for _ in 0..50_000 {
+        gb.run_single_step();
+
+        let cycles   = gb.get_total_cycles_processed() - first_cycles;
+        let expected = (first_dot + cycles) % CPU_CYCLES_PER_FRAME;
+        assert_eq!(expected, gb.get_peripherals().ppu.frame_dot());
+        check_dot_matches_ly(&gb);
+    }
+}
+
+
+#[test]
+fn test_frame_dot_with_lcd_disabled() {
+    let mut gb = create_gameboy();
+    gb.run_frame();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+    assert_eq!(0, gb.get_peripherals().ppu.frame_dot());
+
+    gb.get_peripherals_mut().ppu.update(1000);
+    assert_eq!(0, gb.get_peripherals().ppu.frame_dot());
+
+    // after enabling the LCD, the dot counter starts over, with the first line being shortened
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+    gb.get_peripherals_mut().ppu.update(100);
+    assert_eq!(104, gb.get_peripherals().ppu.frame_dot());
+}
+
+
+#[test]
+fn test_frame_dot_on_first_line_after_lcd_enabled() {
+    let mut gb = create_gameboy();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+
+    // the first line after enabling the LCD is 4 dots shorter, so it starts at dot 4
+    assert_eq!(4, gb.get_peripherals().ppu.frame_dot());
+
+    // the dot counter keeps increasing without gaps across the whole first line,
+    // including the transition from mode 0 into mode 3 and into the next line
+    for cycle in 1 ..= CPU_CYCLES_PER_LINE - 4 {
+        gb.get_peripherals_mut().ppu.update(1);
+
+        let expected = 4 + cycle;
+        assert_eq!(expected, gb.get_peripherals().ppu.frame_dot(), "unexpected dot after {cycle} cycles");
+        check_dot_matches_ly(&gb);
+    }
+
+    // the first line took 452 cycles, now the PPU is on the second line
+    assert_eq!(CPU_CYCLES_PER_LINE, gb.get_peripherals().ppu.frame_dot());
+    assert_eq!(1, gb.get_mmu().read_u8(MEMORY_LOCATION_LY));
+}
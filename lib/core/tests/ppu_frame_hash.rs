@@ -0,0 +1,80 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::ppu::graphic_data::Color;
+use gemi_core::ppu::ppu::LcdBuffer;
+use gemi_core::utils::fnv1a_hash_64;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The hash of a frame with all pixels being white.
+const WHITE_FRAME_HASH: u64 = 0xc850_103d_09c4_eb25;
+
+
+/// Creates a new emulator instance, which runs a number of frames.
+fn run_gameboy(frames: usize) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    for _ in 0..frames {
+        gb.run_frame();
+    }
+
+    gb
+}
+
+
+#[test]
+fn test_fnv1a_reference_values() {
+    assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a_hash_64(b""));
+    assert_eq!(0xe71f_a219_0541_574b, fnv1a_hash_64(b"abc"));
+}
+
+
+#[test]
+fn test_white_frame_hash() {
+    let lcd = LcdBuffer::allow_with_color(Color::white());
+
+    assert_eq!(WHITE_FRAME_HASH, lcd.frame_hash());
+}
+
+
+#[test]
+fn test_identical_frames_hash_equal() {
+    let lcd_a = LcdBuffer::allow_with_color(Color::white());
+    let lcd_b = LcdBuffer::allow_with_color(Color::white());
+    assert_eq!(lcd_a.frame_hash(), lcd_b.frame_hash());
+
+    let gb_a = run_gameboy(3);
+    let gb_b = run_gameboy(3);
+    assert_eq!(
+        gb_a.get_peripherals().ppu.get_lcd().frame_hash(),
+        gb_b.get_peripherals().ppu.get_lcd().frame_hash()
+    );
+}
+
+
+#[test]
+fn test_single_pixel_change_hashes_different() {
+    let lcd_a     = LcdBuffer::allow_with_color(Color::white());
+    let mut lcd_b = LcdBuffer::allow_with_color(Color::white());
+    lcd_b.set_pixel_rgba(80, 72, &Color::from_rgba32(0xfffffeff));
+
+    assert_ne!(lcd_a.frame_hash(), lcd_b.frame_hash());
+}
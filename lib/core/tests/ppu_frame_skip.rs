@@ -0,0 +1,124 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::debug::DebugEvent;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// LCDC value with LCD, background (0x9800 tilemap) and 0x8000 tile data enabled.
+const LCDC_BACKGROUND_ENABLED: u8 = 0b_1001_0001;
+
+/// Background palette mapping the color of the tile to black.
+const PALETTE_BLACK: u8 = 0b_1111_1111;
+
+/// Background palette mapping the color of the tile to white.
+const PALETTE_WHITE: u8 = 0b_0000_0000;
+
+
+/// Creates a new emulator instance running an endless loop stored in WRAM,
+/// while displaying a background filled with a single tile in the given palette.
+fn create_gameboy(palette: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.initialize();
+
+    let mmu = gb.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // tile #0 is filled with color #3, which is displayed on each cell of the tilemap
+    for offset in 0..16u16 {
+        mmu.write_u8(0x8000 + offset, 0xff);
+    }
+
+    for offset in 0..(32 * 32u16) {
+        mmu.write_u8(0x9800 + offset, 0x00);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, palette);
+    mmu.write_u8(MEMORY_LOCATION_SCX, 0);
+    mmu.write_u8(MEMORY_LOCATION_SCY, 0);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_BACKGROUND_ENABLED);
+
+    // JR -2
+    mmu.write_u8(PROGRAM_ADDRESS,     0x18);
+    mmu.write_u8(PROGRAM_ADDRESS + 1, 0xfe);
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.cpu.disable_interrupts();
+
+    gb
+}
+
+
+/// Runs a number of frames and returns the hash of the last frame displayed.
+fn run_frames(gb: &mut GameBoy, frames: usize) -> u64 {
+    for _ in 0..frames {
+        gb.run_frame();
+    }
+
+    gb.get_peripherals().ppu.get_lcd().frame_hash()
+}
+
+
+#[test]
+fn test_timing_identical_with_rendering_disabled() {
+    let mut gb_rendered = create_gameboy(PALETTE_BLACK);
+    let mut gb_skipped  = create_gameboy(PALETTE_BLACK);
+
+    gb_skipped.get_peripherals_mut().ppu.set_render_enabled(false);
+    assert!(!gb_skipped.get_peripherals().ppu.is_render_enabled());
+
+    for frame in 0..10 {
+        let results_rendered = gb_rendered.run_frame();
+        let results_skipped  = gb_skipped.run_frame();
+
+        assert_eq!(results_rendered.cycles, results_skipped.cycles, "cycles differ in frame {frame}");
+        assert_eq!(results_rendered.events, results_skipped.events, "events differ in frame {frame}");
+        assert!(results_skipped.events.contains(DebugEvent::PpuFrameCompleted));
+
+        assert_eq!(gb_rendered.get_total_cycles_processed(), gb_skipped.get_total_cycles_processed());
+        assert_eq!(gb_rendered.get_mmu().read_u8(MEMORY_LOCATION_LY),   gb_skipped.get_mmu().read_u8(MEMORY_LOCATION_LY));
+        assert_eq!(gb_rendered.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS), gb_skipped.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS));
+    }
+}
+
+
+#[test]
+fn test_lcd_unchanged_while_rendering_disabled() {
+    let mut gb = create_gameboy(PALETTE_BLACK);
+    let black_frame = run_frames(&mut gb, 3);
+
+    // change the palette while rendering is disabled, the LCD keeps the previous frame
+    gb.get_peripherals_mut().ppu.set_render_enabled(false);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_PALETTE_BG, PALETTE_WHITE);
+    assert_eq!(black_frame, run_frames(&mut gb, 3));
+
+    // after enabling rendering again, the next frame displays the new palette
+    gb.get_peripherals_mut().ppu.set_render_enabled(true);
+    let white_frame = run_frames(&mut gb, 1);
+    assert_ne!(black_frame, white_frame);
+
+    let mut gb_white = create_gameboy(PALETTE_WHITE);
+    assert_eq!(white_frame, run_frames(&mut gb_white, 3));
+}
@@ -0,0 +1,168 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::graphic_data::{Color, SpritePixelValue};
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::{create_gameboy_color, create_gameboy_for};
+
+
+/// Bit in BCPS/OCPS to enable auto increment.
+const AUTO_INCREMENT: u8 = 0b_1000_0000;
+
+/// The unused bit in BCPS/OCPS, which always reads as 1.
+const UNUSED_BIT: u8 = 0b_0100_0000;
+
+/// Pure red as RGB555 value.
+const COLOR_RED: u16 = 0x001f;
+
+/// Pure blue as RGB555 value.
+const COLOR_BLUE: u16 = 0x7c00;
+
+
+/// Writes a color into palette memory via index and data register with auto increment enabled.
+fn write_color(gb: &mut GameBoy, index_register: u16, palette: u8, color_index: u8, color: u16) {
+    let address = palette * 8 + color_index * 2;
+    let mmu     = gb.get_mmu_mut();
+
+    mmu.write_u8(index_register, AUTO_INCREMENT | address);
+    mmu.write_u8(index_register + 1, (color & 0xff) as u8);
+    mmu.write_u8(index_register + 1, (color >> 8) as u8);
+}
+
+
+/// Reads a color from palette memory via index and data register.
+fn read_color(gb: &mut GameBoy, index_register: u16, palette: u8, color_index: u8) -> u16 {
+    let address = palette * 8 + color_index * 2;
+    let mmu     = gb.get_mmu_mut();
+
+    mmu.write_u8(index_register, address);
+    let low = mmu.read_u8(index_register + 1);
+
+    mmu.write_u8(index_register, address + 1);
+    let high = mmu.read_u8(index_register + 1);
+
+    ((high as u16) << 8) | (low as u16)
+}
+
+
+/// Runs the PPU until it enters the given mode.
+fn run_ppu_until_mode(gb: &mut GameBoy, mode: u8) {
+    for _ in 0..1000 {
+        if gb.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11 == mode {
+            return;
+        }
+
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    panic!("PPU did not enter mode {mode}");
+}
+
+
+#[test]
+fn test_write_background_color_with_auto_increment() {
+    let mut gb = create_gameboy_color();
+
+    write_color(&mut gb, MEMORY_LOCATION_BCPS, 3, 2, COLOR_RED);
+
+    // the pointer was incremented twice and points to the next color
+    assert_eq!(AUTO_INCREMENT | UNUSED_BIT | (3 * 8 + 2 * 2 + 2), gb.get_mmu().read_u8(MEMORY_LOCATION_BCPS));
+
+    assert_eq!(COLOR_RED, read_color(&mut gb, MEMORY_LOCATION_BCPS, 3, 2));
+
+    let palette = &gb.get_peripherals().ppu.get_palettes().gbc_background_palette.get()[3];
+    assert_eq!(COLOR_RED, palette.get_raw_color(&SpritePixelValue::new(2)));
+}
+
+
+#[test]
+fn test_write_object_color_with_auto_increment() {
+    let mut gb = create_gameboy_color();
+
+    write_color(&mut gb, MEMORY_LOCATION_OCPS, 7, 3, COLOR_BLUE);
+
+    assert_eq!(COLOR_BLUE, read_color(&mut gb, MEMORY_LOCATION_OCPS, 7, 3));
+
+    let palette = &gb.get_peripherals().ppu.get_palettes().gbc_object_palette.get()[7];
+    assert_eq!(COLOR_BLUE, palette.get_raw_color(&SpritePixelValue::new(3)));
+
+    // background palettes are not affected
+    assert_eq!(0x0000, read_color(&mut gb, MEMORY_LOCATION_BCPS, 7, 3));
+}
+
+
+#[test]
+fn test_auto_increment_wraps_around() {
+    let mut gb = create_gameboy_color();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPS, AUTO_INCREMENT | 0x3f);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPD, 0x12);
+
+    assert_eq!(AUTO_INCREMENT | UNUSED_BIT, gb.get_mmu().read_u8(MEMORY_LOCATION_BCPS));
+}
+
+
+#[test]
+fn test_palette_memory_locked_during_mode_3() {
+    let mut gb = create_gameboy_color();
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+    run_ppu_until_mode(&mut gb, 3);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPS, AUTO_INCREMENT);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_BCPD, 0x1f);
+
+    // the write was ignored, but the pointer was incremented anyway
+    assert_eq!(AUTO_INCREMENT | UNUSED_BIT | 0x01, gb.get_mmu().read_u8(MEMORY_LOCATION_BCPS));
+    assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_BCPD));
+
+    let palette = &gb.get_peripherals().ppu.get_palettes().gbc_background_palette.get()[0];
+    assert_eq!(0x0000, palette.get_raw_color(&SpritePixelValue::new(0)));
+}
+
+
+#[test]
+fn test_background_drawn_with_palette_memory() {
+    let mut gb = create_gameboy_color();
+
+    // all tiles are empty, so the background is drawn with color #0 of palette #0
+    write_color(&mut gb, MEMORY_LOCATION_BCPS, 0, 0, COLOR_RED);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+
+    // the first frame after enabling the LCD is not drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    let expected = Color::from_rgb_555(COLOR_RED);
+    let pixel    = gb.get_peripherals().ppu.get_lcd().get_pixel(0, 0);
+    assert_eq!((expected.r, expected.g, expected.b), (pixel.r, pixel.g, pixel.b));
+}
+
+
+#[test]
+fn test_palette_registers_unavailable_in_dmg_mode() {
+    let mut gb = create_gameboy_for(DeviceType::GameBoyColor, EmulationType::DMG);
+
+    for address in [MEMORY_LOCATION_BCPS, MEMORY_LOCATION_BCPD, MEMORY_LOCATION_OCPS, MEMORY_LOCATION_OCPD] {
+        gb.get_mmu_mut().write_u8(address, 0x00);
+        assert_eq!(0xff, gb.get_mmu().read_u8(address));
+    }
+}
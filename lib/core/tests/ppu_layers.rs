@@ -0,0 +1,196 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{Layer, CPU_CYCLES_PER_FRAME, SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, window (0x9C00 tilemap), sprites, background (0x9800 tilemap)
+/// and 0x8000 tile data enabled.
+const LCDC_ALL_LAYERS: u8 = 0b_1111_0011;
+
+/// The color index of the tiles displayed on the background.
+const BACKGROUND_COLOR: usize = 1;
+
+/// The color index of the tiles displayed on the window.
+const WINDOW_COLOR: usize = 2;
+
+/// The color index of the sprites.
+const SPRITE_COLOR: usize = 3;
+
+/// The screen position where the window begins.
+const WINDOW_SCREEN_X: u32 = 80;
+const WINDOW_SCREEN_Y: u32 = 72;
+
+/// The screen positions of the sprites, one on the background and one on the window.
+const SPRITE_POSITIONS: [(u32, u32); 2] = [(16, 16), (96, 96)];
+
+
+/// Creates a new emulator instance displaying a background, a window covering the lower right
+/// part of the screen and two sprites, each layer in a different color, with the given layers visible.
+fn create_gameboy(visible_layers: &[Layer]) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.initialize();
+
+    for layer in Layer::ALL {
+        gb.get_peripherals_mut().ppu.set_layer_visible(layer, visible_layers.contains(&layer));
+    }
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // tiles #0 - #3 are filled with the color of their tile number
+    for tile in 0..4u16 {
+        let low  = if (tile & 0x01) != 0 { 0xff } else { 0x00 };
+        let high = if (tile & 0x02) != 0 { 0xff } else { 0x00 };
+
+        for line in 0..8 {
+            mmu.write_u8(0x8000 + tile * 16 + line * 2,     low);
+            mmu.write_u8(0x8000 + tile * 16 + line * 2 + 1, high);
+        }
+    }
+
+    for offset in 0..(32 * 32) {
+        mmu.write_u8(0x9800 + offset, BACKGROUND_COLOR as u8);
+        mmu.write_u8(0x9c00 + offset, WINDOW_COLOR as u8);
+    }
+
+    // clear OAM, then place the sprites
+    for offset in 0..0xa0 {
+        mmu.write_u8(MEMORY_LOCATION_OAM_BEGIN + offset, 0x00);
+    }
+
+    for (index, (x, y)) in SPRITE_POSITIONS.iter().enumerate() {
+        let address = MEMORY_LOCATION_OAM_BEGIN + (index as u16) * 4;
+        mmu.write_u8(address,     (*y + 16) as u8);
+        mmu.write_u8(address + 1, (*x + 8) as u8);
+        mmu.write_u8(address + 2, SPRITE_COLOR as u8);
+        mmu.write_u8(address + 3, 0x00);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_OBP0, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_SCX, 0);
+    mmu.write_u8(MEMORY_LOCATION_SCY, 0);
+    mmu.write_u8(MEMORY_LOCATION_WX, (WINDOW_SCREEN_X + 7) as u8);
+    mmu.write_u8(MEMORY_LOCATION_WY, WINDOW_SCREEN_Y as u8);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_ALL_LAYERS);
+
+    // the first frame after enabling the LCD will not be drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    gb
+}
+
+
+/// Get the color index of a pixel on the LCD.
+fn get_color_index(gb: &GameBoy, x: u32, y: u32) -> usize {
+    let ppu    = &gb.get_peripherals().ppu;
+    let color  = ppu.get_lcd().get_pixel(x, y).to_u32();
+    let colors = ppu.get_dmg_display_palette().get_colors();
+
+    colors.iter().position(|c| c.to_u32() == color).unwrap()
+}
+
+
+/// Computes the color index expected on a pixel with the given layers being visible.
+fn expected_color(x: u32, y: u32, visible_layers: &[Layer]) -> usize {
+    let on_sprite = SPRITE_POSITIONS.iter().any(|(sx, sy)| x >= *sx && x < sx + 8 && y >= *sy && y < sy + 8);
+    let on_window = x >= WINDOW_SCREEN_X && y >= WINDOW_SCREEN_Y;
+
+    if on_sprite && visible_layers.contains(&Layer::Objects) {
+        SPRITE_COLOR
+    }
+    else if on_window && visible_layers.contains(&Layer::Window) {
+        WINDOW_COLOR
+    }
+    else if visible_layers.contains(&Layer::Background) {
+        BACKGROUND_COLOR
+    }
+    else {
+        0
+    }
+}
+
+
+/// Checks each pixel of the screen displays the expected layers.
+fn check_layers(visible_layers: &[Layer]) {
+    let gb = create_gameboy(visible_layers);
+
+    for y in 0..SCREEN_H {
+        for x in 0..SCREEN_W {
+            assert_eq!(
+                expected_color(x, y, visible_layers),
+                get_color_index(&gb, x, y),
+                "unexpected pixel at {x}/{y} with layers {visible_layers:?}"
+            );
+        }
+    }
+
+    // the emulated LCDC register is not affected by hidden layers
+    assert_eq!(LCDC_ALL_LAYERS, gb.get_mmu().read_u8(MEMORY_LOCATION_LCD_CONTROL));
+}
+
+
+#[test]
+fn test_all_layers_visible_by_default() {
+    let gb = create_gameboy_dmg();
+
+    for layer in Layer::ALL {
+        assert!(gb.get_peripherals().ppu.is_layer_visible(layer));
+    }
+
+    check_layers(&Layer::ALL);
+}
+
+
+#[test]
+fn test_hide_objects() {
+    check_layers(&[Layer::Background, Layer::Window]);
+}
+
+
+#[test]
+fn test_hide_window() {
+    check_layers(&[Layer::Background, Layer::Objects]);
+}
+
+
+#[test]
+fn test_hide_background() {
+    check_layers(&[Layer::Window, Layer::Objects]);
+}
+
+
+#[test]
+fn test_background_only() {
+    let gb = create_gameboy(&[Layer::Background]);
+
+    for y in 0..SCREEN_H {
+        for x in 0..SCREEN_W {
+            assert_eq!(BACKGROUND_COLOR, get_color_index(&gb, x, y), "unexpected pixel at {x}/{y}");
+        }
+    }
+}
@@ -0,0 +1,152 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::graphic_data::Color;
+use gemi_core::ppu::ppu::{CPU_CYCLES_OAMSCAN, CPU_CYCLES_PER_FRAME, CPU_CYCLES_PER_LINE, SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, background and 0x8000 tile data enabled.
+const LCDC_ENABLED: u8  = 0b_1001_0001;
+
+/// LCDC value with the same settings as [LCDC_ENABLED], but the LCD disabled.
+const LCDC_DISABLED: u8 = 0b_0001_0001;
+
+
+/// Creates a new emulator instance with the LCD enabled, which displays
+/// a background completely filled with color #3.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // tile #0 is completely filled with color #3
+    for i in 0..16 {
+        mmu.write_u8(0x8000 + i, 0xff);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_ENABLED);
+
+    gb
+}
+
+
+/// Runs the PPU for a number of cycles.
+fn run_ppu(gb: &mut GameBoy, cycles: u64) {
+    for _ in 0..(cycles / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+}
+
+
+/// Get the current PPU mode as read from the LCD status register.
+fn read_ppu_mode(gb: &GameBoy) -> u8 {
+    gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11
+}
+
+
+/// Get the current scanline as read from the LY register.
+fn read_ly(gb: &GameBoy) -> u8 {
+    gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LY)
+}
+
+
+/// Checks whether two colors are equal.
+fn is_same_color(a: &Color, b: &Color) -> bool {
+    a.r == b.r && a.g == b.g && a.b == b.b
+}
+
+
+/// Checks whether the whole LCD is filled with the blank color.
+fn is_screen_blank(gb: &GameBoy) -> bool {
+    let ppu   = &gb.get_peripherals().ppu;
+    let blank = ppu.get_dmg_display_palette().get_colors()[0];
+
+    (0..SCREEN_H).all(|y| {
+        (0..SCREEN_W).all(|x| {
+            is_same_color(&blank, ppu.get_lcd().get_pixel(x, y))
+        })
+    })
+}
+
+
+#[test]
+fn test_lcd_disable_mid_frame_resets_ly() {
+    let mut gb = create_gameboy();
+    run_ppu(&mut gb, CPU_CYCLES_PER_FRAME + 50 * CPU_CYCLES_PER_LINE);
+    assert_eq!(50, read_ly(&gb));
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_DISABLED);
+    assert_eq!(0, read_ly(&gb));
+    assert_eq!(0, read_ppu_mode(&gb));
+
+    // the PPU does not proceed while disabled
+    run_ppu(&mut gb, CPU_CYCLES_PER_FRAME);
+    assert_eq!(0, read_ly(&gb));
+    assert_eq!(0, read_ppu_mode(&gb));
+}
+
+
+#[test]
+fn test_lcd_disable_keeps_registers() {
+    let mut gb = create_gameboy();
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LYC, 0x42);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_SCX, 0x13);
+    run_ppu(&mut gb, 20 * CPU_CYCLES_PER_LINE);
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_DISABLED);
+    assert_eq!(0x42, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LYC));
+    assert_eq!(0x13, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_SCX));
+}
+
+
+#[test]
+fn test_lcd_disable_clears_screen() {
+    let mut gb = create_gameboy();
+
+    // the first frame after enabling the LCD will not be drawn
+    run_ppu(&mut gb, 2 * CPU_CYCLES_PER_FRAME);
+    assert!(!is_screen_blank(&gb));
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_DISABLED);
+    assert!(is_screen_blank(&gb));
+}
+
+
+#[test]
+fn test_lcd_enable_restarts_at_line_0() {
+    let mut gb = create_gameboy();
+    run_ppu(&mut gb, CPU_CYCLES_PER_FRAME + 100 * CPU_CYCLES_PER_LINE);
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_DISABLED);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_ENABLED);
+    assert_eq!(0, read_ly(&gb));
+
+    // the first line does not perform an OAM scan and starts drawing afterwards
+    assert_eq!(0, read_ppu_mode(&gb));
+    run_ppu(&mut gb, CPU_CYCLES_OAMSCAN);
+    assert_eq!(3, read_ppu_mode(&gb));
+
+    // the next line starts with an OAM scan as usual
+    run_ppu(&mut gb, CPU_CYCLES_PER_LINE - CPU_CYCLES_OAMSCAN);
+    assert_eq!(1, read_ly(&gb));
+    assert_eq!(2, read_ppu_mode(&gb));
+}
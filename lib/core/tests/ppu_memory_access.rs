@@ -0,0 +1,245 @@
+/*
+ * Copyright (C) 2022-2023 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Creates a new emulator instance without any cartridge and the LCD enabled.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+
+    gb
+}
+
+
+/// Get the current PPU mode as read from the LCD status register.
+fn read_ppu_mode(gb: &GameBoy) -> u8 {
+    gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11
+}
+
+
+/// Runs the PPU until it enters the given mode.
+fn run_ppu_until_mode(gb: &mut GameBoy, mode: u8) {
+    for _ in 0..1000 {
+        if read_ppu_mode(gb) == mode {
+            return;
+        }
+
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    panic!("PPU did not enter mode {mode}");
+}
+
+
+/// Writes a value into the given address and reads it back like the CPU would do.
+fn write_and_read(gb: &mut GameBoy, address: u16, value: u8) -> u8 {
+    gb.cpu.get_mmu_mut().cpu_write_u8(address, value);
+    gb.cpu.get_mmu_mut().cpu_read_u8(address)
+}
+
+
+#[test]
+fn vram_write_ignored_during_mode_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 3);
+
+    let result = write_and_read(&mut gb, MEMORY_LOCATION_VRAM_BEGIN, 0x42);
+    assert_eq!(0xff, result);
+
+    // after leaving mode 3 the value written before should not be present
+    run_ppu_until_mode(&mut gb, 0);
+    assert_eq!(0x00, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_VRAM_BEGIN));
+}
+
+
+#[test]
+fn vram_write_succeeds_during_hblank() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+
+    let result = write_and_read(&mut gb, MEMORY_LOCATION_VRAM_BEGIN, 0x42);
+    assert_eq!(0x42, result);
+}
+
+
+#[test]
+fn oam_write_ignored_during_mode_2() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+    run_ppu_until_mode(&mut gb, 2);
+
+    let result = write_and_read(&mut gb, MEMORY_LOCATION_OAM_BEGIN, 0x42);
+    assert_eq!(0xff, result);
+}
+
+
+#[test]
+fn oam_write_succeeds_during_hblank() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+
+    let result = write_and_read(&mut gb, MEMORY_LOCATION_OAM_BEGIN, 0x42);
+    assert_eq!(0x42, result);
+}
+
+
+#[test]
+fn vram_read_blocked_during_mode_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN, 0x42);
+
+    run_ppu_until_mode(&mut gb, 2);
+    assert!(gb.get_peripherals().ppu.is_vram_accessible());
+    assert_eq!(0x42, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_VRAM_BEGIN));
+
+    run_ppu_until_mode(&mut gb, 3);
+    assert!(!gb.get_peripherals().ppu.is_vram_accessible());
+    assert_eq!(0xff, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_VRAM_BEGIN));
+
+    // the value is readable again after leaving mode 3
+    run_ppu_until_mode(&mut gb, 0);
+    assert_eq!(0x42, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_VRAM_BEGIN));
+}
+
+
+#[test]
+fn oam_read_blocked_during_mode_2_and_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_OAM_BEGIN, 0x42);
+
+    run_ppu_until_mode(&mut gb, 2);
+    assert!(!gb.get_peripherals().ppu.is_oam_accessible());
+    assert_eq!(0xff, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN));
+
+    run_ppu_until_mode(&mut gb, 3);
+    assert!(!gb.get_peripherals().ppu.is_oam_accessible());
+    assert_eq!(0xff, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN));
+
+    // the value is readable again after leaving mode 3
+    run_ppu_until_mode(&mut gb, 0);
+    assert_eq!(0x42, gb.cpu.get_mmu_mut().cpu_read_u8(MEMORY_LOCATION_OAM_BEGIN));
+}
+
+
+#[test]
+fn opcode_fetch_blocked_during_mode_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+
+    // store 'LD A, 0x42' in VRAM
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN,     0x3e);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 1, 0x42);
+
+    gb.cpu.set_instruction_pointer(MEMORY_LOCATION_VRAM_BEGIN);
+    let instruction = gb.cpu.fetch_next_instruction();
+    assert_eq!(0x3e, instruction.opcode_id);
+    assert_eq!(0x42, instruction.arg[0]);
+
+    // while the PPU locks VRAM, the CPU reads 0xff, which is 'RST 38h'
+    run_ppu_until_mode(&mut gb, 3);
+
+    gb.cpu.set_instruction_pointer(MEMORY_LOCATION_VRAM_BEGIN);
+    let instruction = gb.cpu.fetch_next_instruction();
+    assert_eq!(0xff, instruction.opcode_id);
+    assert_eq!(0xff, instruction.arg[0]);
+    assert_eq!(MEMORY_LOCATION_VRAM_BEGIN + 1, gb.cpu.get_instruction_pointer());
+}
+
+
+#[test]
+fn memory_accessible_while_lcd_disabled() {
+    let mut gb = create_gameboy();
+    gb.initialize();
+
+    // complete the DMA transfer started by setting the initial value of the DMA register
+    gb.get_mmu_mut().update(160);
+
+    run_ppu_until_mode(&mut gb, 0);
+    run_ppu_until_mode(&mut gb, 3);
+
+    // disabling the LCD stops the PPU immediately and unlocks VRAM and OAM
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    assert!(gb.get_peripherals().ppu.is_vram_accessible());
+    assert!(gb.get_peripherals().ppu.is_oam_accessible());
+    assert_eq!(0x42, write_and_read(&mut gb, MEMORY_LOCATION_VRAM_BEGIN, 0x42));
+    assert_eq!(0x42, write_and_read(&mut gb, MEMORY_LOCATION_OAM_BEGIN,  0x42));
+}
+
+
+#[test]
+fn lcd_disabled_after_creating_instance() {
+    let mut gb = GameBoy::build()
+        .finish()
+        .unwrap()
+    ;
+
+    // a new instance starts with the LCD disabled and the PPU in mode 0 on the first line
+    assert_eq!(0x00, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LCD_CONTROL));
+    assert_eq!(0x00, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LY));
+    assert_eq!(0, read_ppu_mode(&gb));
+
+    // the PPU does not proceed while the LCD is disabled, so memory stays accessible
+    gb.get_peripherals_mut().ppu.update(1000);
+    assert_eq!(0x00, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LY));
+    assert_eq!(0, read_ppu_mode(&gb));
+    assert_eq!(0x42, write_and_read(&mut gb, MEMORY_LOCATION_VRAM_BEGIN, 0x42));
+    assert_eq!(0x42, write_and_read(&mut gb, MEMORY_LOCATION_OAM_BEGIN,  0x42));
+}
+
+
+#[test]
+fn debugger_reads_not_blocked_during_mode_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+    gb.cpu.get_mmu_mut().cpu_write_u8(MEMORY_LOCATION_VRAM_BEGIN, 0x42);
+    gb.cpu.get_mmu_mut().cpu_write_u8(MEMORY_LOCATION_OAM_BEGIN,  0x24);
+
+    run_ppu_until_mode(&mut gb, 3);
+
+    // reads not performed by the CPU are able to see the memory contents
+    assert_eq!(0x42, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_VRAM_BEGIN));
+    assert_eq!(0x24, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_OAM_BEGIN));
+}
+
+
+#[test]
+fn dma_reads_vram_during_mode_3() {
+    let mut gb = create_gameboy();
+    run_ppu_until_mode(&mut gb, 0);
+
+    for offset in 0..0xa0 {
+        gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + offset, offset as u8);
+    }
+
+    // run the DMA transfer from VRAM while the PPU is drawing
+    run_ppu_until_mode(&mut gb, 3);
+    gb.cpu.get_mmu_mut().write_u8(MEMORY_LOCATION_DMA_ADDRESS, 0x80);
+    gb.cpu.get_mmu_mut().update(160);
+
+    for offset in 0..0xa0 {
+        assert_eq!(offset as u8, gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_OAM_BEGIN + offset));
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_PER_FRAME, SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, window (0x9C00 tilemap), background (0x9800 tilemap) and 0x8000 tile data enabled.
+const LCDC_WINDOW_ENABLED: u8 = 0b_1111_0001;
+
+/// LCDC value with LCD, background (0x9800 tilemap) and 0x8000 tile data enabled.
+const LCDC_WINDOW_DISABLED: u8 = 0b_1101_0001;
+
+
+/// The color index of the tile displayed on a background tilemap cell.
+fn background_color(tile_x: u32, tile_y: u32) -> usize {
+    ((tile_x + 2 * tile_y) % 4) as usize
+}
+
+
+/// The color index of the tile displayed on a window tilemap cell.
+fn window_color(tile_x: u32, tile_y: u32) -> usize {
+    ((3 * tile_x + tile_y + 1) % 4) as usize
+}
+
+
+/// Creates a new emulator instance with the tilemaps filled with solid colored tiles,
+/// where each cell of the tilemap can be identified by its color and the color of its neighbours.
+fn create_gameboy_with_tilemaps(scx: u8, scy: u8, wx: u8, wy: u8, lcdc: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // tiles #0 - #3 are filled with the color of their tile number
+    for tile in 0..4u16 {
+        let low  = if (tile & 0x01) != 0 { 0xff } else { 0x00 };
+        let high = if (tile & 0x02) != 0 { 0xff } else { 0x00 };
+
+        for line in 0..8 {
+            mmu.write_u8(0x8000 + tile * 16 + line * 2,     low);
+            mmu.write_u8(0x8000 + tile * 16 + line * 2 + 1, high);
+        }
+    }
+
+    for tile_y in 0..32u16 {
+        for tile_x in 0..32u16 {
+            let offset = tile_y * 32 + tile_x;
+            mmu.write_u8(0x9800 + offset, background_color(tile_x as u32, tile_y as u32) as u8);
+            mmu.write_u8(0x9c00 + offset, window_color(tile_x as u32, tile_y as u32) as u8);
+        }
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_SCX, scx);
+    mmu.write_u8(MEMORY_LOCATION_SCY, scy);
+    mmu.write_u8(MEMORY_LOCATION_WX,  wx);
+    mmu.write_u8(MEMORY_LOCATION_WY,  wy);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, lcdc);
+
+    // the first frame after enabling the LCD will not be drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    gb
+}
+
+
+/// Get the color index of a pixel on the LCD.
+fn get_color_index(gb: &GameBoy, x: u32, y: u32) -> usize {
+    let ppu    = &gb.get_peripherals().ppu;
+    let color  = ppu.get_lcd().get_pixel(x, y).to_u32();
+    let colors = ppu.get_dmg_display_palette().get_colors();
+
+    colors.iter().position(|c| c.to_u32() == color).unwrap()
+}
+
+
+/// Get the color index expected on a screen pixel displaying the background.
+fn expected_background_color(x: u32, y: u32, scx: u8, scy: u8) -> usize {
+    let background_x = (x + scx as u32) & 0xff;
+    let background_y = (y + scy as u32) & 0xff;
+
+    background_color(background_x / 8, background_y / 8)
+}
+
+
+#[test]
+fn test_background_wraps_at_map_boundary() {
+    for (scx, scy) in [(250, 250), (255, 255), (248, 0), (0, 248), (129, 130)] {
+        let gb = create_gameboy_with_tilemaps(scx, scy, 0, 0, LCDC_WINDOW_DISABLED);
+
+        for y in 0..SCREEN_H {
+            for x in 0..SCREEN_W {
+                assert_eq!(
+                    expected_background_color(x, y, scx, scy),
+                    get_color_index(&gb, x, y),
+                    "unexpected pixel at {x}/{y} with SCX={scx} SCY={scy}"
+                );
+            }
+        }
+    }
+}
+
+
+#[test]
+fn test_background_wraps_from_bottom_right_to_top_left() {
+    let gb = create_gameboy_with_tilemaps(250, 250, 0, 0, LCDC_WINDOW_DISABLED);
+
+    // the first 6 pixels in both directions show the last tile of the map,
+    // the following ones the first tile of the map
+    assert_eq!(background_color(31, 31), get_color_index(&gb, 5, 5));
+    assert_eq!(background_color(0,  31), get_color_index(&gb, 6, 5));
+    assert_eq!(background_color(31,  0), get_color_index(&gb, 5, 6));
+    assert_eq!(background_color(0,   0), get_color_index(&gb, 6, 6));
+    assert_eq!(background_color(1,   1), get_color_index(&gb, 14, 14));
+}
+
+
+#[test]
+fn test_background_wrap_combined_with_window() {
+    let (scx, scy) = (250, 250);
+    let (wx, wy)   = (87, 72);
+    let gb         = create_gameboy_with_tilemaps(scx, scy, wx, wy, LCDC_WINDOW_ENABLED);
+
+    for y in 0..SCREEN_H {
+        for x in 0..SCREEN_W {
+            let in_window = x + 7 >= wx as u32 && y >= wy as u32;
+
+            let expected = if in_window {
+                let window_x = x + 7 - wx as u32;
+                let window_y = y - wy as u32;
+                window_color(window_x / 8, window_y / 8)
+            }
+            else {
+                expected_background_color(x, y, scx, scy)
+            };
+
+            assert_eq!(expected, get_color_index(&gb, x, y), "unexpected pixel at {x}/{y}");
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_PER_FRAME, SPRITES_PER_LINE};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, sprites, background (0x9800 tilemap) and 0x8000 tile data enabled.
+const LCDC_SPRITES_ENABLED: u8 = 0b_1001_0011;
+
+/// The number of sprites placed on the same scanline.
+const SPRITES_ON_LINE: u8 = 12;
+
+/// The scanline where all sprites are placed.
+const SPRITE_LINE: u8 = 20;
+
+
+/// Creates a new emulator instance with a white background and 12 black sprites
+/// placed next to each other on the same scanline.
+fn create_gameboy(sprite_limit: Option<u8>) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.initialize();
+    gb.get_peripherals_mut().ppu.set_sprite_limit(sprite_limit);
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // tile #0 is empty and used for the background, tile #1 is filled with color #3
+    for offset in 0..16 {
+        mmu.write_u8(0x8000 + offset, 0x00);
+        mmu.write_u8(0x8010 + offset, 0xff);
+    }
+
+    for offset in 0..(32 * 32) {
+        mmu.write_u8(0x9800 + offset, 0x00);
+    }
+
+    // clear OAM, then place the sprites next to each other
+    for offset in 0..0xa0 {
+        mmu.write_u8(MEMORY_LOCATION_OAM_BEGIN + offset, 0x00);
+    }
+
+    for sprite in 0..SPRITES_ON_LINE as u16 {
+        let address = MEMORY_LOCATION_OAM_BEGIN + sprite * 4;
+        mmu.write_u8(address,     SPRITE_LINE + 16);
+        mmu.write_u8(address + 1, 8 + (sprite as u8) * 8);
+        mmu.write_u8(address + 2, 1);
+        mmu.write_u8(address + 3, 0x00);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_OBP0, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_SCX, 0);
+    mmu.write_u8(MEMORY_LOCATION_SCY, 0);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_SPRITES_ENABLED);
+
+    // the first frame after enabling the LCD will not be drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    gb
+}
+
+
+/// Get the color index of a pixel on the LCD.
+fn get_color_index(gb: &GameBoy, x: u32, y: u32) -> usize {
+    let ppu    = &gb.get_peripherals().ppu;
+    let color  = ppu.get_lcd().get_pixel(x, y).to_u32();
+    let colors = ppu.get_dmg_display_palette().get_colors();
+
+    colors.iter().position(|c| c.to_u32() == color).unwrap()
+}
+
+
+/// Get the number of sprites being displayed on the sprite line.
+fn count_visible_sprites(gb: &GameBoy) -> u8 {
+    (0..SPRITES_ON_LINE)
+        .filter(|sprite| get_color_index(gb, (*sprite as u32) * 8 + 4, SPRITE_LINE as u32) == 3)
+        .count() as u8
+}
+
+
+#[test]
+fn test_default_sprite_limit() {
+    let gb = create_gameboy(Some(SPRITES_PER_LINE));
+    let ppu = &gb.get_peripherals().ppu;
+
+    assert_eq!(Some(10), ppu.get_sprite_limit());
+    assert_eq!(10, ppu.do_oam_scan_for_line(SPRITE_LINE).get_sprites().len());
+    assert_eq!(10, count_visible_sprites(&gb));
+
+    // the sprites with the higher OAM index are dropped
+    assert_eq!(3, get_color_index(&gb, 9 * 8 + 4, SPRITE_LINE as u32));
+    assert_eq!(0, get_color_index(&gb, 10 * 8 + 4, SPRITE_LINE as u32));
+
+    // the default of a new instance is the hardware limit
+    let gb_default = create_gameboy_dmg();
+    assert_eq!(Some(SPRITES_PER_LINE), gb_default.get_peripherals().ppu.get_sprite_limit());
+}
+
+
+#[test]
+fn test_sprite_limit_removed() {
+    let gb = create_gameboy(None);
+    let ppu = &gb.get_peripherals().ppu;
+
+    assert_eq!(12, ppu.do_oam_scan_for_line(SPRITE_LINE).get_sprites().len());
+    assert_eq!(12, count_visible_sprites(&gb));
+}
+
+
+#[test]
+fn test_custom_sprite_limit() {
+    let gb = create_gameboy(Some(4));
+    let ppu = &gb.get_peripherals().ppu;
+
+    assert_eq!(4, ppu.do_oam_scan_for_line(SPRITE_LINE).get_sprites().len());
+    assert_eq!(4, count_visible_sprites(&gb));
+}
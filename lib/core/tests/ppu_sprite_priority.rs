@@ -0,0 +1,191 @@
+/*
+ * Copyright (C) 2022-2023 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::{create_gameboy_color, create_gameboy_dmg};
+
+
+/// LCDC value with LCD, sprites, background (0x9800 tilemap) and 0x8000 tile data enabled.
+const LCDC_SPRITES_ENABLED: u8 = 0b_1001_0011;
+
+/// The scanline where all sprites are placed.
+const SPRITE_LINE: u8 = 20;
+
+/// The tile filled with color #1.
+const TILE_COLOR_1: u8 = 1;
+
+/// The tile filled with color #3.
+const TILE_COLOR_3: u8 = 2;
+
+
+/// Fills the video memory with a blank background and two tiles filled with color #1 and #3,
+/// clears OAM and places the given sprites as (x position, tile) on the same scanline.
+fn setup_sprites(gb: &mut GameBoy, sprites: &[(u8, u8)]) {
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    for offset in 0..16 {
+        mmu.write_u8(0x8000 + offset, 0x00);
+        mmu.write_u8(0x8010 + offset, if (offset % 2) == 0 { 0xff } else { 0x00 });
+        mmu.write_u8(0x8020 + offset, 0xff);
+    }
+
+    for offset in 0..(32 * 32) {
+        mmu.write_u8(0x9800 + offset, 0x00);
+    }
+
+    for offset in 0..0xa0 {
+        mmu.write_u8(MEMORY_LOCATION_OAM_BEGIN + offset, 0x00);
+    }
+
+    for (index, (pos_x, tile)) in sprites.iter().enumerate() {
+        let address = MEMORY_LOCATION_OAM_BEGIN + (index as u16) * 4;
+        mmu.write_u8(address,     SPRITE_LINE + 16);
+        mmu.write_u8(address + 1, *pos_x);
+        mmu.write_u8(address + 2, *tile);
+        mmu.write_u8(address + 3, 0x00);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_OBP0, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_SCX, 0);
+    mmu.write_u8(MEMORY_LOCATION_SCY, 0);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_SPRITES_ENABLED);
+}
+
+
+/// Runs the PPU until the first frame after enabling the LCD got drawn.
+fn draw_frame(gb: &mut GameBoy) {
+    // the first frame after enabling the LCD will not be drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+}
+
+
+/// Creates a new DMG emulator instance displaying the given sprites.
+fn create_dmg_with_sprites(sprites: &[(u8, u8)]) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    gb.initialize();
+    setup_sprites(&mut gb, sprites);
+    draw_frame(&mut gb);
+
+    gb
+}
+
+
+/// Creates a new GBC emulator instance displaying the given sprites.
+/// Object palette #0 maps color #1 to red and color #3 to blue.
+fn create_gbc_with_sprites(sprites: &[(u8, u8)], object_priority: u8) -> GameBoy {
+    let mut gb = create_gameboy_color();
+
+    gb.initialize();
+    setup_sprites(&mut gb, sprites);
+
+    let mmu = gb.cpu.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_OPRI, object_priority);
+
+    // write object palette #0 with auto increment
+    mmu.write_u8(MEMORY_LOCATION_OCPS, 0x80);
+
+    for color in [0x7fff_u16, 0x001f, 0x03e0, 0x7c00] {
+        mmu.write_u8(MEMORY_LOCATION_OCPD, (color & 0xff) as u8);
+        mmu.write_u8(MEMORY_LOCATION_OCPD, (color >> 8) as u8);
+    }
+
+    draw_frame(&mut gb);
+
+    gb
+}
+
+
+/// Get the color index of a sprite pixel on the sprite line of a DMG instance.
+fn get_dmg_color_index(gb: &GameBoy, x: u32) -> usize {
+    let ppu    = &gb.get_peripherals().ppu;
+    let color  = ppu.get_lcd().get_pixel(x, SPRITE_LINE as u32);
+    let colors = ppu.get_dmg_display_palette().get_colors();
+
+    colors.iter()
+        .position(|c| c.to_u32() == color.to_u32())
+        .expect("pixel does not match any palette color")
+}
+
+
+/// Checks whether a sprite pixel on the sprite line of a GBC instance is red, which is
+/// color #1 of the object palette, rather than blue, which is color #3.
+fn is_gbc_pixel_red(gb: &GameBoy, x: u32) -> bool {
+    let color = gb.get_peripherals().ppu.get_lcd().get_pixel(x, SPRITE_LINE as u32);
+
+    color.r > color.b
+}
+
+
+#[test]
+fn dmg_equal_x_resolves_by_lower_oam_index() {
+    let gb = create_dmg_with_sprites(&[(16, TILE_COLOR_1), (16, TILE_COLOR_3)]);
+    assert_eq!(1, get_dmg_color_index(&gb, 8));
+
+    let gb = create_dmg_with_sprites(&[(16, TILE_COLOR_3), (16, TILE_COLOR_1)]);
+    assert_eq!(3, get_dmg_color_index(&gb, 8));
+}
+
+
+#[test]
+fn dmg_lower_x_wins_over_lower_oam_index() {
+    // sprite #0 covers screen pixels 12 to 19, sprite #1 covers 8 to 15
+    let gb = create_dmg_with_sprites(&[(20, TILE_COLOR_1), (16, TILE_COLOR_3)]);
+
+    for x in 8..16 {
+        assert_eq!(3, get_dmg_color_index(&gb, x), "unexpected pixel at {x}");
+    }
+
+    for x in 16..20 {
+        assert_eq!(1, get_dmg_color_index(&gb, x), "unexpected pixel at {x}");
+    }
+}
+
+
+#[test]
+fn gbc_lower_oam_index_wins_without_x_sort() {
+    let gb = create_gbc_with_sprites(&[(20, TILE_COLOR_1), (16, TILE_COLOR_3)], 0x00);
+
+    // the overlapping pixels show sprite #0, although sprite #1 has the lower x position
+    for x in 12..20 {
+        assert!(is_gbc_pixel_red(&gb, x), "unexpected pixel at {x}");
+    }
+
+    for x in 8..12 {
+        assert!(!is_gbc_pixel_red(&gb, x), "unexpected pixel at {x}");
+    }
+}
+
+
+#[test]
+fn gbc_object_priority_bit_enables_x_sort() {
+    let gb = create_gbc_with_sprites(&[(20, TILE_COLOR_1), (16, TILE_COLOR_3)], 0x01);
+
+    for x in 8..16 {
+        assert!(!is_gbc_pixel_red(&gb, x), "unexpected pixel at {x}");
+    }
+}
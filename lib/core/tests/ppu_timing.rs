@@ -0,0 +1,157 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_DRAW_MIN, CPU_CYCLES_DRAW_PER_SPRITE, CPU_CYCLES_DRAW_WINDOW, CPU_CYCLES_PER_LINE};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, sprites and background enabled.
+const LCDC_SPRITES_ENABLED: u8 = 0b_1000_0011;
+
+/// LCDC value with LCD, window, sprites and background enabled.
+const LCDC_WINDOW_ENABLED: u8  = 0b_1010_0011;
+
+/// The line to be measured, which is not the first line after enabling the LCD.
+const MEASURED_LINE: u8 = 2;
+
+
+/// Creates a new emulator instance with a number of sprites placed on the first scanlines.
+fn create_gameboy_with_sprites(sprite_count: u8, lcdc: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    for i in 0..40 {
+        let address = MEMORY_LOCATION_OAM_BEGIN + i * 4;
+
+        // place the requested number of sprites on lines 0 - 7, all others outside the screen
+        let (y, x) = if i < sprite_count as u16 {
+            (16, 8 + (i as u8) * 8)
+        }
+        else {
+            (0, 0)
+        };
+
+        mmu.write_u8(address,     y);
+        mmu.write_u8(address + 1, x);
+        mmu.write_u8(address + 2, 0);
+        mmu.write_u8(address + 3, 0);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_WX, 7);
+    mmu.write_u8(MEMORY_LOCATION_WY, 0);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, lcdc);
+
+    gb
+}
+
+
+/// Get the current PPU mode as read from the LCD status register.
+fn read_ppu_mode(gb: &GameBoy) -> u8 {
+    gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LCD_STATUS) & 0b11
+}
+
+
+/// Get the current scanline as read from the LY register.
+fn read_ly(gb: &GameBoy) -> u8 {
+    gb.cpu.get_mmu().read_u8(MEMORY_LOCATION_LY)
+}
+
+
+/// Runs the PPU cycle by cycle until the given line enters the given mode.
+fn run_ppu_until_line_and_mode(gb: &mut GameBoy, line: u8, mode: u8) {
+    for _ in 0..(2 * CPU_CYCLES_PER_LINE * (line as u64 + 1)) {
+        if read_ly(gb) == line && read_ppu_mode(gb) == mode {
+            return;
+        }
+
+        gb.get_peripherals_mut().ppu.update(1);
+    }
+
+    panic!("PPU did not enter mode {mode} on line {line}");
+}
+
+
+/// Counts the number of cycles, the PPU remains in the current mode.
+fn count_cycles_in_mode(gb: &mut GameBoy) -> u64 {
+    let mode       = read_ppu_mode(gb);
+    let mut cycles = 0;
+
+    while read_ppu_mode(gb) == mode {
+        gb.get_peripherals_mut().ppu.update(1);
+        cycles += 1;
+    }
+
+    cycles
+}
+
+
+/// Measures the number of cycles spent in Mode 3 on the measured line.
+fn measure_draw_cycles(gb: &mut GameBoy) -> u64 {
+    run_ppu_until_line_and_mode(gb, MEASURED_LINE, 3);
+    count_cycles_in_mode(gb)
+}
+
+
+#[test]
+fn test_draw_duration_without_sprites() {
+    let mut gb = create_gameboy_with_sprites(0, LCDC_SPRITES_ENABLED);
+
+    assert_eq!(CPU_CYCLES_DRAW_MIN, measure_draw_cycles(&mut gb));
+}
+
+
+#[test]
+fn test_draw_duration_with_sprites() {
+    let mut gb = create_gameboy_with_sprites(3, LCDC_SPRITES_ENABLED);
+
+    assert_eq!(CPU_CYCLES_DRAW_MIN + 3 * CPU_CYCLES_DRAW_PER_SPRITE, measure_draw_cycles(&mut gb));
+}
+
+
+#[test]
+fn test_draw_duration_limited_to_ten_sprites() {
+    let mut gb = create_gameboy_with_sprites(12, LCDC_SPRITES_ENABLED);
+
+    assert_eq!(CPU_CYCLES_DRAW_MIN + 10 * CPU_CYCLES_DRAW_PER_SPRITE, measure_draw_cycles(&mut gb));
+}
+
+
+#[test]
+fn test_draw_duration_with_window() {
+    let mut gb = create_gameboy_with_sprites(0, LCDC_WINDOW_ENABLED);
+
+    assert_eq!(CPU_CYCLES_DRAW_MIN + CPU_CYCLES_DRAW_WINDOW, measure_draw_cycles(&mut gb));
+}
+
+
+#[test]
+fn test_line_duration_with_sprites() {
+    let mut gb = create_gameboy_with_sprites(10, LCDC_SPRITES_ENABLED);
+
+    // the additional time spent in mode 3 will shorten the HBlank
+    run_ppu_until_line_and_mode(&mut gb, MEASURED_LINE, 2);
+    let oam_scan_cycles = count_cycles_in_mode(&mut gb);
+    let draw_cycles     = count_cycles_in_mode(&mut gb);
+    let hblank_cycles   = count_cycles_in_mode(&mut gb);
+
+    assert_eq!(CPU_CYCLES_DRAW_MIN + 10 * CPU_CYCLES_DRAW_PER_SPRITE, draw_cycles);
+    assert_eq!(CPU_CYCLES_PER_LINE, oam_scan_cycles + draw_cycles + hblank_cycles);
+}
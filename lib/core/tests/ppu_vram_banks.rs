@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// Creates a new emulator instance of the given device and emulation type
+/// with the LCD disabled, so VRAM is always accessible.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    gb
+}
+
+
+#[test]
+fn test_vbk_register() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_VBK), 0xfe);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x01);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_VBK), 0xff);
+    assert_eq!(gb.get_peripherals().ppu.get_vram_active_bank(), 1);
+
+    // only bit #0 selects the bank
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0xfe);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_VBK), 0xfe);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x03);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_VBK), 0xff);
+}
+
+
+#[test]
+fn test_writes_land_in_selected_bank() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    // write tile data into bank 0
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x00);
+    gb.get_mmu_mut().write_u8(0x8000, 0x11);
+
+    // bank 1 still contains the original data
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x01);
+    assert_eq!(gb.get_mmu().read_u8(0x8000), 0x00);
+
+    // write tile data and attributes into bank 1
+    gb.get_mmu_mut().write_u8(0x8000, 0x22);
+    gb.get_mmu_mut().write_u8(0x9800, 0x0f);
+    assert_eq!(gb.get_mmu().read_u8(0x8000), 0x22);
+    assert_eq!(gb.get_mmu().read_u8(0x9800), 0x0f);
+
+    // switching back reads from bank 0 again
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x00);
+    assert_eq!(gb.get_mmu().read_u8(0x8000), 0x11);
+    assert_eq!(gb.get_mmu().read_u8(0x9800), 0x00);
+
+    // check the content of each bank directly
+    let ppu = &gb.get_peripherals().ppu;
+    assert_eq!(ppu.get_vram(0)[0x0000], 0x11);
+    assert_eq!(ppu.get_vram(0)[0x1800], 0x00);
+    assert_eq!(ppu.get_vram(1)[0x0000], 0x22);
+    assert_eq!(ppu.get_vram(1)[0x1800], 0x0f);
+}
+
+
+#[test]
+fn test_no_vram_banks_on_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    assert_eq!(gb.get_mmu().read_u8(MEMORY_LOCATION_VBK), 0xff);
+
+    // selecting bank 1 has no effect
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x01);
+    gb.get_mmu_mut().write_u8(0x8000, 0x33);
+    assert_eq!(gb.get_peripherals().ppu.get_vram_active_bank(), 0);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VBK, 0x00);
+    assert_eq!(gb.get_mmu().read_u8(0x8000), 0x33);
+    assert_eq!(gb.get_peripherals().ppu.get_vram(0)[0x0000], 0x33);
+}
@@ -0,0 +1,95 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Get the generation of a tile in VRAM bank #0.
+fn generation_of(gb: &GameBoy, tile_index: usize) -> u32 {
+    gb.get_peripherals().ppu.get_sprite_image_generation(tile_index, 0)
+}
+
+
+#[test]
+fn test_write_increments_generation_of_tile() {
+    let mut gb = create_gameboy_dmg();
+
+    let gen_tile_1 = generation_of(&gb, 1);
+    let gen_tile_2 = generation_of(&gb, 2);
+
+    // each byte of the tile affects the same generation counter
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 16, 0x12);
+    assert_eq!(generation_of(&gb, 1), gen_tile_1 + 1);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 31, 0x34);
+    assert_eq!(generation_of(&gb, 1), gen_tile_1 + 2);
+
+    // the neighbouring tile is not affected
+    assert_eq!(generation_of(&gb, 2), gen_tile_2);
+}
+
+
+#[test]
+fn test_unchanged_value_keeps_generation() {
+    let mut gb = create_gameboy_dmg();
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 0x100, 0xaa);
+    let generation = generation_of(&gb, 0x10);
+
+    // writing the same value again does not change the tile's content
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 0x100, 0xaa);
+    assert_eq!(generation_of(&gb, 0x10), generation);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_VRAM_BEGIN + 0x100, 0x55);
+    assert_eq!(generation_of(&gb, 0x10), generation + 1);
+}
+
+
+#[test]
+fn test_blocked_write_keeps_generation() {
+    let mut gb = create_gameboy_dmg();
+    let generation = generation_of(&gb, 0);
+
+    // enable the LCD and run until the PPU is drawing the first visible line,
+    // where VRAM is not accessible
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+
+    while gb.get_peripherals().ppu.is_vram_accessible() {
+        gb.run_single_step();
+    }
+
+    gb.get_mmu_mut().cpu_write_u8(MEMORY_LOCATION_VRAM_BEGIN, 0xff);
+    assert_eq!(generation_of(&gb, 0), generation);
+}
+
+
+#[test]
+fn test_direct_vram_access_marks_all_tiles() {
+    let mut gb = create_gameboy_dmg();
+
+    let gen_first = generation_of(&gb, 0);
+    let gen_last  = generation_of(&gb, 511);
+
+    gb.get_peripherals_mut().ppu.get_vram_mut(0)[0] = 0x01;
+
+    assert_ne!(generation_of(&gb, 0),   gen_first);
+    assert_ne!(generation_of(&gb, 511), gen_last);
+}
@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2022-2023 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::ppu::{CPU_CYCLES_PER_FRAME, SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// LCDC value with LCD, window (0x9C00 tilemap), background and 0x8000 tile data enabled.
+const LCDC_WINDOW_ENABLED: u8 = 0b_1111_0001;
+
+
+/// Creates a new emulator instance, which displays a window filled with a tile, of which
+/// only the last column is set while the background is blank.
+fn create_gameboy_with_window(wx: u8, wy: u8) -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    let mmu = gb.cpu.get_mmu_mut();
+
+    // disable the LCD to get access to the video memory
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    // tile #1 contains only the last column set to color #3
+    for line in 0..8 {
+        mmu.write_u8(0x8010 + line * 2,     0x01);
+        mmu.write_u8(0x8010 + line * 2 + 1, 0x01);
+    }
+
+    // background tilemap at 0x9800 displays tile #0, window tilemap at 0x9C00 displays tile #1
+    for i in 0..0x400 {
+        mmu.write_u8(0x9800 + i, 0x00);
+        mmu.write_u8(0x9c00 + i, 0x01);
+    }
+
+    mmu.write_u8(MEMORY_LOCATION_PALETTE_BG, 0b_1110_0100);
+    mmu.write_u8(MEMORY_LOCATION_SCX, 0);
+    mmu.write_u8(MEMORY_LOCATION_SCY, 0);
+    mmu.write_u8(MEMORY_LOCATION_WX,  wx);
+    mmu.write_u8(MEMORY_LOCATION_WY,  wy);
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, LCDC_WINDOW_ENABLED);
+
+    // the first frame after enabling the LCD will not be drawn
+    for _ in 0..(2 * CPU_CYCLES_PER_FRAME / 4) {
+        gb.get_peripherals_mut().ppu.update(4);
+    }
+
+    gb
+}
+
+
+/// Checks whether a pixel displays the window tile's last column or not.
+fn is_window_column_pixel(gb: &GameBoy, x: u32, y: u32) -> bool {
+    let ppu   = &gb.get_peripherals().ppu;
+    let color = ppu.get_lcd().get_pixel(x, y);
+    let blank = ppu.get_dmg_display_palette().get_colors()[0];
+
+    color.to_u32() != blank.to_u32()
+}
+
+
+#[test]
+fn window_wx_0_starts_left_of_screen() {
+    let gb = create_gameboy_with_window(0, 0);
+
+    // with WX=0 the window begins 7 pixels left of the screen,
+    // so the last column of the first tile is on screen pixel 0
+    for y in 0..SCREEN_H {
+        for x in 0..SCREEN_W {
+            let expected = (x % 8) == 0;
+            assert_eq!(expected, is_window_column_pixel(&gb, x, y), "unexpected pixel at {x}/{y}");
+        }
+    }
+}
+
+
+#[test]
+fn window_wx_7_starts_at_screen_left() {
+    let gb = create_gameboy_with_window(7, 0);
+
+    for x in 0..SCREEN_W {
+        let expected = (x % 8) == 7;
+        assert_eq!(expected, is_window_column_pixel(&gb, x, 0), "unexpected pixel at {x}/0");
+    }
+}
+
+
+#[test]
+fn window_not_displayed_above_wy() {
+    let gb = create_gameboy_with_window(7, 16);
+
+    for y in 0..SCREEN_H {
+        let expected = y >= 16;
+        assert_eq!(expected, is_window_column_pixel(&gb, 7, y), "unexpected pixel at 7/{y}");
+    }
+}
@@ -0,0 +1,256 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::sync::{Arc, Mutex};
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
+use gemi_core::mmu::memory_bus::MemoryBusConnection;
+use gemi_core::printer::{decompress, GameBoyPrinter, Printout};
+use gemi_core::serial::{SerialDevice, SERIAL_TRANSFER_CYCLES_NORMAL};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// A packet sending the INIT command, as captured from a game.
+const PACKET_INIT: [u8; 10] = [0x88, 0x33, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00];
+
+/// A packet sending the STATUS command, as captured from a game.
+const PACKET_STATUS: [u8; 10] = [0x88, 0x33, 0x0f, 0x00, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x00];
+
+/// A packet sending the DATA command without any data, as sent by games to finish the image data.
+const PACKET_DATA_END: [u8; 10] = [0x88, 0x33, 0x04, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00];
+
+
+/// Creates a printer, which stores all printouts in the returned list.
+fn create_printer() -> (GameBoyPrinter, Arc<Mutex<Vec<Printout>>>) {
+    let printouts = Arc::new(Mutex::new(Vec::new()));
+    let target    = printouts.clone();
+
+    let printer = GameBoyPrinter::new(move |printout| {
+        target.lock().unwrap().push(printout);
+    });
+
+    (printer, printouts)
+}
+
+
+/// Builds a packet with the given command, compression flag and data, including its checksum.
+fn build_packet(command: u8, compressed: bool, data: &[u8]) -> Vec<u8> {
+    let length = data.len() as u16;
+    let mut packet = vec![0x88, 0x33, command, compressed as u8, length as u8, (length >> 8) as u8];
+    packet.extend_from_slice(data);
+
+    let checksum = packet[2..].iter().fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+    packet.push(checksum as u8);
+    packet.push((checksum >> 8) as u8);
+
+    // two trailing bytes to receive device ID and status
+    packet.push(0x00);
+    packet.push(0x00);
+
+    packet
+}
+
+
+/// Sends all bytes of a packet to the printer and returns the bytes received in exchange.
+fn send(printer: &mut GameBoyPrinter, packet: &[u8]) -> Vec<u8> {
+    packet.iter().map(|byte| printer.on_byte_exchanged(*byte)).collect()
+}
+
+
+/// Sends a packet to the printer and returns the device ID and status received.
+fn send_packet(printer: &mut GameBoyPrinter, packet: &[u8]) -> (u8, u8) {
+    let response = send(printer, packet);
+    let count    = response.len();
+
+    (response[count - 2], response[count - 1])
+}
+
+
+/// Creates a band of 2 rows with 20 tiles each, with all pixels of the given color.
+fn create_band(color: u8) -> Vec<u8> {
+    let low  = if (color & 0x01) != 0 { 0xff } else { 0x00 };
+    let high = if (color & 0x02) != 0 { 0xff } else { 0x00 };
+
+    [low, high].repeat(320)
+}
+
+
+#[test]
+fn test_init_response() {
+    let (mut printer, _) = create_printer();
+
+    let response = send(&mut printer, &PACKET_INIT);
+    assert_eq!(vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x81, 0x00], response);
+}
+
+
+#[test]
+fn test_status_reports_unprocessed_data() {
+    let (mut printer, _) = create_printer();
+
+    send_packet(&mut printer, &PACKET_INIT);
+    assert_eq!((0x81, 0x08), send_packet(&mut printer, &build_packet(0x04, false, &create_band(0))));
+    assert_eq!((0x81, 0x08), send_packet(&mut printer, &PACKET_STATUS));
+
+    // INIT clears the image buffer
+    send_packet(&mut printer, &PACKET_INIT);
+    assert_eq!((0x81, 0x00), send_packet(&mut printer, &PACKET_STATUS));
+}
+
+
+#[test]
+fn test_checksum_error() {
+    let (mut printer, _) = create_printer();
+
+    let mut packet = PACKET_INIT;
+    packet[6] = 0x02;
+
+    assert_eq!((0x81, 0x01), send_packet(&mut printer, &packet));
+    assert_eq!(0x01, printer.get_status());
+
+    // the error is cleared by the next valid packet
+    assert_eq!((0x81, 0x00), send_packet(&mut printer, &PACKET_STATUS));
+    assert_eq!(0x00, printer.get_status());
+}
+
+
+#[test]
+fn test_image_data_full() {
+    let (mut printer, _) = create_printer();
+
+    for _ in 0..9 {
+        send_packet(&mut printer, &build_packet(0x04, false, &create_band(0)));
+    }
+
+    assert_eq!((0x81, 0x0c), send_packet(&mut printer, &PACKET_STATUS));
+}
+
+
+#[test]
+fn test_decompress() {
+    // a literal run of 3 bytes, followed by a repeated run of 4 bytes
+    assert_eq!(
+        vec![0x01, 0x02, 0x03, 0xaa, 0xaa, 0xaa, 0xaa],
+        decompress(&[0x02, 0x01, 0x02, 0x03, 0x82, 0xaa])
+    );
+
+    assert_eq!(vec![0x00; 129], decompress(&[0xff, 0x00]));
+    assert_eq!(Vec::<u8>::new(), decompress(&[]));
+}
+
+
+#[test]
+fn test_print() {
+    let (mut printer, printouts) = create_printer();
+
+    send_packet(&mut printer, &PACKET_INIT);
+    send_packet(&mut printer, &build_packet(0x04, false, &create_band(3)));
+    send_packet(&mut printer, &build_packet(0x04, false, &create_band(1)));
+    send_packet(&mut printer, &PACKET_DATA_END);
+
+    // print one sheet with default palette
+    let (device_id, status) = send_packet(&mut printer, &build_packet(0x02, false, &[0x01, 0x13, 0xe4, 0x40]));
+    assert_eq!(0x81, device_id);
+    assert_eq!(0x02, status);
+
+    // the printing flag is reported once
+    assert_eq!((0x81, 0x00), send_packet(&mut printer, &PACKET_STATUS));
+
+    let printouts = printouts.lock().unwrap();
+    assert_eq!(1, printouts.len());
+
+    let printout = &printouts[0];
+    assert_eq!(160, printout.get_width());
+    assert_eq!(32, printout.get_height());
+    assert_eq!(32, printout.rows().count());
+    assert!(printout.rows().take(16).all(|row| row.iter().all(|shade| *shade == 3)));
+    assert!(printout.rows().skip(16).all(|row| row.iter().all(|shade| *shade == 1)));
+}
+
+
+#[test]
+fn test_print_compressed_with_palette() {
+    let (mut printer, printouts) = create_printer();
+
+    // a full band of color 3, compressed into a literal run of 2 bytes, 4 repeated runs of 128 bytes
+    // and a final repeated run of 126 bytes
+    let data = [0x01, 0xff, 0xff, 0xfe, 0xff, 0xfe, 0xff, 0xfe, 0xff, 0xfe, 0xff, 0xfc, 0xff];
+    assert_eq!(create_band(3), decompress(&data));
+
+    send_packet(&mut printer, &PACKET_INIT);
+    assert_eq!((0x81, 0x08), send_packet(&mut printer, &build_packet(0x04, true, &data)));
+    assert_eq!((0x81, 0x08), send_packet(&mut printer, &PACKET_STATUS));
+
+    // inverted palette, mapping color 3 to shade 0
+    send_packet(&mut printer, &build_packet(0x02, false, &[0x01, 0x00, 0x1b, 0x40]));
+
+    let printouts = printouts.lock().unwrap();
+    assert_eq!(1, printouts.len());
+    assert_eq!(16, printouts[0].get_height());
+    assert!(printouts[0].rows().all(|row| row.iter().all(|shade| *shade == 0)));
+}
+
+
+#[test]
+fn test_print_without_sheets() {
+    let (mut printer, printouts) = create_printer();
+
+    send_packet(&mut printer, &build_packet(0x04, false, &create_band(3)));
+    send_packet(&mut printer, &build_packet(0x02, false, &[0x00, 0x00, 0xe4, 0x40]));
+
+    // printing zero sheets just discards the image data
+    assert!(printouts.lock().unwrap().is_empty());
+    assert_eq!((0x81, 0x00), send_packet(&mut printer, &PACKET_STATUS));
+}
+
+
+#[test]
+fn test_print_via_serial_port() {
+    let (printer, printouts) = create_printer();
+
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+    gb.get_peripherals_mut().serial.connect_device(Box::new(printer));
+
+    let mut packets = Vec::new();
+    packets.extend(PACKET_INIT);
+    packets.extend(build_packet(0x04, false, &create_band(2)));
+    packets.extend(build_packet(0x02, false, &[0x01, 0x00, 0xe4, 0x40]));
+
+    let mut received = Vec::new();
+
+    for byte in packets {
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, byte);
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x81);
+        gb.get_peripherals_mut().serial.update(SERIAL_TRANSFER_CYCLES_NORMAL);
+
+        let signals = gb.get_peripherals_mut().serial.take_signals();
+        assert!(signals.interrupts.contains(Interrupt::Serial));
+
+        received.push(gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+    }
+
+    // each packet is answered with the device ID
+    assert_eq!(3, received.iter().filter(|byte| **byte == 0x81).count());
+
+    let printouts = printouts.lock().unwrap();
+    assert_eq!(1, printouts.len());
+    assert_eq!(16, printouts[0].get_height());
+    assert!(printouts[0].rows().all(|row| row.iter().all(|shade| *shade == 2)));
+}
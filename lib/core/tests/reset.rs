@@ -0,0 +1,329 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat};
+use gemi_core::apu::channels::channel::ChannelType;
+use gemi_core::boot_rom::{BootRom, BOOT_ROM_SIZE_DMG};
+use gemi_core::cartridge::*;
+use gemi_core::cpu::cpu::{RegisterR8, CPU_CLOCK_SPEED};
+use gemi_core::gameboy::{GameBoy, ResetKind};
+use gemi_core::mmu::locations::*;
+
+mod common;
+use common::create_gameboy_color;
+
+
+/// Cartridge type MBC1+RAM.
+const ROM_TYPE_MBC1_RAM: u8 = 0x02;
+
+/// Cartridge type MBC1+RAM+BATTERY.
+const ROM_TYPE_MBC1_RAM_BATTERY: u8 = 0x03;
+
+/// ROM size type for 64kiB of ROM, which are 4 banks.
+const ROM_SIZE_64K: u8 = 0x01;
+
+/// RAM size type for 8kiB of cartridge RAM.
+const RAM_SIZE_8K: u8 = 0x02;
+
+/// An address within WRAM.
+const WRAM_ADDRESS: u16 = 0xc123;
+
+/// An address within the cartridge RAM.
+const CARTRIDGE_RAM_ADDRESS: u16 = 0xa123;
+
+
+/// Creates a cartridge of the given type, where each ROM bank starts with its bank number
+/// and the entry point contains an endless loop.
+fn create_cartridge(rom_type: u8) -> Cartridge {
+    let mut rom = vec![0x00; 4 * 0x4000];
+    rom[ROM_OFFSET_ROM_TYPE] = rom_type;
+    rom[ROM_OFFSET_ROM_SIZE] = ROM_SIZE_64K;
+    rom[ROM_OFFSET_RAM_SIZE] = RAM_SIZE_8K;
+
+    // JR -2
+    rom[ROM_OFFSET_ENTRY_POINT]     = 0x18;
+    rom[ROM_OFFSET_ENTRY_POINT + 1] = 0xfe;
+
+    for bank in 1..4 {
+        rom[bank * 0x4000] = bank as u8;
+    }
+
+    Cartridge::load_from_bytes(rom, None).unwrap()
+}
+
+
+/// Creates a new emulator instance with a cartridge of the given type.
+/// See [create_cartridge]
+fn create_gameboy(rom_type: u8) -> GameBoy {
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_cartridge(create_cartridge(rom_type));
+
+    let mut gb = builder.finish().unwrap();
+    gb.initialize();
+    gb
+}
+
+
+/// Creates a new emulator instance with a boot ROM running an endless loop,
+/// so the register values are not set up by the emulator after a reset.
+fn create_gameboy_with_boot_rom() -> GameBoy {
+    let mut boot_rom_data = vec![0x00; BOOT_ROM_SIZE_DMG];
+
+    // JR -2
+    boot_rom_data[0] = 0x18;
+    boot_rom_data[1] = 0xfe;
+
+    let mut builder = GameBoy::build();
+    builder.set_allow_invalid_header(true);
+    builder.set_boot_rom(BootRom::load_from_bytes(&boot_rom_data).unwrap());
+    builder.set_cartridge(create_cartridge(ROM_TYPE_MBC1_RAM));
+
+    let mut gb = builder.finish().unwrap();
+    gb.initialize();
+    gb
+}
+
+
+/// Runs the device for a while and changes its state afterwards.
+fn modify_state(gb: &mut GameBoy) {
+    gb.run_frame();
+
+    // WRAM
+    gb.get_mmu_mut().write_u8(WRAM_ADDRESS, 0x42);
+
+    // cartridge RAM
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+    gb.get_mmu_mut().write_u8(CARTRIDGE_RAM_ADDRESS, 0x24);
+
+    // select ROM bank 3
+    gb.get_mmu_mut().write_u8(0x2000, 0x03);
+
+    // CPU registers
+    gb.cpu.set_r8(RegisterR8::A, 0x99);
+    gb.cpu.set_r8(RegisterR8::B, 0x88);
+    gb.cpu.set_stack_pointer(0xd000);
+    gb.cpu.set_instruction_pointer(0x0101);
+}
+
+
+/// Reads a byte from the cartridge RAM, enabling it before.
+fn read_cartridge_ram(gb: &mut GameBoy) -> u8 {
+    gb.get_mmu_mut().write_u8(0x0000, 0x0a);
+    gb.get_mmu().read_u8(CARTRIDGE_RAM_ADDRESS)
+}
+
+
+/// Checks the CPU and the memory bank controller are in their initial state.
+fn check_initial_cpu_state(gb: &GameBoy, initial: &GameBoy) {
+    assert_eq!(0x0100, gb.cpu.get_instruction_pointer());
+    assert_eq!(initial.cpu.get_stack_pointer(), gb.cpu.get_stack_pointer());
+    assert_eq!(initial.cpu.get_r8(RegisterR8::A), gb.cpu.get_r8(RegisterR8::A));
+    assert_eq!(initial.cpu.get_r8(RegisterR8::B), gb.cpu.get_r8(RegisterR8::B));
+    assert!(!gb.cpu.is_interrupts_enabled());
+
+    // ROM bank 1 is selected again
+    assert_eq!(0x01, gb.get_mmu().read_u8(0x4000));
+
+    // cartridge RAM is disabled again
+    assert_eq!(0xff, gb.get_mmu().read_u8(CARTRIDGE_RAM_ADDRESS));
+}
+
+
+#[test]
+fn test_hard_reset() {
+    let initial = create_gameboy(ROM_TYPE_MBC1_RAM);
+    let mut gb  = create_gameboy(ROM_TYPE_MBC1_RAM);
+    modify_state(&mut gb);
+
+    assert_eq!(0x42, gb.get_mmu().read_u8(WRAM_ADDRESS));
+    assert_eq!(0x03, gb.get_mmu().read_u8(0x4000));
+
+    gb.reset(ResetKind::Hard);
+
+    check_initial_cpu_state(&gb, &initial);
+
+    // WRAM returned to its initial value
+    assert_eq!(initial.get_mmu().read_u8(WRAM_ADDRESS), gb.get_mmu().read_u8(WRAM_ADDRESS));
+
+    // without a battery, cartridge RAM is lost
+    assert_eq!(0x00, read_cartridge_ram(&mut gb));
+
+    // the device continues running
+    gb.run_frame();
+    assert!(gb.cpu.get_instruction_pointer() < 0x0102);
+}
+
+
+#[test]
+fn test_hard_reset_clears_battery_ram() {
+    let mut gb = create_gameboy(ROM_TYPE_MBC1_RAM_BATTERY);
+    modify_state(&mut gb);
+
+    gb.reset(ResetKind::Hard);
+
+    assert_eq!(0x00, read_cartridge_ram(&mut gb));
+}
+
+
+#[test]
+fn test_soft_reset_keeps_battery_ram() {
+    let mut gb = create_gameboy(ROM_TYPE_MBC1_RAM_BATTERY);
+    modify_state(&mut gb);
+
+    gb.reset(ResetKind::Soft);
+
+    assert_eq!(0x24, read_cartridge_ram(&mut gb));
+}
+
+
+#[test]
+fn test_soft_reset() {
+    let initial = create_gameboy(ROM_TYPE_MBC1_RAM);
+    let mut gb  = create_gameboy(ROM_TYPE_MBC1_RAM);
+    modify_state(&mut gb);
+
+    gb.reset(ResetKind::Soft);
+
+    check_initial_cpu_state(&gb, &initial);
+
+    // memory content is kept
+    assert_eq!(0x42, gb.get_mmu().read_u8(WRAM_ADDRESS));
+    assert_eq!(0x24, read_cartridge_ram(&mut gb));
+
+    gb.run_frame();
+    assert!(gb.cpu.get_instruction_pointer() < 0x0102);
+}
+
+
+#[test]
+fn test_reset_keeps_frontend_settings() {
+    let mut gb   = create_gameboy(ROM_TYPE_MBC1_RAM);
+    let receiver = gb.get_peripherals_mut().apu.get_audio_output().open_channel(
+        AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::Native }
+    ).unwrap();
+
+    gb.get_peripherals_mut().serial.enable_output_queue(true);
+
+    for kind in [ResetKind::Soft, ResetKind::Hard] {
+        gb.reset(kind);
+
+        while receiver.try_recv().is_ok() {}
+
+        for _ in 0..10 {
+            gb.run_frame();
+        }
+
+        // the audio channel is still connected
+        assert!(receiver.try_recv().is_ok());
+        assert!(gb.get_peripherals().serial.is_output_queue_enabled());
+        assert!(gb.get_peripherals().mem.get_cartridge().is_some());
+    }
+}
+
+
+#[test]
+fn test_reset_restarts_clock_and_peripherals() {
+    let initial = create_gameboy(ROM_TYPE_MBC1_RAM);
+
+    for kind in [ResetKind::Soft, ResetKind::Hard] {
+        let mut gb = create_gameboy(ROM_TYPE_MBC1_RAM);
+        modify_state(&mut gb);
+
+        // trigger a channel, which stays active after the frame was completed
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR22, 0xf0);
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_APU_NR24, 0x80);
+        gb.run_frame();
+
+        assert!(gb.get_total_cycles_processed() > 0);
+        assert!(gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+        gb.reset(kind);
+
+        assert_eq!(0, gb.get_total_cycles_processed());
+        assert_eq!(0, gb.get_mmu().read_u8(MEMORY_LOCATION_LY));
+        assert!(!gb.get_peripherals().apu.is_channel_enabled(ChannelType::Ch2Pulse2));
+
+        // the timer starts with the same values like after power on
+        assert_eq!(initial.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_DIV), gb.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_DIV));
+        assert_eq!(initial.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_TIMA), gb.get_mmu().read_u8(MEMORY_LOCATION_REGISTER_TIMA));
+    }
+}
+
+
+#[test]
+fn test_soft_reset_aborts_serial_transfer() {
+    let initial = create_gameboy_with_boot_rom();
+    let mut gb  = create_gameboy_with_boot_rom();
+    gb.get_peripherals_mut().serial.enable_output_queue(true);
+
+    // start a transfer using the internal clock and run until half of it was shifted out
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, 0x42);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x81);
+
+    let half_transfer = gb.get_peripherals().serial.get_transfer_cycles() / 2;
+    while gb.get_total_cycles_processed() < half_transfer {
+        gb.run_single_step();
+    }
+
+    assert_ne!(0x42, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+
+    gb.reset(ResetKind::Soft);
+
+    // the registers are back to their initial values
+    assert_eq!(
+        initial.get_mmu().read_u8(MEMORY_LOCATION_SB),
+        gb.get_mmu().read_u8(MEMORY_LOCATION_SB)
+    );
+    assert_eq!(
+        initial.get_mmu().read_u8(MEMORY_LOCATION_SC),
+        gb.get_mmu().read_u8(MEMORY_LOCATION_SC)
+    );
+
+    // the aborted transfer is not completed afterwards
+    for _ in 0..10 {
+        gb.run_frame();
+    }
+
+    assert_eq!(0, gb.get_peripherals().serial.get_transferred_bytes());
+    assert!(gb.get_peripherals().serial.get_output().is_empty());
+    assert!(gb.get_peripherals().serial.is_output_queue_enabled());
+}
+
+
+#[test]
+fn test_reset_returns_into_normal_speed() {
+    for kind in [ResetKind::Soft, ResetKind::Hard] {
+        let mut gb = create_gameboy_color();
+
+        // STOP; STOP
+        gb.get_mmu_mut().write_u8(WRAM_ADDRESS,     0x10);
+        gb.get_mmu_mut().write_u8(WRAM_ADDRESS + 1, 0x10);
+        gb.cpu.set_instruction_pointer(WRAM_ADDRESS);
+
+        // switch into double speed and request another switch
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+        gb.run_single_step();
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_KEY1, 0x01);
+        assert_eq!(CPU_CLOCK_SPEED * 2, gb.effective_clock_speed());
+        assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1));
+
+        gb.reset(kind);
+
+        assert_eq!(CPU_CLOCK_SPEED, gb.effective_clock_speed());
+        assert_eq!(0x7e, gb.get_mmu().read_u8(MEMORY_LOCATION_KEY1));
+    }
+}
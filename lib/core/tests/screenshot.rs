@@ -0,0 +1,75 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(feature = "png")]
+
+use std::path::PathBuf;
+
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::MEMORY_LOCATION_LCD_CONTROL;
+use gemi_core::ppu::ppu::{SCREEN_H, SCREEN_W};
+
+mod common;
+use common::create_gameboy_color;
+
+
+/// Creates a new emulator instance with the LCD turned off, so the display shows a white screen.
+fn create_gameboy_with_lcd_off() -> GameBoy {
+    let mut gb = create_gameboy_color();
+
+    // turn the LCD on and off again to clear the screen
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x80);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    gb.run_frame();
+
+    gb
+}
+
+
+/// Get a unique path within the temporary directory to store a screenshot.
+fn get_temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gemi_{}_{}.png", name, std::process::id()))
+}
+
+
+#[test]
+fn test_screenshot_of_blank_screen() {
+    let gb   = create_gameboy_with_lcd_off();
+    let path = get_temp_file("screenshot_blank");
+
+    gb.save_screenshot(&path).unwrap();
+
+    let image = image::open(&path).unwrap().to_rgba8();
+    _ = std::fs::remove_file(&path);
+
+    assert_eq!(SCREEN_W, image.width());
+    assert_eq!(SCREEN_H, image.height());
+
+    for pixel in image.pixels() {
+        assert_eq!([0xff, 0xff, 0xff, 0xff], pixel.0);
+    }
+}
+
+
+#[test]
+fn test_screenshot_matches_lcd_content() {
+    let gb    = create_gameboy_with_lcd_off();
+    let image = gb.get_peripherals().ppu.get_lcd().to_rgba_image();
+
+    assert_eq!(gb.get_peripherals().ppu.get_lcd().get_pixels_as_slice(), image.as_raw().as_slice());
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::gameboy::GameBoy;
+use gemi_core::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
+use gemi_core::mmu::memory_bus::MemoryBusConnection;
+use gemi_core::serial::SERIAL_TRANSFER_CYCLES_NORMAL;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Creates a new emulator instance.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+    gb.initialize();
+
+    gb
+}
+
+
+/// Runs a transfer sending the given byte with the given SC value and returns the byte received,
+/// or `None` if the transfer did not complete with the duration of a transfer using the internal clock.
+fn run_transfer(gb: &mut GameBoy, byte: u8, sc: u8) -> Option<u8> {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, byte);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, sc);
+    gb.get_peripherals_mut().serial.update(SERIAL_TRANSFER_CYCLES_NORMAL);
+
+    let signals = gb.get_peripherals_mut().serial.take_signals();
+
+    if signals.interrupts.contains(Interrupt::Serial) {
+        assert_eq!(0, gb.get_mmu().read_u8(MEMORY_LOCATION_SC) & 0x80);
+        Some(gb.get_mmu().read_u8(MEMORY_LOCATION_SB))
+    }
+    else {
+        None
+    }
+}
+
+
+#[test]
+fn test_receive_queued_byte() {
+    let mut gb = create_gameboy();
+
+    gb.get_peripherals_mut().serial.queue_incoming(&[0x5a]);
+    assert_eq!(1, gb.get_peripherals().serial.get_incoming_queue_len());
+
+    assert_eq!(Some(0x5a), run_transfer(&mut gb, 0x42, 0x81));
+    assert_eq!(0, gb.get_peripherals().serial.get_incoming_queue_len());
+
+    // with an empty queue, 0xff is received
+    assert_eq!(Some(0xff), run_transfer(&mut gb, 0x42, 0x81));
+}
+
+
+#[test]
+fn test_receive_queued_bytes_in_order() {
+    let mut gb = create_gameboy();
+
+    gb.get_peripherals_mut().serial.queue_incoming(&[0x01, 0x02]);
+    gb.get_peripherals_mut().serial.queue_incoming(&[0x03]);
+
+    assert_eq!(Some(0x01), run_transfer(&mut gb, 0x00, 0x81));
+    assert_eq!(Some(0x02), run_transfer(&mut gb, 0x00, 0x81));
+    assert_eq!(Some(0x03), run_transfer(&mut gb, 0x00, 0x81));
+}
+
+
+#[test]
+fn test_queued_byte_clocks_external_transfer() {
+    let mut gb = create_gameboy();
+    gb.get_peripherals_mut().serial.enable_output_queue(true);
+
+    // without any queued data, the transfer is waiting for the external clock
+    assert_eq!(None, run_transfer(&mut gb, 0x42, 0x80));
+
+    gb.get_peripherals_mut().serial.queue_incoming(&[0x5a]);
+    gb.get_peripherals_mut().serial.update(1);
+
+    let signals = gb.get_peripherals_mut().serial.take_signals();
+    assert!(signals.interrupts.contains(Interrupt::Serial));
+    assert_eq!(0x5a, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+    assert_eq!(vec![0x42], gb.get_peripherals_mut().serial.take_output());
+}
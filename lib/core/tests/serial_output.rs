@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::debug::DebugEvent;
+use gemi_core::gameboy::GameBoy;
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// The address where the test program will be stored.
+const PROGRAM_ADDRESS: u16 = 0xc000;
+
+/// An opcode without any functionality assigned.
+const OPCODE_ILLEGAL: u8 = 0xd3;
+
+
+/// Creates a new emulator instance, which sends a text via the serial port
+/// and afterward continues with the given instructions.
+fn create_gameboy(text: &str, tail: &[u8]) -> GameBoy {
+    let mut program = Vec::new();
+
+    for c in text.bytes() {
+        program.extend_from_slice(&[
+            0x3e, c,                    // LD A, c
+            0xe0, 0x01,                 // LDH (SB), A
+            0x3e, 0x81,                 // LD A, 0x81
+            0xe0, 0x02,                 // LDH (SC), A
+            0xf0, 0x02,                 // LDH A, (SC)
+            0xcb, 0x7f,                 // BIT 7, A
+            0x20, 0xfa,                 // JR NZ, -6
+        ]);
+    }
+
+    program.extend_from_slice(tail);
+
+    let mut gb = create_gameboy_dmg();
+
+    gb.initialize();
+
+    for (offset, byte) in program.iter().enumerate() {
+        gb.get_mmu_mut().write_u8(PROGRAM_ADDRESS + offset as u16, *byte);
+    }
+
+    gb.cpu.set_instruction_pointer(PROGRAM_ADDRESS);
+    gb.cpu.disable_interrupts();
+
+    gb
+}
+
+
+/// The address of the first instruction following the serial output of a text.
+fn get_tail_address(text: &str) -> u16 {
+    PROGRAM_ADDRESS + 14 * text.len() as u16
+}
+
+
+#[test]
+fn test_run_until_serial_output_passed() {
+    let mut gb = create_gameboy("Test Passed", &[0x18, 0xfe]);
+
+    let output = gb.run_until_serial_output("Passed", 10_000_000);
+    assert_eq!(Ok("Test Passed".to_string()), output);
+}
+
+
+#[test]
+fn test_run_until_serial_output_timeout() {
+    let mut gb = create_gameboy("Failed", &[0x18, 0xfe]);
+
+    let error = gb.run_until_serial_output("Passed", 1_000_000).unwrap_err();
+    assert_eq!("Failed", error.output_so_far);
+    assert_eq!(None, error.invalid_opcode);
+    assert!(error.cycles_elapsed >= 1_000_000);
+}
+
+
+#[test]
+fn test_run_until_serial_output_stops_on_invalid_opcode() {
+    let mut gb = create_gameboy("Running", &[OPCODE_ILLEGAL]);
+
+    let error = gb.run_until_serial_output("Passed", 10_000_000).unwrap_err();
+    assert_eq!("Running", error.output_so_far);
+    assert_eq!(Some(get_tail_address("Running")), error.invalid_opcode);
+    assert!(error.cycles_elapsed < 10_000_000);
+}
+
+
+#[test]
+fn test_invalid_opcode_locks_up_cpu() {
+    let mut gb = create_gameboy("", &[OPCODE_ILLEGAL]);
+
+    for _ in 0..3 {
+        let results = gb.run_frame();
+        assert!(results.events.contains(DebugEvent::InvalidOpcode));
+        assert_eq!(PROGRAM_ADDRESS, gb.cpu.get_instruction_pointer());
+    }
+}
@@ -0,0 +1,200 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::gameboy::{Clock, DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::{MEMORY_LOCATION_SB, MEMORY_LOCATION_SC};
+use gemi_core::mmu::memory_bus::MemoryBusConnection;
+use gemi_core::serial::*;
+
+mod common;
+use common::create_gameboy_for;
+
+
+/// The maximum number of cycles to wait for a transfer to be completed.
+const MAX_TRANSFER_CYCLES: Clock = 100_000;
+
+
+/// Creates a new emulator instance of the given device and emulation type.
+fn create_gameboy(device: DeviceType, emulation: EmulationType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, emulation);
+
+    gb.initialize();
+
+    gb
+}
+
+
+/// Starts a transfer with the given SC value and returns the number of cycles until it's completed,
+/// or `None` if the transfer did not complete within [MAX_TRANSFER_CYCLES].
+fn measure_transfer(gb: &mut GameBoy, sc: u8) -> Option<Clock> {
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, 0x42);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, sc);
+
+    for cycles in 1 ..= MAX_TRANSFER_CYCLES {
+        gb.get_peripherals_mut().serial.update(1);
+
+        if (gb.get_mmu().read_u8(MEMORY_LOCATION_SC) & 0x80) == 0 {
+            return Some(cycles);
+        }
+    }
+
+    None
+}
+
+
+#[test]
+fn test_transfer_cycles_dmg() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_NORMAL), measure_transfer(&mut gb, 0x81));
+
+    // the fast clock is not available on DMG
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_NORMAL), measure_transfer(&mut gb, 0x83));
+}
+
+
+#[test]
+fn test_transfer_cycles_gbc() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_NORMAL), measure_transfer(&mut gb, 0x81));
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_FAST),   measure_transfer(&mut gb, 0x83));
+}
+
+
+#[test]
+fn test_transfer_cycles_gbc_in_dmg_mode() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::DMG);
+
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_NORMAL), measure_transfer(&mut gb, 0x83));
+}
+
+
+#[test]
+fn test_transfer_external_clock() {
+    let mut gb = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+
+    // without any device providing the clock, the transfer does not complete
+    assert_eq!(None, measure_transfer(&mut gb, 0x80));
+    assert_eq!(None, measure_transfer(&mut gb, 0x82));
+}
+
+
+#[test]
+fn test_transfer_restarts_with_full_duration() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x81);
+    gb.get_peripherals_mut().serial.update(SERIAL_TRANSFER_CYCLES_NORMAL - 1);
+
+    // restarting the transfer before being completed resets its duration
+    assert_eq!(Some(SERIAL_TRANSFER_CYCLES_NORMAL), measure_transfer(&mut gb, 0x81));
+}
+
+
+#[test]
+fn test_sc_read_write() {
+    let mut dmg = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+    assert_eq!(0x7e, dmg.get_mmu().read_u8(MEMORY_LOCATION_SC));
+
+    dmg.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x01);
+    assert_eq!(0x7f, dmg.get_mmu().read_u8(MEMORY_LOCATION_SC));
+
+    dmg.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x00);
+    assert_eq!(0x7e, dmg.get_mmu().read_u8(MEMORY_LOCATION_SC));
+
+    let mut gbc = create_gameboy(DeviceType::GameBoyColor, EmulationType::GBC);
+    assert_eq!(0x7f, gbc.get_mmu().read_u8(MEMORY_LOCATION_SC));
+
+    gbc.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x01);
+    assert_eq!(0x7d, gbc.get_mmu().read_u8(MEMORY_LOCATION_SC));
+
+    gbc.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x02);
+    assert_eq!(0x7e, gbc.get_mmu().read_u8(MEMORY_LOCATION_SC));
+}
+
+
+#[test]
+fn test_documented_cycle_counts() {
+    assert_eq!(512,  SERIAL_BIT_CYCLES_NORMAL);
+    assert_eq!(16,   SERIAL_BIT_CYCLES_FAST);
+    assert_eq!(4096, SERIAL_TRANSFER_CYCLES_NORMAL);
+    assert_eq!(128,  SERIAL_TRANSFER_CYCLES_FAST);
+}
+
+
+#[test]
+fn test_bits_shifted_per_clock_tick() {
+    for (emulation, sc, bit_cycles) in [
+        (EmulationType::DMG, 0x81, SERIAL_BIT_CYCLES_NORMAL),
+        (EmulationType::GBC, 0x81, SERIAL_BIT_CYCLES_NORMAL),
+        (EmulationType::GBC, 0x83, SERIAL_BIT_CYCLES_FAST),
+    ] {
+        let mut gb = create_gameboy(DeviceType::GameBoyColor, emulation);
+
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, 0b_0100_0010);
+        gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, sc);
+
+        // the bits sent are shifted out, while 1 is received on each bit
+        let mut expected = 0b_0100_0010u8;
+
+        for _ in 0..8 {
+            gb.get_peripherals_mut().serial.update(bit_cycles - 1);
+            assert_eq!(expected, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+
+            gb.get_peripherals_mut().serial.update(1);
+            expected = (expected << 1) | 0x01;
+            assert_eq!(expected, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+        }
+
+        assert_eq!(0xff, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+    }
+}
+
+
+#[test]
+fn test_interrupt_with_last_bit() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x81);
+    gb.get_peripherals_mut().serial.update(SERIAL_TRANSFER_CYCLES_NORMAL - 1);
+
+    let signals = gb.get_peripherals_mut().serial.take_signals();
+    assert!(!signals.interrupts.contains(Interrupt::Serial));
+    assert_eq!(0x81, gb.get_mmu().read_u8(MEMORY_LOCATION_SC) & 0x81);
+
+    gb.get_peripherals_mut().serial.update(1);
+
+    let signals = gb.get_peripherals_mut().serial.take_signals();
+    assert!(signals.interrupts.contains(Interrupt::Serial));
+    assert_eq!(0x01, gb.get_mmu().read_u8(MEMORY_LOCATION_SC) & 0x81);
+}
+
+
+#[test]
+fn test_no_transfer_without_start_bit() {
+    let mut gb = create_gameboy(DeviceType::GameBoyDmg, EmulationType::DMG);
+
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SB, 0x42);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_SC, 0x01);
+    gb.get_peripherals_mut().serial.update(SERIAL_TRANSFER_CYCLES_NORMAL * 4);
+
+    let signals = gb.get_peripherals_mut().serial.take_signals();
+    assert!(!signals.interrupts.contains(Interrupt::Serial));
+    assert_eq!(0x42, gb.get_mmu().read_u8(MEMORY_LOCATION_SB));
+}
@@ -0,0 +1,207 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::gameboy::{DeviceType, EmulationType, GameBoy};
+use gemi_core::mmu::locations::*;
+use gemi_core::ppu::graphic_data::Color;
+use gemi_core::sgb::{SgbState, SgbTransfer};
+
+mod common;
+use common::{create_gameboy_for, start_idle_loop};
+
+
+/// Command code of CHR_TRN.
+const CHR_TRN: u8 = 0x13;
+
+/// Command code of PCT_TRN.
+const PCT_TRN: u8 = 0x14;
+
+/// The size of a single VRAM transfer.
+const TRANSFER_SIZE: usize = 4096;
+
+/// Pure red as RGB555 value.
+const COLOR_RED: u16 = 0x001f;
+
+
+/// Creates a new emulator instance of the given device type,
+/// running an endless loop in WRAM.
+fn create_gameboy(device: DeviceType) -> GameBoy {
+    let mut gb = create_gameboy_for(device, EmulationType::DMG);
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+/// Get the SuperGameBoy state of an emulator instance.
+fn sgb(gb: &GameBoy) -> &SgbState {
+    gb.get_peripherals().sgb.as_ref().unwrap()
+}
+
+
+/// Sends a single packet via JOYP, including the reset pulse and the stop bit.
+fn send_packet(gb: &mut GameBoy, packet: &[u8; 16]) {
+    let mmu = gb.get_mmu_mut();
+
+    // reset pulse
+    mmu.write_u8(MEMORY_LOCATION_JOYP, 0x00);
+    mmu.write_u8(MEMORY_LOCATION_JOYP, 0x30);
+
+    for byte in packet {
+        for bit in 0..8 {
+            let value = if (byte >> bit) & 1 != 0 { 0x10 } else { 0x20 };
+            mmu.write_u8(MEMORY_LOCATION_JOYP, value);
+            mmu.write_u8(MEMORY_LOCATION_JOYP, 0x30);
+        }
+    }
+
+    // stop bit
+    mmu.write_u8(MEMORY_LOCATION_JOYP, 0x20);
+    mmu.write_u8(MEMORY_LOCATION_JOYP, 0x30);
+}
+
+
+/// Sends a command with a single packet.
+fn send_command(gb: &mut GameBoy, command: u8, parameter: u8) {
+    let mut packet = [0u8; 16];
+    packet[0] = (command << 3) | 1;
+    packet[1] = parameter;
+
+    send_packet(gb, &packet);
+}
+
+
+/// Displays the given data in the background, where it can be picked up by a VRAM transfer.
+/// The tilemap will show tiles 0 to 255 in rows of 20 tiles, which contain the data.
+fn display_transfer_data(gb: &mut GameBoy, data: &[u8]) {
+    let mmu = gb.get_mmu_mut();
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x00);
+
+    for (offset, byte) in data.iter().enumerate() {
+        mmu.write_u8(0x8000 + offset as u16, *byte);
+    }
+
+    for tile in 0..256u16 {
+        let address = 0x9800 + (tile / 20) * 32 + (tile % 20);
+        mmu.write_u8(address, tile as u8);
+    }
+
+    // LCD and background on, tiles at 0x8000, tilemap at 0x9800
+    mmu.write_u8(MEMORY_LOCATION_LCD_CONTROL, 0x91);
+}
+
+
+/// Runs the emulator until the PPU completed two frames.
+fn run_frames(gb: &mut GameBoy) {
+    gb.run_frame();
+    gb.run_frame();
+}
+
+
+#[test]
+fn test_sgb_only_on_super_gameboy() {
+    assert!(create_gameboy(DeviceType::GameBoyDmg).get_peripherals().sgb.is_none());
+    assert!(create_gameboy(DeviceType::SuperGameBoy).get_peripherals().sgb.is_some());
+    assert!(create_gameboy(DeviceType::SuperGameBoy2).get_peripherals().sgb.is_some());
+}
+
+
+#[test]
+fn test_decode_transfer_commands() {
+    let mut gb = create_gameboy(DeviceType::SuperGameBoy);
+    assert_eq!(None, sgb(&gb).get_pending_transfer());
+
+    send_command(&mut gb, CHR_TRN, 0x01);
+    assert_eq!(Some(SgbTransfer::BorderTiles { upper_half: true }), sgb(&gb).get_pending_transfer());
+
+    send_command(&mut gb, CHR_TRN, 0x00);
+    assert_eq!(Some(SgbTransfer::BorderTiles { upper_half: false }), sgb(&gb).get_pending_transfer());
+
+    send_command(&mut gb, PCT_TRN, 0x00);
+    assert_eq!(Some(SgbTransfer::BorderTilemap), sgb(&gb).get_pending_transfer());
+}
+
+
+#[test]
+fn test_incomplete_packet_ignored() {
+    let mut gb = create_gameboy(DeviceType::SuperGameBoy);
+
+    // a packet interrupted by a reset pulse is discarded
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x00);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x30);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x10);
+    gb.get_mmu_mut().write_u8(MEMORY_LOCATION_JOYP, 0x30);
+
+    send_command(&mut gb, PCT_TRN, 0x00);
+    assert_eq!(Some(SgbTransfer::BorderTilemap), sgb(&gb).get_pending_transfer());
+}
+
+
+#[test]
+fn test_transfer_border_tiles() {
+    let mut gb = create_gameboy(DeviceType::SuperGameBoy);
+    let data   = (0..TRANSFER_SIZE).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+
+    display_transfer_data(&mut gb, &data);
+    send_command(&mut gb, CHR_TRN, 0x01);
+    run_frames(&mut gb);
+
+    assert_eq!(None, sgb(&gb).get_pending_transfer());
+    assert_eq!(1, sgb(&gb).get_border_generation());
+
+    // the border will be available after receiving the tilemap
+    assert!(sgb(&gb).get_border().is_none());
+
+    send_command(&mut gb, PCT_TRN, 0x00);
+    run_frames(&mut gb);
+
+    let border = sgb(&gb).get_border().unwrap();
+    assert_eq!(&data[..], &border.as_slice()[TRANSFER_SIZE .. 2 * TRANSFER_SIZE]);
+}
+
+
+#[test]
+fn test_render_border() {
+    let mut gb = create_gameboy(DeviceType::SuperGameBoy);
+
+    // tile #0 with its first pixel set to color #1
+    let mut tiles = vec![0x00; TRANSFER_SIZE];
+    tiles[0] = 0x80;
+
+    display_transfer_data(&mut gb, &tiles);
+    send_command(&mut gb, CHR_TRN, 0x00);
+    run_frames(&mut gb);
+
+    // all tilemap entries point to tile #0 with palette #4, with color #1 being red
+    let mut tilemap = vec![0x00; TRANSFER_SIZE];
+    tilemap[0x802] = (COLOR_RED & 0xff) as u8;
+    tilemap[0x803] = (COLOR_RED >> 8) as u8;
+
+    display_transfer_data(&mut gb, &tilemap);
+    send_command(&mut gb, PCT_TRN, 0x00);
+    run_frames(&mut gb);
+
+    let border = sgb(&gb).get_border().unwrap();
+    let red    = border.get_pixel(8, 16).unwrap();
+    assert_eq!((0xff, 0x00, 0x00), (red.r, red.g, red.b));
+    assert!(border.get_pixel(9, 16).is_none());
+
+    let pixels = border.render(Color::white());
+    assert_eq!(256 * 224, pixels.len());
+    assert_eq!(0xff, pixels[1].g);
+}
@@ -0,0 +1,97 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+#![cfg(feature = "snapshots")]
+
+use gemi_core::apu::audio_output::{AudioOutputSpec, SampleFormat};
+use gemi_core::snapshots::{Snapshot, SnapshotError, SNAPSHOT_VERSION};
+
+mod common;
+use common::create_gameboy_dmg;
+
+
+/// Offset of the version number within the snapshot header.
+const VERSION_OFFSET: usize = 4;
+
+
+/// Creates a copy of a snapshot with a different version number in its header.
+fn with_version(snapshot: &Snapshot, version: u32) -> Snapshot {
+    let mut data = snapshot.get_data().to_vec();
+    data[VERSION_OFFSET .. VERSION_OFFSET + 4].copy_from_slice(&version.to_le_bytes());
+
+    Snapshot::from_data(data)
+}
+
+
+#[test]
+fn test_snapshot_roundtrip() {
+    let gb       = create_gameboy_dmg();
+    let snapshot = Snapshot::create_from(&gb).unwrap();
+
+    assert_eq!(SNAPSHOT_VERSION, snapshot.get_version().unwrap());
+    assert!(snapshot.restore().is_ok());
+}
+
+
+#[test]
+fn test_snapshot_with_newer_version_is_rejected() {
+    let gb       = create_gameboy_dmg();
+    let snapshot = Snapshot::create_from(&gb).unwrap();
+    let bumped   = with_version(&snapshot, SNAPSHOT_VERSION + 1);
+
+    match bumped.restore() {
+        Err(SnapshotError::IncompatibleVersion { found, expected }) => {
+            assert_eq!(SNAPSHOT_VERSION + 1, found);
+            assert_eq!(SNAPSHOT_VERSION,     expected);
+        }
+
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_)  => panic!("Snapshot with incompatible version was restored"),
+    }
+}
+
+
+#[test]
+fn test_snapshot_without_header_is_rejected() {
+    let snapshot = Snapshot::from_data(vec![0x00, 0x01, 0x02]);
+
+    assert!(matches!(snapshot.restore(), Err(SnapshotError::InvalidHeader)));
+}
+
+
+#[test]
+fn test_restore_into_keeps_frontend_connections() {
+    let mut gb = create_gameboy_dmg();
+    let receiver = gb.get_peripherals_mut().apu.get_audio_output()
+        .open_channel(AudioOutputSpec { sample_rate: 48_000, format: SampleFormat::Native })
+        .unwrap()
+    ;
+
+    gb.get_breakpoints_mut().add(0x1234);
+
+    let snapshot = Snapshot::create_from(&gb).unwrap();
+    snapshot.restore_into(&mut gb).unwrap();
+
+    // breakpoints are not part of the snapshot, but remain on the instance
+    assert!(gb.get_breakpoints().contains(0x1234));
+
+    // the audio channel opened before restoring the snapshot still receives samples
+    for _ in 0..10 {
+        gb.run_frame();
+    }
+
+    assert!(receiver.try_recv().is_ok());
+}
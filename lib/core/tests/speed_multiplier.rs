@@ -0,0 +1,109 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+use gemi_core::cpu::cpu::CPU_CLOCK_SPEED;
+use gemi_core::gameboy::{Clock, GameBoy};
+use gemi_core::ppu::ppu::CPU_CYCLES_PER_FRAME;
+
+mod common;
+use common::{create_gameboy_dmg, start_idle_loop};
+
+
+/// The maximum number of cycles a single instruction may take.
+const MAX_INSTRUCTION_CYCLES: Clock = 24;
+
+
+/// Creates a new emulator instance running an endless loop in WRAM.
+fn create_gameboy() -> GameBoy {
+    let mut gb = create_gameboy_dmg();
+
+    start_idle_loop(&mut gb);
+
+    gb
+}
+
+
+/// Get the clock speed the audio output is based on.
+fn get_audio_clock_speed(gb: &mut GameBoy) -> Clock {
+    gb.get_peripherals_mut().apu.get_audio_output().get_clock_speed()
+}
+
+
+/// Checks the number of cycles is close to the expected number, with the deviation
+/// of a single instruction, which cannot be interrupted.
+fn assert_cycles(expected: Clock, actual: Clock) {
+    assert!(
+        actual >= expected && actual < expected + MAX_INSTRUCTION_CYCLES,
+        "expected {expected} cycles, got {actual}"
+    );
+}
+
+
+#[test]
+fn test_default_speed() {
+    let mut gb = create_gameboy();
+
+    assert_eq!(1.0, gb.get_speed_multiplier());
+    assert_eq!(CPU_CLOCK_SPEED, get_audio_clock_speed(&mut gb));
+}
+
+
+#[test]
+fn test_half_speed() {
+    let mut gb = create_gameboy();
+    gb.set_speed_multiplier(0.5);
+
+    let cycles = gb.run_frame().cycles;
+    assert_cycles(CPU_CYCLES_PER_FRAME / 2, cycles);
+
+    // the audio output generates samples based on the time passed for the frontend
+    assert_eq!(CPU_CLOCK_SPEED / 2, get_audio_clock_speed(&mut gb));
+}
+
+
+#[test]
+fn test_double_speed() {
+    let mut gb = create_gameboy();
+    gb.set_speed_multiplier(2.0);
+
+    let cycles = gb.run_frame().cycles;
+    assert_cycles(CPU_CYCLES_PER_FRAME * 2, cycles);
+
+    assert_eq!(CPU_CLOCK_SPEED * 2, get_audio_clock_speed(&mut gb));
+}
+
+
+#[test]
+fn test_excess_cycles_carried_over() {
+    let mut gb = create_gameboy();
+    gb.set_speed_multiplier(0.25);
+
+    let total_cycles: Clock = (0..100).map(|_| gb.run_frame().cycles).sum();
+
+    // any cycles processed in excess are taken from the next frame,
+    // so there is no drift over multiple frames
+    assert_cycles(CPU_CYCLES_PER_FRAME * 25, total_cycles);
+}
+
+
+#[test]
+fn test_invalid_multiplier_ignored() {
+    let mut gb = create_gameboy();
+    gb.set_speed_multiplier(0.0);
+    gb.set_speed_multiplier(-1.0);
+
+    assert_eq!(1.0, gb.get_speed_multiplier());
+}
@@ -0,0 +1,109 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::cpu::interrupts::Interrupt;
+use gemi_core::mmu::locations::*;
+use gemi_core::mmu::memory_bus::MemoryBusConnection;
+use gemi_core::timer::Timer;
+
+
+/// TAC value to enable the timer with TIMA being incremented each 16 cycles.
+const TAC_ENABLED_16_CYCLES: u8 = 0b_0000_0101;
+
+/// The value of TMA to be loaded into TIMA after an overflow.
+const TMA_VALUE: u8 = 0x42;
+
+
+/// Creates a timer with the internal counter starting at zero,
+/// which will increment TIMA each 16 cycles.
+fn create_timer(tima: u8) -> Timer {
+    let mut timer = Timer::new();
+    timer.on_write(MEMORY_LOCATION_REGISTER_TMA,  TMA_VALUE);
+    timer.on_write(MEMORY_LOCATION_REGISTER_TIMA, tima);
+    timer.on_write(MEMORY_LOCATION_REGISTER_TAC,  TAC_ENABLED_16_CYCLES);
+
+    timer
+}
+
+
+/// Runs the timer in steps of single machine cycles.
+fn run_timer(timer: &mut Timer, cycles: u64) {
+    for _ in 0..(cycles / 4) {
+        timer.update(4);
+    }
+}
+
+
+fn read_tima(timer: &Timer) -> u8 {
+    timer.on_read(MEMORY_LOCATION_REGISTER_TIMA)
+}
+
+
+/// Checks whether the timer has requested an interrupt since the last call.
+fn has_timer_interrupt(timer: &mut Timer) -> bool {
+    timer.take_signals().interrupts.contains(Interrupt::Timer)
+}
+
+
+#[test]
+fn test_tima_reads_0xff_until_next_increment() {
+    let mut timer = create_timer(0xfe);
+
+    run_timer(&mut timer, 16);
+    assert_eq!(0xff, read_tima(&timer));
+
+    // TIMA keeps the value of 0xff during each cycle until the next increment
+    for _ in 0..3 {
+        run_timer(&mut timer, 4);
+        assert_eq!(0xff, read_tima(&timer));
+    }
+
+    assert!(!has_timer_interrupt(&mut timer));
+}
+
+
+#[test]
+fn test_tima_overflow_sequence() {
+    let mut timer = create_timer(0xff);
+    _ = has_timer_interrupt(&mut timer);
+
+    // on the overflow, TIMA reads as zero for one machine cycle without an interrupt yet
+    run_timer(&mut timer, 16);
+    assert_eq!(0x00, read_tima(&timer));
+    assert!(!has_timer_interrupt(&mut timer));
+
+    // on the next machine cycle TIMA gets reloaded from TMA and the interrupt is requested
+    run_timer(&mut timer, 4);
+    assert_eq!(TMA_VALUE, read_tima(&timer));
+    assert!(has_timer_interrupt(&mut timer));
+}
+
+
+#[test]
+fn test_tima_write_during_overflow_cancels_reload() {
+    let mut timer = create_timer(0xff);
+    _ = has_timer_interrupt(&mut timer);
+
+    run_timer(&mut timer, 16);
+    assert_eq!(0x00, read_tima(&timer));
+
+    timer.on_write(MEMORY_LOCATION_REGISTER_TIMA, 0x10);
+
+    run_timer(&mut timer, 4);
+    assert_eq!(0x10, read_tima(&timer));
+    assert!(!has_timer_interrupt(&mut timer));
+}
@@ -0,0 +1,227 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{Display, Formatter};
+
+
+/// A simple configuration file in an INI like format.
+/// The file consists of sections, each containing a list of entries,
+/// which map a name to a comma separated list of values:
+///
+/// ```ini
+/// # comment
+/// [section]
+/// name = value1, value2
+/// ```
+#[derive(Default)]
+pub struct ConfigFile {
+    /// The list of sections stored in this file.
+    sections: Vec<ConfigSection>,
+}
+
+
+/// A single section within a [ConfigFile].
+pub struct ConfigSection {
+    /// The name of this section.
+    name: String,
+
+    /// The list of entries stored in this section.
+    entries: Vec<ConfigEntry>,
+}
+
+
+/// A single entry within a [ConfigSection], mapping a name to a list of values.
+pub struct ConfigEntry {
+    /// The name of this entry.
+    pub name: String,
+
+    /// The list of values assigned to this entry.
+    pub values: Vec<String>,
+
+    /// The line in the source file where this entry was defined, if any.
+    pub line: Option<usize>,
+}
+
+
+/// An error which occurred while parsing a [ConfigFile].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigParseError {
+    /// The line number where the error occurred, starting at 1.
+    pub line: usize,
+
+    /// A message describing the error.
+    pub message: String,
+}
+
+
+impl ConfigFile {
+    /// Creates a new empty [ConfigFile].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+
+    /// Parses the content of a configuration file.
+    pub fn parse(text: &str) -> Result<Self, ConfigParseError> {
+        let mut file = Self::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line        = line.trim();
+
+            // skip empty lines and comments
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            // begin a new section
+            if let Some(section) = line.strip_prefix('[') {
+                let Some(name) = section.strip_suffix(']') else {
+                    return Err(ConfigParseError::new(line_number, "Missing ']' at the end of the section name"));
+                };
+
+                file.get_or_create_section(name.trim());
+
+                continue;
+            }
+
+            let Some((name, values)) = line.split_once('=') else {
+                return Err(ConfigParseError::new(line_number, "Expected an entry like 'name = value'"));
+            };
+
+            let Some(section) = file.sections.last_mut() else {
+                return Err(ConfigParseError::new(line_number, "Entry defined outside of any section"));
+            };
+
+            section.entries.push(ConfigEntry {
+                name:   name.trim().to_string(),
+                values: values
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                line:   Some(line_number),
+            });
+        }
+
+        Ok(file)
+    }
+
+
+    /// Get a section by its name, if existing.
+    pub fn get_section(&self, name: &str) -> Option<&ConfigSection> {
+        self.sections
+            .iter()
+            .find(|section| section.name == name)
+    }
+
+
+    /// Get a section by its name or creates a new one, if not existing yet.
+    pub fn get_or_create_section(&mut self, name: &str) -> &mut ConfigSection {
+        let index = match self.sections.iter().position(|section| section.name == name) {
+            Some(index) => index,
+
+            None => {
+                self.sections.push(ConfigSection::new(name));
+                self.sections.len() - 1
+            }
+        };
+
+        &mut self.sections[index]
+    }
+
+
+    /// Get the list of all sections of this file.
+    pub fn get_sections(&self) -> &Vec<ConfigSection> {
+        &self.sections
+    }
+}
+
+
+impl Display for ConfigFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, section) in self.sections.iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "[{}]", section.name)?;
+
+            for entry in &section.entries {
+                writeln!(f, "{} = {}", entry.name, entry.values.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ConfigSection {
+    /// Creates a new empty section.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name:    name.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+
+    /// Get the name of this section.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+
+    /// Get the list of all entries of this section.
+    pub fn get_entries(&self) -> &Vec<ConfigEntry> {
+        &self.entries
+    }
+
+
+    /// Adds a new entry to this section.
+    pub fn add_entry(&mut self, name: &str, values: Vec<String>) {
+        self.entries.push(ConfigEntry {
+            name: name.to_string(),
+            values,
+            line: None,
+        });
+    }
+}
+
+
+impl ConfigParseError {
+    /// Creates a new error with a message for the given line.
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+
+impl Display for ConfigParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+
+impl std::error::Error for ConfigParseError {
+}
@@ -15,8 +15,26 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::fmt::{Display, Formatter};
 use gemi_core::gameboy::GameBoy;
 use gemi_core::input::{Input, InputButton};
+use crate::config_file::{ConfigEntry, ConfigFile, ConfigParseError};
+
+
+/// The name of the config file section containing the button bindings.
+pub const SECTION_BUTTONS: &str = "buttons";
+
+/// The names of each [InputButton] as used in config files.
+const BUTTON_NAMES: [(InputButton, &str); 8] = [
+    (InputButton::DPadRight,    "Right"),
+    (InputButton::DPadLeft,     "Left"),
+    (InputButton::DPadUp,       "Up"),
+    (InputButton::DPadDown,     "Down"),
+    (InputButton::A,            "A"),
+    (InputButton::B,            "B"),
+    (InputButton::Select,       "Select"),
+    (InputButton::Start,        "Start"),
+];
 
 
 /// An utility to map key bindings of a frontend system to emulator input buttons.
@@ -30,6 +48,36 @@ pub struct KeyBindings<KeyCode>
 }
 
 
+/// A lookup table of all native [KeyCode] values known by a frontend and their names.
+/// This is used to read and write key bindings from and into config files.
+pub struct KeyNames<KeyCode> {
+    /// The list of all known keys with their names.
+    keys: Vec<(String, KeyCode)>,
+}
+
+
+/// An error which occurred while reading key bindings from a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyBindingsError {
+    /// The config file could not be parsed.
+    Parse(ConfigParseError),
+
+    /// An entry refers to a button or function which does not exist.
+    UnknownName {
+        line:           Option<usize>,
+        name:           String,
+        valid_names:    Vec<String>,
+    },
+
+    /// An entry refers to a key which does not exist.
+    UnknownKey {
+        line:           Option<usize>,
+        key:            String,
+        valid_names:    Vec<String>,
+    },
+}
+
+
 /// A single key binding entry mapping a set of native [KeyCode] values to a single [InputButton].
 struct KeyBindEntry<KeyCode>
     where KeyCode: Eq
@@ -92,6 +140,56 @@ impl<KeyCode> KeyBindings<KeyCode>
     }
 
 
+    /// Reads the key bindings from the buttons section of a config file.
+    /// Buttons not listed in the config file will not have any keys assigned.
+    pub fn from_config(config: &ConfigFile, key_names: &KeyNames<KeyCode>) -> Result<Self, KeyBindingsError>
+        where KeyCode: Clone
+    {
+        let mut key_bindings = Self::default();
+
+        if let Some(section) = config.get_section(SECTION_BUTTONS) {
+            for entry in section.get_entries() {
+                let Some(button) = find_button_by_name(&entry.name) else {
+                    return Err(KeyBindingsError::unknown_name(
+                        entry,
+                        BUTTON_NAMES.iter().map(|(_, name)| *name)
+                    ));
+                };
+
+                for key in key_names.parse_entry(entry)? {
+                    key_bindings.add_keybinding(button, key);
+                }
+            }
+        }
+
+        Ok(key_bindings)
+    }
+
+
+    /// Writes the current key bindings into the buttons section of a config file.
+    /// Keys without a known name will be skipped.
+    pub fn write_config(&self, config: &mut ConfigFile, key_names: &KeyNames<KeyCode>) {
+        let section = config.get_or_create_section(SECTION_BUTTONS);
+
+        for entry in self.bindings.iter() {
+            section.add_entry(
+                get_button_name(entry.button),
+                key_names.get_names_of(entry.keys.iter().map(|key_entry| &key_entry.key))
+            );
+        }
+    }
+
+
+    /// Get the list of keys assigned to a given button.
+    pub fn get_keys_of(&self, button: InputButton) -> Vec<&KeyCode> {
+        self.bindings
+            .iter()
+            .filter(|entry| entry.button == button)
+            .flat_map(|entry| entry.keys.iter().map(|key_entry| &key_entry.key))
+            .collect()
+    }
+
+
     /// Assign a new key binding to a given button.
     pub fn add_keybinding(&mut self, button: InputButton, key: KeyCode) {
         if let Some(entry) = self.find_entry_mut(button) {
@@ -186,6 +284,143 @@ impl<KeyCode> KeyBindings<KeyCode>
 }
 
 
+impl<KeyCode> KeyNames<KeyCode>
+    where KeyCode: Eq
+{
+    /// Creates a new lookup table from a list of keys and their names.
+    pub fn new(keys: Vec<(String, KeyCode)>) -> Self {
+        Self {
+            keys,
+        }
+    }
+
+
+    /// Find a key by its name. The name is not case sensitive.
+    pub fn find_key(&self, name: &str) -> Option<&KeyCode> {
+        self.keys
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|(_, key)| key)
+    }
+
+
+    /// Get the name of any given key.
+    pub fn get_name(&self, key: &KeyCode) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(_, k)| k == key)
+            .map(|(name, _)| name.as_str())
+    }
+
+
+    /// Get the names of a list of keys, skipping each key without a known name.
+    pub fn get_names_of<'a>(&self, keys: impl Iterator<Item=&'a KeyCode>) -> Vec<String>
+        where KeyCode: 'a
+    {
+        keys
+            .filter_map(|key| self.get_name(key))
+            .map(str::to_string)
+            .collect()
+    }
+
+
+    /// Get the names of all known keys.
+    pub fn get_all_names(&self) -> impl Iterator<Item=&str> {
+        self.keys.iter().map(|(name, _)| name.as_str())
+    }
+
+
+    /// Parses the values of a config file entry into a list of keys.
+    pub fn parse_entry(&self, entry: &ConfigEntry) -> Result<Vec<KeyCode>, KeyBindingsError>
+        where KeyCode: Clone
+    {
+        entry.values
+            .iter()
+            .map(|value| {
+                self.find_key(value)
+                    .cloned()
+                    .ok_or_else(|| KeyBindingsError::UnknownKey {
+                        line:        entry.line,
+                        key:         value.clone(),
+                        valid_names: self.get_all_names().map(str::to_string).collect(),
+                    })
+            })
+            .collect()
+    }
+}
+
+
+impl KeyBindingsError {
+    /// Creates an error for an entry with a name, which is not in the list of valid names.
+    pub fn unknown_name<'a>(entry: &ConfigEntry, valid_names: impl Iterator<Item=&'a str>) -> Self {
+        Self::UnknownName {
+            line:        entry.line,
+            name:        entry.name.clone(),
+            valid_names: valid_names.map(str::to_string).collect(),
+        }
+    }
+}
+
+
+impl From<ConfigParseError> for KeyBindingsError {
+    fn from(value: ConfigParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+
+impl Display for KeyBindingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // print the line number, if known
+        let write_line = |f: &mut Formatter<'_>, line: &Option<usize>| -> std::fmt::Result {
+            match line {
+                Some(line) => write!(f, "line {line}: "),
+                None       => Ok(()),
+            }
+        };
+
+        match self {
+            KeyBindingsError::Parse(error) => {
+                write!(f, "{error}")
+            }
+
+            KeyBindingsError::UnknownName { line, name, valid_names } => {
+                write_line(f, line)?;
+                write!(f, "Unknown name '{name}', valid names are: {}", valid_names.join(", "))
+            }
+
+            KeyBindingsError::UnknownKey { line, key, valid_names } => {
+                write_line(f, line)?;
+                write!(f, "Unknown key '{key}', valid key names are: {}", valid_names.join(", "))
+            }
+        }
+    }
+}
+
+
+impl std::error::Error for KeyBindingsError {
+}
+
+
+/// Get the name of an [InputButton] as used in config files.
+fn get_button_name(button: InputButton) -> &'static str {
+    BUTTON_NAMES
+        .iter()
+        .find(|(b, _)| *b == button)
+        .map(|(_, name)| *name)
+        .unwrap_or_default()
+}
+
+
+/// Find an [InputButton] by its name used in config files. The name is not case sensitive.
+fn find_button_by_name(name: &str) -> Option<InputButton> {
+    BUTTON_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(button, _)| *button)
+}
+
+
 impl<KeyCode> KeyBindEntry<KeyCode>
     where KeyCode: Eq
 {
@@ -0,0 +1,132 @@
+/*
+ * Copyright (C) 2022-2024 by Christian Fischer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use gemi_core::input::InputButton;
+use gemi_utils::config_file::ConfigFile;
+use gemi_utils::keybindings::{KeyBindings, KeyBindingsError, KeyNames};
+
+
+/// A config file with key bindings for each button.
+const CONFIG: &str = "
+# key bindings
+[buttons]
+Right  = D, Right
+Left   = A, Left
+Up     = W
+Down   = S
+A      = K
+B      = J
+Select = Space
+Start  = Return
+";
+
+
+/// Creates a lookup table for a small set of keys, using their names as key codes.
+fn create_key_names() -> KeyNames<String> {
+    KeyNames::new(
+        ["A", "D", "J", "K", "S", "W", "Left", "Right", "Space", "Return"]
+            .iter()
+            .map(|name| (name.to_string(), name.to_string()))
+            .collect()
+    )
+}
+
+
+/// Reads key bindings from the given text.
+fn read_key_bindings(text: &str) -> Result<KeyBindings<String>, KeyBindingsError> {
+    let config = ConfigFile::parse(text)?;
+    KeyBindings::from_config(&config, &create_key_names())
+}
+
+
+#[test]
+fn test_parse_key_bindings() {
+    let key_bindings = read_key_bindings(CONFIG).unwrap();
+
+    assert_eq!(key_bindings.get_keys_of(InputButton::DPadRight), vec!["D", "Right"]);
+    assert_eq!(key_bindings.get_keys_of(InputButton::DPadLeft),  vec!["A", "Left"]);
+    assert_eq!(key_bindings.get_keys_of(InputButton::A),         vec!["K"]);
+    assert_eq!(key_bindings.get_keys_of(InputButton::Start),     vec!["Return"]);
+}
+
+
+#[test]
+fn test_key_names_are_not_case_sensitive() {
+    let key_bindings = read_key_bindings("[buttons]\nstart = return, SPACE").unwrap();
+
+    assert_eq!(key_bindings.get_keys_of(InputButton::Start), vec!["Return", "Space"]);
+    assert!(key_bindings.get_keys_of(InputButton::Select).is_empty());
+}
+
+
+#[test]
+fn test_write_and_read_key_bindings() {
+    let key_names    = create_key_names();
+    let key_bindings = read_key_bindings(CONFIG).unwrap();
+
+    let mut config = ConfigFile::new();
+    key_bindings.write_config(&mut config, &key_names);
+
+    let text = config.to_string();
+    assert!(text.contains("[buttons]\n"));
+    assert!(text.contains("Right = D, Right\n"));
+
+    let restored = read_key_bindings(&text).unwrap();
+
+    for button in InputButton::ALL {
+        assert_eq!(restored.get_keys_of(button), key_bindings.get_keys_of(button));
+    }
+}
+
+
+#[test]
+fn test_unknown_key() {
+    let error = read_key_bindings("[buttons]\n\nA = K, Ctrl").err().unwrap();
+
+    match &error {
+        KeyBindingsError::UnknownKey { line, key, valid_names } => {
+            assert_eq!(*line, Some(3));
+            assert_eq!(key, "Ctrl");
+            assert!(valid_names.contains(&String::from("Space")));
+        }
+
+        _ => panic!("Unexpected error: {error}"),
+    }
+
+    // the error message lists the valid key names
+    assert!(error.to_string().starts_with("line 3: Unknown key 'Ctrl', valid key names are: A, D, J,"));
+}
+
+
+#[test]
+fn test_unknown_button() {
+    let error = read_key_bindings("[buttons]\nTurbo = K").err().unwrap();
+    assert_eq!(error.to_string(), "line 2: Unknown name 'Turbo', valid names are: Right, Left, Up, Down, A, B, Select, Start");
+}
+
+
+#[test]
+fn test_syntax_errors() {
+    let error = read_key_bindings("A = K").err().unwrap();
+    assert!(matches!(error, KeyBindingsError::Parse(e) if e.line == 1));
+
+    let error = read_key_bindings("[buttons]\n[functions\n").err().unwrap();
+    assert!(matches!(error, KeyBindingsError::Parse(e) if e.line == 2));
+
+    let error = read_key_bindings("[buttons]\nA K\n").err().unwrap();
+    assert!(matches!(error, KeyBindingsError::Parse(e) if e.line == 2));
+}
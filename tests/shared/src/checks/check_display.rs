@@ -76,7 +76,7 @@ pub fn apply_color_mod(lcd: &LcdBuffer, color_mod: &LcdColorMod) -> LcdBuffer {
                 }
             };
 
-            new_buffer.set_pixel(x, y, modified_color);
+            new_buffer.set_pixel_rgba(x, y, &modified_color);
         }
     }
 
@@ -114,6 +114,9 @@ pub fn create_device_with_config(workspace: &Workspace, device_type: &DeviceType
     // set the device type to be emulated
     builder.set_device_type(*device_type);
 
+    // test ROMs may not contain a valid cartridge header
+    builder.set_allow_invalid_header(true);
+
     // load the cartridge file
     let cartridge_path = PathBuf::from(workspace.get_path_to_str(&setup.cartridge_path));
     let cartridge = Cartridge::load_file(&cartridge_path)
@@ -133,7 +136,7 @@ pub fn create_device_with_config(workspace: &Workspace, device_type: &DeviceType
 
     // create the device emulator
     let mut gb = builder.finish()
-        .map_err(|e| TestCaseError::SetUpError(e))
+        .map_err(|e| TestCaseError::SetUpError(e.to_string()))
         ?;
 
     // initialize